@@ -0,0 +1,80 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Benchmarks for passes which iterate over the whole widget arena.
+//!
+//! Run with `cargo bench -p masonry --bench widget_arena`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use masonry::dpi::PhysicalSize;
+use masonry::widget::{Flex, SizedBox};
+use masonry::{RenderRoot, RenderRootOptions, WindowEvent, WindowSizePolicy};
+
+const ROWS: usize = 100;
+const COLUMNS: usize = 100;
+const ITERATIONS: u32 = 50;
+
+fn make_render_root() -> RenderRoot {
+    let mut column = Flex::column();
+    for _ in 0..ROWS {
+        let mut row = Flex::row();
+        for _ in 0..COLUMNS {
+            row = row.with_child(SizedBox::empty().width(4.0).height(4.0));
+        }
+        column = column.with_child(row);
+    }
+
+    let mut render_root = RenderRoot::new(
+        column,
+        RenderRootOptions {
+            use_system_fonts: false,
+            size_policy: WindowSizePolicy::User,
+            scale_factor: 1.0,
+            test_font: None,
+            formatter: None,
+            pixel_snapping: false,
+        },
+    );
+    render_root.handle_window_event(WindowEvent::Resize(PhysicalSize::new(800, 600)));
+    let _ = render_root.redraw();
+    render_root
+}
+
+fn bench(name: &str, mut f: impl FnMut(&mut RenderRoot)) {
+    let mut render_root = make_render_root();
+    let mut total = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        f(&mut render_root);
+        total += start.elapsed();
+    }
+    println!(
+        "{name:<24} {:>10.3?} / iter ({} widgets)",
+        total / ITERATIONS,
+        ROWS * COLUMNS + ROWS + 1
+    );
+}
+
+fn main() {
+    // Sets invalidation flags on every widget, then runs the paint and accessibility
+    // passes over the whole tree.
+    bench("redraw_all", |render_root| {
+        render_root.handle_window_event(WindowEvent::Rescale(1.0));
+        black_box(render_root.redraw());
+    });
+
+    // Runs the layout and compose passes over the whole tree.
+    let mut wide = false;
+    bench("relayout_all", |render_root| {
+        wide = !wide;
+        let width = if wide { 900 } else { 800 };
+        render_root.handle_window_event(WindowEvent::Resize(PhysicalSize::new(width, 600)));
+    });
+
+    // Only walks the arena to check flags, without calling any widget method.
+    bench("redraw_clean", |render_root| {
+        black_box(render_root.redraw());
+    });
+}
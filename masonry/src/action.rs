@@ -3,7 +3,12 @@
 
 use std::any::Any;
 
+use winit::keyboard::{Key, ModifiersState};
+
 use crate::event::PointerButton;
+use crate::gesture::Gesture;
+use crate::text::ArcStr;
+use crate::WidgetId;
 
 // TODO - Refactor - See issue https://github.com/linebender/xilem/issues/335
 
@@ -17,6 +22,64 @@ pub enum Action {
     TextChanged(String),
     TextEntered(String),
     CheckboxChecked(bool),
+    /// A tri-state [`Checkbox`](crate::widget::Checkbox) was toggled to indeterminate.
+    CheckboxIndeterminate,
+    /// A [`Link`](crate::widget::Link) was activated, with the link's target.
+    LinkActivated(ArcStr),
+    /// The child of an [`Interactive`](crate::widget::Interactive) widget was clicked.
+    ///
+    /// Holds the id of the child.
+    Clicked(PointerButton, WidgetId),
+    /// The pointer entered (`true`) or left (`false`) the child of an
+    /// [`Interactive`](crate::widget::Interactive) widget.
+    ///
+    /// Holds the id of the child.
+    HoverChanged(bool, WidgetId),
+    /// A key was pressed while the child of an [`Interactive`](crate::widget::Interactive)
+    /// widget had focus.
+    ///
+    /// Holds the id of the child.
+    KeyPressed(Key, ModifiersState, WidgetId),
+    /// Text was pasted while the child of an [`Interactive`](crate::widget::Interactive)
+    /// widget had focus.
+    ///
+    /// Holds the id of the child.
+    Pasted(String, WidgetId),
+    /// A gesture was recognized on the child of a
+    /// [`GestureDetector`](crate::widget::GestureDetector) widget.
+    ///
+    /// Holds the id of the child.
+    Gesture(Gesture, WidgetId),
+    /// The panel of a [`Drawer`](crate::widget::Drawer) was dismissed by the user.
+    DrawerDismissed,
+    /// An item of a [`MenuButton`](crate::widget::MenuButton)'s menu, or of a menu shown with
+    /// [`EventCtx::show_context_menu`](crate::EventCtx::show_context_menu), was chosen,
+    /// with its index.
+    MenuItemSelected(usize),
+    /// The rating of a [`Rating`](crate::widget::Rating) was changed by the user.
+    RatingChanged(f64),
+    /// A segment of a [`SegmentedControl`](crate::widget::SegmentedControl) was selected,
+    /// with its index.
+    SegmentSelected(usize),
+    /// A tag was added to or removed from a [`TagInput`](crate::widget::TagInput),
+    /// with all its tags.
+    TagsChanged(Vec<String>),
+    /// A panic was caught in the child of an [`ErrorBoundary`](crate::widget::ErrorBoundary),
+    /// with the panic message.
+    ChildPanicked(String),
+    /// The retry button of an [`ErrorBoundary`](crate::widget::ErrorBoundary) was pressed.
+    RetryRequested,
+    /// A command was run from a [`CommandPalette`](crate::widget::CommandPalette),
+    /// with its id.
+    CommandInvoked(ArcStr),
+    /// The items selected with a [`RubberBand`](crate::widget::RubberBand) changed,
+    /// with the indices of all selected items.
+    ItemsSelected(Vec<usize>),
+    /// A registered [global hotkey](crate::global_hotkeys) was pressed.
+    GlobalHotkey(crate::global_hotkeys::HotkeyId),
+    /// A frame showing the placeholder of a [`Prebuilt`](crate::widget::Prebuilt) was
+    /// presented, so its content can be built.
+    PlaceholderPresented,
     // FIXME - This is a huge hack
     Other(Box<dyn Any + Send>),
 }
@@ -28,6 +91,26 @@ impl PartialEq for Action {
             (Self::TextChanged(l0), Self::TextChanged(r0)) => l0 == r0,
             (Self::TextEntered(l0), Self::TextEntered(r0)) => l0 == r0,
             (Self::CheckboxChecked(l0), Self::CheckboxChecked(r0)) => l0 == r0,
+            (Self::CheckboxIndeterminate, Self::CheckboxIndeterminate) => true,
+            (Self::LinkActivated(l0), Self::LinkActivated(r0)) => l0 == r0,
+            (Self::Clicked(l0, l1), Self::Clicked(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::HoverChanged(l0, l1), Self::HoverChanged(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::KeyPressed(l0, l1, l2), Self::KeyPressed(r0, r1, r2)) => {
+                l0 == r0 && l1 == r1 && l2 == r2
+            }
+            (Self::Pasted(l0, l1), Self::Pasted(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::Gesture(l0, l1), Self::Gesture(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::DrawerDismissed, Self::DrawerDismissed) => true,
+            (Self::MenuItemSelected(l0), Self::MenuItemSelected(r0)) => l0 == r0,
+            (Self::RatingChanged(l0), Self::RatingChanged(r0)) => l0 == r0,
+            (Self::SegmentSelected(l0), Self::SegmentSelected(r0)) => l0 == r0,
+            (Self::TagsChanged(l0), Self::TagsChanged(r0)) => l0 == r0,
+            (Self::ChildPanicked(l0), Self::ChildPanicked(r0)) => l0 == r0,
+            (Self::RetryRequested, Self::RetryRequested) => true,
+            (Self::CommandInvoked(l0), Self::CommandInvoked(r0)) => l0 == r0,
+            (Self::ItemsSelected(l0), Self::ItemsSelected(r0)) => l0 == r0,
+            (Self::GlobalHotkey(l0), Self::GlobalHotkey(r0)) => l0 == r0,
+            (Self::PlaceholderPresented, Self::PlaceholderPresented) => true,
             // FIXME
             // (Self::Other(val_l), Self::Other(val_r)) => false,
             _ => false,
@@ -35,6 +118,37 @@ impl PartialEq for Action {
     }
 }
 
+impl Action {
+    /// The name of the variant of this action, without the data it holds.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::ButtonPressed(_) => "ButtonPressed",
+            Self::TextChanged(_) => "TextChanged",
+            Self::TextEntered(_) => "TextEntered",
+            Self::CheckboxChecked(_) => "CheckboxChecked",
+            Self::CheckboxIndeterminate => "CheckboxIndeterminate",
+            Self::LinkActivated(_) => "LinkActivated",
+            Self::Clicked(..) => "Clicked",
+            Self::HoverChanged(..) => "HoverChanged",
+            Self::KeyPressed(..) => "KeyPressed",
+            Self::Pasted(..) => "Pasted",
+            Self::Gesture(..) => "Gesture",
+            Self::DrawerDismissed => "DrawerDismissed",
+            Self::MenuItemSelected(_) => "MenuItemSelected",
+            Self::RatingChanged(_) => "RatingChanged",
+            Self::SegmentSelected(_) => "SegmentSelected",
+            Self::TagsChanged(_) => "TagsChanged",
+            Self::ChildPanicked(_) => "ChildPanicked",
+            Self::RetryRequested => "RetryRequested",
+            Self::CommandInvoked(_) => "CommandInvoked",
+            Self::ItemsSelected(_) => "ItemsSelected",
+            Self::GlobalHotkey(_) => "GlobalHotkey",
+            Self::PlaceholderPresented => "PlaceholderPresented",
+            Self::Other(_) => "Other",
+        }
+    }
+}
+
 impl std::fmt::Debug for Action {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -42,6 +156,37 @@ impl std::fmt::Debug for Action {
             Self::TextChanged(text) => f.debug_tuple("TextChanged").field(text).finish(),
             Self::TextEntered(text) => f.debug_tuple("TextEntered").field(text).finish(),
             Self::CheckboxChecked(b) => f.debug_tuple("CheckboxChecked").field(b).finish(),
+            Self::CheckboxIndeterminate => write!(f, "CheckboxIndeterminate"),
+            Self::LinkActivated(target) => f.debug_tuple("LinkActivated").field(target).finish(),
+            Self::Clicked(button, id) => f.debug_tuple("Clicked").field(button).field(id).finish(),
+            Self::HoverChanged(hovered, id) => f
+                .debug_tuple("HoverChanged")
+                .field(hovered)
+                .field(id)
+                .finish(),
+            Self::KeyPressed(key, mods, id) => f
+                .debug_tuple("KeyPressed")
+                .field(key)
+                .field(mods)
+                .field(id)
+                .finish(),
+            Self::Pasted(text, id) => f.debug_tuple("Pasted").field(text).field(id).finish(),
+            Self::Gesture(gesture, id) => {
+                f.debug_tuple("Gesture").field(gesture).field(id).finish()
+            }
+            Self::DrawerDismissed => write!(f, "DrawerDismissed"),
+            Self::MenuItemSelected(index) => {
+                f.debug_tuple("MenuItemSelected").field(index).finish()
+            }
+            Self::RatingChanged(value) => f.debug_tuple("RatingChanged").field(value).finish(),
+            Self::SegmentSelected(index) => f.debug_tuple("SegmentSelected").field(index).finish(),
+            Self::TagsChanged(tags) => f.debug_tuple("TagsChanged").field(tags).finish(),
+            Self::ChildPanicked(message) => f.debug_tuple("ChildPanicked").field(message).finish(),
+            Self::RetryRequested => write!(f, "RetryRequested"),
+            Self::CommandInvoked(id) => f.debug_tuple("CommandInvoked").field(id).finish(),
+            Self::ItemsSelected(indices) => f.debug_tuple("ItemsSelected").field(indices).finish(),
+            Self::GlobalHotkey(id) => f.debug_tuple("GlobalHotkey").field(id).finish(),
+            Self::PlaceholderPresented => write!(f, "PlaceholderPresented"),
             Self::Other(_) => write!(f, "Other(...)"),
         }
     }
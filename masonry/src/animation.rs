@@ -0,0 +1,239 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Easing curves and values which move smoothly towards their target.
+
+use std::time::Duration;
+
+use crate::{Size, Vec2};
+
+/// The curve a transition follows over its duration.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slowly, and ends at full speed.
+    EaseIn,
+    /// Starts at full speed, and ends slowly.
+    #[default]
+    EaseOut,
+    /// Starts and ends slowly.
+    EaseInOut,
+}
+
+/// How a value moves towards its target when it changes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+/// A value which can be interpolated by a [`Transition`].
+pub trait Interpolate: Copy + PartialEq {
+    /// The value at `t` of the way from `self` to `other`, where `t` is between 0 and 1.
+    fn interpolate(self, other: Self, t: f64) -> Self;
+}
+
+/// A value which moves towards its target with a [`Transition`], one animation frame at a time.
+///
+/// Widgets set the target with [`set_target`](Self::set_target), request an animation frame
+/// when it returns `true`, and call [`advance`](Self::advance) in
+/// [`on_anim_frame`](crate::Widget::on_anim_frame) until [`is_animating`](Self::is_animating)
+/// returns `false`. Without a transition, the value jumps to its target.
+#[derive(Clone, Debug)]
+pub struct AnimatedValue<T> {
+    from: T,
+    to: T,
+    value: T,
+    /// The time since the transition started, in nanoseconds.
+    elapsed: u64,
+    transition: Option<Transition>,
+}
+
+// --- MARK: EASING ---
+impl Easing {
+    /// The progress of the transition when `t` of its duration has elapsed, where `t`
+    /// is between 0 and 1.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1. - (1. - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - (2. - 2. * t).powi(3) / 2.
+                }
+            }
+        }
+    }
+}
+
+impl Transition {
+    pub const fn new(duration: Duration, easing: Easing) -> Self {
+        Self { duration, easing }
+    }
+}
+
+impl Interpolate for f64 {
+    fn interpolate(self, other: Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vec2 {
+    fn interpolate(self, other: Self, t: f64) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Size {
+    fn interpolate(self, other: Self, t: f64) -> Self {
+        Size::new(
+            self.width.interpolate(other.width, t),
+            self.height.interpolate(other.height, t),
+        )
+    }
+}
+
+// --- MARK: ANIMATED VALUE ---
+impl<T: Interpolate> AnimatedValue<T> {
+    /// Create a value which is at rest at `value`, without a transition.
+    pub fn new(value: T) -> Self {
+        Self {
+            from: value,
+            to: value,
+            value,
+            elapsed: 0,
+            transition: None,
+        }
+    }
+
+    /// The current value.
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// The value the current transition ends at.
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    pub fn transition(&self) -> Option<Transition> {
+        self.transition
+    }
+
+    /// Whether the value is still moving towards its target.
+    pub fn is_animating(&self) -> bool {
+        self.value != self.to
+    }
+
+    /// Set the transition used by the next changes of the target.
+    ///
+    /// The current transition, if any, keeps going with its previous duration and easing.
+    pub fn set_transition(&mut self, transition: Option<Transition>) {
+        self.transition = transition;
+    }
+
+    /// Start moving towards `target` from the current value.
+    ///
+    /// Returns `true` if the value started animating, in which case the caller must
+    /// request an animation frame. Without a transition, the value jumps to `target`.
+    pub fn set_target(&mut self, target: T) -> bool {
+        if target == self.to {
+            return false;
+        }
+        self.from = self.value;
+        self.to = target;
+        self.elapsed = 0;
+        match self.transition {
+            Some(transition) if !transition.duration.is_zero() => true,
+            _ => {
+                self.value = target;
+                false
+            }
+        }
+    }
+
+    /// Jump to `value`, stopping the current transition.
+    pub fn jump_to(&mut self, value: T) {
+        self.from = value;
+        self.to = value;
+        self.value = value;
+    }
+
+    /// Move towards the target, `interval` nanoseconds after the last frame.
+    ///
+    /// Returns `true` if the value changed.
+    pub fn advance(&mut self, interval: u64) -> bool {
+        if !self.is_animating() {
+            return false;
+        }
+        self.elapsed = self.elapsed.saturating_add(interval);
+        let Transition { duration, easing } = self.transition.unwrap_or(Transition {
+            duration: Duration::ZERO,
+            easing: Easing::Linear,
+        });
+        let t = self.elapsed as f64 / duration.as_nanos() as f64;
+        self.value = if t.is_nan() || t >= 1. {
+            self.to
+        } else {
+            self.from.interpolate(self.to, easing.apply(t))
+        };
+        true
+    }
+}
+
+impl<T: Interpolate + Default> Default for AnimatedValue<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easing_curves() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.), 0.);
+            assert_eq!(easing.apply(1.), 1.);
+            assert_eq!(easing.apply(2.), 1.);
+        }
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn value_moves_towards_target() {
+        let mut value = AnimatedValue::new(0.);
+        assert!(!value.set_target(10.));
+        assert_eq!(value.value(), 10.);
+
+        let transition = Transition::new(Duration::from_millis(100), Easing::Linear);
+        value.set_transition(Some(transition));
+        assert!(value.set_target(20.));
+        assert!(value.advance(25_000_000));
+        assert_eq!(value.value(), 12.5);
+
+        // Retargeting starts from the current value.
+        assert!(value.set_target(0.));
+        assert!(value.advance(50_000_000));
+        assert_eq!(value.value(), 6.25);
+        assert!(value.advance(100_000_000));
+        assert_eq!(value.value(), 0.);
+        assert!(!value.is_animating());
+        assert!(!value.advance(16_000_000));
+    }
+}
@@ -1,9 +1,14 @@
 // Copyright 2024 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
+use winit::window::WindowAttributes;
+
+use crate::dpi::LogicalSize;
 use crate::event_loop_runner::MasonryState;
-use crate::widget::WidgetMut;
-use crate::{Action, Widget, WidgetId};
+use crate::render_root::{AppWindowId, RenderRootSignal};
+use crate::text::{measure_text, text_metrics, TextMetrics, TextStyle};
+use crate::widget::{Portal, Prose, WidgetMut};
+use crate::{Action, RenderRoot, Size, Widget, WidgetId};
 
 pub struct DriverCtx<'a> {
     // TODO
@@ -13,6 +18,10 @@ pub struct DriverCtx<'a> {
     // expedience only while better solutions are devised.
     #[doc(hidden)]
     pub main_root_widget: WidgetMut<'a, Box<dyn Widget>>,
+    /// The secondary window the current action comes from, or `None` for the main window.
+    window: Option<AppWindowId>,
+    /// The widget trees of the open secondary windows.
+    secondary_windows: Vec<(AppWindowId, &'a mut RenderRoot)>,
 }
 
 pub trait AppDriver {
@@ -24,9 +33,74 @@ pub trait AppDriver {
     ///
     /// Use cases include loading fonts.
     fn on_start(&mut self, state: &mut MasonryState) {}
+
+    #[allow(unused_variables)]
+    // reason: otherwise `ctx` would need to be named `_ctx` which behaves badly when using rust-analyzer to implement the trait
+    /// A hook which will be executed when the window is about to close, or the app is asked to terminate.
+    ///
+    /// Return [`CloseResponse::Veto`] to keep the app running, e.g. to show an "unsaved changes"
+    /// dialog. The app can then close the window later with [`DriverCtx::close_window`].
+    fn on_close_requested(
+        &mut self,
+        ctx: &mut DriverCtx<'_>,
+        reason: CloseReason,
+    ) -> CloseResponse {
+        CloseResponse::Close
+    }
+
+    #[allow(unused_variables)]
+    // reason: otherwise `ctx` would need to be named `_ctx` which behaves badly when using rust-analyzer to implement the trait
+    /// A hook which will be executed when a secondary window is closed, either by the
+    /// user or with [`DriverCtx::close_app_window`].
+    ///
+    /// The widget tree of the window is dropped after this returns, so it can still be
+    /// edited with [`DriverCtx::edit_window_root`], e.g. to tear down the views it was built from.
+    fn on_window_closed(&mut self, ctx: &mut DriverCtx<'_>, window: AppWindowId) {}
+}
+
+/// Why the app was asked to close, in [`AppDriver::on_close_requested`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The user asked to close the window, e.g. with its close button.
+    Window,
+    /// The app asked itself to close, by sending [`MasonryUserEvent::CloseRequested`].
+    ///
+    /// [`MasonryUserEvent::CloseRequested`]: crate::event_loop_runner::MasonryUserEvent::CloseRequested
+    Requested,
+    /// The OS asked the app to terminate, e.g. with `SIGTERM` or `SIGINT`.
+    ///
+    /// This requires the `close-signals` feature, and is currently only sent on Unix.
+    Terminate,
+    /// The session of the user is ending, e.g. because they log out, which is
+    /// signaled with `SIGHUP`.
+    ///
+    /// This requires the `close-signals` feature, and is currently only sent on Unix.
+    SessionEnd,
+}
+
+/// Whether the app should close, returned by [`AppDriver::on_close_requested`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CloseResponse {
+    #[default]
+    Close,
+    /// Keep the app running.
+    Veto,
 }
 
 impl<'a> DriverCtx<'a> {
+    pub(crate) fn new(
+        main_root_widget: WidgetMut<'a, Box<dyn Widget>>,
+        window: Option<AppWindowId>,
+        secondary_windows: Vec<(AppWindowId, &'a mut RenderRoot)>,
+    ) -> Self {
+        Self {
+            main_root_widget,
+            window,
+            secondary_windows,
+        }
+    }
+
     // TODO - Add method to create timer
 
     /// Return a [`WidgetMut`] to the root widget.
@@ -34,6 +108,114 @@ impl<'a> DriverCtx<'a> {
         self.main_root_widget.downcast()
     }
 
+    /// Measure the size `text` would have if displayed with `style`, without creating a widget.
+    ///
+    /// See [`RenderRoot::measure_text`](crate::RenderRoot::measure_text).
+    pub fn measure_text(
+        &mut self,
+        text: &str,
+        style: &TextStyle,
+        max_advance: Option<f32>,
+    ) -> Size {
+        let global_state = &mut *self.main_root_widget.ctx.global_state;
+        measure_text(
+            &mut global_state.font_context,
+            &mut global_state.text_layout_context,
+            text,
+            style,
+            &global_state.env,
+            max_advance,
+        )
+    }
+
+    /// Resolve the line height, ascent and descent of text displayed with `style`.
+    ///
+    /// See [`RenderRoot::text_metrics`](crate::RenderRoot::text_metrics).
+    pub fn text_metrics(&mut self, style: &TextStyle) -> TextMetrics {
+        let global_state = &mut *self.main_root_widget.ctx.global_state;
+        text_metrics(
+            &mut global_state.font_context,
+            &mut global_state.text_layout_context,
+            style,
+            &global_state.env,
+        )
+    }
+
+    /// Close the main window, which exits the app.
+    ///
+    /// This doesn't call [`AppDriver::on_close_requested`], so it can be used to close the
+    /// window after a close was vetoed.
+    pub fn close_window(&mut self) {
+        self.main_root_widget
+            .ctx
+            .global_state
+            .emit_signal(RenderRootSignal::CloseWindow);
+    }
+
+    /// Open a secondary window showing `root_widget`.
+    ///
+    /// See [`EventCtx::open_window`](crate::EventCtx::open_window).
+    pub fn open_window(
+        &mut self,
+        attributes: WindowAttributes,
+        root_widget: impl Widget,
+    ) -> AppWindowId {
+        self.main_root_widget
+            .ctx
+            .global_state
+            .open_window(attributes, Box::new(root_widget))
+    }
+
+    /// Open a secondary window showing the [diagnostics report](RenderRoot::diagnostics_report)
+    /// of the main window, refreshed after each frame of the main window.
+    ///
+    /// If the diagnostics window is already open, this returns its id.
+    pub fn open_diagnostics_window(&mut self) -> AppWindowId {
+        let global_state = &mut *self.main_root_widget.ctx.global_state;
+        if let Some(id) = global_state.diagnostics_window {
+            return id;
+        }
+        let attributes = WindowAttributes::default()
+            .with_title("Diagnostics")
+            .with_inner_size(LogicalSize::new(480., 640.));
+        let root_widget = Portal::new(Prose::new(""));
+        let id = global_state.open_window(attributes, Box::new(root_widget));
+        global_state.diagnostics_window = Some(id);
+        id
+    }
+
+    /// Close a secondary window opened with [`open_window`](Self::open_window).
+    pub fn close_app_window(&mut self, id: AppWindowId) {
+        self.main_root_widget
+            .ctx
+            .global_state
+            .emit_signal(RenderRootSignal::CloseAppWindow(id));
+    }
+
+    /// The secondary window the current action comes from, or `None` if it comes from
+    /// the main window.
+    ///
+    /// [`get_root`](Self::get_root) always returns the root widget of the main window.
+    pub fn window(&self) -> Option<AppWindowId> {
+        self.window
+    }
+
+    /// Edit the root widget of the secondary window `id`.
+    ///
+    /// Returns `None` if the window isn't open, including if it was opened while handling
+    /// the current action: windows are only added once the action is handled.
+    pub fn edit_window_root<R>(
+        &mut self,
+        id: AppWindowId,
+        f: impl FnOnce(WidgetMut<'_, Box<dyn Widget>>) -> R,
+    ) -> Option<R> {
+        let (_, render_root) = self
+            .secondary_windows
+            .iter_mut()
+            .find(|(window, _)| *window == id)?;
+        Some(render_root.edit_root_widget(f))
+    }
+
     pub fn content_changed(&self) -> bool {
         let ctx = &self.main_root_widget.ctx;
         ctx.widget_state.needs_rewrite_passes()
@@ -0,0 +1,453 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Loading and decoding of images, fonts and SVGs on background threads.
+//!
+//! An [`AssetLoader`] owns a pool of worker threads. Requests are picked up by priority,
+//! and the loaded asset is handed to a callback on the worker thread. The callback is
+//! expected to forward the asset to the UI thread, e.g. through an
+//! [`EventLoopProxy`](crate::event_loop_runner::EventLoopProxy).
+//!
+//! Requests which are no longer needed, e.g. because the widget which requested them
+//! was removed, can be cancelled through their [`AssetHandle`].
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+use tracing::{trace, warn};
+use vello::peniko::{Blob, Format, Image as ImageBuf};
+
+/// The maximum number of threads used by [`AssetLoader::default`].
+const DEFAULT_MAX_THREADS: usize = 4;
+
+/// Where an asset is loaded from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetSource {
+    /// A file on the local filesystem.
+    Path(PathBuf),
+    /// Encoded data which is already in memory.
+    Bytes(Blob<u8>),
+}
+
+impl From<PathBuf> for AssetSource {
+    fn from(path: PathBuf) -> Self {
+        Self::Path(path)
+    }
+}
+
+impl From<&Path> for AssetSource {
+    fn from(path: &Path) -> Self {
+        Self::Path(path.to_path_buf())
+    }
+}
+
+impl From<Vec<u8>> for AssetSource {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Bytes(Blob::new(Arc::new(data)))
+    }
+}
+
+impl From<&'static [u8]> for AssetSource {
+    fn from(data: &'static [u8]) -> Self {
+        Self::Bytes(Blob::new(Arc::new(data)))
+    }
+}
+
+/// How the data of an asset is decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AssetKind {
+    /// A bitmap image, in any format supported by the `image` crate.
+    Image,
+    /// A TrueType or OpenType font, or a font collection.
+    Font,
+    /// An SVG document.
+    Svg,
+}
+
+/// A loaded asset.
+#[derive(Clone, Debug)]
+pub enum Asset {
+    /// A decoded image, ready to be used by the [`Image`](crate::widget::Image) widget.
+    Image(ImageBuf),
+    /// The data of a font, which can be registered with
+    /// [`RenderRoot::register_fonts`](crate::RenderRoot::register_fonts).
+    Font(Blob<u8>),
+    /// The text of an SVG document.
+    ///
+    /// Masonry doesn't render SVGs itself; the document is read and checked off the UI thread,
+    /// and is left to the app to draw.
+    Svg(Arc<str>),
+}
+
+/// The error returned when an asset can't be loaded.
+#[derive(Clone, Debug)]
+pub enum AssetError {
+    /// The source couldn't be read.
+    Io(Arc<std::io::Error>),
+    /// The data couldn't be decoded as the requested [`AssetKind`].
+    Decode(Arc<str>),
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read asset: {err}"),
+            Self::Decode(reason) => write!(f, "failed to decode asset: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(&**err),
+            Self::Decode(_) => None,
+        }
+    }
+}
+
+/// The priority of an asset request.
+///
+/// Pending requests with a higher priority are loaded first. Requests with the same
+/// priority are loaded in the order they were made.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AssetPriority {
+    /// For assets which aren't visible yet, e.g. when prefetching.
+    Low,
+    #[default]
+    Normal,
+    /// For assets which are blocking the display of the app.
+    High,
+}
+
+/// A request to load an asset, made with [`AssetLoader::load`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssetRequest {
+    pub source: AssetSource,
+    pub kind: AssetKind,
+    pub priority: AssetPriority,
+}
+
+impl AssetRequest {
+    /// Create a request with [`AssetPriority::Normal`].
+    pub fn new(source: impl Into<AssetSource>, kind: AssetKind) -> Self {
+        Self {
+            source: source.into(),
+            kind,
+            priority: AssetPriority::Normal,
+        }
+    }
+
+    /// Builder-style method for setting the priority of the request.
+    pub fn with_priority(mut self, priority: AssetPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Read and decode the asset on the current thread.
+    pub fn load_blocking(&self) -> Result<Asset, AssetError> {
+        let data = match &self.source {
+            AssetSource::Path(path) => Blob::new(Arc::new(
+                std::fs::read(path).map_err(|err| AssetError::Io(Arc::new(err)))?,
+            )),
+            AssetSource::Bytes(data) => data.clone(),
+        };
+        match self.kind {
+            AssetKind::Image => decode_image(data.data()).map(Asset::Image),
+            AssetKind::Font => check_font(data.data()).map(|()| Asset::Font(data)),
+            AssetKind::Svg => decode_svg(data.data()).map(Asset::Svg),
+        }
+    }
+}
+
+fn decode_image(data: &[u8]) -> Result<ImageBuf, AssetError> {
+    let image = image::load_from_memory(data)
+        .map_err(|err| AssetError::Decode(err.to_string().into()))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok(ImageBuf::new(
+        Blob::new(Arc::new(image.into_vec())),
+        Format::Rgba8,
+        width,
+        height,
+    ))
+}
+
+fn check_font(data: &[u8]) -> Result<(), AssetError> {
+    const SIGNATURES: [&[u8; 4]; 4] = [b"\x00\x01\x00\x00", b"OTTO", b"true", b"ttcf"];
+
+    if SIGNATURES
+        .iter()
+        .any(|signature| data.starts_with(*signature))
+    {
+        Ok(())
+    } else {
+        Err(AssetError::Decode("not a TrueType or OpenType font".into()))
+    }
+}
+
+fn decode_svg(data: &[u8]) -> Result<Arc<str>, AssetError> {
+    let text = std::str::from_utf8(data)
+        .map_err(|err| AssetError::Decode(format!("SVG is not valid UTF-8: {err}").into()))?;
+    if !text.contains("<svg") {
+        return Err(AssetError::Decode("missing <svg> element".into()));
+    }
+    Ok(text.into())
+}
+
+/// A handle to a pending asset request, returned by [`AssetLoader::load`].
+///
+/// Dropping the handle doesn't cancel the request.
+#[derive(Clone, Debug)]
+pub struct AssetHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl AssetHandle {
+    /// Cancel the request.
+    ///
+    /// If the asset hasn't been loaded yet, its callback won't be called. A request which
+    /// is being loaded when it is cancelled may still deliver its asset.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, AtomicOrdering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(AtomicOrdering::Relaxed)
+    }
+}
+
+type AssetCallback = Box<dyn FnOnce(Result<Asset, AssetError>) + Send>;
+
+struct Job {
+    request: AssetRequest,
+    /// Used to load requests with the same priority in order.
+    sequence: u64,
+    cancelled: Arc<AtomicBool>,
+    on_loaded: AssetCallback,
+}
+
+impl Job {
+    fn key(&self) -> (AssetPriority, std::cmp::Reverse<u64>) {
+        (self.request.priority, std::cmp::Reverse(self.sequence))
+    }
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Job {}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: BinaryHeap<Job>,
+    next_sequence: u64,
+    shutdown: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    job_available: Condvar,
+}
+
+/// A pool of threads loading assets in the background.
+///
+/// Dropping the loader discards pending requests, and waits for the requests which are
+/// being loaded to finish.
+pub struct AssetLoader {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl AssetLoader {
+    /// Create a loader with `threads` worker threads.
+    pub fn new(threads: usize) -> Self {
+        let shared = Arc::new(Shared::default());
+        let workers = (0..threads.max(1))
+            .map(|i| {
+                let shared = shared.clone();
+                std::thread::Builder::new()
+                    .name(format!("masonry-assets-{i}"))
+                    .spawn(move || run_worker(&shared))
+                    .expect("failed to spawn asset loader thread")
+            })
+            .collect();
+        Self { shared, workers }
+    }
+
+    /// Queue `request`, and call `on_loaded` from a worker thread once it's loaded.
+    pub fn load(
+        &self,
+        request: AssetRequest,
+        on_loaded: impl FnOnce(Result<Asset, AssetError>) + Send + 'static,
+    ) -> AssetHandle {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut queue = self.shared.queue.lock().unwrap();
+        let sequence = queue.next_sequence;
+        queue.next_sequence += 1;
+        queue.jobs.push(Job {
+            request,
+            sequence,
+            cancelled: cancelled.clone(),
+            on_loaded: Box::new(on_loaded),
+        });
+        drop(queue);
+        self.shared.job_available.notify_one();
+        AssetHandle { cancelled }
+    }
+
+    /// The number of requests which haven't been picked up by a worker yet.
+    pub fn pending_count(&self) -> usize {
+        self.shared.queue.lock().unwrap().jobs.len()
+    }
+}
+
+impl Default for AssetLoader {
+    /// Create a loader with one thread per core, up to 4 threads.
+    fn default() -> Self {
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |threads| threads.get().min(DEFAULT_MAX_THREADS));
+        Self::new(threads)
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().shutdown = true;
+        self.shared.job_available.notify_all();
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                warn!("Asset loader thread panicked");
+            }
+        }
+    }
+}
+
+fn run_worker(shared: &Shared) {
+    loop {
+        let job = {
+            let mut queue = shared.queue.lock().unwrap();
+            loop {
+                if queue.shutdown {
+                    return;
+                }
+                if let Some(job) = queue.jobs.pop() {
+                    break job;
+                }
+                queue = shared.job_available.wait(queue).unwrap();
+            }
+        };
+        if job.cancelled.load(AtomicOrdering::Relaxed) {
+            trace!("Skipping cancelled asset request {:?}", job.request.source);
+            continue;
+        }
+        let result = job.request.load_blocking();
+        if !job.cancelled.load(AtomicOrdering::Relaxed) {
+            (job.on_loaded)(result);
+        }
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::mpsc;
+
+    use image::{ImageFormat, RgbaImage};
+
+    use super::*;
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        RgbaImage::new(width, height)
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn decode_assets() {
+        let image = AssetRequest::new(png_bytes(3, 2), AssetKind::Image).load_blocking();
+        let Ok(Asset::Image(image)) = image else {
+            panic!("expected an image, got {image:?}");
+        };
+        assert_eq!((image.width, image.height), (3, 2));
+
+        let svg = AssetRequest::new(b"<svg></svg>".as_slice(), AssetKind::Svg).load_blocking();
+        assert!(matches!(svg, Ok(Asset::Svg(text)) if &*text == "<svg></svg>"));
+
+        let not_a_font = AssetRequest::new(b"<svg></svg>".as_slice(), AssetKind::Font);
+        assert!(matches!(
+            not_a_font.load_blocking(),
+            Err(AssetError::Decode(_))
+        ));
+
+        let missing = AssetRequest::new(Path::new("does/not/exist.png"), AssetKind::Image);
+        assert!(matches!(missing.load_blocking(), Err(AssetError::Io(_))));
+    }
+
+    #[test]
+    fn load_by_priority() {
+        let loader = AssetLoader::new(1);
+        let (sender, receiver) = mpsc::channel();
+
+        // Keep the only worker busy while the other requests are queued.
+        let (unblock, blocked) = mpsc::channel::<()>();
+        loader.load(
+            AssetRequest::new(png_bytes(1, 1), AssetKind::Image),
+            move |_| blocked.recv().unwrap(),
+        );
+        while loader.pending_count() > 0 {
+            std::thread::yield_now();
+        }
+
+        let priorities = [
+            ("low", AssetPriority::Low),
+            ("normal", AssetPriority::Normal),
+            ("high", AssetPriority::High),
+            ("normal 2", AssetPriority::Normal),
+            ("cancelled", AssetPriority::High),
+        ];
+        let handles: Vec<_> = priorities
+            .into_iter()
+            .map(|(name, priority)| {
+                let sender = sender.clone();
+                let request =
+                    AssetRequest::new(png_bytes(1, 1), AssetKind::Image).with_priority(priority);
+                loader.load(request, move |result| {
+                    assert!(result.is_ok());
+                    sender.send(name).unwrap();
+                })
+            })
+            .collect();
+        handles[4].cancel();
+        drop(sender);
+        unblock.send(()).unwrap();
+
+        // The channel is closed once every callback has been called or dropped.
+        let loaded: Vec<_> = receiver.iter().collect();
+        assert_eq!(loaded, ["high", "normal", "normal 2", "low"]);
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use vello::kurbo::{Affine, Insets, Rect, Vec2};
+use vello::peniko::{Color, Fill};
+use vello::Scene;
+
+/// How far a blurred shadow extends beyond its rect, in standard deviations of the blur.
+///
+/// This is the extent of the rect drawn by [`Scene::draw_blurred_rounded_rect`].
+const BLUR_EXTENT: f64 = 2.5;
+
+/// A drop shadow painted below a rounded rect, see [`PaintCtx::paint_shadow`](crate::PaintCtx::paint_shadow).
+///
+/// Shadows usually extend beyond the layout rect of the widget which paints them. Widgets
+/// should pass [`paint_insets`](Self::paint_insets) to
+/// [`LayoutCtx::set_paint_insets`](crate::LayoutCtx::set_paint_insets), so that the shadow
+/// isn't culled or clipped with the widget.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoxShadow {
+    pub color: Color,
+    /// The offset of the shadow from the rect which casts it.
+    pub offset: Vec2,
+    /// The blur radius, as in CSS: the edge of the shadow is a gaussian blur with a standard
+    /// deviation of half this radius.
+    pub blur_radius: f64,
+}
+
+impl BoxShadow {
+    pub const fn new(color: Color, offset: Vec2, blur_radius: f64) -> Self {
+        Self {
+            color,
+            offset,
+            blur_radius,
+        }
+    }
+
+    /// How far the shadow of a rect extends beyond each side of it.
+    pub fn paint_insets(&self) -> Insets {
+        let blur = BLUR_EXTENT * self.std_dev();
+        Insets::new(
+            blur - self.offset.x,
+            blur - self.offset.y,
+            blur + self.offset.x,
+            blur + self.offset.y,
+        )
+        .nonnegative()
+    }
+
+    /// Paint the shadow cast by `rect`, with corners of the given `radius`.
+    pub(crate) fn paint(&self, scene: &mut Scene, rect: Rect, radius: f64) {
+        let rect = rect + self.offset;
+        let std_dev = self.std_dev();
+        if std_dev == 0. {
+            let shape = rect.to_rounded_rect(radius);
+            scene.fill(Fill::NonZero, Affine::IDENTITY, self.color, None, &shape);
+            return;
+        }
+        scene.draw_blurred_rounded_rect(Affine::IDENTITY, rect, self.color, radius, std_dev);
+    }
+
+    /// The standard deviation of the gaussian blur, half of the blur radius as in CSS.
+    fn std_dev(&self) -> f64 {
+        self.blur_radius.max(0.) / 2.
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_insets_include_offset_and_blur() {
+        let shadow = BoxShadow::new(Color::BLACK, Vec2::new(0., 4.), 8.);
+        assert_eq!(shadow.paint_insets(), Insets::new(10., 6., 10., 14.));
+        // A shadow moved further than its blur doesn't extend on the opposite side.
+        let shadow = BoxShadow::new(Color::BLACK, Vec2::new(10., 0.), 2.);
+        assert_eq!(shadow.paint_insets(), Insets::new(0., 2.5, 12.5, 2.5));
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use vello::kurbo::{Affine, BezPath, Point, Rect, RoundedRect, Shape};
+use vello::peniko::Mix;
+use vello::Scene;
+
+/// The shape a widget clips its children to, see [`LayoutCtx::set_clip_path`](crate::LayoutCtx::set_clip_path).
+///
+/// Rectangles are the most common and the cheapest clips. The other shapes let e.g. rounded
+/// cards and circular avatars clip their content to their outline.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipPath {
+    Rect(Rect),
+    RoundedRect(RoundedRect),
+    /// An arbitrary path, filled with the non-zero winding rule.
+    Path(BezPath),
+}
+
+impl ClipPath {
+    /// The smallest rectangle which contains the clip path.
+    pub fn bounding_box(&self) -> Rect {
+        match self {
+            Self::Rect(rect) => *rect,
+            Self::RoundedRect(rect) => rect.bounding_box(),
+            Self::Path(path) => path.bounding_box(),
+        }
+    }
+
+    /// Whether `point` is inside the clip path.
+    pub fn contains(&self, point: Point) -> bool {
+        match self {
+            Self::Rect(rect) => rect.contains(point),
+            Self::RoundedRect(rect) => rect.contains(point),
+            Self::Path(path) => path.contains(point),
+        }
+    }
+
+    /// Push a layer which clips everything painted until it's popped to this path.
+    pub(crate) fn push_layer(&self, scene: &mut Scene, transform: Affine) {
+        match self {
+            Self::Rect(rect) => scene.push_layer(Mix::Clip, 1., transform, rect),
+            Self::RoundedRect(rect) => scene.push_layer(Mix::Clip, 1., transform, rect),
+            Self::Path(path) => scene.push_layer(Mix::Clip, 1., transform, path),
+        }
+    }
+}
+
+impl From<Rect> for ClipPath {
+    fn from(rect: Rect) -> Self {
+        Self::Rect(rect)
+    }
+}
+
+impl From<RoundedRect> for ClipPath {
+    fn from(rect: RoundedRect) -> Self {
+        Self::RoundedRect(rect)
+    }
+}
+
+impl From<BezPath> for ClipPath {
+    fn from(path: BezPath) -> Self {
+        Self::Path(path)
+    }
+}
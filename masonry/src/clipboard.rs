@@ -0,0 +1,279 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exchanging data with the system clipboard.
+//!
+//! Widgets copy data with [`EventCtx::set_clipboard`](crate::EventCtx::set_clipboard), and
+//! ask for the content of the clipboard with [`EventCtx::request_paste`](crate::EventCtx::request_paste),
+//! which delivers it as a [`TextEvent::Paste`](crate::TextEvent::Paste).
+//!
+//! This module also has helpers to copy and paste tabular data, e.g. the cells of a table, in
+//! the formats understood by spreadsheet apps: tab- or comma-separated values, and HTML tables.
+//!
+//! The system clipboard is only used with the `clipboard` feature. Without it, copied data
+//! is discarded, and nothing is pasted.
+
+use std::fmt::Write as _;
+
+/// Data copied to the clipboard.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClipboardContent {
+    /// The plain text flavor, which all apps can paste.
+    pub text: String,
+    /// An HTML flavor, which apps supporting rich text paste instead of `text`.
+    pub html: Option<String>,
+}
+
+/// The character separating the fields of delimited data.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Tab-separated values, which is what spreadsheet apps copy.
+    #[default]
+    Tab,
+    /// Comma-separated values.
+    Comma,
+}
+
+impl ClipboardContent {
+    /// Plain text content.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            html: None,
+        }
+    }
+
+    /// The rows of a table, as tab-separated values with an HTML table flavor.
+    pub fn table(rows: &[Vec<String>]) -> Self {
+        Self {
+            text: to_delimited(rows, Delimiter::Tab),
+            html: Some(to_html_table(rows)),
+        }
+    }
+}
+
+impl Delimiter {
+    /// The delimiter used by `text`: tabs if there are any, commas otherwise.
+    pub fn detect(text: &str) -> Self {
+        if text.contains('\t') {
+            Self::Tab
+        } else {
+            Self::Comma
+        }
+    }
+
+    fn as_char(self) -> char {
+        match self {
+            Self::Tab => '\t',
+            Self::Comma => ',',
+        }
+    }
+}
+
+// --- MARK: FORMATS ---
+/// Write `rows` as delimited text, with one line per row.
+///
+/// Fields containing the delimiter, a quote or a line break are quoted, with quotes doubled.
+pub fn to_delimited(rows: &[Vec<String>], delimiter: Delimiter) -> String {
+    let delimiter = delimiter.as_char();
+    let mut text = String::new();
+    for row in rows {
+        for (i, field) in row.iter().enumerate() {
+            if i > 0 {
+                text.push(delimiter);
+            }
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                text.push('"');
+                text.push_str(&field.replace('"', "\"\""));
+                text.push('"');
+            } else {
+                text.push_str(field);
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Write `rows` as an HTML table, with the fields escaped.
+pub fn to_html_table(rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>");
+    for row in rows {
+        html.push_str("<tr>");
+        for field in row {
+            let _ = write!(html, "<td>{}</td>", escape_html(field));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+    html
+}
+
+/// Read the rows of delimited text, as written by [`to_delimited`] or by spreadsheet apps.
+///
+/// Quoted fields may contain the delimiter, doubled quotes and line breaks. A trailing
+/// line break doesn't start a new row.
+pub fn parse_delimited(text: &str, delimiter: Delimiter) -> Vec<Vec<String>> {
+    let delimiter = delimiter.as_char();
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("<br>"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// --- MARK: SYSTEM CLIPBOARD ---
+/// The clipboard of the platform, used by the event loop runner.
+#[derive(Default)]
+pub(crate) struct SystemClipboard {
+    // On X11 and Wayland, the copied data is served by this handle, so it's kept alive.
+    #[cfg(feature = "clipboard")]
+    inner: Option<arboard::Clipboard>,
+}
+
+#[cfg(feature = "clipboard")]
+impl SystemClipboard {
+    pub(crate) fn set(&mut self, content: ClipboardContent) {
+        let Some(clipboard) = self.clipboard() else {
+            return;
+        };
+        let result = match content.html {
+            Some(html) => clipboard.set_html(html, Some(content.text)),
+            None => clipboard.set_text(content.text),
+        };
+        if let Err(err) = result {
+            tracing::warn!("Failed to copy to the clipboard: {err}");
+        }
+    }
+
+    pub(crate) fn get(&mut self) -> Option<String> {
+        match self.clipboard()?.get_text() {
+            Ok(text) => Some(text),
+            Err(err) => {
+                tracing::warn!("Failed to paste from the clipboard: {err}");
+                None
+            }
+        }
+    }
+
+    fn clipboard(&mut self) -> Option<&mut arboard::Clipboard> {
+        if self.inner.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.inner = Some(clipboard),
+                Err(err) => tracing::warn!("Failed to access the clipboard: {err}"),
+            }
+        }
+        self.inner.as_mut()
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+impl SystemClipboard {
+    pub(crate) fn set(&mut self, _content: ClipboardContent) {
+        tracing::debug!("Copying requires the `clipboard` feature");
+    }
+
+    pub(crate) fn get(&mut self) -> Option<String> {
+        tracing::debug!("Pasting requires the `clipboard` feature");
+        None
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|field| field.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn delimited_round_trip() {
+        let table = rows(&[
+            &["Name", "Notes"],
+            &["Ada", "says \"hi\", often"],
+            &["Bob", "a\nb"],
+        ]);
+
+        let csv = to_delimited(&table, Delimiter::Comma);
+        assert_eq!(
+            csv,
+            "Name,Notes\nAda,\"says \"\"hi\"\", often\"\nBob,\"a\nb\"\n"
+        );
+        assert_eq!(parse_delimited(&csv, Delimiter::Comma), table);
+
+        let tsv = to_delimited(&table, Delimiter::Tab);
+        assert_eq!(
+            tsv,
+            "Name\tNotes\nAda\t\"says \"\"hi\"\", often\"\nBob\t\"a\nb\"\n"
+        );
+        assert_eq!(Delimiter::detect(&tsv), Delimiter::Tab);
+        assert_eq!(parse_delimited(&tsv, Delimiter::Tab), table);
+    }
+
+    #[test]
+    fn parse_spreadsheet_data() {
+        assert_eq!(
+            parse_delimited("1\t2\r\n\t4\r\n", Delimiter::Tab),
+            rows(&[&["1", "2"], &["", "4"]])
+        );
+        assert_eq!(
+            parse_delimited("a,b", Delimiter::Comma),
+            rows(&[&["a", "b"]])
+        );
+        assert_eq!(
+            parse_delimited("", Delimiter::Comma),
+            Vec::<Vec<String>>::new()
+        );
+        assert_eq!(
+            to_html_table(&rows(&[&["<b>", "a & b"]])),
+            "<table><tr><td>&lt;b&gt;</td><td>a &amp; b</td></tr></table>"
+        );
+    }
+}
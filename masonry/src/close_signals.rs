@@ -0,0 +1,91 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Forwarding of the termination signals of the OS to [`AppDriver::on_close_requested`].
+//!
+//! With the `close-signals` feature on Unix, `SIGTERM` and `SIGINT` are delivered with
+//! [`CloseReason::Terminate`], and `SIGHUP`, which is sent when the session of the user
+//! ends, with [`CloseReason::SessionEnd`]. The default handlers of these signals are
+//! replaced, so the app only exits if the driver returns [`CloseResponse::Close`].
+//!
+//! Winit doesn't expose the session end notifications of Windows (`WM_QUERYENDSESSION`)
+//! and macOS (`applicationShouldTerminate:`), so these aren't forwarded yet.
+//!
+//! [`AppDriver::on_close_requested`]: crate::AppDriver::on_close_requested
+//! [`CloseResponse::Close`]: crate::CloseResponse::Close
+
+use crate::event_loop_runner::EventLoopProxy;
+#[cfg(all(feature = "close-signals", unix))]
+use crate::CloseReason;
+
+/// Send [`MasonryUserEvent::CloseRequested`] through `proxy` when the process receives
+/// a termination signal, until the event loop exits.
+///
+/// [`MasonryUserEvent::CloseRequested`]: crate::event_loop_runner::MasonryUserEvent::CloseRequested
+#[cfg(all(feature = "close-signals", unix))]
+pub(crate) fn forward_close_signals(proxy: EventLoopProxy) {
+    use crate::event_loop_runner::MasonryUserEvent;
+
+    let result = listen(move |reason| {
+        proxy
+            .send_event(MasonryUserEvent::CloseRequested(reason))
+            .is_ok()
+    });
+    if let Err(err) = result {
+        tracing::warn!("Couldn't listen to termination signals: {err}");
+    }
+}
+
+#[cfg(not(all(feature = "close-signals", unix)))]
+pub(crate) fn forward_close_signals(_proxy: EventLoopProxy) {}
+
+/// Call `send` on a new thread with the reason of each termination signal received,
+/// until it returns `false`.
+///
+/// The signal handlers are installed before this returns.
+#[cfg(all(feature = "close-signals", unix))]
+fn listen(mut send: impl FnMut(CloseReason) -> bool + Send + 'static) -> std::io::Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGTERM, SIGINT, SIGHUP])?;
+    std::thread::Builder::new()
+        .name("masonry-signals".into())
+        .spawn(move || {
+            for signal in signals.forever() {
+                let reason = match signal {
+                    SIGHUP => CloseReason::SessionEnd,
+                    _ => CloseReason::Terminate,
+                };
+                if !send(reason) {
+                    // The event loop has exited.
+                    return;
+                }
+            }
+        })?;
+    Ok(())
+}
+
+// --- MARK: TESTS ---
+#[cfg(all(test, feature = "close-signals", unix))]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use signal_hook::consts::{SIGHUP, SIGTERM};
+    use signal_hook::low_level::raise;
+
+    use super::*;
+
+    #[test]
+    fn signals_are_sent_with_their_reason() {
+        let (sender, receiver) = mpsc::channel();
+        listen(move |reason| sender.send(reason).is_ok()).unwrap();
+        let next = || receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        raise(SIGHUP).unwrap();
+        assert_eq!(next(), CloseReason::SessionEnd);
+        raise(SIGTERM).unwrap();
+        assert_eq!(next(), CloseReason::Terminate);
+    }
+}
@@ -0,0 +1,447 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hosting several independent widget trees in a single window.
+
+use accesskit::{ActionRequest, NodeBuilder, NodeId, Role, Tree, TreeUpdate};
+use dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
+use vello::kurbo::{Affine, Point, Rect};
+use vello::peniko::Mix;
+use vello::Scene;
+
+use crate::event::{PlatformEvent, PointerEvent, TextEvent, WindowEvent};
+use crate::render_root::{RenderRoot, RenderRootSignal};
+use crate::{Handled, WidgetId};
+
+/// Identifies a panel of a [`Composition`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PanelId(usize);
+
+/// Several independent [`RenderRoot`]s, each shown in its own region of a single window.
+///
+/// Each panel is a separate widget tree, with its own focus, signals, and options such as
+/// the [formatter](RenderRoot::set_formatter), so a plugin's panel can't affect the rest
+/// of the window. This also lets an app migrate to Masonry one region at a time.
+///
+/// A `Composition` is driven like a single `RenderRoot`, e.g. from an external event
+/// loop: pointer events are sent to the panel under the pointer, in the panel's own
+/// coordinates, and text events to the panel which was last clicked. Panels can be
+/// zoomed independently, in which case their content is laid out at a different scale.
+/// [`redraw`](Self::redraw) paints the panels on top of each other, in the order they
+/// were added, and merges their accessibility trees.
+pub struct Composition {
+    panels: Vec<Option<Panel>>,
+    scale_factor: f64,
+    /// The id of the accessibility node containing the panels.
+    window_id: WidgetId,
+    focused_panel: Option<PanelId>,
+    /// The panel which received the last pointer event, if the pointer is in the window.
+    pointer_panel: Option<PanelId>,
+}
+
+struct Panel {
+    root: RenderRoot,
+    /// The area covered by the panel, in the logical coordinates of the window.
+    region: Rect,
+    zoom: f64,
+    /// The id of the accessibility node wrapping the tree of the panel.
+    node_id: WidgetId,
+}
+
+impl Composition {
+    /// Create an empty composition, for a window with the given scale factor.
+    pub fn new(scale_factor: f64) -> Self {
+        Self {
+            panels: Vec::new(),
+            scale_factor,
+            window_id: WidgetId::next(),
+            focused_panel: None,
+            pointer_panel: None,
+        }
+    }
+
+    /// Add a panel showing `root` in `region`, above the existing panels.
+    ///
+    /// `region` is in the logical coordinates of the window. The scale factor of `root`
+    /// is set to the one of the window times the panel's [zoom](Self::set_zoom), which is
+    /// initially 1.
+    pub fn add_panel(&mut self, root: RenderRoot, region: Rect) -> PanelId {
+        let id = PanelId(self.panels.len());
+        self.panels.push(Some(Panel {
+            root,
+            region,
+            zoom: 1.0,
+            node_id: WidgetId::next(),
+        }));
+        self.update_panel_size(id);
+        id
+    }
+
+    /// Remove a panel, and return its render root.
+    pub fn remove_panel(&mut self, id: PanelId) -> Option<RenderRoot> {
+        let panel = self.panels.get_mut(id.0)?.take()?;
+        if self.focused_panel == Some(id) {
+            self.focused_panel = None;
+        }
+        if self.pointer_panel == Some(id) {
+            self.pointer_panel = None;
+        }
+        Some(panel.root)
+    }
+
+    /// Move or resize a panel. `region` is in the logical coordinates of the window.
+    pub fn set_region(&mut self, id: PanelId, region: Rect) {
+        if let Some(panel) = self.panel_mut(id) {
+            panel.region = region;
+            self.update_panel_size(id);
+        }
+    }
+
+    /// Set the zoom of a panel, i.e. how many logical pixels of the window each logical
+    /// pixel of the panel covers.
+    pub fn set_zoom(&mut self, id: PanelId, zoom: f64) {
+        if let Some(panel) = self.panel_mut(id) {
+            panel.zoom = zoom;
+            self.update_panel_size(id);
+        }
+    }
+
+    /// The area covered by a panel, in the logical coordinates of the window.
+    ///
+    /// Returns `None` if the panel was removed.
+    pub fn region(&self, id: PanelId) -> Option<Rect> {
+        self.panel(id).map(|panel| panel.region)
+    }
+
+    /// The render root of a panel, e.g. to edit its widgets.
+    pub fn root(&self, id: PanelId) -> Option<&RenderRoot> {
+        self.panel(id).map(|panel| &panel.root)
+    }
+
+    /// The render root of a panel, e.g. to edit its widgets or set its formatter.
+    ///
+    /// The scale factor and size of the panel are managed by the composition, so they're
+    /// reset when the panel is moved or zoomed, and when the window is rescaled.
+    pub fn root_mut(&mut self, id: PanelId) -> Option<&mut RenderRoot> {
+        self.panel_mut(id).map(|panel| &mut panel.root)
+    }
+
+    /// The ids of the panels, from bottom to top.
+    pub fn panels(&self) -> impl Iterator<Item = PanelId> + '_ {
+        self.panels
+            .iter()
+            .enumerate()
+            .filter(|(_, panel)| panel.is_some())
+            .map(|(idx, _)| PanelId(idx))
+    }
+
+    /// The topmost panel at `pos`, in the logical coordinates of the window.
+    pub fn panel_at(&self, pos: Point) -> Option<PanelId> {
+        let idx = self.panels.iter().rposition(|panel| {
+            panel
+                .as_ref()
+                .is_some_and(|panel| panel.region.contains(pos))
+        })?;
+        Some(PanelId(idx))
+    }
+
+    /// The panel which receives text events.
+    pub fn focused_panel(&self) -> Option<PanelId> {
+        self.focused_panel
+    }
+
+    /// Send text events to the given panel, and clear the focus of the previous one.
+    ///
+    /// This is done automatically when a panel is clicked.
+    pub fn focus_panel(&mut self, id: Option<PanelId>) {
+        if self.focused_panel == id {
+            return;
+        }
+        if let Some(previous) = self.focused_panel.and_then(|id| self.panel_mut(id)) {
+            previous.root.global_state.next_focused_widget = None;
+            previous.root.run_rewrite_passes();
+        }
+        self.focused_panel = id;
+    }
+
+    // --- MARK: EVENTS ---
+    /// Handle an event of the window.
+    ///
+    /// Resizing the window doesn't affect the panels, whose regions are set with
+    /// [`set_region`](Self::set_region); other events are sent to every panel.
+    pub fn handle_window_event(&mut self, event: WindowEvent) -> Handled {
+        match event {
+            WindowEvent::Rescale(scale_factor) => {
+                self.scale_factor = scale_factor;
+                for id in self.panels().collect::<Vec<_>>() {
+                    self.update_panel_size(id);
+                }
+                Handled::Yes
+            }
+            WindowEvent::Resize(_) => Handled::No,
+            event => {
+                for panel in self.panels.iter_mut().flatten() {
+                    panel.root.handle_window_event(event.clone());
+                }
+                Handled::Yes
+            }
+        }
+    }
+
+    /// Send a pointer event to the panel under the pointer, or the panel which captured it.
+    pub fn handle_pointer_event(&mut self, mut event: PointerEvent) -> Handled {
+        let captured = self
+            .pointer_panel
+            .and_then(|id| self.panel(id))
+            .is_some_and(|panel| panel.root.global_state.pointer_capture_target.is_some());
+        let target = match event.position() {
+            _ if captured => self.pointer_panel,
+            Some(pos) => self.panel_at(Point::new(pos.x, pos.y)),
+            None => self.pointer_panel,
+        };
+
+        if self.pointer_panel != target {
+            if let Some(previous) = self.pointer_panel.and_then(|id| self.panel_mut(id)) {
+                previous
+                    .root
+                    .handle_pointer_event(PointerEvent::new_pointer_leave());
+            }
+        }
+        self.pointer_panel = event.position().and(target);
+        if let PointerEvent::PointerDown(..) = event {
+            self.focus_panel(target);
+        }
+
+        let scale_factor = self.scale_factor;
+        let Some(panel) = target.and_then(|id| self.panel_mut(id)) else {
+            return Handled::No;
+        };
+        let origin = panel.region.origin();
+        let state = event.pointer_state_mut();
+        state.position = LogicalPosition::new(
+            (state.position.x - origin.x) / panel.zoom,
+            (state.position.y - origin.y) / panel.zoom,
+        );
+        state.physical_position = PhysicalPosition::new(
+            state.physical_position.x - origin.x * scale_factor,
+            state.physical_position.y - origin.y * scale_factor,
+        );
+        if let PointerEvent::MouseWheel(delta, _, _) = &mut event {
+            *delta = LogicalPosition::new(delta.x / panel.zoom, delta.y / panel.zoom);
+        }
+        panel.root.handle_pointer_event(event)
+    }
+
+    /// Send a text event to the focused panel.
+    pub fn handle_text_event(&mut self, event: TextEvent) -> Handled {
+        match self.focused_panel.and_then(|id| self.panel_mut(id)) {
+            Some(panel) => panel.root.handle_text_event(event),
+            None => Handled::No,
+        }
+    }
+
+    /// Send an accessibility action to the panel containing its target.
+    pub fn handle_access_event(&mut self, event: ActionRequest) {
+        let Ok(id) = event.target.0.try_into() else {
+            return;
+        };
+        let id = WidgetId(id);
+        if let Some(panel) = self
+            .panels
+            .iter_mut()
+            .flatten()
+            .find(|panel| panel.root.widget_arena.has(id))
+        {
+            panel.root.handle_access_event(event);
+        }
+    }
+
+    /// Send a platform event to every panel.
+    pub fn handle_platform_event(&mut self, event: PlatformEvent) -> Handled {
+        let mut handled = Handled::No;
+        for panel in self.panels.iter_mut().flatten() {
+            if panel.root.handle_platform_event(event.clone()).is_handled() {
+                handled = Handled::Yes;
+            }
+        }
+        handled
+    }
+
+    /// Pop the next signal emitted by one of the panels.
+    pub fn pop_signal(&mut self) -> Option<(PanelId, RenderRootSignal)> {
+        self.panels
+            .iter_mut()
+            .enumerate()
+            .find_map(|(idx, panel)| Some((PanelId(idx), panel.as_mut()?.root.pop_signal()?)))
+    }
+
+    // --- MARK: REDRAW ---
+    /// Paint every panel, each clipped to its region, and merge their accessibility trees.
+    ///
+    /// The scene is in the logical coordinates of the window.
+    pub fn redraw(&mut self) -> (Scene, TreeUpdate) {
+        let mut scene = Scene::new();
+        let mut window_node = NodeBuilder::new(Role::Window);
+        let mut nodes = Vec::new();
+        let mut focus = None;
+
+        for (idx, panel) in self.panels.iter_mut().enumerate() {
+            let Some(panel) = panel else {
+                continue;
+            };
+            let (panel_scene, panel_update) = panel.root.redraw();
+            let transform =
+                Affine::translate(panel.region.origin().to_vec2()) * Affine::scale(panel.zoom);
+            scene.push_layer(Mix::Clip, 1.0, Affine::IDENTITY, &panel.region);
+            scene.append(&panel_scene, Some(transform));
+            scene.pop_layer();
+
+            // The bounds of the nodes of the panel are in physical pixels, relative to
+            // the panel.
+            let mut panel_node = NodeBuilder::new(Role::Pane);
+            let origin = panel.region.origin().to_vec2() * self.scale_factor;
+            panel_node.set_transform(accesskit::Affine::translate(accesskit::Vec2::new(
+                origin.x, origin.y,
+            )));
+            let size = panel.region.size() * self.scale_factor;
+            panel_node.set_bounds(accesskit::Rect::new(0., 0., size.width, size.height));
+            panel_node.set_children(vec![NodeId::from(panel.root.root.id())]);
+            window_node.push_child(panel.node_id.into());
+            nodes.push((panel.node_id.into(), panel_node.build()));
+            nodes.extend(panel_update.nodes);
+
+            if self.focused_panel == Some(PanelId(idx)) {
+                focus = Some(panel_update.focus);
+            }
+        }
+        nodes.push((self.window_id.into(), window_node.build()));
+
+        let tree_update = TreeUpdate {
+            nodes,
+            tree: Some(Tree {
+                root: self.window_id.into(),
+                app_name: None,
+                toolkit_name: Some("Masonry".to_string()),
+                toolkit_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+            focus: focus.unwrap_or(self.window_id.into()),
+        };
+        (scene, tree_update)
+    }
+
+    // --- MARK: INTERNALS ---
+    fn panel(&self, id: PanelId) -> Option<&Panel> {
+        self.panels.get(id.0)?.as_ref()
+    }
+
+    fn panel_mut(&mut self, id: PanelId) -> Option<&mut Panel> {
+        self.panels.get_mut(id.0)?.as_mut()
+    }
+
+    /// Give the panel the scale factor and physical size matching its region and zoom.
+    fn update_panel_size(&mut self, id: PanelId) {
+        let scale_factor = self.scale_factor;
+        let Some(panel) = self.panel_mut(id) else {
+            return;
+        };
+        let size = panel.region.size() * scale_factor;
+        panel
+            .root
+            .handle_window_event(WindowEvent::Rescale(scale_factor * panel.zoom));
+        panel
+            .root
+            .handle_window_event(WindowEvent::Resize(PhysicalSize::new(
+                size.width.round() as u32,
+                size.height.round() as u32,
+            )));
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::event::{PointerButton, PointerState};
+    use crate::render_root::{RenderRootOptions, WindowSizePolicy};
+    use crate::testing::ModularWidget;
+
+    fn recording_root(clicked_at: Rc<Cell<Option<Point>>>) -> RenderRoot {
+        let widget = ModularWidget::new(clicked_at).pointer_event_fn(|clicked_at, _, event| {
+            if let PointerEvent::PointerDown(_, state) = event {
+                clicked_at.set(Some(Point::new(state.position.x, state.position.y)));
+            }
+        });
+        RenderRoot::new(
+            widget,
+            RenderRootOptions {
+                use_system_fonts: false,
+                size_policy: WindowSizePolicy::User,
+                scale_factor: 1.0,
+                test_font: None,
+                formatter: None,
+                pixel_snapping: false,
+            },
+        )
+    }
+
+    fn pointer_down(composition: &mut Composition, pos: Point) {
+        let mut state = PointerState::empty();
+        state.position = LogicalPosition::new(pos.x, pos.y);
+        state.physical_position = PhysicalPosition::new(pos.x, pos.y);
+        composition.handle_pointer_event(PointerEvent::PointerDown(PointerButton::Primary, state));
+    }
+
+    #[test]
+    fn pointer_events_reach_panel_under_pointer() {
+        let left_clicks = Rc::new(Cell::new(None));
+        let right_clicks = Rc::new(Cell::new(None));
+        let mut composition = Composition::new(1.0);
+        let left = composition.add_panel(
+            recording_root(left_clicks.clone()),
+            Rect::new(0., 0., 100., 100.),
+        );
+        let right = composition.add_panel(
+            recording_root(right_clicks.clone()),
+            Rect::new(100., 0., 200., 100.),
+        );
+        composition.set_zoom(right, 2.0);
+
+        pointer_down(&mut composition, Point::new(150., 40.));
+        assert_eq!(left_clicks.get(), None);
+        assert_eq!(right_clicks.get(), Some(Point::new(25., 20.)));
+        assert_eq!(composition.focused_panel(), Some(right));
+
+        pointer_down(&mut composition, Point::new(10., 10.));
+        assert_eq!(left_clicks.get(), Some(Point::new(10., 10.)));
+        assert_eq!(composition.focused_panel(), Some(left));
+    }
+
+    #[test]
+    fn accessibility_trees_are_merged() {
+        let mut composition = Composition::new(2.0);
+        let first =
+            composition.add_panel(recording_root(Rc::default()), Rect::new(0., 0., 100., 100.));
+        let second = composition.add_panel(
+            recording_root(Rc::default()),
+            Rect::new(100., 0., 200., 100.),
+        );
+
+        let (_, tree_update) = composition.redraw();
+        let root_id = tree_update.tree.unwrap().root;
+        let (_, window) = tree_update
+            .nodes
+            .iter()
+            .find(|(id, _)| *id == root_id)
+            .unwrap();
+        assert_eq!(window.children().len(), 2);
+
+        // Each panel's root widget is in the merged tree.
+        for panel in [first, second] {
+            let widget_id = NodeId::from(composition.root(panel).unwrap().root.id());
+            assert!(tree_update.nodes.iter().any(|(id, _)| *id == widget_id));
+        }
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Menus shown over the window, e.g. when the user right-clicks a widget.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::text::ArcStr;
+use crate::widget::menu::MenuKeyResult;
+use crate::widget::{self, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, Action, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A list of items shown with [`EventCtx::show_context_menu`](crate::EventCtx::show_context_menu),
+/// or by a [`MenuButton`](crate::widget::MenuButton).
+///
+/// When the user chooses an item, the widget which showed the menu submits
+/// [`Action::MenuItemSelected`](crate::Action::MenuItemSelected) with
+/// the index of the item. Separators aren't counted in the indices.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Menu {
+    items: Vec<MenuItem>,
+    /// The indices of the items which have a separator above them.
+    separators: Vec<usize>,
+}
+
+/// An item of a [`Menu`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct MenuItem {
+    label: ArcStr,
+    enabled: bool,
+}
+
+// --- MARK: BUILDERS ---
+impl Menu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style method to add an item at the end of the menu.
+    pub fn with_item(mut self, item: MenuItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// Builder-style method to add a separator between the last item and the next one.
+    pub fn with_separator(mut self) -> Self {
+        self.separators.push(self.items.len());
+        self
+    }
+
+    pub fn items(&self) -> &[MenuItem] {
+        &self.items
+    }
+
+    /// Whether the item at `index` has a separator above it.
+    pub(crate) fn has_separator_above(&self, index: usize) -> bool {
+        index > 0 && self.separators.contains(&index)
+    }
+}
+
+impl<T: Into<ArcStr>> FromIterator<T> for Menu {
+    /// Create a menu of enabled items with the given labels.
+    fn from_iter<I: IntoIterator<Item = T>>(labels: I) -> Self {
+        Self {
+            items: labels.into_iter().map(MenuItem::new).collect(),
+            separators: Vec::new(),
+        }
+    }
+}
+
+impl MenuItem {
+    pub fn new(label: impl Into<ArcStr>) -> Self {
+        Self {
+            label: label.into(),
+            enabled: true,
+        }
+    }
+
+    /// Builder-style method for setting whether the item can be chosen.
+    ///
+    /// Disabled items are greyed out, and skipped by keyboard navigation.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+// --- MARK: OPEN MENU ---
+/// The popup showing a context menu, see [`EventCtx::show_context_menu`], or the menu
+/// of a [`MenuButton`](crate::widget::MenuButton).
+///
+/// The menu takes the focus while it's open, so it receives the arrow keys,
+/// and gives it back to the widget which had it when it closes.
+pub(crate) struct ContextMenu {
+    menu: WidgetPod<widget::menu::Menu>,
+    /// The widget which showed the menu, and submits the chosen item.
+    owner: WidgetId,
+    /// The widget focused when the menu was shown.
+    prev_focused: Option<WidgetId>,
+}
+
+impl ContextMenu {
+    pub(crate) fn new(menu: Menu, owner: WidgetId, prev_focused: Option<WidgetId>) -> Self {
+        Self::with_menu(widget::menu::Menu::new(menu), owner, prev_focused)
+    }
+
+    /// Show the given menu widget, e.g. with its first item highlighted.
+    pub(crate) fn with_menu(
+        menu: widget::menu::Menu,
+        owner: WidgetId,
+        prev_focused: Option<WidgetId>,
+    ) -> Self {
+        Self {
+            menu: WidgetPod::new(menu),
+            owner,
+            prev_focused,
+        }
+    }
+
+    /// Close the menu, and submit the chosen item, if any, from the widget which
+    /// showed the menu.
+    ///
+    /// The widget which showed the menu receives [`Update::PopupClosed`].
+    fn close(&mut self, ctx: &mut EventCtx, item: Option<usize>) {
+        if let Some(index) = item {
+            ctx.global_state
+                .submit_action(Action::MenuItemSelected(index), self.owner);
+        }
+        if ctx.has_focus() {
+            match self.prev_focused {
+                Some(id) => ctx.set_focus(id),
+                None => ctx.resign_focus(),
+            }
+        }
+        ctx.global_state.dismiss_popup(ctx.widget_id());
+    }
+}
+
+impl Widget for ContextMenu {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        if !matches!(event, PointerEvent::PointerUp(..)) {
+            return;
+        }
+        let (item, highlighted) = {
+            let mut menu = ctx.get_raw_mut(&mut self.menu);
+            let menu = menu.widget();
+            (menu.take_activated(), menu.highlighted())
+        };
+        // The release of the click which opened the menu doesn't choose the item under
+        // the pointer, unless the pointer moved onto it since.
+        if item.is_some() && item == highlighted {
+            self.close(ctx, item);
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        let TextEvent::KeyboardKey(event, _) = event else {
+            return;
+        };
+        if !event.state.is_pressed() {
+            return;
+        }
+        let result = {
+            let mut menu = ctx.get_raw_mut(&mut self.menu);
+            let result = menu.widget().on_key(&event.logical_key);
+            if result == MenuKeyResult::Highlighted {
+                menu.ctx().request_render();
+                menu.ctx().request_accessibility_update();
+            }
+            result
+        };
+        match result {
+            MenuKeyResult::Ignored => return,
+            MenuKeyResult::Highlighted => {}
+            MenuKeyResult::Activated(index) => self.close(ctx, Some(index)),
+            MenuKeyResult::Close => self.close(ctx, None),
+        }
+        ctx.set_handled();
+    }
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, _event: &AccessEvent) {
+        let item = ctx.get_raw_mut(&mut self.menu).widget().take_activated();
+        if item.is_some() {
+            self.close(ctx, item);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::WidgetAdded => ctx.set_focus(self.menu.id()),
+            // E.g. when the window loses focus.
+            Update::FocusChanged(false) => ctx.global_state.dismiss_popup(ctx.widget_id()),
+            _ => {}
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.menu);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.menu, bc);
+        ctx.place_child(&mut self.menu, Point::ORIGIN);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.menu.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("ContextMenu")
+    }
+}
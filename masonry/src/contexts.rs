@@ -3,20 +3,39 @@
 
 //! The context types that are passed into various widget methods.
 
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use accesskit::TreeUpdate;
 use parley::{FontContext, LayoutContext};
 use tracing::{trace, warn};
-use vello::kurbo::Vec2;
+use vello::kurbo::{Affine, BezPath, Vec2};
+use vello::Scene;
+use winit::window::WindowAttributes;
 
 use crate::action::Action;
+use crate::clipboard::ClipboardContent;
+use crate::context_menu::{ContextMenu, Menu};
+use crate::dpi::LogicalSize;
+use crate::locale::LocaleFormatter;
+use crate::overlay::{PopupPlacement, PopupState};
 use crate::passes::layout::run_layout_on;
-use crate::render_root::{MutateCallback, RenderRootSignal, RenderRootState};
-use crate::text::TextBrush;
+use crate::passes::paint::paint_subtree;
+use crate::render_root::{
+    AppWindowId, DragState, MutateCallback, RenderRootSignal, RenderRootState, WidgetScene,
+    MIN_TIMER_INTERVAL,
+};
+use crate::text::{
+    measure_text, text_metrics, ArcStr, GlyphSnapping, TextBrush, TextMetrics, TextStyle,
+};
 use crate::tree_arena::{ArenaMutChildren, ArenaRefChildren};
-use crate::widget::{WidgetMut, WidgetRef, WidgetState};
-use crate::{AllowRawMut, BoxConstraints, Insets, Point, Rect, Size, Widget, WidgetId, WidgetPod};
+use crate::widget::{WidgetFlags, WidgetMut, WidgetRef, WidgetState};
+use crate::{
+    AllowRawMut, BoxConstraints, BoxShadow, ClipPath, Env, FocusDirection, Insets, MonitorInfo,
+    Point, Rect, Size, TimerToken, Widget, WidgetId, WidgetPod, WindowPlacement, WindowTheme,
+};
 
 // Note - Most methods defined in this file revolve around `WidgetState` fields.
 // Consider reading `WidgetState` documentation (especially the documented naming scheme)
@@ -118,6 +137,8 @@ pub struct PaintCtx<'a> {
     pub(crate) widget_state: &'a WidgetState,
     pub(crate) widget_state_children: ArenaMutChildren<'a, WidgetState>,
     pub(crate) widget_children: ArenaMutChildren<'a, Box<dyn Widget>>,
+    /// The scenes of all widgets, which are taken out of the global state during the paint pass.
+    pub(crate) scenes: &'a mut HashMap<WidgetId, Scene>,
     pub(crate) debug_paint: bool,
 }
 
@@ -148,6 +169,31 @@ impl_context_method!(
             self.widget_state.id
         }
 
+        /// The env of this widget, which theme values are read from.
+        ///
+        /// This combines the values set with [`set_env`] on this widget and its
+        /// ancestors, and the root env set with [`RenderRoot::set_env`].
+        ///
+        /// [`set_env`]: EventCtx::set_env
+        /// [`RenderRoot::set_env`]: crate::RenderRoot::set_env
+        pub fn env(&self) -> &Env {
+            &self.widget_state.env
+        }
+
+        /// The monitors connected to the system.
+        ///
+        /// This is empty until the platform has enumerated them, e.g. in unit tests.
+        pub fn monitors(&self) -> &[MonitorInfo] {
+            &self.global_state.monitors
+        }
+
+        /// The formatter used to display numbers and dates to the user.
+        ///
+        /// See the [`locale`](crate::locale) module for details.
+        pub fn formatter(&self) -> &dyn LocaleFormatter {
+            &*self.global_state.formatter
+        }
+
         #[allow(dead_code)]
         /// Helper method to get a direct reference to a child widget from its `WidgetPod`.
         fn get_child<Child: Widget>(&self, child: &'_ WidgetPod<Child>) -> &'_ Child {
@@ -160,7 +206,10 @@ impl_context_method!(
 
         #[allow(dead_code)]
         /// Helper method to get a direct reference to a child widget's `WidgetState` from its `WidgetPod`.
-        fn get_child_state<Child: Widget>(&self, child: &'_ WidgetPod<Child>) -> &'_ WidgetState {
+        pub(crate) fn get_child_state<Child: Widget>(
+            &self,
+            child: &'_ WidgetPod<Child>,
+        ) -> &'_ WidgetState {
             let child_state_ref = self
                 .widget_state_children
                 .get_child(child.id())
@@ -234,6 +283,22 @@ impl_context_method!(
             self.widget_state.window_layout_rect()
         }
 
+        /// The layout rect of the widget, moved by its presentation offset.
+        ///
+        /// See [`ComposeCtx::set_child_presentation_offset`].
+        pub fn presented_rect(&self) -> Rect {
+            self.widget_state.presented_rect()
+        }
+
+        /// The layout rect of the widget in window coordinates, once the presentation offsets
+        /// of the widget and its ancestors are removed.
+        ///
+        /// Unlike [`window_layout_rect`](Self::window_layout_rect), this doesn't move during
+        /// layout animations.
+        pub fn window_target_rect(&self) -> Rect {
+            self.widget_state.window_target_rect()
+        }
+
         pub fn paint_rect(&self) -> Rect {
             self.widget_state.paint_rect()
         }
@@ -242,15 +307,36 @@ impl_context_method!(
         ///
         /// For more information, see
         /// [`LayoutCtx::set_clip_path`](crate::LayoutCtx::set_clip_path).
-        pub fn clip_path(&self) -> Option<Rect> {
-            self.widget_state.clip_path
+        pub fn clip_path(&self) -> Option<&ClipPath> {
+            self.widget_state.clip_path.as_ref()
         }
 
         /// Convert a point from the widget's coordinate space to the window's.
         ///
         /// The returned point is relative to the content area; it excludes window chrome.
         pub fn to_window(&self, widget_point: Point) -> Point {
-            self.window_origin() + widget_point.to_vec2()
+            self.widget_state.window_transform() * widget_point
+        }
+
+        /// The scale from the widget's coordinate space to the window's.
+        ///
+        /// This is 1 unless the widget or one of its ancestors is scaled, e.g. by a
+        /// [`FitBox`](crate::widget::FitBox). See [`ComposeCtx::set_child_scale`] and
+        /// [`ComposeCtx::set_child_transform`].
+        pub fn window_scale(&self) -> f64 {
+            self.widget_state.window_scale()
+        }
+
+        /// The transform from the widget's coordinate space to the window's.
+        pub fn window_transform(&self) -> Affine {
+            self.widget_state.window_transform()
+        }
+
+        /// The color scheme of the system.
+        ///
+        /// Widgets receive [`Update::ThemeChanged`](crate::Update::ThemeChanged) when it changes.
+        pub fn color_scheme(&self) -> WindowTheme {
+            self.global_state.color_scheme
         }
     }
 );
@@ -282,16 +368,30 @@ impl_context_method!(
         /// pointer (for example, when clicking one widget and dragging to the
         /// next). The documentation should clearly state the resolution.
         pub fn is_hovered(&self) -> bool {
-            self.widget_state.is_hovered
+            self.widget_state.flags.contains(WidgetFlags::IS_HOVERED)
         }
 
         /// Whether the pointer is captured by this widget.
         ///
+        /// While a pointer event is handled, this is about the pointer which sent it.
         /// See [`capture_pointer`] for more information about pointer capture.
         ///
         /// [`capture_pointer`]: EventCtx::capture_pointer
         pub fn has_pointer_capture(&self) -> bool {
-            self.global_state.pointer_capture_target == Some(self.widget_state.id)
+            let target = match self.global_state.secondary_pointer {
+                Some(pointer_id) => self.global_state.pointer_captures.get(&pointer_id).copied(),
+                None => self.global_state.pointer_capture_target,
+            };
+            target == Some(self.widget_state.id)
+        }
+
+        /// Whether the pointer is locked by this widget.
+        ///
+        /// See [`lock_pointer`] for more information about pointer lock.
+        ///
+        /// [`lock_pointer`]: EventCtx::lock_pointer
+        pub fn has_pointer_lock(&self) -> bool {
+            self.global_state.pointer_lock_target == Some(self.widget_state.id)
         }
 
         /// The focus status of a widget.
@@ -317,27 +417,71 @@ impl_context_method!(
             self.global_state.focused_widget == Some(self.widget_id())
         }
 
+        /// Whether this widget is focused, and should show it, because it was focused
+        /// with the keyboard.
+        ///
+        /// The focus stops being visible when the user clicks, and becomes visible again
+        /// when they press a key. Masonry paints a focus ring around the widget while this
+        /// is true, unless it [paints its own](crate::Widget::paints_own_focus_indicator).
+        pub fn is_focus_visible(&self) -> bool {
+            self.is_focused() && self.global_state.focus_visible
+        }
+
         /// The (tree) focus status of a widget.
         ///
         /// Returns `true` if either this specific widget or any one of its descendants is focused.
         /// To check if only this specific widget is focused use [`is_focused`](Self::is_focused).
         pub fn has_focus(&self) -> bool {
-            self.widget_state.has_focus
+            self.widget_state.flags.contains(WidgetFlags::HAS_FOCUS)
         }
 
         /// Whether this widget gets pointer events and hovered status.
         pub fn accepts_pointer_interaction(&self) -> bool {
-            self.widget_state.accepts_pointer_interaction
+            self.widget_state
+                .flags
+                .contains(WidgetFlags::ACCEPTS_POINTER_INTERACTION)
+        }
+
+        /// Whether this widget and its descendants were made transparent to the pointer
+        /// with [`set_pointer_transparent`](EventCtx::set_pointer_transparent).
+        pub fn is_pointer_transparent(&self) -> bool {
+            self.widget_state
+                .flags
+                .contains(WidgetFlags::IS_POINTER_TRANSPARENT)
+        }
+
+        /// Whether this widget and its descendants were left out of the accessibility tree
+        /// with [`set_accessibility_hidden`](EventCtx::set_accessibility_hidden).
+        pub fn is_accessibility_hidden(&self) -> bool {
+            self.widget_state
+                .flags
+                .contains(WidgetFlags::IS_ACCESSIBILITY_HIDDEN)
+        }
+
+        /// The index of this widget in a set of items and the size of the set, set with
+        /// [`set_position_in_set`](EventCtx::set_position_in_set).
+        pub fn position_in_set(&self) -> Option<(usize, usize)> {
+            self.global_state
+                .positions_in_set
+                .get(&self.widget_state.id)
+                .copied()
+        }
+
+        /// The opacity set with [`set_opacity`](EventCtx::set_opacity), 1 by default.
+        pub fn opacity(&self) -> f32 {
+            self.widget_state.opacity
         }
 
         /// Whether this widget gets text focus.
         pub fn accepts_focus(&self) -> bool {
-            self.widget_state.accepts_focus
+            self.widget_state.flags.contains(WidgetFlags::ACCEPTS_FOCUS)
         }
 
         /// Whether this widget gets IME events.
         pub fn accepts_text_input(&self) -> bool {
-            self.widget_state.accepts_text_input
+            self.widget_state
+                .flags
+                .contains(WidgetFlags::ACCEPTS_TEXT_INPUT)
         }
 
         /// The disabled state of a widget.
@@ -354,14 +498,24 @@ impl_context_method!(
         ///
         /// [`set_disabled`]: EventCtx::set_disabled
         pub fn is_disabled(&self) -> bool {
-            self.widget_state.is_disabled
+            self.widget_state.flags.contains(WidgetFlags::IS_DISABLED)
         }
 
         /// Check is widget is stashed.
         ///
         /// **Note:** Stashed widgets are a WIP feature.
         pub fn is_stashed(&self) -> bool {
-            self.widget_state.is_stashed
+            self.widget_state.flags.contains(WidgetFlags::IS_STASHED)
+        }
+
+        /// The text style inherited by this widget.
+        ///
+        /// This combines the styles set with [`set_text_style`] on this widget and
+        /// its ancestors, closest first. Properties which no widget sets are `None`.
+        ///
+        /// [`set_text_style`]: EventCtx::set_text_style
+        pub fn text_style(&self) -> &TextStyle {
+            &self.widget_state.text_style
         }
     }
 );
@@ -377,6 +531,16 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
         trace!("cursor_icon_changed");
         self.global_state.needs_pointer_pass = true;
     }
+
+    /// Notifies Masonry that the value returned by [`Widget::accepts_focus`] has changed.
+    ///
+    /// The focus chain is rebuilt, so that <kbd>Tab</kbd> reaches or skips this widget.
+    pub fn accepts_focus_changed(&mut self) {
+        trace!("accepts_focus_changed");
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::UPDATE_FOCUS_CHAIN);
+    }
 });
 
 // --- MARK: WIDGET_MUT ---
@@ -456,10 +620,12 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
     /// Request a [`paint`](crate::Widget::paint) and an [`accessibility`](crate::Widget::accessibility) pass.
     pub fn request_render(&mut self) {
         trace!("request_render");
-        self.widget_state.request_paint = true;
-        self.widget_state.needs_paint = true;
-        self.widget_state.needs_accessibility = true;
-        self.widget_state.request_accessibility = true;
+        self.widget_state.flags.insert(
+            WidgetFlags::REQUEST_PAINT
+                | WidgetFlags::NEEDS_PAINT
+                | WidgetFlags::NEEDS_ACCESSIBILITY
+                | WidgetFlags::REQUEST_ACCESSIBILITY,
+        );
     }
 
     /// Request a [`paint`](crate::Widget::paint) pass.
@@ -468,8 +634,9 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
     /// Use request_render unless you're sure an accessibility pass is not needed.
     pub fn request_paint_only(&mut self) {
         trace!("request_paint");
-        self.widget_state.request_paint = true;
-        self.widget_state.needs_paint = true;
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::REQUEST_PAINT | WidgetFlags::NEEDS_PAINT);
     }
 
     /// Request an [`accessibility`](crate::Widget::accessibility) pass.
@@ -478,8 +645,9 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
     /// If you want to request both an accessibility pass and a paint pass, use [`request_render`](Self::request_render).
     pub fn request_accessibility_update(&mut self) {
         trace!("request_accessibility_update");
-        self.widget_state.needs_accessibility = true;
-        self.widget_state.request_accessibility = true;
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::NEEDS_ACCESSIBILITY | WidgetFlags::REQUEST_ACCESSIBILITY);
     }
 
     /// Request a layout pass.
@@ -494,8 +662,9 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
     /// [`layout`]: crate::Widget::layout
     pub fn request_layout(&mut self) {
         trace!("request_layout");
-        self.widget_state.request_layout = true;
-        self.widget_state.needs_layout = true;
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::REQUEST_LAYOUT | WidgetFlags::NEEDS_LAYOUT);
     }
 
     // TODO - Document better
@@ -505,15 +674,17 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
     /// [`compose`]: crate::Widget::compose
     pub fn request_compose(&mut self) {
         trace!("request_compose");
-        self.widget_state.needs_compose = true;
-        self.widget_state.request_compose = true;
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::NEEDS_COMPOSE | WidgetFlags::REQUEST_COMPOSE);
     }
 
     /// Request an animation frame.
     pub fn request_anim_frame(&mut self) {
         trace!("request_anim_frame");
-        self.widget_state.request_anim = true;
-        self.widget_state.needs_anim = true;
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::REQUEST_ANIM | WidgetFlags::NEEDS_ANIM);
     }
 
     /// Indicate that your children have changed.
@@ -521,8 +692,9 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
     /// Widgets must call this method after adding a new child.
     pub fn children_changed(&mut self) {
         trace!("children_changed");
-        self.widget_state.children_changed = true;
-        self.widget_state.update_focus_chain = true;
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::CHILDREN_CHANGED | WidgetFlags::UPDATE_FOCUS_CHAIN);
         self.request_layout();
     }
 
@@ -542,6 +714,10 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
             .remove_child(id)
             .expect("remove_child: child not found");
         self.global_state.scenes.remove(&child.id());
+        self.global_state.ime_areas.remove(&child.id());
+        self.global_state.tooltips.remove(&child.id());
+        self.global_state.snap_points.remove(&child.id());
+        self.global_state.positions_in_set.remove(&child.id());
 
         self.children_changed();
     }
@@ -553,8 +729,356 @@ impl_context_method!(MutateCtx<'_>, EventCtx<'_>, UpdateCtx<'_>, {
     ///
     /// [`is_disabled`]: EventCtx::is_disabled
     pub fn set_disabled(&mut self, disabled: bool) {
-        self.widget_state.needs_update_disabled = true;
-        self.widget_state.is_explicitly_disabled = disabled;
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::NEEDS_UPDATE_DISABLED);
+        self.widget_state
+            .flags
+            .set(WidgetFlags::IS_EXPLICITLY_DISABLED, disabled);
+    }
+
+    /// Make this widget and its descendants transparent to the pointer, like
+    /// `pointer-events: none` in CSS.
+    ///
+    /// While transparent, they don't get pointer events nor hovered status: the pointer
+    /// interacts with whatever is under them instead, e.g. the content below a decorative
+    /// overlay. Unlike [`Widget::accepts_pointer_interaction`], this can be changed at any
+    /// time, and applies to the whole subtree.
+    pub fn set_pointer_transparent(&mut self, transparent: bool) {
+        if self.is_pointer_transparent() == transparent {
+            return;
+        }
+        self.widget_state
+            .flags
+            .set(WidgetFlags::IS_POINTER_TRANSPARENT, transparent);
+        // The widget under the pointer may have changed.
+        self.global_state.needs_pointer_pass = true;
+    }
+
+    /// Set whether arrow-key navigation which starts in a descendant of this widget stays
+    /// within its descendants.
+    ///
+    /// See [`EventCtx::focus_in_direction`] for how the arrow keys move focus.
+    pub fn set_focus_scope(&mut self, focus_scope: bool) {
+        self.widget_state
+            .flags
+            .set(WidgetFlags::IS_FOCUS_SCOPE, focus_scope);
+    }
+
+    /// Leave this widget and its descendants out of the accessibility tree, like
+    /// `aria-hidden` in HTML.
+    ///
+    /// This is meant for purely decorative content, such as backgrounds, dividers and
+    /// shadows, which would only add noise for assistive technologies. The widgets are
+    /// still painted and get events. Hidden subtrees shouldn't contain focusable widgets;
+    /// if one of them is focused, the root widget is reported as focused instead.
+    ///
+    /// This has no effect on the root widget.
+    pub fn set_accessibility_hidden(&mut self, hidden: bool) {
+        if self.is_accessibility_hidden() == hidden {
+            return;
+        }
+        self.widget_state
+            .flags
+            .set(WidgetFlags::IS_ACCESSIBILITY_HIDDEN, hidden);
+        // The parent's node lists this widget, so it must be rebuilt too.
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::ACCESSIBILITY_HIDDEN_CHANGED | WidgetFlags::NEEDS_ACCESSIBILITY);
+    }
+
+    /// Report this widget as the item at `index` (starting at 0) of a set of `size` items,
+    /// e.g. a row of a list, or remove it from its set with `None`.
+    ///
+    /// Assistive technologies announce the position, e.g. "item 500 of 10,000". The set
+    /// doesn't need to contain a widget for each item: a virtualized list only creates
+    /// widgets for the items around its viewport, and sets their position in the whole list.
+    ///
+    /// Items which are entirely outside the visible area, i.e. outside the window and the
+    /// clip paths of their ancestors, are left out of the accessibility tree with their
+    /// descendants, so that long lists don't make the tree huge. They are added back when
+    /// they become visible, or when they contain the focused widget.
+    pub fn set_position_in_set(&mut self, position: Option<(usize, usize)>) {
+        if let Some((index, size)) = position {
+            debug_assert!(index < size, "index {index} out of set of size {size}");
+        }
+        let id = self.widget_id();
+        let changed = match position {
+            Some(position) => {
+                self.global_state.positions_in_set.insert(id, position) != Some(position)
+            }
+            None => self.global_state.positions_in_set.remove(&id).is_some(),
+        };
+        if changed {
+            self.request_accessibility_update();
+        }
+    }
+
+    /// Set the text of the tooltip shown when the pointer rests on this widget.
+    ///
+    /// The tooltip appears once the pointer has stayed still over the widget for
+    /// [`TOOLTIP_DELAY`](crate::theme::TOOLTIP_DELAY) milliseconds, and is hidden when the
+    /// pointer moves or leaves. If several hovered widgets have a tooltip, the innermost
+    /// one is shown. The text is also exposed to assistive technologies.
+    pub fn set_tooltip(&mut self, text: impl Into<ArcStr>) {
+        let text = text.into();
+        let id = self.widget_id();
+        if self.global_state.tooltips.get(&id) != Some(&text) {
+            self.global_state.tooltips.insert(id, text);
+            self.tooltip_changed();
+        }
+    }
+
+    /// Remove the tooltip set with [`set_tooltip`](Self::set_tooltip).
+    pub fn clear_tooltip(&mut self) {
+        if self
+            .global_state
+            .tooltips
+            .remove(&self.widget_id())
+            .is_some()
+        {
+            self.tooltip_changed();
+        }
+    }
+
+    fn tooltip_changed(&mut self) {
+        self.request_accessibility_update();
+        let id = self.widget_id();
+        if (self.global_state.tooltip.as_ref())
+            .is_some_and(|tooltip| tooltip.widget_id == id && tooltip.shown)
+        {
+            self.global_state
+                .emit_signal(RenderRootSignal::RequestRedraw);
+        }
+    }
+
+    /// Show `popup` above all other widgets, next to this widget.
+    ///
+    /// The popup is placed relative to this widget's layout rect, and follows this widget
+    /// when it moves. It's closed when the user clicks outside of both the popup and this
+    /// widget, in which case this widget gets [`Update::PopupClosed`](crate::Update::PopupClosed),
+    /// when this widget is removed or stashed, or with [`close_popup`](Self::close_popup).
+    ///
+    /// Returns the id of the popup's root widget.
+    pub fn show_popup(&mut self, popup: impl Widget, placement: PopupPlacement) -> WidgetId {
+        self.add_popup(WidgetPod::new(popup).boxed(), placement, None)
+    }
+
+    fn add_popup(
+        &mut self,
+        popup: WidgetPod<Box<dyn Widget>>,
+        placement: PopupPlacement,
+        position: Option<Point>,
+    ) -> WidgetId {
+        let id = popup.id();
+        trace!("show_popup {id}");
+        self.global_state.popups.push(PopupState {
+            id,
+            anchor: self.widget_state.id,
+            placement,
+            position,
+            anchor_rect: self.widget_state.window_layout_rect(),
+        });
+        self.global_state.new_popups.push(popup);
+        id
+    }
+
+    /// Close a popup shown with [`show_popup`](Self::show_popup).
+    pub fn close_popup(&mut self, id: WidgetId) {
+        trace!("close_popup {id}");
+        self.global_state.close_popup(id);
+    }
+
+    /// Set the opacity this widget and its descendants are painted with, between 0 and 1.
+    ///
+    /// Values outside that range are clamped, and non-finite values are treated as 1.
+    ///
+    /// The subtree is composited as a group, so overlapping descendants don't show
+    /// through each other. Changing the opacity doesn't repaint the widgets, it only
+    /// composites their cached scenes again.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        let opacity = valid_opacity(opacity);
+        if self.widget_state.opacity != opacity {
+            trace!("set_opacity {opacity}");
+            self.widget_state.opacity = opacity;
+            self.widget_state.flags.insert(WidgetFlags::NEEDS_PAINT);
+        }
+    }
+
+    /// Set whether this widget receives [`PlatformEvent`]s, in
+    /// [`Widget::on_platform_event`].
+    ///
+    /// Platform events are only sent to the widgets which asked for them, so that rare
+    /// events don't visit the whole tree. Widgets usually call this when they receive
+    /// [`Update::WidgetAdded`](crate::Update::WidgetAdded).
+    ///
+    /// [`PlatformEvent`]: crate::PlatformEvent
+    pub fn set_receives_platform_events(&mut self, receives: bool) {
+        let id = self.widget_state.id;
+        let subscribers = &mut self.global_state.platform_event_subscribers;
+        if receives && !subscribers.contains(&id) {
+            subscribers.push(id);
+        } else if !receives {
+            subscribers.retain(|subscriber| *subscriber != id);
+        }
+    }
+
+    /// Set whether this widget needs frames to be presented with as little latency as
+    /// possible, e.g. while the user draws a stroke with a pen.
+    ///
+    /// Low latency presentation queues fewer frames, which may cause stutter when
+    /// frames take long to paint, so widgets should only ask for it while needed.
+    /// See also [`PointerPredictor`](crate::gesture::PointerPredictor).
+    pub fn set_low_latency(&mut self, low_latency: bool) {
+        let id = self.widget_state.id;
+        let widgets = &mut self.global_state.low_latency_widgets;
+        let was_low_latency = !widgets.is_empty();
+        if low_latency && !widgets.contains(&id) {
+            widgets.push(id);
+        } else if !low_latency {
+            widgets.retain(|widget| *widget != id);
+        }
+        let is_low_latency = !widgets.is_empty();
+        if was_low_latency != is_low_latency {
+            self.global_state
+                .emit_signal(RenderRootSignal::SetLowLatency(is_low_latency));
+        }
+    }
+
+    /// Set the default text style of this widget and its descendants.
+    ///
+    /// Properties left unset in `style` are inherited from this widget's ancestors.
+    /// Text widgets use the resolved style for any property they don't set themselves,
+    /// and receive [`Update::TextStyleChanged`] when it changes.
+    ///
+    /// [`Update::TextStyleChanged`]: crate::Update::TextStyleChanged
+    pub fn set_text_style(&mut self, style: TextStyle) {
+        self.widget_state.explicit_text_style = Some(Arc::new(style));
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::NEEDS_UPDATE_TEXT_STYLE);
+    }
+
+    /// Override env values for this widget and its descendants.
+    ///
+    /// Keys left unset in `env` are inherited from this widget's ancestors. Widgets
+    /// whose env changes receive [`Update::EnvChanged`].
+    ///
+    /// [`Update::EnvChanged`]: crate::Update::EnvChanged
+    pub fn set_env(&mut self, env: Env) {
+        self.widget_state.explicit_env = Some(Arc::new(env));
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::NEEDS_UPDATE_ENV);
+    }
+
+    /// Only let the user resize the window by multiples of `increments`.
+    ///
+    /// This is useful for content made of cells, like a terminal or a pixel art canvas.
+    /// Depending on the platform, the size of the window can still be any size, e.g. when
+    /// it is maximized. Pass `None` to resize the window freely again.
+    pub fn set_window_resize_increments(&mut self, increments: Option<LogicalSize<f64>>) {
+        self.global_state
+            .emit_signal(RenderRootSignal::SetResizeIncrements(increments));
+    }
+
+    /// Lock the ratio of the width to the height of the window's content area.
+    ///
+    /// Whenever the window is resized, its height is adjusted to match its width.
+    /// Pass `None` to resize the window freely again.
+    ///
+    /// This has no effect if the size of the window is determined by its content
+    /// (see [`WindowSizePolicy`](crate::WindowSizePolicy)).
+    pub fn set_window_aspect_ratio(&mut self, ratio: Option<f64>) {
+        if let Some(ratio) = ratio {
+            assert!(ratio > 0., "aspect ratio must be positive!");
+        }
+        self.global_state.window_aspect_ratio = ratio;
+        // The ratio is applied in the layout pass.
+        self.request_layout();
+    }
+
+    /// Resize the window to the preferred size of the root widget.
+    ///
+    /// See [`RenderRoot::size_to_content`](crate::RenderRoot::size_to_content).
+    pub fn request_size_to_content(&mut self) {
+        self.global_state.size_to_content = true;
+        // The window is resized in the layout pass.
+        self.request_layout();
+    }
+
+    /// Move the window relative to a monitor or to the pointer, and possibly resize it.
+    ///
+    /// See [`monitors`](Self::monitors) for the monitors the window can be placed on.
+    pub fn place_window(&mut self, placement: WindowPlacement) {
+        self.global_state
+            .emit_signal(RenderRootSignal::PlaceWindow(placement));
+    }
+
+    /// Set the region of the window which receives pointer events, in window coordinates.
+    ///
+    /// This is meant for windows with a custom shape, e.g. splash screens or widgets on the
+    /// desktop, whose background is transparent: the window must be created with
+    /// [`with_transparent`](winit::window::WindowAttributes::with_transparent), and with a
+    /// transparent background color. Outside of the shape, widgets aren't hovered and don't
+    /// get pointer events, and where the platform allows it, clicks go through the window
+    /// to whatever is behind it. Pass `None` to make the whole window receive pointer events.
+    pub fn set_window_shape(&mut self, shape: Option<BezPath>) {
+        self.global_state.window_shape = shape;
+        self.global_state.needs_pointer_pass = true;
+    }
+
+    /// Close the window of this widget. Closing the main window exits the app.
+    ///
+    /// Unlike a close requested by the user, this doesn't call
+    /// [`AppDriver::on_close_requested`](crate::AppDriver::on_close_requested).
+    pub fn close_window(&mut self) {
+        self.global_state.emit_signal(RenderRootSignal::CloseWindow);
+    }
+
+    /// Open a secondary window showing `root_widget`, e.g. for a tool palette or an inspector.
+    ///
+    /// The window has its own widget tree. Its actions are sent to the [`AppDriver`](crate::AppDriver)
+    /// like those of the main window, and [`DriverCtx::window`](crate::DriverCtx::window)
+    /// tells them apart.
+    pub fn open_window(
+        &mut self,
+        attributes: WindowAttributes,
+        root_widget: impl Widget,
+    ) -> AppWindowId {
+        self.global_state
+            .open_window(attributes, Box::new(root_widget))
+    }
+
+    /// Close a secondary window opened with [`open_window`](Self::open_window).
+    pub fn close_app_window(&mut self, id: AppWindowId) {
+        self.global_state
+            .emit_signal(RenderRootSignal::CloseAppWindow(id));
+    }
+
+    /// Copy `content` to the system clipboard.
+    pub fn set_clipboard(&mut self, content: ClipboardContent) {
+        self.global_state
+            .emit_signal(RenderRootSignal::SetClipboard(content));
+    }
+
+    /// Copy `text` to the system clipboard.
+    ///
+    /// This is a shorthand for [`set_clipboard`](Self::set_clipboard) with plain text.
+    /// To read the text of the clipboard, use [`request_paste`](Self::request_paste):
+    /// since reading it may block, there is no method which returns it directly.
+    pub fn copy_to_clipboard(&mut self, text: &str) {
+        self.set_clipboard(ClipboardContent::text(text));
+    }
+
+    /// Ask for the text content of the system clipboard.
+    ///
+    /// Reading the clipboard may block, so the text is delivered later: it is sent to the
+    /// focused widget as a [`TextEvent::Paste`], which bubbles up to its ancestors like
+    /// other text events.
+    pub fn request_paste(&mut self) {
+        self.global_state
+            .emit_signal(RenderRootSignal::RequestPaste);
     }
 });
 
@@ -599,26 +1123,58 @@ impl_context_method!(
 
         /// Submit an [`Action`].
         ///
+        /// See [`RenderRootSignal::Action`] for the order in which actions are emitted.
+        ///
         /// Note: Actions are still a WIP feature.
         pub fn submit_action(&mut self, action: Action) {
             trace!("submit_action");
             self.global_state
-                .emit_signal(RenderRootSignal::Action(action, self.widget_state.id));
+                .submit_action(action, self.widget_state.id);
+        }
+
+        /// Request a timer event after `delay`.
+        ///
+        /// When the delay has passed, this widget receives [`Update::Timer`] with the
+        /// returned token, which can be used to associate the request with the event.
+        /// The timer fires once, unless it's [cancelled](Self::cancel_timer) first.
+        ///
+        /// [`Update::Timer`]: crate::Update::Timer
+        pub fn request_timer(&mut self, delay: Duration) -> TimerToken {
+            trace!("request_timer");
+            self.global_state
+                .add_timer(self.widget_state.id, delay, None)
         }
 
-        /// Request a timer event.
+        /// Request a timer event every `interval`, until the timer is
+        /// [cancelled](Self::cancel_timer), e.g. to blink a text cursor.
         ///
-        /// The return value is a token, which can be used to associate the
-        /// request with the event.
-        pub fn request_timer(&mut self, _deadline: Duration) -> TimerToken {
-            todo!("request_timer");
+        /// If the app falls behind, missed repetitions are skipped rather than
+        /// delivered in a burst. Intervals shorter than a millisecond, including zero,
+        /// are rounded up to a millisecond.
+        pub fn request_repeating_timer(&mut self, interval: Duration) -> TimerToken {
+            trace!("request_repeating_timer");
+            let interval = interval.max(MIN_TIMER_INTERVAL);
+            self.global_state
+                .add_timer(self.widget_state.id, interval, Some(interval))
+        }
+
+        /// Cancel a timer requested with [`request_timer`](Self::request_timer) or
+        /// [`request_repeating_timer`](Self::request_repeating_timer).
+        ///
+        /// Does nothing if the timer has already fired or been cancelled.
+        pub fn cancel_timer(&mut self, token: TimerToken) {
+            self.global_state
+                .timers
+                .retain(|timer| timer.token != token);
         }
 
         /// Mark child widget as stashed.
         ///
         /// If `stashed` is true, the child will not be painted or listed in the accessibility tree.
         ///
-        /// This will *not* trigger a layout pass.
+        /// This will *not* trigger a layout pass. The stashed state of the child is
+        /// updated before the next one, even when this is called during an event, so the
+        /// child can be laid out right after it's unstashed.
         ///
         /// **Note:** Stashed widgets are a WIP feature.
         pub fn set_stashed(&mut self, child: &mut WidgetPod<impl Widget>, stashed: bool) {
@@ -626,17 +1182,76 @@ impl_context_method!(
             // Stashing is generally a property derived from the parent widget's state
             // (rather than set imperatively), so it is likely to be set as part of passes.
             // Therefore, we avoid re-running the update_stashed_pass in most cases.
-            if child_state.is_explicitly_stashed != stashed {
-                child_state.needs_update_stashed = true;
-                child_state.is_explicitly_stashed = stashed;
+            if child_state
+                .flags
+                .contains(WidgetFlags::IS_EXPLICITLY_STASHED)
+                != stashed
+            {
+                child_state.flags.insert(WidgetFlags::NEEDS_UPDATE_STASHED);
+                child_state
+                    .flags
+                    .set(WidgetFlags::IS_EXPLICITLY_STASHED, stashed);
+                // The child's flags may only be merged up after the layout pass,
+                // so we mark this widget too, to update the child before its layout.
+                self.widget_state
+                    .flags
+                    .insert(WidgetFlags::NEEDS_UPDATE_STASHED);
+            }
+        }
+
+        /// Disable a child widget and its descendants, independently of the disabled state
+        /// set with [`set_disabled`](EventCtx::set_disabled).
+        ///
+        /// This is for containers which block their content while in some state, e.g. a modal
+        /// dialog, without overwriting whether the app disabled that content.
+        pub fn set_child_disabled(&mut self, child: &mut WidgetPod<impl Widget>, disabled: bool) {
+            let child_state = self.get_child_state_mut(child);
+            if child_state
+                .flags
+                .contains(WidgetFlags::IS_DISABLED_BY_PARENT)
+                != disabled
+            {
+                child_state
+                    .flags
+                    .set(WidgetFlags::IS_DISABLED_BY_PARENT, disabled);
+                child_state.flags.insert(WidgetFlags::NEEDS_UPDATE_DISABLED);
+                self.widget_state
+                    .flags
+                    .insert(WidgetFlags::NEEDS_UPDATE_DISABLED);
+            }
+        }
+
+        /// Leave a child widget and its descendants out of the accessibility tree,
+        /// independently of [`set_accessibility_hidden`](EventCtx::set_accessibility_hidden).
+        ///
+        /// This is for containers which hide their content from assistive technologies while
+        /// in some state, e.g. the content behind a modal dialog.
+        pub fn set_child_accessibility_hidden(
+            &mut self,
+            child: &mut WidgetPod<impl Widget>,
+            hidden: bool,
+        ) {
+            let child_state = self.get_child_state_mut(child);
+            if child_state
+                .flags
+                .contains(WidgetFlags::IS_ACCESSIBILITY_HIDDEN_BY_PARENT)
+                != hidden
+            {
+                child_state
+                    .flags
+                    .set(WidgetFlags::IS_ACCESSIBILITY_HIDDEN_BY_PARENT, hidden);
+                child_state.flags.insert(
+                    WidgetFlags::ACCESSIBILITY_HIDDEN_CHANGED | WidgetFlags::NEEDS_ACCESSIBILITY,
+                );
+                // This widget's node lists the child, so it must be rebuilt.
+                self.widget_state
+                    .flags
+                    .insert(WidgetFlags::REQUEST_ACCESSIBILITY | WidgetFlags::NEEDS_ACCESSIBILITY);
             }
         }
     }
 );
 
-// FIXME - Remove
-pub struct TimerToken;
-
 impl EventCtx<'_> {
     // TODO - clearly document all semantics of pointer capture when they've been decided on
     // TODO - Figure out cases where widget should be notified of pointer capture
@@ -657,6 +1272,10 @@ impl EventCtx<'_> {
     /// captured the pointer and another widget captures it, the first widget loses the pointer
     /// capture.
     ///
+    /// Each finger on a touchscreen other than the [primary](crate::PointerState::is_primary)
+    /// one is captured independently: this captures the pointer of the event being handled,
+    /// and the other fingers can still interact with other widgets.
+    ///
     /// # Releasing the pointer
     ///
     /// Any widget can [`release`] the pointer during any event. The pointer is automatically
@@ -675,14 +1294,191 @@ impl EventCtx<'_> {
             self.widget_id(),
         );
         // TODO: plumb pointer capture through to platform (through winit)
-        self.global_state.pointer_capture_target = Some(self.widget_state.id);
+        match self.global_state.secondary_pointer {
+            Some(pointer_id) => {
+                let id = self.widget_state.id;
+                self.global_state.pointer_captures.insert(pointer_id, id);
+            }
+            None => self.global_state.pointer_capture_target = Some(self.widget_state.id),
+        }
     }
 
     /// Release the pointer previously captured through [`capture_pointer`].
     ///
     /// [`capture_pointer`]: EventCtx::capture_pointer
     pub fn release_pointer(&mut self) {
+        match self.global_state.secondary_pointer {
+            Some(pointer_id) => {
+                self.global_state.pointer_captures.remove(&pointer_id);
+            }
+            None => self.global_state.pointer_capture_target = None,
+        }
+    }
+
+    /// Whether the pointer of the current event is captured by a widget other than this one.
+    ///
+    /// When a [`PointerDown`] event bubbles up from a descendant which captured the pointer,
+    /// e.g. a button, containers which react to presses can check this to leave the press to it.
+    ///
+    /// [`PointerDown`]: crate::PointerEvent::PointerDown
+    pub fn is_pointer_captured_by_other(&self) -> bool {
+        let target = match self.global_state.secondary_pointer {
+            Some(pointer_id) => self.global_state.pointer_captures.get(&pointer_id).copied(),
+            None => self.global_state.pointer_capture_target,
+        };
+        target.is_some_and(|id| id != self.widget_state.id)
+    }
+
+    /// Lock the pointer in the current widget, e.g. to orbit the camera of a 3D viewport.
+    ///
+    /// While the pointer is locked, the cursor is hidden and stays in place, and the motion
+    /// of the mouse is sent to this widget as [`LockedMove`] events. All other pointer events
+    /// are sent to this widget too, as if it had [captured](Self::capture_pointer) the pointer.
+    /// If the platform can't lock the cursor, it is confined to the window instead.
+    ///
+    /// # Unlocking the pointer
+    ///
+    /// The widget holding the lock can [`unlock`] it during any event. So that users are never
+    /// stuck, pressing Escape always unlocks the pointer, and so does the window losing focus.
+    /// The widget then receives a [`PointerLeave`] event, and the Escape key isn't sent to
+    /// any widget.
+    ///
+    /// [`LockedMove`]: crate::PointerEvent::LockedMove
+    /// [`PointerLeave`]: crate::PointerEvent::PointerLeave
+    /// [`unlock`]: Self::unlock_pointer
+    pub fn lock_pointer(&mut self) {
+        trace!("lock_pointer");
+        if self.global_state.pointer_lock_target.is_none() {
+            self.global_state
+                .emit_signal(RenderRootSignal::SetPointerLock(true));
+        }
+        self.global_state.pointer_lock_target = Some(self.widget_state.id);
+    }
+
+    /// Unlock the pointer previously locked through [`lock_pointer`].
+    ///
+    /// This does nothing if the pointer is locked by another widget.
+    ///
+    /// [`lock_pointer`]: EventCtx::lock_pointer
+    pub fn unlock_pointer(&mut self) {
+        if self.has_pointer_lock() {
+            trace!("unlock_pointer");
+            self.global_state.release_pointer_lock();
+        }
+    }
+
+    /// Show `menu` over the window at `position`, in window coordinates, e.g. at the pointer
+    /// when this widget is right-clicked.
+    ///
+    /// The menu is a [popup](Self::show_popup) moved to fit in the window if needed, which
+    /// takes the focus while it's open: the user can choose an item with the pointer, or with
+    /// the arrow keys and Enter. This widget then submits
+    /// [`Action::MenuItemSelected`](crate::Action::MenuItemSelected) with the
+    /// index of the item. Clicking anywhere outside of the menu, including on this widget,
+    /// or pressing Escape, closes it without an action. Showing a menu replaces the one
+    /// already open, if any.
+    ///
+    /// This releases the pointer capture. Returns the id of the popup.
+    pub fn show_context_menu(&mut self, menu: Menu, position: Point) -> WidgetId {
+        trace!("show_context_menu");
+        let open_menus: Vec<_> = (self.global_state.popups.iter())
+            .filter(|popup| popup.position.is_some())
+            .map(|popup| popup.id)
+            .collect();
+        for id in open_menus {
+            self.global_state.close_popup(id);
+        }
         self.global_state.pointer_capture_target = None;
+        self.global_state.needs_pointer_pass = true;
+        let menu = ContextMenu::new(menu, self.widget_state.id, self.global_state.focused_widget);
+        self.add_popup(
+            WidgetPod::new(menu).boxed(),
+            PopupPlacement::default(),
+            Some(position),
+        )
+    }
+
+    /// Start an in-app drag-and-drop operation carrying `payload`, e.g. when the pointer
+    /// moves far enough from where it was pressed on a list item.
+    ///
+    /// While the pointer is pressed, `drag_image` is painted under it, in the local coordinates
+    /// of this widget when the drag started, and the widgets under the pointer receive
+    /// [`DragOver`] events instead of [`PointerMove`]. When the pointer is released, the widget
+    /// which [accepted](Self::set_drop_accepted) the drop receives [`DragDrop`].
+    /// The drag is cancelled when the pointer leaves the window or Escape is pressed.
+    ///
+    /// A drag started while handling a key press, e.g. Space on a focused list item, is moved
+    /// with the keyboard instead: the arrow keys move it to the next or previous widget of the
+    /// focus chain which accepts the drop, which receives [`DragOver`], and Enter or Space drops
+    /// it. The steps of the drag are [announced](Self::announce) to assistive technologies,
+    /// with the text of the app's [`LocaleFormatter::drag_announcement`].
+    ///
+    /// This releases the pointer capture. A drag started with the pointer takes it over, so
+    /// this widget receives a [`PointerUp`] right after the event, as if the button had been
+    /// released: widgets which track presses end them there. It is a logic error to start a
+    /// drag outside of a pointer or keyboard event.
+    ///
+    /// [`DragOver`]: crate::PointerEvent::DragOver
+    /// [`PointerMove`]: crate::PointerEvent::PointerMove
+    /// [`DragDrop`]: crate::PointerEvent::DragDrop
+    /// [`PointerUp`]: crate::PointerEvent::PointerUp
+    pub fn start_drag(&mut self, payload: Box<dyn Any>, drag_image: Scene) {
+        trace!("start_drag");
+        self.global_state.pointer_capture_target = None;
+        self.global_state.drag = Some(DragState {
+            source: self.widget_state.id,
+            payload: Some(payload),
+            image: drag_image,
+            source_origin: self.widget_state.window_origin(),
+            anchor: None,
+            drop_target: None,
+            keyboard_over: None,
+        });
+        self.global_state
+            .emit_signal(RenderRootSignal::RequestRedraw);
+    }
+
+    /// The widget which started the drag in progress, if any.
+    pub fn drag_source(&self) -> Option<WidgetId> {
+        self.global_state.drag.as_ref().map(|drag| drag.source)
+    }
+
+    /// The payload of the drag in progress, if any.
+    ///
+    /// Drop targets can [downcast](Any::downcast_ref) it to check whether they accept it.
+    pub fn drag_payload(&self) -> Option<&dyn Any> {
+        self.global_state.drag.as_ref()?.payload.as_deref()
+    }
+
+    /// Take the payload of the drag in progress, usually when handling [`DragDrop`].
+    ///
+    /// [`DragDrop`]: crate::PointerEvent::DragDrop
+    pub fn take_drag_payload(&mut self) -> Option<Box<dyn Any>> {
+        self.global_state.drag.as_mut()?.payload.take()
+    }
+
+    /// Set whether this widget accepts the payload of the drag in progress, when handling
+    /// [`DragOver`].
+    ///
+    /// The last widget to accept the drop during a `DragOver` event is the drop target,
+    /// so a widget accepting the drop usually [sets the event as handled](Self::set_handled).
+    /// Rejecting the drop also rejects it for the descendants of this widget which accepted it.
+    ///
+    /// [`DragOver`]: crate::PointerEvent::DragOver
+    pub fn set_drop_accepted(&mut self, accepted: bool) {
+        let Some(drag) = &mut self.global_state.drag else {
+            return;
+        };
+        drag.drop_target = accepted.then_some(self.widget_state.id);
+    }
+
+    /// Have assistive technologies announce `text`, e.g. to describe where a dragged item
+    /// would be dropped.
+    ///
+    /// The text is shown in a polite live region, so it's announced once the screen reader
+    /// is done speaking, and only if it's different from the previous announcement.
+    pub fn announce(&mut self, text: impl Into<String>) {
+        self.global_state.announce(text);
     }
 
     /// Send a signal to parent widgets to scroll this widget into view.
@@ -721,6 +1517,34 @@ impl EventCtx<'_> {
         self.target
     }
 
+    /// The indices of the children of `container` whose layout rects intersect `rect`,
+    /// in window coordinates.
+    ///
+    /// The indices are positions in the [`children_ids`](Widget::children_ids) of the
+    /// container. Stashed children are skipped.
+    pub fn items_in_rect(&self, container: &WidgetPod<impl Widget>, rect: Rect) -> Vec<usize> {
+        let state = self
+            .widget_state_children
+            .get_child(container.id())
+            .expect("items_in_rect: child not found");
+        let widget = self
+            .widget_children
+            .get_child(container.id())
+            .expect("items_in_rect: child not found");
+        let items = widget.item.children_ids();
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                state.children.get_child(**item).is_some_and(|item| {
+                    !item.item.flags.contains(WidgetFlags::IS_STASHED)
+                        && item.item.window_layout_rect().overlaps(rect)
+                })
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
     /// Request keyboard focus.
     ///
     /// Because only one widget can be focused at a time, multiple focus requests
@@ -746,6 +1570,34 @@ impl EventCtx<'_> {
         self.global_state.next_focused_widget = Some(target);
     }
 
+    /// Move the focus to the next widget of the focus chain, like <kbd>Tab</kbd> does.
+    ///
+    /// Disabled and stashed widgets are skipped, and the focus wraps around after the
+    /// last widget of the window.
+    pub fn focus_next(&mut self) {
+        trace!("focus_next");
+        self.global_state.next_focused_widget = self.global_state.widget_from_focus_chain(true);
+    }
+
+    /// Move the focus to the previous widget of the focus chain, like
+    /// <kbd>Shift</kbd>+<kbd>Tab</kbd> does.
+    ///
+    /// See [`focus_next`](Self::focus_next) for details.
+    pub fn focus_prev(&mut self) {
+        trace!("focus_prev");
+        self.global_state.next_focused_widget = self.global_state.widget_from_focus_chain(false);
+    }
+
+    /// Move the focus to the focusable widget nearest to the focused one in `direction`,
+    /// like the arrow keys do when the focused widget doesn't handle them.
+    ///
+    /// If the focused widget is in a [focus scope](crate::widget::FocusGroup::with_focus_scope),
+    /// focus stays within it. Nothing happens if there is no widget in that direction.
+    pub fn focus_in_direction(&mut self, direction: FocusDirection) {
+        trace!("focus_in_direction direction={:?}", direction);
+        self.global_state.next_focus_direction = Some(direction);
+    }
+
     /// Give up focus.
     ///
     /// This should only be called by a widget that currently has focus.
@@ -786,13 +1638,37 @@ impl RegisterCtx<'_> {
         self.widget_children.insert_child(id, Box::new(widget));
         self.widget_state_children.insert_child(id, state);
     }
+
+    /// Register a child widget, which is [stashed](EventCtx::set_stashed) from the start
+    /// if `stashed` is true.
+    ///
+    /// Children can't be stashed before they are registered, so this is how containers
+    /// hide children added while the container is in a state which hides them.
+    /// This has no effect on children which are already registered.
+    pub fn register_child_stashed(&mut self, child: &mut WidgetPod<impl Widget>, stashed: bool) {
+        let id = child.id();
+        if !child.incomplete() {
+            return;
+        }
+        self.register_child(child);
+        if stashed {
+            let Some(state) = self.widget_state_children.get_child_mut(id) else {
+                return;
+            };
+            state.item.flags.insert(WidgetFlags::IS_EXPLICITLY_STASHED);
+        }
+    }
 }
 
 // --- MARK: UPDATE LAYOUT ---
 impl LayoutCtx<'_> {
     #[track_caller]
     fn assert_layout_done(&self, child: &WidgetPod<impl Widget>, method_name: &str) {
-        if self.get_child_state(child).needs_layout {
+        if self
+            .get_child_state(child)
+            .flags
+            .contains(WidgetFlags::NEEDS_LAYOUT)
+        {
             debug_panic!(
                 "Error in {}: trying to call '{}' with child '{}' {} before computing its layout",
                 self.widget_id(),
@@ -805,7 +1681,11 @@ impl LayoutCtx<'_> {
 
     #[track_caller]
     fn assert_placed(&self, child: &WidgetPod<impl Widget>, method_name: &str) {
-        if self.get_child_state(child).is_expecting_place_child_call {
+        if self
+            .get_child_state(child)
+            .flags
+            .contains(WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL)
+        {
             debug_panic!(
                 "Error in {}: trying to call '{}' with child '{}' {} before placing it",
                 self.widget_id(),
@@ -885,14 +1765,33 @@ impl LayoutCtx<'_> {
         self.widget_state.baseline_offset = baseline;
     }
 
+    /// Report the positions in this widget which a scrolling ancestor can align its
+    /// viewport to, e.g. the start of each item of a list.
+    ///
+    /// The positions are in this widget's coordinate space. They are cleared before each
+    /// call to [`Widget::layout`], so they must be reported on each layout.
+    ///
+    /// See [`SnapPoints::Content`](crate::widget::SnapPoints::Content).
+    pub fn set_snap_points(&mut self, points: impl IntoIterator<Item = Point>) {
+        let points: Vec<_> = points.into_iter().collect();
+        let id = self.widget_id();
+        if points.is_empty() {
+            self.global_state.snap_points.remove(&id);
+        } else {
+            self.global_state.snap_points.insert(id, points);
+        }
+    }
+
     /// Returns whether this widget needs to call [`WidgetPod::layout`]
     pub fn needs_layout(&self) -> bool {
-        self.widget_state.needs_layout
+        self.widget_state.flags.contains(WidgetFlags::NEEDS_LAYOUT)
     }
 
     /// Returns whether a child of this widget needs to call [`WidgetPod::layout`]
     pub fn child_needs_layout(&self, child: &WidgetPod<impl Widget>) -> bool {
-        self.get_child_state(child).needs_layout
+        self.get_child_state(child)
+            .flags
+            .contains(WidgetFlags::NEEDS_LAYOUT)
     }
 
     /// The distance from the bottom of the given widget to the baseline.
@@ -907,6 +1806,22 @@ impl LayoutCtx<'_> {
         self.get_child_state(child).baseline_offset
     }
 
+    /// The positions the given child reported with [`set_snap_points`](Self::set_snap_points),
+    /// in the child's coordinate space.
+    ///
+    /// ## Panics
+    ///
+    /// This method will panic if [`WidgetPod::layout`] has not been called yet for
+    /// the child.
+    #[track_caller]
+    pub fn child_snap_points(&self, child: &WidgetPod<impl Widget>) -> &[Point] {
+        self.assert_layout_done(child, "child_snap_points");
+        self.global_state
+            .snap_points
+            .get(&child.id())
+            .map_or(&[], Vec::as_slice)
+    }
+
     /// Get the given child's layout rect.
     ///
     /// ## Panics
@@ -950,7 +1865,9 @@ impl LayoutCtx<'_> {
     /// This may be removed in the future. Currently it's useful for
     /// stashed children and children whose layout is cached.
     pub fn skip_layout(&mut self, child: &mut WidgetPod<impl Widget>) {
-        self.get_child_state_mut(child).request_layout = false;
+        self.get_child_state_mut(child)
+            .flags
+            .remove(WidgetFlags::REQUEST_LAYOUT);
     }
 
     /// Gives the widget a clip path.
@@ -958,7 +1875,12 @@ impl LayoutCtx<'_> {
     /// A widget's clip path will have two effects:
     /// - It serves as a mask for painting operations of the widget's children (*not* the widget itself).
     /// - Pointer events must be inside that path to reach the widget's children.
-    pub fn set_clip_path(&mut self, path: Rect) {
+    ///
+    /// The path can be a [`Rect`], a [`RoundedRect`](crate::kurbo::RoundedRect) or any
+    /// [`BezPath`](crate::kurbo::BezPath). Children are only culled and hidden from
+    /// assistive technologies when they are outside of the path's bounding box.
+    pub fn set_clip_path(&mut self, path: impl Into<ClipPath>) {
+        let path = path.into();
         // We intentionally always log this because clip paths are:
         // 1) Relatively rare in the tree
         // 2) An easy potential source of items not being visible when expected
@@ -966,9 +1888,11 @@ impl LayoutCtx<'_> {
         self.widget_state.clip_path = Some(path);
         // TODO - Updating the clip path may have
         // other knock-on effects we'd need to document.
-        self.widget_state.request_accessibility = true;
-        self.widget_state.needs_accessibility = true;
-        self.widget_state.needs_paint = true;
+        self.widget_state.flags.insert(
+            WidgetFlags::REQUEST_ACCESSIBILITY
+                | WidgetFlags::NEEDS_ACCESSIBILITY
+                | WidgetFlags::NEEDS_PAINT,
+        );
     }
 
     /// Remove the widget's clip path.
@@ -979,9 +1903,11 @@ impl LayoutCtx<'_> {
         self.widget_state.clip_path = None;
         // TODO - Updating the clip path may have
         // other knock-on effects we'd need to document.
-        self.widget_state.request_accessibility = true;
-        self.widget_state.needs_accessibility = true;
-        self.widget_state.needs_paint = true;
+        self.widget_state.flags.insert(
+            WidgetFlags::REQUEST_ACCESSIBILITY
+                | WidgetFlags::NEEDS_ACCESSIBILITY
+                | WidgetFlags::NEEDS_PAINT,
+        );
     }
 
     /// Set the position of a child widget, in the parent's coordinate space.
@@ -996,6 +1922,22 @@ impl LayoutCtx<'_> {
     /// the child.
     #[track_caller]
     pub fn place_child<W: Widget>(&mut self, child: &mut WidgetPod<W>, origin: Point) {
+        self.place_child_with_z(child, origin, 0);
+    }
+
+    /// Like [`place_child`](Self::place_child), but also set the child's z-index.
+    ///
+    /// Children are painted by increasing z-index, so a child with a higher z-index is
+    /// painted above its overlapping siblings, and receives the pointer events on their
+    /// intersection. Children with the same z-index are painted in the order of
+    /// [`Widget::children_ids`]. Children placed with `place_child` have a z-index of 0.
+    #[track_caller]
+    pub fn place_child_with_z<W: Widget>(
+        &mut self,
+        child: &mut WidgetPod<W>,
+        origin: Point,
+        z_index: i32,
+    ) {
         self.assert_layout_done(child, "place_child");
         if origin.x.is_nan()
             || origin.x.is_infinite()
@@ -1012,21 +1954,31 @@ impl LayoutCtx<'_> {
         }
         if origin != self.get_child_state_mut(child).origin {
             self.get_child_state_mut(child).origin = origin;
-            self.get_child_state_mut(child).translation_changed = true;
+            self.get_child_state_mut(child)
+                .flags
+                .insert(WidgetFlags::TRANSLATION_CHANGED);
         }
         self.get_child_state_mut(child)
-            .is_expecting_place_child_call = false;
+            .flags
+            .remove(WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL);
 
         self.widget_state.local_paint_rect = self
             .widget_state
             .local_paint_rect
             .union(self.get_child_state(child).paint_rect());
+
+        let child_state = self.get_child_state_mut(child);
+        if child_state.z_index != z_index {
+            child_state.z_index = z_index;
+            // The cached scenes are composited in the new order.
+            self.widget_state.flags.insert(WidgetFlags::NEEDS_PAINT);
+        }
     }
 }
 
 impl ComposeCtx<'_> {
     pub fn needs_compose(&self) -> bool {
-        self.widget_state.needs_compose
+        self.widget_state.flags.contains(WidgetFlags::NEEDS_COMPOSE)
     }
 
     /// Set a translation for the child widget.
@@ -1053,7 +2005,94 @@ impl ComposeCtx<'_> {
         let child = self.get_child_state_mut(child);
         if translation != child.translation {
             child.translation = translation;
-            child.translation_changed = true;
+            child.flags.insert(WidgetFlags::TRANSLATION_CHANGED);
+        }
+    }
+
+    /// Set the scale the child widget and its descendants are presented with, around
+    /// the child's origin.
+    ///
+    /// This is a shorthand for [`set_child_transform`](Self::set_child_transform) with
+    /// [`Affine::scale`], and replaces any transform set before.
+    pub fn set_child_scale<W: Widget>(&mut self, child: &mut WidgetPod<W>, scale: f64) {
+        if !(scale.is_finite() && scale > 0.) {
+            debug_panic!(
+                "Error in {}: trying to call 'set_child_scale' with child '{}' {} with invalid scale {}",
+                self.widget_id(),
+                self.get_child(child).short_type_name(),
+                child.id(),
+                scale,
+            );
+            return;
+        }
+        self.set_child_transform(child, Affine::scale(scale));
+    }
+
+    /// Set the transform the child widget and its descendants are presented with, relative
+    /// to the child's origin.
+    ///
+    /// This supports scaling, rotation and skewing, e.g. for zoomable canvases or rotation
+    /// animations. The child is laid out untransformed, and painted, hit-tested and exposed
+    /// to accessibility with the transform applied. The positions of the pointer events it
+    /// receives are mapped accordingly, so it can compute local positions from its
+    /// [`window_origin`](Self::window_origin) as usual.
+    pub fn set_child_transform<W: Widget>(&mut self, child: &mut WidgetPod<W>, transform: Affine) {
+        if !(transform.is_finite() && transform.determinant() != 0.) {
+            debug_panic!(
+                "Error in {}: trying to call 'set_child_transform' with child '{}' {} with non-invertible transform {:?}",
+                self.widget_id(),
+                self.get_child(child).short_type_name(),
+                child.id(),
+                transform,
+            );
+            return;
+        }
+        let child = self.get_child_state_mut(child);
+        if transform != child.transform {
+            child.transform = transform;
+            child.flags.insert(WidgetFlags::TRANSLATION_CHANGED);
+        }
+    }
+
+    /// Set the opacity the child widget and its descendants are painted with, between 0 and 1.
+    ///
+    /// This is the same as the child calling [`EventCtx::set_opacity`] on itself, and lets
+    /// containers fade their children in and out. The child isn't repainted, its cached
+    /// scene is composited again through an alpha layer.
+    pub fn set_child_opacity<W: Widget>(&mut self, child: &mut WidgetPod<W>, opacity: f32) {
+        let opacity = valid_opacity(opacity);
+        let child = self.get_child_state_mut(child);
+        if opacity != child.opacity {
+            child.opacity = opacity;
+            // The compose pass repaints this widget, which merges up to the root.
+            child.flags.insert(WidgetFlags::NEEDS_PAINT);
+        }
+    }
+
+    /// Set the offset of the presented position of the child widget from its layout position.
+    ///
+    /// This is meant for animated layouts: the layout pass places the child at its target
+    /// position, and the presentation offset is animated back to zero.
+    /// The child is painted, hit-tested and exposed to accessibility at its presented position,
+    /// while the `window_target_rect` method of its contexts still reports its target position.
+    pub fn set_child_presentation_offset<W: Widget>(
+        &mut self,
+        child: &mut WidgetPod<W>,
+        offset: Vec2,
+    ) {
+        if !offset.is_finite() {
+            debug_panic!(
+                "Error in {}: trying to call 'set_child_presentation_offset' with child '{}' {} with invalid offset {:?}",
+                self.widget_id(),
+                self.get_child(child).short_type_name(),
+                child.id(),
+                offset,
+            );
+        }
+        let child = self.get_child_state_mut(child);
+        if offset != child.presentation_offset {
+            child.presentation_offset = offset;
+            child.flags.insert(WidgetFlags::TRANSLATION_CHANGED);
         }
     }
 }
@@ -1069,6 +2108,158 @@ impl_context_method!(LayoutCtx<'_>, PaintCtx<'_>, {
     }
 });
 
+impl LayoutCtx<'_> {
+    /// Measure the size `text` would have if displayed with `style`, without creating a widget.
+    ///
+    /// Properties which `style` leaves unset are taken from the text style this widget
+    /// inherits, then from its env. Lines are broken at `max_advance`, if provided.
+    ///
+    /// This lets containers size themselves from their data, e.g. to fit a column to
+    /// its longest cell.
+    pub fn measure_text(
+        &mut self,
+        text: &str,
+        style: &TextStyle,
+        max_advance: Option<f32>,
+    ) -> Size {
+        let style = style.inherit_from(&self.widget_state.text_style);
+        measure_text(
+            &mut self.global_state.font_context,
+            &mut self.global_state.text_layout_context,
+            text,
+            &style,
+            &self.widget_state.env,
+            max_advance,
+        )
+    }
+
+    /// The rhythm unit of the baseline grid, if enabled.
+    ///
+    /// Text widgets pass it to [`TextLayout::set_line_height_grid`] before rebuilding their
+    /// layout. See [`RenderRoot::set_baseline_grid`] for details.
+    ///
+    /// [`TextLayout::set_line_height_grid`]: crate::text::TextLayout::set_line_height_grid
+    /// [`RenderRoot::set_baseline_grid`]: crate::RenderRoot::set_baseline_grid
+    pub fn baseline_grid(&self) -> Option<f64> {
+        self.global_state.baseline_grid
+    }
+
+    /// Round `height` up to a multiple of the baseline grid unit, if the grid is enabled.
+    ///
+    /// Widgets with vertical paddings use this on their height, so that the paddings
+    /// around content which is already on the grid, such as text, keep it on the grid.
+    pub fn snap_to_baseline_grid(&self, height: f64) -> f64 {
+        match self.global_state.baseline_grid {
+            // We leave some slack for floating point errors.
+            Some(unit) => (height / unit - 1e-6).ceil() * unit,
+            None => height,
+        }
+    }
+
+    /// Resolve the line height, ascent and descent of text displayed with `style`.
+    ///
+    /// Properties which `style` leaves unset are taken from the text style this widget
+    /// inherits, then from its env.
+    pub fn text_metrics(&mut self, style: &TextStyle) -> TextMetrics {
+        let style = style.inherit_from(&self.widget_state.text_style);
+        text_metrics(
+            &mut self.global_state.font_context,
+            &mut self.global_state.text_layout_context,
+            &style,
+            &self.widget_state.env,
+        )
+    }
+
+    /// Send a signal to parent widgets to scroll this area into view, once layout is done.
+    ///
+    /// `rect` is in local coordinates.
+    /// This is useful when the area depends on the new layout, e.g. for the caret of
+    /// a text box.
+    pub fn request_scroll_to(&mut self, rect: Rect) {
+        self.global_state
+            .scroll_request_targets
+            .push((self.widget_state.id, rect));
+    }
+
+    /// Request an animation frame.
+    ///
+    /// This is useful for animations towards a value which depends on the layout,
+    /// e.g. the size of a child.
+    pub fn request_anim_frame(&mut self) {
+        trace!("request_anim_frame");
+        self.widget_state
+            .flags
+            .insert(WidgetFlags::REQUEST_ANIM | WidgetFlags::NEEDS_ANIM);
+    }
+}
+
+impl PaintCtx<'_> {
+    /// How text drawn by this widget should be snapped to physical pixels, if at all.
+    ///
+    /// This is `None` while text should be positioned at subpixel offsets, e.g. during
+    /// an animation, or if the widget is rotated or scaled. Widgets should pass it to
+    /// [`TextLayout::set_glyph_snapping`] before drawing text.
+    /// See [`RenderRoot::set_text_positioning`] for details.
+    ///
+    /// [`TextLayout::set_glyph_snapping`]: crate::text::TextLayout::set_glyph_snapping
+    /// [`RenderRoot::set_text_positioning`]: crate::RenderRoot::set_text_positioning
+    pub fn glyph_snapping(&self) -> Option<GlyphSnapping> {
+        let scale = self.global_state.text_snap_scale?;
+        let [a, b, c, d, x, y] = self.widget_state.window_transform().as_coeffs();
+        ((a, b, c, d) == (1.0, 0.0, 0.0, 1.0)).then_some(GlyphSnapping {
+            scale,
+            window_origin: Point::new(x, y),
+        })
+    }
+
+    /// Paint the drop shadow cast by `rect`, with corners of the given `radius`, in the
+    /// widget's local coordinates.
+    ///
+    /// Shadows are painted before the shape which casts them. The widget should set
+    /// [`BoxShadow::paint_insets`] as its paint insets during layout, so that the shadow
+    /// isn't culled when only the shadow is visible.
+    pub fn paint_shadow(&mut self, scene: &mut Scene, rect: Rect, radius: f64, shadow: &BoxShadow) {
+        shadow.paint(scene, rect, radius);
+    }
+
+    /// Paint a descendant of this widget and its own descendants into a standalone scene,
+    /// in the local coordinates of the descendant.
+    ///
+    /// Widgets which haven't changed since they were last painted reuse their cached scene.
+    /// Descendants outside the window are painted too, which makes this suitable for
+    /// previews of scrolled content.
+    ///
+    /// Returns `None` if the descendant is stashed.
+    ///
+    /// ## Panics
+    ///
+    /// If `id` is not a descendant of this widget.
+    pub fn paint_descendant_scene(&mut self, id: WidgetId) -> Option<WidgetScene> {
+        let state = self
+            .widget_state_children
+            .reborrow_mut()
+            .find_mut(id)
+            .expect("paint_descendant_scene: descendant not found");
+        let widget = self
+            .widget_children
+            .reborrow_mut()
+            .find_mut(id)
+            .expect("paint_descendant_scene: descendant not found");
+        paint_subtree(self.global_state, self.scenes, widget, state)
+    }
+}
+
+/// Clamp `opacity` between 0 and 1.
+///
+/// NaN isn't affected by clamping, so non-finite values are treated as fully opaque.
+fn valid_opacity(opacity: f32) -> f32 {
+    if opacity.is_finite() {
+        opacity.clamp(0., 1.)
+    } else {
+        1.
+    }
+}
+
 // --- MARK: RAW WRAPPERS ---
 macro_rules! impl_get_raw {
     ($SomeCtx:tt) => {
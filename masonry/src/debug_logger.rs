@@ -9,7 +9,7 @@ use std::sync::Arc;
 use crate::debug_values::{
     LayoutInfo, LayoutTree, LogId, MyWidgetId, Snapshot, StateTree, Timeline, Value,
 };
-use crate::widget::WidgetRef;
+use crate::widget::{WidgetFlags, WidgetRef};
 use crate::Widget;
 
 #[derive(Debug)]
@@ -197,15 +197,35 @@ impl DebugLogger {
         state.children = vec![
             StateTree::new(
                 "is_expecting_place_child_call",
-                w_state.is_expecting_place_child_call,
+                w_state
+                    .flags
+                    .contains(WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL),
+            ),
+            StateTree::new(
+                "is_explicitly_disabled",
+                w_state.flags.contains(WidgetFlags::IS_EXPLICITLY_DISABLED),
+            ),
+            StateTree::new(
+                "is_hovered",
+                w_state.flags.contains(WidgetFlags::IS_HOVERED),
+            ),
+            StateTree::new(
+                "needs_layout",
+                w_state.flags.contains(WidgetFlags::NEEDS_LAYOUT),
+            ),
+            StateTree::new("has_focus", w_state.flags.contains(WidgetFlags::HAS_FOCUS)),
+            StateTree::new(
+                "request_anim",
+                w_state.flags.contains(WidgetFlags::REQUEST_ANIM),
+            ),
+            StateTree::new(
+                "children_changed",
+                w_state.flags.contains(WidgetFlags::CHILDREN_CHANGED),
+            ),
+            StateTree::new(
+                "update_focus_chain",
+                w_state.flags.contains(WidgetFlags::UPDATE_FOCUS_CHAIN),
             ),
-            StateTree::new("is_explicitly_disabled", w_state.is_explicitly_disabled),
-            StateTree::new("is_hovered", w_state.is_hovered),
-            StateTree::new("needs_layout", w_state.needs_layout),
-            StateTree::new("has_focus", w_state.has_focus),
-            StateTree::new("request_anim", w_state.request_anim),
-            StateTree::new("children_changed", w_state.children_changed),
-            StateTree::new("update_focus_chain", w_state.update_focus_chain),
         ]
         .into();
         state
@@ -0,0 +1,183 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Theme values which can be overridden for a subtree of widgets.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+
+use vello::kurbo::{Insets, Size};
+use vello::peniko::Color;
+
+/// A set of theme values, looked up with [`Key`]s.
+///
+/// Each widget has an environment, read with `ctx.env()`. It combines the values set on
+/// the widget and its ancestors with [`EventCtx::set_env`](crate::EventCtx::set_env), and
+/// the ones set for the whole window with [`RenderRoot::set_env`](crate::RenderRoot::set_env).
+/// Keys which aren't set anywhere resolve to their default value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Env {
+    values: HashMap<&'static str, Value>,
+}
+
+/// A typed key for a value of an [`Env`], with the value used when no environment sets it.
+///
+/// The theme keys used by Masonry's widgets are in the [`theme`](crate::theme) module.
+pub struct Key<T> {
+    name: &'static str,
+    default: T,
+}
+
+/// A value stored in an [`Env`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Value {
+    Bool(bool),
+    Float(f64),
+    Color(Color),
+    Insets(Insets),
+    Size(Size),
+}
+
+/// The types which can be stored in an [`Env`].
+pub trait ValueType: Clone + Into<Value> {
+    /// Extract a value of this type, or `None` if `value` holds another type.
+    fn try_from_value(value: &Value) -> Option<Self>;
+}
+
+// --- MARK: ENV ---
+impl Env {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style method to set the value of `key`.
+    pub fn with<T: ValueType>(mut self, key: impl Borrow<Key<T>>, value: T) -> Self {
+        self.set(key, value);
+        self
+    }
+
+    /// Set the value of `key`.
+    pub fn set<T: ValueType>(&mut self, key: impl Borrow<Key<T>>, value: T) {
+        self.values.insert(key.borrow().name, value.into());
+    }
+
+    /// The value of `key`, or its default value if it isn't set.
+    ///
+    /// ## Panics
+    ///
+    /// If a value of another type was set for the name of `key`.
+    pub fn get<T: ValueType>(&self, key: impl Borrow<Key<T>>) -> T {
+        let key = key.borrow();
+        match self.values.get(key.name) {
+            Some(value) => T::try_from_value(value).unwrap_or_else(|| {
+                panic!(
+                    "Env value for key '{}' has the wrong type: {value:?}",
+                    key.name
+                )
+            }),
+            None => key.default.clone(),
+        }
+    }
+
+    /// Whether a value was set for `key`, instead of using its default value.
+    pub fn contains<T>(&self, key: impl Borrow<Key<T>>) -> bool {
+        self.values.contains_key(key.borrow().name)
+    }
+
+    /// The environment with the values of `self`, and those of `parent` for keys which
+    /// `self` doesn't set.
+    pub(crate) fn inherit_from(&self, parent: &Self) -> Self {
+        let mut values = parent.values.clone();
+        values.extend(
+            self.values
+                .iter()
+                .map(|(name, value)| (*name, value.clone())),
+        );
+        Self { values }
+    }
+}
+
+// --- MARK: KEY ---
+impl<T> Key<T> {
+    /// Create a key.
+    ///
+    /// Names should be namespaced to avoid collisions, e.g. `"my_app.sidebar_width"`.
+    pub const fn new(name: &'static str, default: T) -> Self {
+        Self { name, default }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The value used when no environment sets this key.
+    pub const fn default_value(&self) -> &T {
+        &self.default
+    }
+}
+
+impl<T: Clone> Clone for Key<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            default: self.default.clone(),
+        }
+    }
+}
+
+impl<T: Copy> Copy for Key<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for Key<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("name", &self.name)
+            .field("default", &self.default)
+            .finish()
+    }
+}
+
+// --- MARK: VALUE TYPES ---
+macro_rules! impl_value_type {
+    ($ty:ty, $variant:ident) => {
+        impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                Self::$variant(value)
+            }
+        }
+
+        impl ValueType for $ty {
+            fn try_from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::$variant(value) => Some(*value),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_value_type!(bool, Bool);
+impl_value_type!(f64, Float);
+impl_value_type!(Color, Color);
+impl_value_type!(Insets, Insets);
+impl_value_type!(Size, Size);
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: Key<f64> = Key::new("test.width", 10.);
+    const COLOR: Key<Color> = Key::new("test.color", Color::BLACK);
+
+    #[test]
+    fn overrides_inherit_from_parent() {
+        let parent = Env::new().with(WIDTH, 20.).with(COLOR, Color::WHITE);
+        let child = Env::new().with(COLOR, Color::RED).inherit_from(&parent);
+        assert_eq!(child.get(WIDTH), 20.);
+        assert_eq!(child.get(COLOR), Color::RED);
+        assert_eq!(Env::new().get(WIDTH), 10.);
+    }
+}
@@ -3,17 +3,23 @@
 
 //! Events.
 
+use std::any::Any;
+use std::fmt;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use winit::event::{Force, Ime, KeyEvent, Modifiers};
-use winit::keyboard::ModifiersState;
+use winit::event::{ElementState, Force, Ime, Modifiers};
+use winit::keyboard::{
+    Key, KeyCode, KeyLocation, ModifiersState, NamedKey, NativeKeyCode, PhysicalKey, SmolStr,
+};
 
-use crate::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
-use crate::kurbo::Rect;
+use crate::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
+use crate::kurbo::{Rect, Size, Vec2};
+use crate::WidgetId;
 
 // TODO - Occluded(bool) event
 // TODO - winit ActivationTokenDone thing
-// TODO - Suspended/Resume/NewEvents/MemoryWarning
+// TODO - Suspended/Resume/NewEvents
 // TODO - wtf is InnerSizeWriter?
 // TODO - Move AnimFrame to Update
 // TODO - switch anim frames to being about age / an absolute timestamp
@@ -25,11 +31,57 @@ pub enum WindowEvent {
     Resize(PhysicalSize<u32>),
     AnimFrame,
     RebuildAccessTree,
+    /// The monitors connected to the system, as enumerated by the platform.
+    MonitorsChanged(Vec<MonitorInfo>),
+    /// The top-left corner of the content area of the window moved to this position
+    /// on the desktop.
+    Moved(PhysicalPosition<i32>),
+}
+
+/// A monitor connected to the system.
+///
+/// The monitors of the system are available through
+/// [`EventCtx::monitors`](crate::EventCtx::monitors) and the other contexts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    /// A human-readable name of the monitor, if the platform provides one.
+    pub name: Option<String>,
+    /// The position of the top-left corner of the monitor on the desktop.
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    pub is_primary: bool,
+}
+
+/// Where to move a window, requested with
+/// [`EventCtx::place_window`](crate::EventCtx::place_window) and the other contexts.
+///
+/// Monitors are identified by their index in the list of monitors of the contexts.
+/// Sizes and positions are in the logical coordinates of the monitor the window is placed on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowPlacement {
+    /// Center the window on `monitor`, or on the primary monitor if `None`,
+    /// optionally resizing its content area to `size`.
+    Centered {
+        monitor: Option<usize>,
+        size: Option<LogicalSize<f64>>,
+    },
+    /// Move the top-left corner of the window to `position`, relative to the top-left corner
+    /// of `monitor`, optionally resizing its content area to `size`.
+    ///
+    /// This can be used to restore the placement of a window from a previous session.
+    OnMonitor {
+        monitor: usize,
+        position: LogicalPosition<f64>,
+        size: Option<LogicalSize<f64>>,
+    },
+    /// Move the top-left corner of the window to the pointer, moved by `offset`,
+    /// e.g. for popup windows.
+    AtPointer { offset: Vec2 },
 }
 
 /// An indicator of which pointer button was pressed.
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
-#[repr(u8)]
 pub enum PointerButton {
     /// No mouse button.
     None,
@@ -39,28 +91,31 @@ pub enum PointerButton {
     Secondary,
     /// Auxiliary button, commonly the middle mouse button.
     Auxiliary,
-    /// X1 (back) Mouse.
-    X1,
-    /// X2 (forward) Mouse.
-    X2,
-    /// Other mouse button. This isn't fleshed out yet.
-    Other,
+    /// The back button of the mouse, commonly the X1 button on the side of the mouse.
+    Back,
+    /// The forward button of the mouse, commonly the X2 button on the side of the mouse.
+    Forward,
+    /// Any other mouse button, with the number given by the platform.
+    Other(u16),
 }
 
 /// A set of [`PointerButton`]s.
 #[derive(PartialEq, Eq, Clone, Copy, Default)]
-pub struct PointerButtons(u8);
+pub struct PointerButtons(u32);
+
+/// The first bit used by [`PointerButton::Other`] buttons.
+const OTHER_BUTTONS_SHIFT: u16 = 5;
 
-fn button_bit(button: PointerButton) -> u8 {
+fn button_bit(button: PointerButton) -> u32 {
     match button {
         PointerButton::None => 0,
         PointerButton::Primary => 0b1,
         PointerButton::Secondary => 0b10,
         PointerButton::Auxiliary => 0b100,
-        PointerButton::X1 => 0b1000,
-        PointerButton::X2 => 0b10000,
-        // TODO: When we properly do `Other`, this changes
-        PointerButton::Other => 0b100000,
+        PointerButton::Back => 0b1000,
+        PointerButton::Forward => 0b10000,
+        // Buttons with numbers too big for the set share its last bit.
+        PointerButton::Other(n) => 1 << OTHER_BUTTONS_SHIFT.saturating_add(n).min(31),
     }
 }
 
@@ -131,14 +186,16 @@ impl std::fmt::Debug for PointerButtons {
         if self.contains(PointerButton::Auxiliary) {
             tuple.field(&"Auxiliary");
         }
-        if self.contains(PointerButton::X1) {
-            tuple.field(&"X1");
+        if self.contains(PointerButton::Back) {
+            tuple.field(&"Back");
         }
-        if self.contains(PointerButton::X2) {
-            tuple.field(&"X2");
+        if self.contains(PointerButton::Forward) {
+            tuple.field(&"Forward");
         }
-        if self.contains(PointerButton::Other) {
-            tuple.field(&"Other");
+        for n in 0..32 - OTHER_BUTTONS_SHIFT {
+            if self.contains(PointerButton::Other(n)) {
+                tuple.field(&PointerButton::Other(n));
+            }
         }
         tuple.finish()
     }
@@ -178,8 +235,7 @@ impl From<PointerButton> for PointerButtons {
     }
 }
 
-// TODO - How can RenderRoot express "I started a drag-and-drop op"?
-// TODO - Touchpad, Touch, AxisMotion
+// TODO - Touchpad, AxisMotion
 // TODO - How to handle CursorEntered?
 // Note to self: Events like "pointerenter", "pointerleave" are handled differently at the Widget level. But that's weird because WidgetPod can distribute them. Need to think about this again.
 #[derive(Debug, Clone)]
@@ -189,14 +245,94 @@ pub enum PointerEvent {
     PointerMove(PointerState),
     PointerEnter(PointerState),
     PointerLeave(PointerState),
-    MouseWheel(LogicalPosition<f64>, PointerState),
+    /// The mouse wheel or the touchpad scrolled, by the given horizontal and vertical amounts.
+    ///
+    /// The [`ScrollDeltaKind`] tells whether the amounts are wheel notches, or a precise
+    /// scroll from a touchpad, which shouldn't be animated since it already follows the fingers.
+    ///
+    /// While Shift is held, a vertical scroll of a mouse without a horizontal wheel
+    /// is reported as a horizontal scroll.
+    MouseWheel(LogicalPosition<f64>, ScrollDeltaKind, PointerState),
+    /// A file from another app is being dragged over the widget.
+    ///
+    /// If several files are dragged, an event is sent for each of them. Some platforms
+    /// don't report pointer moves while files are dragged, so the position is the last
+    /// known position of the pointer. Files are also sent as
+    /// [`PlatformEvent::FileHovered`].
     HoverFile(PathBuf, PointerState),
+    /// A file from another app was dropped on the widget.
+    ///
+    /// If several files are dropped, an event is sent for each of them.
     DropFile(PathBuf, PointerState),
+    /// The files dragged over the widget left the window, or the drag was cancelled.
     HoverFileCancel(PointerState),
+    /// A pinch gesture on a touchpad, or of two fingers on a touchscreen.
+    ///
+    /// The value is the change of the scale of the content: a positive value means
+    /// the content should get bigger.
+    ///
+    /// Gestures of two fingers are sent to the widget under the point between them, even
+    /// if the fingers [captured](crate::EventCtx::capture_pointer) other widgets. Their
+    /// pointer state is at that point, and isn't [primary](PointerState::is_primary).
     Pinch(f64, PointerState),
+    /// A rotation gesture on a touchpad, or of two fingers on a touchscreen.
+    ///
+    /// The value is the change of the angle of the content in radians, clockwise on screen.
+    /// See [`Pinch`](Self::Pinch) for how two-finger gestures are routed.
+    Rotate(f64, PointerState),
+    /// A pan gesture on a touchpad, or the motion of two fingers together on a touchscreen,
+    /// in logical pixels.
+    ///
+    /// See [`Pinch`](Self::Pinch) for how two-finger gestures are routed.
+    Pan(Vec2, PointerState),
+    /// The motion of the mouse while the pointer is locked, in device units.
+    ///
+    /// This is only sent to the widget holding the pointer lock, see
+    /// [`EventCtx::lock_pointer`](crate::EventCtx::lock_pointer). The position of the
+    /// pointer doesn't change while it's locked.
+    LockedMove(Vec2, PointerState),
+    /// The primary button stayed pressed without the pointer moving, for the
+    /// [`long_press_duration`](crate::gesture::GestureConfig::long_press_duration).
+    ///
+    /// This is sent to the widget under the pointer, or the widget which captured it, with
+    /// the buttons and modifiers of the pointer when the button was pressed. A widget which
+    /// handles it, e.g. to show a context menu, usually ignores the following
+    /// [`PointerUp`](Self::PointerUp).
+    LongPress(PointerState),
+    /// The pointer moved further than the
+    /// [`drag_threshold`](crate::gesture::GestureConfig::drag_threshold) while the primary
+    /// button was pressed.
+    ///
+    /// This is sent right before the [`PointerMove`](Self::PointerMove) which crossed the
+    /// threshold, with the state of the pointer at that move. Widgets can start an in-app drag
+    /// with [`EventCtx::start_drag`](crate::EventCtx::start_drag) in response.
+    DragStart(PointerState),
+    /// The pointer moved over the widget during an in-app drag, started with
+    /// [`EventCtx::start_drag`](crate::EventCtx::start_drag).
+    ///
+    /// Like other pointer events, this is sent to the widget under the pointer and bubbles up
+    /// to its ancestors. A widget which can receive the [payload](crate::EventCtx::drag_payload)
+    /// calls [`EventCtx::set_drop_accepted`](crate::EventCtx::set_drop_accepted) to become
+    /// the drop target. This is sent instead of [`PointerMove`](Self::PointerMove).
+    ///
+    /// During a drag moved with the keyboard, this is sent to the widgets of the focus chain
+    /// in turn, with the position of their center, until one of them accepts the drop.
+    DragOver(PointerState),
+    /// The pointer was released during an in-app drag, or Enter or Space was pressed during
+    /// a drag moved with the keyboard.
+    ///
+    /// This is only sent to the widget which accepted the drop during the last
+    /// [`DragOver`](Self::DragOver), which can take the payload with
+    /// [`EventCtx::take_drag_payload`](crate::EventCtx::take_drag_payload).
+    /// This is sent instead of [`PointerUp`](Self::PointerUp).
+    DragDrop(PointerState),
+    /// The pointer left the drop target during an in-app drag, or the drag was cancelled.
+    ///
+    /// This is only sent to the widget which accepted the drop, so it can remove
+    /// its drop indicator.
+    DragLeave(PointerState),
 }
 
-// TODO - Clipboard Paste?
 // TODO skip is_synthetic=true events
 #[derive(Debug, Clone)]
 pub enum TextEvent {
@@ -205,6 +341,152 @@ pub enum TextEvent {
     ModifierChange(ModifiersState),
     // TODO - Document difference with Update focus change
     FocusChange(bool),
+    /// The text content of the clipboard, requested with
+    /// [`EventCtx::request_paste`](crate::EventCtx::request_paste).
+    Paste(String),
+}
+
+/// A key pressed or released, sent with [`TextEvent::KeyboardKey`].
+///
+/// This has the fields of winit's [`KeyEvent`](winit::event::KeyEvent), which can't be
+/// created outside of winit, so that key presses can be simulated, e.g. by the
+/// [`TestHarness`](crate::testing::TestHarness).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    /// The position of the key on the keyboard, regardless of the layout.
+    pub physical_key: PhysicalKey,
+    /// The key, according to the layout and the modifiers.
+    pub logical_key: Key,
+    /// The text produced by the key, if any.
+    pub text: Option<SmolStr>,
+    pub location: KeyLocation,
+    pub state: ElementState,
+    pub repeat: bool,
+    /// The key according to the layout, without the modifiers, used to match shortcuts.
+    ///
+    /// This is the same as `logical_key` on platforms which don't report it.
+    pub key_without_modifiers: Key,
+}
+
+impl KeyEvent {
+    /// A press of `key` on a keyboard with the US layout.
+    ///
+    /// The physical key is deduced from named keys and ASCII letters and digits,
+    /// and is [unidentified](PhysicalKey::Unidentified) for other keys.
+    pub fn key_down(key: Key) -> Self {
+        let text = match &key {
+            Key::Character(text) => Some(text.clone()),
+            Key::Named(NamedKey::Space) => Some(" ".into()),
+            Key::Named(NamedKey::Enter) => Some("\r".into()),
+            Key::Named(NamedKey::Tab) => Some("\t".into()),
+            _ => None,
+        };
+        Self {
+            physical_key: physical_key_of(&key),
+            logical_key: key.clone(),
+            text,
+            location: KeyLocation::Standard,
+            state: ElementState::Pressed,
+            repeat: false,
+            key_without_modifiers: key,
+        }
+    }
+
+    /// A release of `key`, see [`key_down`](Self::key_down).
+    pub fn key_up(key: Key) -> Self {
+        Self {
+            text: None,
+            state: ElementState::Released,
+            ..Self::key_down(key)
+        }
+    }
+}
+
+/// The key of the US layout which produces `key`.
+fn physical_key_of(key: &Key) -> PhysicalKey {
+    let code = match key {
+        Key::Named(NamedKey::Tab) => Some(KeyCode::Tab),
+        Key::Named(NamedKey::Enter) => Some(KeyCode::Enter),
+        Key::Named(NamedKey::Escape) => Some(KeyCode::Escape),
+        Key::Named(NamedKey::Space) => Some(KeyCode::Space),
+        Key::Named(NamedKey::Backspace) => Some(KeyCode::Backspace),
+        Key::Named(NamedKey::Delete) => Some(KeyCode::Delete),
+        Key::Named(NamedKey::Home) => Some(KeyCode::Home),
+        Key::Named(NamedKey::End) => Some(KeyCode::End),
+        Key::Named(NamedKey::PageUp) => Some(KeyCode::PageUp),
+        Key::Named(NamedKey::PageDown) => Some(KeyCode::PageDown),
+        Key::Named(NamedKey::ArrowUp) => Some(KeyCode::ArrowUp),
+        Key::Named(NamedKey::ArrowDown) => Some(KeyCode::ArrowDown),
+        Key::Named(NamedKey::ArrowLeft) => Some(KeyCode::ArrowLeft),
+        Key::Named(NamedKey::ArrowRight) => Some(KeyCode::ArrowRight),
+        Key::Character(text) => match text.to_ascii_lowercase().as_str() {
+            "a" => Some(KeyCode::KeyA),
+            "b" => Some(KeyCode::KeyB),
+            "c" => Some(KeyCode::KeyC),
+            "d" => Some(KeyCode::KeyD),
+            "e" => Some(KeyCode::KeyE),
+            "f" => Some(KeyCode::KeyF),
+            "g" => Some(KeyCode::KeyG),
+            "h" => Some(KeyCode::KeyH),
+            "i" => Some(KeyCode::KeyI),
+            "j" => Some(KeyCode::KeyJ),
+            "k" => Some(KeyCode::KeyK),
+            "l" => Some(KeyCode::KeyL),
+            "m" => Some(KeyCode::KeyM),
+            "n" => Some(KeyCode::KeyN),
+            "o" => Some(KeyCode::KeyO),
+            "p" => Some(KeyCode::KeyP),
+            "q" => Some(KeyCode::KeyQ),
+            "r" => Some(KeyCode::KeyR),
+            "s" => Some(KeyCode::KeyS),
+            "t" => Some(KeyCode::KeyT),
+            "u" => Some(KeyCode::KeyU),
+            "v" => Some(KeyCode::KeyV),
+            "w" => Some(KeyCode::KeyW),
+            "x" => Some(KeyCode::KeyX),
+            "y" => Some(KeyCode::KeyY),
+            "z" => Some(KeyCode::KeyZ),
+            "0" => Some(KeyCode::Digit0),
+            "1" => Some(KeyCode::Digit1),
+            "2" => Some(KeyCode::Digit2),
+            "3" => Some(KeyCode::Digit3),
+            "4" => Some(KeyCode::Digit4),
+            "5" => Some(KeyCode::Digit5),
+            "6" => Some(KeyCode::Digit6),
+            "7" => Some(KeyCode::Digit7),
+            "8" => Some(KeyCode::Digit8),
+            "9" => Some(KeyCode::Digit9),
+            _ => None,
+        },
+        _ => None,
+    };
+    code.map_or(
+        PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+        PhysicalKey::Code,
+    )
+}
+
+impl From<winit::event::KeyEvent> for KeyEvent {
+    fn from(event: winit::event::KeyEvent) -> Self {
+        #[cfg(not(any(target_os = "android", target_os = "ios")))]
+        let key_without_modifiers = {
+            use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
+            event.key_without_modifiers()
+        };
+        // We think it will be rare that users are using a physical keyboard with Android,
+        // and so we don't really need to worry *too much* about the text selection shortcuts
+        #[cfg(any(target_os = "android", target_os = "ios"))]
+        let key_without_modifiers = event.logical_key.clone();
+        Self {
+            physical_key: event.physical_key,
+            logical_key: event.logical_key,
+            text: event.text,
+            location: event.location,
+            state: event.state,
+            repeat: event.repeat,
+            key_without_modifiers,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -213,6 +495,28 @@ pub struct AccessEvent {
     pub data: Option<accesskit::ActionData>,
 }
 
+/// What the amounts of a [`MouseWheel`](PointerEvent::MouseWheel) event count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollDeltaKind {
+    /// Lines, from the notches of a mouse wheel.
+    Line,
+    /// Logical pixels, from a touchpad or another device which scrolls precisely.
+    Pixel,
+}
+
+/// Identifies one of the pointers interacting with the window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PointerId {
+    /// The mouse, or any other device which moves the cursor, like a touchpad or a pen.
+    #[default]
+    Mouse,
+    /// A finger on a touchscreen.
+    ///
+    /// The id is given by the platform, and stays the same from the moment the finger
+    /// touches the screen until it is lifted.
+    Touch(u64),
+}
+
 #[derive(Debug, Clone)]
 pub struct PointerState {
     // TODO
@@ -221,17 +525,177 @@ pub struct PointerState {
     pub position: LogicalPosition<f64>,
     pub buttons: PointerButtons,
     pub mods: Modifiers,
+    /// The number of presses of the button in quick succession at the same place, e.g. 2 for
+    /// a double click and 3 for a triple click.
+    ///
+    /// This is set on [`PointerDown`](PointerEvent::PointerDown), and on the events of the
+    /// same press: [`PointerUp`](PointerEvent::PointerUp),
+    /// [`LongPress`](PointerEvent::LongPress) and [`DragStart`](PointerEvent::DragStart).
+    /// It's 0 for other events.
     pub count: u8,
     pub focus: bool,
     pub force: Option<Force>,
+    /// The pointer which sent the event.
+    pub pointer_id: PointerId,
+    /// Whether this is the primary pointer: the mouse, or the first finger touching the screen.
+    ///
+    /// Only the primary pointer hovers widgets. Events of the other pointers are sent to the
+    /// widget under them, or to the widget which [captured](crate::EventCtx::capture_pointer)
+    /// them, independently of the primary pointer.
+    pub is_primary: bool,
+    /// The size of the area of the screen touched by a finger, in logical pixels,
+    /// if the platform reports it.
+    ///
+    /// winit doesn't report it, so this is always `None` with the default event loop.
+    pub contact_size: Option<Size>,
+    /// The state of the pen, if the pointer is a pen or a stylus.
+    pub pen: Option<PenState>,
+    /// On [`PointerMove`](PointerEvent::PointerMove), the positions the pointer went through
+    /// since the previous move event, oldest first, not including [`position`](Self::position).
+    ///
+    /// The event loop coalesces the cursor moves it receives in a batch of platform events
+    /// into a single event, which is all most widgets need. Widgets which draw what the
+    /// pointer went through, such as inking widgets, should use these positions too.
+    /// Empty for other events, and for touch moves, which are sent one by one.
+    pub coalesced: Vec<LogicalPosition<f64>>,
 }
 
-#[derive(Debug, Clone)]
+/// The state of a pen or stylus, see [`PointerState::pen`].
+///
+/// Platforms which can't report some of these fields leave them to their default values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PenState {
+    /// How hard the pen is pressed, from 0 to 1.
+    pub pressure: f64,
+    /// The angle between the pen and the surface of the screen, in radians.
+    ///
+    /// This is `PI / 2` when the pen is perpendicular to the screen.
+    pub altitude_angle: f64,
+    /// The direction in which the pen is tilted, in radians, clockwise from the positive x axis.
+    pub azimuth_angle: f64,
+    /// The rotation of the pen around its own axis, in radians, clockwise.
+    pub twist: f64,
+    /// Whether the pen is touching the screen with its eraser end.
+    pub eraser: bool,
+    /// Whether the button on the barrel of the pen is pressed.
+    pub barrel_button: bool,
+}
+
+impl Default for PenState {
+    fn default() -> Self {
+        Self {
+            pressure: 0.5,
+            altitude_angle: std::f64::consts::FRAC_PI_2,
+            azimuth_angle: 0.,
+            twist: 0.,
+            eraser: false,
+            barrel_button: false,
+        }
+    }
+}
+
+/// The color scheme of the system, see [`EventCtx::color_scheme`](crate::EventCtx::color_scheme).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum WindowTheme {
     Light,
+    /// The default, which matches Masonry's built-in theme.
+    #[default]
     Dark,
 }
 
+/// A direction in which focus can move with the arrow keys, see
+/// [`EventCtx::focus_in_direction`](crate::EventCtx::focus_in_direction).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl FocusDirection {
+    /// The direction moved in by the given arrow key, if it is one.
+    pub(crate) fn from_key(key: &Key) -> Option<Self> {
+        match key {
+            Key::Named(NamedKey::ArrowUp) => Some(Self::Up),
+            Key::Named(NamedKey::ArrowDown) => Some(Self::Down),
+            Key::Named(NamedKey::ArrowLeft) => Some(Self::Left),
+            Key::Named(NamedKey::ArrowRight) => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    /// How far focus moves from `from` to `to` in this direction, or `None` if `to` isn't
+    /// in this direction.
+    ///
+    /// The first value is whether `to` is outside the band swept by `from` when moving in
+    /// this direction, so that e.g. the widget to the right in the same row is preferred
+    /// over a closer one in the next row. The second one is the distance between the centers
+    /// of the rects, weighted to favor widgets in line with `from`.
+    pub(crate) fn distance(self, from: Rect, to: Rect) -> Option<(bool, f64)> {
+        let (from_center, to_center) = (from.center(), to.center());
+        let (along, across, overlaps) = match self {
+            Self::Up | Self::Down => (
+                to_center.y - from_center.y,
+                to_center.x - from_center.x,
+                to.x0 < from.x1 && from.x0 < to.x1,
+            ),
+            Self::Left | Self::Right => (
+                to_center.x - from_center.x,
+                to_center.y - from_center.y,
+                to.y0 < from.y1 && from.y0 < to.y1,
+            ),
+        };
+        let along = match self {
+            Self::Up | Self::Left => -along,
+            Self::Down | Self::Right => along,
+        };
+        if along <= 0. {
+            return None;
+        }
+        Some((!overlaps, along + 2. * across.abs()))
+    }
+}
+
+/// A less common event from the platform, sent to the widgets which asked for them with
+/// [`EventCtx::set_receives_platform_events`](crate::EventCtx::set_receives_platform_events).
+///
+/// Unlike pointer and text events, platform events aren't targeted at a specific widget,
+/// and don't bubble. New variants may be added, so widgets should ignore the events they
+/// don't handle.
+#[non_exhaustive]
+#[derive(Clone)]
+pub enum PlatformEvent {
+    /// A file is being dragged over the window.
+    ///
+    /// `position` is the last known position of the pointer in the window, if any.
+    /// Some platforms don't report pointer moves while a file is dragged.
+    /// The file is also sent to the widget under the pointer as [`PointerEvent::HoverFile`].
+    FileHovered {
+        path: PathBuf,
+        position: Option<LogicalPosition<f64>>,
+    },
+    /// The files dragged over the window left it, or the drag was cancelled.
+    FileHoverCancelled,
+    /// A file was dropped on the window.
+    FileDropped {
+        path: PathBuf,
+        position: Option<LogicalPosition<f64>>,
+    },
+    /// The theme of the window changed, e.g. because the user switched the system to dark mode.
+    ThemeChanged(WindowTheme),
+    /// The system is running low on memory, and widgets should drop the caches they can rebuild.
+    MemoryWarning,
+    /// The locale formatter was changed with
+    /// [`RenderRoot::set_formatter`](crate::RenderRoot::set_formatter).
+    LocaleChanged,
+    /// An event defined by the app, sent with
+    /// [`RenderRoot::handle_platform_event`](crate::RenderRoot::handle_platform_event).
+    ///
+    /// Widgets can downcast it to the types they know about.
+    Custom(Arc<dyn Any + Send + Sync>),
+}
+
 // TODO - Rewrite that doc.
 /// Application life cycle events.
 ///
@@ -283,6 +747,40 @@ pub enum Update {
     /// [`set_stashed`]: crate::EventCtx::set_stashed
     StashedChanged(bool),
 
+    /// Called when the inherited text style of the widget is changed.
+    ///
+    /// This is sent when this widget or one of its ancestors calls [`set_text_style`].
+    /// The new style can be read with [`text_style`].
+    ///
+    /// [`set_text_style`]: crate::EventCtx::set_text_style
+    /// [`text_style`]: crate::EventCtx::text_style
+    TextStyleChanged,
+
+    /// Called when the env of the widget is changed.
+    ///
+    /// This is sent when this widget or one of its ancestors calls [`set_env`], or when
+    /// the root env is replaced with [`RenderRoot::set_env`]. The widget is laid out and
+    /// painted again, so it only needs to handle this event if it caches values read from
+    /// [`env`], e.g. in a text layout.
+    ///
+    /// [`set_env`]: crate::EventCtx::set_env
+    /// [`env`]: crate::EventCtx::env
+    /// [`RenderRoot::set_env`]: crate::RenderRoot::set_env
+    EnvChanged,
+
+    /// Called on all widgets when the color scheme of the system changes, e.g. because
+    /// the user switched to dark mode.
+    ///
+    /// The new scheme can also be read with [`color_scheme`](crate::EventCtx::color_scheme).
+    ThemeChanged(WindowTheme),
+
+    /// Called on all widgets when the formatter is replaced with
+    /// [`RenderRoot::set_formatter`](crate::RenderRoot::set_formatter).
+    ///
+    /// Widgets which display text built with [`formatter`](crate::EventCtx::formatter)
+    /// should rebuild it, and request a layout.
+    LocaleChanged,
+
     /// Called when a child widgets uses
     /// [`EventCtx::request_pan_to_this`](crate::EventCtx::request_pan_to_this).
     RequestPanToChild(Rect),
@@ -310,8 +808,28 @@ pub enum Update {
 
     /// Called when a widget becomes or no longer is parent of a focused widget.
     ChildFocusChanged(bool),
+
+    /// Called when a timer requested by this widget with
+    /// [`request_timer`](crate::EventCtx::request_timer) or
+    /// [`request_repeating_timer`](crate::EventCtx::request_repeating_timer) fires.
+    ///
+    /// Only the widget which requested the timer receives this.
+    Timer(TimerToken),
+
+    /// Called when the user closes a popup opened by this widget with
+    /// [`show_popup`](crate::EventCtx::show_popup) or
+    /// [`show_context_menu`](crate::EventCtx::show_context_menu), by clicking outside of it,
+    /// or for menus, by choosing an item or pressing Escape.
+    ///
+    /// Popups closed with [`close_popup`](crate::EventCtx::close_popup) don't send this.
+    PopupClosed(WidgetId),
 }
 
+/// Identifies a timer requested with [`EventCtx::request_timer`](crate::EventCtx::request_timer),
+/// and is sent with the [`Update::Timer`] it fires with.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct TimerToken(pub(crate) u64);
+
 impl PointerEvent {
     pub fn new_pointer_leave() -> Self {
         // TODO - The fact we're creating so many dummy values might be
@@ -324,6 +842,11 @@ impl PointerEvent {
             count: 0,
             focus: false,
             force: None,
+            pointer_id: PointerId::Mouse,
+            is_primary: true,
+            contact_size: None,
+            pen: None,
+            coalesced: Vec::new(),
         };
         PointerEvent::PointerLeave(pointer_state)
     }
@@ -335,17 +858,48 @@ impl PointerEvent {
             | PointerEvent::PointerMove(state)
             | PointerEvent::PointerEnter(state)
             | PointerEvent::PointerLeave(state)
-            | PointerEvent::MouseWheel(_, state)
+            | PointerEvent::MouseWheel(_, _, state)
             | PointerEvent::HoverFile(_, state)
             | PointerEvent::DropFile(_, state)
             | PointerEvent::HoverFileCancel(state)
-            | PointerEvent::Pinch(_, state) => state,
+            | PointerEvent::Pinch(_, state)
+            | PointerEvent::Rotate(_, state)
+            | PointerEvent::Pan(_, state)
+            | PointerEvent::LockedMove(_, state)
+            | PointerEvent::LongPress(state)
+            | PointerEvent::DragStart(state)
+            | PointerEvent::DragOver(state)
+            | PointerEvent::DragDrop(state)
+            | PointerEvent::DragLeave(state) => state,
+        }
+    }
+
+    pub(crate) fn pointer_state_mut(&mut self) -> &mut PointerState {
+        match self {
+            PointerEvent::PointerDown(_, state)
+            | PointerEvent::PointerUp(_, state)
+            | PointerEvent::PointerMove(state)
+            | PointerEvent::PointerEnter(state)
+            | PointerEvent::PointerLeave(state)
+            | PointerEvent::MouseWheel(_, _, state)
+            | PointerEvent::HoverFile(_, state)
+            | PointerEvent::DropFile(_, state)
+            | PointerEvent::HoverFileCancel(state)
+            | PointerEvent::Pinch(_, state)
+            | PointerEvent::Rotate(_, state)
+            | PointerEvent::Pan(_, state)
+            | PointerEvent::LockedMove(_, state)
+            | PointerEvent::LongPress(state)
+            | PointerEvent::DragStart(state)
+            | PointerEvent::DragOver(state)
+            | PointerEvent::DragDrop(state)
+            | PointerEvent::DragLeave(state) => state,
         }
     }
 
     pub fn position(&self) -> Option<LogicalPosition<f64>> {
         match self {
-            PointerEvent::PointerLeave(_) | PointerEvent::HoverFileCancel(_) => None,
+            PointerEvent::PointerLeave(_) | PointerEvent::DragLeave(_) => None,
             _ => Some(self.pointer_state().position),
         }
     }
@@ -357,14 +911,31 @@ impl PointerEvent {
             PointerEvent::PointerMove(_) => "PointerMove",
             PointerEvent::PointerEnter(_) => "PointerEnter",
             PointerEvent::PointerLeave(_) => "PointerLeave",
-            PointerEvent::MouseWheel(_, _) => "MouseWheel",
+            PointerEvent::MouseWheel(..) => "MouseWheel",
             PointerEvent::HoverFile(_, _) => "HoverFile",
             PointerEvent::DropFile(_, _) => "DropFile",
             PointerEvent::HoverFileCancel(_) => "HoverFileCancel",
             PointerEvent::Pinch(_, _) => "Pinch",
+            PointerEvent::Rotate(_, _) => "Rotate",
+            PointerEvent::Pan(_, _) => "Pan",
+            PointerEvent::LockedMove(_, _) => "LockedMove",
+            PointerEvent::LongPress(_) => "LongPress",
+            PointerEvent::DragStart(_) => "DragStart",
+            PointerEvent::DragOver(_) => "DragOver",
+            PointerEvent::DragDrop(_) => "DragDrop",
+            PointerEvent::DragLeave(_) => "DragLeave",
         }
     }
 
+    /// Whether this is a [`Pinch`](Self::Pinch), [`Rotate`](Self::Rotate) or [`Pan`](Self::Pan)
+    /// gesture.
+    pub fn is_gesture(&self) -> bool {
+        matches!(
+            self,
+            PointerEvent::Pinch(..) | PointerEvent::Rotate(..) | PointerEvent::Pan(..)
+        )
+    }
+
     pub fn is_high_density(&self) -> bool {
         match self {
             PointerEvent::PointerDown(_, _) => false,
@@ -372,11 +943,19 @@ impl PointerEvent {
             PointerEvent::PointerMove(_) => true,
             PointerEvent::PointerEnter(_) => false,
             PointerEvent::PointerLeave(_) => false,
-            PointerEvent::MouseWheel(_, _) => true,
+            PointerEvent::MouseWheel(..) => true,
             PointerEvent::HoverFile(_, _) => true,
             PointerEvent::DropFile(_, _) => false,
             PointerEvent::HoverFileCancel(_) => false,
             PointerEvent::Pinch(_, _) => true,
+            PointerEvent::Rotate(_, _) => true,
+            PointerEvent::Pan(_, _) => true,
+            PointerEvent::LockedMove(_, _) => true,
+            PointerEvent::LongPress(_) => false,
+            PointerEvent::DragStart(_) => false,
+            PointerEvent::DragOver(_) => true,
+            PointerEvent::DragDrop(_) => false,
+            PointerEvent::DragLeave(_) => false,
         }
     }
 }
@@ -393,6 +972,7 @@ impl TextEvent {
             TextEvent::Ime(Ime::Preedit(_, _)) => "Ime::Preedit",
             TextEvent::ModifierChange(_) => "ModifierChange",
             TextEvent::FocusChange(_) => "FocusChange",
+            TextEvent::Paste(_) => "Paste",
         }
     }
 
@@ -403,6 +983,7 @@ impl TextEvent {
             // Basically every mouse click/scroll event seems to produce a modifier change event.
             TextEvent::ModifierChange(_) => true,
             TextEvent::FocusChange(_) => false,
+            TextEvent::Paste(_) => false,
         }
     }
 }
@@ -459,6 +1040,23 @@ impl PointerState {
             count: 0,
             focus: false,
             force: None,
+            pointer_id: PointerId::Mouse,
+            is_primary: true,
+            contact_size: None,
+            pen: None,
+            coalesced: Vec::new(),
+        }
+    }
+
+    /// How hard the pointer is pressed, from 0 to 1, if the device reports it.
+    ///
+    /// This is the pressure of the [pen](Self::pen), or the normalized [force](Self::force)
+    /// of a touch.
+    pub fn pressure(&self) -> Option<f64> {
+        match (&self.pen, &self.force) {
+            (Some(pen), _) => Some(pen.pressure),
+            (None, Some(force)) => Some(force.normalized()),
+            (None, None) => None,
         }
     }
 }
@@ -472,10 +1070,38 @@ impl Update {
             Update::WidgetAdded => "WidgetAdded",
             Update::DisabledChanged(_) => "DisabledChanged",
             Update::StashedChanged(_) => "StashedChanged",
+            Update::TextStyleChanged => "TextStyleChanged",
+            Update::EnvChanged => "EnvChanged",
+            Update::ThemeChanged(_) => "ThemeChanged",
+            Update::LocaleChanged => "LocaleChanged",
             Update::RequestPanToChild(_) => "RequestPanToChild",
             Update::HoveredChanged(_) => "HoveredChanged",
             Update::FocusChanged(_) => "FocusChanged",
             Update::ChildFocusChanged(_) => "ChildFocusChanged",
+            Update::Timer(_) => "Timer",
+            Update::PopupClosed(_) => "PopupClosed",
+        }
+    }
+}
+
+impl fmt::Debug for PlatformEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileHovered { path, position } => f
+                .debug_struct("FileHovered")
+                .field("path", path)
+                .field("position", position)
+                .finish(),
+            Self::FileHoverCancelled => f.write_str("FileHoverCancelled"),
+            Self::FileDropped { path, position } => f
+                .debug_struct("FileDropped")
+                .field("path", path)
+                .field("position", position)
+                .finish(),
+            Self::ThemeChanged(theme) => f.debug_tuple("ThemeChanged").field(theme).finish(),
+            Self::MemoryWarning => f.write_str("MemoryWarning"),
+            Self::LocaleChanged => f.write_str("LocaleChanged"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
         }
     }
 }
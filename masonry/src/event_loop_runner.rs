@@ -2,35 +2,58 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
 use accesskit_winit::Adapter;
-use tracing::{debug, info_span, warn};
-use vello::kurbo::Affine;
+use tracing::{debug, info, info_span, warn};
+use vello::kurbo::{Affine, Vec2};
 use vello::peniko::Color;
 use vello::util::{RenderContext, RenderSurface};
 use vello::{AaSupport, RenderParams, Renderer, RendererOptions, Scene};
-use wgpu::PresentMode;
+use wgpu::{CompositeAlphaMode, PresentMode};
 use winit::application::ApplicationHandler;
 use winit::error::EventLoopError;
 use winit::event::{
-    DeviceEvent as WinitDeviceEvent, DeviceId, MouseButton as WinitMouseButton,
+    DeviceEvent as WinitDeviceEvent, DeviceId, Force, MouseButton as WinitMouseButton,
     WindowEvent as WinitWindowEvent,
 };
-use winit::event_loop::ActiveEventLoop;
-use winit::window::{Window, WindowAttributes, WindowId};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, DeviceEvents};
+use winit::keyboard::{Key, NamedKey};
+use winit::window::{CursorGrabMode, Window, WindowAttributes, WindowId};
+
+use crate::app_driver::{AppDriver, CloseReason, CloseResponse, DriverCtx};
+use crate::clipboard::{ClipboardContent, SystemClipboard};
+use crate::dpi::{LogicalPosition, PhysicalPosition};
+use crate::event::{
+    MonitorInfo, PenState, PlatformEvent, PointerButton, PointerState, WindowEvent,
+    WindowPlacement, WindowTheme,
+};
+use crate::locale::LocaleFormatter;
+use crate::render_root::{self, AppWindowId, RenderRoot, WindowSizePolicy};
+use crate::testing::screenshots::render_scene;
+use crate::widget::{Portal, Prose};
+use crate::{PointerEvent, ScrollDeltaKind, TextEvent, Widget, WidgetId};
 
-use crate::app_driver::{AppDriver, DriverCtx};
-use crate::dpi::LogicalPosition;
-use crate::event::{PointerButton, PointerState, WindowEvent};
-use crate::render_root::{self, RenderRoot, WindowSizePolicy};
-use crate::{PointerEvent, TextEvent, Widget, WidgetId};
+/// How many times in a row the signals of all windows are handled after an event.
+const MAX_SIGNAL_ROUNDS: usize = 16;
 
 #[derive(Debug)]
 pub enum MasonryUserEvent {
     AccessKit(accesskit_winit::Event),
     // TODO: A more considered design here
     Action(crate::Action, WidgetId),
+    /// Ask the app to close, which calls [`AppDriver::on_close_requested`] with the reason.
+    ///
+    /// Apps send this with [`CloseReason::Requested`]. With the `close-signals` feature,
+    /// Masonry sends it when the process receives a termination signal, see
+    /// [`CloseReason::Terminate`] and [`CloseReason::SessionEnd`].
+    CloseRequested(CloseReason),
 }
 
 impl From<accesskit_winit::Event> for MasonryUserEvent {
@@ -45,12 +68,9 @@ impl From<WinitMouseButton> for PointerButton {
             WinitMouseButton::Left => PointerButton::Primary,
             WinitMouseButton::Right => PointerButton::Secondary,
             WinitMouseButton::Middle => PointerButton::Auxiliary,
-            WinitMouseButton::Back => PointerButton::X1,
-            WinitMouseButton::Forward => PointerButton::X2,
-            WinitMouseButton::Other(other) => {
-                warn!("Got winit MouseButton::Other({other}) which is not yet fully supported.");
-                PointerButton::Other
-            }
+            WinitMouseButton::Back => PointerButton::Back,
+            WinitMouseButton::Forward => PointerButton::Forward,
+            WinitMouseButton::Other(other) => PointerButton::Other(other),
         }
     }
 }
@@ -71,21 +91,152 @@ pub enum WindowState<'a> {
 /// The state of the Masonry application. If you run Masonry from an external Winit event loop, create a
 /// `MasonryState` via [`MasonryState::new`] and forward events to it via the appropriate method (e.g.,
 /// calling [`handle_window_event`](MasonryState::handle_window_event) in [`window_event`](ApplicationHandler::window_event)).
+///
+/// The debugging hotkeys below are off by default, as they write files and change what
+/// the app shows. Enable them with [`set_debug_hotkeys`](Self::set_debug_hotkeys).
+///
+/// Pressing <kbd>Ctrl</kbd>+<kbd>Shift</kbd>+<kbd>F12</kbd> writes a bug report bundle to a new
+/// `masonry-bug-report-*` directory in the working directory. The bundle holds a screenshot of
+/// the current frame, a dump of the widget tree, and the last events and actions handled by
+/// the app, so that users can attach it to issue reports.
+///
+/// Pressing <kbd>Ctrl</kbd>+<kbd>Shift</kbd>+<kbd>F11</kbd> does the same for the focused
+/// widget, in a `masonry-widget-report-*` directory with a screenshot of just that widget,
+/// and also copies the text of the report to the clipboard.
+/// See [`RenderRoot::write_widget_report`].
+///
+/// Pressing <kbd>Ctrl</kbd>+<kbd>Shift</kbd>+<kbd>F10</kbd> toggles paint flashing, which
+/// highlights the regions repainted in each frame. See [`RenderRoot::set_paint_flashing`].
+///
+/// Besides its main window, the app can have secondary windows, opened with
+/// [`open_window`](Self::open_window) or by widgets and drivers. Each window has its own
+/// [`RenderRoot`], and the actions of all windows are sent to the [`AppDriver`].
+/// Closing the main window exits the app.
 pub struct MasonryState<'a> {
     render_cx: RenderContext,
-    render_root: RenderRoot,
-    pointer_state: PointerState,
-    renderer: Option<Renderer>,
+    /// The renderers of the devices used by the windows, by device id.
+    renderers: Vec<Option<Renderer>>,
+    /// The adapters of the devices used by the windows, by device id.
+    gpu_adapters: Vec<Option<wgpu::Adapter>>,
     // TODO: Winit doesn't seem to let us create these proxies from within the loop
     // The reasons for this are unclear
     proxy: EventLoopProxy,
     #[cfg(feature = "tracy")]
     frame: Option<tracing_tracy::client::Frame>,
+    clipboard: SystemClipboard,
+    /// Whether the debugging hotkeys are enabled.
+    debug_hotkeys: bool,
+
+    /// The windows of the app. The first one is the main window.
+    windows: Vec<AppWindow<'a>>,
+}
 
-    // Per-Window state
-    // In future, this will support multiple windows
+/// A window of the app, with its own widget tree.
+struct AppWindow<'a> {
+    /// The id of a secondary window, or `None` for the main window.
+    id: Option<AppWindowId>,
+    render_root: RenderRoot,
+    pointer_state: PointerState,
     window: WindowState<'a>,
     background_color: Color,
+    /// Whether a bug report should be written on the next frame.
+    bug_report_requested: bool,
+    /// Whether a widget holds the pointer lock, in which case raw mouse motion is forwarded.
+    pointer_locked: bool,
+    /// Whether the window was created with a transparent background.
+    transparent: bool,
+    /// Whether the window catches pointer events, which it doesn't while the pointer
+    /// is outside of its shape, unless a widget holds the pointer lock.
+    cursor_hittest: bool,
+    /// Whether a widget asked for frames to be presented with low latency.
+    low_latency: bool,
+    /// The positions of the pointer received since the last [`PointerEvent::PointerMove`]
+    /// was sent, oldest first.
+    pending_pointer_moves: Vec<LogicalPosition<f64>>,
+}
+
+impl AppWindow<'_> {
+    fn new(
+        id: Option<AppWindowId>,
+        attributes: WindowAttributes,
+        root_widget: impl Widget,
+        background_color: Color,
+        formatter: Option<Arc<dyn LocaleFormatter>>,
+    ) -> Self {
+        // TODO: We can't know this scale factor until later?
+        let scale_factor = 1.0;
+
+        AppWindow {
+            id,
+            render_root: RenderRoot::new(
+                root_widget,
+                render_root::RenderRootOptions {
+                    use_system_fonts: true,
+                    size_policy: WindowSizePolicy::User,
+                    scale_factor,
+                    test_font: None,
+                    formatter,
+                    pixel_snapping: true,
+                },
+            ),
+            pointer_state: PointerState::empty(),
+            window: WindowState::Uninitialized(attributes),
+            background_color,
+            bug_report_requested: false,
+            pointer_locked: false,
+            transparent: false,
+            cursor_hittest: true,
+            low_latency: false,
+            pending_pointer_moves: Vec::new(),
+        }
+    }
+
+    /// The id winit gives to the window, if it was created.
+    fn winit_id(&self) -> Option<WindowId> {
+        match &self.window {
+            WindowState::Uninitialized(_) => None,
+            WindowState::Rendering { window, .. } | WindowState::Suspended { window, .. } => {
+                Some(window.id())
+            }
+        }
+    }
+
+    /// Let pointer events through the window while the pointer is outside of its shape,
+    /// unless a widget holds the pointer lock.
+    ///
+    /// Returns whether the window started or stopped catching pointer events.
+    fn update_cursor_hittest(&mut self) -> bool {
+        let hittest = self.pointer_locked
+            || (self.render_root).is_in_window_shape(self.pointer_state.position);
+        if hittest == self.cursor_hittest {
+            return false;
+        }
+        let WindowState::Rendering { window, .. } = &self.window else {
+            return false;
+        };
+        if let Err(err) = window.set_cursor_hittest(hittest) {
+            warn!("Failed to let pointer events through the window: {err}");
+            return false;
+        }
+        self.cursor_hittest = hittest;
+        true
+    }
+
+    /// Send a single [`PointerEvent::PointerMove`] for the cursor moves received since the
+    /// last one, with the intermediate positions in [`PointerState::coalesced`].
+    ///
+    /// Returns whether an event was sent.
+    fn send_pointer_moves(&mut self) -> bool {
+        // The last position is already the position of the pointer.
+        if self.pending_pointer_moves.pop().is_none() {
+            return false;
+        }
+        let mut state = self.pointer_state.clone();
+        state.coalesced = std::mem::take(&mut self.pending_pointer_moves);
+        self.render_root
+            .handle_pointer_event(PointerEvent::PointerMove(state));
+        true
+    }
 }
 
 struct MainState<'a> {
@@ -110,7 +261,7 @@ pub fn run(
     // Clearly, this API needs to be refactored, so we don't mind forcing this to be passed in here directly
     // This is passed in mostly to allow configuring the Android app
     mut loop_builder: EventLoopBuilder,
-    // In future, we intend to support multiple windows. At the moment though, we only support one
+    // The attributes of the main window. Secondary windows are opened at runtime.
     window_attributes: WindowAttributes,
     root_widget: impl Widget,
     app_driver: impl AppDriver + 'static,
@@ -138,6 +289,7 @@ pub fn run_with(
     // By now, we're about to take control of the event loop. The user is unlikely
     // to try to set their own subscriber once the event loop has started.
     let _ = crate::tracing_backend::try_init_tracing();
+    crate::close_signals::forward_close_signals(event_loop.create_proxy());
 
     let mut main_state = MainState {
         masonry_state: MasonryState::new(window, &event_loop, root_widget, background_color),
@@ -192,12 +344,12 @@ impl ApplicationHandler<MasonryUserEvent> for MainState<'_> {
             .handle_user_event(event_loop, event, self.app_driver.as_mut());
     }
 
-    // The following have empty handlers, but adding this here for future proofing. E.g., memory
-    // warning is very likely to be handled for mobile and we in particular want to make sure
-    // external event loops can let masonry handle these callbacks.
+    // The following mostly have empty handlers, but adding this here for future proofing.
+    // We in particular want to make sure external event loops can let masonry handle these callbacks.
 
     fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        self.masonry_state.handle_about_to_wait(event_loop);
+        self.masonry_state
+            .handle_about_to_wait(event_loop, self.app_driver.as_mut());
     }
 
     fn new_events(
@@ -213,7 +365,8 @@ impl ApplicationHandler<MasonryUserEvent> for MainState<'_> {
     }
 
     fn memory_warning(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        self.masonry_state.handle_memory_warning(event_loop);
+        self.masonry_state
+            .handle_memory_warning(event_loop, self.app_driver.as_mut());
     }
 }
 
@@ -225,40 +378,79 @@ impl MasonryState<'_> {
         background_color: Color,
     ) -> Self {
         let render_cx = RenderContext::new();
-        // TODO: We can't know this scale factor until later?
-        let scale_factor = 1.0;
 
         MasonryState {
             render_cx,
-            render_root: RenderRoot::new(
-                root_widget,
-                render_root::RenderRootOptions {
-                    use_system_fonts: true,
-                    size_policy: WindowSizePolicy::User,
-                    scale_factor,
-                    test_font: None,
-                },
-            ),
-            renderer: None,
+            renderers: Vec::new(),
+            gpu_adapters: Vec::new(),
+            proxy: event_loop.create_proxy(),
             #[cfg(feature = "tracy")]
             frame: None,
-            pointer_state: PointerState::empty(),
-            proxy: event_loop.create_proxy(),
-
-            window: WindowState::Uninitialized(window),
-            background_color,
+            clipboard: SystemClipboard::default(),
+            debug_hotkeys: false,
+            windows: vec![AppWindow::new(
+                None,
+                window,
+                root_widget,
+                background_color,
+                None,
+            )],
         }
     }
 
+    /// Open a secondary window showing `root_widget`.
+    ///
+    /// The window is created when the app resumes, or if it's already running, after the
+    /// next event is handled. Widgets and drivers can also open windows with
+    /// [`EventCtx::open_window`](crate::EventCtx::open_window) and
+    /// [`DriverCtx::open_window`](crate::DriverCtx::open_window).
+    pub fn open_window(
+        &mut self,
+        attributes: WindowAttributes,
+        root_widget: impl Widget,
+    ) -> AppWindowId {
+        let id = AppWindowId::next();
+        self.push_window(id, attributes, root_widget);
+        id
+    }
+
+    fn push_window(
+        &mut self,
+        id: AppWindowId,
+        attributes: WindowAttributes,
+        root_widget: impl Widget,
+    ) {
+        let main = &self.windows[0];
+        let formatter = main.render_root.global_state.formatter.clone();
+        let mut window = AppWindow::new(
+            Some(id),
+            attributes,
+            root_widget,
+            main.background_color,
+            Some(formatter),
+        );
+        window.render_root.set_record_actions(self.debug_hotkeys);
+        self.windows.push(window);
+    }
+
     // --- MARK: RESUMED ---
     pub fn handle_resumed(&mut self, event_loop: &ActiveEventLoop) {
+        for index in 0..self.windows.len() {
+            self.resume_window(event_loop, index);
+        }
+    }
+
+    /// Create the window at `index` if needed, and its surface.
+    fn resume_window(&mut self, event_loop: &ActiveEventLoop, index: usize) {
+        let win = &mut self.windows[index];
         match std::mem::replace(
-            &mut self.window,
+            &mut win.window,
             // TODO: Is there a better default value which could be used?
             WindowState::Uninitialized(WindowAttributes::default()),
         ) {
             WindowState::Uninitialized(attributes) => {
                 let visible = attributes.visible;
+                win.transparent = attributes.transparent;
                 let attributes = attributes.with_visible(false);
 
                 let window = event_loop.create_window(attributes).unwrap();
@@ -271,21 +463,43 @@ impl MasonryState<'_> {
                 let size = window.outer_size();
                 #[cfg(not(target_os = "ios"))]
                 let size = window.inner_size();
-                let surface = pollster::block_on(self.render_cx.create_surface(
+                let mut surface = pollster::block_on(self.render_cx.create_surface(
                     window.clone(),
                     size.width,
                     size.height,
                     PresentMode::AutoVsync,
                 ))
                 .unwrap();
+                let gpu_adapter = device_adapter(&self.render_cx, &mut self.gpu_adapters, &surface);
+                if let (true, Some(adapter)) = (win.transparent, gpu_adapter) {
+                    enable_surface_transparency(&self.render_cx, adapter, &mut surface);
+                }
+                if win.low_latency {
+                    set_surface_low_latency(&self.render_cx, gpu_adapter, &mut surface, true);
+                }
+                win.render_root
+                    .set_renderer_info(gpu_adapter.map(renderer_info));
                 let scale_factor = window.scale_factor();
-                self.window = WindowState::Rendering {
+                let monitors = enumerate_monitors(&window);
+                // Some platforms, e.g. Wayland, don't tell where windows are.
+                let window_position = window.inner_position().ok();
+                // Platforms which can't detect the color scheme keep the default one.
+                if let Some(theme) = window.theme() {
+                    win.render_root.set_color_scheme(window_theme(theme));
+                }
+                win.window = WindowState::Rendering {
                     window,
                     surface,
                     accesskit_adapter: adapter,
                 };
-                self.render_root
+                win.render_root
                     .handle_window_event(WindowEvent::Rescale(scale_factor));
+                win.render_root
+                    .handle_window_event(WindowEvent::MonitorsChanged(monitors));
+                if let Some(position) = window_position {
+                    win.render_root
+                        .handle_window_event(WindowEvent::Moved(position));
+                }
             }
             WindowState::Suspended {
                 window,
@@ -296,54 +510,84 @@ impl MasonryState<'_> {
                 let size = window.outer_size();
                 #[cfg(not(target_os = "ios"))]
                 let size = window.inner_size();
-                let surface = pollster::block_on(self.render_cx.create_surface(
+                let mut surface = pollster::block_on(self.render_cx.create_surface(
                     window.clone(),
                     size.width,
                     size.height,
                     PresentMode::AutoVsync,
                 ))
                 .unwrap();
-                self.window = WindowState::Rendering {
+                let gpu_adapter = device_adapter(&self.render_cx, &mut self.gpu_adapters, &surface);
+                if let (true, Some(adapter)) = (win.transparent, gpu_adapter) {
+                    enable_surface_transparency(&self.render_cx, adapter, &mut surface);
+                }
+                if win.low_latency {
+                    set_surface_low_latency(&self.render_cx, gpu_adapter, &mut surface, true);
+                }
+                win.render_root
+                    .set_renderer_info(gpu_adapter.map(renderer_info));
+                win.window = WindowState::Rendering {
                     window,
                     surface,
                     accesskit_adapter,
                 }
             }
-            _ => {
+            state @ WindowState::Rendering { .. } => {
                 // We have received a redundant resumed event. That's allowed by winit
+                win.window = state;
+            }
+        }
+    }
+
+    /// Create the windows opened since the app started running.
+    fn create_pending_windows(&mut self, event_loop: &ActiveEventLoop) {
+        // Windows can't be created while the app is suspended.
+        if !matches!(self.windows[0].window, WindowState::Rendering { .. }) {
+            return;
+        }
+        for index in 1..self.windows.len() {
+            if let WindowState::Uninitialized(_) = self.windows[index].window {
+                self.resume_window(event_loop, index);
+                if let WindowState::Rendering { window, .. } = &self.windows[index].window {
+                    window.request_redraw();
+                }
             }
         }
     }
 
     // --- MARK: SUSPENDED ---
     pub fn handle_suspended(&mut self, _event_loop: &ActiveEventLoop) {
-        match std::mem::replace(
-            &mut self.window,
-            // TODO: Is there a better default value which could be used?
-            WindowState::Uninitialized(WindowAttributes::default()),
-        ) {
-            WindowState::Rendering {
-                window,
-                surface,
-                accesskit_adapter,
-            } => {
-                drop(surface);
-                self.window = WindowState::Suspended {
+        for win in &mut self.windows {
+            match std::mem::replace(
+                &mut win.window,
+                // TODO: Is there a better default value which could be used?
+                WindowState::Uninitialized(WindowAttributes::default()),
+            ) {
+                WindowState::Rendering {
                     window,
+                    surface,
                     accesskit_adapter,
-                };
-            }
-            _ => {
-                // We have received a redundant resumed event. That's allowed by winit
+                } => {
+                    drop(surface);
+                    win.window = WindowState::Suspended {
+                        window,
+                        accesskit_adapter,
+                    };
+                }
+                state => {
+                    // We have received a redundant suspended event. That's allowed by winit
+                    win.window = state;
+                }
             }
         }
     }
 
     // --- MARK: RENDER ---
-    fn render(&mut self, scene: Scene) {
+    fn render(&mut self, index: usize, scene: Scene) {
+        let win = &mut self.windows[index];
         let WindowState::Rendering {
             window, surface, ..
-        } = &mut self.window
+        } = &mut win.window
         else {
             tracing::warn!("Tried to render whilst suspended or before window created");
             return;
@@ -370,6 +614,7 @@ impl MasonryState<'_> {
         };
         let scene_ref = transformed_scene.as_ref().unwrap_or(&scene);
 
+        let start = Instant::now();
         let Ok(surface_texture) = surface.surface.get_current_texture() else {
             warn!("failed to acquire next swapchain texture");
             return;
@@ -388,16 +633,19 @@ impl MasonryState<'_> {
             num_init_threads: NonZeroUsize::new(1),
         };
         let render_params = RenderParams {
-            base_color: self.background_color,
+            base_color: win.background_color,
             width,
             height,
             antialiasing_method: vello::AaConfig::Area,
         };
         // TODO: Run this in-between `submit` and `present`.
         window.pre_present_notify();
+        if self.renderers.len() <= dev_id {
+            self.renderers.resize_with(dev_id + 1, || None);
+        }
         {
             let _render_span = tracing::info_span!("Rendering using Vello").entered();
-            self.renderer
+            self.renderers[dev_id]
                 .get_or_insert_with(|| {
                     // Should be `expect`, when we up our MSRV.
                     #[cfg_attr(not(feature = "tracy"), allow(unused_mut))]
@@ -422,6 +670,13 @@ impl MasonryState<'_> {
         }
         surface_texture.present();
         device.poll(wgpu::Maintain::Wait);
+
+        // Vello renders to a target texture, which is copied to the surface texture.
+        // The surface keeps one texture per frame in flight, plus the one being presented.
+        let texture_size = u64::from(width) * u64::from(height) * 4;
+        let surface_textures = u64::from(surface.config.desired_maximum_frame_latency) + 1;
+        win.render_root
+            .record_present(start.elapsed(), texture_size * (1 + surface_textures));
         #[cfg(feature = "tracy")]
         drop(self.frame.take());
     }
@@ -430,15 +685,25 @@ impl MasonryState<'_> {
     pub fn handle_window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
-        _: WindowId,
+        window_id: WindowId,
         event: WinitWindowEvent,
         app_driver: &mut dyn AppDriver,
     ) {
+        let Some(index) = self
+            .windows
+            .iter()
+            .position(|win| win.winit_id() == Some(window_id))
+        else {
+            // Winit can still send events to a window we just closed.
+            debug!(?event, "Got event for unknown window {window_id:?}");
+            return;
+        };
+        let win = &mut self.windows[index];
         let WindowState::Rendering {
             window,
             accesskit_adapter,
             ..
-        } = &mut self.window
+        } = &mut win.window
         else {
             tracing::warn!(
                 ?event,
@@ -451,145 +716,238 @@ impl MasonryState<'_> {
             self.frame = Some(tracing_tracy::client::non_continuous_frame!("Masonry"));
         }
         accesskit_adapter.process_event(window, &event);
+        if !matches!(event, WinitWindowEvent::CursorMoved { .. }) {
+            win.send_pointer_moves();
+        }
 
         match event {
             WinitWindowEvent::ScaleFactorChanged { scale_factor, .. } => {
-                self.render_root
+                win.render_root
                     .handle_window_event(WindowEvent::Rescale(scale_factor));
+                // The window was likely moved to another monitor, which may have just been
+                // connected, so this is a good time to refresh the list.
+                win.render_root
+                    .handle_window_event(WindowEvent::MonitorsChanged(enumerate_monitors(window)));
             }
             WinitWindowEvent::RedrawRequested => {
                 let _span = info_span!("redraw");
-                self.render_root.handle_window_event(WindowEvent::AnimFrame);
-                let (scene, tree_update) = self.render_root.redraw();
-                self.render(scene);
+                win.render_root.handle_window_event(WindowEvent::AnimFrame);
+                let (scene, tree_update) = win.render_root.redraw();
+                if std::mem::take(&mut win.bug_report_requested) {
+                    self.write_bug_report(index, &scene);
+                }
+                self.render(index, scene);
                 let WindowState::Rendering {
                     accesskit_adapter, ..
-                } = &mut self.window
+                } = &mut self.windows[index].window
                 else {
                     debug_panic!("Suspended inside event");
                     return;
                 };
                 accesskit_adapter.update_if_active(|| tree_update);
+                if index == 0 {
+                    self.refresh_diagnostics_window();
+                }
+            }
+            WinitWindowEvent::CloseRequested if index == 0 => {
+                self.handle_close_request(event_loop, CloseReason::Window, app_driver);
+            }
+            WinitWindowEvent::CloseRequested => {
+                self.close_secondary_window(index, app_driver);
             }
-            WinitWindowEvent::CloseRequested => event_loop.exit(),
             WinitWindowEvent::Resized(size) => {
-                self.render_root
+                win.render_root
                     .handle_window_event(WindowEvent::Resize(size));
             }
+            // winit reports the position of the window's frame, but the position of the
+            // content area is what we need.
+            WinitWindowEvent::Moved(_) => {
+                if let Ok(position) = window.inner_position() {
+                    win.render_root
+                        .handle_window_event(WindowEvent::Moved(position));
+                }
+            }
             WinitWindowEvent::ModifiersChanged(modifiers) => {
-                self.pointer_state.mods = modifiers;
-                self.render_root
+                win.pointer_state.mods = modifiers;
+                win.render_root
                     .handle_text_event(TextEvent::ModifierChange(modifiers.state()));
             }
+            WinitWindowEvent::KeyboardInput { event, .. }
+                if self.debug_hotkeys
+                    && event.logical_key == Key::Named(NamedKey::F12)
+                    && win.pointer_state.mods.state().control_key()
+                    && win.pointer_state.mods.state().shift_key() =>
+            {
+                // The chord isn't forwarded to widgets.
+                win.bug_report_requested |= event.state.is_pressed() && !event.repeat;
+                window.request_redraw();
+            }
+            WinitWindowEvent::KeyboardInput { event, .. }
+                if self.debug_hotkeys
+                    && event.logical_key == Key::Named(NamedKey::F11)
+                    && win.pointer_state.mods.state().control_key()
+                    && win.pointer_state.mods.state().shift_key() =>
+            {
+                // The chord isn't forwarded to widgets.
+                let report = event.state.is_pressed() && !event.repeat;
+                if report {
+                    self.write_widget_report(index);
+                }
+            }
+            WinitWindowEvent::KeyboardInput { event, .. }
+                if self.debug_hotkeys
+                    && event.logical_key == Key::Named(NamedKey::F10)
+                    && win.pointer_state.mods.state().control_key()
+                    && win.pointer_state.mods.state().shift_key() =>
+            {
+                // The chord isn't forwarded to widgets.
+                let toggle = event.state.is_pressed() && !event.repeat;
+                if toggle {
+                    let enabled = win.render_root.paint_flashing();
+                    win.render_root.set_paint_flashing(!enabled);
+                }
+            }
             WinitWindowEvent::KeyboardInput {
                 device_id: _,
                 event,
                 is_synthetic: false, // TODO: Introduce an escape hatch for synthetic keys
             } => {
-                self.render_root.handle_text_event(TextEvent::KeyboardKey(
-                    event,
-                    self.pointer_state.mods.state(),
+                win.render_root.handle_text_event(TextEvent::KeyboardKey(
+                    event.into(),
+                    win.pointer_state.mods.state(),
                 ));
             }
             WinitWindowEvent::Ime(ime) => {
-                self.render_root.handle_text_event(TextEvent::Ime(ime));
+                win.render_root.handle_text_event(TextEvent::Ime(ime));
             }
             WinitWindowEvent::Focused(new_focus) => {
-                self.render_root
+                win.render_root
                     .handle_text_event(TextEvent::FocusChange(new_focus));
             }
             WinitWindowEvent::CursorEntered { .. } => {
-                self.render_root
-                    .handle_pointer_event(PointerEvent::PointerEnter(self.pointer_state.clone()));
+                win.render_root
+                    .handle_pointer_event(PointerEvent::PointerEnter(win.pointer_state.clone()));
             }
+            // Cursor moves are coalesced until another event arrives, or until winit is
+            // done with the current batch of events.
             WinitWindowEvent::CursorMoved { position, .. } => {
-                self.pointer_state.physical_position = position;
-                self.pointer_state.position = position.to_logical(window.scale_factor());
-                self.render_root
-                    .handle_pointer_event(PointerEvent::PointerMove(self.pointer_state.clone()));
+                win.pointer_state.physical_position = position;
+                win.pointer_state.position = position.to_logical(window.scale_factor());
+                win.pending_pointer_moves.push(win.pointer_state.position);
             }
             WinitWindowEvent::CursorLeft { .. } => {
-                self.render_root
-                    .handle_pointer_event(PointerEvent::PointerLeave(self.pointer_state.clone()));
+                win.render_root
+                    .handle_pointer_event(PointerEvent::PointerLeave(win.pointer_state.clone()));
             }
             WinitWindowEvent::MouseInput { state, button, .. } => match state {
                 winit::event::ElementState::Pressed => {
-                    self.render_root
+                    win.render_root
                         .handle_pointer_event(PointerEvent::PointerDown(
                             button.into(),
-                            self.pointer_state.clone(),
+                            win.pointer_state.clone(),
                         ));
                 }
                 winit::event::ElementState::Released => {
-                    self.render_root
+                    win.render_root
                         .handle_pointer_event(PointerEvent::PointerUp(
                             button.into(),
-                            self.pointer_state.clone(),
+                            win.pointer_state.clone(),
                         ));
                 }
             },
             WinitWindowEvent::MouseWheel { delta, .. } => {
-                let delta = match delta {
-                    winit::event::MouseScrollDelta::LineDelta(x, y) => {
-                        LogicalPosition::new(x as f64, y as f64)
-                    }
-                    winit::event::MouseScrollDelta::PixelDelta(delta) => {
-                        delta.to_logical(window.scale_factor())
-                    }
+                let (delta, kind) = match delta {
+                    winit::event::MouseScrollDelta::LineDelta(x, y) => (
+                        LogicalPosition::new(x as f64, y as f64),
+                        ScrollDeltaKind::Line,
+                    ),
+                    winit::event::MouseScrollDelta::PixelDelta(delta) => (
+                        delta.to_logical(window.scale_factor()),
+                        ScrollDeltaKind::Pixel,
+                    ),
                 };
-                self.render_root
+                win.render_root
                     .handle_pointer_event(PointerEvent::MouseWheel(
                         delta,
-                        self.pointer_state.clone(),
+                        kind,
+                        win.pointer_state.clone(),
                     ));
             }
             WinitWindowEvent::Touch(winit::event::Touch {
                 location,
                 phase,
                 force,
+                id,
                 ..
             }) => {
-                // FIXME: This is naïve and should be refined for actual use.
-                //        It will also interact with gesture discrimination.
-                self.pointer_state.physical_position = location;
-                self.pointer_state.position = location.to_logical(window.scale_factor());
-                self.pointer_state.force = force;
-                match phase {
-                    winit::event::TouchPhase::Started => {
-                        self.render_root
-                            .handle_pointer_event(PointerEvent::PointerMove(
-                                self.pointer_state.clone(),
-                            ));
-                        self.render_root
-                            .handle_pointer_event(PointerEvent::PointerDown(
-                                PointerButton::Primary,
-                                self.pointer_state.clone(),
-                            ));
-                    }
-                    winit::event::TouchPhase::Ended => {
-                        self.render_root
-                            .handle_pointer_event(PointerEvent::PointerUp(
-                                PointerButton::Primary,
-                                self.pointer_state.clone(),
-                            ));
-                    }
-                    winit::event::TouchPhase::Moved => {
-                        self.render_root
-                            .handle_pointer_event(PointerEvent::PointerMove(
-                                self.pointer_state.clone(),
-                            ));
-                    }
-                    winit::event::TouchPhase::Cancelled => {
-                        self.render_root
-                            .handle_pointer_event(PointerEvent::PointerLeave(
-                                self.pointer_state.clone(),
-                            ));
-                    }
-                }
+                // TODO: This will interact with gesture discrimination.
+                let mut state = win.pointer_state.clone();
+                state.physical_position = location;
+                state.position = location.to_logical(window.scale_factor());
+                state.force = force;
+                // Only styluses report their altitude, so that's how winit tells them apart
+                // from fingers.
+                state.pen = match force {
+                    Some(
+                        force @ Force::Calibrated {
+                            altitude_angle: Some(altitude_angle),
+                            ..
+                        },
+                    ) => Some(PenState {
+                        pressure: force.normalized(),
+                        altitude_angle,
+                        ..Default::default()
+                    }),
+                    _ => None,
+                };
+                win.render_root.handle_touch_event(id, phase, state);
             }
             WinitWindowEvent::PinchGesture { delta, .. } => {
-                self.render_root
-                    .handle_pointer_event(PointerEvent::Pinch(delta, self.pointer_state.clone()));
+                win.render_root
+                    .handle_pointer_event(PointerEvent::Pinch(delta, win.pointer_state.clone()));
+            }
+            WinitWindowEvent::RotationGesture { delta, .. } => {
+                // winit reports counterclockwise degrees.
+                let angle = -f64::from(delta).to_radians();
+                win.render_root
+                    .handle_pointer_event(PointerEvent::Rotate(angle, win.pointer_state.clone()));
+            }
+            WinitWindowEvent::PanGesture { delta, .. } => {
+                let delta = delta.cast::<f64>().to_logical::<f64>(window.scale_factor());
+                win.render_root.handle_pointer_event(PointerEvent::Pan(
+                    Vec2::new(delta.x, delta.y),
+                    win.pointer_state.clone(),
+                ));
+            }
+            // Files are sent to the widget under the pointer, then to the widgets
+            // which receive platform events.
+            WinitWindowEvent::HoveredFile(path) => {
+                win.render_root
+                    .handle_pointer_event(PointerEvent::HoverFile(
+                        path.clone(),
+                        win.pointer_state.clone(),
+                    ));
+                let position = win.render_root.last_mouse_pos;
+                win.render_root
+                    .handle_platform_event(PlatformEvent::FileHovered { path, position });
+            }
+            WinitWindowEvent::HoveredFileCancelled => {
+                win.render_root
+                    .handle_pointer_event(PointerEvent::HoverFileCancel(win.pointer_state.clone()));
+                win.render_root
+                    .handle_platform_event(PlatformEvent::FileHoverCancelled);
+            }
+            WinitWindowEvent::DroppedFile(path) => {
+                win.render_root.handle_pointer_event(PointerEvent::DropFile(
+                    path.clone(),
+                    win.pointer_state.clone(),
+                ));
+                let position = win.render_root.last_mouse_pos;
+                win.render_root
+                    .handle_platform_event(PlatformEvent::FileDropped { path, position });
+            }
+            WinitWindowEvent::ThemeChanged(theme) => {
+                win.render_root.set_color_scheme(window_theme(theme));
             }
             _ => (),
         }
@@ -597,14 +955,97 @@ impl MasonryState<'_> {
         self.handle_signals(event_loop, app_driver);
     }
 
+    // --- MARK: BUG REPORT ---
+    /// Write a bug report bundle with `scene` as the current frame of the window at `index`.
+    ///
+    /// See [`RenderRoot::write_bug_report`].
+    fn write_bug_report(&self, index: usize, scene: &Scene) {
+        let win = &self.windows[index];
+        let WindowState::Rendering { window, .. } = &win.window else {
+            return;
+        };
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let dir = PathBuf::from(format!("masonry-bug-report-{timestamp}"));
+
+        let size = window.inner_size();
+        let mut frame = Scene::new();
+        frame.append(scene, Some(Affine::scale(window.scale_factor())));
+        let result = win.render_root.write_bug_report(&dir).and_then(|()| {
+            render_scene(&frame, size.width, size.height, win.background_color)?
+                .save(dir.join("frame.png"))
+                .map_err(std::io::Error::other)
+        });
+        match result {
+            Ok(()) => info!("Wrote bug report to {}", dir.display()),
+            Err(err) => warn!("Failed to write bug report to {}: {err}", dir.display()),
+        }
+    }
+
+    /// Write a bug report bundle about the focused widget of the window at `index`, or its
+    /// root widget if no widget has focus, and copy its text report to the clipboard.
+    ///
+    /// See [`RenderRoot::write_widget_report`].
+    fn write_widget_report(&mut self, index: usize) {
+        let render_root = &mut self.windows[index].render_root;
+        let id = (render_root.global_state.focused_widget).unwrap_or_else(|| render_root.root.id());
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let dir = PathBuf::from(format!("masonry-widget-report-{timestamp}"));
+        match render_root.write_widget_report(id, &dir) {
+            Ok(report) => {
+                self.clipboard.set(ClipboardContent::text(report));
+                info!("Wrote report on widget {id} to {}", dir.display());
+            }
+            Err(err) => warn!("Failed to write widget report to {}: {err}", dir.display()),
+        }
+    }
+
     // --- MARK: DEVICE_EVENT ---
     pub fn handle_device_event(
         &mut self,
-        _: &ActiveEventLoop,
+        event_loop: &ActiveEventLoop,
         _: DeviceId,
-        _: WinitDeviceEvent,
-        _: &mut dyn AppDriver,
+        event: WinitDeviceEvent,
+        app_driver: &mut dyn AppDriver,
     ) {
+        if let WinitDeviceEvent::MouseMotion { delta: (x, y) } = event {
+            let mut any_locked = false;
+            let mut hittest_changed = false;
+            for win in &mut self.windows {
+                // While the pointer is outside of the window shape, the window doesn't get cursor
+                // moves, so the position of the pointer is estimated from the raw mouse motion,
+                // until it's back in the shape and the cursor moves tell where it is again.
+                if !win.cursor_hittest {
+                    if let WindowState::Rendering { window, .. } = &win.window {
+                        let position = &mut win.pointer_state.physical_position;
+                        position.x += x;
+                        position.y += y;
+                        win.pointer_state.position = position.to_logical(window.scale_factor());
+                    }
+                    hittest_changed |= win.update_cursor_hittest();
+                }
+                // Raw mouse motion is otherwise only used while the pointer is locked, as the
+                // cursor doesn't move.
+                if win.pointer_locked {
+                    win.render_root
+                        .handle_pointer_event(PointerEvent::LockedMove(
+                            Vec2::new(x, y),
+                            win.pointer_state.clone(),
+                        ));
+                    any_locked = true;
+                }
+            }
+            if any_locked {
+                self.handle_signals(event_loop, app_driver);
+            } else if hittest_changed {
+                self.update_device_events(event_loop);
+            }
+        }
     }
 
     // --- MARK: USER_EVENT ---
@@ -616,55 +1057,237 @@ impl MasonryState<'_> {
     ) {
         match event {
             MasonryUserEvent::AccessKit(event) => {
+                let Some(win) = self
+                    .windows
+                    .iter_mut()
+                    .find(|win| win.winit_id() == Some(event.window_id))
+                else {
+                    return;
+                };
                 match event.window_event {
                     // Note that this event can be called at any time, even multiple times if
                     // the user restarts their screen reader.
                     accesskit_winit::WindowEvent::InitialTreeRequested => {
-                        self.render_root
+                        win.render_root
                             .handle_window_event(WindowEvent::RebuildAccessTree);
                     }
                     accesskit_winit::WindowEvent::ActionRequested(action_request) => {
-                        self.render_root.handle_access_event(action_request);
+                        win.render_root.handle_access_event(action_request);
                     }
                     accesskit_winit::WindowEvent::AccessibilityDeactivated => {}
                 }
             }
-            MasonryUserEvent::Action(action, widget) => self
-                .render_root
-                .global_state
-                .signal_queue
-                .push_back(render_root::RenderRootSignal::Action(action, widget)),
+            // Actions sent from outside the event loop are ordered with the actions
+            // submitted by widgets, after the ones already emitted.
+            MasonryUserEvent::Action(action, widget) => {
+                self.windows[0]
+                    .render_root
+                    .global_state
+                    .submit_action(action, widget);
+            }
+            MasonryUserEvent::CloseRequested(reason) => {
+                self.handle_close_request(event_loop, reason, app_driver);
+            }
         }
 
         self.handle_signals(event_loop, app_driver);
     }
 
-    // --- MARK: EMPTY WINIT HANDLERS ---
-    pub fn handle_about_to_wait(&mut self, _: &ActiveEventLoop) {}
+    // --- MARK: TIMERS ---
+    pub fn handle_about_to_wait(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        app_driver: &mut dyn AppDriver,
+    ) {
+        let mut sent_pointer_moves = false;
+        for win in &mut self.windows {
+            sent_pointer_moves |= win.send_pointer_moves();
+        }
+        let now = Instant::now();
+        let mut ran_timers = false;
+        for win in &mut self.windows {
+            if win
+                .render_root
+                .next_timer_deadline()
+                .is_some_and(|deadline| deadline <= now)
+            {
+                win.render_root.run_timers();
+                ran_timers = true;
+            }
+        }
+        // Signals left over by `handle_signals` are handled in the next iteration.
+        if sent_pointer_moves || ran_timers || self.has_pending_signals() {
+            self.handle_signals(event_loop, app_driver);
+        }
+        // Wake up for the next timer, if any.
+        let next_deadline = self
+            .windows
+            .iter()
+            .filter_map(|win| win.render_root.next_timer_deadline())
+            .min();
+        let control_flow = match next_deadline {
+            _ if self.has_pending_signals() => ControlFlow::Poll,
+            Some(deadline) => ControlFlow::WaitUntil(deadline),
+            None => ControlFlow::Wait,
+        };
+        event_loop.set_control_flow(control_flow);
+    }
 
+    // --- MARK: EMPTY WINIT HANDLERS ---
     pub fn handle_new_events(&mut self, _: &ActiveEventLoop, _: winit::event::StartCause) {}
 
     pub fn handle_exiting(&mut self, _: &ActiveEventLoop) {}
 
-    pub fn handle_memory_warning(&mut self, _: &ActiveEventLoop) {}
+    pub fn handle_memory_warning(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        app_driver: &mut dyn AppDriver,
+    ) {
+        for win in &mut self.windows {
+            win.render_root
+                .handle_platform_event(PlatformEvent::MemoryWarning);
+        }
+        self.handle_signals(event_loop, app_driver);
+    }
+
+    fn handle_close_request(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        reason: CloseReason,
+        app_driver: &mut dyn AppDriver,
+    ) {
+        let response = with_driver_ctx(&mut self.windows, None, |driver_ctx| {
+            app_driver.on_close_requested(driver_ctx, reason)
+        });
+        if response == CloseResponse::Close {
+            event_loop.exit();
+        }
+    }
+
+    /// Tell the driver that the secondary window at `index` is closed, then close it.
+    ///
+    /// The driver is called first, so that it can still edit the widget tree of the window.
+    fn close_secondary_window(&mut self, index: usize, app_driver: &mut dyn AppDriver) {
+        if index == 0 {
+            debug_panic!("Tried to close the main window as a secondary window");
+            return;
+        }
+        let Some(id) = self.windows[index].id else {
+            self.windows.remove(index);
+            return;
+        };
+        with_driver_ctx(&mut self.windows, None, |driver_ctx| {
+            app_driver.on_window_closed(driver_ctx, id);
+        });
+        // Dropping the window closes it.
+        if let Some(index) = self.windows.iter().position(|win| win.id == Some(id)) {
+            self.windows.remove(index);
+        }
+    }
+
+    /// Show the diagnostics report of the main window in the diagnostics window, if open.
+    fn refresh_diagnostics_window(&mut self) {
+        let Some(id) = self.windows[0].render_root.global_state.diagnostics_window else {
+            return;
+        };
+        let Some(index) = self.windows.iter().position(|win| win.id == Some(id)) else {
+            // The window was closed.
+            self.windows[0].render_root.global_state.diagnostics_window = None;
+            return;
+        };
+        let report = self.windows[0].render_root.diagnostics_report();
+        let win = &mut self.windows[index];
+        win.render_root.edit_root_widget(|mut root| {
+            let mut portal = root.downcast::<Portal<Prose>>();
+            let mut prose = Portal::child_mut(&mut portal);
+            Prose::set_text(&mut prose, report.into());
+        });
+        if let WindowState::Rendering { window, .. } = &win.window {
+            window.request_redraw();
+        }
+    }
 
     // --- MARK: SIGNALS ---
-    fn handle_signals(&mut self, _event_loop: &ActiveEventLoop, app_driver: &mut dyn AppDriver) {
-        let WindowState::Rendering { window, .. } = &mut self.window else {
+    fn handle_signals(&mut self, event_loop: &ActiveEventLoop, app_driver: &mut dyn AppDriver) {
+        self.handle_all_window_signals(event_loop, app_driver);
+        // The pointer, the window shapes and the pointer lock may have changed.
+        let mut hittest_changed = false;
+        for win in &mut self.windows {
+            hittest_changed |= win.update_cursor_hittest();
+        }
+        if hittest_changed {
+            self.update_device_events(event_loop);
+        }
+    }
+
+    /// Listen to raw mouse motion while the pointer is outside of the shape of a window,
+    /// which is how the window knows the pointer may be back, and only while the app is
+    /// focused otherwise, which is winit's default.
+    fn update_device_events(&self, event_loop: &ActiveEventLoop) {
+        let outside_shape = self.windows.iter().any(|win| !win.cursor_hittest);
+        event_loop.listen_device_events(if outside_shape {
+            DeviceEvents::Always
+        } else {
+            DeviceEvents::WhenFocused
+        });
+    }
+
+    fn handle_all_window_signals(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        app_driver: &mut dyn AppDriver,
+    ) {
+        // The main window goes last, as the actions of all windows are handled by editing
+        // its widget tree, which may emit more signals. The driver can also edit the
+        // widget trees of secondary windows, so we go on until all signals are handled,
+        // up to a limit so that windows emitting signals in response to each other can't
+        // stall the event loop. The remaining signals are handled in `handle_about_to_wait`.
+        for _ in 0..MAX_SIGNAL_ROUNDS {
+            for index in (0..self.windows.len()).rev() {
+                // Windows after this one may have been closed in the meantime.
+                if index < self.windows.len() {
+                    self.handle_window_signals(event_loop, index, app_driver);
+                }
+            }
+            if !self.has_pending_signals() {
+                break;
+            }
+        }
+        self.create_pending_windows(event_loop);
+    }
+
+    /// Whether a window which can handle signals has emitted some which aren't handled yet.
+    fn has_pending_signals(&self) -> bool {
+        self.windows.iter().any(|win| {
+            matches!(win.window, WindowState::Rendering { .. })
+                && win.render_root.has_pending_signals()
+        })
+    }
+
+    fn handle_window_signals(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        index: usize,
+        app_driver: &mut dyn AppDriver,
+    ) {
+        let WindowState::Rendering { window, .. } = &self.windows[index].window else {
             tracing::warn!("Tried to handle a signal whilst suspended or before window created");
             return;
         };
+        let window = window.clone();
 
         let mut needs_redraw = false;
-        while let Some(signal) = self.render_root.pop_signal() {
+        let mut needs_accessibility_update = false;
+        let mut close = false;
+        let mut closed_windows = Vec::new();
+        while let Some(signal) = self.windows[index].render_root.pop_signal() {
+            let win = &mut self.windows[index];
             match signal {
-                render_root::RenderRootSignal::Action(action, widget_id) => {
-                    self.render_root.edit_root_widget(|root| {
-                        debug!("Action {:?} on widget {:?}", action, widget_id);
-                        let mut driver_ctx = DriverCtx {
-                            main_root_widget: root,
-                        };
-                        app_driver.on_action(&mut driver_ctx, widget_id, action);
+                render_root::RenderRootSignal::Action(action, widget_id, sequence) => {
+                    let window_id = win.id;
+                    debug!("Action #{sequence} {:?} on widget {:?}", action, widget_id);
+                    with_driver_ctx(&mut self.windows, window_id, |driver_ctx| {
+                        app_driver.on_action(driver_ctx, widget_id, action);
                     });
                 }
                 render_root::RenderRootSignal::StartIme => {
@@ -679,6 +1302,9 @@ impl MasonryState<'_> {
                 render_root::RenderRootSignal::RequestRedraw => {
                     needs_redraw = true;
                 }
+                render_root::RenderRootSignal::RequestAccessibilityUpdate => {
+                    needs_accessibility_update = true;
+                }
                 render_root::RenderRootSignal::RequestAnimFrame => {
                     // TODO
                     needs_redraw = true;
@@ -696,6 +1322,44 @@ impl MasonryState<'_> {
                 render_root::RenderRootSignal::SetTitle(title) => {
                     window.set_title(&title);
                 }
+                render_root::RenderRootSignal::SetResizeIncrements(increments) => {
+                    window.set_resize_increments(increments);
+                }
+                render_root::RenderRootSignal::PlaceWindow(placement) => {
+                    place_window(&window, placement, win.pointer_state.position);
+                }
+                render_root::RenderRootSignal::CloseWindow if index == 0 => {
+                    event_loop.exit();
+                }
+                render_root::RenderRootSignal::CloseWindow => {
+                    close = true;
+                }
+                render_root::RenderRootSignal::OpenWindow(id, attributes, root_widget) => {
+                    self.push_window(id, *attributes, root_widget);
+                }
+                render_root::RenderRootSignal::CloseAppWindow(id) => {
+                    closed_windows.push(id);
+                }
+                render_root::RenderRootSignal::SetClipboard(content) => {
+                    self.clipboard.set(content);
+                }
+                render_root::RenderRootSignal::RequestPaste => {
+                    if let Some(text) = self.clipboard.get() {
+                        win.render_root.handle_text_event(TextEvent::Paste(text));
+                    }
+                }
+                render_root::RenderRootSignal::SetPointerLock(locked) => {
+                    set_pointer_lock(&window, locked);
+                    win.pointer_locked = locked;
+                }
+                render_root::RenderRootSignal::SetLowLatency(low_latency) => {
+                    if let WindowState::Rendering { surface, .. } = &mut win.window {
+                        let adapter =
+                            device_adapter(&self.render_cx, &mut self.gpu_adapters, surface);
+                        set_surface_low_latency(&self.render_cx, adapter, surface, low_latency);
+                    }
+                    win.low_latency = low_latency;
+                }
             }
         }
 
@@ -703,20 +1367,245 @@ impl MasonryState<'_> {
         // We batch them up to avoid redundant requests.
         if needs_redraw {
             window.request_redraw();
+        } else if needs_accessibility_update {
+            // Nothing changed visually, so we don't paint and present a frame.
+            let win = &mut self.windows[index];
+            let tree_update = win.render_root.update_accessibility();
+            if let WindowState::Rendering {
+                accesskit_adapter, ..
+            } = &mut win.window
+            {
+                accesskit_adapter.update_if_active(|| tree_update);
+            }
+        }
+
+        if close {
+            self.close_secondary_window(index, app_driver);
+        }
+        for id in closed_windows {
+            match self.windows.iter().position(|win| win.id == Some(id)) {
+                Some(index) => self.close_secondary_window(index, app_driver),
+                None => debug!("Tried to close window {id:?}, which is already closed"),
+            }
         }
     }
 
     pub fn get_window_state(&self) -> &WindowState {
-        &self.window
+        &self.windows[0].window
     }
 
     pub fn get_root(&mut self) -> &mut RenderRoot {
-        &mut self.render_root
+        &mut self.windows[0].render_root
+    }
+
+    /// Enable or disable the debugging hotkeys, which are off by default.
+    ///
+    /// See [`MasonryState`] for the list of hotkeys. Apps can enable them in
+    /// [`AppDriver::on_start`], e.g. only in debug builds or when an environment variable is set.
+    ///
+    /// The windows also [record the actions](RenderRoot::set_record_actions) of their widgets
+    /// while the hotkeys are enabled, for the reports.
+    pub fn set_debug_hotkeys(&mut self, enabled: bool) {
+        self.debug_hotkeys = enabled;
+        for win in &mut self.windows {
+            win.render_root.set_record_actions(enabled);
+        }
     }
 
     pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
-        if let WindowState::Rendering { surface, .. } = &mut self.window {
+        if let WindowState::Rendering { surface, .. } = &mut self.windows[0].window {
             self.render_cx.set_present_mode(surface, present_mode);
         }
     }
 }
+
+/// Run `f` with a [`DriverCtx`] for an action from `window`, or from the main window if `None`.
+fn with_driver_ctx<R>(
+    windows: &mut [AppWindow<'_>],
+    window: Option<AppWindowId>,
+    f: impl FnOnce(&mut DriverCtx<'_>) -> R,
+) -> R {
+    let (main, secondary) = windows
+        .split_first_mut()
+        .expect("The main window is never removed");
+    let secondary_windows = secondary
+        .iter_mut()
+        .filter_map(|win| Some((win.id?, &mut win.render_root)))
+        .collect();
+    main.render_root
+        .edit_root_widget(|root| f(&mut DriverCtx::new(root, window, secondary_windows)))
+}
+
+// --- MARK: THEME ---
+fn window_theme(theme: winit::window::Theme) -> WindowTheme {
+    match theme {
+        winit::window::Theme::Light => WindowTheme::Light,
+        winit::window::Theme::Dark => WindowTheme::Dark,
+    }
+}
+
+// --- MARK: MONITORS ---
+/// The monitors of the system, in the order used by [`WindowPlacement`].
+fn enumerate_monitors(window: &Window) -> Vec<MonitorInfo> {
+    let primary = window.primary_monitor();
+    window
+        .available_monitors()
+        .map(|monitor| MonitorInfo {
+            name: monitor.name(),
+            position: monitor.position(),
+            size: monitor.size(),
+            scale_factor: monitor.scale_factor(),
+            is_primary: primary.as_ref() == Some(&monitor),
+        })
+        .collect()
+}
+
+/// Present frames as soon as they are rendered, replacing the queued frame if any,
+/// if the surface supports it, or go back to the default presentation.
+fn set_surface_low_latency(
+    render_cx: &RenderContext,
+    adapter: Option<&wgpu::Adapter>,
+    surface: &mut RenderSurface<'_>,
+    low_latency: bool,
+) {
+    let mut present_mode = PresentMode::AutoVsync;
+    if let (true, Some(adapter)) = (low_latency, adapter) {
+        let capabilities = surface.surface.get_capabilities(adapter);
+        if capabilities.present_modes.contains(&PresentMode::Mailbox) {
+            present_mode = PresentMode::Mailbox;
+        }
+    }
+    // This is the default of wgpu.
+    surface.config.desired_maximum_frame_latency = if low_latency { 1 } else { 2 };
+    // This also applies the new frame latency.
+    render_cx.set_present_mode(surface, present_mode);
+}
+
+/// The adapter of the device rendering to the surface.
+///
+/// Vello creates the device of a surface from the adapter wgpu picks for that surface,
+/// so the same adapter is requested the first time a device is used, and kept after that.
+fn device_adapter<'a>(
+    render_cx: &RenderContext,
+    gpu_adapters: &'a mut Vec<Option<wgpu::Adapter>>,
+    surface: &RenderSurface<'_>,
+) -> Option<&'a wgpu::Adapter> {
+    let dev_id = surface.dev_id;
+    if gpu_adapters.len() <= dev_id {
+        gpu_adapters.resize_with(dev_id + 1, || None);
+    }
+    if gpu_adapters[dev_id].is_none() {
+        gpu_adapters[dev_id] =
+            pollster::block_on(wgpu::util::initialize_adapter_from_env_or_default(
+                &render_cx.instance,
+                Some(&surface.surface),
+            ));
+    }
+    gpu_adapters[dev_id].as_ref()
+}
+
+/// Describe the graphics adapter and backend rendering to a surface.
+fn renderer_info(adapter: &wgpu::Adapter) -> String {
+    let info = adapter.get_info();
+    format!(
+        "{} ({:?}, {:?}), driver: {} {}",
+        info.name, info.backend, info.device_type, info.driver, info.driver_info
+    )
+}
+
+/// Let the compositor blend the window with what's behind it, if the surface supports it.
+fn enable_surface_transparency(
+    render_cx: &RenderContext,
+    adapter: &wgpu::Adapter,
+    surface: &mut RenderSurface<'_>,
+) {
+    let capabilities = surface.surface.get_capabilities(adapter);
+    // Vello renders with straight alpha.
+    let alpha_mode = [
+        CompositeAlphaMode::PostMultiplied,
+        CompositeAlphaMode::PreMultiplied,
+    ]
+    .into_iter()
+    .find(|mode| capabilities.alpha_modes.contains(mode));
+    let Some(alpha_mode) = alpha_mode else {
+        warn!("The window surface doesn't support transparency");
+        return;
+    };
+    surface.config.alpha_mode = alpha_mode;
+    let (width, height) = (surface.config.width, surface.config.height);
+    render_cx.resize_surface(surface, width, height);
+}
+
+fn set_pointer_lock(window: &Window, locked: bool) {
+    let result = if locked {
+        // Not all platforms can lock the cursor, e.g. X11 can only confine it.
+        window
+            .set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+    } else {
+        window.set_cursor_grab(CursorGrabMode::None)
+    };
+    if let Err(err) = result {
+        warn!("Failed to set the pointer lock: {err}");
+    }
+    window.set_cursor_visible(!locked);
+}
+
+fn place_window(window: &Window, placement: WindowPlacement, pointer: LogicalPosition<f64>) {
+    let monitor = |index: Option<usize>| match index {
+        Some(index) => window.available_monitors().nth(index),
+        None => window
+            .primary_monitor()
+            .or_else(|| window.current_monitor()),
+    };
+    let position = match placement {
+        WindowPlacement::Centered {
+            monitor: index,
+            size,
+        } => {
+            let Some(monitor) = monitor(index) else {
+                warn!("Cannot center window on unknown monitor {index:?}");
+                return;
+            };
+            let window_size = match size {
+                Some(size) => {
+                    let size = size.to_physical(monitor.scale_factor());
+                    // TODO - Handle return value?
+                    let _ = window.request_inner_size(size);
+                    size
+                }
+                None => window.outer_size(),
+            };
+            let origin = monitor.position();
+            let free_width = monitor.size().width as i32 - window_size.width as i32;
+            let free_height = monitor.size().height as i32 - window_size.height as i32;
+            PhysicalPosition::new(origin.x + free_width / 2, origin.y + free_height / 2)
+        }
+        WindowPlacement::OnMonitor {
+            monitor: index,
+            position,
+            size,
+        } => {
+            let Some(monitor) = monitor(Some(index)) else {
+                warn!("Cannot place window on unknown monitor {index}");
+                return;
+            };
+            if let Some(size) = size {
+                let _ = window.request_inner_size(size.to_physical::<u32>(monitor.scale_factor()));
+            }
+            let origin = monitor.position();
+            let offset = position.to_physical::<i32>(monitor.scale_factor());
+            PhysicalPosition::new(origin.x + offset.x, origin.y + offset.y)
+        }
+        WindowPlacement::AtPointer { offset } => {
+            let Ok(origin) = window.inner_position() else {
+                warn!("Cannot place window at the pointer: the window position is unknown");
+                return;
+            };
+            let pointer = LogicalPosition::new(pointer.x + offset.x, pointer.y + offset.y)
+                .to_physical::<i32>(window.scale_factor());
+            PhysicalPosition::new(origin.x + pointer.x, origin.y + pointer.y)
+        }
+    };
+    window.set_outer_position(position);
+}
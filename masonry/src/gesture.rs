@@ -0,0 +1,551 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recognition of taps, long presses, drags and pinches from pointer events, and prediction
+//! of pointer positions.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use vello::kurbo::{Point, Vec2};
+
+use crate::{PointerButton, PointerEvent};
+
+/// How far the pointer must move while pressed before a press becomes a drag, in logical pixels.
+pub const DRAG_THRESHOLD: f64 = 4.0;
+
+/// The longest time between two presses for them to count as a double tap.
+pub const MULTI_TAP_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the pointer must stay pressed without moving for a press to become a long press.
+pub const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+
+/// The time span of the pointer positions used to measure the velocity of a drag.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+
+/// How far ahead a [`PointerPredictor`] will predict the pointer position.
+///
+/// Predictions further ahead are too unreliable to be useful.
+pub const MAX_PREDICTION: Duration = Duration::from_millis(50);
+
+/// A gesture recognized by a [`GestureRecognizer`].
+///
+/// Positions are in the local coordinates of the widget which recognized the gesture,
+/// and velocities are in logical pixels per second.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Gesture {
+    /// The primary button was pressed and released without moving the pointer.
+    ///
+    /// `count` is the number of taps in quick succession at the same place, e.g. 2 for
+    /// a double click and 3 for a triple click. Every tap is reported, so a double tap is
+    /// preceded by a tap with a `count` of 1.
+    Tap { position: Point, count: u8 },
+    /// The primary button stayed pressed without moving the pointer for the
+    /// [`long_press_duration`](GestureConfig::long_press_duration).
+    ///
+    /// Releasing the button afterwards doesn't report a tap, but moving the pointer
+    /// still starts a drag.
+    LongPress { position: Point },
+    /// The pointer moved further than the [`drag_threshold`](GestureConfig::drag_threshold)
+    /// while the primary button was pressed.
+    ///
+    /// `position` is where the button was pressed.
+    DragStart { position: Point },
+    /// The pointer moved during a drag.
+    DragUpdate {
+        position: Point,
+        delta: Vec2,
+        velocity: Vec2,
+    },
+    /// The primary button was released, ending a drag.
+    ///
+    /// `velocity` is the velocity of the pointer right before the release, which can
+    /// be used to fling or dismiss content.
+    DragEnd { position: Point, velocity: Vec2 },
+    /// A drag was interrupted, e.g. because the pointer left the window.
+    DragCancel,
+    /// A pinch gesture on a touchpad, or of two fingers on a touchscreen.
+    ///
+    /// A positive `scale_delta` means the content should get bigger.
+    Pinch { position: Point, scale_delta: f64 },
+    /// A rotation gesture on a touchpad, or of two fingers on a touchscreen.
+    ///
+    /// `angle_delta` is in radians, clockwise on screen.
+    Rotate { position: Point, angle_delta: f64 },
+    /// A pan gesture on a touchpad, or two fingers moving together on a touchscreen.
+    Pan { position: Point, delta: Vec2 },
+}
+
+/// The thresholds of the gestures of the primary pointer, set with
+/// [`RenderRoot::set_gesture_config`](crate::RenderRoot::set_gesture_config).
+///
+/// These are applied by the event pass, which sets [`PointerState::count`](crate::PointerState::count)
+/// and sends [`PointerEvent::LongPress`] and [`PointerEvent::DragStart`] to all widgets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// How far the pointer must move while pressed before a press becomes a drag,
+    /// in logical pixels.
+    pub drag_threshold: f64,
+    /// The longest time between two presses for them to be counted as the same
+    /// [`Tap`](Gesture::Tap).
+    pub multi_tap_interval: Duration,
+    /// How far apart two presses can be for them to be counted as the same
+    /// [`Tap`](Gesture::Tap), in logical pixels.
+    pub multi_tap_distance: f64,
+    /// How long the pointer must stay pressed before a press becomes a
+    /// [`LongPress`](Gesture::LongPress).
+    pub long_press_duration: Duration,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            drag_threshold: DRAG_THRESHOLD,
+            multi_tap_interval: MULTI_TAP_INTERVAL,
+            multi_tap_distance: DRAG_THRESHOLD,
+            long_press_duration: LONG_PRESS_DURATION,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Phase {
+    Idle,
+    Pressed { start: Point, long_pressed: bool },
+    Dragging { last: Point },
+}
+
+/// Turns the pointer events received by a widget into [`Gesture`]s.
+///
+/// This is used by [`GestureDetector`](crate::widget::GestureDetector), and can be used
+/// by other widgets handling gestures themselves.
+///
+/// The thresholds of the [`GestureConfig`] are applied by the event pass: taps are counted
+/// with [`PointerState::count`](crate::PointerState::count), and long presses and drags start
+/// with [`PointerEvent::LongPress`] and [`PointerEvent::DragStart`].
+#[derive(Debug)]
+pub struct GestureRecognizer {
+    phase: Phase,
+    velocity: VelocityTracker,
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self {
+            phase: Phase::Idle,
+            velocity: VelocityTracker::default(),
+        }
+    }
+
+    /// Whether the primary button is pressed, or a drag is in progress.
+    pub fn is_active(&self) -> bool {
+        !matches!(self.phase, Phase::Idle)
+    }
+
+    /// Feed `event` to the recognizer, and return the gesture it completes, if any.
+    ///
+    /// `origin` is the position of the widget in the window, and `now` is the time at
+    /// which the event was received.
+    pub fn handle_pointer_event(
+        &mut self,
+        event: &PointerEvent,
+        origin: Point,
+        now: Instant,
+    ) -> Option<Gesture> {
+        let local = |pos: &dpi::LogicalPosition<f64>| Point::new(pos.x, pos.y) - origin.to_vec2();
+        match event {
+            PointerEvent::PointerDown(PointerButton::Primary, state) => {
+                let position = local(&state.position);
+                self.phase = Phase::Pressed {
+                    start: position,
+                    long_pressed: false,
+                };
+                self.velocity.reset(now, position);
+                None
+            }
+            PointerEvent::LongPress(_) => {
+                let Phase::Pressed {
+                    start,
+                    long_pressed: false,
+                } = self.phase
+                else {
+                    return None;
+                };
+                self.phase = Phase::Pressed {
+                    start,
+                    long_pressed: true,
+                };
+                Some(Gesture::LongPress { position: start })
+            }
+            PointerEvent::DragStart(_) => {
+                let Phase::Pressed { start, .. } = self.phase else {
+                    return None;
+                };
+                self.phase = Phase::Dragging { last: start };
+                Some(Gesture::DragStart { position: start })
+            }
+            PointerEvent::PointerMove(state) => {
+                let position = local(&state.position);
+                match self.phase {
+                    Phase::Idle => None,
+                    Phase::Pressed { .. } => {
+                        self.velocity.add(now, position);
+                        None
+                    }
+                    Phase::Dragging { last } => {
+                        self.velocity.add(now, position);
+                        self.phase = Phase::Dragging { last: position };
+                        Some(Gesture::DragUpdate {
+                            position,
+                            delta: position - last,
+                            velocity: self.velocity.velocity(),
+                        })
+                    }
+                }
+            }
+            PointerEvent::PointerUp(PointerButton::Primary, state) => {
+                let position = local(&state.position);
+                let gesture = match self.phase {
+                    Phase::Idle
+                    | Phase::Pressed {
+                        long_pressed: true, ..
+                    } => None,
+                    Phase::Pressed { .. } => Some(Gesture::Tap {
+                        position,
+                        count: state.count.max(1),
+                    }),
+                    Phase::Dragging { .. } => {
+                        self.velocity.add(now, position);
+                        Some(Gesture::DragEnd {
+                            position,
+                            velocity: self.velocity.velocity(),
+                        })
+                    }
+                };
+                self.phase = Phase::Idle;
+                gesture
+            }
+            PointerEvent::PointerLeave(_) => {
+                let was_dragging = matches!(self.phase, Phase::Dragging { .. });
+                self.phase = Phase::Idle;
+                was_dragging.then_some(Gesture::DragCancel)
+            }
+            PointerEvent::Pinch(delta, state) => Some(Gesture::Pinch {
+                position: local(&state.position),
+                scale_delta: *delta,
+            }),
+            PointerEvent::Rotate(delta, state) => Some(Gesture::Rotate {
+                position: local(&state.position),
+                angle_delta: *delta,
+            }),
+            PointerEvent::Pan(delta, state) => Some(Gesture::Pan {
+                position: local(&state.position),
+                delta: *delta,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Predicts where the pointer will be in the near future while the primary button is pressed.
+///
+/// Drawing a stroke up to the predicted position hides some of the latency between the
+/// pen moving and the stroke appearing on screen. Inking widgets should combine this with
+/// [`EventCtx::set_low_latency`](crate::EventCtx::set_low_latency), and replace the
+/// predicted part of the stroke once the real pointer events arrive.
+#[derive(Debug, Default)]
+pub struct PointerPredictor {
+    /// Whether the primary button is pressed.
+    pressed: bool,
+    velocity: VelocityTracker,
+}
+
+impl PointerPredictor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed `event` to the predictor.
+    ///
+    /// `origin` is the position of the widget in the window, and `now` is the time at
+    /// which the event was received. The [coalesced](crate::PointerState::coalesced)
+    /// positions of move events are used too.
+    pub fn handle_pointer_event(&mut self, event: &PointerEvent, origin: Point, now: Instant) {
+        let local = |pos: &dpi::LogicalPosition<f64>| Point::new(pos.x, pos.y) - origin.to_vec2();
+        match event {
+            PointerEvent::PointerDown(PointerButton::Primary, state) => {
+                self.pressed = true;
+                self.velocity.reset(now, local(&state.position));
+            }
+            PointerEvent::PointerMove(state) if self.pressed => {
+                // The coalesced positions don't have their own time, so they are
+                // spread evenly since the previous event.
+                let previous = self.velocity.last().map_or(now, |(time, _)| time);
+                let step =
+                    now.saturating_duration_since(previous) / (state.coalesced.len() as u32 + 1);
+                for (i, position) in state.coalesced.iter().enumerate() {
+                    self.velocity
+                        .add(previous + step * (i as u32 + 1), local(position));
+                }
+                self.velocity.add(now, local(&state.position));
+            }
+            PointerEvent::PointerUp(PointerButton::Primary, _) | PointerEvent::PointerLeave(_) => {
+                self.pressed = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// The predicted position of the pointer `ahead` of the last event, in local coordinates.
+    ///
+    /// `ahead` is clamped to [`MAX_PREDICTION`]. Returns `None` if the primary button isn't pressed.
+    pub fn predict(&self, ahead: Duration) -> Option<Point> {
+        if !self.pressed {
+            return None;
+        }
+        let (_, last) = self.velocity.last()?;
+        let ahead = ahead.min(MAX_PREDICTION).as_secs_f64();
+        Some(last + self.velocity.velocity() * ahead)
+    }
+}
+
+/// Measures the velocity of the pointer from its recent positions.
+#[derive(Debug, Default)]
+struct VelocityTracker {
+    samples: VecDeque<(Instant, Point)>,
+}
+
+impl VelocityTracker {
+    fn reset(&mut self, now: Instant, position: Point) {
+        self.samples.clear();
+        self.samples.push_back((now, position));
+    }
+
+    fn add(&mut self, now: Instant, position: Point) {
+        self.samples.push_back((now, position));
+        while let Some(&(time, _)) = self.samples.front() {
+            if now.duration_since(time) <= VELOCITY_WINDOW || self.samples.len() <= 2 {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    fn last(&self) -> Option<(Instant, Point)> {
+        self.samples.back().copied()
+    }
+
+    fn velocity(&self) -> Vec2 {
+        let (Some(&(t0, p0)), Some(&(t1, p1))) = (self.samples.front(), self.samples.back()) else {
+            return Vec2::ZERO;
+        };
+        // Events may arrive in the same instant, e.g. when they are batched.
+        let elapsed = t1.duration_since(t0).max(Duration::from_millis(1));
+        (p1 - p0) / elapsed.as_secs_f64()
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use dpi::LogicalPosition;
+
+    use super::*;
+    use crate::PointerState;
+
+    fn at(x: f64, y: f64) -> PointerState {
+        let mut state = PointerState::empty();
+        state.position = LogicalPosition::new(x, y);
+        state
+    }
+
+    #[test]
+    fn tap() {
+        let mut recognizer = GestureRecognizer::new();
+        let origin = Point::new(10., 10.);
+        let now = Instant::now();
+
+        let down = PointerEvent::PointerDown(PointerButton::Primary, at(20., 20.));
+        assert_eq!(recognizer.handle_pointer_event(&down, origin, now), None);
+        let small_move = PointerEvent::PointerMove(at(21., 21.));
+        assert_eq!(
+            recognizer.handle_pointer_event(&small_move, origin, now),
+            None
+        );
+        let up = PointerEvent::PointerUp(PointerButton::Primary, at(21., 21.));
+        assert_eq!(
+            recognizer.handle_pointer_event(&up, origin, now),
+            Some(Gesture::Tap {
+                position: Point::new(11., 11.),
+                count: 1,
+            })
+        );
+        assert!(!recognizer.is_active());
+    }
+
+    #[test]
+    fn multi_tap_and_long_press() {
+        let mut recognizer = GestureRecognizer::new();
+        let now = Instant::now();
+        // The event pass counts the presses.
+        let mut up_state = at(0., 0.);
+        up_state.count = 2;
+        let down = PointerEvent::PointerDown(PointerButton::Primary, up_state.clone());
+        recognizer.handle_pointer_event(&down, Point::ORIGIN, now);
+        let up = PointerEvent::PointerUp(PointerButton::Primary, up_state);
+        assert_eq!(
+            recognizer.handle_pointer_event(&up, Point::ORIGIN, now),
+            Some(Gesture::Tap {
+                position: Point::ORIGIN,
+                count: 2,
+            })
+        );
+
+        let down = PointerEvent::PointerDown(PointerButton::Primary, at(0., 0.));
+        recognizer.handle_pointer_event(&down, Point::ORIGIN, now);
+        let long_press = PointerEvent::LongPress(at(1., 0.));
+        assert_eq!(
+            recognizer.handle_pointer_event(&long_press, Point::ORIGIN, now),
+            Some(Gesture::LongPress {
+                position: Point::ORIGIN
+            })
+        );
+        assert_eq!(
+            recognizer.handle_pointer_event(&long_press, Point::ORIGIN, now),
+            None
+        );
+        let up = PointerEvent::PointerUp(PointerButton::Primary, at(0., 0.));
+        assert_eq!(
+            recognizer.handle_pointer_event(&up, Point::ORIGIN, now),
+            None
+        );
+        // A long press without a press isn't reported.
+        assert_eq!(
+            recognizer.handle_pointer_event(&long_press, Point::ORIGIN, now),
+            None
+        );
+    }
+
+    #[test]
+    fn drag_with_velocity() {
+        let mut recognizer = GestureRecognizer::new();
+        let origin = Point::ORIGIN;
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+
+        let down = PointerEvent::PointerDown(PointerButton::Primary, at(0., 0.));
+        recognizer.handle_pointer_event(&down, origin, ms(0));
+        let small_move = PointerEvent::PointerMove(at(2., 0.));
+        assert_eq!(
+            recognizer.handle_pointer_event(&small_move, origin, ms(2)),
+            None
+        );
+        // The event pass sends the start of the drag before the move crossing the threshold.
+        let drag_start = PointerEvent::DragStart(at(10., 0.));
+        assert_eq!(
+            recognizer.handle_pointer_event(&drag_start, origin, ms(10)),
+            Some(Gesture::DragStart {
+                position: Point::ORIGIN
+            })
+        );
+        let first_move = PointerEvent::PointerMove(at(10., 0.));
+        let Some(Gesture::DragUpdate {
+            position, delta, ..
+        }) = recognizer.handle_pointer_event(&first_move, origin, ms(10))
+        else {
+            panic!("expected a drag update");
+        };
+        assert_eq!(position, Point::new(10., 0.));
+        assert_eq!(delta, Vec2::new(10., 0.));
+        let second_move = PointerEvent::PointerMove(at(20., 0.));
+        let Some(Gesture::DragUpdate { delta, .. }) =
+            recognizer.handle_pointer_event(&second_move, origin, ms(20))
+        else {
+            panic!("expected a drag update");
+        };
+        assert_eq!(delta, Vec2::new(10., 0.));
+
+        let up = PointerEvent::PointerUp(PointerButton::Primary, at(30., 0.));
+        let Some(Gesture::DragEnd { velocity, .. }) =
+            recognizer.handle_pointer_event(&up, origin, ms(30))
+        else {
+            panic!("expected the drag to end");
+        };
+        // 30 pixels in 30ms.
+        assert!((velocity.x - 1000.).abs() < 1e-6);
+        assert_eq!(velocity.y, 0.);
+    }
+
+    #[test]
+    fn cancel_drag() {
+        let mut recognizer = GestureRecognizer::new();
+        let now = Instant::now();
+
+        let down = PointerEvent::PointerDown(PointerButton::Primary, at(0., 0.));
+        recognizer.handle_pointer_event(&down, Point::ORIGIN, now);
+        let drag = PointerEvent::DragStart(at(0., 50.));
+        recognizer.handle_pointer_event(&drag, Point::ORIGIN, now);
+        let leave = PointerEvent::PointerLeave(at(0., 50.));
+        assert_eq!(
+            recognizer.handle_pointer_event(&leave, Point::ORIGIN, now),
+            Some(Gesture::DragCancel)
+        );
+    }
+
+    #[test]
+    fn predict_pointer() {
+        let mut predictor = PointerPredictor::new();
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        assert_eq!(predictor.predict(Duration::from_millis(10)), None);
+
+        let down = PointerEvent::PointerDown(PointerButton::Primary, at(0., 0.));
+        predictor.handle_pointer_event(&down, Point::ORIGIN, ms(0));
+        let stroke = PointerEvent::PointerMove(at(20., 0.));
+        predictor.handle_pointer_event(&stroke, Point::ORIGIN, ms(20));
+
+        // 1 pixel per millisecond.
+        let predicted = predictor.predict(Duration::from_millis(10)).unwrap();
+        assert!((predicted.x - 30.).abs() < 1e-6);
+        let clamped = predictor.predict(Duration::from_secs(1)).unwrap();
+        assert!((clamped.x - 70.).abs() < 1e-6);
+
+        let up = PointerEvent::PointerUp(PointerButton::Primary, at(20., 0.));
+        predictor.handle_pointer_event(&up, Point::ORIGIN, ms(20));
+        assert_eq!(predictor.predict(Duration::from_millis(10)), None);
+    }
+
+    #[test]
+    fn predict_pointer_from_coalesced_moves() {
+        let mut predictor = PointerPredictor::new();
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+
+        let down = PointerEvent::PointerDown(PointerButton::Primary, at(0., 0.));
+        predictor.handle_pointer_event(&down, Point::ORIGIN, ms(0));
+        // The pen turned back halfway through the frame.
+        let mut state = at(0., 0.);
+        state.coalesced = vec![LogicalPosition::new(20., 0.), LogicalPosition::new(40., 0.)];
+        let stroke = PointerEvent::PointerMove(state);
+        predictor.handle_pointer_event(&stroke, Point::ORIGIN, ms(30));
+
+        // The coalesced positions are spread evenly since the press.
+        let samples = &predictor.velocity.samples;
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[1], (ms(10), Point::new(20., 0.)));
+        assert_eq!(samples[2], (ms(20), Point::new(40., 0.)));
+        let predicted = predictor.predict(Duration::from_millis(10)).unwrap();
+        assert_eq!(predicted, Point::ORIGIN);
+    }
+}
@@ -0,0 +1,470 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! System-wide hotkeys, which are delivered to the app even when none of its windows has focus.
+//!
+//! This is useful e.g. to show or hide a launcher window with a keyboard shortcut.
+//!
+//! Hotkeys are registered with a [`GlobalHotkeys`] manager, which reports each press of a
+//! registered hotkey to the [`AppDriver`](crate::AppDriver) as an [`Action::GlobalHotkey`](crate::Action::GlobalHotkey).
+//! A hotkey stays registered for as long as its [`HotkeyRegistration`] is alive.
+//!
+//! Global hotkeys are currently only supported on X11, with the `global-hotkeys` feature.
+//! Without it, or on other platforms, [`GlobalHotkeys::new`] returns [`HotkeyError::Unsupported`].
+
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use winit::keyboard::{Key, ModifiersState};
+
+use crate::event_loop_runner::EventLoopProxy;
+use crate::WidgetId;
+
+/// A key combination which can be registered as a global hotkey.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    /// The modifiers which must be held when the key is pressed.
+    pub mods: ModifiersState,
+    /// The key which triggers the hotkey.
+    ///
+    /// Character keys are matched regardless of case, so the shift state
+    /// must be set in `mods` instead.
+    pub key: Key,
+}
+
+/// Identifies a registered hotkey in [`Action::GlobalHotkey`](crate::Action::GlobalHotkey).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HotkeyId(u64);
+
+/// An error which prevented a hotkey from being registered.
+#[derive(Debug)]
+pub enum HotkeyError {
+    /// Global hotkeys aren't supported on this platform, or the `global-hotkeys` feature
+    /// is disabled.
+    Unsupported,
+    /// The key can't be used in a global hotkey, or isn't on the keyboard.
+    UnsupportedKey(Key),
+    /// The hotkey is already registered, either by this app or by another one.
+    Conflict(Hotkey),
+    /// The connection to the windowing system failed.
+    Connection(String),
+}
+
+/// Registers global hotkeys, and delivers their presses to the app.
+///
+/// Dropping the manager stops delivering hotkeys, but they stay registered
+/// until their [`HotkeyRegistration`] is dropped.
+pub struct GlobalHotkeys {
+    backend: Arc<imp::Backend>,
+    next_id: AtomicU64,
+}
+
+/// A registered global hotkey.
+///
+/// The hotkey is unregistered when this is dropped.
+#[must_use = "the hotkey is unregistered when the registration is dropped"]
+pub struct HotkeyRegistration {
+    id: HotkeyId,
+    hotkey: Hotkey,
+    backend: Arc<imp::Backend>,
+}
+
+// --- MARK: IMPL HOTKEYS ---
+impl Hotkey {
+    pub fn new(mods: ModifiersState, key: impl Into<Key>) -> Self {
+        Self {
+            mods,
+            key: key.into(),
+        }
+    }
+}
+
+impl GlobalHotkeys {
+    /// Connect to the windowing system to register global hotkeys.
+    ///
+    /// Hotkey presses are sent through `proxy` as [`Action::GlobalHotkey`](crate::Action::GlobalHotkey), coming
+    /// from the widget `target`. This is usually the id of the root widget.
+    pub fn new(proxy: EventLoopProxy, target: WidgetId) -> Result<Self, HotkeyError> {
+        Ok(Self {
+            backend: imp::Backend::new(proxy, target)?,
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Register `hotkey`, so that it's reported to the app until the returned
+    /// registration is dropped.
+    ///
+    /// Returns [`HotkeyError::Conflict`] if the hotkey is already registered.
+    pub fn register(&self, hotkey: Hotkey) -> Result<HotkeyRegistration, HotkeyError> {
+        let id = HotkeyId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.backend.register(id, &hotkey)?;
+        Ok(HotkeyRegistration {
+            id,
+            hotkey,
+            backend: self.backend.clone(),
+        })
+    }
+}
+
+impl Drop for GlobalHotkeys {
+    fn drop(&mut self) {
+        self.backend.shutdown();
+    }
+}
+
+impl HotkeyRegistration {
+    /// The id which is reported in [`Action::GlobalHotkey`](crate::Action::GlobalHotkey) when the hotkey is pressed.
+    pub fn id(&self) -> HotkeyId {
+        self.id
+    }
+
+    /// The registered hotkey.
+    pub fn hotkey(&self) -> &Hotkey {
+        &self.hotkey
+    }
+}
+
+impl Drop for HotkeyRegistration {
+    fn drop(&mut self) {
+        self.backend.unregister(self.id);
+    }
+}
+
+impl fmt::Debug for HotkeyRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotkeyRegistration")
+            .field("id", &self.id)
+            .field("hotkey", &self.hotkey)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for HotkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "global hotkeys aren't supported on this platform"),
+            Self::UnsupportedKey(key) => write!(f, "{key:?} can't be used in a global hotkey"),
+            Self::Conflict(hotkey) => write!(
+                f,
+                "{:?} + {:?} is already registered as a hotkey",
+                hotkey.mods, hotkey.key
+            ),
+            Self::Connection(err) => write!(f, "couldn't connect to the windowing system: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for HotkeyError {}
+
+// --- MARK: X11 ---
+#[cfg(all(
+    feature = "global-hotkeys",
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+))]
+mod imp {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use winit::keyboard::{Key, ModifiersState, NamedKey};
+    use x11rb::connection::Connection;
+    use x11rb::errors::ReplyError;
+    use x11rb::protocol::xproto::{ConnectionExt, GrabMode, Keycode, ModMask, Window};
+    use x11rb::protocol::{ErrorKind, Event};
+    use x11rb::rust_connection::RustConnection;
+
+    use super::{Hotkey, HotkeyError, HotkeyId};
+    use crate::event_loop_runner::{EventLoopProxy, MasonryUserEvent};
+    use crate::{Action, WidgetId};
+
+    /// How often the connection is checked for hotkey presses.
+    const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+    pub(super) struct Backend {
+        conn: RustConnection,
+        root: Window,
+        grabs: Mutex<HashMap<HotkeyId, (Keycode, ModMask)>>,
+        running: AtomicBool,
+    }
+
+    /// Grabs don't match if a lock modifier is set, so each hotkey is also grabbed with
+    /// all combinations of Caps Lock and Num Lock.
+    fn lock_variants() -> [ModMask; 4] {
+        [
+            ModMask::from(0_u16),
+            ModMask::LOCK,
+            ModMask::M2,
+            ModMask::LOCK | ModMask::M2,
+        ]
+    }
+
+    impl Backend {
+        pub(super) fn new(
+            proxy: EventLoopProxy,
+            target: WidgetId,
+        ) -> Result<Arc<Self>, HotkeyError> {
+            let (conn, screen) =
+                x11rb::connect(None).map_err(|err| HotkeyError::Connection(err.to_string()))?;
+            let root = conn.setup().roots[screen].root;
+            let backend = Arc::new(Self {
+                conn,
+                root,
+                grabs: Mutex::new(HashMap::new()),
+                running: AtomicBool::new(true),
+            });
+
+            let thread_backend = backend.clone();
+            std::thread::Builder::new()
+                .name("masonry-hotkeys".into())
+                .spawn(move || thread_backend.run(proxy, target))
+                .map_err(|err| HotkeyError::Connection(err.to_string()))?;
+            Ok(backend)
+        }
+
+        fn run(&self, proxy: EventLoopProxy, target: WidgetId) {
+            while self.running.load(Ordering::Relaxed) {
+                let event = match self.conn.poll_for_event() {
+                    Ok(Some(event)) => event,
+                    Ok(None) => {
+                        std::thread::sleep(POLL_INTERVAL);
+                        continue;
+                    }
+                    Err(err) => {
+                        tracing::error!("Lost the connection for global hotkeys: {err}");
+                        return;
+                    }
+                };
+                let Event::KeyPress(event) = event else {
+                    continue;
+                };
+                let mods = u16::from(event.state) & !u16::from(ModMask::LOCK | ModMask::M2);
+                let id = self
+                    .grabs
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|(_, &(keycode, grab_mods))| {
+                        keycode == event.detail && u16::from(grab_mods) == mods
+                    })
+                    .map(|(&id, _)| id);
+                if let Some(id) = id {
+                    let action = MasonryUserEvent::Action(Action::GlobalHotkey(id), target);
+                    if proxy.send_event(action).is_err() {
+                        // The event loop has exited.
+                        return;
+                    }
+                }
+            }
+        }
+
+        pub(super) fn register(&self, id: HotkeyId, hotkey: &Hotkey) -> Result<(), HotkeyError> {
+            let unsupported = || HotkeyError::UnsupportedKey(hotkey.key.clone());
+            let keysym = keysym(&hotkey.key).ok_or_else(unsupported)?;
+            let keycode = self.keycode(keysym)?.ok_or_else(unsupported)?;
+            let mods = mod_mask(hotkey.mods);
+
+            let mut grabs = self.grabs.lock().unwrap();
+            if grabs.values().any(|&grab| grab == (keycode, mods)) {
+                return Err(HotkeyError::Conflict(hotkey.clone()));
+            }
+            for (i, variant) in lock_variants().into_iter().enumerate() {
+                let result = self
+                    .conn
+                    .grab_key(
+                        false,
+                        self.root,
+                        mods | variant,
+                        keycode,
+                        GrabMode::ASYNC,
+                        GrabMode::ASYNC,
+                    )
+                    .map_err(ReplyError::from)
+                    .and_then(|cookie| cookie.check());
+                if let Err(err) = result {
+                    for &variant in &lock_variants()[..i] {
+                        self.ungrab(keycode, mods | variant);
+                    }
+                    let _ = self.conn.flush();
+                    return Err(match err {
+                        ReplyError::X11Error(err) if err.error_kind == ErrorKind::Access => {
+                            HotkeyError::Conflict(hotkey.clone())
+                        }
+                        err => HotkeyError::Connection(err.to_string()),
+                    });
+                }
+            }
+            grabs.insert(id, (keycode, mods));
+            Ok(())
+        }
+
+        pub(super) fn unregister(&self, id: HotkeyId) {
+            let Some((keycode, mods)) = self.grabs.lock().unwrap().remove(&id) else {
+                return;
+            };
+            for variant in lock_variants() {
+                self.ungrab(keycode, mods | variant);
+            }
+            let _ = self.conn.flush();
+        }
+
+        pub(super) fn shutdown(&self) {
+            self.running.store(false, Ordering::Relaxed);
+        }
+
+        fn ungrab(&self, keycode: Keycode, mods: ModMask) {
+            if let Err(err) = self.conn.ungrab_key(keycode, self.root, mods) {
+                tracing::warn!("Failed to unregister a global hotkey: {err}");
+            }
+        }
+
+        /// Find the key which produces `keysym` in the current keyboard layout.
+        fn keycode(&self, keysym: u32) -> Result<Option<Keycode>, HotkeyError> {
+            let setup = self.conn.setup();
+            let count = setup.max_keycode - setup.min_keycode + 1;
+            let mapping = self
+                .conn
+                .get_keyboard_mapping(setup.min_keycode, count)
+                .map_err(ReplyError::from)
+                .and_then(|cookie| cookie.reply())
+                .map_err(|err| HotkeyError::Connection(err.to_string()))?;
+            let per_keycode = usize::from(mapping.keysyms_per_keycode).max(1);
+            let index = mapping
+                .keysyms
+                .chunks(per_keycode)
+                .position(|keysyms| keysyms.contains(&keysym));
+            Ok(index.map(|index| setup.min_keycode + index as u8))
+        }
+    }
+
+    fn mod_mask(mods: ModifiersState) -> ModMask {
+        let mut mask = ModMask::from(0_u16);
+        if mods.shift_key() {
+            mask |= ModMask::SHIFT;
+        }
+        if mods.control_key() {
+            mask |= ModMask::CONTROL;
+        }
+        if mods.alt_key() {
+            mask |= ModMask::M1;
+        }
+        if mods.super_key() {
+            mask |= ModMask::M4;
+        }
+        mask
+    }
+
+    /// The X11 keysym of `key`, as listed in `X11/keysymdef.h` and `X11/XF86keysym.h`.
+    fn keysym(key: &Key) -> Option<u32> {
+        let keysym = match key {
+            Key::Character(text) => {
+                let mut chars = text.chars();
+                let (Some(c), None) = (chars.next(), chars.next()) else {
+                    return None;
+                };
+                let mut lowercase = c.to_lowercase();
+                let c = match (lowercase.next(), lowercase.next()) {
+                    (Some(lower), None) => lower,
+                    _ => c,
+                };
+                match u32::from(c) {
+                    c @ (0x20..=0x7e | 0xa0..=0xff) => c,
+                    c => 0x0100_0000 + c,
+                }
+            }
+            Key::Named(named) => match named {
+                NamedKey::Space => 0x20,
+                NamedKey::Backspace => 0xff08,
+                NamedKey::Tab => 0xff09,
+                NamedKey::Enter => 0xff0d,
+                NamedKey::Pause => 0xff13,
+                NamedKey::Escape => 0xff1b,
+                NamedKey::Home => 0xff50,
+                NamedKey::ArrowLeft => 0xff51,
+                NamedKey::ArrowUp => 0xff52,
+                NamedKey::ArrowRight => 0xff53,
+                NamedKey::ArrowDown => 0xff54,
+                NamedKey::PageUp => 0xff55,
+                NamedKey::PageDown => 0xff56,
+                NamedKey::End => 0xff57,
+                NamedKey::PrintScreen => 0xff61,
+                NamedKey::Insert => 0xff63,
+                NamedKey::Delete => 0xffff,
+                NamedKey::F1 => 0xffbe,
+                NamedKey::F2 => 0xffbf,
+                NamedKey::F3 => 0xffc0,
+                NamedKey::F4 => 0xffc1,
+                NamedKey::F5 => 0xffc2,
+                NamedKey::F6 => 0xffc3,
+                NamedKey::F7 => 0xffc4,
+                NamedKey::F8 => 0xffc5,
+                NamedKey::F9 => 0xffc6,
+                NamedKey::F10 => 0xffc7,
+                NamedKey::F11 => 0xffc8,
+                NamedKey::F12 => 0xffc9,
+                NamedKey::AudioVolumeDown => 0x1008_ff11,
+                NamedKey::AudioVolumeMute => 0x1008_ff12,
+                NamedKey::AudioVolumeUp => 0x1008_ff13,
+                NamedKey::MediaPlayPause => 0x1008_ff14,
+                NamedKey::MediaStop => 0x1008_ff15,
+                NamedKey::MediaTrackPrevious => 0x1008_ff16,
+                NamedKey::MediaTrackNext => 0x1008_ff17,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(keysym)
+    }
+
+    // --- MARK: TESTS ---
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn keysyms() {
+            assert_eq!(keysym(&Key::Character("a".into())), Some(0x61));
+            assert_eq!(keysym(&Key::Character("A".into())), Some(0x61));
+            assert_eq!(keysym(&Key::Character("é".into())), Some(0xe9));
+            assert_eq!(keysym(&Key::Character("ж".into())), Some(0x0100_0436));
+            assert_eq!(keysym(&Key::Named(NamedKey::F4)), Some(0xffc1));
+            assert_eq!(keysym(&Key::Character("ab".into())), None);
+            assert_eq!(keysym(&Key::Named(NamedKey::Shift)), None);
+        }
+    }
+}
+
+// --- MARK: UNSUPPORTED ---
+#[cfg(not(all(
+    feature = "global-hotkeys",
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+)))]
+mod imp {
+    use std::sync::Arc;
+
+    use super::{Hotkey, HotkeyError, HotkeyId};
+    use crate::event_loop_runner::EventLoopProxy;
+    use crate::WidgetId;
+
+    pub(super) struct Backend;
+
+    impl Backend {
+        pub(super) fn new(
+            _proxy: EventLoopProxy,
+            _target: WidgetId,
+        ) -> Result<Arc<Self>, HotkeyError> {
+            Err(HotkeyError::Unsupported)
+        }
+
+        pub(super) fn register(&self, _id: HotkeyId, _hotkey: &Hotkey) -> Result<(), HotkeyError> {
+            Err(HotkeyError::Unsupported)
+        }
+
+        pub(super) fn unregister(&self, _id: HotkeyId) {}
+
+        pub(super) fn shutdown(&self) {}
+    }
+}
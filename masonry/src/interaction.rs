@@ -0,0 +1,251 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracking of the hovered, pressed, focused and disabled states of a widget.
+
+use std::time::Duration;
+
+use crate::{EventCtx, PointerButton, PointerEvent, Update, UpdateCtx};
+
+/// The time the highlight takes to go from none to full, or back.
+pub const HIGHLIGHT_DURATION: Duration = Duration::from_millis(120);
+
+/// The highlight of a hovered or focused widget.
+pub const HOVER_HIGHLIGHT: f64 = 0.5;
+
+/// Tracks the interaction states of a widget, and animates a highlight amount from them.
+///
+/// Widgets forward their pointer events, updates and animation frames to the matching
+/// methods, and read [`highlight`](Self::highlight) when painting, e.g. to blend between
+/// their normal and pressed colors. The highlight is 0 when idle or disabled,
+/// [`HOVER_HIGHLIGHT`] when hovered or focused, and 1 when pressed, and moves towards
+/// that target over [`HIGHLIGHT_DURATION`].
+///
+/// The widget doesn't have to request paints when the states change, but it must still
+/// capture the pointer on [`PointerDown`](PointerEvent::PointerDown) if it wants to
+/// stay pressed while the pointer is dragged outside of it.
+#[derive(Clone, Debug)]
+pub struct InteractionState {
+    hovered: bool,
+    pressed: bool,
+    focused: bool,
+    disabled: bool,
+    highlight: f64,
+    duration: Duration,
+}
+
+// --- MARK: BUILDERS ---
+impl InteractionState {
+    pub fn new() -> Self {
+        Self {
+            hovered: false,
+            pressed: false,
+            focused: false,
+            disabled: false,
+            highlight: 0.,
+            duration: HIGHLIGHT_DURATION,
+        }
+    }
+
+    /// Builder-style method to set the duration of the transitions.
+    ///
+    /// With a zero duration, the highlight jumps to its target.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+// --- MARK: STATES ---
+impl InteractionState {
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    /// Whether the primary button was pressed on the widget and not released yet.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// The current highlight amount, between 0 and 1.
+    pub fn highlight(&self) -> f64 {
+        self.highlight
+    }
+
+    /// The highlight amount the current states lead to.
+    pub fn target_highlight(&self) -> f64 {
+        if self.disabled {
+            0.
+        } else if self.pressed {
+            1.
+        } else if self.hovered || self.focused {
+            HOVER_HIGHLIGHT
+        } else {
+            0.
+        }
+    }
+
+    /// Whether the highlight is still moving towards its target.
+    pub fn is_animating(&self) -> bool {
+        self.highlight != self.target_highlight()
+    }
+}
+
+// --- MARK: EVENTS ---
+impl InteractionState {
+    /// Update the pressed state from a pointer event.
+    pub fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let pressed = match event {
+            PointerEvent::PointerDown(PointerButton::Primary, _) => !self.disabled,
+            PointerEvent::PointerUp(PointerButton::Primary, _) | PointerEvent::PointerLeave(_) => {
+                false
+            }
+            _ => return,
+        };
+        if pressed != self.pressed {
+            self.pressed = pressed;
+            ctx.request_paint_only();
+            if self.is_animating() {
+                ctx.request_anim_frame();
+            }
+        }
+    }
+
+    /// Update the hovered, focused and disabled states.
+    ///
+    /// When the widget is added, the highlight starts at its target without a transition.
+    pub fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::WidgetAdded => {
+                self.hovered = ctx.is_hovered();
+                self.focused = ctx.has_focus();
+                self.disabled = ctx.is_disabled();
+                self.highlight = self.target_highlight();
+                return;
+            }
+            Update::HoveredChanged(hovered) => self.hovered = *hovered,
+            Update::FocusChanged(focused) => self.focused = *focused,
+            Update::DisabledChanged(disabled) => {
+                self.disabled = *disabled;
+                self.pressed &= !disabled;
+            }
+            _ => return,
+        }
+        ctx.request_paint_only();
+        if self.is_animating() {
+            ctx.request_anim_frame();
+        }
+    }
+
+    /// Move the highlight towards its target, `interval` nanoseconds after the last frame.
+    pub fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
+        if !self.is_animating() {
+            return;
+        }
+        self.advance(interval);
+        ctx.request_paint_only();
+        if self.is_animating() {
+            ctx.request_anim_frame();
+        }
+    }
+
+    fn advance(&mut self, interval: u64) {
+        let target = self.target_highlight();
+        let step = interval as f64 / self.duration.as_nanos() as f64;
+        self.highlight = if step.is_nan() || (target - self.highlight).abs() <= step {
+            target
+        } else {
+            self.highlight + step.copysign(target - self.highlight)
+        };
+    }
+}
+
+impl Default for InteractionState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::testing::{widget_ids, ModularWidget, TestHarness};
+    use crate::widget::Flex;
+    use crate::Size;
+
+    fn interactive(shared: Rc<RefCell<InteractionState>>) -> ModularWidget<()> {
+        let (events, updates, frames) = (shared.clone(), shared.clone(), shared);
+        ModularWidget::new(())
+            .pointer_event_fn(move |_, ctx, event| {
+                if let PointerEvent::PointerDown(..) = event {
+                    ctx.capture_pointer();
+                }
+                events.borrow_mut().on_pointer_event(ctx, event);
+            })
+            .update_fn(move |_, ctx, event| updates.borrow_mut().update(ctx, event))
+            .anim_frame_fn(move |_, ctx, interval| {
+                frames.borrow_mut().on_anim_frame(ctx, interval);
+            })
+            .layout_fn(|_, _, _| Size::new(50., 50.))
+    }
+
+    #[test]
+    fn highlight_follows_states() {
+        let [id] = widget_ids();
+        let state = Rc::new(RefCell::new(InteractionState::new()));
+        let widget = Flex::row().with_child_id(interactive(state.clone()), id);
+        let mut harness = TestHarness::create(widget);
+        assert_eq!(state.borrow().highlight(), 0.);
+
+        harness.mouse_move_to(id);
+        assert!(state.borrow().is_hovered());
+        assert!(state.borrow().is_animating());
+        harness.animate_ms(30);
+        assert!((state.borrow().highlight() - 0.25).abs() < 1e-9);
+        harness.animate_ms(100);
+        assert_eq!(state.borrow().highlight(), HOVER_HIGHLIGHT);
+        assert!(!state.borrow().is_animating());
+
+        harness.mouse_button_press(PointerButton::Primary);
+        assert!(state.borrow().is_pressed());
+        assert_eq!(state.borrow().target_highlight(), 1.);
+        harness.animate_ms(200);
+        assert_eq!(state.borrow().highlight(), 1.);
+
+        harness.mouse_button_release(PointerButton::Primary);
+        harness.edit_widget(id, |mut widget| widget.ctx.set_disabled(true));
+        assert!(state.borrow().is_disabled());
+        assert!(!state.borrow().is_pressed());
+        harness.animate_ms(200);
+        assert_eq!(state.borrow().highlight(), 0.);
+    }
+
+    #[test]
+    fn disabled_widget_is_not_pressed() {
+        let [id] = widget_ids();
+        let state = Rc::new(RefCell::new(
+            InteractionState::new().with_duration(Duration::ZERO),
+        ));
+        let widget = Flex::row().with_child_id(interactive(state.clone()), id);
+        let mut harness = TestHarness::create(widget);
+        harness.edit_widget(id, |mut widget| widget.ctx.set_disabled(true));
+
+        harness.mouse_move_to(id);
+        harness.mouse_button_press(PointerButton::Primary);
+        assert!(!state.borrow().is_pressed());
+        harness.animate_ms(16);
+        assert_eq!(state.borrow().highlight(), 0.);
+    }
+}
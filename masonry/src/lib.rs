@@ -119,15 +119,30 @@ pub mod doc;
 mod action;
 mod app_driver;
 mod box_constraints;
+mod box_shadow;
+mod clip_path;
+mod close_signals;
+mod composition;
+mod context_menu;
 mod contexts;
+mod env;
 mod event;
+mod overlay;
+mod paginator;
 mod paint_scene_helpers;
 mod passes;
 mod render_root;
 mod tracing_backend;
 mod tree_arena;
 
+pub mod animation;
+pub mod assets;
+pub mod clipboard;
 pub mod event_loop_runner;
+pub mod gesture;
+pub mod global_hotkeys;
+pub mod interaction;
+pub mod locale;
 pub mod testing;
 pub mod text;
 pub mod theme;
@@ -146,20 +161,31 @@ pub use parley::layout::Alignment as TextAlignment;
 pub use vello::peniko::{Color, Gradient};
 
 pub use action::Action;
-pub use app_driver::{AppDriver, DriverCtx};
+pub use app_driver::{AppDriver, CloseReason, CloseResponse, DriverCtx};
 pub use box_constraints::BoxConstraints;
+pub use box_shadow::BoxShadow;
+pub use clip_path::ClipPath;
+pub use composition::{Composition, PanelId};
+pub use context_menu::{Menu, MenuItem};
 pub use contexts::{
     AccessCtx, ComposeCtx, EventCtx, IsContext, LayoutCtx, MutateCtx, PaintCtx, QueryCtx,
     RawWrapper, RawWrapperMut, RegisterCtx, UpdateCtx,
 };
+pub use env::{Env, Key, Value, ValueType};
 pub use event::{
-    AccessEvent, PointerButton, PointerEvent, PointerState, TextEvent, Update, WindowEvent,
-    WindowTheme,
+    AccessEvent, FocusDirection, KeyEvent, MonitorInfo, PenState, PlatformEvent, PointerButton,
+    PointerEvent, PointerId, PointerState, ScrollDeltaKind, TextEvent, TimerToken, Update,
+    WindowEvent, WindowPlacement, WindowTheme,
 };
+pub use overlay::PopupPlacement;
+pub use paginator::{Page, PageBreak, Paginator};
 pub use paint_scene_helpers::UnitPoint;
-pub use render_root::{RenderRoot, RenderRootOptions, RenderRootSignal, WindowSizePolicy};
+pub use render_root::{
+    AppWindowId, FrameStats, RenderRoot, RenderRootOptions, RenderRootSignal, WidgetScene,
+    WindowSizePolicy,
+};
 pub use util::{AsAny, Handled};
 pub use widget::widget::{AllowRawMut, Widget, WidgetId};
 pub use widget::WidgetPod;
 
-pub(crate) use widget::WidgetState;
+pub(crate) use widget::{WidgetFlags, WidgetState};
@@ -0,0 +1,532 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Locale-aware formatting and parsing of numbers and dates.
+//!
+//! Widgets access the formatter of their app through [`EventCtx::formatter`], and apps
+//! set it with [`RenderRoot::set_formatter`].
+//!
+//! With the `icu` feature, the default formatter is an `IcuFormatter`, which uses the locale
+//! data of ICU4X. Otherwise, it's a [`BasicFormatter`], which covers the separators and date
+//! order of common locales. See [`system_formatter`].
+//!
+//! [`EventCtx::formatter`]: crate::EventCtx::formatter
+//! [`RenderRoot::set_formatter`]: crate::RenderRoot::set_formatter
+
+use std::fmt;
+use std::sync::Arc;
+
+/// A locale identifier, made of a language and an optional region, e.g. `en-US` or `de`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Locale {
+    language: String,
+    region: Option<String>,
+}
+
+impl Locale {
+    /// Create a locale from a language and an optional region subtag.
+    pub fn new(language: &str, region: Option<&str>) -> Self {
+        Self {
+            language: language.to_ascii_lowercase(),
+            region: region.map(str::to_ascii_uppercase),
+        }
+    }
+
+    /// Parse a BCP 47 language tag, e.g. `en-US`.
+    ///
+    /// POSIX locale names such as `de_DE.UTF-8` are also accepted.
+    ///
+    /// With the `icu` feature, tags are validated with ICU4X.
+    pub fn parse(tag: &str) -> Option<Self> {
+        // Strip the encoding and modifier of POSIX locale names.
+        let tag = tag.split(['.', '@']).next()?.replace('_', "-");
+
+        #[cfg(feature = "icu")]
+        {
+            let locale = icu_locid::Locale::try_from_bytes(tag.as_bytes()).ok()?;
+            Some(Self::new(
+                locale.id.language.as_str(),
+                locale.id.region.as_ref().map(|region| region.as_str()),
+            ))
+        }
+
+        #[cfg(not(feature = "icu"))]
+        {
+            let mut subtags = tag.split('-');
+            let language = subtags.next()?;
+            if !(2..=3).contains(&language.len())
+                || !language.bytes().all(|b| b.is_ascii_alphabetic())
+            {
+                return None;
+            }
+            // Skip the script subtag, if any.
+            let region = subtags.find(|subtag| {
+                subtag.len() == 2 && subtag.bytes().all(|b| b.is_ascii_alphabetic())
+                    || subtag.len() == 3 && subtag.bytes().all(|b| b.is_ascii_digit())
+            });
+            Some(Self::new(language, region))
+        }
+    }
+
+    /// The locale of the current process, read from the `LC_ALL`, `LC_NUMERIC` and `LANG`
+    /// environment variables.
+    ///
+    /// Falls back to the default `en-US` locale.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .filter(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .find_map(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+
+    /// The language subtag, in lowercase.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// The region subtag, in uppercase.
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new("en", Some("US"))
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.region {
+            Some(region) => write!(f, "{}-{}", self.language, region),
+            None => f.write_str(&self.language),
+        }
+    }
+}
+
+/// Formats and parses values for display to the user.
+pub trait LocaleFormatter: Send + Sync {
+    /// The locale values are formatted for.
+    fn locale(&self) -> &Locale;
+
+    /// Format a number with a fixed number of fraction digits.
+    fn format_number(&self, value: f64, fraction_digits: usize) -> String;
+
+    /// Parse a number typed by the user.
+    fn parse_number(&self, text: &str) -> Option<f64>;
+
+    /// Format a date in the short, numeric style of the locale.
+    fn format_date(&self, date: time::Date) -> String;
+
+    /// Format a number with as many fraction digits as it needs, up to 6.
+    fn format_shortest(&self, value: f64) -> String {
+        let shortest = value.to_string();
+        let fraction_digits = shortest.split_once('.').map_or(0, |(_, f)| f.len());
+        self.format_number(value, fraction_digits.min(6))
+    }
+
+    /// Format a fraction, e.g. `0.25`, as a whole percentage.
+    fn format_percent(&self, fraction: f64) -> String {
+        format!("{}%", self.format_number(fraction * 100., 0))
+    }
+
+    /// The text announced to assistive technologies at a step of a keyboard drag-and-drop.
+    ///
+    /// The default implementation is in English, override it to translate the announcements.
+    fn drag_announcement(&self, step: DragAnnouncement) -> String {
+        match step {
+            DragAnnouncement::PickedUp => "Picked up. Use the arrow keys to choose where to drop, \
+                Enter to drop, and Escape to cancel."
+                .into(),
+            DragAnnouncement::Over { position, count } => format!(
+                "Over a drop target, {} of {}.",
+                self.format_number(position as f64, 0),
+                self.format_number(count as f64, 0)
+            ),
+            DragAnnouncement::NoOtherTarget => "There is no other drop target.".into(),
+            DragAnnouncement::NothingToDropOn => "There is nothing to drop on.".into(),
+            DragAnnouncement::Dropped => "Dropped.".into(),
+            DragAnnouncement::DroppedOnNothing => {
+                "Drag cancelled, there was nothing to drop on.".into()
+            }
+            DragAnnouncement::Cancelled => "Drag cancelled.".into(),
+        }
+    }
+}
+
+/// A step of a keyboard drag-and-drop, see [`LocaleFormatter::drag_announcement`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DragAnnouncement {
+    /// The drag was started with the keyboard.
+    PickedUp,
+    /// The drag moved over a drop target.
+    ///
+    /// The target is the widget at `position`, starting from 1, among the `count` widgets
+    /// of the focus chain, so that moving between targets changes the announcement.
+    Over { position: usize, count: usize },
+    /// The drag stayed over its drop target, because no other widget accepts the drop.
+    NoOtherTarget,
+    /// No widget accepts the drop.
+    NothingToDropOn,
+    /// The payload was dropped on a target.
+    Dropped,
+    /// The drag was released while no widget accepted the drop.
+    DroppedOnNothing,
+    /// The drag was cancelled, e.g. with Escape.
+    Cancelled,
+}
+
+/// The formatter for the locale of the current process, see [`Locale::from_env`].
+///
+/// This is an `IcuFormatter` with the `icu` feature, and a [`BasicFormatter`] otherwise.
+pub fn system_formatter() -> Arc<dyn LocaleFormatter> {
+    #[cfg(feature = "icu")]
+    if let Some(formatter) = IcuFormatter::new(Locale::from_env()) {
+        return Arc::new(formatter);
+    }
+    Arc::new(BasicFormatter::from_env())
+}
+
+/// The order of the components of a numeric date.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// A [`LocaleFormatter`] with built-in data for common locales.
+///
+/// Numbers are grouped by thousands, and unknown locales are formatted like `en-US`.
+#[derive(Clone, Debug)]
+pub struct BasicFormatter {
+    locale: Locale,
+    decimal_separator: char,
+    group_separator: char,
+    date_order: DateOrder,
+    date_separator: char,
+}
+
+impl BasicFormatter {
+    /// Create a formatter for `locale`.
+    pub fn new(locale: Locale) -> Self {
+        use DateOrder::*;
+
+        const NARROW_NBSP: char = '\u{202f}';
+        const NBSP: char = '\u{a0}';
+
+        let (decimal_separator, group_separator, date_order, date_separator) =
+            match (locale.language(), locale.region()) {
+                ("en", Some("US" | "PH") | None) => ('.', ',', MonthDayYear, '/'),
+                ("en", Some("CA")) => ('.', ',', YearMonthDay, '-'),
+                ("en", _) => ('.', ',', DayMonthYear, '/'),
+                ("de" | "it", Some("CH")) => ('.', '\'', DayMonthYear, '.'),
+                ("de", _) => (',', '.', DayMonthYear, '.'),
+                ("fr", Some("CH")) => (',', NARROW_NBSP, DayMonthYear, '.'),
+                ("fr", Some("CA")) => (',', NBSP, YearMonthDay, '-'),
+                ("fr", _) => (',', NARROW_NBSP, DayMonthYear, '/'),
+                ("es" | "it" | "pt" | "id", _) => (',', '.', DayMonthYear, '/'),
+                ("nl", _) => (',', '.', DayMonthYear, '-'),
+                ("da" | "tr", _) => (',', '.', DayMonthYear, '.'),
+                ("ru" | "uk" | "pl" | "cs" | "fi" | "nb" | "no", _) => {
+                    (',', NBSP, DayMonthYear, '.')
+                }
+                ("sv", _) => (',', NBSP, YearMonthDay, '-'),
+                ("ja" | "zh", _) => ('.', ',', YearMonthDay, '/'),
+                ("ko", _) => ('.', ',', YearMonthDay, '.'),
+                ("hi", _) => ('.', ',', DayMonthYear, '/'),
+                _ => ('.', ',', MonthDayYear, '/'),
+            };
+
+        Self {
+            locale,
+            decimal_separator,
+            group_separator,
+            date_order,
+            date_separator,
+        }
+    }
+
+    /// Create a formatter for the locale of the current process.
+    ///
+    /// See [`Locale::from_env`].
+    pub fn from_env() -> Self {
+        Self::new(Locale::from_env())
+    }
+
+    pub fn decimal_separator(&self) -> char {
+        self.decimal_separator
+    }
+
+    pub fn group_separator(&self) -> char {
+        self.group_separator
+    }
+
+    pub fn date_order(&self) -> DateOrder {
+        self.date_order
+    }
+}
+
+impl Default for BasicFormatter {
+    fn default() -> Self {
+        Self::new(Locale::default())
+    }
+}
+
+impl LocaleFormatter for BasicFormatter {
+    fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    fn format_number(&self, value: f64, fraction_digits: usize) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        let digits = format!("{:.*}", fraction_digits, value.abs());
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits.as_str(), None),
+        };
+
+        let mut formatted = String::with_capacity(digits.len() + integer.len() / 3 + 1);
+        // Don't show "-0" when a small negative value rounds to zero.
+        if value.is_sign_negative() && digits.bytes().any(|b| (b'1'..=b'9').contains(&b)) {
+            formatted.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                formatted.push(self.group_separator);
+            }
+            formatted.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push(self.decimal_separator);
+            formatted.push_str(fraction);
+        }
+        formatted
+    }
+
+    fn parse_number(&self, text: &str) -> Option<f64> {
+        let mut normalized = String::with_capacity(text.len());
+        for c in text.trim().chars() {
+            match c {
+                c if c == self.decimal_separator => normalized.push('.'),
+                c if c == self.group_separator || c.is_whitespace() => {}
+                // Unicode minus sign
+                '\u{2212}' => normalized.push('-'),
+                c => normalized.push(c),
+            }
+        }
+        normalized.parse().ok()
+    }
+
+    fn format_date(&self, date: time::Date) -> String {
+        let (year, month, day) = (date.year(), u8::from(date.month()), date.day());
+        let sep = self.date_separator;
+        match self.date_order {
+            DateOrder::DayMonthYear => format!("{day:02}{sep}{month:02}{sep}{year}"),
+            DateOrder::MonthDayYear => format!("{month}{sep}{day}{sep}{year}"),
+            DateOrder::YearMonthDay => format!("{year}{sep}{month:02}{sep}{day:02}"),
+        }
+    }
+}
+
+// --- MARK: ICU ---
+/// A [`LocaleFormatter`] backed by the locale data of ICU4X.
+///
+/// Numbers use the digits, separators and grouping of the locale, and dates use its
+/// default calendar.
+#[cfg(feature = "icu")]
+pub struct IcuFormatter {
+    locale: Locale,
+    decimal: icu_decimal::FixedDecimalFormatter,
+    date: icu_datetime::DateFormatter,
+    /// The digits of the locale, from 0 to 9, used to parse numbers.
+    digits: [char; 10],
+    decimal_separator: String,
+    group_separator: String,
+}
+
+#[cfg(feature = "icu")]
+impl IcuFormatter {
+    /// Create a formatter for `locale`.
+    ///
+    /// Returns `None` if ICU4X has no data for the locale nor for any of its fallbacks.
+    pub fn new(locale: Locale) -> Option<Self> {
+        let icu_locale = icu_locid::Locale::try_from_bytes(locale.to_string().as_bytes()).ok()?;
+        let data_locale = icu_provider::DataLocale::from(&icu_locale);
+        let decimal =
+            icu_decimal::FixedDecimalFormatter::try_new(&data_locale, Default::default()).ok()?;
+        let date = icu_datetime::DateFormatter::try_new_with_length(
+            &data_locale,
+            icu_datetime::options::length::Date::Short,
+        )
+        .ok()?;
+
+        // The symbols aren't exposed, so they are read from formatted numbers.
+        let format = |value: fixed_decimal::FixedDecimal| decimal.format_to_string(&value);
+        let mut digits = ['0'; 10];
+        for (digit, c) in digits.iter_mut().enumerate() {
+            *c = format((digit as i64).into()).chars().next()?;
+        }
+        // "1.5" and "1,000,000", with the symbols of the locale.
+        let decimal_separator = format(fixed_decimal::FixedDecimal::from(15).multiplied_pow10(-1))
+            .trim_matches(|c| digits.contains(&c))
+            .to_string();
+        let group_separator = format(1_000_000.into())
+            .chars()
+            .skip(1)
+            .take_while(|c| !digits.contains(c))
+            .collect();
+
+        Some(Self {
+            locale,
+            decimal,
+            date,
+            digits,
+            decimal_separator,
+            group_separator,
+        })
+    }
+
+    /// Create a formatter for the locale of the current process.
+    ///
+    /// See [`Locale::from_env`].
+    pub fn from_env() -> Option<Self> {
+        Self::new(Locale::from_env())
+    }
+}
+
+#[cfg(feature = "icu")]
+impl LocaleFormatter for IcuFormatter {
+    fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    fn format_number(&self, value: f64, fraction_digits: usize) -> String {
+        use fixed_decimal::{FixedDecimal, FloatPrecision, Sign};
+
+        let magnitude = -i16::try_from(fraction_digits).unwrap_or(i16::MAX);
+        let Ok(mut decimal) =
+            FixedDecimal::try_from_f64(value, FloatPrecision::Magnitude(magnitude))
+        else {
+            // Infinities and NaN.
+            return value.to_string();
+        };
+        // Don't show "-0" when a small negative value rounds to zero.
+        if decimal.is_zero() {
+            decimal.set_sign(Sign::None);
+        }
+        self.decimal.format_to_string(&decimal)
+    }
+
+    fn parse_number(&self, text: &str) -> Option<f64> {
+        let text = text.trim();
+        let text = if self.group_separator.is_empty() {
+            text.to_string()
+        } else {
+            text.replace(&self.group_separator, "")
+        };
+        let mut normalized = String::with_capacity(text.len());
+        for c in text.replace(&self.decimal_separator, ".").chars() {
+            match c {
+                c if c.is_whitespace() => {}
+                // Bidi marks around the signs of right-to-left locales.
+                '\u{200e}' | '\u{200f}' | '\u{61c}' => {}
+                // Unicode minus sign
+                '\u{2212}' => normalized.push('-'),
+                c => match self.digits.iter().position(|digit| *digit == c) {
+                    Some(digit) => normalized.push(char::from(b'0' + digit as u8)),
+                    None => normalized.push(c),
+                },
+            }
+        }
+        normalized.parse().ok()
+    }
+
+    fn format_date(&self, date: time::Date) -> String {
+        let iso =
+            icu_calendar::Date::try_new_iso_date(date.year(), date.month().into(), date.day());
+        iso.ok()
+            .and_then(|iso| self.date.format_to_string(&iso.to_any()).ok())
+            .unwrap_or_else(|| date.to_string())
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    #[test]
+    fn parse_locale() {
+        assert_eq!(Locale::parse("de-DE"), Some(Locale::new("de", Some("DE"))));
+        assert_eq!(
+            Locale::parse("pt_BR.UTF-8"),
+            Some(Locale::new("pt", Some("BR")))
+        );
+        assert_eq!(
+            Locale::parse("zh-Hant-TW"),
+            Some(Locale::new("zh", Some("TW")))
+        );
+        assert_eq!(Locale::parse("fr"), Some(Locale::new("fr", None)));
+        assert_eq!(Locale::parse(""), None);
+    }
+
+    #[test]
+    fn format_numbers() {
+        let en = BasicFormatter::new(Locale::new("en", Some("US")));
+        let de = BasicFormatter::new(Locale::new("de", Some("DE")));
+
+        assert_eq!(en.format_number(1234.56, 2), "1,234.56");
+        assert_eq!(de.format_number(1234.56, 2), "1.234,56");
+        assert_eq!(en.format_number(-1234567.0, 0), "-1,234,567");
+        assert_eq!(en.format_number(-0.001, 2), "0.00");
+        assert_eq!(en.format_number(999.0, 1), "999.0");
+        assert_eq!(de.format_shortest(-1234.5), "-1.234,5");
+        assert_eq!(en.format_shortest(3.0), "3");
+        assert_eq!(en.format_percent(0.256), "26%");
+
+        assert_eq!(de.parse_number("1.234,56"), Some(1234.56));
+        assert_eq!(en.parse_number(" 1,234.56 "), Some(1234.56));
+        assert_eq!(en.parse_number("abc"), None);
+    }
+
+    #[test]
+    fn format_dates() {
+        let day = date!(2024 - 03 - 07);
+        let format = |tag| BasicFormatter::new(Locale::parse(tag).unwrap()).format_date(day);
+
+        assert_eq!(format("en-US"), "3/7/2024");
+        assert_eq!(format("en-GB"), "07/03/2024");
+        assert_eq!(format("de-DE"), "07.03.2024");
+        assert_eq!(format("ja-JP"), "2024/03/07");
+    }
+
+    #[cfg(feature = "icu")]
+    #[test]
+    fn icu_formatter() {
+        let en = IcuFormatter::new(Locale::new("en", Some("US"))).unwrap();
+        let de = IcuFormatter::new(Locale::new("de", Some("DE"))).unwrap();
+        let ar = IcuFormatter::new(Locale::new("ar", Some("EG"))).unwrap();
+
+        assert_eq!(en.format_number(1234.56, 2), "1,234.56");
+        assert_eq!(de.format_number(1234.56, 2), "1.234,56");
+        assert_eq!(en.format_number(-0.001, 2), "0.00");
+        assert_eq!(de.parse_number("1.234,56"), Some(1234.56));
+        // Numbers in other digits are parsed back.
+        assert_eq!(
+            ar.parse_number(&ar.format_number(-1234.5, 1)),
+            Some(-1234.5)
+        );
+
+        let day = date!(2024 - 03 - 07);
+        assert_eq!(en.format_date(day), "3/7/24");
+        assert_eq!(de.format_date(day), "07.03.24");
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Popups shown above all other widgets, like dropdown lists and autocomplete suggestions.
+
+use vello::kurbo::{Point, Rect, Size};
+
+use crate::{BoxConstraints, WidgetId};
+
+/// Where a popup shown with [`EventCtx::show_popup`] is placed, relative to the
+/// widget which showed it.
+///
+/// Popups are moved to stay inside the window, and are flipped to the other side of
+/// the widget if they don't fit on the requested side.
+///
+/// [`EventCtx::show_popup`]: crate::EventCtx::show_popup
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PopupPlacement {
+    /// Below the widget, and at least as wide as it.
+    #[default]
+    Below,
+    /// Above the widget, and at least as wide as it.
+    Above,
+    /// To the right of the widget, aligned with its top edge.
+    After,
+}
+
+impl PopupPlacement {
+    /// The origin of a popup of the given size, in window coordinates.
+    fn origin(self, anchor: Rect, popup: Size, window: Size) -> Point {
+        let fits_below = anchor.y1 + popup.height <= window.height;
+        let fits_above = anchor.y0 >= popup.height;
+        let (x, y) = match self {
+            Self::Below if fits_below || !fits_above => (anchor.x0, anchor.y1),
+            Self::Above if fits_above || !fits_below => (anchor.x0, anchor.y0 - popup.height),
+            Self::Below => (anchor.x0, anchor.y0 - popup.height),
+            Self::Above => (anchor.x0, anchor.y1),
+            Self::After => {
+                let fits_after = anchor.x1 + popup.width <= window.width;
+                if fits_after || anchor.x0 < popup.width {
+                    (anchor.x1, anchor.y0)
+                } else {
+                    (anchor.x0 - popup.width, anchor.y0)
+                }
+            }
+        };
+        fit_in_window(Point::new(x, y), popup, window)
+    }
+}
+
+/// Move the origin of a popup of the given size so the popup stays inside the window.
+fn fit_in_window(origin: Point, popup: Size, window: Size) -> Point {
+    Point::new(
+        origin.x.min(window.width - popup.width).max(0.),
+        origin.y.min(window.height - popup.height).max(0.),
+    )
+}
+
+/// A popup shown with [`EventCtx::show_popup`].
+///
+/// The popups aren't children of any widget: their root widgets are separate roots
+/// of the widget tree, which the passes visit after the root widget, from bottom to top.
+///
+/// [`EventCtx::show_popup`]: crate::EventCtx::show_popup
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PopupState {
+    /// The id of the popup's root widget.
+    pub(crate) id: WidgetId,
+    /// The widget which showed the popup, which it's placed next to.
+    pub(crate) anchor: WidgetId,
+    pub(crate) placement: PopupPlacement,
+    /// Where the popup was requested in window coordinates, for popups shown at a point
+    /// rather than next to their anchor, like context menus.
+    ///
+    /// These popups aren't kept open by clicks on their anchor.
+    pub(crate) position: Option<Point>,
+    /// The layout rect of the anchor in window coordinates, as of the last compose pass.
+    pub(crate) anchor_rect: Rect,
+}
+
+impl PopupState {
+    /// The constraints the popup is laid out with, inside the root widget of the given size.
+    ///
+    /// Popups are confined to the root widget, which fills the window unless the window
+    /// is sized to its content.
+    pub(crate) fn constraints(&self, root_size: Size) -> BoxConstraints {
+        let min_width = match self.placement {
+            _ if self.position.is_some() => 0.,
+            PopupPlacement::Below | PopupPlacement::Above => {
+                self.anchor_rect.width().min(root_size.width)
+            }
+            PopupPlacement::After => 0.,
+        };
+        BoxConstraints::new(Size::new(min_width, 0.), root_size)
+    }
+
+    /// The origin of the popup once laid out with the given size, in window coordinates.
+    pub(crate) fn origin(&self, popup_size: Size, root_size: Size) -> Point {
+        match self.position {
+            Some(position) => fit_in_window(position, popup_size, root_size),
+            None => self
+                .placement
+                .origin(self.anchor_rect, popup_size, root_size),
+        }
+    }
+}
+
+/// The id of the accessibility node of the live region, see [`EventCtx::announce`].
+///
+/// Like the popups, the live region is a child of the root widget's node.
+///
+/// [`EventCtx::announce`]: crate::EventCtx::announce
+pub(crate) const ANNOUNCEMENT_NODE: WidgetId = WidgetId::reserved(0);
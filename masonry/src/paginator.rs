@@ -0,0 +1,306 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Splitting widget content into pages, for printing.
+
+use vello::kurbo::{Affine, Rect, Size};
+use vello::peniko::Mix;
+use vello::Scene;
+
+use crate::widget::{SizedBox, WidgetRef};
+use crate::{RenderRoot, RenderRootOptions, Widget, WindowSizePolicy};
+
+/// How the [`Paginator`] may split a widget across pages.
+///
+/// This is returned by [`Widget::page_break`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PageBreak {
+    /// Pages may break between the children of the widget.
+    ///
+    /// Widgets without children are kept on a single page.
+    #[default]
+    Auto,
+    /// The widget is kept on a single page, unless it's taller than a page.
+    Avoid,
+    /// The widget always starts a new page.
+    Before,
+}
+
+/// A single page produced by a [`Paginator`].
+#[derive(Clone)]
+pub struct Page {
+    /// The content of the page, with the top of the page at the origin.
+    pub scene: Scene,
+    /// The area of the content shown on this page.
+    pub content_rect: Rect,
+}
+
+/// Lays out a widget tree against the width of a page, and splits it into pages.
+///
+/// Pages break at the edges of widgets, as allowed by their [`PageBreak`] hint.
+/// When no break is allowed within a page, the content is cut at the bottom of the page.
+///
+/// The content is laid out once, with the width of a page and an unbounded height,
+/// and its scene is cut into pages. Widgets aren't laid out again against the height
+/// left on each page, so widgets which fill the height they're given, like a portal,
+/// should be avoided in the paginated content.
+///
+/// The resulting scenes can be handed to a PDF or print backend.
+#[derive(Clone, Debug)]
+pub struct Paginator {
+    page_size: Size,
+}
+
+/// Content at most this far past a page break is considered on the page.
+const BREAK_EPSILON: f64 = 1e-6;
+
+impl Paginator {
+    /// Create a paginator producing pages of `page_size`, in logical pixels.
+    pub fn new(page_size: Size) -> Self {
+        Self { page_size }
+    }
+
+    pub fn page_size(&self) -> Size {
+        self.page_size
+    }
+
+    /// Lay out `root_widget` and split it into pages.
+    ///
+    /// The widget is laid out in its own [`RenderRoot`], created with `options`.
+    /// Its width is set to the width of the page, and its height is unbounded.
+    pub fn paginate(&self, root_widget: impl Widget, options: RenderRootOptions) -> Vec<Page> {
+        let root_widget = SizedBox::new(root_widget).width(self.page_size.width);
+        let mut root = RenderRoot::new(
+            root_widget,
+            RenderRootOptions {
+                size_policy: WindowSizePolicy::Content,
+                ..options
+            },
+        );
+
+        let content = root.get_root_widget();
+        let content_id = content.id();
+        let content_height = content.ctx().layout_rect().height();
+        let breaks = PageBreaks::collect(content);
+        let page_ranges = breaks.page_ranges(content_height, self.page_size.height);
+
+        let Some(content_scene) = root.capture_widget_scene(content_id) else {
+            return Vec::new();
+        };
+        page_ranges
+            .into_iter()
+            .map(|(start, end)| {
+                let content_rect = Rect::new(0.0, start, self.page_size.width, end);
+                let mut scene = Scene::new();
+                scene.push_layer(
+                    Mix::Clip,
+                    1.0,
+                    Affine::IDENTITY,
+                    &Rect::from_origin_size((0.0, 0.0), content_rect.size()),
+                );
+                scene.append(&content_scene.scene, Some(Affine::translate((0.0, -start))));
+                scene.pop_layer();
+                Page {
+                    scene,
+                    content_rect,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The vertical positions where pages may break, in content coordinates.
+#[derive(Default)]
+struct PageBreaks {
+    candidates: Vec<f64>,
+    forced: Vec<f64>,
+    /// Spans which pages can't break inside.
+    unbreakable: Vec<(f64, f64)>,
+}
+
+impl PageBreaks {
+    fn collect(root: WidgetRef<'_, dyn Widget>) -> Self {
+        let mut breaks = Self::default();
+        breaks.visit(root);
+        breaks.candidates.sort_by(f64::total_cmp);
+        breaks.forced.sort_by(f64::total_cmp);
+        breaks
+    }
+
+    fn visit(&mut self, widget: WidgetRef<'_, dyn Widget>) {
+        if widget.ctx().is_stashed() {
+            return;
+        }
+        let rect = widget.ctx().window_target_rect();
+        let children = widget.children();
+
+        self.candidates.extend([rect.y0, rect.y1]);
+        match widget.widget.page_break() {
+            PageBreak::Auto if children.is_empty() => self.unbreakable.push((rect.y0, rect.y1)),
+            PageBreak::Auto => {}
+            PageBreak::Avoid => self.unbreakable.push((rect.y0, rect.y1)),
+            PageBreak::Before => self.forced.push(rect.y0),
+        }
+
+        for child in children {
+            self.visit(child);
+        }
+    }
+
+    fn is_allowed(&self, y: f64) -> bool {
+        !self
+            .unbreakable
+            .iter()
+            .any(|&(y0, y1)| y0 + BREAK_EPSILON < y && y < y1 - BREAK_EPSILON)
+    }
+
+    /// Split `0..content_height` into ranges at most `page_height` tall.
+    fn page_ranges(&self, content_height: f64, page_height: f64) -> Vec<(f64, f64)> {
+        let mut ranges = Vec::new();
+        let mut start = 0.0;
+        loop {
+            let limit = start + page_height;
+            let after_start = |y: f64| y > start + BREAK_EPSILON;
+
+            let end = if let Some(&forced) = self
+                .forced
+                .iter()
+                .find(|&&y| after_start(y) && y <= limit.min(content_height))
+            {
+                forced
+            } else if limit >= content_height - BREAK_EPSILON {
+                content_height
+            } else {
+                self.candidates
+                    .iter()
+                    .rfind(|&&y| after_start(y) && y <= limit && self.is_allowed(y))
+                    .copied()
+                    .unwrap_or(limit)
+            };
+
+            ranges.push((start, end));
+            if end >= content_height - BREAK_EPSILON || page_height <= 0.0 {
+                return ranges;
+            }
+            start = end;
+        }
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::screenshots::render_scene;
+    use crate::testing::ModularWidget;
+    use crate::widget::{CrossAxisAlignment, Flex};
+    use crate::Color;
+
+    fn test_options() -> RenderRootOptions {
+        RenderRootOptions {
+            use_system_fonts: false,
+            size_policy: WindowSizePolicy::Content,
+            scale_factor: 1.0,
+            test_font: None,
+            formatter: None,
+            pixel_snapping: false,
+        }
+    }
+
+    fn content_ranges(pages: &[Page]) -> Vec<(f64, f64)> {
+        pages
+            .iter()
+            .map(|page| (page.content_rect.y0, page.content_rect.y1))
+            .collect()
+    }
+
+    #[test]
+    fn break_between_children() {
+        let mut column = Flex::column().gap(0.);
+        for _ in 0..10 {
+            column = column.with_child(SizedBox::empty().width(50.).height(30.));
+        }
+
+        let pages = Paginator::new(Size::new(200., 100.)).paginate(column, test_options());
+
+        assert_eq!(
+            content_ranges(&pages),
+            [(0., 90.), (90., 180.), (180., 270.), (270., 300.)]
+        );
+        assert!(pages.iter().all(|page| page.content_rect.width() == 200.));
+    }
+
+    #[test]
+    fn keep_leaves_whole() {
+        // The bottom of the short box is inside the tall one, so the page breaks
+        // before the row.
+        let column = Flex::column()
+            .gap(0.)
+            .with_child(SizedBox::empty().width(50.).height(60.))
+            .with_child(
+                Flex::row()
+                    .gap(0.)
+                    .cross_axis_alignment(CrossAxisAlignment::Start)
+                    .with_child(SizedBox::empty().width(50.).height(20.))
+                    .with_child(SizedBox::empty().width(50.).height(60.)),
+            );
+
+        let pages = Paginator::new(Size::new(200., 100.)).paginate(column, test_options());
+
+        assert_eq!(content_ranges(&pages), [(0., 60.), (60., 120.)]);
+    }
+
+    #[test]
+    fn cut_oversized_widget() {
+        let widget = SizedBox::empty().width(50.).height(250.);
+
+        let pages = Paginator::new(Size::new(200., 100.)).paginate(widget, test_options());
+
+        assert_eq!(
+            content_ranges(&pages),
+            [(0., 100.), (100., 200.), (200., 250.)]
+        );
+    }
+
+    #[test]
+    fn forced_break() {
+        let column = Flex::column()
+            .gap(0.)
+            .with_child(SizedBox::empty().width(50.).height(30.))
+            .with_child(
+                ModularWidget::new(())
+                    .layout_fn(|_, _, _| Size::new(50., 30.))
+                    .page_break(PageBreak::Before),
+            )
+            .with_child(SizedBox::empty().width(50.).height(30.));
+
+        let pages = Paginator::new(Size::new(200., 100.)).paginate(column, test_options());
+
+        assert_eq!(content_ranges(&pages), [(0., 30.), (30., 90.)]);
+    }
+
+    #[test]
+    fn page_scenes_show_their_content() {
+        let red = Color::rgb8(255, 0, 0);
+        let blue = Color::rgb8(0, 0, 255);
+        let column = Flex::column()
+            .gap(0.)
+            .with_child(SizedBox::empty().width(200.).height(60.).background(red))
+            .with_child(SizedBox::empty().width(200.).height(60.).background(blue));
+
+        let pages = Paginator::new(Size::new(200., 100.)).paginate(column, test_options());
+        assert_eq!(content_ranges(&pages), [(0., 60.), (60., 120.)]);
+        if std::env::var("SKIP_RENDER_TESTS").is_ok_and(|it| !it.is_empty()) {
+            return;
+        }
+
+        // Each page starts with its own content, and the next page's content is cut off.
+        let render = |page: &Page| render_scene(&page.scene, 200, 100, Color::WHITE).unwrap();
+        let first = render(&pages[0]);
+        assert_eq!(first.get_pixel(100, 30).0, [255, 0, 0, 255]);
+        assert_eq!(first.get_pixel(100, 80).0, [255, 255, 255, 255]);
+        let second = render(&pages[1]);
+        assert_eq!(second.get_pixel(100, 30).0, [0, 0, 255, 255]);
+        assert_eq!(second.get_pixel(100, 80).0, [255, 255, 255, 255]);
+    }
+}
@@ -1,16 +1,27 @@
 // Copyright 2024 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
-use accesskit::{NodeBuilder, NodeId, Tree, TreeUpdate};
+use std::collections::HashMap;
+
+use accesskit::{Live, NodeBuilder, NodeId, Role, Tree, TreeUpdate};
 use tracing::{debug, info_span, trace};
 use vello::kurbo::Rect;
 
+use crate::overlay::ANNOUNCEMENT_NODE;
 use crate::passes::recurse_on_children;
 use crate::render_root::{RenderRoot, RenderRootState};
-use crate::tree_arena::ArenaMut;
-use crate::{AccessCtx, Widget, WidgetState};
+use crate::tree_arena::{ArenaMut, ArenaMutChildren};
+use crate::{AccessCtx, Widget, WidgetFlags, WidgetId, WidgetState};
+
+/// The flags which leave a widget and its descendants out of the accessibility tree.
+const HIDDEN_FLAGS: WidgetFlags =
+    WidgetFlags::IS_ACCESSIBILITY_HIDDEN.union(WidgetFlags::IS_ACCESSIBILITY_HIDDEN_BY_PARENT);
 
 // --- MARK: BUILD TREE ---
+/// Build the nodes of the widget and its descendants which need it.
+///
+/// `extra_children` are added to the children of the widget's node, after its own.
+#[allow(clippy::too_many_arguments)] // reason: the arguments are the state threaded through the recursion
 fn build_accessibility_tree(
     global_state: &mut RenderRootState,
     tree_update: &mut TreeUpdate,
@@ -18,6 +29,8 @@ fn build_accessibility_tree(
     mut state: ArenaMut<'_, WidgetState>,
     rebuild_all: bool,
     scale_factor: f64,
+    visible_rect: Rect,
+    extra_children: &[NodeId],
 ) {
     let _span = global_state
         .trace
@@ -25,11 +38,39 @@ fn build_accessibility_tree(
         .then(|| widget.item.make_trace_span().entered());
     let id = state.item.id;
 
-    if !rebuild_all && !state.item.needs_accessibility {
+    if !rebuild_all && !state.item.flags.contains(WidgetFlags::NEEDS_ACCESSIBILITY) {
         return;
     }
 
-    if rebuild_all || state.item.request_accessibility {
+    // The area in which the children of this widget are visible, in window coordinates.
+    let visible_rect = match &state.item.clip_path {
+        Some(clip) => visible_rect.intersect(state.item.to_window_rect(clip.bounding_box())),
+        None => visible_rect,
+    };
+    update_elided_children(
+        &*widget.item,
+        state.children.reborrow_mut(),
+        &global_state.positions_in_set,
+        visible_rect,
+    );
+
+    // A child which was hidden or shown changes the children listed by this node.
+    let hidden_children_changed = widget.item.children_ids().iter().any(|child_id| {
+        state.children.get_child(*child_id).is_some_and(|child| {
+            child
+                .item
+                .flags
+                .contains(WidgetFlags::ACCESSIBILITY_HIDDEN_CHANGED)
+        })
+    });
+
+    if rebuild_all
+        || hidden_children_changed
+        || state
+            .item
+            .flags
+            .contains(WidgetFlags::REQUEST_ACCESSIBILITY)
+    {
         if global_state.trace.access {
             trace!(
                 "Building accessibility node for widget '{}' {}",
@@ -49,6 +90,9 @@ fn build_accessibility_tree(
         };
         let mut node = build_access_node(widget.item, &mut ctx);
         widget.item.accessibility(&mut ctx, &mut node);
+        for child in extra_children {
+            node.push_child(*child);
+        }
         let node = node.build();
 
         let id: NodeId = ctx.widget_state.id.into();
@@ -63,8 +107,10 @@ fn build_accessibility_tree(
         ctx.tree_update.nodes.push((id, node));
     }
 
-    state.item.request_accessibility = false;
-    state.item.needs_accessibility = false;
+    state
+        .item
+        .flags
+        .remove(WidgetFlags::REQUEST_ACCESSIBILITY | WidgetFlags::NEEDS_ACCESSIBILITY);
 
     let id = state.item.id;
     let parent_state = state.item;
@@ -73,16 +119,79 @@ fn build_accessibility_tree(
         widget.reborrow_mut(),
         state.children,
         |widget, mut state| {
-            // TODO - We don't skip updating stashed items because doing so
-            // is error-prone. We may want to revisit that decision.
-            build_accessibility_tree(
-                global_state,
-                tree_update,
-                widget,
-                state.reborrow_mut(),
-                rebuild_all,
-                scale_factor,
-            );
+            let flags = &mut state.item.flags;
+            let hidden = flags.intersects(HIDDEN_FLAGS | WidgetFlags::IS_ACCESSIBILITY_ELIDED);
+            // A subtree which is shown again was removed from the tree, so we rebuild all of it.
+            let shown = !hidden && flags.contains(WidgetFlags::ACCESSIBILITY_HIDDEN_CHANGED);
+            flags.remove(WidgetFlags::ACCESSIBILITY_HIDDEN_CHANGED);
+            if hidden {
+                skip_accessibility_tree(widget, state.reborrow_mut());
+            } else {
+                // TODO - We don't skip updating stashed items because doing so
+                // is error-prone. We may want to revisit that decision.
+                build_accessibility_tree(
+                    global_state,
+                    tree_update,
+                    widget,
+                    state.reborrow_mut(),
+                    rebuild_all || shown,
+                    scale_factor,
+                    visible_rect,
+                    &[],
+                );
+            }
+            parent_state.merge_up(state.item);
+        },
+    );
+}
+
+/// Elide the children which are items of a set and are outside `visible_rect`, and
+/// restore the ones which became visible.
+///
+/// See [`set_position_in_set`](crate::EventCtx::set_position_in_set).
+fn update_elided_children(
+    widget: &dyn Widget,
+    mut children: ArenaMutChildren<'_, WidgetState>,
+    positions_in_set: &HashMap<WidgetId, (usize, usize)>,
+    visible_rect: Rect,
+) {
+    for child_id in widget.children_ids() {
+        let Some(child) = children.get_child_mut(child_id) else {
+            continue;
+        };
+        let visible = child.item.window_layout_rect().intersect(visible_rect);
+        let flags = &mut child.item.flags;
+        // The focused widget is always in the tree, so that assistive technologies can find it.
+        let elided = positions_in_set.contains_key(&child_id)
+            && !flags.contains(WidgetFlags::HAS_FOCUS)
+            && (visible.width() <= 0. || visible.height() <= 0.);
+        if flags.contains(WidgetFlags::IS_ACCESSIBILITY_ELIDED) != elided {
+            flags.set(WidgetFlags::IS_ACCESSIBILITY_ELIDED, elided);
+            flags.insert(WidgetFlags::ACCESSIBILITY_HIDDEN_CHANGED);
+        }
+    }
+}
+
+/// Clear the accessibility flags of a subtree hidden with
+/// [`set_accessibility_hidden`](crate::EventCtx::set_accessibility_hidden), without building its nodes.
+fn skip_accessibility_tree(
+    mut widget: ArenaMut<'_, Box<dyn Widget>>,
+    state: ArenaMut<'_, WidgetState>,
+) {
+    state.item.flags.remove(
+        WidgetFlags::REQUEST_ACCESSIBILITY
+            | WidgetFlags::NEEDS_ACCESSIBILITY
+            | WidgetFlags::ACCESSIBILITY_HIDDEN_CHANGED,
+    );
+
+    let id = state.item.id;
+    let parent_state = state.item;
+    recurse_on_children(
+        id,
+        widget.reborrow_mut(),
+        state.children,
+        |widget, mut state| {
+            skip_accessibility_tree(widget, state.reborrow_mut());
             parent_state.merge_up(state.item);
         },
     );
@@ -96,11 +205,18 @@ fn build_access_node(widget: &mut dyn Widget, ctx: &mut AccessCtx) -> NodeBuilde
         ctx.scale_factor,
     ));
 
+    let children = &ctx.widget_state_children;
     node.set_children(
         widget
             .children_ids()
             .iter()
             .copied()
+            .filter(|id| {
+                !children.get_child(*id).is_some_and(|child| {
+                    (child.item.flags)
+                        .intersects(HIDDEN_FLAGS | WidgetFlags::IS_ACCESSIBILITY_ELIDED)
+                })
+            })
             .map(|id| id.into())
             .collect::<Vec<NodeId>>(),
     );
@@ -119,6 +235,13 @@ fn build_access_node(widget: &mut dyn Widget, ctx: &mut AccessCtx) -> NodeBuilde
     if ctx.widget_state.clip_path.is_some() {
         node.set_clips_children();
     }
+    if let Some(&(index, size)) = ctx.global_state.positions_in_set.get(&ctx.widget_state.id) {
+        node.set_position_in_set(index + 1);
+        node.set_size_of_set(size);
+    }
+    if let Some(tooltip) = ctx.global_state.tooltips.get(&ctx.widget_state.id) {
+        node.set_tooltip(&**tooltip);
+    }
     if ctx.accepts_focus() && !ctx.is_disabled() && !ctx.is_stashed() {
         node.add_action(accesskit::Action::Focus);
     }
@@ -134,6 +257,16 @@ fn to_accesskit_rect(r: Rect, scale_factor: f64) -> accesskit::Rect {
     accesskit::Rect::new(sr.x0, sr.y0, sr.x1, sr.y1)
 }
 
+/// Whether the widget or one of its ancestors is left out of the accessibility tree.
+fn is_accessibility_hidden(root: &RenderRoot, id: WidgetId) -> bool {
+    let states = &root.widget_arena.widget_states;
+    states.get_id_path(id).iter().any(|id| {
+        states
+            .find(*id)
+            .is_some_and(|state| state.item.flags.intersects(HIDDEN_FLAGS))
+    })
+}
+
 // --- MARK: ROOT ---
 pub(crate) fn run_accessibility_pass(root: &mut RenderRoot, scale_factor: f64) -> TreeUpdate {
     let _span = info_span!("accessibility").entered();
@@ -149,37 +282,158 @@ pub(crate) fn run_accessibility_pass(root: &mut RenderRoot, scale_factor: f64) -
         focus: root
             .global_state
             .focused_widget
+            .filter(|id| !is_accessibility_hidden(root, *id))
             .unwrap_or(root.root.id())
             .into(),
     };
 
-    let (root_widget, root_state) = {
-        let widget_id = root.root.id();
-        let widget = root
-            .widget_arena
-            .widgets
-            .find_mut(widget_id)
-            .expect("root_accessibility: root not in widget tree");
-        let state = root
-            .widget_arena
-            .widget_states
-            .find_mut(widget_id)
-            .expect("root_accessibility: root state not in widget tree");
-        (widget, state)
-    };
-
     if root.rebuild_access_tree {
         debug!("Running ACCESSIBILITY pass with rebuild_all");
     }
-    build_accessibility_tree(
-        &mut root.global_state,
-        &mut tree_update,
-        root_widget,
-        root_state,
-        root.rebuild_access_tree,
-        scale_factor,
-    );
+
+    // The popups and the live region of `EventCtx::announce` are children
+    // of the root widget's node.
+    let root_ids = root.root_ids();
+    let mut root_extra_children: Vec<NodeId> =
+        root_ids[1..].iter().map(|id| (*id).into()).collect();
+    if root.global_state.announcement.is_some() {
+        root_extra_children.push(ANNOUNCEMENT_NODE.into());
+    }
+
+    let window_rect = root.get_kurbo_size().to_rect();
+    for (i, id) in root_ids.iter().enumerate() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(*id);
+        let extra_children: &[NodeId] = if i == 0 { &root_extra_children } else { &[] };
+        build_accessibility_tree(
+            &mut root.global_state,
+            &mut tree_update,
+            root_widget,
+            root_state,
+            root.rebuild_access_tree,
+            scale_factor,
+            window_rect,
+            extra_children,
+        );
+    }
     root.rebuild_access_tree = false;
 
+    // The live region is sent again whenever the root widget's node is.
+    let root_node_id: NodeId = root.root.id().into();
+    let root_node_built = (tree_update.nodes.iter()).any(|(id, _)| *id == root_node_id);
+    let announcement = root.global_state.announcement.as_ref();
+    if let Some(announcement) = announcement.filter(|_| root_node_built) {
+        let mut live_region = NodeBuilder::new(Role::Status);
+        live_region.set_live(Live::Polite);
+        live_region.set_name(announcement.as_str());
+        tree_update
+            .nodes
+            .push((ANNOUNCEMENT_NODE.into(), live_region.build()));
+    }
+
     tree_update
 }
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::PhysicalSize;
+
+    use crate::render_root::{RenderRootOptions, WindowSizePolicy};
+    use crate::testing::{widget_ids, TestHarness};
+    use crate::widget::{Flex, Portal, SizedBox};
+    use crate::{Point, WindowEvent};
+
+    fn has_node(update: &TreeUpdate, id: WidgetId) -> bool {
+        update
+            .nodes
+            .iter()
+            .any(|(node_id, _)| *node_id == id.into())
+    }
+
+    fn lists_child(update: &TreeUpdate, parent: WidgetId, child: WidgetId) -> bool {
+        update
+            .nodes
+            .iter()
+            .find(|(node_id, _)| *node_id == parent.into())
+            .is_some_and(|(_, node)| node.children().contains(&child.into()))
+    }
+
+    fn render_root(widget: impl Widget) -> RenderRoot {
+        RenderRoot::new(
+            widget,
+            RenderRootOptions {
+                use_system_fonts: false,
+                size_policy: WindowSizePolicy::User,
+                scale_factor: 1.0,
+                test_font: None,
+                formatter: None,
+                pixel_snapping: false,
+            },
+        )
+    }
+
+    #[test]
+    fn hidden_subtree_is_left_out() {
+        let [flex_id, divider_id, inner_id] = widget_ids();
+        let divider = SizedBox::new_with_id(SizedBox::empty(), inner_id);
+        let widget = Flex::row().with_child_id(divider, divider_id);
+        let mut harness = TestHarness::create(SizedBox::new_with_id(widget, flex_id));
+        assert!(harness.access_node(inner_id).is_some());
+
+        harness.edit_widget(divider_id, |mut divider| {
+            divider.ctx.set_accessibility_hidden(true);
+        });
+        assert!(harness.access_node(divider_id).is_none());
+        assert!(harness.access_node(inner_id).is_none());
+        assert!(harness.access_node(flex_id).is_some());
+
+        // Showing the subtree again adds all of its nodes back.
+        harness.edit_widget(divider_id, |mut divider| {
+            divider.ctx.set_accessibility_hidden(false);
+        });
+        assert!(harness.access_node(divider_id).is_some());
+        assert!(harness.access_node(inner_id).is_some());
+    }
+
+    #[test]
+    fn offscreen_items_are_elided() {
+        const SET_SIZE: usize = 10_000;
+        let [portal_id, list_id] = widget_ids();
+        let item_ids: Vec<WidgetId> = (0..20).map(|_| WidgetId::next()).collect();
+        let mut list = Flex::column().gap(0.);
+        for id in &item_ids {
+            list = list.with_child_id(SizedBox::empty().width(100.).height(20.), *id);
+        }
+        let widget = Portal::new(SizedBox::new_with_id(list, list_id));
+        let mut root = render_root(SizedBox::new_with_id(widget, portal_id));
+        root.handle_window_event(WindowEvent::Resize(PhysicalSize::new(100, 100)));
+        for (index, id) in item_ids.iter().enumerate() {
+            // The items are the last of a long list, as a virtualized list would report them.
+            root.edit_widget(*id, |mut item| {
+                item.ctx
+                    .set_position_in_set(Some((SET_SIZE - 20 + index, SET_SIZE)));
+            });
+        }
+
+        let update = root.update_accessibility();
+        let (_, first) = update
+            .nodes
+            .iter()
+            .find(|(node_id, _)| *node_id == item_ids[0].into())
+            .unwrap();
+        assert_eq!(first.position_in_set(), Some(SET_SIZE - 19));
+        assert_eq!(first.size_of_set(), Some(SET_SIZE));
+        assert!(!lists_child(&update, list_id, item_ids[10]));
+
+        // Scrolling down brings the items into the tree.
+        root.edit_widget(portal_id, |mut portal| {
+            let mut portal = portal.downcast::<Portal<SizedBox>>();
+            Portal::set_viewport_pos(&mut portal, Point::new(0., 200.));
+        });
+        let update = root.update_accessibility();
+        assert!(lists_child(&update, list_id, item_ids[10]));
+        assert!(has_node(&update, item_ids[10]));
+        assert!(!lists_child(&update, list_id, item_ids[0]));
+    }
+}
@@ -6,7 +6,7 @@ use tracing::info_span;
 use crate::passes::recurse_on_children;
 use crate::render_root::{RenderRoot, RenderRootState};
 use crate::tree_arena::ArenaMut;
-use crate::{UpdateCtx, Widget, WidgetState};
+use crate::{UpdateCtx, Widget, WidgetFlags, WidgetState};
 
 // --- MARK: UPDATE ANIM ---
 fn update_anim_for_widget(
@@ -20,16 +20,16 @@ fn update_anim_for_widget(
         .anim
         .then(|| widget.item.make_trace_span().entered());
 
-    if !state.item.needs_anim {
+    if !state.item.flags.contains(WidgetFlags::NEEDS_ANIM) {
         return;
     }
-    state.item.needs_anim = false;
+    state.item.flags.remove(WidgetFlags::NEEDS_ANIM);
 
     // Most passes reset their `needs` and `request` flags after the call to
     // the widget method, but it's valid and expected for `request_anim` to be
     // set in response to `AnimFrame`.
-    if state.item.request_anim {
-        state.item.request_anim = false;
+    if state.item.flags.contains(WidgetFlags::REQUEST_ANIM) {
+        state.item.flags.remove(WidgetFlags::REQUEST_ANIM);
         let mut ctx = UpdateCtx {
             global_state,
             widget_state: state.item,
@@ -56,11 +56,13 @@ fn update_anim_for_widget(
 pub(crate) fn run_update_anim_pass(root: &mut RenderRoot, elapsed_ns: u64) {
     let _span = info_span!("update_anim").entered();
 
-    let (root_widget, mut root_state) = root.widget_arena.get_pair_mut(root.root.id());
-    update_anim_for_widget(
-        &mut root.global_state,
-        root_widget,
-        root_state.reborrow_mut(),
-        elapsed_ns,
-    );
+    for id in root.root_ids() {
+        let (root_widget, mut root_state) = root.widget_arena.get_pair_mut(id);
+        update_anim_for_widget(
+            &mut root.global_state,
+            root_widget,
+            root_state.reborrow_mut(),
+            elapsed_ns,
+        );
+    }
 }
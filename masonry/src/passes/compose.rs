@@ -2,12 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use tracing::info_span;
-use vello::kurbo::Vec2;
+use vello::kurbo::{Affine, Vec2};
 
-use crate::passes::recurse_on_children;
+use crate::passes::{recurse_on_children, snap_to_pixel};
 use crate::render_root::{RenderRoot, RenderRootSignal, RenderRootState};
 use crate::tree_arena::ArenaMut;
-use crate::{ComposeCtx, Widget, WidgetState};
+use crate::{ComposeCtx, Widget, WidgetFlags, WidgetState};
 
 // --- MARK: RECURSE ---
 fn compose_widget(
@@ -15,20 +15,37 @@ fn compose_widget(
     mut widget: ArenaMut<'_, Box<dyn Widget>>,
     mut state: ArenaMut<'_, WidgetState>,
     parent_moved: bool,
-    parent_translation: Vec2,
+    parent_transform: Affine,
+    parent_target_transform: Affine,
 ) {
     let _span = global_state
         .trace
         .compose
         .then(|| widget.item.make_trace_span().entered());
+    global_state.frame_stats.widgets_composed += 1;
 
-    let moved = parent_moved || state.item.translation_changed;
-    let translation = parent_translation + state.item.translation + state.item.origin.to_vec2();
-    state.item.window_origin = translation.to_point();
-
-    if !parent_moved && !state.item.translation_changed && !state.item.needs_compose {
-        return;
+    let moved = parent_moved || state.item.flags.contains(WidgetFlags::TRANSLATION_CHANGED);
+    // The position of the widget is in its parent's coordinate space, which may be transformed.
+    let target_offset = state.item.translation + state.item.origin.to_vec2();
+    let mut target_transform =
+        parent_target_transform * Affine::translate(target_offset) * state.item.transform;
+    let mut transform = parent_transform
+        * Affine::translate(target_offset + state.item.presentation_offset)
+        * state.item.transform;
+    if let Some(scale) = global_state.pixel_snap_scale {
+        // Widgets are aligned to physical pixels, so that their edges aren't blurry.
+        let snap = |t: Affine| {
+            let v = t.translation();
+            t.with_translation(Vec2::new(
+                snap_to_pixel(v.x, scale),
+                snap_to_pixel(v.y, scale),
+            ))
+        };
+        transform = snap(transform);
+        target_transform = snap(target_transform);
     }
+    state.item.window_transform = transform;
+    state.item.window_target_transform = target_transform;
 
     let mut ctx = ComposeCtx {
         global_state,
@@ -36,24 +53,38 @@ fn compose_widget(
         widget_state_children: state.children.reborrow_mut(),
         widget_children: widget.children.reborrow_mut(),
     };
-    if ctx.widget_state.request_compose {
+    if ctx
+        .widget_state
+        .flags
+        .contains(WidgetFlags::REQUEST_COMPOSE)
+    {
         widget.item.compose(&mut ctx);
     }
 
     // TODO - Add unit tests for this.
-    if moved && state.item.accepts_text_input && global_state.is_focused(state.item.id) {
-        let ime_area = state.item.get_ime_area();
+    if moved
+        && state.item.flags.contains(WidgetFlags::ACCEPTS_TEXT_INPUT)
+        && global_state.is_focused(state.item.id)
+    {
+        let ime_area = global_state.get_ime_area(state.item);
         global_state.emit_signal(RenderRootSignal::new_ime_moved_signal(ime_area));
     }
 
     // We need to update the accessibility node's coordinates and repaint it at the new position.
-    state.item.request_accessibility = true;
-    state.item.needs_accessibility = true;
-    state.item.needs_paint = true;
+    // Widgets which are only visited because a descendant needs compose keep both.
+    if moved {
+        state.item.flags.insert(
+            WidgetFlags::REQUEST_ACCESSIBILITY
+                | WidgetFlags::NEEDS_ACCESSIBILITY
+                | WidgetFlags::NEEDS_PAINT,
+        );
+    }
 
-    state.item.needs_compose = false;
-    state.item.request_compose = false;
-    state.item.translation_changed = false;
+    state.item.flags.remove(
+        WidgetFlags::NEEDS_COMPOSE
+            | WidgetFlags::REQUEST_COMPOSE
+            | WidgetFlags::TRANSLATION_CHANGED,
+    );
 
     let id = state.item.id;
     let parent_state = state.item;
@@ -62,34 +93,53 @@ fn compose_widget(
         widget.reborrow_mut(),
         state.children,
         |widget, mut state| {
+            // If neither this widget nor its ancestors moved, and nothing in the child's
+            // subtree requested compose, the whole subtree is unchanged and we skip it.
+            if !is_compose_dirty(state.item, moved) {
+                return;
+            }
             compose_widget(
                 global_state,
                 widget,
                 state.reborrow_mut(),
                 moved,
-                translation,
+                transform,
+                target_transform,
             );
             parent_state.merge_up(state.item);
         },
     );
 }
 
+/// Returns `true` if the subtree rooted at this widget must be visited by the compose pass.
+fn is_compose_dirty(state: &WidgetState, parent_moved: bool) -> bool {
+    parent_moved
+        || state.flags.contains(WidgetFlags::TRANSLATION_CHANGED)
+        || state.flags.contains(WidgetFlags::NEEDS_COMPOSE)
+}
+
 // --- MARK: ROOT ---
 pub(crate) fn run_compose_pass(root: &mut RenderRoot) {
     let _span = info_span!("compose").entered();
 
     // If widgets are moved, pointer-related info may be stale.
     // For instance, the "hovered" widget may have moved and no longer be under the pointer.
-    if root.root_state().needs_compose {
+    if root.roots_have(WidgetFlags::NEEDS_COMPOSE) {
         root.global_state.needs_pointer_pass = true;
     }
 
-    let (root_widget, root_state) = root.widget_arena.get_pair_mut(root.root.id());
-    compose_widget(
-        &mut root.global_state,
-        root_widget,
-        root_state,
-        false,
-        Vec2::ZERO,
-    );
+    for id in root.root_ids() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(id);
+        if !is_compose_dirty(root_state.item, false) {
+            continue;
+        }
+        compose_widget(
+            &mut root.global_state,
+            root_widget,
+            root_state,
+            false,
+            Affine::IDENTITY,
+            Affine::IDENTITY,
+        );
+    }
 }
@@ -1,20 +1,32 @@
 // Copyright 2024 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
+use std::panic::{self, AssertUnwindSafe};
+
 use dpi::LogicalPosition;
 use tracing::{debug, info_span, trace};
 use winit::event::ElementState;
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::keyboard::{Key, KeyCode, NamedKey, PhysicalKey};
 
+use crate::locale::DragAnnouncement;
 use crate::passes::merge_state_up;
-use crate::render_root::RenderRoot;
-use crate::{AccessEvent, EventCtx, Handled, PointerEvent, TextEvent, Widget, WidgetId};
+use crate::passes::update::run_dismiss_popups_pass;
+use crate::render_root::{PressState, RenderRoot, RenderRootSignal};
+use crate::widget::report_caught_panic;
+use crate::{
+    AccessEvent, Action, EventCtx, FocusDirection, Handled, PlatformEvent, Point, PointerButton,
+    PointerEvent, PointerState, TextEvent, Widget, WidgetId,
+};
 
 // --- MARK: HELPERS ---
 fn get_target_widget(
     root: &RenderRoot,
     pointer_pos: Option<LogicalPosition<f64>>,
 ) -> Option<WidgetId> {
+    if let Some(lock_target) = root.global_state.pointer_lock_target {
+        return Some(lock_target);
+    }
+
     if let Some(capture_target) = root.global_state.pointer_capture_target {
         return Some(capture_target);
     }
@@ -22,10 +34,7 @@ fn get_target_widget(
     if let Some(pointer_pos) = pointer_pos {
         // TODO - Apply scale?
         let pointer_pos = (pointer_pos.x, pointer_pos.y).into();
-        return root
-            .get_root_widget()
-            .find_widget_at_pos(pointer_pos)
-            .map(|widget| widget.id());
+        return root.hit_test(pointer_pos);
     }
 
     None
@@ -69,8 +78,16 @@ fn run_event_pass<E>(
                 );
             }
 
-            pass_fn(widget, &mut ctx, event);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| pass_fn(widget, &mut ctx, event)));
             is_handled = ctx.is_handled;
+            if let Err(payload) = result {
+                // The panic is caught by the closest error boundary, if any.
+                let Some(boundary) = root.widget_arena.error_boundary_of(widget_id) else {
+                    panic::resume_unwind(payload);
+                };
+                report_caught_panic(&mut root.global_state, boundary, &*payload);
+                is_handled = true;
+            }
         }
 
         merge_state_up(&mut root.widget_arena, widget_id);
@@ -80,6 +97,379 @@ fn run_event_pass<E>(
     Handled::from(is_handled)
 }
 
+fn on_pointer_event(widget: &mut dyn Widget, ctx: &mut EventCtx, event: &PointerEvent) {
+    let [a, b, c, d, _, _] = ctx.widget_state.window_transform.as_coeffs();
+    if [a, b, c, d] == [1., 0., 0., 1.] {
+        widget.on_pointer_event(ctx, event);
+        return;
+    }
+    // Widgets compute local positions from their window origin, so we map the
+    // pointer position into their coordinate space, relative to it.
+    let mut event = event.clone();
+    let state = event.pointer_state_mut();
+    let origin = ctx.widget_state.window_origin();
+    let widget_state = &ctx.widget_state;
+    let map = |position: LogicalPosition<f64>| {
+        let local = widget_state.to_local(Point::new(position.x, position.y));
+        LogicalPosition::new(origin.x + local.x, origin.y + local.y)
+    };
+    state.position = map(state.position);
+    for position in &mut state.coalesced {
+        *position = map(*position);
+    }
+    widget.on_pointer_event(ctx, &event);
+}
+
+/// Send a pointer event to `target` only.
+///
+/// The event runs through the ancestors of the target, so that their state is merged up.
+fn run_single_pointer_event_pass(
+    root: &mut RenderRoot,
+    target: WidgetId,
+    event: &PointerEvent,
+) -> Handled {
+    if !root.widget_arena.has(target) {
+        return Handled::No;
+    }
+    run_event_pass(
+        root,
+        Some(target),
+        event,
+        false,
+        |widget, ctx, event| {
+            if ctx.widget_id() == target {
+                on_pointer_event(widget, ctx, event);
+            }
+        },
+        !event.is_high_density(),
+    )
+}
+
+// --- MARK: DRAG AND DROP ---
+/// Route pointer events to drop targets while an in-app drag is in progress.
+///
+/// Returns `None` if the event isn't part of the drag, and should be dispatched as usual.
+fn run_drag_event_pass(root: &mut RenderRoot, event: &PointerEvent) -> Option<Handled> {
+    let drag = root.global_state.drag.as_mut()?;
+    if drag.keyboard_over.is_some() {
+        // Drags started with the keyboard ignore the pointer.
+        return None;
+    }
+    let state = event.pointer_state().clone();
+    match event {
+        PointerEvent::PointerMove(_) => {
+            let previous_target = drag.drop_target.take();
+            let target = event
+                .position()
+                .and_then(|pos| root.hit_test((pos.x, pos.y).into()));
+            let handled = run_event_pass(
+                root,
+                target,
+                &PointerEvent::DragOver(state.clone()),
+                false,
+                on_pointer_event,
+                false,
+            );
+            let drop_target = root
+                .global_state
+                .drag
+                .as_ref()
+                .and_then(|drag| drag.drop_target);
+            if let Some(previous_target) = previous_target {
+                if drop_target != Some(previous_target) {
+                    let event = PointerEvent::DragLeave(state);
+                    run_single_pointer_event_pass(root, previous_target, &event);
+                }
+            }
+            // The drag image follows the pointer.
+            root.global_state
+                .emit_signal(RenderRootSignal::RequestRedraw);
+            Some(handled)
+        }
+        PointerEvent::PointerUp(..) => Some(drop_drag(root, state)),
+        PointerEvent::PointerLeave(_) => {
+            cancel_drag(root);
+            // The pointer still leaves the widget it's over.
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Drop the payload of the drag in progress on its drop target, if any.
+fn drop_drag(root: &mut RenderRoot, state: PointerState) -> Handled {
+    let drop_target = root
+        .global_state
+        .drag
+        .as_ref()
+        .and_then(|drag| drag.drop_target);
+    let handled = match drop_target {
+        Some(target) => {
+            // The drop target can announce a more specific message.
+            announce_drag(root, DragAnnouncement::Dropped);
+            let event = PointerEvent::DragDrop(state);
+            run_single_pointer_event_pass(root, target, &event)
+        }
+        None => {
+            announce_drag(root, DragAnnouncement::DroppedOnNothing);
+            Handled::No
+        }
+    };
+    root.global_state.drag = None;
+    root.global_state
+        .emit_signal(RenderRootSignal::RequestRedraw);
+    handled
+}
+
+/// Cancel the drag in progress, if any.
+fn cancel_drag(root: &mut RenderRoot) {
+    let Some(drag) = root.global_state.drag.take() else {
+        return;
+    };
+    announce_drag(root, DragAnnouncement::Cancelled);
+    if let Some(target) = drag.drop_target {
+        let event =
+            PointerEvent::DragLeave(PointerEvent::new_pointer_leave().pointer_state().clone());
+        run_single_pointer_event_pass(root, target, &event);
+    }
+    root.global_state
+        .emit_signal(RenderRootSignal::RequestRedraw);
+}
+
+/// Make the drag started during a key press a keyboard drag, over its source.
+fn start_keyboard_drag(root: &mut RenderRoot) {
+    let Some(drag) = &root.global_state.drag else {
+        return;
+    };
+    if drag.anchor.is_some() || drag.keyboard_over.is_some() {
+        return;
+    }
+    let source = drag.source;
+    let center = root
+        .widget_arena
+        .get_state(source)
+        .item
+        .window_layout_rect()
+        .center();
+    if let Some(drag) = &mut root.global_state.drag {
+        drag.anchor = Some(center);
+        drag.keyboard_over = Some((source, center));
+    }
+    announce_drag(root, DragAnnouncement::PickedUp);
+}
+
+/// Announce a step of a keyboard drag, in the language of the app's formatter.
+fn announce_drag(root: &mut RenderRoot, step: DragAnnouncement) {
+    let text = root.global_state.formatter.drag_announcement(step);
+    root.global_state.announce(text);
+}
+
+/// Handle a key pressed during a keyboard drag.
+///
+/// Returns `None` if the key isn't used by the drag, and should be dispatched as usual.
+fn run_drag_key_pass(root: &mut RenderRoot, key: &Key, repeat: bool) -> Option<Handled> {
+    let (over, _) = root.global_state.drag.as_ref()?.keyboard_over?;
+    match key {
+        Key::Named(NamedKey::ArrowDown | NamedKey::ArrowRight) => {
+            move_keyboard_drag(root, over, true);
+        }
+        Key::Named(NamedKey::ArrowUp | NamedKey::ArrowLeft) => {
+            move_keyboard_drag(root, over, false);
+        }
+        // Holding the key which picked up the item doesn't drop it right away.
+        Key::Named(NamedKey::Enter | NamedKey::Space) if !repeat => {
+            let (_, center) = root.global_state.drag.as_ref()?.keyboard_over?;
+            drop_drag(root, keyboard_pointer_state(center));
+        }
+        Key::Named(NamedKey::Enter | NamedKey::Space) => {}
+        _ => return None,
+    }
+    Some(Handled::Yes)
+}
+
+/// Move a keyboard drag from the widget `from` to the next widget of the focus chain which
+/// accepts the drop, or the previous one if `forward` is false.
+fn move_keyboard_drag(root: &mut RenderRoot, from: WidgetId, forward: bool) {
+    let chain = root.global_state.focus_chain.clone();
+    let len = chain.len();
+    if len == 0 {
+        return;
+    }
+    let start = chain
+        .iter()
+        .position(|id| *id == from)
+        .unwrap_or(if forward { len - 1 } else { 0 });
+    let Some(previous_target) = root
+        .global_state
+        .drag
+        .as_mut()
+        .map(|drag| drag.drop_target.take())
+    else {
+        return;
+    };
+
+    // The widget `from` comes last, so that the drag stays over it if nothing else accepts it.
+    let mut new_target = None;
+    for step in 1..=len {
+        let index = if forward {
+            (start + step) % len
+        } else {
+            (start + len - step % len) % len
+        };
+        let id = chain[index];
+        if !root.widget_arena.has(id) {
+            continue;
+        }
+        let center = root
+            .widget_arena
+            .get_state(id)
+            .item
+            .window_layout_rect()
+            .center();
+        // The drop target can announce a more specific message.
+        let (position, count) = (index + 1, len);
+        announce_drag(root, DragAnnouncement::Over { position, count });
+        let event = PointerEvent::DragOver(keyboard_pointer_state(center));
+        run_event_pass(root, Some(id), &event, false, on_pointer_event, false);
+        let Some(drag) = &mut root.global_state.drag else {
+            return;
+        };
+        if drag.drop_target.is_some() {
+            drag.keyboard_over = Some((id, center));
+            new_target = drag.drop_target;
+            break;
+        }
+    }
+    if new_target.is_none() {
+        announce_drag(root, DragAnnouncement::NothingToDropOn);
+    } else if new_target == previous_target {
+        announce_drag(root, DragAnnouncement::NoOtherTarget);
+    }
+
+    if let Some(previous_target) = previous_target {
+        if new_target != Some(previous_target) {
+            let event =
+                PointerEvent::DragLeave(PointerEvent::new_pointer_leave().pointer_state().clone());
+            run_single_pointer_event_pass(root, previous_target, &event);
+        }
+    }
+    root.global_state
+        .emit_signal(RenderRootSignal::RequestRedraw);
+}
+
+/// The state of the pointer sent with the events of a keyboard drag, at the center of
+/// the widget the drag is over.
+fn keyboard_pointer_state(center: Point) -> PointerState {
+    let mut state = PointerEvent::new_pointer_leave().pointer_state().clone();
+    state.position = LogicalPosition::new(center.x, center.y);
+    state
+}
+
+// --- MARK: GESTURES ---
+/// Set the [`count`](PointerState::count) of presses and releases, and remember the current
+/// press of the primary pointer for [`PointerEvent::LongPress`] and [`PointerEvent::DragStart`].
+pub(crate) fn track_press(root: &mut RenderRoot, event: &mut PointerEvent) {
+    if !event.pointer_state().is_primary {
+        if matches!(
+            event,
+            PointerEvent::PointerDown(..) | PointerEvent::PointerUp(..)
+        ) {
+            event.pointer_state_mut().count = 1;
+        }
+        return;
+    }
+    let now = root.global_state.now();
+    let config = root.global_state.gesture_config;
+    match event {
+        PointerEvent::PointerDown(button, state) => {
+            let position = Point::new(state.position.x, state.position.y);
+            state.count = match &root.global_state.press {
+                Some(press)
+                    if press.button == *button
+                        && !press.dragging
+                        && !press.long_pressed
+                        && now.saturating_duration_since(press.time)
+                            <= config.multi_tap_interval
+                        && (position - press.position).hypot() <= config.multi_tap_distance =>
+                {
+                    press.count.saturating_add(1)
+                }
+                _ => 1,
+            };
+            root.global_state.press = Some(PressState {
+                button: *button,
+                time: now,
+                position,
+                count: state.count,
+                pressed: true,
+                dragging: false,
+                long_pressed: false,
+                state: state.clone(),
+            });
+        }
+        PointerEvent::PointerUp(button, state) => {
+            state.count = match &mut root.global_state.press {
+                Some(press) if press.pressed && press.button == *button => {
+                    press.pressed = false;
+                    press.count
+                }
+                _ => 1,
+            };
+        }
+        PointerEvent::PointerLeave(_) => {
+            if let Some(press) = &mut root.global_state.press {
+                press.pressed = false;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Send [`PointerEvent::DragStart`] if `event` moves the primary pointer past the drag
+/// threshold for the first time since its primary button was pressed.
+fn run_drag_start_pass(root: &mut RenderRoot, event: &PointerEvent) {
+    let PointerEvent::PointerMove(state) = event else {
+        return;
+    };
+    let threshold = root.global_state.gesture_config.drag_threshold;
+    let Some(press) = &mut root.global_state.press else {
+        return;
+    };
+    let position = Point::new(state.position.x, state.position.y);
+    if !press.pressed
+        || press.dragging
+        || press.button != PointerButton::Primary
+        || (position - press.position).hypot() <= threshold
+    {
+        return;
+    }
+    press.dragging = true;
+    let mut state = state.clone();
+    state.count = press.count;
+    run_on_pointer_event_pass(root, &PointerEvent::DragStart(state));
+}
+
+/// Send [`PointerEvent::LongPress`] if the primary button stayed pressed for the
+/// long press duration without the pointer moving.
+pub(crate) fn run_long_press_pass(root: &mut RenderRoot) {
+    let Some(deadline) = root.global_state.long_press_deadline() else {
+        return;
+    };
+    if deadline > root.global_state.now() {
+        return;
+    }
+    let Some(press) = &mut root.global_state.press else {
+        return;
+    };
+    press.long_pressed = true;
+    let mut state = press.state.clone();
+    // The pointer may have moved a little, without starting a drag.
+    if let Some(position) = root.last_mouse_pos {
+        state.position = position;
+    }
+    run_on_pointer_event_pass(root, &PointerEvent::LongPress(state));
+}
+
 // --- MARK: POINTER_EVENT ---
 pub(crate) fn run_on_pointer_event_pass(root: &mut RenderRoot, event: &PointerEvent) -> Handled {
     let _span = info_span!("dispatch_pointer_event").entered();
@@ -93,24 +483,67 @@ pub(crate) fn run_on_pointer_event_pass(root: &mut RenderRoot, event: &PointerEv
         debug!("Running ON_POINTER_EVENT pass with {}", event.short_name());
     }
 
+    if !event.pointer_state().is_primary {
+        return run_secondary_pointer_event_pass(root, event);
+    }
+
+    if matches!(event, PointerEvent::LockedMove(..))
+        && root.global_state.pointer_lock_target.is_none()
+    {
+        // The lock was released before the platform stopped sending motion.
+        return Handled::No;
+    }
+
+    run_drag_start_pass(root, event);
+
     if event.position() != root.last_mouse_pos {
         root.global_state.needs_pointer_pass = true;
         root.last_mouse_pos = event.position();
     }
 
+    // Clicking hides the focus ring until the keyboard is used again.
+    if matches!(event, PointerEvent::PointerDown(..)) {
+        root.set_focus_visible(false);
+    }
+
+    if let Some(handled) = run_drag_event_pass(root, event) {
+        return handled;
+    }
+
     let target_widget_id = get_target_widget(root, event.position());
 
+    if matches!(event, PointerEvent::PointerDown(..)) {
+        run_dismiss_popups_pass(root, target_widget_id);
+    }
+
     let handled = run_event_pass(
         root,
         target_widget_id,
         event,
         matches!(event, PointerEvent::PointerDown(..)),
-        |widget, ctx, event| {
-            widget.on_pointer_event(ctx, event);
-        },
+        on_pointer_event,
         !event.is_high_density(),
     );
 
+    // A drag started during this event follows the pointer from here.
+    let drag_source = match (&mut root.global_state.drag, event.position()) {
+        (Some(drag), Some(pos)) if drag.anchor.is_none() => {
+            drag.anchor = Some(Point::new(pos.x, pos.y));
+            Some(drag.source)
+        }
+        _ => None,
+    };
+    // The drag takes the pointer from its source, which won't see the button being released,
+    // so its press ends here. It has already lost the capture, so this doesn't activate it.
+    if let Some(source) = drag_source {
+        if !matches!(event, PointerEvent::PointerUp(..)) {
+            let mut state = event.pointer_state().clone();
+            state.buttons.remove(PointerButton::Primary);
+            let event = PointerEvent::PointerUp(PointerButton::Primary, state);
+            run_single_pointer_event_pass(root, source, &event);
+        }
+    }
+
     if matches!(
         event,
         PointerEvent::PointerUp(..) | PointerEvent::PointerLeave(..)
@@ -120,6 +553,9 @@ pub(crate) fn run_on_pointer_event_pass(root: &mut RenderRoot, event: &PointerEv
         // about to lose the pointer.
         root.global_state.pointer_capture_target = None;
     }
+    if matches!(event, PointerEvent::PointerLeave(..)) {
+        root.global_state.release_pointer_lock();
+    }
 
     if !event.is_high_density() {
         debug!(
@@ -132,6 +568,44 @@ pub(crate) fn run_on_pointer_event_pass(root: &mut RenderRoot, event: &PointerEv
     handled
 }
 
+/// Send an event of a pointer other than the primary one, e.g. the second finger of a pinch.
+///
+/// These pointers don't hover widgets, nor interact with popups, menus and drags: their events
+/// are sent to the widget which captured them, or to the widget under them.
+/// Two-finger gestures are always sent to the widget under them.
+fn run_secondary_pointer_event_pass(root: &mut RenderRoot, event: &PointerEvent) -> Handled {
+    let pointer_id = event.pointer_state().pointer_id;
+    let capture_target = if event.is_gesture() {
+        None
+    } else {
+        root.global_state.pointer_captures.get(&pointer_id).copied()
+    };
+    let target = capture_target.or_else(|| {
+        let pos = event.position()?;
+        root.hit_test((pos.x, pos.y).into())
+    });
+
+    root.global_state.secondary_pointer = Some(pointer_id);
+    let handled = run_event_pass(
+        root,
+        target,
+        event,
+        matches!(event, PointerEvent::PointerDown(..)),
+        on_pointer_event,
+        !event.is_high_density(),
+    );
+    root.global_state.secondary_pointer = None;
+
+    if matches!(
+        event,
+        PointerEvent::PointerUp(..) | PointerEvent::PointerLeave(..)
+    ) {
+        root.global_state.pointer_captures.remove(&pointer_id);
+    }
+
+    handled
+}
+
 // TODO https://github.com/linebender/xilem/issues/376 - Some implicit invariants:
 // - If a Widget gets a keyboard event or an ImeStateChange, then
 // focus is on it, its child or its parent.
@@ -143,6 +617,32 @@ pub(crate) fn run_on_text_event_pass(root: &mut RenderRoot, event: &TextEvent) -
         run_on_pointer_event_pass(root, &PointerEvent::new_pointer_leave());
     }
 
+    if let TextEvent::KeyboardKey(key, _) = event {
+        if key.state == ElementState::Pressed {
+            root.set_focus_visible(true);
+        }
+    }
+
+    // Escape always unlocks the pointer, so users can't get stuck.
+    // It also cancels the drag in progress.
+    if let TextEvent::KeyboardKey(key, _) = event {
+        if key.logical_key == Key::Named(NamedKey::Escape) && key.state == ElementState::Pressed {
+            if root.global_state.pointer_lock_target.is_some() {
+                run_on_pointer_event_pass(root, &PointerEvent::new_pointer_leave());
+                return Handled::Yes;
+            }
+            if root.global_state.drag.is_some() {
+                cancel_drag(root);
+                return Handled::Yes;
+            }
+        }
+        if key.state == ElementState::Pressed {
+            if let Some(handled) = run_drag_key_pass(root, &key.logical_key, key.repeat) {
+                return handled;
+            }
+        }
+    }
+
     let _span = info_span!("dispatch_text_event").entered();
 
     if event.is_high_density() {
@@ -167,19 +667,29 @@ pub(crate) fn run_on_text_event_pass(root: &mut RenderRoot, event: &TextEvent) -
         !event.is_high_density(),
     );
 
+    // A drag started during this event is moved with the keyboard from here.
+    start_keyboard_drag(root);
+
     // Handle Tab focus
     if let TextEvent::KeyboardKey(key, mods) = event {
         if key.physical_key == PhysicalKey::Code(KeyCode::Tab)
             && key.state == ElementState::Pressed
             && handled == Handled::No
         {
-            if !mods.shift_key() {
-                root.global_state.next_focused_widget = root.widget_from_focus_chain(true);
-            } else {
-                root.global_state.next_focused_widget = root.widget_from_focus_chain(false);
-            }
+            root.global_state.next_focused_widget =
+                root.global_state.widget_from_focus_chain(!mods.shift_key());
             handled = Handled::Yes;
         }
+
+        // Handle arrow-key focus
+        if let Some(direction) = FocusDirection::from_key(&key.logical_key) {
+            if key.state == ElementState::Pressed && handled == Handled::No {
+                if let Some(id) = root.widget_in_direction(direction) {
+                    root.global_state.next_focused_widget = Some(id);
+                    handled = Handled::Yes;
+                }
+            }
+        }
     }
 
     if !event.is_high_density() {
@@ -238,3 +748,227 @@ pub(crate) fn run_on_access_event_pass(
 
     handled
 }
+
+// --- MARK: PLATFORM EVENT ---
+pub(crate) fn run_on_platform_event_pass(root: &mut RenderRoot, event: &PlatformEvent) -> Handled {
+    let _span = info_span!("platform_event").entered();
+    debug!("Running ON_PLATFORM_EVENT pass with {event:?}");
+
+    // Widgets which were removed from the tree are unsubscribed here.
+    let arena = &root.widget_arena;
+    root.global_state
+        .platform_event_subscribers
+        .retain(|id| arena.has(*id));
+
+    let mut handled = Handled::No;
+    for id in root.global_state.platform_event_subscribers.clone() {
+        // The event runs through the ancestors of the subscriber, so that their state
+        // is merged up, but only the subscriber receives it.
+        let result = run_event_pass(
+            root,
+            Some(id),
+            event,
+            false,
+            |widget, ctx, event| {
+                if ctx.widget_id() == id {
+                    widget.on_platform_event(ctx, event);
+                }
+            },
+            false,
+        );
+        if result.is_handled() {
+            handled = Handled::Yes;
+        }
+    }
+    handled
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use accesskit::Live;
+    use dpi::PhysicalSize;
+    use vello::Scene;
+    use winit::keyboard::ModifiersState;
+
+    use super::*;
+    use crate::locale::{BasicFormatter, Locale, LocaleFormatter};
+    use crate::overlay::ANNOUNCEMENT_NODE;
+    use crate::render_root::{RenderRootOptions, WindowSizePolicy};
+    use crate::testing::{widget_ids, ModularWidget, Record, Recording, TestWidgetExt as _};
+    use crate::widget::Flex;
+    use crate::{KeyEvent, WindowEvent};
+
+    /// The text of the live region in the accessibility tree, if it changed.
+    fn announcement(root: &mut RenderRoot) -> Option<String> {
+        let update = root.update_accessibility();
+        let (_, node) = update
+            .nodes
+            .iter()
+            .find(|(id, _)| *id == ANNOUNCEMENT_NODE.into())?;
+        assert_eq!(node.live(), Some(Live::Polite));
+        node.name().map(str::to_string)
+    }
+
+    fn drag_events(record: &Recording) -> Vec<&'static str> {
+        record
+            .drain()
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::PE(
+                    event @ (PointerEvent::DragOver(_)
+                    | PointerEvent::DragDrop(_)
+                    | PointerEvent::DragLeave(_)),
+                ) => Some(event.short_name()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn press(root: &mut RenderRoot, key: NamedKey) -> Handled {
+        let key = Key::Named(key);
+        let mods = ModifiersState::default();
+        let handled = root.handle_text_event(TextEvent::KeyboardKey(
+            KeyEvent::key_down(key.clone()),
+            mods,
+        ));
+        root.handle_text_event(TextEvent::KeyboardKey(KeyEvent::key_up(key), mods));
+        handled
+    }
+
+    /// A formatter which announces the steps of a drag in French.
+    struct FrenchFormatter(BasicFormatter);
+
+    impl LocaleFormatter for FrenchFormatter {
+        fn locale(&self) -> &Locale {
+            self.0.locale()
+        }
+
+        fn format_number(&self, value: f64, fraction_digits: usize) -> String {
+            self.0.format_number(value, fraction_digits)
+        }
+
+        fn parse_number(&self, text: &str) -> Option<f64> {
+            self.0.parse_number(text)
+        }
+
+        fn format_date(&self, date: time::Date) -> String {
+            self.0.format_date(date)
+        }
+
+        fn drag_announcement(&self, step: DragAnnouncement) -> String {
+            match step {
+                DragAnnouncement::PickedUp => "Élément saisi.".into(),
+                step => self.0.drag_announcement(step),
+            }
+        }
+    }
+
+    fn drag_root(widget: impl Widget, formatter: Option<Arc<dyn LocaleFormatter>>) -> RenderRoot {
+        let mut root = RenderRoot::new(
+            widget,
+            RenderRootOptions {
+                use_system_fonts: false,
+                size_policy: WindowSizePolicy::User,
+                scale_factor: 1.0,
+                test_font: None,
+                formatter,
+                pixel_snapping: false,
+            },
+        );
+        root.handle_window_event(WindowEvent::Resize(PhysicalSize::new(100, 400)));
+        root
+    }
+
+    /// A widget which starts a drag when Space is pressed while it's focused.
+    fn drag_source() -> ModularWidget<()> {
+        ModularWidget::new(())
+            .accepts_focus(true)
+            .text_event_fn(|_, ctx, event| {
+                if let TextEvent::KeyboardKey(key, _) = event {
+                    if key.state.is_pressed() && key.logical_key == Key::Named(NamedKey::Space) {
+                        ctx.start_drag(Box::new(42_u32), Scene::new());
+                        ctx.set_handled();
+                    }
+                }
+            })
+    }
+
+    #[test]
+    fn keyboard_drag_and_drop() {
+        let [source_id, other_id, target_id] = widget_ids();
+        let other = ModularWidget::new(()).accepts_focus(true);
+        let target_record = Recording::default();
+        let target = ModularWidget::new(())
+            .accepts_focus(true)
+            .pointer_event_fn(|_, ctx, event| match event {
+                PointerEvent::DragOver(_) => {
+                    ctx.set_drop_accepted(true);
+                    ctx.set_handled();
+                }
+                PointerEvent::DragDrop(_) => {
+                    let payload = ctx.take_drag_payload().unwrap();
+                    let payload = payload.downcast::<u32>().unwrap();
+                    ctx.announce(format!("Dropped {payload}."));
+                    ctx.submit_action(Action::TextChanged(payload.to_string()));
+                }
+                _ => {}
+            })
+            .record(&target_record);
+        let widget = Flex::column()
+            .with_child_id(drag_source(), source_id)
+            .with_child_id(other, other_id)
+            .with_child_id(target, target_id);
+        let mut root = drag_root(widget, None);
+        root.global_state.next_focused_widget = Some(source_id);
+        root.run_rewrite_passes();
+
+        assert_eq!(press(&mut root, NamedKey::Space), Handled::Yes);
+        assert!(announcement(&mut root).unwrap().starts_with("Picked up."));
+
+        // The drag skips the widget which doesn't accept the drop, and the focus stays
+        // on the source.
+        assert_eq!(press(&mut root, NamedKey::ArrowDown), Handled::Yes);
+        assert_eq!(drag_events(&target_record), ["DragOver"]);
+        assert_eq!(
+            announcement(&mut root).as_deref(),
+            Some("Over a drop target, 3 of 3.")
+        );
+        assert_eq!(root.global_state.focused_widget, Some(source_id));
+
+        // The drag stays over the only drop target.
+        press(&mut root, NamedKey::ArrowUp);
+        assert_eq!(drag_events(&target_record), ["DragOver"]);
+        assert_eq!(
+            announcement(&mut root).as_deref(),
+            Some("There is no other drop target.")
+        );
+
+        press(&mut root, NamedKey::Enter);
+        assert_eq!(drag_events(&target_record), ["DragDrop"]);
+        assert_eq!(announcement(&mut root).as_deref(), Some("Dropped 42."));
+        let action = root.pop_signal_matching(|signal| {
+            matches!(signal, RenderRootSignal::Action(Action::TextChanged(_), ..))
+        });
+        assert!(action.is_some());
+        assert!(root.global_state.drag.is_none());
+        assert_eq!(press(&mut root, NamedKey::Enter), Handled::No);
+    }
+
+    #[test]
+    fn keyboard_drag_announcements_are_translated() {
+        let [source_id] = widget_ids();
+        let formatter = FrenchFormatter(BasicFormatter::new(Locale::new("fr", Some("FR"))));
+        let widget = Flex::column().with_child_id(drag_source(), source_id);
+        let mut root = drag_root(widget, Some(Arc::new(formatter)));
+        root.global_state.next_focused_widget = Some(source_id);
+        root.run_rewrite_passes();
+
+        press(&mut root, NamedKey::Space);
+        assert_eq!(announcement(&mut root).as_deref(), Some("Élément saisi."));
+        press(&mut root, NamedKey::Escape);
+        assert_eq!(announcement(&mut root).as_deref(), Some("Drag cancelled."));
+    }
+}
@@ -5,14 +5,18 @@
 //! before any translations applied in [`compose`](crate::passes::compose).
 //! Most of the logic for this pass happens in [`Widget::layout`] implementations.
 
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
 use dpi::LogicalSize;
 use smallvec::SmallVec;
 use tracing::{info_span, trace};
 use vello::kurbo::{Point, Rect, Size};
 
-use crate::passes::recurse_on_children;
-use crate::render_root::{RenderRoot, RenderRootSignal, WindowSizePolicy};
-use crate::widget::WidgetState;
+use crate::passes::{recurse_on_children, snap_size_to_pixels};
+use crate::render_root::{RenderRoot, RenderRootSignal, RenderRootState, WindowSizePolicy};
+use crate::tree_arena::ArenaMut;
+use crate::widget::{WidgetFlags, WidgetState};
 use crate::{BoxConstraints, LayoutCtx, Widget, WidgetPod};
 
 // --- MARK: RUN LAYOUT ---
@@ -30,43 +34,81 @@ pub(crate) fn run_layout_on<W: Widget>(
     let trace = parent_ctx.global_state.trace.layout;
     let _span = trace.then(|| widget.item.make_trace_span().entered());
 
-    let mut children_ids = SmallVec::new();
-    if cfg!(debug_assertions) {
-        children_ids = widget.item.children_ids();
-
-        // We forcefully set request_layout to true for all children.
-        // This is used below to check that widget.layout(..) visited all of them.
-        for child_id in widget.item.children_ids() {
-            let child_state = state.children.get_child_mut(child_id).unwrap().item;
-            if !child_state.is_stashed {
-                child_state.request_layout = true;
-            }
-        }
-    }
-
     // This checks reads is_explicitly_stashed instead of is_stashed because the latter may be outdated.
     // A widget's is_explicitly_stashed flag is controlled by its direct parent.
     // The parent may set this flag during layout, in which case it should avoid calling run_layout.
     // Note that, because this check exits before recursing, run_layout can only ever be
     // reached for a widget whose parent is not stashed, which means is_explicitly_stashed
     // being false is sufficient to know the widget is non-stashed.
-    if state.item.is_explicitly_stashed {
+    if state
+        .item
+        .flags
+        .contains(WidgetFlags::IS_EXPLICITLY_STASHED)
+    {
         debug_panic!(
             "Error in '{}' {}: trying to compute layout of stashed widget.",
             widget.item.short_type_name(),
             pod.id(),
         );
         state.item.size = Size::ZERO;
+        state.item.layout_cache_bc = None;
         return Size::ZERO;
     }
 
+    // If the constraints are the same as last time and nothing in the subtree
+    // requested layout, the previous layout is still valid and we skip calling layout.
+    if !state.item.flags.contains(WidgetFlags::NEEDS_LAYOUT)
+        && state.item.layout_cache_bc == Some(*bc)
+    {
+        if trace {
+            trace!("Reusing cached layout with constraints {:?}", bc);
+        }
+        state.item.flags.remove(WidgetFlags::REQUEST_LAYOUT);
+        state
+            .item
+            .flags
+            .insert(WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL);
+        let size = state.item.size;
+        parent_ctx.widget_state.merge_up(state.item);
+        return size;
+    }
+
+    let now = parent_ctx.global_state.now();
+    if parent_ctx.global_state.watchdog.check(now) {
+        // The pass exceeded its time budget, so the widget keeps its previous layout
+        // until the next frame. It still needs layout, which its parent inherits below.
+        state.item.flags.remove(WidgetFlags::REQUEST_LAYOUT);
+        state
+            .item
+            .flags
+            .insert(WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL);
+        return state.item.size;
+    }
+    parent_ctx.global_state.frame_stats.widgets_laid_out += 1;
+
+    let mut children_ids = SmallVec::new();
+    if cfg!(debug_assertions) {
+        children_ids = widget.item.children_ids();
+
+        // We forcefully set request_layout to true for all children.
+        // This is used below to check that widget.layout(..) visited all of them.
+        for child_id in widget.item.children_ids() {
+            let child_state = state.children.get_child_mut(child_id).unwrap().item;
+            if !child_state.flags.contains(WidgetFlags::IS_STASHED) {
+                child_state.flags.insert(WidgetFlags::REQUEST_LAYOUT);
+            }
+        }
+    }
+
     // TODO - Not everything that has been re-laid out needs to be repainted.
-    state.item.needs_paint = true;
-    state.item.needs_compose = true;
-    state.item.needs_accessibility = true;
-    state.item.request_paint = true;
-    state.item.request_compose = true;
-    state.item.request_accessibility = true;
+    state.item.flags.insert(
+        WidgetFlags::NEEDS_PAINT
+            | WidgetFlags::NEEDS_COMPOSE
+            | WidgetFlags::NEEDS_ACCESSIBILITY
+            | WidgetFlags::REQUEST_PAINT
+            | WidgetFlags::REQUEST_COMPOSE
+            | WidgetFlags::REQUEST_ACCESSIBILITY,
+    );
 
     bc.debug_check(widget.item.short_type_name());
     if trace {
@@ -74,6 +116,7 @@ pub(crate) fn run_layout_on<W: Widget>(
     }
 
     state.item.local_paint_rect = Rect::ZERO;
+    parent_ctx.global_state.snap_points.remove(&state.item.id);
 
     // TODO - Handle more elegantly
     // We suppress need_layout and request_layout for stashed children
@@ -83,13 +126,19 @@ pub(crate) fn run_layout_on<W: Widget>(
         widget.reborrow_mut(),
         state.children.reborrow_mut(),
         |_, state| {
-            if state.item.is_stashed {
-                state.item.needs_layout = false;
-                state.item.request_layout = false;
+            if state.item.flags.contains(WidgetFlags::IS_STASHED) {
+                state
+                    .item
+                    .flags
+                    .remove(WidgetFlags::NEEDS_LAYOUT | WidgetFlags::REQUEST_LAYOUT);
             }
         },
     );
 
+    parent_ctx
+        .global_state
+        .watchdog
+        .enter_widget(widget.item.short_type_name(), id);
     let new_size = {
         let mut inner_ctx = LayoutCtx {
             widget_state: state.item,
@@ -98,12 +147,18 @@ pub(crate) fn run_layout_on<W: Widget>(
             global_state: parent_ctx.global_state,
         };
 
-        // TODO - If constraints are the same and request_layout isn't set,
-        // skip calling layout
-        inner_ctx.widget_state.request_layout = false;
+        inner_ctx
+            .widget_state
+            .flags
+            .remove(WidgetFlags::REQUEST_LAYOUT);
         widget.item.layout(&mut inner_ctx, bc)
     };
-    if state.item.request_layout {
+    parent_ctx.global_state.watchdog.exit_widget();
+    let new_size = match parent_ctx.global_state.pixel_snap_scale {
+        Some(scale) if new_size.is_finite() => snap_size_to_pixels(new_size, bc, scale),
+        _ => new_size,
+    };
+    if state.item.flags.contains(WidgetFlags::REQUEST_LAYOUT) {
         debug_panic!(
             "Error in '{}' {}: layout request flag was set during layout pass",
             widget.item.short_type_name(),
@@ -119,8 +174,16 @@ pub(crate) fn run_layout_on<W: Widget>(
         );
     }
 
-    state.item.needs_layout = false;
-    state.item.is_expecting_place_child_call = true;
+    // If the watchdog cut the pass short, some descendants weren't laid out,
+    // so the widget is laid out again on the next frame.
+    if !parent_ctx.global_state.watchdog.frame_tripped() {
+        state.item.flags.remove(WidgetFlags::NEEDS_LAYOUT);
+    }
+    state
+        .item
+        .flags
+        .insert(WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL);
+    state.item.layout_cache_bc = Some(*bc);
 
     state.item.local_paint_rect = state
         .item
@@ -133,11 +196,11 @@ pub(crate) fn run_layout_on<W: Widget>(
         for child_id in widget.item.children_ids() {
             let child_state = state.children.get_child_mut(child_id).unwrap().item;
 
-            if child_state.is_stashed {
+            if child_state.flags.contains(WidgetFlags::IS_STASHED) {
                 continue;
             }
 
-            if child_state.request_layout {
+            if child_state.flags.contains(WidgetFlags::REQUEST_LAYOUT) {
                 debug_panic!(
                     "Error in '{}' {}: LayoutCtx::run_layout() was not called with child widget '{}' {}.",
                     name,
@@ -147,7 +210,10 @@ pub(crate) fn run_layout_on<W: Widget>(
                 );
             }
 
-            if child_state.is_expecting_place_child_call {
+            if child_state
+                .flags
+                .contains(WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL)
+            {
                 debug_panic!(
                     "Error in '{}' {}: LayoutCtx::place_child() was not called with child widget '{}' {}.",
                     name,
@@ -175,7 +241,9 @@ pub(crate) fn run_layout_on<W: Widget>(
         }
 
         let new_children_ids = widget.item.children_ids();
-        if children_ids != new_children_ids && !state.item.children_changed {
+        if children_ids != new_children_ids
+            && !state.item.flags.contains(WidgetFlags::CHILDREN_CHANGED)
+        {
             debug_panic!(
                 "Error in '{}' {}: children changed during layout pass",
                 name,
@@ -209,17 +277,156 @@ pub(crate) fn run_layout_on<W: Widget>(
     new_size
 }
 
+/// Like [`run_layout_on`], but catches panics raised in the layout of `pod`'s subtree.
+///
+/// When a panic is caught, the layout state of the subtree is reset as if it had never
+/// been laid out, so that it's laid out from scratch when it's shown again, and the
+/// parent isn't expected to place it.
+pub(crate) fn run_layout_catching_panic<W: Widget>(
+    parent_ctx: &mut LayoutCtx<'_>,
+    pod: &mut WidgetPod<W>,
+    bc: &BoxConstraints,
+) -> Result<Size, Box<dyn Any + Send>> {
+    let depth = parent_ctx.global_state.watchdog.depth();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run_layout_on(parent_ctx, pod, bc)));
+    let payload = match result {
+        Ok(size) => return Ok(size),
+        Err(payload) => payload,
+    };
+    // The panic unwound through the widgets it was laying out.
+    parent_ctx.global_state.watchdog.exit_widgets_to(depth);
+
+    let id = pod.id();
+    let widget = parent_ctx.widget_children.get_child_mut(id).unwrap();
+    let state = parent_ctx.widget_state_children.get_child_mut(id).unwrap();
+    reset_layout_state(parent_ctx.global_state, widget, state);
+    Err(payload)
+}
+
+fn reset_layout_state(
+    global_state: &mut RenderRootState,
+    mut widget: ArenaMut<'_, Box<dyn Widget>>,
+    mut state: ArenaMut<'_, WidgetState>,
+) {
+    state
+        .item
+        .flags
+        .remove(WidgetFlags::REQUEST_LAYOUT | WidgetFlags::IS_EXPECTING_PLACE_CHILD_CALL);
+    state.item.flags.insert(WidgetFlags::NEEDS_LAYOUT);
+    state.item.layout_cache_bc = None;
+    state.item.size = Size::ZERO;
+    state.item.local_paint_rect = Rect::ZERO;
+    global_state.snap_points.remove(&state.item.id);
+
+    let id = state.item.id;
+    recurse_on_children(
+        id,
+        widget.reborrow_mut(),
+        state.children,
+        |widget, state| reset_layout_state(global_state, widget, state),
+    );
+}
+
+/// The size of the monitor assumed when the monitors are unknown, e.g. in tests.
+const UNKNOWN_MONITOR_SIZE: Size = Size::new(7680., 4320.);
+
+/// The largest size the window can be resized to fit its content, in logical pixels.
+///
+/// This is the size of the monitor the window is on, or of the primary monitor if the
+/// position of the window is unknown.
+fn max_window_size(root: &RenderRoot) -> Size {
+    let monitors = &root.global_state.monitors;
+    let window_center = root.global_state.window_position.map(|position| {
+        (
+            i64::from(position.x) + i64::from(root.size.width / 2),
+            i64::from(position.y) + i64::from(root.size.height / 2),
+        )
+    });
+    let current = window_center.and_then(|(x, y)| {
+        monitors.iter().find(|monitor| {
+            let (left, top) = (i64::from(monitor.position.x), i64::from(monitor.position.y));
+            (left..left + i64::from(monitor.size.width)).contains(&x)
+                && (top..top + i64::from(monitor.size.height)).contains(&y)
+        })
+    });
+    let Some(monitor) = current
+        .or_else(|| monitors.iter().find(|monitor| monitor.is_primary))
+        .or(monitors.first())
+    else {
+        return UNKNOWN_MONITOR_SIZE;
+    };
+    let size = monitor.size.to_logical::<f64>(monitor.scale_factor);
+    Size::new(size.width, size.height)
+}
+
+/// The size of the window for `content` laid out with at most `probe`.
+///
+/// Along the axes where the content fills the space it's given, the window keeps its size.
+fn fit_window_to_content(content: Size, probe: Size, window: Size, max: Size) -> Size {
+    let fit = |content: f64, probe: f64, window: f64, max: f64| {
+        if content >= probe {
+            window
+        } else {
+            content.min(max)
+        }
+    };
+    Size::new(
+        fit(content.width, probe.width, window.width, max.width),
+        fit(content.height, probe.height, window.height, max.height),
+    )
+}
+
 // --- MARK: ROOT ---
 pub(crate) fn run_layout_pass(root: &mut RenderRoot) {
-    if !root.root_state().needs_layout {
+    if !root.roots_have(WidgetFlags::NEEDS_LAYOUT) {
         return;
     }
 
     let _span = info_span!("layout").entered();
     root.global_state.needs_pointer_pass = true;
 
+    // Popups are confined to the root widget, so they're laid out again with it.
+    let root_laid_out = root.root_state().flags.contains(WidgetFlags::NEEDS_LAYOUT);
+    if root_laid_out {
+        layout_root_widget(root);
+    }
+    let popups_need_layout = root.popup_roots.iter().any(|popup| {
+        let state = root.widget_arena.widget_states.find(popup.id());
+        state.is_some_and(|state| state.item.flags.contains(WidgetFlags::NEEDS_LAYOUT))
+    });
+    if root_laid_out || popups_need_layout {
+        layout_popups(root);
+    }
+}
+
+/// Lay out the root widget, and resize the window to it if needed.
+fn layout_root_widget(root: &mut RenderRoot) {
+    if let (WindowSizePolicy::User, Some(ratio)) =
+        (root.size_policy, root.global_state.window_aspect_ratio)
+    {
+        // The height follows the width. We leave a pixel of slack for rounding errors,
+        // so that the platform's answer to our resize request doesn't cause another one.
+        let height = (f64::from(root.size.width) / ratio).round() as u32;
+        if root.size.height.abs_diff(height) > 1 {
+            root.size.height = height;
+            root.global_state
+                .emit_signal(RenderRootSignal::SetSize(root.size));
+        }
+    }
+
+    let size_to_content = std::mem::take(&mut root.global_state.size_to_content)
+        && root.size_policy == WindowSizePolicy::User;
     let window_size = root.get_kurbo_size();
+    let max_size = max_window_size(root);
+    // Widgets which fill the space they're given, e.g. a portal, return the largest size
+    // they're allowed. We give them more space than the monitor has, so that they can be
+    // told apart from content which is larger than the monitor.
+    let probe_size = Size::new(
+        (2. * max_size.width).max(window_size.width + 1.),
+        (2. * max_size.height).max(window_size.height + 1.),
+    );
     let bc = match root.size_policy {
+        WindowSizePolicy::User if size_to_content => BoxConstraints::new(Size::ZERO, probe_size),
         WindowSizePolicy::User => BoxConstraints::tight(window_size),
         WindowSizePolicy::Content => BoxConstraints::UNBOUNDED,
     };
@@ -234,10 +441,16 @@ pub(crate) fn run_layout_pass(root: &mut RenderRoot) {
         widget_children: root_widget_token,
     };
 
-    let size = run_layout_on(&mut ctx, &mut root.root, &bc);
+    let mut size = run_layout_on(&mut ctx, &mut root.root, &bc);
+    if size_to_content {
+        let new_size = fit_window_to_content(size, probe_size, window_size, max_size);
+        if new_size != size {
+            size = run_layout_on(&mut ctx, &mut root.root, &BoxConstraints::tight(new_size));
+        }
+    }
     ctx.place_child(&mut root.root, Point::ORIGIN);
 
-    if let WindowSizePolicy::Content = root.size_policy {
+    if size_to_content || root.size_policy == WindowSizePolicy::Content {
         let new_size = LogicalSize::new(size.width, size.height).to_physical(root.scale_factor);
         if root.size != new_size {
             root.size = new_size;
@@ -246,3 +459,27 @@ pub(crate) fn run_layout_pass(root: &mut RenderRoot) {
         }
     }
 }
+
+/// Lay out the popups, and place them in window coordinates.
+fn layout_popups(root: &mut RenderRoot) {
+    let root_size = root.root_state().size;
+    let mut popup_roots = std::mem::take(&mut root.popup_roots);
+    let mut dummy_state = WidgetState::synthetic(root.root.id(), root.get_kurbo_size());
+    let mut ctx = LayoutCtx {
+        global_state: &mut root.global_state,
+        widget_state: &mut dummy_state,
+        widget_state_children: root.widget_arena.widget_states.root_token_mut(),
+        widget_children: root.widget_arena.widgets.root_token_mut(),
+    };
+    for popup in &mut popup_roots {
+        let Some(state) = (ctx.global_state.popups.iter())
+            .find(|state| state.id == popup.id())
+            .copied()
+        else {
+            continue;
+        };
+        let popup_size = run_layout_on(&mut ctx, popup, &state.constraints(root_size));
+        ctx.place_child(popup, state.origin(popup_size, root_size));
+    }
+    root.popup_roots = popup_roots;
+}
@@ -3,7 +3,7 @@
 
 use crate::tree_arena::{ArenaMut, ArenaMutChildren};
 use crate::widget::WidgetArena;
-use crate::{Widget, WidgetId, WidgetState};
+use crate::{BoxConstraints, Size, Widget, WidgetId, WidgetState};
 
 pub(crate) mod accessibility;
 pub(crate) mod anim;
@@ -13,17 +13,48 @@ pub(crate) mod layout;
 pub(crate) mod mutate;
 pub(crate) mod paint;
 pub(crate) mod update;
+pub(crate) mod watchdog;
 
 pub(crate) fn recurse_on_children(
+    id: WidgetId,
+    widget: ArenaMut<'_, Box<dyn Widget>>,
+    state: ArenaMutChildren<'_, WidgetState>,
+    callback: impl FnMut(ArenaMut<'_, Box<dyn Widget>>, ArenaMut<'_, WidgetState>),
+) {
+    let children_ids = widget.item.children_ids();
+    recurse_on_children_ids(id, widget, state, &children_ids, callback);
+}
+
+/// Like [`recurse_on_children`], but visits the children in the order they are painted:
+/// by increasing [z-index](crate::LayoutCtx::place_child_with_z), and in the order of
+/// `children_ids` for children with the same z-index.
+pub(crate) fn recurse_on_children_in_paint_order(
+    id: WidgetId,
+    widget: ArenaMut<'_, Box<dyn Widget>>,
+    state: ArenaMutChildren<'_, WidgetState>,
+    callback: impl FnMut(ArenaMut<'_, Box<dyn Widget>>, ArenaMut<'_, WidgetState>),
+) {
+    let mut children_ids = widget.item.children_ids();
+    // The sort is stable, so children with the same z-index keep their order.
+    children_ids.sort_by_key(|child_id| {
+        state
+            .get_child(*child_id)
+            .map_or(0, |child| child.item.z_index)
+    });
+    recurse_on_children_ids(id, widget, state, &children_ids, callback);
+}
+
+fn recurse_on_children_ids(
     id: WidgetId,
     mut widget: ArenaMut<'_, Box<dyn Widget>>,
     mut state: ArenaMutChildren<'_, WidgetState>,
+    children_ids: &[WidgetId],
     mut callback: impl FnMut(ArenaMut<'_, Box<dyn Widget>>, ArenaMut<'_, WidgetState>),
 ) {
     let parent_name = widget.item.short_type_name();
     let parent_id = id;
 
-    for child_id in widget.item.children_ids() {
+    for &child_id in children_ids {
         let widget = widget.children.get_child_mut(child_id).unwrap_or_else(|| {
             panic!(
                 "Error in '{}' #{}: cannot find child #{} returned by children_ids()",
@@ -55,6 +86,30 @@ pub(crate) fn merge_state_up(arena: &mut WidgetArena, widget_id: WidgetId) {
     parent_state_mut.item.merge_up(child_state_mut.item);
 }
 
+/// Round `value`, in logical pixels, to the closest physical pixel for the given `scale`.
+pub(crate) fn snap_to_pixel(value: f64, scale: f64) -> f64 {
+    (value * scale).round() / scale
+}
+
+/// Round `size` to physical pixels, while staying within `bc` if possible.
+pub(crate) fn snap_size_to_pixels(size: Size, bc: &BoxConstraints, scale: f64) -> Size {
+    let snap = |value: f64, min: f64, max: f64| {
+        let snapped = snap_to_pixel(value, scale);
+        if snapped > max {
+            (value * scale).floor() / scale
+        } else if snapped < min {
+            (value * scale).ceil() / scale
+        } else {
+            snapped
+        }
+    };
+    let (min, max) = (bc.min(), bc.max());
+    Size::new(
+        snap(size.width, min.width, max.width),
+        snap(size.height, min.height, max.height),
+    )
+}
+
 /// Masonry has a significant number of passes which may traverse a significant number of
 /// items.
 ///
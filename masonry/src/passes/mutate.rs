@@ -1,6 +1,8 @@
 // Copyright 2024 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashSet;
+
 use tracing::info_span;
 
 use crate::passes::merge_state_up;
@@ -12,12 +14,22 @@ pub(crate) fn mutate_widget<R>(
     root: &mut RenderRoot,
     id: WidgetId,
     mutate_fn: impl FnOnce(WidgetMut<'_, Box<dyn Widget>>) -> R,
+) -> R {
+    let result = call_mutate_fn(root, id, mutate_fn);
+    merge_state_up_to_root(root, &[id]);
+    result
+}
+
+fn call_mutate_fn<R>(
+    root: &mut RenderRoot,
+    id: WidgetId,
+    mutate_fn: impl FnOnce(WidgetMut<'_, Box<dyn Widget>>) -> R,
 ) -> R {
     let (widget_mut, state_mut) = root.widget_arena.get_pair_mut(id);
 
     let _span = info_span!("mutate_widget", name = widget_mut.item.short_type_name()).entered();
-    // NOTE - parent_widget_state can be None here, because the loop below will merge the
-    // state up to the root.
+    // NOTE - parent_widget_state can be None here, because the state is merged
+    // up to the root afterwards.
     let root_widget = WidgetMut {
         ctx: MutateCtx {
             global_state: &mut root.global_state,
@@ -29,24 +41,65 @@ pub(crate) fn mutate_widget<R>(
         widget: widget_mut.item,
     };
 
-    let result = mutate_fn(root_widget);
+    mutate_fn(root_widget)
+}
 
-    // Merge all state changes up to the root.
-    let mut current_id = Some(id);
-    while let Some(id) = current_id {
-        let parent_id = root.widget_arena.parent_of(id);
+/// Merge the state changes of the given widgets up to the root.
+///
+/// Ancestors shared by several of these widgets are only merged once, after all
+/// their mutated descendants.
+fn merge_state_up_to_root(root: &mut RenderRoot, ids: &[WidgetId]) {
+    let mut seen = HashSet::new();
+    let mut widgets = Vec::new();
+    for &id in ids {
+        // The widget may have been removed by a later callback.
+        if !root.widget_arena.has(id) {
+            continue;
+        }
+        let mut path = Vec::new();
+        let mut current_id = Some(id);
+        while let Some(id) = current_id {
+            path.push(id);
+            current_id = root.widget_arena.parent_of(id);
+        }
+        let depth = path.len();
+        for (i, id) in path.into_iter().enumerate() {
+            if seen.insert(id) {
+                widgets.push((depth - i, id));
+            }
+        }
+    }
+    // Children must be merged into their parents before the parents are merged up.
+    widgets.sort_by(|(depth, _), (other_depth, _)| other_depth.cmp(depth));
+    for (_, id) in widgets {
         merge_state_up(&mut root.widget_arena, id);
-        current_id = parent_id;
     }
-
-    result
 }
 
+/// How many times callbacks queued by other callbacks are run in the same pass.
+///
+/// The rest are left for the next rewrite pass, so that callbacks which keep queuing
+/// themselves don't hang the app.
+const MUTATE_ROUNDS_MAX: usize = 8;
+
 // TODO - Add link to mutate pass documentation
 /// Apply any deferred mutations (created using [`...Ctx::mutate_later`](crate::LayoutCtx::mutate_later)).
+///
+/// Callbacks queued by other callbacks are run in the same pass, and the state changes of
+/// all the callbacks are merged up together, so that many callbacks touching the same
+/// subtree only invalidate it once, and the passes depending on it only run once.
 pub(crate) fn run_mutate_pass(root: &mut RenderRoot) {
-    let callbacks = std::mem::take(&mut root.global_state.mutate_callbacks);
-    for callback in callbacks {
-        mutate_widget(root, callback.id, callback.callback);
+    let _span = info_span!("mutate").entered();
+    let mut ids = Vec::new();
+    for _ in 0..MUTATE_ROUNDS_MAX {
+        let callbacks = std::mem::take(&mut root.global_state.mutate_callbacks);
+        if callbacks.is_empty() {
+            break;
+        }
+        for callback in callbacks {
+            call_mutate_fn(root, callback.id, callback.callback);
+            ids.push(callback.id);
+        }
     }
+    merge_state_up_to_root(root, &ids);
 }
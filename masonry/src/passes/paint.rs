@@ -2,89 +2,169 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
 
 use tracing::{info_span, trace};
-use vello::kurbo::{Affine, Stroke};
-use vello::peniko::Mix;
+use vello::kurbo::{Affine, Point, Rect, Size, Stroke};
+use vello::peniko::{Color, Fill, Mix};
 use vello::Scene;
 
-use crate::passes::recurse_on_children;
-use crate::render_root::{RenderRoot, RenderRootState};
-use crate::theme::get_debug_color;
+use crate::passes::recurse_on_children_in_paint_order;
+use crate::render_root::{RenderRoot, RenderRootSignal, RenderRootState, WidgetScene};
+use crate::text::TextLayout;
+use crate::theme::{self, get_debug_color};
 use crate::tree_arena::ArenaMut;
-use crate::{PaintCtx, Widget, WidgetId, WidgetState};
+use crate::widget::report_caught_panic;
+use crate::{PaintCtx, Widget, WidgetFlags, WidgetId, WidgetState};
 
 // --- MARK: PAINT WIDGET ---
+#[allow(clippy::too_many_arguments)] // reason: the arguments are the state threaded through the recursion
 fn paint_widget(
     global_state: &mut RenderRootState,
     complete_scene: &mut Scene,
     scenes: &mut HashMap<WidgetId, Scene>,
     mut widget: ArenaMut<'_, Box<dyn Widget>>,
     mut state: ArenaMut<'_, WidgetState>,
+    visible_rect: Rect,
     debug_paint: bool,
+    error_boundary: Option<WidgetId>,
 ) {
     let trace = global_state.trace.paint;
     let _span = trace.then(|| widget.item.make_trace_span().entered());
     let id = state.item.id;
 
+    let now = global_state.now();
+    if global_state.watchdog.check(now) {
+        // The pass exceeded its time budget, so the rest of the tree isn't painted.
+        return;
+    }
+    global_state
+        .watchdog
+        .enter_widget(widget.item.short_type_name(), id);
+
     // TODO - Handle invalidation regions
     let mut ctx = PaintCtx {
         global_state,
         widget_state: state.item,
         widget_state_children: state.children.reborrow_mut(),
         widget_children: widget.children.reborrow_mut(),
+        scenes,
         debug_paint,
     };
-    if ctx.widget_state.request_paint {
+    if ctx.widget_state.flags.contains(WidgetFlags::REQUEST_PAINT) {
         if trace {
             trace!("Painting widget '{}' {}", widget.item.short_type_name(), id);
         }
+        ctx.global_state.frame_stats.widgets_painted += 1;
 
         // TODO - Reserve scene
         // https://github.com/linebender/xilem/issues/524
-        let scene = scenes.entry(id).or_default();
+        // The scene is taken out of the cache while painting, so the widget
+        // can paint its descendants with `PaintCtx::paint_descendant_scene`.
+        let mut scene = ctx.scenes.remove(&id).unwrap_or_default();
         scene.reset();
-        widget.item.paint(&mut ctx, scene);
+        match error_boundary {
+            Some(boundary) => {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    widget.item.paint(&mut ctx, &mut scene);
+                }));
+                if let Err(payload) = result {
+                    // Drop whatever was painted before the panic.
+                    scene.reset();
+                    report_caught_panic(ctx.global_state, boundary, &*payload);
+                }
+            }
+            None => widget.item.paint(&mut ctx, &mut scene),
+        }
+        ctx.scenes.insert(id, scene);
+
+        if ctx.global_state.paint_flashing.is_enabled() {
+            let window_paint_rect = ctx
+                .widget_state
+                .to_window_rect(ctx.widget_state.local_paint_rect);
+            ctx.global_state
+                .paint_flashing
+                .record(window_paint_rect.intersect(visible_rect));
+        }
     }
 
-    state.item.request_paint = false;
-    state.item.needs_paint = false;
+    state
+        .item
+        .flags
+        .remove(WidgetFlags::REQUEST_PAINT | WidgetFlags::NEEDS_PAINT);
 
-    let clip = state.item.clip_path;
+    let clip = state.item.clip_path.as_ref();
     let has_clip = clip.is_some();
-    let transform = Affine::translate(state.item.window_origin.to_vec2());
+    let transform = state.item.window_transform();
     let scene = scenes.get(&id).unwrap();
 
+    let opacity = state.item.opacity;
+    let has_opacity = opacity < 1.;
+    if has_opacity {
+        let group_rect = state.item.local_paint_rect;
+        complete_scene.push_layer(Mix::Normal, opacity, transform, &group_rect);
+    }
     if let Some(clip) = clip {
-        complete_scene.push_layer(Mix::Clip, 1., transform, &clip);
+        clip.push_layer(complete_scene, transform);
     }
 
     complete_scene.append(scene, Some(transform));
+    global_state.frame_stats.scene_fragments += 1;
+
+    // Children can only be seen through the intersection of the parent's visible
+    // region and its clip path, both in window coordinates.
+    let visible_rect = match clip {
+        Some(clip) => visible_rect.intersect(state.item.to_window_rect(clip.bounding_box())),
+        None => visible_rect,
+    };
 
     let id = state.item.id;
     let size = state.item.size;
+    let paints_focus_ring = global_state.focus_visible
+        && global_state.focused_widget == Some(id)
+        && !widget.item.paints_own_focus_indicator();
+    let env = paints_focus_ring.then(|| state.item.env.clone());
+    let error_boundary = if state.item.flags.contains(WidgetFlags::IS_ERROR_BOUNDARY) {
+        Some(id)
+    } else {
+        error_boundary
+    };
     let parent_state = state.item;
-    recurse_on_children(
+    recurse_on_children_in_paint_order(
         id,
         widget.reborrow_mut(),
         state.children,
         |widget, mut state| {
             // TODO - We skip painting stashed items.
             // This may have knock-on effects we'd need to document.
-            if state.item.is_stashed {
+            if state.item.flags.contains(WidgetFlags::IS_STASHED) {
+                return;
+            }
+            // We skip painting subtrees whose paint rect is entirely outside the
+            // visible region. Their paint flags are left untouched, so they will
+            // be painted once they're scrolled back into view.
+            // TODO - Once we implement compositor layers, we may want to paint
+            // outside of the clip path anyway in anticipation of user scrolling.
+            let window_paint_rect = state.item.to_window_rect(state.item.local_paint_rect);
+            if !window_paint_rect.overlaps(visible_rect) {
+                if global_state.trace.paint {
+                    trace!(
+                        "Skipping offscreen widget '{}' {}",
+                        widget.item.short_type_name(),
+                        state.item.id
+                    );
+                }
                 return;
             }
-            // TODO: We could skip painting children outside the parent clip path.
-            // There's a few things to consider if we do:
-            // - Some widgets can paint outside of their layout box.
-            // - Once we implement compositor layers, we may want to paint outside of the clip path anyway in anticipation of user scrolling.
             paint_widget(
                 global_state,
                 complete_scene,
                 scenes,
                 widget,
                 state.reborrow_mut(),
+                visible_rect,
                 debug_paint,
+                error_boundary,
             );
             parent_state.merge_up(state.item);
         },
@@ -100,6 +180,78 @@ fn paint_widget(
     if has_clip {
         complete_scene.pop_layer();
     }
+    // The ring is painted outside the widget's clip, since it surrounds its layout rect.
+    if let Some(env) = env {
+        let width = env.get(theme::FOCUS_RING_WIDTH);
+        let inflation = env.get(theme::FOCUS_RING_OFFSET) + width / 2.;
+        let rect = size.to_rect().inflate(inflation, inflation);
+        let shape = rect.to_rounded_rect(env.get(theme::FOCUS_RING_RADIUS));
+        let color = env.get(theme::FOCUS_RING_COLOR);
+        complete_scene.stroke(&Stroke::new(width), transform, color, None, &shape);
+    }
+    if has_opacity {
+        complete_scene.pop_layer();
+    }
+    global_state.watchdog.exit_widget();
+}
+
+// --- MARK: PAINT FLASHING ---
+/// The number of frames a repainted region stays highlighted for.
+const FLASH_FRAMES: u32 = 10;
+const FLASH_COLOR: Color = Color::rgba8(0xff, 0x00, 0xff, 0x80);
+
+/// A debug overlay which briefly highlights the regions repainted in each frame,
+/// to check that widgets only repaint when they need to.
+///
+/// The overlay is disabled by default. It can be toggled with
+/// [`RenderRoot::set_paint_flashing`], or enabled with the `MASONRY_PAINT_FLASHING`
+/// environment variable.
+pub(crate) struct PaintFlashing {
+    enabled: bool,
+    /// The regions repainted in recent frames, in window coordinates,
+    /// with the number of frames they are still highlighted for.
+    flashes: Vec<(Rect, u32)>,
+}
+
+impl PaintFlashing {
+    pub(crate) fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("MASONRY_PAINT_FLASHING").is_ok_and(|it| !it.is_empty()),
+            flashes: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.flashes.clear();
+        }
+    }
+
+    /// Highlight a region repainted in the current frame.
+    pub(crate) fn record(&mut self, rect: Rect) {
+        if rect.area() > 0. {
+            self.flashes.push((rect, FLASH_FRAMES));
+        }
+    }
+
+    /// Paint the highlighted regions on top of `scene`, fading them out.
+    ///
+    /// Returns `true` if some regions are still highlighted, and another frame is needed.
+    pub(crate) fn paint_overlay(&mut self, scene: &mut Scene) -> bool {
+        for (rect, frames_left) in &mut self.flashes {
+            let alpha = *frames_left as f32 / FLASH_FRAMES as f32;
+            let color = FLASH_COLOR.with_alpha_factor(alpha);
+            scene.fill(Fill::NonZero, Affine::IDENTITY, color, None, rect);
+            *frames_left -= 1;
+        }
+        self.flashes.retain(|(_, frames_left)| *frames_left > 0);
+        !self.flashes.is_empty()
+    }
 }
 
 // --- MARK: ROOT ---
@@ -112,6 +264,8 @@ pub(crate) fn run_paint_pass(root: &mut RenderRoot) -> Scene {
     // https://github.com/linebender/xilem/issues/524
     let mut complete_scene = Scene::new();
 
+    let visible_rect = Rect::from_origin_size(Point::ORIGIN, root.get_kurbo_size());
+
     let (root_widget, root_state) = {
         let widget_id = root.root.id();
         let widget = root
@@ -137,9 +291,170 @@ pub(crate) fn run_paint_pass(root: &mut RenderRoot) -> Scene {
         &mut scenes,
         root_widget,
         root_state,
+        visible_rect,
         debug_paint,
+        None,
     );
+
+    // The popups are painted over the root widget, from bottom to top.
+    for popup_id in root.root_ids().into_iter().skip(1) {
+        let (popup_widget, popup_state) = root.widget_arena.get_pair_mut(popup_id);
+        paint_widget(
+            &mut root.global_state,
+            &mut complete_scene,
+            &mut scenes,
+            popup_widget,
+            popup_state,
+            visible_rect,
+            debug_paint,
+            None,
+        );
+    }
     root.global_state.scenes = scenes;
 
+    // The image of the drag in progress is painted over the widgets, under the pointer,
+    // or over the widget a keyboard drag is over.
+    let drag_pos = root.global_state.drag.as_ref().and_then(|drag| {
+        let pointer_pos = root.last_mouse_pos.map(|pos| Point::new(pos.x, pos.y));
+        drag.keyboard_over.map(|(_, center)| center).or(pointer_pos)
+    });
+    if let (Some(drag), Some(pos)) = (&root.global_state.drag, drag_pos) {
+        let anchor = drag.anchor.unwrap_or(drag.source_origin);
+        let origin = drag.source_origin + (pos - anchor);
+        complete_scene.append(&drag.image, Some(Affine::translate(origin.to_vec2())));
+    }
+
+    paint_tooltip(root, &mut complete_scene);
+
+    if root
+        .global_state
+        .paint_flashing
+        .paint_overlay(&mut complete_scene)
+    {
+        // Keep redrawing until the flashes have faded out.
+        root.global_state
+            .emit_signal(RenderRootSignal::RequestRedraw);
+    }
+
     complete_scene
 }
+
+// --- MARK: TOOLTIP ---
+/// Paint the tooltip shown, if any, under the pointer and within the window.
+fn paint_tooltip(root: &mut RenderRoot, scene: &mut Scene) {
+    let Some(tooltip) = root
+        .global_state
+        .tooltip
+        .as_ref()
+        .filter(|tooltip| tooltip.shown)
+    else {
+        return;
+    };
+    let (Some(state), Some(pos)) = (
+        root.widget_arena.widget_states.find(tooltip.widget_id),
+        root.last_mouse_pos,
+    ) else {
+        return;
+    };
+    let Some(text) = root.global_state.tooltips.get(&tooltip.widget_id).cloned() else {
+        return;
+    };
+    // The tooltip is themed like the widget it describes.
+    let env = state.item.env.clone();
+
+    let mut layout = TextLayout::new(env.get(theme::TEXT_SIZE_NORMAL) as f32);
+    layout.set_brush(env.get(theme::TEXT_COLOR));
+    layout.rebuild(
+        &mut root.global_state.font_context,
+        &mut root.global_state.text_layout_context,
+        &text,
+        true,
+    );
+    let insets = env.get(theme::TOOLTIP_INSETS);
+    let size = layout.size() + Size::new(insets.x_value(), insets.y_value());
+
+    // Show the tooltip below the pointer, or above it if there is no room below.
+    let window = root.get_kurbo_size();
+    let x = pos.x.min(window.width - size.width).max(0.);
+    let offset = env.get(theme::TOOLTIP_OFFSET);
+    let mut y = pos.y + offset;
+    if y + size.height > window.height {
+        y = (pos.y - size.height - offset / 2.).max(0.);
+    }
+    let rect =
+        Rect::from_origin_size((x, y), size).to_rounded_rect(env.get(theme::TEXTBOX_BORDER_RADIUS));
+
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        env.get(theme::BACKGROUND_LIGHT),
+        None,
+        &rect,
+    );
+    scene.stroke(
+        &Stroke::new(env.get(theme::TEXTBOX_BORDER_WIDTH)),
+        Affine::IDENTITY,
+        env.get(theme::BORDER_LIGHT),
+        None,
+        &rect,
+    );
+    layout.draw(scene, (x + insets.x0, y + insets.y0));
+}
+
+// --- MARK: SUBTREE ---
+/// Paint the subtree rooted at `id` into a standalone scene, in the local coordinates of `id`.
+///
+/// Widgets which don't request paint reuse their cached scene. Unlike in [`run_paint_pass`],
+/// descendants outside the window are painted, since the subtree is displayed on its own.
+pub(crate) fn run_paint_subtree_pass(root: &mut RenderRoot, id: WidgetId) -> Option<WidgetScene> {
+    let _span = info_span!("paint_subtree").entered();
+
+    let state = root.widget_arena.widget_states.find_mut(id)?;
+    let widget = root
+        .widget_arena
+        .widgets
+        .find_mut(id)
+        .expect("paint_subtree: found state but not widget");
+
+    let mut scenes = std::mem::take(&mut root.global_state.scenes);
+    let widget_scene = paint_subtree(&mut root.global_state, &mut scenes, widget, state);
+    root.global_state.scenes = scenes;
+
+    widget_scene
+}
+
+pub(crate) fn paint_subtree(
+    global_state: &mut RenderRootState,
+    scenes: &mut HashMap<WidgetId, Scene>,
+    widget: ArenaMut<'_, Box<dyn Widget>>,
+    state: ArenaMut<'_, WidgetState>,
+) -> Option<WidgetScene> {
+    if state.item.flags.contains(WidgetFlags::IS_STASHED) {
+        return None;
+    }
+
+    let transform = state.item.window_transform();
+    let size = state.item.size;
+    let paint_rect = state.item.local_paint_rect;
+    let window_paint_rect = state.item.to_window_rect(paint_rect);
+
+    let mut window_scene = Scene::new();
+    paint_widget(
+        global_state,
+        &mut window_scene,
+        scenes,
+        widget,
+        state,
+        window_paint_rect,
+        false,
+        None,
+    );
+
+    let mut scene = Scene::new();
+    scene.append(&window_scene, Some(transform.inverse()));
+    Some(WidgetScene {
+        scene,
+        size,
+        paint_rect,
+    })
+}
@@ -1,7 +1,8 @@
 // Copyright 2024 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use cursor_icon::CursorIcon;
 use tracing::{info_span, trace};
@@ -9,9 +10,11 @@ use tracing::{info_span, trace};
 use crate::passes::event::run_on_pointer_event_pass;
 use crate::passes::{merge_state_up, recurse_on_children};
 use crate::render_root::{RenderRoot, RenderRootSignal, RenderRootState};
+use crate::text::TextStyle;
 use crate::tree_arena::ArenaMut;
 use crate::{
-    PointerEvent, QueryCtx, RegisterCtx, Update, UpdateCtx, Widget, WidgetId, WidgetState,
+    Env, PointerEvent, QueryCtx, RegisterCtx, Update, UpdateCtx, Widget, WidgetFlags, WidgetId,
+    WidgetState, WindowTheme,
 };
 
 // --- MARK: HELPERS ---
@@ -85,10 +88,10 @@ fn update_widget_tree(
     let _span = trace.then(|| widget.item.make_trace_span().entered());
     let id = state.item.id;
 
-    if !state.item.children_changed {
+    if !state.item.flags.contains(WidgetFlags::CHILDREN_CHANGED) {
         return;
     }
-    state.item.children_changed = false;
+    state.item.flags.remove(WidgetFlags::CHILDREN_CHANGED);
 
     {
         let mut ctx = RegisterCtx {
@@ -132,7 +135,7 @@ fn update_widget_tree(
         }
     }
 
-    if state.item.is_new {
+    if state.item.flags.contains(WidgetFlags::IS_NEW) {
         let mut ctx = UpdateCtx {
             global_state,
             widget_state: state.item,
@@ -146,10 +149,19 @@ fn update_widget_tree(
                 widget.item.short_type_name()
             );
         }
-        state.item.accepts_pointer_interaction = widget.item.accepts_pointer_interaction();
-        state.item.accepts_focus = widget.item.accepts_focus();
-        state.item.accepts_text_input = widget.item.accepts_text_input();
-        state.item.is_new = false;
+        state.item.flags.set(
+            WidgetFlags::ACCEPTS_POINTER_INTERACTION,
+            widget.item.accepts_pointer_interaction(),
+        );
+        state
+            .item
+            .flags
+            .set(WidgetFlags::ACCEPTS_FOCUS, widget.item.accepts_focus());
+        state.item.flags.set(
+            WidgetFlags::ACCEPTS_TEXT_INPUT,
+            widget.item.accepts_text_input(),
+        );
+        state.item.flags.remove(WidgetFlags::IS_NEW);
     }
 
     // We can recurse on this widget's children, because they have already been added
@@ -178,13 +190,68 @@ pub(crate) fn run_update_widget_tree_pass(root: &mut RenderRoot) {
         };
         ctx.register_child(&mut root.root);
     }
+    update_popup_roots(root);
+
+    for id in root.root_ids() {
+        let (root_widget, mut root_state) = root.widget_arena.get_pair_mut(id);
+        update_widget_tree(
+            &mut root.global_state,
+            root_widget,
+            root_state.reborrow_mut(),
+        );
+    }
+}
+
+/// Add the popups shown since the last rewrite passes to the widget tree, as new roots,
+/// and remove the popups which were closed.
+fn update_popup_roots(root: &mut RenderRoot) {
+    let closed = std::mem::take(&mut root.global_state.closed_popups);
+    let mut new = std::mem::take(&mut root.global_state.new_popups);
+    if closed.is_empty() && new.is_empty() {
+        return;
+    }
+
+    // Popups closed before being added are dropped right away.
+    new.retain(|popup| !closed.contains(&popup.id()));
+    let mut ctx = RegisterCtx {
+        widget_state_children: root.widget_arena.widget_states.root_token_mut(),
+        widget_children: root.widget_arena.widgets.root_token_mut(),
+        #[cfg(debug_assertions)]
+        registered_ids: Vec::new(),
+    };
+    for popup in &mut new {
+        ctx.register_child(popup);
+    }
+    root.popup_roots.extend(new);
+
+    for id in closed {
+        let Some(index) = root.popup_roots.iter().position(|popup| popup.id() == id) else {
+            continue;
+        };
+        root.popup_roots.remove(index);
+        let _ = root
+            .widget_arena
+            .widget_states
+            .root_token_mut()
+            .remove_child(id);
+        let _ = root.widget_arena.widgets.root_token_mut().remove_child(id);
+        let global_state = &mut root.global_state;
+        global_state.scenes.remove(&id);
+        global_state.ime_areas.remove(&id);
+        global_state.tooltips.remove(&id);
+        global_state.snap_points.remove(&id);
+        global_state.positions_in_set.remove(&id);
+    }
 
-    let (root_widget, mut root_state) = root.widget_arena.get_pair_mut(root.root.id());
-    update_widget_tree(
-        &mut root.global_state,
-        root_widget,
-        root_state.reborrow_mut(),
+    // The root widget's node lists the popups, and the focus chain includes them.
+    // The window is painted again without the closed popups.
+    root.root_state().flags.insert(
+        WidgetFlags::REQUEST_ACCESSIBILITY
+            | WidgetFlags::NEEDS_ACCESSIBILITY
+            | WidgetFlags::UPDATE_FOCUS_CHAIN
+            | WidgetFlags::NEEDS_PAINT,
     );
+    root.global_state.needs_pointer_pass = true;
 }
 
 // ----------------
@@ -199,12 +266,21 @@ fn update_disabled_for_widget(
     let _span = widget.item.make_trace_span().entered();
     let id = state.item.id;
 
-    let disabled = state.item.is_explicitly_disabled || parent_disabled;
-    if !state.item.needs_update_disabled && disabled == state.item.is_disabled {
+    let disabled = state
+        .item
+        .flags
+        .intersects(WidgetFlags::IS_EXPLICITLY_DISABLED | WidgetFlags::IS_DISABLED_BY_PARENT)
+        || parent_disabled;
+    if !state
+        .item
+        .flags
+        .contains(WidgetFlags::NEEDS_UPDATE_DISABLED)
+        && disabled == state.item.flags.contains(WidgetFlags::IS_DISABLED)
+    {
         return;
     }
 
-    if disabled != state.item.is_disabled {
+    if disabled != state.item.flags.contains(WidgetFlags::IS_DISABLED) {
         let mut ctx = UpdateCtx {
             global_state,
             widget_state: state.item,
@@ -214,13 +290,15 @@ fn update_disabled_for_widget(
         widget
             .item
             .update(&mut ctx, &Update::DisabledChanged(disabled));
-        state.item.is_disabled = disabled;
-        state.item.update_focus_chain = true;
-        state.item.request_accessibility = true;
-        state.item.needs_accessibility = true;
+        state.item.flags.set(WidgetFlags::IS_DISABLED, disabled);
+        state.item.flags.insert(
+            WidgetFlags::UPDATE_FOCUS_CHAIN
+                | WidgetFlags::REQUEST_ACCESSIBILITY
+                | WidgetFlags::NEEDS_ACCESSIBILITY,
+        );
     }
 
-    state.item.needs_update_disabled = false;
+    state.item.flags.remove(WidgetFlags::NEEDS_UPDATE_DISABLED);
 
     let parent_state = state.item;
     recurse_on_children(
@@ -238,12 +316,14 @@ pub(crate) fn run_update_disabled_pass(root: &mut RenderRoot) {
     let _span = info_span!("update_disabled").entered();
 
     // If a widget was enabled or disabled, the pointer icon may need to change.
-    if root.root_state().needs_update_disabled {
+    if root.roots_have(WidgetFlags::NEEDS_UPDATE_DISABLED) {
         root.global_state.needs_pointer_pass = true;
     }
 
-    let (root_widget, root_state) = root.widget_arena.get_pair_mut(root.root.id());
-    update_disabled_for_widget(&mut root.global_state, root_widget, root_state, false);
+    for id in root.root_ids() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(id);
+        update_disabled_for_widget(&mut root.global_state, root_widget, root_state, false);
+    }
 }
 
 // ----------------
@@ -253,6 +333,240 @@ pub(crate) fn run_update_disabled_pass(root: &mut RenderRoot) {
 // The stereotypical use case would be the contents of hidden tabs in a "tab group" widget.
 // Scrolled-out widgets are *not* stashed.
 
+// --- MARK: UPDATE TEXT STYLE ---
+fn update_text_style_for_widget(
+    global_state: &mut RenderRootState,
+    mut widget: ArenaMut<'_, Box<dyn Widget>>,
+    mut state: ArenaMut<'_, WidgetState>,
+    parent_style: &Arc<TextStyle>,
+) {
+    let _span = widget.item.make_trace_span().entered();
+    let id = state.item.id;
+
+    let style = match &state.item.explicit_text_style {
+        Some(explicit) => Arc::new(explicit.inherit_from(parent_style)),
+        None => parent_style.clone(),
+    };
+    let style_changed = style != state.item.text_style;
+    if !state
+        .item
+        .flags
+        .contains(WidgetFlags::NEEDS_UPDATE_TEXT_STYLE)
+        && !style_changed
+    {
+        return;
+    }
+
+    // Unlike the disabled pass, the new value is stored before the event is sent,
+    // so that widgets can read it from the context.
+    state.item.text_style = style.clone();
+    if style_changed {
+        let mut ctx = UpdateCtx {
+            global_state,
+            widget_state: state.item,
+            widget_state_children: state.children.reborrow_mut(),
+            widget_children: widget.children.reborrow_mut(),
+        };
+        widget.item.update(&mut ctx, &Update::TextStyleChanged);
+    }
+
+    state
+        .item
+        .flags
+        .remove(WidgetFlags::NEEDS_UPDATE_TEXT_STYLE);
+
+    let parent_state = state.item;
+    recurse_on_children(
+        id,
+        widget.reborrow_mut(),
+        state.children,
+        |widget, mut state| {
+            update_text_style_for_widget(global_state, widget, state.reborrow_mut(), &style);
+            parent_state.merge_up(state.item);
+        },
+    );
+}
+
+pub(crate) fn run_update_text_style_pass(root: &mut RenderRoot) {
+    let _span = info_span!("update_text_style").entered();
+
+    let root_style = Arc::default();
+    for id in root.root_ids() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(id);
+        update_text_style_for_widget(&mut root.global_state, root_widget, root_state, &root_style);
+    }
+}
+
+// ----------------
+
+// --- MARK: UPDATE ENV ---
+fn update_env_for_widget(
+    global_state: &mut RenderRootState,
+    mut widget: ArenaMut<'_, Box<dyn Widget>>,
+    mut state: ArenaMut<'_, WidgetState>,
+    parent_env: &Arc<Env>,
+    parent_env_changed: bool,
+) {
+    let _span = widget.item.make_trace_span().entered();
+    let id = state.item.id;
+
+    if !parent_env_changed && !state.item.flags.contains(WidgetFlags::NEEDS_UPDATE_ENV) {
+        return;
+    }
+
+    let mut env = match &state.item.explicit_env {
+        Some(explicit) => Arc::new(explicit.inherit_from(parent_env)),
+        None => parent_env.clone(),
+    };
+    // Widgets which don't set values share their parent's `Arc`, so the envs are only
+    // compared when the `Arc` was replaced.
+    let env_changed = !Arc::ptr_eq(&env, &state.item.env) && env != state.item.env;
+    if !env_changed {
+        // Keep sharing the same `Arc` with the children.
+        env = state.item.env.clone();
+    }
+
+    // Like the text style, the new env is stored before the event is sent.
+    state.item.env = env.clone();
+    if env_changed {
+        // Theme values are read in layout and paint, so both are done again.
+        state.item.flags.insert(
+            WidgetFlags::REQUEST_LAYOUT
+                | WidgetFlags::NEEDS_LAYOUT
+                | WidgetFlags::REQUEST_PAINT
+                | WidgetFlags::NEEDS_PAINT,
+        );
+        let mut ctx = UpdateCtx {
+            global_state,
+            widget_state: state.item,
+            widget_state_children: state.children.reborrow_mut(),
+            widget_children: widget.children.reborrow_mut(),
+        };
+        widget.item.update(&mut ctx, &Update::EnvChanged);
+    }
+
+    state.item.flags.remove(WidgetFlags::NEEDS_UPDATE_ENV);
+
+    let parent_state = state.item;
+    recurse_on_children(
+        id,
+        widget.reborrow_mut(),
+        state.children,
+        |widget, mut state| {
+            update_env_for_widget(
+                global_state,
+                widget,
+                state.reborrow_mut(),
+                &env,
+                env_changed,
+            );
+            parent_state.merge_up(state.item);
+        },
+    );
+}
+
+pub(crate) fn run_update_env_pass(root: &mut RenderRoot) {
+    let _span = info_span!("update_env").entered();
+
+    let root_env = root.global_state.env.clone();
+    for id in root.root_ids() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(id);
+        update_env_for_widget(
+            &mut root.global_state,
+            root_widget,
+            root_state,
+            &root_env,
+            false,
+        );
+    }
+}
+
+// ----------------
+
+// --- MARK: UPDATE THEME ---
+fn update_theme_for_widget(
+    global_state: &mut RenderRootState,
+    mut widget: ArenaMut<'_, Box<dyn Widget>>,
+    mut state: ArenaMut<'_, WidgetState>,
+    theme: WindowTheme,
+) {
+    let _span = widget.item.make_trace_span().entered();
+    let id = state.item.id;
+
+    let mut ctx = UpdateCtx {
+        global_state,
+        widget_state: state.item,
+        widget_state_children: state.children.reborrow_mut(),
+        widget_children: widget.children.reborrow_mut(),
+    };
+    widget.item.update(&mut ctx, &Update::ThemeChanged(theme));
+
+    let parent_state = state.item;
+    recurse_on_children(
+        id,
+        widget.reborrow_mut(),
+        state.children,
+        |widget, mut state| {
+            update_theme_for_widget(global_state, widget, state.reborrow_mut(), theme);
+            parent_state.merge_up(state.item);
+        },
+    );
+}
+
+/// Send [`Update::ThemeChanged`] to every widget, stashed or not.
+pub(crate) fn run_update_theme_pass(root: &mut RenderRoot) {
+    let _span = info_span!("update_theme").entered();
+
+    let theme = root.global_state.color_scheme;
+    for id in root.root_ids() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(id);
+        update_theme_for_widget(&mut root.global_state, root_widget, root_state, theme);
+    }
+}
+
+// ----------------
+
+// --- MARK: UPDATE LOCALE ---
+fn update_locale_for_widget(
+    global_state: &mut RenderRootState,
+    mut widget: ArenaMut<'_, Box<dyn Widget>>,
+    mut state: ArenaMut<'_, WidgetState>,
+) {
+    let _span = widget.item.make_trace_span().entered();
+    let id = state.item.id;
+
+    let mut ctx = UpdateCtx {
+        global_state,
+        widget_state: state.item,
+        widget_state_children: state.children.reborrow_mut(),
+        widget_children: widget.children.reborrow_mut(),
+    };
+    widget.item.update(&mut ctx, &Update::LocaleChanged);
+
+    let parent_state = state.item;
+    recurse_on_children(
+        id,
+        widget.reborrow_mut(),
+        state.children,
+        |widget, mut state| {
+            update_locale_for_widget(global_state, widget, state.reborrow_mut());
+            parent_state.merge_up(state.item);
+        },
+    );
+}
+
+/// Send [`Update::LocaleChanged`] to every widget, stashed or not.
+pub(crate) fn run_update_locale_pass(root: &mut RenderRoot) {
+    let _span = info_span!("update_locale").entered();
+
+    for id in root.root_ids() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(id);
+        update_locale_for_widget(&mut root.global_state, root_widget, root_state);
+    }
+}
+
+// ----------------
+
 // --- MARK: UPDATE STASHED ---
 fn update_stashed_for_widget(
     global_state: &mut RenderRootState,
@@ -263,12 +577,18 @@ fn update_stashed_for_widget(
     let _span = widget.item.make_trace_span().entered();
     let id = state.item.id;
 
-    let stashed = state.item.is_explicitly_stashed || parent_stashed;
-    if !state.item.needs_update_stashed && stashed == state.item.is_stashed {
+    let stashed = state
+        .item
+        .flags
+        .contains(WidgetFlags::IS_EXPLICITLY_STASHED)
+        || parent_stashed;
+    if !state.item.flags.contains(WidgetFlags::NEEDS_UPDATE_STASHED)
+        && stashed == state.item.flags.contains(WidgetFlags::IS_STASHED)
+    {
         return;
     }
 
-    if stashed != state.item.is_stashed {
+    if stashed != state.item.flags.contains(WidgetFlags::IS_STASHED) {
         let mut ctx = UpdateCtx {
             global_state,
             widget_state: state.item,
@@ -278,22 +598,24 @@ fn update_stashed_for_widget(
         widget
             .item
             .update(&mut ctx, &Update::StashedChanged(stashed));
-        state.item.is_stashed = stashed;
-        state.item.update_focus_chain = true;
+        state.item.flags.set(WidgetFlags::IS_STASHED, stashed);
+        state.item.flags.insert(WidgetFlags::UPDATE_FOCUS_CHAIN);
         // Note: We don't need request_repaint because stashing doesn't actually change
         // how widgets are painted, only how the Scenes they create are composed.
-        state.item.needs_paint = true;
-        state.item.needs_accessibility = true;
+        state
+            .item
+            .flags
+            .insert(WidgetFlags::NEEDS_PAINT | WidgetFlags::NEEDS_ACCESSIBILITY);
         // TODO - Remove once accessibility can be composed, same as above.
-        state.item.request_accessibility = true;
+        state.item.flags.insert(WidgetFlags::REQUEST_ACCESSIBILITY);
         // A stashed child doesn't need layout. We assumed that a child that just got
         // un-stashed will need relayout.
         // TODO - Handle this interaction more elegantly.
-        state.item.needs_layout = !stashed;
-        state.item.request_layout = !stashed;
+        state.item.flags.set(WidgetFlags::NEEDS_LAYOUT, !stashed);
+        state.item.flags.set(WidgetFlags::REQUEST_LAYOUT, !stashed);
     }
 
-    state.item.needs_update_stashed = false;
+    state.item.flags.remove(WidgetFlags::NEEDS_UPDATE_STASHED);
 
     let parent_state = state.item;
     recurse_on_children(
@@ -310,8 +632,10 @@ fn update_stashed_for_widget(
 pub(crate) fn run_update_stashed_pass(root: &mut RenderRoot) {
     let _span = info_span!("update_stashed").entered();
 
-    let (root_widget, root_state) = root.widget_arena.get_pair_mut(root.root.id());
-    update_stashed_for_widget(&mut root.global_state, root_widget, root_state, false);
+    for id in root.root_ids() {
+        let (root_widget, root_state) = root.widget_arena.get_pair_mut(id);
+        update_stashed_for_widget(&mut root.global_state, root_widget, root_state, false);
+    }
 }
 
 // ----------------
@@ -333,19 +657,31 @@ fn update_focus_chain_for_widget(
     let _span = widget.item.make_trace_span().entered();
     let id = state.item.id;
 
-    if !state.item.update_focus_chain {
+    if !state.item.flags.contains(WidgetFlags::UPDATE_FOCUS_CHAIN) {
+        // The chain of this subtree is up to date, but the parent is rebuilding its own.
+        if is_in_focus_chain(state.item) {
+            parent_focus_chain.extend(&state.item.focus_chain);
+        }
         return;
     }
 
     // Replace has_focus to check if the value changed in the meantime
-    state.item.has_focus = global_state.focused_widget == Some(id);
-    let had_focus = state.item.has_focus;
+    state.item.flags.set(
+        WidgetFlags::HAS_FOCUS,
+        global_state.focused_widget == Some(id),
+    );
+    let had_focus = state.item.flags.contains(WidgetFlags::HAS_FOCUS);
 
     state.item.focus_chain.clear();
-    if state.item.accepts_focus {
+    // The widget may have called `accepts_focus_changed`.
+    state
+        .item
+        .flags
+        .set(WidgetFlags::ACCEPTS_FOCUS, widget.item.accepts_focus());
+    if state.item.flags.contains(WidgetFlags::ACCEPTS_FOCUS) {
         state.item.focus_chain.push(id);
     }
-    state.item.update_focus_chain = false;
+    state.item.flags.remove(WidgetFlags::UPDATE_FOCUS_CHAIN);
 
     let parent_state = &mut *state.item;
     recurse_on_children(
@@ -363,31 +699,45 @@ fn update_focus_chain_for_widget(
         },
     );
 
-    if !state.item.is_disabled {
+    if is_in_focus_chain(state.item) {
         parent_focus_chain.extend(&state.item.focus_chain);
     }
 
-    // had_focus is the old focus value. state.has_focus was replaced with parent_ctx.is_focused().
-    // Therefore if had_focus is true but state.has_focus is false then the widget which is
+    // had_focus is the old focus value. The `HAS_FOCUS` flag was replaced with parent_ctx.is_focused().
+    // Therefore if had_focus is true but the `HAS_FOCUS` flag is false then the widget which is
     // currently focused is not part of the functional tree anymore and should resign the focus.
-    if had_focus && !state.item.has_focus {
+    if had_focus && !state.item.flags.contains(WidgetFlags::HAS_FOCUS) {
         // Not sure about this logic, might remove
         global_state.next_focused_widget = None;
     }
-    state.item.has_focus = had_focus;
+    state.item.flags.set(WidgetFlags::HAS_FOCUS, had_focus);
+}
+
+/// Whether the focusable widgets of this subtree are reachable with <kbd>Tab</kbd>.
+fn is_in_focus_chain(state: &WidgetState) -> bool {
+    !state
+        .flags
+        .intersects(WidgetFlags::IS_DISABLED | WidgetFlags::IS_STASHED)
 }
 
 pub(crate) fn run_update_focus_chain_pass(root: &mut RenderRoot) {
     let _span = info_span!("update_focus_chain").entered();
-    let mut dummy_focus_chain = Vec::new();
-
-    let (root_widget, mut root_state) = root.widget_arena.get_pair_mut(root.root.id());
-    update_focus_chain_for_widget(
-        &mut root.global_state,
-        root_widget,
-        root_state.reborrow_mut(),
-        &mut dummy_focus_chain,
-    );
+    let mut focus_chain = Vec::new();
+
+    if !root.roots_have(WidgetFlags::UPDATE_FOCUS_CHAIN) {
+        return;
+    }
+    // The chains of the roots which are up to date are reused.
+    for id in root.root_ids() {
+        let (root_widget, mut root_state) = root.widget_arena.get_pair_mut(id);
+        update_focus_chain_for_widget(
+            &mut root.global_state,
+            root_widget,
+            root_state.reborrow_mut(),
+            &mut focus_chain,
+        );
+    }
+    root.global_state.focus_chain = focus_chain;
 }
 
 // ----------------
@@ -395,6 +745,12 @@ pub(crate) fn run_update_focus_chain_pass(root: &mut RenderRoot) {
 // --- MARK: UPDATE FOCUS ---
 pub(crate) fn run_update_focus_pass(root: &mut RenderRoot) {
     let _span = info_span!("update_focus").entered();
+    if let Some(direction) = root.global_state.next_focus_direction.take() {
+        root.set_focus_visible(true);
+        if let Some(id) = root.widget_in_direction(direction) {
+            root.global_state.next_focused_widget = Some(id);
+        }
+    }
     // If the focused widget is disabled, stashed or removed, we set
     // the focused id to None
     if let Some(id) = root.global_state.next_focused_widget {
@@ -425,18 +781,34 @@ pub(crate) fn run_update_focus_pass(root: &mut RenderRoot) {
         // run_update_pointer_pass
         // See comment in that function.
 
+        // `HAS_FOCUS` is merged up from children while we walk up the tree, so we
+        // remember the focus status of each widget from before this pass.
+        let mut had_focus = HashMap::new();
+        for widget_id in prev_focused_path.iter().chain(&next_focused_path).copied() {
+            if let Some(state) = root.widget_arena.widget_states.find(widget_id) {
+                let flags = state.item.flags;
+                had_focus.insert(widget_id, flags.contains(WidgetFlags::HAS_FOCUS));
+            }
+        }
+
         fn update_focused_status_of(
             root: &mut RenderRoot,
             widget_id: WidgetId,
             focused_set: &HashSet<WidgetId>,
+            had_focus: &mut HashMap<WidgetId, bool>,
         ) {
             run_targeted_update_pass(root, Some(widget_id), |widget, ctx| {
                 let has_focus = focused_set.contains(&ctx.widget_id());
+                let had_focus = had_focus
+                    .insert(ctx.widget_id(), has_focus)
+                    .unwrap_or_else(|| ctx.widget_state.flags.contains(WidgetFlags::HAS_FOCUS));
 
-                if ctx.widget_state.has_focus != has_focus {
+                if had_focus != has_focus {
                     widget.update(ctx, &Update::ChildFocusChanged(has_focus));
                 }
-                ctx.widget_state.has_focus = has_focus;
+                ctx.widget_state
+                    .flags
+                    .set(WidgetFlags::HAS_FOCUS, has_focus);
             });
         }
 
@@ -444,18 +816,28 @@ pub(crate) fn run_update_focus_pass(root: &mut RenderRoot) {
         // TODO - Document the iteration order for update_focus pass.
         for widget_id in prev_focused_path.iter().copied() {
             if root.widget_arena.has(widget_id)
-                && root.widget_arena.get_state_mut(widget_id).item.has_focus
+                && root
+                    .widget_arena
+                    .get_state_mut(widget_id)
+                    .item
+                    .flags
+                    .contains(WidgetFlags::HAS_FOCUS)
                     != focused_set.contains(&widget_id)
             {
-                update_focused_status_of(root, widget_id, &focused_set);
+                update_focused_status_of(root, widget_id, &focused_set, &mut had_focus);
             }
         }
         for widget_id in next_focused_path.iter().copied() {
             if root.widget_arena.has(widget_id)
-                && root.widget_arena.get_state_mut(widget_id).item.has_focus
+                && root
+                    .widget_arena
+                    .get_state_mut(widget_id)
+                    .item
+                    .flags
+                    .contains(WidgetFlags::HAS_FOCUS)
                     != focused_set.contains(&widget_id)
             {
-                update_focused_status_of(root, widget_id, &focused_set);
+                update_focused_status_of(root, widget_id, &focused_set, &mut had_focus);
             }
         }
     }
@@ -463,7 +845,11 @@ pub(crate) fn run_update_focus_pass(root: &mut RenderRoot) {
     if prev_focused != next_focused {
         let was_ime_active = root.global_state.is_ime_active;
         let is_ime_active = if let Some(id) = next_focused {
-            root.widget_arena.get_state(id).item.accepts_text_input
+            root.widget_arena
+                .get_state(id)
+                .item
+                .flags
+                .contains(WidgetFlags::ACCEPTS_TEXT_INPUT)
         } else {
             false
         };
@@ -471,15 +857,25 @@ pub(crate) fn run_update_focus_pass(root: &mut RenderRoot) {
 
         // We send FocusChange event to widget that lost and the widget that gained focus.
         // We also request accessibility, because build_access_node() depends on the focus state.
+        // If the focus is visible, the focus ring moves from one widget to the other.
+        let focus_visible = root.global_state.focus_visible;
         run_single_update_pass(root, prev_focused, |widget, ctx| {
             widget.update(ctx, &Update::FocusChanged(false));
-            ctx.widget_state.request_accessibility = true;
-            ctx.widget_state.needs_accessibility = true;
+            ctx.widget_state
+                .flags
+                .insert(WidgetFlags::REQUEST_ACCESSIBILITY | WidgetFlags::NEEDS_ACCESSIBILITY);
+            if focus_visible {
+                ctx.widget_state.flags.insert(WidgetFlags::NEEDS_PAINT);
+            }
         });
         run_single_update_pass(root, next_focused, |widget, ctx| {
             widget.update(ctx, &Update::FocusChanged(true));
-            ctx.widget_state.request_accessibility = true;
-            ctx.widget_state.needs_accessibility = true;
+            ctx.widget_state
+                .flags
+                .insert(WidgetFlags::REQUEST_ACCESSIBILITY | WidgetFlags::NEEDS_ACCESSIBILITY);
+            if focus_visible {
+                ctx.widget_state.flags.insert(WidgetFlags::NEEDS_PAINT);
+            }
         });
 
         if prev_focused.is_some() && was_ime_active {
@@ -490,13 +886,17 @@ pub(crate) fn run_update_focus_pass(root: &mut RenderRoot) {
         }
 
         if let Some(id) = next_focused {
-            let ime_area = root.widget_arena.get_state(id).item.get_ime_area();
+            let ime_area = root
+                .global_state
+                .get_ime_area(root.widget_arena.get_state(id).item);
             root.global_state
                 .emit_signal(RenderRootSignal::new_ime_moved_signal(ime_area));
         }
     }
 
-    root.global_state.focused_widget = root.global_state.next_focused_widget;
+    // Widgets may have moved focus again when it changed, e.g. a FocusGroup redirecting
+    // focus to a remembered widget, in which case this pass runs again.
+    root.global_state.focused_widget = next_focused;
     root.global_state.focused_path = next_focused_path;
 }
 
@@ -531,6 +931,100 @@ pub(crate) fn run_update_scroll_pass(root: &mut RenderRoot) {
 
 // ----------------
 
+// --- MARK: UPDATE TIMERS ---
+// This pass sends Update::Timer to the widgets whose timers are due.
+// Repeating timers are rescheduled, and others are removed.
+pub(crate) fn run_update_timers_pass(root: &mut RenderRoot) {
+    let _span = info_span!("update_timers").entered();
+
+    // Timers of widgets which were removed from the tree are dropped here.
+    let arena = &root.widget_arena;
+    root.global_state
+        .timers
+        .retain(|timer| arena.has(timer.widget_id));
+
+    let now = root.global_state.now();
+    let mut due: Vec<_> = root
+        .global_state
+        .timers
+        .iter()
+        .filter(|timer| timer.deadline <= now)
+        .map(|timer| (timer.deadline, timer.token, timer.widget_id))
+        .collect();
+    due.sort_by_key(|(deadline, ..)| *deadline);
+
+    root.global_state.timers.retain_mut(|timer| {
+        if timer.deadline > now {
+            return true;
+        }
+        let Some(interval) = timer.interval else {
+            return false;
+        };
+        timer.deadline += interval;
+        if timer.deadline <= now {
+            // Skip the repetitions we missed.
+            timer.deadline = now + interval;
+        }
+        true
+    });
+
+    for (_, token, widget_id) in due {
+        // An earlier timer may have removed the widget.
+        if !root.widget_arena.has(widget_id) {
+            continue;
+        }
+        run_single_update_pass(root, Some(widget_id), |widget, ctx| {
+            widget.update(ctx, &Update::Timer(token));
+        });
+    }
+}
+
+// --- MARK: DISMISS POPUPS ---
+// This pass closes the popups the user clicked outside of, and sends
+// Update::PopupClosed to the widgets which showed them, like for the popups
+// which closed themselves because of the user, e.g. a menu after Escape.
+pub(crate) fn run_dismiss_popups_pass(root: &mut RenderRoot, target: Option<WidgetId>) {
+    if root.global_state.popups.is_empty() {
+        return;
+    }
+    let _span = info_span!("dismiss_popups").entered();
+
+    // A popup stays open if the click is inside it or inside its anchor, or inside
+    // another popup which stays open and is anchored inside it.
+    let target_path = get_id_path(root, target);
+    let mut kept_anchor_paths: Vec<Vec<WidgetId>> = Vec::new();
+    let mut closed = Vec::new();
+    for popup in root.global_state.popups.iter().rev() {
+        let keep = target_path.contains(&popup.id)
+            || popup.position.is_none() && target_path.contains(&popup.anchor)
+            || kept_anchor_paths
+                .iter()
+                .any(|path| path.contains(&popup.id));
+        if keep {
+            kept_anchor_paths.push(get_id_path(root, Some(popup.anchor)));
+        } else {
+            closed.push(*popup);
+        }
+    }
+
+    for popup in closed {
+        root.global_state.dismiss_popup(popup.id);
+    }
+    run_popup_closed_pass(root);
+}
+
+pub(crate) fn run_popup_closed_pass(root: &mut RenderRoot) {
+    for popup in std::mem::take(&mut root.global_state.dismissed_popups) {
+        if root.widget_arena.has(popup.anchor) {
+            run_single_update_pass(root, Some(popup.anchor), |widget, ctx| {
+                widget.update(ctx, &Update::PopupClosed(popup.id));
+            });
+        }
+    }
+}
+
+// ----------------
+
 // --- MARK: UPDATE POINTER ---
 pub(crate) fn run_update_pointer_pass(root: &mut RenderRoot) {
     if !root.global_state.needs_pointer_pass {
@@ -544,18 +1038,31 @@ pub(crate) fn run_update_pointer_pass(root: &mut RenderRoot) {
 
     // Release pointer capture if target can no longer hold it.
     if let Some(id) = root.global_state.pointer_capture_target {
-        if !root.is_still_interactive(id) {
+        if !root.can_hold_pointer(id) {
             root.global_state.pointer_capture_target = None;
             run_on_pointer_event_pass(root, &PointerEvent::new_pointer_leave());
         }
     }
+    // Likewise for the other pointers.
+    let lost_captures: Vec<_> = (root.global_state.pointer_captures.iter())
+        .filter(|(_, id)| !root.can_hold_pointer(**id))
+        .map(|(pointer_id, _)| *pointer_id)
+        .collect();
+    for pointer_id in lost_captures {
+        root.global_state.pointer_captures.remove(&pointer_id);
+    }
+    // Likewise for the pointer lock.
+    if let Some(id) = root.global_state.pointer_lock_target {
+        if !root.can_hold_pointer(id) {
+            root.global_state.release_pointer_lock();
+            run_on_pointer_event_pass(root, &PointerEvent::new_pointer_leave());
+        }
+    }
 
     // -- UPDATE HOVERED WIDGETS --
     let mut next_hovered_widget = if let Some(pos) = pointer_pos {
         // TODO - Apply scale?
-        root.get_root_widget()
-            .find_widget_at_pos(pos)
-            .map(|widget| widget.id())
+        root.hit_test(pos)
     } else {
         None
     };
@@ -596,12 +1103,15 @@ pub(crate) fn run_update_pointer_pass(root: &mut RenderRoot) {
             run_targeted_update_pass(root, Some(widget_id), |widget, ctx| {
                 let is_hovered = hovered_set.contains(&ctx.widget_id());
 
-                if ctx.widget_state.is_hovered != is_hovered {
+                if ctx.widget_state.flags.contains(WidgetFlags::IS_HOVERED) != is_hovered {
                     widget.update(ctx, &Update::HoveredChanged(is_hovered));
-                    ctx.widget_state.request_accessibility = true;
-                    ctx.widget_state.needs_accessibility = true;
+                    ctx.widget_state.flags.insert(
+                        WidgetFlags::REQUEST_ACCESSIBILITY | WidgetFlags::NEEDS_ACCESSIBILITY,
+                    );
                 }
-                ctx.widget_state.is_hovered = is_hovered;
+                ctx.widget_state
+                    .flags
+                    .set(WidgetFlags::IS_HOVERED, is_hovered);
             });
         }
 
@@ -609,7 +1119,12 @@ pub(crate) fn run_update_pointer_pass(root: &mut RenderRoot) {
         // TODO - Document the iteration order for update_pointer pass.
         for widget_id in prev_hovered_path.iter().copied() {
             if root.widget_arena.has(widget_id)
-                && root.widget_arena.get_state_mut(widget_id).item.is_hovered
+                && root
+                    .widget_arena
+                    .get_state_mut(widget_id)
+                    .item
+                    .flags
+                    .contains(WidgetFlags::IS_HOVERED)
                     != hovered_set.contains(&widget_id)
             {
                 update_hovered_status_of(root, widget_id, &hovered_set);
@@ -617,7 +1132,12 @@ pub(crate) fn run_update_pointer_pass(root: &mut RenderRoot) {
         }
         for widget_id in next_hovered_path.iter().copied() {
             if root.widget_arena.has(widget_id)
-                && root.widget_arena.get_state_mut(widget_id).item.is_hovered
+                && root
+                    .widget_arena
+                    .get_state_mut(widget_id)
+                    .item
+                    .flags
+                    .contains(WidgetFlags::IS_HOVERED)
                     != hovered_set.contains(&widget_id)
             {
                 update_hovered_status_of(root, widget_id, &hovered_set);
@@ -644,7 +1164,7 @@ pub(crate) fn run_update_pointer_pass(root: &mut RenderRoot) {
             widget_state: state.item,
         };
 
-        if state.item.is_disabled {
+        if state.item.flags.contains(WidgetFlags::IS_DISABLED) {
             CursorIcon::Default
         } else {
             widget.item.get_cursor(&ctx, pos)
@@ -0,0 +1,299 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An optional watchdog which reports passes exceeding a time budget.
+
+use std::fmt::Write;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::WidgetId;
+
+/// The widgets currently visited by a pass, starting from the root.
+type WidgetPath = Vec<(&'static str, WidgetId)>;
+
+/// Detects passes which exceed a time budget, e.g. because a widget misuses the layout
+/// protocol and ends up in an infinite loop.
+///
+/// The watchdog is checked each time a pass visits a widget, against the time of the
+/// [`RenderRoot`](crate::RenderRoot), which tests can mock. The first time the current
+/// pass exceeds the budget, the path of the widget being visited and the timings of the
+/// passes of the current frame are logged. In debug builds, the rest of the pass is then
+/// skipped: remaining widgets keep their previous layout, or aren't painted. Skipped
+/// widgets are laid out on the next frame.
+///
+/// A widget can also hang without visiting other widgets, e.g. in a loop inside its own
+/// `layout` method. Outside of wasm, a background thread reports passes which are still
+/// running after their budget, with the widget they are stuck in.
+///
+/// The watchdog is disabled by default. It can be enabled with
+/// [`RenderRoot::set_pass_watchdog`](crate::RenderRoot::set_pass_watchdog), or the
+/// `MASONRY_PASS_WATCHDOG_MS` environment variable, which holds the budget in milliseconds.
+pub(crate) struct PassWatchdog {
+    budget: Option<Duration>,
+    /// The name of the current pass, and when it started.
+    pass: Option<(&'static str, Instant)>,
+    /// The widgets currently visited by the pass, shared with the hang monitor.
+    #[cfg(not(target_arch = "wasm32"))]
+    path: Arc<Mutex<WidgetPath>>,
+    #[cfg(target_arch = "wasm32")]
+    path: WidgetPath,
+    /// How long each pass of the current frame took.
+    timings: Vec<(&'static str, Duration)>,
+    /// Whether the current pass has exceeded its budget.
+    tripped: bool,
+    /// Whether a pass of the current frame has exceeded its budget.
+    frame_tripped: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    hang_monitor: Option<HangMonitor>,
+}
+
+impl PassWatchdog {
+    pub(crate) fn from_env() -> Self {
+        let budget = match std::env::var("MASONRY_PASS_WATCHDOG_MS") {
+            Ok(env_var) => match env_var.trim().parse::<u64>() {
+                Ok(ms) => Some(Duration::from_millis(ms)),
+                Err(err) => {
+                    tracing::error!(
+                        ?env_var,
+                        "Couldn't parse `MASONRY_PASS_WATCHDOG_MS` environment variable: {err}",
+                    );
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+        Self {
+            budget,
+            pass: None,
+            path: Default::default(),
+            timings: Vec::new(),
+            tripped: false,
+            frame_tripped: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            hang_monitor: None,
+        }
+    }
+
+    pub(crate) fn set_budget(&mut self, budget: Option<Duration>) {
+        self.budget = budget;
+    }
+
+    /// Forget the timings of the previous frame.
+    pub(crate) fn start_frame(&mut self) {
+        self.timings.clear();
+        self.frame_tripped = false;
+    }
+
+    /// Whether a pass of the current frame exceeded its budget and was cut short.
+    ///
+    /// The rest of the work is left for the next frame.
+    pub(crate) fn frame_tripped(&self) -> bool {
+        self.frame_tripped && cfg!(debug_assertions)
+    }
+
+    pub(crate) fn start_pass(&mut self, name: &'static str, now: Instant) {
+        let Some(budget) = self.budget else {
+            return;
+        };
+        self.pass = Some((name, now));
+        self.path_mut().clear();
+        self.tripped = false;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = self.path.clone();
+            self.hang_monitor
+                .get_or_insert_with(|| HangMonitor::spawn(path))
+                .arm(name, budget);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = budget;
+    }
+
+    pub(crate) fn end_pass(&mut self, now: Instant) {
+        let Some((name, start)) = self.pass else {
+            return;
+        };
+        self.disarm_hang_monitor();
+        // Passes which don't check the watchdog while visiting widgets are reported here.
+        if !self.tripped && self.is_over_budget(start, now) {
+            self.report(false, now);
+        }
+        self.timings.push((name, now - start));
+        self.pass = None;
+    }
+
+    pub(crate) fn enter_widget(&mut self, name: &'static str, id: WidgetId) {
+        if self.pass.is_some() {
+            self.path_mut().push((name, id));
+        }
+    }
+
+    pub(crate) fn exit_widget(&mut self) {
+        if self.pass.is_some() {
+            self.path_mut().pop();
+        }
+    }
+
+    /// The number of widgets entered and not exited yet.
+    pub(crate) fn depth(&mut self) -> usize {
+        self.path_mut().len()
+    }
+
+    /// Exit the widgets entered after the path had length `depth`, e.g. when a panic
+    /// unwound through them.
+    pub(crate) fn exit_widgets_to(&mut self, depth: usize) {
+        self.path_mut().truncate(depth);
+    }
+
+    /// Check whether the current pass has exceeded its budget at time `now`.
+    ///
+    /// Returns `true` if the rest of the pass should be skipped, which only happens in debug builds.
+    pub(crate) fn check(&mut self, now: Instant) -> bool {
+        let Some((_, start)) = self.pass else {
+            return false;
+        };
+        if !self.tripped && self.is_over_budget(start, now) {
+            self.tripped = true;
+            self.frame_tripped = true;
+            // The overrun is reported here, with a more precise path.
+            self.disarm_hang_monitor();
+            self.report(cfg!(debug_assertions), now);
+        }
+        self.tripped && cfg!(debug_assertions)
+    }
+
+    fn is_over_budget(&self, start: Instant, now: Instant) -> bool {
+        self.budget.is_some_and(|budget| now - start > budget)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn path_mut(&mut self) -> std::sync::MutexGuard<'_, WidgetPath> {
+        // The path is only read by the hang monitor, which doesn't panic while holding it.
+        self.path.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn path_mut(&mut self) -> &mut WidgetPath {
+        &mut self.path
+    }
+
+    fn disarm_hang_monitor(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(monitor) = &self.hang_monitor {
+            monitor.disarm();
+        }
+    }
+
+    fn report(&mut self, skipping_pass: bool, now: Instant) {
+        let Some(((name, start), budget)) = self.pass.zip(self.budget) else {
+            return;
+        };
+        let path = format_path(&self.path_mut());
+        let mut timings = String::new();
+        for (pass, duration) in &self.timings {
+            let _ = write!(timings, "{pass}: {duration:?}, ");
+        }
+        let _ = write!(timings, "{name}: {:?}", now - start);
+        tracing::error!(
+            "Pass '{name}' exceeded its budget of {budget:?} while visiting [{path}]. \
+             Timings of this frame: {timings}.",
+        );
+        if skipping_pass {
+            tracing::error!("Skipping the rest of the '{name}' pass.");
+        }
+    }
+}
+
+fn format_path(path: &[(&'static str, WidgetId)]) -> String {
+    path.iter()
+        .map(|(name, id)| format!("'{name}' {id}"))
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+// --- MARK: HANG MONITOR ---
+/// A thread which reports passes still running after their budget.
+///
+/// Unlike [`PassWatchdog::check`], this catches widgets which hang without visiting other
+/// widgets. It uses the real time, and only reports: the pass can't be interrupted.
+#[cfg(not(target_arch = "wasm32"))]
+struct HangMonitor {
+    shared: Arc<(Mutex<MonitorState>, Condvar)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct MonitorState {
+    /// The running pass, with its budget and when it started.
+    pass: Option<(&'static str, Duration, std::time::Instant)>,
+    shutdown: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HangMonitor {
+    fn spawn(path: Arc<Mutex<WidgetPath>>) -> Self {
+        let shared = Arc::new((Mutex::new(MonitorState::default()), Condvar::new()));
+        let thread_shared = shared.clone();
+        let spawned = std::thread::Builder::new()
+            .name("masonry-pass-watchdog".into())
+            .spawn(move || Self::run(&thread_shared, &path));
+        if let Err(err) = spawned {
+            tracing::warn!("Couldn't start the pass watchdog thread: {err}");
+        }
+        Self { shared }
+    }
+
+    fn run(shared: &(Mutex<MonitorState>, Condvar), path: &Mutex<WidgetPath>) {
+        let (state, condvar) = shared;
+        let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+        while !state.shutdown {
+            let Some((name, budget, start)) = state.pass else {
+                state = condvar.wait(state).unwrap_or_else(|err| err.into_inner());
+                continue;
+            };
+            let elapsed = start.elapsed();
+            if elapsed <= budget {
+                state = condvar
+                    .wait_timeout(state, budget - elapsed)
+                    .unwrap_or_else(|err| err.into_inner())
+                    .0;
+                continue;
+            }
+            // The pass is reported once.
+            state.pass = None;
+            let path = format_path(&path.lock().unwrap_or_else(|err| err.into_inner()));
+            tracing::error!(
+                "Pass '{name}' has been running for over {budget:?}, \
+                 and may be stuck in [{path}].",
+            );
+        }
+    }
+
+    fn arm(&self, name: &'static str, budget: Duration) {
+        self.update(|state| state.pass = Some((name, budget, std::time::Instant::now())));
+    }
+
+    fn disarm(&self) {
+        self.update(|state| state.pass = None);
+    }
+
+    fn update(&self, f: impl FnOnce(&mut MonitorState)) {
+        let (state, condvar) = &*self.shared;
+        f(&mut state.lock().unwrap_or_else(|err| err.into_inner()));
+        condvar.notify_one();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for HangMonitor {
+    fn drop(&mut self) {
+        self.update(|state| state.shutdown = true);
+    }
+}
@@ -1,47 +1,79 @@
 // Copyright 2019 the Xilem Authors and the Druid Authors
 // SPDX-License-Identifier: Apache-2.0
 
+use std::any::Any;
 use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use accesskit::{ActionRequest, TreeUpdate};
 use parley::fontique::{self, Collection, CollectionOptions};
 use parley::{FontContext, LayoutContext};
 use tracing::{info_span, warn};
-use vello::kurbo::{self, Rect};
+use vello::kurbo::{self, BezPath, Point, Rect, Shape as _, Size, Vec2};
 use vello::Scene;
+use winit::event::TouchPhase;
+use winit::window::WindowAttributes;
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 #[cfg(target_arch = "wasm32")]
 use web_time::Instant;
 
+use crate::clipboard::ClipboardContent;
 use crate::debug_logger::DebugLogger;
-use crate::dpi::{LogicalPosition, LogicalSize, PhysicalSize};
-use crate::event::{PointerEvent, TextEvent, WindowEvent};
+use crate::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
+use crate::event::{
+    FocusDirection, MonitorInfo, PlatformEvent, PointerButton, PointerEvent, PointerId,
+    PointerState, TextEvent, WindowEvent, WindowPlacement, WindowTheme,
+};
+use crate::gesture::GestureConfig;
+use crate::locale::{system_formatter, LocaleFormatter};
+use crate::overlay::PopupState;
 use crate::passes::accessibility::run_accessibility_pass;
 use crate::passes::anim::run_update_anim_pass;
 use crate::passes::compose::run_compose_pass;
 use crate::passes::event::{
-    run_on_access_event_pass, run_on_pointer_event_pass, run_on_text_event_pass,
+    run_long_press_pass, run_on_access_event_pass, run_on_platform_event_pass,
+    run_on_pointer_event_pass, run_on_text_event_pass, track_press,
 };
 use crate::passes::layout::run_layout_pass;
 use crate::passes::mutate::{mutate_widget, run_mutate_pass};
-use crate::passes::paint::run_paint_pass;
+use crate::passes::paint::{run_paint_pass, run_paint_subtree_pass, PaintFlashing};
 use crate::passes::update::{
-    run_update_disabled_pass, run_update_focus_chain_pass, run_update_focus_pass,
+    run_popup_closed_pass, run_update_disabled_pass, run_update_env_pass,
+    run_update_focus_chain_pass, run_update_focus_pass, run_update_locale_pass,
     run_update_pointer_pass, run_update_scroll_pass, run_update_stashed_pass,
+    run_update_text_style_pass, run_update_theme_pass, run_update_timers_pass,
     run_update_widget_tree_pass,
 };
-use crate::passes::{recurse_on_children, PassTracing};
-use crate::text::TextBrush;
+use crate::passes::watchdog::PassWatchdog;
+use crate::passes::{merge_state_up, recurse_on_children, PassTracing};
+use crate::testing::screenshots::render_widget_scene;
+use crate::text::{
+    measure_text, text_metrics, ArcStr, TextBrush, TextMetrics, TextPositioning, TextStyle,
+};
+use crate::theme;
 use crate::tree_arena::{ArenaMut, TreeArena};
-use crate::widget::{WidgetArena, WidgetMut, WidgetRef, WidgetState};
-use crate::{AccessEvent, Action, CursorIcon, Handled, QueryCtx, Widget, WidgetId, WidgetPod};
+use crate::widget::{WidgetArena, WidgetFlags, WidgetMut, WidgetRef, WidgetState};
+use crate::{
+    AccessEvent, Action, Color, CursorIcon, Env, Handled, QueryCtx, TimerToken, Widget, WidgetId,
+    WidgetPod,
+};
 
 // --- MARK: STRUCTS ---
 
 pub struct RenderRoot {
     pub(crate) root: WidgetPod<Box<dyn Widget>>,
+    /// The root widgets of the popups shown above the root widget, from bottom to top.
+    ///
+    /// They're separate roots of the widget tree, see [`RenderRoot::root_ids`].
+    pub(crate) popup_roots: Vec<WidgetPod<Box<dyn Widget>>>,
     pub(crate) size_policy: WindowSizePolicy,
     pub(crate) size: PhysicalSize<u32>,
     // TODO - Currently this is always 1.0
@@ -50,14 +82,34 @@ pub struct RenderRoot {
     /// Is `Some` if the most recently displayed frame was an animation frame.
     pub(crate) last_anim: Option<Instant>,
     pub(crate) last_mouse_pos: Option<LogicalPosition<f64>>,
+    /// The touch which is the primary pointer, see [`PointerState::is_primary`].
+    primary_touch: Option<u64>,
+    /// The fingers on the touchscreen, in the order they touched it, with their positions.
+    touches: Vec<(u64, Point)>,
     pub(crate) cursor_icon: CursorIcon,
     pub(crate) global_state: RenderRootState,
     // TODO - Add "access_tree_active" to detect when you don't need to update the
     // access tree
     pub(crate) rebuild_access_tree: bool,
     pub(crate) widget_arena: WidgetArena,
+    /// The most recent events, for bug reports.
+    pub(crate) event_history: VecDeque<String>,
+    /// The statistics of the last frame, returned by [`RenderRoot::frame_stats`].
+    pub(crate) last_frame_stats: FrameStats,
+    /// The renderer, set by the driver, for [`RenderRoot::diagnostics_report`].
+    pub(crate) renderer_info: Option<String>,
+    /// The env set with [`RenderRoot::set_env`], which the root env combines with the theme
+    /// values of the color scheme.
+    pub(crate) app_env: Env,
 }
 
+/// The number of events kept in [`RenderRoot::event_history`], and of actions kept in
+/// [`RenderRoot::action_history`].
+const EVENT_HISTORY_LEN: usize = 100;
+
+/// The shortest interval of repeating timers, so that they can't keep the event loop busy.
+pub(crate) const MIN_TIMER_INTERVAL: Duration = Duration::from_millis(1);
+
 // TODO - Document these fields.
 pub(crate) struct RenderRootState {
     pub(crate) debug_logger: DebugLogger,
@@ -65,18 +117,222 @@ pub(crate) struct RenderRootState {
     pub(crate) focused_widget: Option<WidgetId>,
     pub(crate) focused_path: Vec<WidgetId>,
     pub(crate) next_focused_widget: Option<WidgetId>,
+    /// The direction requested with [`EventCtx::focus_in_direction`](crate::EventCtx::focus_in_direction),
+    /// resolved in the next `update_focus` pass.
+    pub(crate) next_focus_direction: Option<FocusDirection>,
+    /// Whether focus was last moved with the keyboard, so the focused widget shows a focus ring.
+    pub(crate) focus_visible: bool,
+    /// The widgets which can be focused with <kbd>Tab</kbd>, in tree order.
+    pub(crate) focus_chain: Vec<WidgetId>,
     pub(crate) scroll_request_targets: Vec<(WidgetId, Rect)>,
     pub(crate) hovered_path: Vec<WidgetId>,
     pub(crate) pointer_capture_target: Option<WidgetId>,
+    /// The widgets which captured pointers other than the primary one.
+    pub(crate) pointer_captures: HashMap<PointerId, WidgetId>,
+    /// The pointer other than the primary one whose event is being dispatched, if any.
+    pub(crate) secondary_pointer: Option<PointerId>,
+    pub(crate) pointer_lock_target: Option<WidgetId>,
     pub(crate) cursor_icon: CursorIcon,
     pub(crate) font_context: FontContext,
     pub(crate) text_layout_context: LayoutContext<TextBrush>,
     pub(crate) mutate_callbacks: Vec<MutateCallback>,
     pub(crate) is_ime_active: bool,
     pub(crate) scenes: HashMap<WidgetId, Scene>,
+    /// The area of each widget that is being edited by an IME, in local coordinates.
+    ///
+    /// Kept out of [`WidgetState`] since very few widgets ever set it.
+    pub(crate) ime_areas: HashMap<WidgetId, Rect>,
+    /// The text of the tooltip of each widget which has one.
+    ///
+    /// Kept out of [`WidgetState`] since very few widgets ever set it.
+    pub(crate) tooltips: HashMap<WidgetId, ArcStr>,
+    /// The positions a scrolling ancestor can snap to, of each widget which reported some
+    /// with [`LayoutCtx::set_snap_points`](crate::LayoutCtx::set_snap_points).
+    ///
+    /// Kept out of [`WidgetState`] since only the content of scroll areas sets it.
+    pub(crate) snap_points: HashMap<WidgetId, Vec<Point>>,
+    /// The index in a set of items and the size of the set, of each widget which is an item
+    /// of a set, see [`EventCtx::set_position_in_set`](crate::EventCtx::set_position_in_set).
+    ///
+    /// Kept out of [`WidgetState`] since very few widgets ever set it.
+    pub(crate) positions_in_set: HashMap<WidgetId, (usize, usize)>,
     /// Whether data set in the pointer pass has been invalidated.
     pub(crate) needs_pointer_pass: bool,
+    pub(crate) formatter: Arc<dyn LocaleFormatter>,
+    /// The env which the envs of all widgets inherit from.
+    pub(crate) env: Arc<Env>,
+    /// The color scheme of the system, as last reported by the platform.
+    pub(crate) color_scheme: WindowTheme,
     pub(crate) trace: PassTracing,
+    pub(crate) watchdog: PassWatchdog,
+    pub(crate) paint_flashing: PaintFlashing,
+    /// The scale factor widget origins and sizes are rounded with, if pixel snapping is enabled.
+    pub(crate) pixel_snap_scale: Option<f64>,
+    pub(crate) text_positioning: TextPositioning,
+    /// The scale factor glyphs are snapped with in the current paint pass, if any.
+    pub(crate) text_snap_scale: Option<f64>,
+    /// The rhythm unit of the baseline grid, if enabled.
+    pub(crate) baseline_grid: Option<f64>,
+    /// The ratio of width to height the window is locked to, if any.
+    pub(crate) window_aspect_ratio: Option<f64>,
+    /// Whether the next layout pass resizes the window to the preferred size of the root widget.
+    pub(crate) size_to_content: bool,
+    pub(crate) monitors: Vec<MonitorInfo>,
+    /// The position of the content area of the window on the desktop, if known.
+    pub(crate) window_position: Option<PhysicalPosition<i32>>,
+    /// The region of the window which receives pointer events, in window coordinates.
+    pub(crate) window_shape: Option<BezPath>,
+    /// Whether actions are recorded in `action_history`.
+    pub(crate) record_actions: bool,
+    /// The most recent actions submitted by widgets, for bug reports.
+    pub(crate) action_history: VecDeque<String>,
+    /// The sequence number of the next action submitted.
+    pub(crate) next_action_sequence: u64,
+    /// The widgets which receive platform events, in the order they subscribed.
+    pub(crate) platform_event_subscribers: Vec<WidgetId>,
+    /// The widgets which asked for low latency presentation.
+    pub(crate) low_latency_widgets: Vec<WidgetId>,
+    /// The window opened with [`DriverCtx::open_diagnostics_window`](crate::app_driver::DriverCtx::open_diagnostics_window),
+    /// which shows the diagnostics report of this window.
+    pub(crate) diagnostics_window: Option<AppWindowId>,
+    /// The statistics of the frame in progress, moved to
+    /// [`RenderRoot::last_frame_stats`] when the frame is painted.
+    pub(crate) frame_stats: FrameStats,
+    /// The pending timers, in the order they were requested.
+    pub(crate) timers: Vec<Timer>,
+    pub(crate) next_timer_token: u64,
+    /// The current time, if it's simulated by the [`TestHarness`](crate::testing::TestHarness).
+    pub(crate) mock_time: Option<Instant>,
+    /// The thresholds of the click counting, long presses and drag starts of the primary pointer.
+    pub(crate) gesture_config: GestureConfig,
+    /// The last press of the primary pointer, which the next one may continue.
+    pub(crate) press: Option<PressState>,
+    /// The in-app drag-and-drop operation in progress, if any.
+    pub(crate) drag: Option<DragState>,
+    /// The text of the live region, announced by assistive technologies when it changes.
+    pub(crate) announcement: Option<String>,
+    /// The tooltip waiting for the pointer to rest, or shown, if any.
+    pub(crate) tooltip: Option<TooltipState>,
+    /// The popups shown above the root widget, from bottom to top.
+    pub(crate) popups: Vec<PopupState>,
+    /// The popups closed by the user since the last rewrite passes, whose anchors haven't
+    /// received [`Update::PopupClosed`](crate::Update::PopupClosed) yet.
+    pub(crate) dismissed_popups: Vec<PopupState>,
+    /// The root widgets of the popups shown since the last rewrite passes, which
+    /// haven't been added to the widget tree yet.
+    pub(crate) new_popups: Vec<WidgetPod<Box<dyn Widget>>>,
+    /// The popups closed since the last rewrite passes, which haven't been removed
+    /// from the widget tree yet.
+    pub(crate) closed_popups: Vec<WidgetId>,
+    /// The id of the root widget, whose accessibility node holds the popups and the
+    /// live region.
+    pub(crate) root_id: WidgetId,
+}
+
+/// An in-app drag, see [`EventCtx::start_drag`](crate::EventCtx::start_drag).
+pub(crate) struct DragState {
+    pub(crate) source: WidgetId,
+    pub(crate) payload: Option<Box<dyn Any>>,
+    pub(crate) image: Scene,
+    /// The window origin of the source widget when the drag started.
+    pub(crate) source_origin: Point,
+    /// The position of the pointer when the drag started, set by the pointer pass.
+    pub(crate) anchor: Option<Point>,
+    /// The widget which accepted the drop during the last [`PointerEvent::DragOver`].
+    pub(crate) drop_target: Option<WidgetId>,
+    /// For drags started with the keyboard, the widget of the focus chain the drag is over
+    /// and its center, which replaces the position of the pointer.
+    pub(crate) keyboard_over: Option<(WidgetId, Point)>,
+}
+
+/// A press of a button of the primary pointer, see [`PointerState::count`].
+pub(crate) struct PressState {
+    pub(crate) button: PointerButton,
+    /// When the button was pressed.
+    pub(crate) time: Instant,
+    /// Where the button was pressed.
+    pub(crate) position: Point,
+    pub(crate) count: u8,
+    /// Whether the button is still pressed.
+    pub(crate) pressed: bool,
+    /// Whether [`PointerEvent::DragStart`] was sent for this press.
+    pub(crate) dragging: bool,
+    /// Whether [`PointerEvent::LongPress`] was sent for this press.
+    pub(crate) long_pressed: bool,
+    /// The state of the pointer when the button was pressed.
+    pub(crate) state: PointerState,
+}
+
+/// The tooltip of a hovered widget, see [`EventCtx::set_tooltip`](crate::EventCtx::set_tooltip).
+pub(crate) struct TooltipState {
+    pub(crate) widget_id: WidgetId,
+    /// When the tooltip is shown, if the pointer doesn't move until then.
+    pub(crate) deadline: Instant,
+    pub(crate) shown: bool,
+}
+
+/// A timer requested by a widget, see [`EventCtx::request_timer`](crate::EventCtx::request_timer).
+pub(crate) struct Timer {
+    pub(crate) token: TimerToken,
+    pub(crate) widget_id: WidgetId,
+    pub(crate) deadline: Instant,
+    /// The interval of repeating timers.
+    pub(crate) interval: Option<Duration>,
+}
+
+/// The scene of a widget and its descendants, captured with [`RenderRoot::capture_widget_scene`].
+///
+/// The scene is in the widget's local coordinates, so it can be drawn anywhere,
+/// e.g. as a drag preview or a thumbnail.
+#[derive(Clone)]
+pub struct WidgetScene {
+    pub scene: Scene,
+    /// The layout size of the widget.
+    pub size: Size,
+    /// The area the widget and its descendants paint to, which may extend past `size`.
+    pub paint_rect: Rect,
+}
+
+/// Statistics about the last frame, returned by [`RenderRoot::frame_stats`].
+///
+/// These are cheap to collect, and meant for in-app performance overlays. For a detailed
+/// view of each frame, use a profiler, e.g. with the `tracy` feature.
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    /// How long each pass took since the previous frame, in the order they first ran.
+    ///
+    /// Passes which ran several times, e.g. because several events were handled
+    /// before the frame was painted, have their durations summed.
+    pub pass_durations: Vec<(&'static str, Duration)>,
+    /// The number of widgets whose layout was computed, rather than reused from the cache.
+    pub widgets_laid_out: usize,
+    /// The number of widgets visited by the compose pass.
+    ///
+    /// Subtrees which didn't move and didn't request compose are skipped.
+    pub widgets_composed: usize,
+    /// The number of widgets whose scene was painted again.
+    pub widgets_painted: usize,
+    /// The number of widget scenes the scene of the frame is made of.
+    pub scene_fragments: usize,
+    /// How long it took to render and present the frame, as reported by the driver
+    /// with [`RenderRoot::record_present`].
+    pub present_duration: Option<Duration>,
+    /// The memory used by the textures the frame was rendered to, in bytes, as reported
+    /// by the driver with [`RenderRoot::record_present`].
+    pub texture_bytes: u64,
+}
+
+impl FrameStats {
+    pub(crate) fn record_pass(&mut self, name: &'static str, duration: Duration) {
+        match self
+            .pass_durations
+            .iter_mut()
+            .find(|(pass, _)| *pass == name)
+        {
+            Some((_, total)) => *total += duration,
+            None => self.pass_durations.push((name, duration)),
+        }
+    }
 }
 
 pub(crate) struct MutateCallback {
@@ -110,19 +366,90 @@ pub struct RenderRootOptions {
     /// We expect to develop a much more fully-featured font API in the future, but
     /// this is necessary for our testing of Masonry.
     pub test_font: Option<Vec<u8>>,
+
+    /// The formatter used by widgets to display numbers and dates.
+    ///
+    /// If `None`, the [`system_formatter`] is used.
+    pub formatter: Option<Arc<dyn LocaleFormatter>>,
+
+    /// Whether the origins and sizes of widgets are rounded to physical pixels.
+    ///
+    /// See [`RenderRoot::set_pixel_snapping`].
+    pub pixel_snapping: bool,
+}
+
+/// Identifies a secondary window opened with [`EventCtx::open_window`](crate::EventCtx::open_window)
+/// or [`DriverCtx::open_window`](crate::DriverCtx::open_window).
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub struct AppWindowId(u64);
+
+impl AppWindowId {
+    /// Allocate a new, unique `AppWindowId`.
+    pub(crate) fn next() -> Self {
+        static WINDOW_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
+        Self(WINDOW_ID_COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub fn to_raw(self) -> u64 {
+        self.0
+    }
 }
 
 pub enum RenderRootSignal {
-    Action(Action, WidgetId),
+    /// An action submitted by a widget, with the widget's id and the action's sequence number.
+    ///
+    /// Actions are emitted in a deterministic order. Within a frame:
+    /// 1. Actions submitted while handling an event come first, in the order the widgets
+    ///    handled it, i.e. the target of the event then its ancestors.
+    /// 2. Then actions submitted by the callbacks queued with
+    ///    [`mutate_later`](crate::EventCtx::mutate_later), in the order they were queued.
+    /// 3. Then actions submitted by the other passes, such as update and layout, in the
+    ///    order the passes run, and in tree order within a pass.
+    ///
+    /// Sequence numbers start at 0 and increase by one with each action submitted to this
+    /// `RenderRoot`, so drivers can check that actions are handled in order, and refer to
+    /// an action unambiguously, e.g. in logs.
+    Action(Action, WidgetId, u64),
     StartIme,
     EndIme,
     ImeMoved(LogicalPosition<f64>, LogicalSize<f64>),
     RequestRedraw,
+    /// The accessibility tree changed, but nothing needs to be repainted, e.g. because
+    /// the pointer moved to a widget which doesn't change its appearance when hovered.
+    ///
+    /// The runner should send the result of [`RenderRoot::update_accessibility`] to the
+    /// platform, without painting a new frame. This signal is redundant with a pending
+    /// [`RequestRedraw`](Self::RequestRedraw), which also updates the accessibility tree.
+    RequestAccessibilityUpdate,
     RequestAnimFrame,
     TakeFocus,
     SetCursor(CursorIcon),
     SetSize(PhysicalSize<u32>),
     SetTitle(String),
+    /// Only let the user resize the window by multiples of the given size, e.g. to keep
+    /// the window aligned to the cells of a grid. `None` removes the constraint.
+    SetResizeIncrements(Option<LogicalSize<f64>>),
+    /// Move the window, and possibly resize it.
+    PlaceWindow(WindowPlacement),
+    /// Close the window. Closing the main window exits the app.
+    CloseWindow,
+    /// Open a secondary window with the given attributes, showing the given widget.
+    OpenWindow(AppWindowId, Box<WindowAttributes>, Box<dyn Widget>),
+    /// Close a secondary window opened with [`OpenWindow`](Self::OpenWindow).
+    CloseAppWindow(AppWindowId),
+    /// Copy data to the system clipboard.
+    SetClipboard(ClipboardContent),
+    /// Read the system clipboard, and send its text to the focused widget as a
+    /// [`TextEvent::Paste`].
+    RequestPaste,
+    /// Lock the cursor in place and hide it, or release it.
+    SetPointerLock(bool),
+    /// Present frames with as little latency as the platform allows, or go back to the
+    /// default presentation, which may queue more frames to avoid stutter.
+    ///
+    /// This is emitted when the first widget asks for low latency, and when the last
+    /// one stops.
+    SetLowLatency(bool),
 }
 
 impl RenderRoot {
@@ -133,15 +460,22 @@ impl RenderRoot {
             size_policy,
             scale_factor,
             test_font,
+            formatter,
+            pixel_snapping,
         }: RenderRootOptions,
     ) -> Self {
+        let root_widget = WidgetPod::new(root_widget).boxed();
+        let root_id = root_widget.id();
         let mut root = RenderRoot {
-            root: WidgetPod::new(root_widget).boxed(),
+            root: root_widget,
+            popup_roots: Vec::new(),
             size_policy,
             size: PhysicalSize::new(0, 0),
             scale_factor,
             last_anim: None,
             last_mouse_pos: None,
+            primary_touch: None,
+            touches: Vec::new(),
             cursor_icon: CursorIcon::Default,
             global_state: RenderRootState {
                 debug_logger: DebugLogger::new(false),
@@ -149,9 +483,15 @@ impl RenderRoot {
                 focused_widget: None,
                 focused_path: Vec::new(),
                 next_focused_widget: None,
+                next_focus_direction: None,
+                focus_visible: false,
+                focus_chain: Vec::new(),
                 scroll_request_targets: Vec::new(),
                 hovered_path: Vec::new(),
                 pointer_capture_target: None,
+                pointer_captures: HashMap::new(),
+                secondary_pointer: None,
+                pointer_lock_target: None,
                 cursor_icon: CursorIcon::Default,
                 font_context: FontContext {
                     collection: Collection::new(CollectionOptions {
@@ -164,14 +504,56 @@ impl RenderRoot {
                 mutate_callbacks: Vec::new(),
                 is_ime_active: false,
                 scenes: HashMap::new(),
+                ime_areas: HashMap::new(),
+                tooltips: HashMap::new(),
+                snap_points: HashMap::new(),
+                positions_in_set: HashMap::new(),
                 needs_pointer_pass: false,
+                formatter: formatter.unwrap_or_else(system_formatter),
+                env: Arc::default(),
+                color_scheme: WindowTheme::default(),
                 trace: PassTracing::from_env(),
+                watchdog: PassWatchdog::from_env(),
+                paint_flashing: PaintFlashing::from_env(),
+                pixel_snap_scale: pixel_snapping.then_some(scale_factor),
+                text_positioning: TextPositioning::default(),
+                text_snap_scale: None,
+                baseline_grid: None,
+                window_aspect_ratio: None,
+                size_to_content: false,
+                monitors: Vec::new(),
+                window_position: None,
+                window_shape: None,
+                record_actions: false,
+                action_history: VecDeque::new(),
+                next_action_sequence: 0,
+                platform_event_subscribers: Vec::new(),
+                low_latency_widgets: Vec::new(),
+                diagnostics_window: None,
+                frame_stats: FrameStats::default(),
+                timers: Vec::new(),
+                next_timer_token: 0,
+                mock_time: None,
+                gesture_config: GestureConfig::default(),
+                press: None,
+                drag: None,
+                announcement: None,
+                tooltip: None,
+                popups: Vec::new(),
+                dismissed_popups: Vec::new(),
+                new_popups: Vec::new(),
+                closed_popups: Vec::new(),
+                root_id,
             },
             widget_arena: WidgetArena {
                 widgets: TreeArena::new(),
                 widget_states: TreeArena::new(),
             },
             rebuild_access_tree: true,
+            event_history: VecDeque::with_capacity(EVENT_HISTORY_LEN),
+            last_frame_stats: FrameStats::default(),
+            renderer_info: None,
+            app_env: Env::new(),
         };
 
         if let Some(test_font_data) = test_font {
@@ -199,18 +581,62 @@ impl RenderRoot {
             .item
     }
 
+    /// The ids of the roots of the widget tree: the root widget, then the root widgets
+    /// of the popups, from bottom to top.
+    ///
+    /// The popups aren't children of the root widget, so that they can be laid out,
+    /// painted and hit above it. Passes visit them after the root widget, and their flags
+    /// aren't merged up into the root widget's.
+    pub(crate) fn root_ids(&self) -> Vec<WidgetId> {
+        std::iter::once(self.root.id())
+            .chain(self.popup_roots.iter().map(|popup| popup.id()))
+            .collect()
+    }
+
+    /// Whether the state of the root widget or of a popup contains any of `flags`.
+    pub(crate) fn roots_have(&self, flags: WidgetFlags) -> bool {
+        self.root_ids().into_iter().any(|id| {
+            let state = self.widget_arena.widget_states.find(id);
+            state.is_some_and(|state| state.item.flags.intersects(flags))
+        })
+    }
+
+    /// Whether the root widget or a popup needs the rewrite passes to run.
+    fn roots_need_rewrite_passes(&self) -> bool {
+        self.root_ids().into_iter().any(|id| {
+            let state = self.widget_arena.widget_states.find(id);
+            state.is_some_and(|state| state.item.needs_rewrite_passes())
+        })
+    }
+
+    fn record_event(&mut self, event: &impl Debug) {
+        if self.event_history.len() == EVENT_HISTORY_LEN {
+            self.event_history.pop_front();
+        }
+        self.event_history.push_back(format!("{event:?}"));
+    }
+
     // --- MARK: WINDOW_EVENT ---
     pub fn handle_window_event(&mut self, event: WindowEvent) -> Handled {
+        // Animation frames would quickly push every other event out of the history.
+        if !matches!(event, WindowEvent::AnimFrame) {
+            self.record_event(&event);
+        }
         match event {
             WindowEvent::Rescale(scale_factor) => {
                 self.scale_factor = scale_factor;
+                if self.global_state.pixel_snap_scale.is_some() {
+                    self.global_state.pixel_snap_scale = Some(scale_factor);
+                    self.request_layout_all();
+                }
                 self.request_render_all();
                 Handled::Yes
             }
             WindowEvent::Resize(size) => {
                 self.size = size;
-                self.root_state().request_layout = true;
-                self.root_state().needs_layout = true;
+                self.root_state()
+                    .flags
+                    .insert(WidgetFlags::REQUEST_LAYOUT | WidgetFlags::NEEDS_LAYOUT);
                 self.run_rewrite_passes();
                 Handled::Yes
             }
@@ -228,11 +654,19 @@ impl RenderRoot {
 
                 // If this animation will continue, store the time.
                 // If a new animation starts, then it will have zero reported elapsed time.
-                let animation_continues = self.root_state().needs_anim;
+                let animation_continues = self.roots_have(WidgetFlags::NEEDS_ANIM);
                 self.last_anim = animation_continues.then_some(now);
 
                 Handled::Yes
             }
+            WindowEvent::MonitorsChanged(monitors) => {
+                self.global_state.monitors = monitors;
+                Handled::Yes
+            }
+            WindowEvent::Moved(position) => {
+                self.global_state.window_position = Some(position);
+                Handled::Yes
+            }
             WindowEvent::RebuildAccessTree => {
                 self.rebuild_access_tree = true;
                 self.global_state
@@ -243,17 +677,111 @@ impl RenderRoot {
     }
 
     // --- MARK: PUB FUNCTIONS ---
-    pub fn handle_pointer_event(&mut self, event: PointerEvent) -> Handled {
+    pub fn handle_pointer_event(&mut self, mut event: PointerEvent) -> Handled {
         let _span = info_span!("pointer_event");
+        if let PointerEvent::MouseWheel(delta, _, state) = &mut event {
+            // Some platforms already turn Shift+wheel into a horizontal scroll.
+            if state.mods.state().shift_key() && delta.x == 0. {
+                *delta = LogicalPosition::new(delta.y, 0.);
+            }
+        }
+        track_press(self, &mut event);
+        self.record_event(&event);
         let handled = run_on_pointer_event_pass(self, &event);
         run_update_pointer_pass(self);
+        self.update_tooltip(&event);
         self.run_rewrite_passes();
 
         handled
     }
 
+    /// Send the pointer events of a finger on a touchscreen, with the given platform `id`.
+    ///
+    /// The first finger touching the window becomes the primary pointer, which hovers
+    /// widgets like the mouse does. The other fingers are routed independently,
+    /// see [`PointerState::is_primary`].
+    ///
+    /// While exactly two fingers touch the window, their motion is also sent as
+    /// [`Pinch`](PointerEvent::Pinch), [`Rotate`](PointerEvent::Rotate) and
+    /// [`Pan`](PointerEvent::Pan) gestures.
+    pub fn handle_touch_event(
+        &mut self,
+        id: u64,
+        phase: TouchPhase,
+        mut state: PointerState,
+    ) -> Handled {
+        if phase == TouchPhase::Started && self.primary_touch.is_none() {
+            self.primary_touch = Some(id);
+        }
+        state.pointer_id = PointerId::Touch(id);
+        state.is_primary = self.primary_touch == Some(id);
+        if state.is_primary && matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+            self.primary_touch = None;
+        }
+        let position = Point::new(state.position.x, state.position.y);
+        match phase {
+            TouchPhase::Started => {
+                self.touches.push((id, position));
+                if state.is_primary {
+                    // The finger hovers the widget it touches before pressing it.
+                    self.handle_pointer_event(PointerEvent::PointerMove(state.clone()));
+                }
+                self.handle_pointer_event(PointerEvent::PointerDown(PointerButton::Primary, state))
+            }
+            TouchPhase::Moved => {
+                let handled = self.handle_pointer_event(PointerEvent::PointerMove(state.clone()));
+                self.move_touch(id, position, state);
+                handled
+            }
+            TouchPhase::Ended => {
+                self.touches.retain(|(touch, _)| *touch != id);
+                self.handle_pointer_event(PointerEvent::PointerUp(PointerButton::Primary, state))
+            }
+            TouchPhase::Cancelled => {
+                self.touches.retain(|(touch, _)| *touch != id);
+                self.handle_pointer_event(PointerEvent::PointerLeave(state))
+            }
+        }
+    }
+
+    /// Update the position of a finger, and send the gestures of the two fingers
+    /// touching the window, if there are two.
+    fn move_touch(&mut self, id: u64, position: Point, mut state: PointerState) {
+        let Some(index) = self.touches.iter().position(|(touch, _)| *touch == id) else {
+            return;
+        };
+        let old_touches = self.touches.clone();
+        self.touches[index].1 = position;
+        let ([(_, a0), (_, b0)], [(_, a1), (_, b1)]) = (&old_touches[..], &self.touches[..]) else {
+            return;
+        };
+        let (old_span, new_span) = (*b0 - *a0, *b1 - *a1);
+        let (old_center, new_center) = (a0.midpoint(*b0), a1.midpoint(*b1));
+
+        state.position = LogicalPosition::new(new_center.x, new_center.y);
+        state.physical_position = state.position.to_physical(self.scale_factor);
+        state.is_primary = false;
+        if old_span.hypot() > 0. && new_span.hypot() > 0. {
+            let scale_delta = new_span.hypot() / old_span.hypot() - 1.;
+            if scale_delta != 0. {
+                self.handle_pointer_event(PointerEvent::Pinch(scale_delta, state.clone()));
+            }
+            let angle = new_span.atan2() - old_span.atan2();
+            // The angle between the fingers wraps around at half a turn.
+            let angle_delta = (angle + PI).rem_euclid(2. * PI) - PI;
+            if angle_delta != 0. {
+                self.handle_pointer_event(PointerEvent::Rotate(angle_delta, state.clone()));
+            }
+        }
+        let pan = new_center - old_center;
+        if pan != Vec2::ZERO {
+            self.handle_pointer_event(PointerEvent::Pan(pan, state));
+        }
+    }
+
     pub fn handle_text_event(&mut self, event: TextEvent) -> Handled {
         let _span = info_span!("text_event");
+        self.record_event(&event);
         let handled = run_on_text_event_pass(self, &event);
         run_update_focus_pass(self);
         self.run_rewrite_passes();
@@ -261,6 +789,18 @@ impl RenderRoot {
         handled
     }
 
+    /// Send `event` to the widgets which asked for platform events.
+    ///
+    /// Drivers can send their own events with [`PlatformEvent::Custom`].
+    pub fn handle_platform_event(&mut self, event: PlatformEvent) -> Handled {
+        let _span = info_span!("platform_event");
+        self.record_event(&event);
+        let handled = run_on_platform_event_pass(self, &event);
+        self.run_rewrite_passes();
+
+        handled
+    }
+
     pub fn handle_access_event(&mut self, event: ActionRequest) {
         let _span = info_span!("access_event");
         let Ok(id) = event.target.0.try_into() else {
@@ -271,11 +811,244 @@ impl RenderRoot {
             action: event.action,
             data: event.data,
         };
+        self.record_event(&event);
 
         run_on_access_event_pass(self, &event, WidgetId(id));
         self.run_rewrite_passes();
     }
 
+    /// Set the formatter used by widgets to display numbers and dates.
+    ///
+    /// All widgets receive [`Update::LocaleChanged`](crate::Update::LocaleChanged), so they
+    /// can rebuild their text, and the widgets which asked for platform events receive
+    /// [`PlatformEvent::LocaleChanged`].
+    ///
+    /// See also [`RenderRootOptions::formatter`].
+    pub fn set_formatter(&mut self, formatter: Arc<dyn LocaleFormatter>) {
+        self.global_state.formatter = formatter;
+        run_update_locale_pass(self);
+        self.handle_platform_event(PlatformEvent::LocaleChanged);
+    }
+
+    /// Set the env which the envs of all widgets inherit from.
+    ///
+    /// Widgets whose env changes receive [`Update::EnvChanged`](crate::Update::EnvChanged).
+    /// Values set on a subtree with [`EventCtx::set_env`](crate::EventCtx::set_env) take
+    /// precedence over the root env, and values of this env take precedence over the ones
+    /// of the color scheme.
+    pub fn set_env(&mut self, env: Env) {
+        self.app_env = env;
+        self.update_root_env();
+        self.run_rewrite_passes();
+    }
+
+    /// The env set with [`set_env`](Self::set_env).
+    pub fn env(&self) -> &Env {
+        &self.app_env
+    }
+
+    /// Combine the env set with [`set_env`](Self::set_env) with the theme values of the
+    /// color scheme, and mark the envs of all widgets for update.
+    fn update_root_env(&mut self) {
+        let env = match self.global_state.color_scheme {
+            WindowTheme::Dark => self.app_env.clone(),
+            WindowTheme::Light => self.app_env.inherit_from(&theme::light_env()),
+        };
+        self.global_state.env = Arc::new(env);
+        for id in self.root_ids() {
+            (self.widget_arena.get_state_mut(id).item.flags).insert(WidgetFlags::NEEDS_UPDATE_ENV);
+        }
+    }
+
+    /// Set the color scheme of the system, e.g. when the user switches to dark mode.
+    ///
+    /// If it changed, all widgets receive [`Update::ThemeChanged`](crate::Update::ThemeChanged),
+    /// and the widgets which asked for platform events receive
+    /// [`PlatformEvent::ThemeChanged`].
+    ///
+    /// The default theme follows the scheme: in the light scheme, the root env takes the
+    /// values of [`theme::light_env`] for the keys which [`set_env`](Self::set_env) doesn't
+    /// set, so widgets whose env changes also receive
+    /// [`Update::EnvChanged`](crate::Update::EnvChanged).
+    pub fn set_color_scheme(&mut self, theme: WindowTheme) {
+        if self.global_state.color_scheme == theme {
+            return;
+        }
+        self.global_state.color_scheme = theme;
+        // The envs are updated first, so that widgets see the new theme values when they
+        // handle `ThemeChanged`.
+        self.update_root_env();
+        run_update_env_pass(self);
+        run_update_theme_pass(self);
+        self.handle_platform_event(PlatformEvent::ThemeChanged(theme));
+    }
+
+    /// The color scheme set with [`set_color_scheme`](Self::set_color_scheme).
+    pub fn color_scheme(&self) -> WindowTheme {
+        self.global_state.color_scheme
+    }
+
+    /// Set the thresholds of the click counting, long presses and drag starts of the
+    /// primary pointer, e.g. from the settings of the platform.
+    pub fn set_gesture_config(&mut self, config: GestureConfig) {
+        self.global_state.gesture_config = config;
+    }
+
+    /// The thresholds set with [`set_gesture_config`](Self::set_gesture_config).
+    pub fn gesture_config(&self) -> GestureConfig {
+        self.global_state.gesture_config
+    }
+
+    /// Set the time budget of each pass, or `None` to disable the pass watchdog.
+    ///
+    /// When a pass exceeds its budget, e.g. because a widget is stuck in an infinite loop,
+    /// the path of the widget being visited and the timings of the passes of the current
+    /// frame are logged. In debug builds, the rest of the pass is skipped, so that the app
+    /// stays responsive enough to be debugged.
+    ///
+    /// The watchdog is disabled by default, unless the `MASONRY_PASS_WATCHDOG_MS`
+    /// environment variable is set to a budget in milliseconds.
+    pub fn set_pass_watchdog(&mut self, budget: Option<Duration>) {
+        self.global_state.watchdog.set_budget(budget);
+    }
+
+    /// Set whether the regions repainted in each frame are briefly highlighted.
+    ///
+    /// This is a debugging aid, similar to the paint flashing of web browsers,
+    /// to check that widgets only repaint when they need to.
+    /// It can also be enabled with the `MASONRY_PAINT_FLASHING` environment variable.
+    pub fn set_paint_flashing(&mut self, enabled: bool) {
+        self.global_state.paint_flashing.set_enabled(enabled);
+        self.global_state
+            .emit_signal(RenderRootSignal::RequestRedraw);
+    }
+
+    /// Whether the regions repainted in each frame are highlighted.
+    ///
+    /// See [`RenderRoot::set_paint_flashing`].
+    pub fn paint_flashing(&self) -> bool {
+        self.global_state.paint_flashing.is_enabled()
+    }
+
+    /// Set whether the actions submitted by widgets are recorded in the
+    /// [action history](Self::action_history), for bug reports.
+    ///
+    /// This is off by default. Only the names of the actions are recorded, not the text
+    /// or other data they hold.
+    pub fn set_record_actions(&mut self, enabled: bool) {
+        self.global_state.record_actions = enabled;
+        if !enabled {
+            self.global_state.action_history.clear();
+        }
+    }
+
+    /// Set whether the origins and sizes of widgets are rounded to physical pixels.
+    ///
+    /// With pixel snapping, the edges of widgets fall on physical pixels, so that
+    /// 1px borders and text aren't blurry on displays with a fractional scale factor.
+    /// Sizes are rounded after each widget's layout, and origins are rounded in window
+    /// coordinates during the compose pass.
+    pub fn set_pixel_snapping(&mut self, enabled: bool) {
+        let scale = enabled.then_some(self.scale_factor);
+        if self.global_state.pixel_snap_scale != scale {
+            self.global_state.pixel_snap_scale = scale;
+            self.request_layout_all();
+        }
+    }
+
+    /// Set the rhythm unit of the baseline grid, or disable it with `None`.
+    ///
+    /// With a baseline grid, text line heights and the vertical paddings of widgets are
+    /// rounded up to multiples of `unit` during layout, so that text and controls stay
+    /// vertically aligned across complex screens. Since line heights are whole numbers of
+    /// pixels, `unit` should be too. [`theme::BASELINE_GRID_UNIT`] is a good default unit.
+    /// The grid is disabled by default, and units which aren't positive disable it too.
+    ///
+    /// See [`LayoutCtx::snap_to_baseline_grid`] for how widgets follow the grid.
+    ///
+    /// [`theme::BASELINE_GRID_UNIT`]: crate::theme::BASELINE_GRID_UNIT
+    /// [`LayoutCtx::snap_to_baseline_grid`]: crate::LayoutCtx::snap_to_baseline_grid
+    pub fn set_baseline_grid(&mut self, unit: Option<f64>) {
+        let unit = unit.filter(|&unit| {
+            let valid = unit.is_finite() && unit > 0.;
+            if !valid {
+                warn!("Ignoring invalid baseline grid unit {unit}, the grid is disabled");
+            }
+            valid
+        });
+        if self.global_state.baseline_grid != unit {
+            self.global_state.baseline_grid = unit;
+            self.request_layout_all();
+        }
+    }
+
+    /// Resize the window to the preferred size of the root widget.
+    ///
+    /// The root widget is laid out once with unbounded constraints, capped by the size of the
+    /// primary monitor, and the window is resized to the result with
+    /// [`RenderRootSignal::SetSize`]. The user can resize the window freely afterwards.
+    ///
+    /// This is useful for dialogs and utility windows. Widgets can do the same with
+    /// [`EventCtx::request_size_to_content`](crate::EventCtx::request_size_to_content).
+    /// This has no effect if the size of the window is already determined by its content
+    /// (see [`WindowSizePolicy`]).
+    pub fn size_to_content(&mut self) {
+        self.global_state.size_to_content = true;
+        self.root_state()
+            .flags
+            .insert(WidgetFlags::REQUEST_LAYOUT | WidgetFlags::NEEDS_LAYOUT);
+        self.run_rewrite_passes();
+    }
+
+    /// Set whether text is positioned at subpixel offsets, or snapped to physical pixels.
+    ///
+    /// By default, text is positioned at subpixel offsets while an animation is running,
+    /// so that it moves smoothly, and is snapped once the animation ends, so that it's crisp.
+    pub fn set_text_positioning(&mut self, positioning: TextPositioning) {
+        if self.global_state.text_positioning != positioning {
+            self.global_state.text_positioning = positioning;
+            self.global_state
+                .emit_signal(RenderRootSignal::RequestRedraw);
+        }
+    }
+
+    pub fn formatter(&self) -> &dyn LocaleFormatter {
+        &*self.global_state.formatter
+    }
+
+    /// Measure the size `text` would have if displayed with `style`, without creating a widget.
+    ///
+    /// Properties which `style` leaves unset use the theme values of the root [`Env`].
+    /// See [`LayoutCtx::measure_text`](crate::LayoutCtx::measure_text) for use in widgets.
+    pub fn measure_text(
+        &mut self,
+        text: &str,
+        style: &TextStyle,
+        max_advance: Option<f32>,
+    ) -> Size {
+        measure_text(
+            &mut self.global_state.font_context,
+            &mut self.global_state.text_layout_context,
+            text,
+            style,
+            &self.global_state.env,
+            max_advance,
+        )
+    }
+
+    /// Resolve the line height, ascent and descent of text displayed with `style`.
+    ///
+    /// Properties which `style` leaves unset use the theme values of the root [`Env`].
+    /// Embedders can use this to align native overlays with masonry text.
+    pub fn text_metrics(&mut self, style: &TextStyle) -> TextMetrics {
+        text_metrics(
+            &mut self.global_state.font_context,
+            &mut self.global_state.text_layout_context,
+            style,
+            &self.global_state.env,
+        )
+    }
+
     /// Registers all fonts that exist in the given data.
     ///
     /// Returns a list of pairs each containing the family identifier and fonts
@@ -291,22 +1064,244 @@ impl RenderRoot {
     }
 
     pub fn redraw(&mut self) -> (Scene, TreeUpdate) {
-        if self.root_state().needs_layout {
+        if self.roots_have(WidgetFlags::NEEDS_LAYOUT) {
             // TODO - Rewrite more clearly after run_rewrite_passes is rewritten
             self.run_rewrite_passes();
         }
-        if self.root_state().needs_layout {
+        if self.roots_have(WidgetFlags::NEEDS_LAYOUT) && !self.global_state.watchdog.frame_tripped()
+        {
             warn!("Widget requested layout during layout pass");
             self.global_state
                 .emit_signal(RenderRootSignal::RequestRedraw);
         }
 
+        self.update_text_snapping();
+
         // TODO - Handle invalidation regions
         // TODO - Improve caching of scenes.
-        (
-            run_paint_pass(self),
-            run_accessibility_pass(self, self.scale_factor),
-        )
+        let mut scene = self.run_pass("paint", run_paint_pass);
+        if !self.global_state.mutate_callbacks.is_empty() {
+            // A panic was caught by an error boundary while painting, so we replace
+            // the faulty subtree and paint again.
+            self.run_rewrite_passes();
+            scene = self.run_pass("paint", run_paint_pass);
+        }
+        let scale_factor = self.scale_factor;
+        let tree_update = self.run_pass("accessibility", |root| {
+            run_accessibility_pass(root, scale_factor)
+        });
+        self.last_frame_stats = std::mem::take(&mut self.global_state.frame_stats);
+        (scene, tree_update)
+    }
+
+    /// Rebuild the accessibility tree, without painting.
+    ///
+    /// This is used to answer [`RenderRootSignal::RequestAccessibilityUpdate`].
+    /// [`redraw`](Self::redraw) also updates the accessibility tree.
+    pub fn update_accessibility(&mut self) -> TreeUpdate {
+        let scale_factor = self.scale_factor;
+        run_accessibility_pass(self, scale_factor)
+    }
+
+    /// The earliest deadline of the timers requested by widgets, if any.
+    ///
+    /// The driver should call [`run_timers`](Self::run_timers) once it's reached.
+    ///
+    /// This includes the delay before the tooltip of the hovered widget is shown, and
+    /// before a press of the primary button becomes a [`LongPress`](PointerEvent::LongPress).
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        let tooltip = (self.global_state.tooltip.as_ref())
+            .filter(|tooltip| !tooltip.shown)
+            .map(|tooltip| tooltip.deadline);
+        (self.global_state.timers.iter())
+            .map(|timer| timer.deadline)
+            .chain(tooltip)
+            .chain(self.global_state.long_press_deadline())
+            .min()
+    }
+
+    /// Send [`Update::Timer`](crate::Update::Timer) to the widgets whose timers are due,
+    /// in the order of their deadlines, send [`PointerEvent::LongPress`] if the primary
+    /// button was pressed long enough, and show the tooltip of the hovered widget if
+    /// the pointer rested on it long enough.
+    pub fn run_timers(&mut self) {
+        let _span = info_span!("timers");
+        run_update_timers_pass(self);
+        run_long_press_pass(self);
+        let now = self.global_state.now();
+        if let Some(tooltip) = &mut self.global_state.tooltip {
+            if !tooltip.shown && tooltip.deadline <= now {
+                tooltip.shown = true;
+                self.global_state
+                    .emit_signal(RenderRootSignal::RequestRedraw);
+            }
+        }
+        self.run_rewrite_passes();
+    }
+
+    /// The text of the tooltip shown, if any.
+    ///
+    /// See [`EventCtx::set_tooltip`](crate::EventCtx::set_tooltip).
+    pub fn tooltip(&self) -> Option<&str> {
+        let tooltip = self.global_state.tooltip.as_ref()?;
+        if !tooltip.shown {
+            return None;
+        }
+        self.global_state
+            .tooltips
+            .get(&tooltip.widget_id)
+            .map(|text| &**text)
+    }
+
+    /// Hide the tooltip when the pointer moves, and wait for the pointer to rest on the
+    /// innermost hovered widget with a tooltip.
+    fn update_tooltip(&mut self, event: &PointerEvent) {
+        if !matches!(
+            event,
+            PointerEvent::PointerMove(..)
+                | PointerEvent::PointerLeave(..)
+                | PointerEvent::PointerDown(..)
+                | PointerEvent::MouseWheel(..)
+        ) {
+            return;
+        }
+        if let Some(tooltip) = self.global_state.tooltip.take() {
+            if tooltip.shown {
+                self.global_state
+                    .emit_signal(RenderRootSignal::RequestRedraw);
+            }
+        }
+        // The tooltip only comes back once the pointer moves again.
+        if !matches!(event, PointerEvent::PointerMove(..)) {
+            return;
+        }
+        let tooltips = &self.global_state.tooltips;
+        // The hovered path starts with the innermost widget.
+        let widget_id =
+            (self.global_state.hovered_path.iter().copied()).find(|id| tooltips.contains_key(id));
+        let deadline = self.global_state.now() + Duration::from_millis(theme::TOOLTIP_DELAY);
+        self.global_state.tooltip = widget_id.map(|widget_id| TooltipState {
+            widget_id,
+            deadline,
+            shown: false,
+        });
+    }
+
+    /// Statistics about the last frame painted with [`redraw`](Self::redraw).
+    pub fn frame_stats(&self) -> &FrameStats {
+        &self.last_frame_stats
+    }
+
+    /// Record how long it took to render and present the last frame, and the memory
+    /// used by the textures it was rendered to, for [`frame_stats`](Self::frame_stats).
+    pub fn record_present(&mut self, duration: Duration, texture_bytes: u64) {
+        self.last_frame_stats.present_duration = Some(duration);
+        self.last_frame_stats.texture_bytes = texture_bytes;
+    }
+
+    /// Set the description of the graphics adapter and backend frames are rendered with,
+    /// for [`diagnostics_report`](Self::diagnostics_report).
+    pub fn set_renderer_info(&mut self, info: Option<String>) {
+        self.renderer_info = info;
+    }
+
+    /// A text report about the environment the app runs in, to show in an about screen
+    /// or to attach to support requests.
+    ///
+    /// The report holds the renderer set with [`set_renderer_info`](Self::set_renderer_info),
+    /// the scale factors of the window and the monitors, the enabled features of Masonry,
+    /// the [statistics of the last frame](Self::frame_stats) and the available font families.
+    /// Call it again after each frame for live statistics.
+    ///
+    /// To show the report of the main window in a window of its own, kept up to date,
+    /// use [`DriverCtx::open_diagnostics_window`](crate::app_driver::DriverCtx::open_diagnostics_window).
+    pub fn diagnostics_report(&mut self) -> String {
+        use std::fmt::Write as _;
+
+        let mut report = String::from("# Renderer\n\n");
+        let renderer = self.renderer_info.as_deref().unwrap_or("unknown");
+        let _ = writeln!(report, "{renderer}");
+
+        report.push_str("\n# Scale factors\n\n");
+        let _ = writeln!(report, "window: {}", self.scale_factor);
+        for (index, monitor) in self.global_state.monitors.iter().enumerate() {
+            let name = monitor.name.as_deref().unwrap_or("unnamed");
+            let primary = if monitor.is_primary { ", primary" } else { "" };
+            let _ = writeln!(
+                report,
+                "monitor {index} ({name}{primary}): {}",
+                monitor.scale_factor
+            );
+        }
+
+        report.push_str("\n# Features\n\n");
+        let features = [
+            ("standard-icons", cfg!(feature = "standard-icons")),
+            ("icu", cfg!(feature = "icu")),
+            ("global-hotkeys", cfg!(feature = "global-hotkeys")),
+            ("clipboard", cfg!(feature = "clipboard")),
+            ("tracy", cfg!(feature = "tracy")),
+        ];
+        for (feature, enabled) in features {
+            let _ = writeln!(report, "{feature}: {}", if enabled { "on" } else { "off" });
+        }
+
+        report.push_str("\n# Last frame\n\n");
+        let stats = &self.last_frame_stats;
+        for (pass, duration) in &stats.pass_durations {
+            let _ = writeln!(report, "{pass}: {duration:?}");
+        }
+        if let Some(present) = stats.present_duration {
+            let _ = writeln!(report, "present: {present:?}");
+        }
+        let _ = writeln!(report, "widgets laid out: {}", stats.widgets_laid_out);
+        let _ = writeln!(report, "widgets painted: {}", stats.widgets_painted);
+        let _ = writeln!(report, "scene fragments: {}", stats.scene_fragments);
+        let _ = writeln!(report, "texture memory: {} bytes", stats.texture_bytes);
+
+        report.push_str("\n# Fonts\n\n");
+        let mut families: Vec<_> = self
+            .global_state
+            .font_context
+            .collection
+            .family_names()
+            .map(str::to_string)
+            .collect();
+        families.sort();
+        for family in families {
+            let _ = writeln!(report, "{family}");
+        }
+        report
+    }
+
+    /// Resolve whether glyphs should be snapped in this frame, and repaint
+    /// everything if that changed, e.g. because an animation just ended.
+    fn update_text_snapping(&mut self) {
+        let snapped = match self.global_state.text_positioning {
+            TextPositioning::Subpixel => false,
+            TextPositioning::Snapped => true,
+            TextPositioning::Auto => !self.roots_have(WidgetFlags::NEEDS_ANIM),
+        };
+        let scale = snapped.then_some(self.scale_factor);
+        if self.global_state.text_snap_scale != scale {
+            self.global_state.text_snap_scale = scale;
+            self.insert_flags_in_all(WidgetFlags::NEEDS_PAINT | WidgetFlags::REQUEST_PAINT);
+        }
+    }
+
+    /// Capture the scene of the widget with the given id and its descendants.
+    ///
+    /// Widgets which haven't changed since the last paint reuse their cached scene.
+    /// Returns `None` if the widget doesn't exist or is stashed.
+    pub fn capture_widget_scene(&mut self, id: WidgetId) -> Option<WidgetScene> {
+        if self.roots_have(WidgetFlags::NEEDS_LAYOUT) {
+            self.run_rewrite_passes();
+        }
+        run_paint_subtree_pass(self, id)
+    }
+
+    pub(crate) fn has_pending_signals(&self) -> bool {
+        !self.global_state.signal_queue.is_empty()
     }
 
     pub fn pop_signal(&mut self) -> Option<RenderRootSignal> {
@@ -325,6 +1320,82 @@ impl RenderRoot {
         self.cursor_icon
     }
 
+    /// Whether `position` is inside the shape of the window set with
+    /// [`set_window_shape`](crate::EventCtx::set_window_shape).
+    ///
+    /// Runners should let pointer events outside of the shape through to whatever is
+    /// behind the window, if the platform allows it.
+    pub fn is_in_window_shape(&self, position: LogicalPosition<f64>) -> bool {
+        self.global_state
+            .is_in_window_shape(Point::new(position.x, position.y))
+    }
+
+    /// The last 100 events handled by this `RenderRoot`, oldest first.
+    ///
+    /// Animation frames aren't included.
+    pub fn event_history(&self) -> impl Iterator<Item = &str> {
+        self.event_history.iter().map(String::as_str)
+    }
+
+    /// The names of the last 100 actions submitted by widgets, oldest first, with their
+    /// sequence number and the id of their widget.
+    ///
+    /// This is empty unless [`set_record_actions`](Self::set_record_actions) is enabled.
+    pub fn action_history(&self) -> impl Iterator<Item = &str> {
+        self.global_state.action_history.iter().map(String::as_str)
+    }
+
+    /// Write the widget tree, the [event history](Self::event_history) and the
+    /// [action history](Self::action_history) to the directory `dir`, creating it if needed.
+    ///
+    /// The files are named `widget_tree.txt`, `events.txt` and `actions.txt`.
+    /// Together with a screenshot of the current frame, they make a bug report bundle.
+    /// See [`TestHarness::write_bug_report`](crate::testing::TestHarness::write_bug_report)
+    /// and [`MasonryState`](crate::event_loop_runner::MasonryState) for where frames are added.
+    pub fn write_bug_report(&self, dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(
+            dir.join("widget_tree.txt"),
+            format!("{:#?}\n", self.get_root_widget()),
+        )?;
+        std::fs::write(dir.join("events.txt"), lines(self.event_history()))?;
+        std::fs::write(dir.join("actions.txt"), lines(self.action_history()))
+    }
+
+    /// A text report about the widget with the given id and its descendants, to paste
+    /// into bug reports.
+    ///
+    /// The report holds the subtree of the widget, then the [event history](Self::event_history)
+    /// and the [action history](Self::action_history).
+    /// Returns `None` if the widget doesn't exist.
+    pub fn widget_report(&self, id: WidgetId) -> Option<String> {
+        let widget = self.get_widget(id)?;
+        Some(format!(
+            "# Widget {id}\n\n{widget:#?}\n\n# Recent events\n\n{}\n# Recent actions\n\n{}",
+            lines(self.event_history()),
+            lines(self.action_history()),
+        ))
+    }
+
+    /// Write a bug report bundle about the widget with the given id to the directory `dir`,
+    /// creating it if needed, and return its [text report](Self::widget_report).
+    ///
+    /// The bundle holds the text report in `report.txt`, and a screenshot of the widget and
+    /// its descendants, at the scale factor of the window, in `widget.png`.
+    /// Runners write one for the focused widget when Ctrl+Shift+F11 is pressed, if their
+    /// debugging hotkeys are enabled.
+    pub fn write_widget_report(&mut self, id: WidgetId, dir: &Path) -> std::io::Result<String> {
+        let not_found = || std::io::Error::new(ErrorKind::NotFound, format!("no widget {id}"));
+        let report = self.widget_report(id).ok_or_else(not_found)?;
+        let captured = self.capture_widget_scene(id).ok_or_else(not_found)?;
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join("report.txt"), &report)?;
+        render_widget_scene(&captured, self.scale_factor, Color::TRANSPARENT)?
+            .save(dir.join("widget.png"))
+            .map_err(std::io::Error::other)?;
+        Ok(report)
+    }
+
     // --- MARK: ACCESS WIDGETS---
     /// Get a [`WidgetRef`] to the root widget.
     pub fn get_root_widget(&self) -> WidgetRef<dyn Widget> {
@@ -423,6 +1494,20 @@ impl RenderRoot {
         res
     }
 
+    /// Apply the callbacks queued with [`mutate_later`](crate::LayoutCtx::mutate_later)
+    /// right away, along with the passes they invalidate, such as layout.
+    ///
+    /// This is meant for drivers which need to measure widgets after queuing mutations,
+    /// instead of waiting for the next event or frame.
+    /// Returns `false` if there were no callbacks to apply.
+    pub fn run_mutations_now(&mut self) -> bool {
+        if self.global_state.mutate_callbacks.is_empty() {
+            return false;
+        }
+        self.run_rewrite_passes();
+        true
+    }
+
     pub(crate) fn get_kurbo_size(&self) -> kurbo::Size {
         let size = self.size.to_logical(self.scale_factor);
         kurbo::Size::new(size.width, size.height)
@@ -438,61 +1523,159 @@ impl RenderRoot {
     pub(crate) fn run_rewrite_passes(&mut self) {
         const REWRITE_PASSES_MAX: usize = 4;
 
+        self.global_state.watchdog.start_frame();
         for _ in 0..REWRITE_PASSES_MAX {
             // Note: this code doesn't do any short-circuiting, because each pass is
             // expected to have its own early exits.
             // Calling a run_xxx_pass (or root_xxx) should always be very fast if
             // the pass doesn't need to do anything.
 
-            run_mutate_pass(self);
-            run_update_widget_tree_pass(self);
-            run_update_disabled_pass(self);
-            run_update_stashed_pass(self);
-            run_update_focus_chain_pass(self);
-            run_update_focus_pass(self);
-            run_layout_pass(self);
-            run_update_scroll_pass(self);
-            run_compose_pass(self);
-            run_update_pointer_pass(self);
-
-            if !self.root_state().needs_rewrite_passes()
-                && !self.global_state.needs_rewrite_passes()
-            {
+            self.run_pass("mutate", run_mutate_pass);
+            self.run_pass("popup_closed", run_popup_closed_pass);
+            self.run_pass("update_widget_tree", run_update_widget_tree_pass);
+            self.run_pass("update_disabled", run_update_disabled_pass);
+            self.run_pass("update_text_style", run_update_text_style_pass);
+            self.run_pass("update_env", run_update_env_pass);
+            self.run_pass("update_stashed", run_update_stashed_pass);
+            self.run_pass("update_focus_chain", run_update_focus_chain_pass);
+            self.run_pass("update_focus", run_update_focus_pass);
+            self.run_pass("layout", run_layout_pass);
+            self.run_pass("update_scroll", run_update_scroll_pass);
+            self.run_pass("compose", run_compose_pass);
+            self.update_popup_anchors();
+            self.run_pass("update_pointer", run_update_pointer_pass);
+
+            if self.global_state.watchdog.frame_tripped() {
+                // The rest of the work is left for the next frame, to stay responsive.
+                self.global_state
+                    .emit_signal(RenderRootSignal::RequestRedraw);
+                break;
+            }
+            if !self.roots_need_rewrite_passes() && !self.global_state.needs_rewrite_passes() {
                 break;
             }
         }
 
-        if self.root_state().needs_rewrite_passes() || self.global_state.needs_rewrite_passes() {
+        if (self.roots_need_rewrite_passes() || self.global_state.needs_rewrite_passes())
+            && !self.global_state.watchdog.frame_tripped()
+        {
             warn!("All rewrite passes have run {REWRITE_PASSES_MAX} times, but invalidations are still set");
             // To avoid an infinite loop, we delay re-running the passes until the next frame.
             self.global_state
                 .emit_signal(RenderRootSignal::RequestRedraw);
         }
 
-        if self.root_state().needs_anim {
+        if self.roots_have(WidgetFlags::NEEDS_ANIM) {
             self.global_state
                 .emit_signal(RenderRootSignal::RequestAnimFrame);
         }
 
-        // We request a redraw if either the render tree or the accessibility
-        // tree needs to be rebuilt. Usually both happen at the same time.
+        // Widgets which were removed from the tree no longer need low latency.
+        if !self.global_state.low_latency_widgets.is_empty() {
+            let arena = &self.widget_arena;
+            self.global_state
+                .low_latency_widgets
+                .retain(|id| arena.has(*id));
+            if self.global_state.low_latency_widgets.is_empty() {
+                self.global_state
+                    .emit_signal(RenderRootSignal::SetLowLatency(false));
+            }
+        }
+
+        // The side tables of widget data only keep the widgets which are still in the tree.
+        // The removed children themselves are dropped in `remove_child`, but not their descendants.
+        let arena = &self.widget_arena;
+        self.global_state.tooltips.retain(|id, _| arena.has(*id));
+        self.global_state.snap_points.retain(|id, _| arena.has(*id));
+        self.global_state
+            .positions_in_set
+            .retain(|id, _| arena.has(*id));
+
+        // We request a redraw if the render tree needs to be rebuilt.
         // A redraw will trigger a rebuild of the accessibility tree.
+        // If only the accessibility tree needs to be rebuilt, e.g. because the hovered
+        // widget changed, we skip painting and presenting a frame entirely.
         // TODO - We assume that a relayout will trigger a repaint
-        if self.root_state().needs_paint || self.root_state().needs_accessibility {
+        if self.roots_have(WidgetFlags::NEEDS_PAINT) {
             self.global_state
                 .emit_signal(RenderRootSignal::RequestRedraw);
+        } else if self.roots_have(WidgetFlags::NEEDS_ACCESSIBILITY) {
+            self.global_state
+                .emit_signal(RenderRootSignal::RequestAccessibilityUpdate);
         }
     }
 
+    /// Run `pass`, keeping track of its duration for the pass watchdog and the frame stats.
+    fn run_pass<R>(&mut self, name: &'static str, pass: impl FnOnce(&mut Self) -> R) -> R {
+        let start = Instant::now();
+        let now = self.global_state.now();
+        self.global_state.watchdog.start_pass(name, now);
+        let result = pass(self);
+        let now = self.global_state.now();
+        self.global_state.watchdog.end_pass(now);
+        self.global_state
+            .frame_stats
+            .record_pass(name, start.elapsed());
+        result
+    }
+
     pub(crate) fn request_render_all(&mut self) {
-        fn request_render_all_in(
+        self.insert_flags_in_all(
+            WidgetFlags::NEEDS_PAINT
+                | WidgetFlags::NEEDS_ACCESSIBILITY
+                | WidgetFlags::REQUEST_PAINT
+                | WidgetFlags::REQUEST_ACCESSIBILITY,
+        );
+        self.global_state
+            .emit_signal(RenderRootSignal::RequestRedraw);
+    }
+
+    /// Recompute the layout of all widgets, and their position in the window.
+    pub(crate) fn request_layout_all(&mut self) {
+        self.insert_flags_in_all(
+            WidgetFlags::NEEDS_LAYOUT
+                | WidgetFlags::REQUEST_LAYOUT
+                | WidgetFlags::TRANSLATION_CHANGED,
+        );
+        self.run_rewrite_passes();
+    }
+
+    /// Close the popups whose anchor was removed or stashed, and lay the popups out
+    /// again if their anchor moved during the compose pass.
+    fn update_popup_anchors(&mut self) {
+        let mut moved = Vec::new();
+        let mut closed = Vec::new();
+        for popup in &mut self.global_state.popups {
+            let anchor_rect = (self.widget_arena.widget_states.find(popup.anchor))
+                .filter(|state| !state.item.flags.contains(WidgetFlags::IS_STASHED))
+                .map(|state| state.item.window_layout_rect());
+            match anchor_rect {
+                None => closed.push(popup.id),
+                Some(rect) if rect != popup.anchor_rect => {
+                    popup.anchor_rect = rect;
+                    moved.push(popup.id);
+                }
+                Some(_) => {}
+            }
+        }
+        for id in closed {
+            self.global_state.close_popup(id);
+        }
+        for id in moved {
+            // Popups which haven't been added to the widget tree yet are laid out anyway.
+            if let Some(state) = self.widget_arena.widget_states.find_mut(id) {
+                (state.item.flags).insert(WidgetFlags::NEEDS_LAYOUT | WidgetFlags::REQUEST_LAYOUT);
+            }
+        }
+    }
+
+    fn insert_flags_in_all(&mut self, flags: WidgetFlags) {
+        fn insert_flags_in(
             mut widget: ArenaMut<'_, Box<dyn Widget>>,
             state: ArenaMut<'_, WidgetState>,
+            flags: WidgetFlags,
         ) {
-            state.item.needs_paint = true;
-            state.item.needs_accessibility = true;
-            state.item.request_paint = true;
-            state.item.request_accessibility = true;
+            state.item.flags.insert(flags);
 
             let id = state.item.id;
             recurse_on_children(
@@ -500,15 +1683,15 @@ impl RenderRoot {
                 widget.reborrow_mut(),
                 state.children,
                 |widget, mut state| {
-                    request_render_all_in(widget, state.reborrow_mut());
+                    insert_flags_in(widget, state.reborrow_mut(), flags);
                 },
             );
         }
 
-        let (root_widget, mut root_state) = self.widget_arena.get_pair_mut(self.root.id());
-        request_render_all_in(root_widget, root_state.reborrow_mut());
-        self.global_state
-            .emit_signal(RenderRootSignal::RequestRedraw);
+        for id in self.root_ids() {
+            let (root_widget, mut root_state) = self.widget_arena.get_pair_mut(id);
+            insert_flags_in(root_widget, root_state.reborrow_mut(), flags);
+        }
     }
 
     // Checks whether the given id points to a widget that is "interactive".
@@ -519,48 +1702,103 @@ impl RenderRoot {
             return false;
         };
 
-        !state.item.is_stashed && !state.item.is_disabled
+        !state.item.flags.contains(WidgetFlags::IS_STASHED)
+            && !state.item.flags.contains(WidgetFlags::IS_DISABLED)
     }
 
-    pub(crate) fn widget_from_focus_chain(&mut self, forward: bool) -> Option<WidgetId> {
-        let focused_widget = self.global_state.focused_widget;
-        let focused_idx = focused_widget.and_then(|focused_widget| {
-            self.focus_chain()
-                .iter()
-                // Find where the focused widget is in the focus chain
-                .position(|id| id == &focused_widget)
-        });
+    /// Whether the given widget is interactive and not transparent to the pointer.
+    ///
+    /// Only these widgets can keep pointer capture or the pointer lock. Pointer transparent
+    /// widgets can still have text focus.
+    pub(crate) fn can_hold_pointer(&self, id: WidgetId) -> bool {
+        self.is_still_interactive(id)
+            && !self
+                .widget_arena
+                .widget_states
+                .find(id)
+                .is_some_and(|state| {
+                    state
+                        .item
+                        .flags
+                        .contains(WidgetFlags::IS_POINTER_TRANSPARENT)
+                })
+    }
 
-        if let Some(idx) = focused_idx {
-            // Return the id that's next to it in the focus chain
-            let len = self.focus_chain().len();
-            let new_idx = if forward {
-                (idx + 1) % len
-            } else {
-                (idx + len - 1) % len
-            };
-            Some(self.focus_chain()[new_idx])
-        } else {
-            // If no widget is currently focused or the
-            // currently focused widget isn't in the focus chain,
-            // then we'll just return the first/last entry of the chain, if any.
-            if forward {
-                self.focus_chain().first().copied()
-            } else {
-                self.focus_chain().last().copied()
-            }
+    /// Show or hide the focus ring of the focused widget.
+    pub(crate) fn set_focus_visible(&mut self, visible: bool) {
+        if self.global_state.focus_visible == visible {
+            return;
         }
+        self.global_state.focus_visible = visible;
+        let Some(id) = self.global_state.focused_widget else {
+            return;
+        };
+        if !self.widget_arena.has(id) {
+            return;
+        }
+        // The ring is painted around the widget's scene, which doesn't need to be repainted.
+        self.widget_arena
+            .get_state_mut(id)
+            .item
+            .flags
+            .insert(WidgetFlags::NEEDS_PAINT);
+        let mut current_id = Some(id);
+        while let Some(widget_id) = current_id {
+            merge_state_up(&mut self.widget_arena, widget_id);
+            current_id = self.widget_arena.parent_of(widget_id);
+        }
+    }
+
+    /// The focusable widget nearest to the focused one in `direction`.
+    ///
+    /// Only the widgets in the focus chain of the closest [focus scope](crate::widget::FocusGroup::with_focus_scope)
+    /// of the focused widget are candidates, or those of the whole window if it has none.
+    pub(crate) fn widget_in_direction(&self, direction: FocusDirection) -> Option<WidgetId> {
+        let focused = self.global_state.focused_widget?;
+        let arena = &self.widget_arena;
+        let focused_rect = arena.widget_states.find(focused)?.item.window_layout_rect();
+        let chain = match arena.focus_scope_of(focused) {
+            Some(scope) => arena.widget_states.find(scope)?.item.focus_chain.clone(),
+            None => self.global_state.focus_chain.clone(),
+        };
+        chain
+            .into_iter()
+            .filter(|id| *id != focused)
+            .filter_map(|id| {
+                let rect = arena.widget_states.find(id)?.item.window_layout_rect();
+                Some((id, direction.distance(focused_rect, rect)?))
+            })
+            .min_by(|(_, a), (_, b)| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)))
+            .map(|(id, _)| id)
     }
 
-    // TODO - Store in RenderRootState
-    pub(crate) fn focus_chain(&mut self) -> &[WidgetId] {
-        &self.root_state().focus_chain
+    /// The widget under `pos`, in window coordinates, if `pos` is inside the window shape.
+    pub(crate) fn hit_test(&self, pos: Point) -> Option<WidgetId> {
+        if !self.global_state.is_in_window_shape(pos) {
+            return None;
+        }
+        // The popups are above the root widget, and the last one is the topmost.
+        self.root_ids()
+            .into_iter()
+            .rev()
+            .filter_map(|id| self.get_widget(id))
+            .find_map(|root| root.find_widget_at_pos(pos).map(|widget| widget.id()))
     }
 
     #[allow(dead_code)]
     pub(crate) fn needs_rewrite_passes(&mut self) -> bool {
-        self.root_state().needs_rewrite_passes() || self.global_state.focus_changed()
+        self.roots_need_rewrite_passes() || self.global_state.focus_changed()
+    }
+}
+
+/// Join `items` with a newline after each of them.
+fn lines<'a>(items: impl Iterator<Item = &'a str>) -> String {
+    let mut text = String::new();
+    for item in items {
+        text.push_str(item);
+        text.push('\n');
     }
+    text
 }
 
 impl RenderRootState {
@@ -569,6 +1807,133 @@ impl RenderRootState {
         self.signal_queue.push_back(signal);
     }
 
+    /// Ask the runner to open a secondary window, and return its id.
+    pub(crate) fn open_window(
+        &mut self,
+        attributes: WindowAttributes,
+        root_widget: Box<dyn Widget>,
+    ) -> AppWindowId {
+        let id = AppWindowId::next();
+        self.emit_signal(RenderRootSignal::OpenWindow(
+            id,
+            Box::new(attributes),
+            root_widget,
+        ));
+        id
+    }
+
+    /// The widget after the focused one in the focus chain, or before it if `forward`
+    /// is false, wrapping around.
+    ///
+    /// If no widget in the chain is focused, this is the first or last widget of the chain.
+    pub(crate) fn widget_from_focus_chain(&self, forward: bool) -> Option<WidgetId> {
+        let chain = &self.focus_chain;
+        let focused_idx =
+            (self.focused_widget).and_then(|focused| chain.iter().position(|id| *id == focused));
+        match (focused_idx, forward) {
+            (Some(idx), true) => Some(chain[(idx + 1) % chain.len()]),
+            (Some(idx), false) => Some(chain[(idx + chain.len() - 1) % chain.len()]),
+            (None, true) => chain.first().copied(),
+            (None, false) => chain.last().copied(),
+        }
+    }
+
+    /// Set the text of the live region, which assistive technologies announce.
+    ///
+    /// See [`EventCtx::announce`](crate::EventCtx::announce).
+    pub(crate) fn announce(&mut self, text: impl Into<String>) {
+        self.announcement = Some(text.into());
+        // The live region is a child of the root widget's node.
+        self.mutate_callbacks.push(MutateCallback {
+            id: self.root_id,
+            callback: Box::new(|mut root| {
+                root.ctx.request_accessibility_update();
+            }),
+        });
+    }
+
+    /// Close the popup with the given id because of the user, and let the widget which
+    /// showed it know with [`Update::PopupClosed`](crate::Update::PopupClosed).
+    pub(crate) fn dismiss_popup(&mut self, id: WidgetId) {
+        if let Some(popup) = self.popups.iter().find(|popup| popup.id == id) {
+            self.dismissed_popups.push(*popup);
+            self.close_popup(id);
+        }
+    }
+
+    /// Forget the popup with the given id, and remove it from the widget tree
+    /// in the next rewrite passes.
+    pub(crate) fn close_popup(&mut self, id: WidgetId) {
+        let Some(index) = self.popups.iter().position(|popup| popup.id == id) else {
+            return;
+        };
+        self.popups.remove(index);
+        self.closed_popups.push(id);
+    }
+
+    /// Emit an action with the next sequence number.
+    ///
+    /// See [`RenderRootSignal::Action`] for the order of actions.
+    pub(crate) fn submit_action(&mut self, action: Action, id: WidgetId) {
+        let sequence = self.next_action_sequence;
+        self.next_action_sequence += 1;
+        if self.record_actions {
+            if self.action_history.len() == EVENT_HISTORY_LEN {
+                self.action_history.pop_front();
+            }
+            // The data of actions may be private, e.g. the text of a password field.
+            let name = action.name();
+            self.action_history
+                .push_back(format!("#{sequence} {name} from {id}"));
+        }
+        self.emit_signal(RenderRootSignal::Action(action, id, sequence));
+    }
+
+    /// When the current press of the primary button becomes a long press, if it can.
+    pub(crate) fn long_press_deadline(&self) -> Option<Instant> {
+        let press = self.press.as_ref()?;
+        let waiting = press.pressed
+            && !press.dragging
+            && !press.long_pressed
+            && press.button == PointerButton::Primary;
+        waiting.then(|| press.time + self.gesture_config.long_press_duration)
+    }
+
+    /// The current time, which timers are scheduled against.
+    pub(crate) fn now(&self) -> Instant {
+        self.mock_time.unwrap_or_else(Instant::now)
+    }
+
+    pub(crate) fn add_timer(
+        &mut self,
+        widget_id: WidgetId,
+        delay: Duration,
+        interval: Option<Duration>,
+    ) -> TimerToken {
+        let token = TimerToken(self.next_timer_token);
+        self.next_timer_token += 1;
+        self.timers.push(Timer {
+            token,
+            widget_id,
+            deadline: self.now() + delay,
+            interval,
+        });
+        token
+    }
+
+    /// Release the pointer lock, if any, and return the widget which held it.
+    pub(crate) fn release_pointer_lock(&mut self) -> Option<WidgetId> {
+        let target = self.pointer_lock_target.take()?;
+        self.emit_signal(RenderRootSignal::SetPointerLock(false));
+        Some(target)
+    }
+
+    pub(crate) fn is_in_window_shape(&self, pos: Point) -> bool {
+        self.window_shape
+            .as_ref()
+            .map_or(true, |shape| shape.contains(pos))
+    }
+
     pub(crate) fn focus_changed(&self) -> bool {
         self.focused_widget != self.next_focused_widget
     }
@@ -578,7 +1943,25 @@ impl RenderRootState {
     }
 
     pub(crate) fn needs_rewrite_passes(&self) -> bool {
-        self.needs_pointer_pass || self.focused_widget != self.next_focused_widget
+        // Callbacks queued by the layout or compose passes must run before the next paint.
+        self.needs_pointer_pass
+            || self.focused_widget != self.next_focused_widget
+            || !self.mutate_callbacks.is_empty()
+            || !self.dismissed_popups.is_empty()
+            || !self.new_popups.is_empty()
+            || !self.closed_popups.is_empty()
+    }
+
+    /// Returns the area of the given widget being edited by an IME, in global coordinates.
+    ///
+    /// By default, returns the same as [`WidgetState::window_layout_rect`].
+    pub(crate) fn get_ime_area(&self, state: &WidgetState) -> Rect {
+        let area = self
+            .ime_areas
+            .get(&state.id)
+            .copied()
+            .unwrap_or_else(|| state.size.to_rect());
+        state.to_window_rect(area)
     }
 }
 
@@ -3,31 +3,39 @@
 
 //! Tools and infrastructure for testing widgets.
 
-use std::collections::VecDeque;
-use std::num::NonZeroUsize;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
+use accesskit::{Node, NodeId, TreeUpdate};
 use cursor_icon::CursorIcon;
 use dpi::LogicalSize;
 use image::{DynamicImage, ImageReader, Rgba, RgbaImage};
 use tracing::debug;
-use vello::util::RenderContext;
-use vello::{block_on_wgpu, RendererOptions};
-use wgpu::{
-    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
-    TextureDescriptor, TextureFormat, TextureUsages,
-};
-use winit::event::Ime;
+use vello::Scene;
+use winit::event::{Ime, TouchPhase};
+use winit::keyboard::{Key, ModifiersState};
 
 use crate::action::Action;
+use crate::clipboard::ClipboardContent;
 use crate::dpi::{LogicalPosition, PhysicalPosition, PhysicalSize};
-use crate::event::{PointerButton, PointerEvent, PointerState, TextEvent, WindowEvent};
+use crate::event::{
+    KeyEvent, PenState, PlatformEvent, PointerButton, PointerEvent, PointerState, ScrollDeltaKind,
+    TextEvent, WindowEvent, WindowPlacement, WindowTheme,
+};
+use crate::locale::{BasicFormatter, LocaleFormatter};
 use crate::passes::anim::run_update_anim_pass;
-use crate::render_root::{RenderRoot, RenderRootOptions, RenderRootSignal, WindowSizePolicy};
-use crate::testing::screenshots::get_image_diff;
+use crate::render_root::{
+    AppWindowId, FrameStats, RenderRoot, RenderRootOptions, RenderRootSignal, WidgetScene,
+    WindowSizePolicy,
+};
+use crate::testing::screenshots::{get_image_diff, render_scene};
 use crate::testing::snapshot_utils::get_cargo_workspace;
+use crate::text::TextPositioning;
 use crate::tracing_backend::try_init_test_tracing;
-use crate::widget::{WidgetMut, WidgetRef};
-use crate::{Color, Handled, Point, Size, Vec2, Widget, WidgetId};
+use crate::widget::{WidgetFlags, WidgetMut, WidgetRef};
+use crate::{Color, Env, Handled, Point, Size, Vec2, Widget, WidgetId};
 
 /// Default canvas size for tests.
 pub const HARNESS_DEFAULT_SIZE: Size = Size::new(400., 400.);
@@ -112,10 +120,22 @@ pub struct TestHarness {
     mouse_state: PointerState,
     window_size: PhysicalSize<u32>,
     background_color: Color,
-    action_queue: VecDeque<(Action, WidgetId)>,
+    action_queue: VecDeque<(Action, WidgetId, u64)>,
     has_ime_session: bool,
     ime_rect: (LogicalPosition<f64>, LogicalSize<f64>),
     title: String,
+    resize_increments: Option<LogicalSize<f64>>,
+    window_placement: Option<WindowPlacement>,
+    window_closed: bool,
+    /// The secondary windows opened by widgets, with their title.
+    open_windows: Vec<(AppWindowId, String)>,
+    clipboard: Option<ClipboardContent>,
+    pointer_locked: bool,
+    redraw_requested: bool,
+    low_latency: bool,
+    /// The nodes of the accessibility tree, as of the last accessibility pass.
+    access_nodes: HashMap<NodeId, Node>,
+    access_root: Option<NodeId>,
 }
 
 /// Assert a snapshot of a rendered frame of your app.
@@ -190,6 +210,9 @@ impl TestHarness {
                     size_policy: WindowSizePolicy::User,
                     scale_factor: 1.0,
                     test_font: Some(data),
+                    // Make formatting independent of the locale tests are run in.
+                    formatter: Some(Arc::new(BasicFormatter::default())),
+                    pixel_snapping: false,
                 },
             ),
             mouse_state,
@@ -199,7 +222,24 @@ impl TestHarness {
             has_ime_session: false,
             ime_rect: Default::default(),
             title: String::new(),
+            resize_increments: None,
+            window_placement: None,
+            window_closed: false,
+            open_windows: Vec::new(),
+            clipboard: None,
+            pointer_locked: false,
+            redraw_requested: false,
+            low_latency: false,
+            access_nodes: HashMap::new(),
+            access_root: None,
         };
+        // Timers only move forward with `move_timers_forward`.
+        let state = &mut harness.render_root.global_state;
+        state.mock_time = Some(state.now());
+        // Keep screenshots independent of whether an animation is running.
+        harness
+            .render_root
+            .set_text_positioning(TextPositioning::Subpixel);
         harness.process_window_event(WindowEvent::Resize(window_size));
 
         harness
@@ -238,11 +278,20 @@ impl TestHarness {
         handled
     }
 
+    /// Send a [`PlatformEvent`] to the widgets which asked for platform events.
+    ///
+    /// If this event triggers rewrite passes, they will also run as normal.
+    pub fn process_platform_event(&mut self, event: PlatformEvent) -> Handled {
+        let handled = self.render_root.handle_platform_event(event);
+        self.process_signals();
+        handled
+    }
+
     fn process_signals(&mut self) {
         while let Some(signal) = self.render_root.pop_signal() {
             match signal {
-                RenderRootSignal::Action(action, widget_id) => {
-                    self.action_queue.push_back((action, widget_id));
+                RenderRootSignal::Action(action, widget_id, sequence) => {
+                    self.action_queue.push_back((action, widget_id, sequence));
                 }
                 RenderRootSignal::StartIme => {
                     self.has_ime_session = true;
@@ -253,7 +302,13 @@ impl TestHarness {
                 RenderRootSignal::ImeMoved(position, size) => {
                     self.ime_rect = (position, size);
                 }
-                RenderRootSignal::RequestRedraw => (),
+                RenderRootSignal::RequestRedraw => {
+                    self.redraw_requested = true;
+                }
+                RenderRootSignal::RequestAccessibilityUpdate => {
+                    let update = self.render_root.update_accessibility();
+                    self.apply_tree_update(update);
+                }
                 RenderRootSignal::RequestAnimFrame => (),
                 RenderRootSignal::TakeFocus => (),
                 RenderRootSignal::SetCursor(_) => (),
@@ -264,6 +319,36 @@ impl TestHarness {
                 RenderRootSignal::SetTitle(title) => {
                     self.title = title;
                 }
+                RenderRootSignal::SetResizeIncrements(increments) => {
+                    self.resize_increments = increments;
+                }
+                RenderRootSignal::PlaceWindow(placement) => {
+                    self.window_placement = Some(placement);
+                }
+                RenderRootSignal::CloseWindow => {
+                    self.window_closed = true;
+                }
+                RenderRootSignal::OpenWindow(id, attributes, _) => {
+                    self.open_windows.push((id, attributes.title));
+                }
+                RenderRootSignal::CloseAppWindow(id) => {
+                    self.open_windows.retain(|(window, _)| *window != id);
+                }
+                RenderRootSignal::SetClipboard(content) => {
+                    self.clipboard = Some(content);
+                }
+                RenderRootSignal::RequestPaste => {
+                    if let Some(content) = &self.clipboard {
+                        let text = content.text.clone();
+                        self.render_root.handle_text_event(TextEvent::Paste(text));
+                    }
+                }
+                RenderRootSignal::SetPointerLock(locked) => {
+                    self.pointer_locked = locked;
+                }
+                RenderRootSignal::SetLowLatency(low_latency) => {
+                    self.low_latency = low_latency;
+                }
             }
         }
     }
@@ -273,100 +358,192 @@ impl TestHarness {
     // TODO - Should be async?
     /// Create a bitmap (an array of pixels), paint the window and return the bitmap as an 8-bits-per-channel RGB image.
     pub fn render(&mut self) -> RgbaImage {
-        let (scene, _tree_update) = self.render_root.redraw();
+        self.redraw_requested = false;
+        let (scene, tree_update) = self.render_root.redraw();
+        self.apply_tree_update(tree_update);
         if std::env::var("SKIP_RENDER_TESTS").is_ok_and(|it| !it.is_empty()) {
             return RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255]));
         }
-        // TODO: Cache/share the context
-        let mut context = RenderContext::new();
-        let device_id =
-            pollster::block_on(context.device(None)).expect("No compatible device found");
-        let device_handle = &mut context.devices[device_id];
-        let device = &device_handle.device;
-        let queue = &device_handle.queue;
-        let mut renderer = vello::Renderer::new(
-            device,
-            RendererOptions {
-                surface_format: None,
-                // TODO - Examine this value
-                use_cpu: true,
-                num_init_threads: NonZeroUsize::new(1),
-                // TODO - Examine this value
-                antialiasing_support: vello::AaSupport::area_only(),
-            },
-        )
-        .expect("Got non-Send/Sync error from creating renderer");
-
         // TODO - fix window_size
         let (width, height) = (self.window_size.width, self.window_size.height);
-        let render_params = vello::RenderParams {
-            // TODO - Parameterize
-            base_color: self.background_color,
-            width,
-            height,
-            antialiasing_method: vello::AaConfig::Area,
-        };
+        render_scene(&scene, width, height, self.background_color)
+            .expect("failed to render the scene")
+    }
 
-        let size = Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        };
-        let target = device.create_texture(&TextureDescriptor {
-            label: Some("Target texture"),
-            size,
-            mip_level_count: 1,
-            sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
-            format: TextureFormat::Rgba8Unorm,
-            usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
-            view_formats: &[],
-        });
-        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
-        renderer
-            .render_to_texture(device, queue, &scene, &view, &render_params)
-            .expect("Got non-Send/Sync error from rendering");
-        let padded_byte_width = (width * 4).next_multiple_of(256);
-        let buffer_size = padded_byte_width as u64 * height as u64;
-        let buffer = device.create_buffer(&BufferDescriptor {
-            label: Some("val"),
-            size: buffer_size,
-            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
-            label: Some("Copy out buffer"),
-        });
-        encoder.copy_texture_to_buffer(
-            target.as_image_copy(),
-            ImageCopyBuffer {
-                buffer: &buffer,
-                layout: wgpu::ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(padded_byte_width),
-                    rows_per_image: None,
-                },
-            },
-            size,
-        );
-
-        queue.submit([encoder.finish()]);
-        let buf_slice = buffer.slice(..);
-
-        let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
-        buf_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
-        let recv_result = block_on_wgpu(device, receiver.receive()).expect("channel was closed");
-        recv_result.expect("failed to map buffer");
-
-        let data = buf_slice.get_mapped_range();
-        let mut result_unpadded =
-            Vec::<u8>::with_capacity((width * height * 4).try_into().unwrap());
-        for row in 0..height {
-            let start = (row * padded_byte_width).try_into().unwrap();
-            result_unpadded.extend(&data[start..start + (width * 4) as usize]);
+    /// Run the paint and accessibility passes, like [`render`](Self::render), but return
+    /// the scene without rendering it.
+    pub fn paint(&mut self) -> Scene {
+        self.redraw_requested = false;
+        let (scene, tree_update) = self.render_root.redraw();
+        self.apply_tree_update(tree_update);
+        scene
+    }
+
+    /// The accessibility node of the widget with the given id, if it's in the
+    /// accessibility tree.
+    ///
+    /// This runs the accessibility pass first, so that pending changes are included.
+    /// Nodes which aren't reachable from the root, e.g. because they are in a
+    /// hidden subtree, aren't returned.
+    pub fn access_node(&mut self, id: WidgetId) -> Option<&Node> {
+        let update = self.render_root.update_accessibility();
+        self.apply_tree_update(update);
+
+        let target = NodeId::from(id);
+        let mut stack = vec![self.access_root?];
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = self.access_nodes.get(&node_id) else {
+                continue;
+            };
+            if node_id == target {
+                return Some(node);
+            }
+            stack.extend_from_slice(node.children());
+        }
+        None
+    }
+
+    fn apply_tree_update(&mut self, update: TreeUpdate) {
+        if let Some(tree) = update.tree {
+            self.access_root = Some(tree.root);
         }
+        self.access_nodes.extend(update.nodes);
+    }
+
+    /// Statistics about the last frame, painted with [`render`](Self::render) or
+    /// [`paint`](Self::paint).
+    ///
+    /// See [`RenderRoot::frame_stats`] for details.
+    pub fn frame_stats(&self) -> &FrameStats {
+        self.render_root.frame_stats()
+    }
 
-        RgbaImage::from_vec(width, height, result_unpadded).expect("failed to create image")
+    /// A text report about the environment the app runs in.
+    ///
+    /// See [`RenderRoot::diagnostics_report`] for details.
+    pub fn diagnostics_report(&mut self) -> String {
+        self.render_root.diagnostics_report()
+    }
+
+    /// Capture the scene of a widget and its descendants.
+    ///
+    /// See [`RenderRoot::capture_widget_scene`].
+    pub fn capture_widget_scene(&mut self, id: WidgetId) -> Option<WidgetScene> {
+        self.render_root.capture_widget_scene(id)
+    }
+
+    /// The last events sent to the window, oldest first.
+    ///
+    /// See [`RenderRoot::event_history`].
+    pub fn event_history(&self) -> impl Iterator<Item = &str> {
+        self.render_root.event_history()
+    }
+
+    /// Write a bug report bundle to the directory `dir`.
+    ///
+    /// The bundle holds a screenshot of the current frame in `frame.png`, as well as
+    /// the files written by [`RenderRoot::write_bug_report`].
+    pub fn write_bug_report(&mut self, dir: impl AsRef<Path>) -> std::io::Result<()> {
+        let dir = dir.as_ref();
+        self.render_root.write_bug_report(dir)?;
+        self.render()
+            .save(dir.join("frame.png"))
+            .map_err(std::io::Error::other)
+    }
+
+    /// The last actions submitted by widgets, oldest first.
+    ///
+    /// See [`RenderRoot::action_history`].
+    pub fn action_history(&self) -> impl Iterator<Item = &str> {
+        self.render_root.action_history()
+    }
+
+    /// A text report about the widget with the given id and its descendants.
+    ///
+    /// See [`RenderRoot::widget_report`].
+    pub fn widget_report(&self, id: WidgetId) -> Option<String> {
+        self.render_root.widget_report(id)
+    }
+
+    /// Write a bug report bundle about the widget with the given id to the directory `dir`.
+    ///
+    /// See [`RenderRoot::write_widget_report`].
+    pub fn write_widget_report(
+        &mut self,
+        id: WidgetId,
+        dir: impl AsRef<Path>,
+    ) -> std::io::Result<String> {
+        self.render_root.write_widget_report(id, dir.as_ref())
+    }
+
+    /// Set whether the actions submitted by widgets are recorded in the action history.
+    ///
+    /// See [`RenderRoot::set_record_actions`].
+    pub fn set_record_actions(&mut self, enabled: bool) {
+        self.render_root.set_record_actions(enabled);
+    }
+
+    /// Set the time budget of each pass.
+    ///
+    /// See [`RenderRoot::set_pass_watchdog`].
+    pub fn set_pass_watchdog(&mut self, budget: Option<Duration>) {
+        self.render_root.set_pass_watchdog(budget);
+    }
+
+    /// Set whether the origins and sizes of widgets are rounded to physical pixels.
+    ///
+    /// See [`RenderRoot::set_pixel_snapping`].
+    pub fn set_pixel_snapping(&mut self, enabled: bool) {
+        self.render_root.set_pixel_snapping(enabled);
+    }
+
+    /// Set the rhythm unit of the baseline grid, or disable it with `None`.
+    ///
+    /// See [`RenderRoot::set_baseline_grid`].
+    pub fn set_baseline_grid(&mut self, unit: Option<f64>) {
+        self.render_root.set_baseline_grid(unit);
+        self.process_signals();
+    }
+
+    /// Set whether the regions repainted in each frame are highlighted.
+    ///
+    /// See [`RenderRoot::set_paint_flashing`].
+    pub fn set_paint_flashing(&mut self, enabled: bool) {
+        self.render_root.set_paint_flashing(enabled);
+        self.process_signals();
+    }
+
+    /// Set whether text is positioned at subpixel offsets, or snapped to physical pixels.
+    ///
+    /// Text is positioned at subpixel offsets by default in tests.
+    /// See [`RenderRoot::set_text_positioning`].
+    pub fn set_text_positioning(&mut self, positioning: TextPositioning) {
+        self.render_root.set_text_positioning(positioning);
+        self.process_signals();
+    }
+
+    /// Set the formatter used by widgets to display numbers and dates.
+    ///
+    /// See [`RenderRoot::set_formatter`].
+    pub fn set_formatter(&mut self, formatter: impl LocaleFormatter + 'static) {
+        self.render_root.set_formatter(Arc::new(formatter));
+        self.process_signals();
+    }
+
+    /// Set the env which the envs of all widgets inherit from.
+    ///
+    /// See [`RenderRoot::set_env`].
+    pub fn set_env(&mut self, env: Env) {
+        self.render_root.set_env(env);
+        self.process_signals();
+    }
+
+    /// Set the color scheme of the system.
+    ///
+    /// See [`RenderRoot::set_color_scheme`].
+    pub fn set_color_scheme(&mut self, theme: WindowTheme) {
+        self.render_root.set_color_scheme(theme);
+        self.process_signals();
     }
 
     // --- MARK: EVENT HELPERS ---
@@ -399,13 +576,91 @@ impl TestHarness {
         self.process_pointer_event(PointerEvent::PointerUp(button, self.mouse_state.clone()));
     }
 
-    /// Send a [`MouseWheel`](PointerEvent::MouseWheel) event to the window.
+    /// Send the pointer events of a finger with the given `id` touching, moving on or
+    /// leaving the screen at `pos`.
+    ///
+    /// See [`RenderRoot::handle_touch_event`].
+    pub fn touch(&mut self, id: u64, phase: TouchPhase, pos: impl Into<Point>) {
+        let pos = pos.into();
+        let mut state = PointerState::empty();
+        state.position = LogicalPosition::new(pos.x, pos.y);
+        state.physical_position = PhysicalPosition::new(pos.x, pos.y);
+        self.render_root.handle_touch_event(id, phase, state);
+        self.process_signals();
+    }
+
+    /// Send the event of a pen touching the screen at `pos`, with the given `pen` state.
+    ///
+    /// Pens are routed like [touches](Self::touch) with the same `id`.
+    pub fn pen(&mut self, id: u64, phase: TouchPhase, pos: impl Into<Point>, pen: PenState) {
+        let pos = pos.into();
+        let mut state = PointerState::empty();
+        state.position = LogicalPosition::new(pos.x, pos.y);
+        state.physical_position = PhysicalPosition::new(pos.x, pos.y);
+        state.pen = Some(pen);
+        self.render_root.handle_touch_event(id, phase, state);
+        self.process_signals();
+    }
+
+    /// Set the keyboard modifiers held during the next pointer events, and send a
+    /// [`ModifierChange`](TextEvent::ModifierChange) event to the window.
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.mouse_state.mods = modifiers.into();
+        self.process_text_event(TextEvent::ModifierChange(modifiers));
+    }
+
+    /// Send a [`MouseWheel`](PointerEvent::MouseWheel) event to the window, as if the
+    /// mouse wheel turned by `wheel_delta` lines.
     pub fn mouse_wheel(&mut self, wheel_delta: Vec2) {
-        let pixel_delta = LogicalPosition::new(wheel_delta.x, wheel_delta.y);
+        let line_delta = LogicalPosition::new(wheel_delta.x, wheel_delta.y);
+        self.process_pointer_event(PointerEvent::MouseWheel(
+            line_delta,
+            ScrollDeltaKind::Line,
+            self.mouse_state.clone(),
+        ));
+    }
+
+    /// Send a [`MouseWheel`](PointerEvent::MouseWheel) event to the window, as if the
+    /// fingers moved by `delta` on a touchpad.
+    pub fn touchpad_scroll(&mut self, delta: Vec2) {
+        let pixel_delta = LogicalPosition::new(delta.x, delta.y);
         self.process_pointer_event(PointerEvent::MouseWheel(
             pixel_delta,
+            ScrollDeltaKind::Pixel,
+            self.mouse_state.clone(),
+        ));
+    }
+
+    /// Send a [`LockedMove`](PointerEvent::LockedMove) event to the window, as if the mouse
+    /// moved by `delta` while the pointer is locked.
+    pub fn mouse_move_locked(&mut self, delta: Vec2) {
+        self.process_pointer_event(PointerEvent::LockedMove(delta, self.mouse_state.clone()));
+    }
+
+    /// Send a [`HoverFile`](PointerEvent::HoverFile) event and a
+    /// [`FileHovered`](PlatformEvent::FileHovered) event to the window, as if a file
+    /// from another app was dragged at the mouse position.
+    pub fn hover_file(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        self.process_pointer_event(PointerEvent::HoverFile(
+            path.clone(),
+            self.mouse_state.clone(),
+        ));
+        let position = Some(self.mouse_state.position);
+        self.process_platform_event(PlatformEvent::FileHovered { path, position });
+    }
+
+    /// Send a [`DropFile`](PointerEvent::DropFile) event and a
+    /// [`FileDropped`](PlatformEvent::FileDropped) event to the window, as if a file
+    /// from another app was dropped at the mouse position.
+    pub fn drop_file(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        self.process_pointer_event(PointerEvent::DropFile(
+            path.clone(),
             self.mouse_state.clone(),
         ));
+        let position = Some(self.mouse_state.position);
+        self.process_platform_event(PlatformEvent::FileDropped { path, position });
     }
 
     /// Send events that lead to a given widget being clicked.
@@ -431,16 +686,27 @@ impl TestHarness {
     }
 
     // TODO - Handle complicated IME
-    // TODO - Mock Winit keyboard events
     /// Send a [`TextEvent`] for each character in the given string.
     pub fn keyboard_type_chars(&mut self, text: &str) {
         // For each character
         for c in text.split("").filter(|s| !s.is_empty()) {
             let event = TextEvent::Ime(Ime::Commit(c.to_string()));
-            self.render_root.handle_text_event(event);
+            self.process_text_event(event);
         }
     }
 
+    /// Press and release `key`, with the modifiers set with
+    /// [`set_modifiers`](Self::set_modifiers).
+    ///
+    /// See [`KeyEvent::key_down`] for the event sent.
+    pub fn keyboard_key(&mut self, key: Key) {
+        let mods = self.mouse_state.mods.state();
+        let press = KeyEvent::key_down(key.clone());
+        self.process_text_event(TextEvent::KeyboardKey(press, mods));
+        let release = KeyEvent::key_up(key);
+        self.process_text_event(TextEvent::KeyboardKey(release, mods));
+    }
+
     /// Sets the focused widget.
     ///
     /// ## Panics
@@ -454,10 +720,10 @@ impl TestHarness {
             let Some(state) = arena.widget_states.find(id) else {
                 panic!("Cannot focus widget {id}: widget not found in tree");
             };
-            if state.item.is_stashed {
+            if state.item.flags.contains(WidgetFlags::IS_STASHED) {
                 panic!("Cannot focus widget {id}: widget is stashed");
             }
-            if state.item.is_disabled {
+            if state.item.flags.contains(WidgetFlags::IS_DISABLED) {
                 panic!("Cannot focus widget {id}: widget is disabled");
             }
         }
@@ -474,8 +740,7 @@ impl TestHarness {
         self.process_signals();
     }
 
-    #[cfg(FALSE)]
-    /// Simulate the passage of time.
+    /// Simulate the passage of time, and fire the timers which are due.
     ///
     /// If you create any timer in a widget, this method is the only way to trigger
     /// them in unit tests. The testing model assumes that everything else executes
@@ -483,17 +748,10 @@ impl TestHarness {
     ///
     /// **(TODO - Doesn't move animations forward.)**
     pub fn move_timers_forward(&mut self, duration: Duration) {
-        // TODO - handle animations
-        let tokens = self
-            .mock_app
-            .window
-            .mock_timer_queue
-            .as_mut()
-            .unwrap()
-            .move_forward(duration);
-        for token in tokens {
-            self.process_event(Event::Timer(token));
-        }
+        let state = &mut self.render_root.global_state;
+        state.mock_time = Some(state.now() + duration);
+        self.render_root.run_timers();
+        self.process_signals();
     }
 
     // --- MARK: GETTERS ---
@@ -576,10 +834,25 @@ impl TestHarness {
         self.render_root.edit_widget(id, f)
     }
 
+    /// Apply the callbacks queued with `mutate_later` right away.
+    ///
+    /// See [`RenderRoot::run_mutations_now`] for details.
+    pub fn run_mutations_now(&mut self) -> bool {
+        self.render_root.run_mutations_now()
+    }
+
     /// Pop the next action from the queue.
     ///
     /// **Note:** Actions are still a WIP feature.
     pub fn pop_action(&mut self) -> Option<(Action, WidgetId)> {
+        let (action, widget_id, _) = self.action_queue.pop_front()?;
+        Some((action, widget_id))
+    }
+
+    /// Pop the next action from the queue, with its sequence number.
+    ///
+    /// See [`RenderRootSignal::Action`] for the order of actions.
+    pub fn pop_action_with_sequence(&mut self) -> Option<(Action, WidgetId, u64)> {
         self.action_queue.pop_front()
     }
 
@@ -615,6 +888,63 @@ impl TestHarness {
         self.title.clone()
     }
 
+    /// Return the resize increments requested for the simulated window.
+    pub fn resize_increments(&self) -> Option<LogicalSize<f64>> {
+        self.resize_increments
+    }
+
+    /// Return the last placement requested for the simulated window.
+    pub fn window_placement(&self) -> Option<&WindowPlacement> {
+        self.window_placement.as_ref()
+    }
+
+    /// Return whether a widget closed the simulated window.
+    pub fn window_closed(&self) -> bool {
+        self.window_closed
+    }
+
+    /// Return the ids and titles of the secondary windows opened by widgets, and not
+    /// closed since.
+    ///
+    /// The harness doesn't run the widgets of secondary windows.
+    pub fn open_windows(&self) -> &[(AppWindowId, String)] {
+        &self.open_windows
+    }
+
+    /// Return the text of the tooltip shown, if any.
+    ///
+    /// Tooltips are shown after a delay, see [`move_timers_forward`](Self::move_timers_forward).
+    pub fn tooltip(&self) -> Option<&str> {
+        self.render_root.tooltip()
+    }
+
+    /// Return the content of the simulated clipboard.
+    pub fn clipboard(&self) -> Option<&ClipboardContent> {
+        self.clipboard.as_ref()
+    }
+
+    /// Set the content of the simulated clipboard, which is pasted when a widget
+    /// calls [`request_paste`](crate::EventCtx::request_paste).
+    pub fn set_clipboard(&mut self, content: ClipboardContent) {
+        self.clipboard = Some(content);
+    }
+
+    /// Return whether a widget holds the pointer lock of the simulated window.
+    pub fn pointer_locked(&self) -> bool {
+        self.pointer_locked
+    }
+
+    /// Return whether a widget asked for low latency presentation.
+    pub fn low_latency(&self) -> bool {
+        self.low_latency
+    }
+
+    /// Return whether a new frame was requested since the last [`render`](Self::render)
+    /// or [`paint`](Self::paint).
+    pub fn redraw_requested(&self) -> bool {
+        self.redraw_requested
+    }
+
     // --- MARK: SNAPSHOT ---
 
     /// Method used by [`assert_render_snapshot`]. Use the macro instead.
@@ -26,6 +26,7 @@ use crate::*;
 pub type PointerEventFn<S> = dyn FnMut(&mut S, &mut EventCtx, &PointerEvent);
 pub type TextEventFn<S> = dyn FnMut(&mut S, &mut EventCtx, &TextEvent);
 pub type AccessEventFn<S> = dyn FnMut(&mut S, &mut EventCtx, &AccessEvent);
+pub type PlatformEventFn<S> = dyn FnMut(&mut S, &mut EventCtx, &PlatformEvent);
 pub type AnimFrameFn<S> = dyn FnMut(&mut S, &mut UpdateCtx, u64);
 pub type RegisterChildrenFn<S> = dyn FnMut(&mut S, &mut RegisterCtx);
 pub type UpdateFn<S> = dyn FnMut(&mut S, &mut UpdateCtx, &Update);
@@ -47,9 +48,12 @@ pub struct ModularWidget<S> {
     accepts_pointer_interaction: bool,
     accepts_focus: bool,
     accepts_text_input: bool,
+    paints_own_focus_indicator: bool,
+    page_break: PageBreak,
     on_pointer_event: Option<Box<PointerEventFn<S>>>,
     on_text_event: Option<Box<TextEventFn<S>>>,
     on_access_event: Option<Box<AccessEventFn<S>>>,
+    on_platform_event: Option<Box<PlatformEventFn<S>>>,
     on_anim_frame: Option<Box<AnimFrameFn<S>>>,
     register_children: Option<Box<RegisterChildrenFn<S>>>,
     update: Option<Box<UpdateFn<S>>>,
@@ -107,6 +111,7 @@ pub enum Record {
     PE(PointerEvent),
     TE(TextEvent),
     AE(AccessEvent),
+    Platform(PlatformEvent),
     AF(u64),
     RC,
     U(Update),
@@ -145,9 +150,12 @@ impl<S> ModularWidget<S> {
             accepts_pointer_interaction: true,
             accepts_focus: false,
             accepts_text_input: false,
+            paints_own_focus_indicator: false,
+            page_break: PageBreak::Auto,
             on_pointer_event: None,
             on_text_event: None,
             on_access_event: None,
+            on_platform_event: None,
             on_anim_frame: None,
             register_children: None,
             update: None,
@@ -182,6 +190,18 @@ impl<S> ModularWidget<S> {
         self.accepts_text_input = flag;
         self
     }
+
+    /// See [`Widget::paints_own_focus_indicator`]
+    pub fn paints_own_focus_indicator(mut self, flag: bool) -> Self {
+        self.paints_own_focus_indicator = flag;
+        self
+    }
+
+    /// See [`Widget::page_break`]
+    pub fn page_break(mut self, page_break: PageBreak) -> Self {
+        self.page_break = page_break;
+        self
+    }
 }
 
 /// Builder methods.
@@ -215,6 +235,15 @@ impl<S> ModularWidget<S> {
         self
     }
 
+    /// See [`Widget::on_platform_event`]
+    pub fn platform_event_fn(
+        mut self,
+        f: impl FnMut(&mut S, &mut EventCtx, &PlatformEvent) + 'static,
+    ) -> Self {
+        self.on_platform_event = Some(Box::new(f));
+        self
+    }
+
     /// See [`Widget::on_anim_frame`]
     pub fn anim_frame_fn(mut self, f: impl FnMut(&mut S, &mut UpdateCtx, u64) + 'static) -> Self {
         self.on_anim_frame = Some(Box::new(f));
@@ -282,6 +311,13 @@ impl<S> ModularWidget<S> {
     }
 }
 
+impl<S> ModularWidget<S> {
+    /// The state passed to every function.
+    pub(crate) fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+}
+
 #[warn(clippy::missing_trait_methods)]
 impl<S: 'static> Widget for ModularWidget<S> {
     fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &event::PointerEvent) {
@@ -302,6 +338,12 @@ impl<S: 'static> Widget for ModularWidget<S> {
         }
     }
 
+    fn on_platform_event(&mut self, ctx: &mut EventCtx, event: &PlatformEvent) {
+        if let Some(f) = self.on_platform_event.as_mut() {
+            f(&mut self.state, ctx, event);
+        }
+    }
+
     fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
         if let Some(f) = self.on_anim_frame.as_mut() {
             f(&mut self.state, ctx, interval);
@@ -378,6 +420,14 @@ impl<S: 'static> Widget for ModularWidget<S> {
         self.accepts_text_input
     }
 
+    fn paints_own_focus_indicator(&self) -> bool {
+        self.paints_own_focus_indicator
+    }
+
+    fn page_break(&self) -> PageBreak {
+        self.page_break
+    }
+
     fn make_trace_span(&self) -> tracing::Span {
         trace_span!("ModularWidget")
     }
@@ -522,6 +572,11 @@ impl<W: Widget> Widget for Recorder<W> {
         self.child.on_access_event(ctx, event);
     }
 
+    fn on_platform_event(&mut self, ctx: &mut EventCtx, event: &PlatformEvent) {
+        self.recording.push(Record::Platform(event.clone()));
+        self.child.on_platform_event(ctx, event);
+    }
+
     fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
         self.recording.push(Record::AF(interval));
         self.child.on_anim_frame(ctx, interval);
@@ -578,6 +633,14 @@ impl<W: Widget> Widget for Recorder<W> {
         self.child.accepts_text_input()
     }
 
+    fn paints_own_focus_indicator(&self) -> bool {
+        self.child.paints_own_focus_indicator()
+    }
+
+    fn page_break(&self) -> PageBreak {
+        self.child.page_break()
+    }
+
     fn make_trace_span(&self) -> tracing::Span {
         self.child.make_trace_span()
     }
@@ -10,7 +10,7 @@ mod harness;
 #[cfg(not(tarpaulin_include))]
 mod helper_widgets;
 #[cfg(not(tarpaulin_include))]
-mod screenshots;
+pub(crate) mod screenshots;
 #[cfg(not(tarpaulin_include))]
 mod snapshot_utils;
 
@@ -1,10 +1,139 @@
 // Copyright 2022 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
-//! Helper functions for writing snapshot tests and comparing images.
+//! Helper functions for rendering screenshots, writing snapshot tests and comparing images.
 
-use image::{GenericImageView as _, RgbImage};
+use std::num::NonZeroUsize;
+
+use image::{GenericImageView as _, RgbImage, RgbaImage};
 use nv_flip::{FlipImageRgb8, DEFAULT_PIXELS_PER_DEGREE};
+use vello::util::RenderContext;
+use vello::{block_on_wgpu, RendererOptions, Scene};
+use wgpu::{
+    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+    TextureDescriptor, TextureFormat, TextureUsages,
+};
+
+use crate::render_root::WidgetScene;
+use crate::{Affine, Color};
+
+/// Render a captured widget scene to an image on the CPU at `scale`, cropped to the area
+/// the widget paints to, over a background of `base_color`.
+pub(crate) fn render_widget_scene(
+    captured: &WidgetScene,
+    scale: f64,
+    base_color: Color,
+) -> std::io::Result<RgbaImage> {
+    let rect = captured.paint_rect.expand();
+    let mut scene = Scene::new();
+    scene.append(
+        &captured.scene,
+        Some(Affine::scale(scale) * Affine::translate(-rect.origin().to_vec2())),
+    );
+    let width = (rect.width() * scale).ceil().max(1.) as u32;
+    let height = (rect.height() * scale).ceil().max(1.) as u32;
+    render_scene(&scene, width, height, base_color)
+}
+
+/// Render `scene` to an image on the CPU, over a background of `base_color`.
+///
+/// Fails if no compatible device is found or if rendering fails.
+pub(crate) fn render_scene(
+    scene: &Scene,
+    width: u32,
+    height: u32,
+    base_color: Color,
+) -> std::io::Result<RgbaImage> {
+    // TODO: Cache/share the context
+    let mut context = RenderContext::new();
+    let device_id = pollster::block_on(context.device(None))
+        .ok_or_else(|| std::io::Error::other("no compatible device found"))?;
+    let device_handle = &mut context.devices[device_id];
+    let device = &device_handle.device;
+    let queue = &device_handle.queue;
+    let mut renderer = vello::Renderer::new(
+        device,
+        RendererOptions {
+            surface_format: None,
+            // TODO - Examine this value
+            use_cpu: true,
+            num_init_threads: NonZeroUsize::new(1),
+            // TODO - Examine this value
+            antialiasing_support: vello::AaSupport::area_only(),
+        },
+    )
+    // The error isn't `Send`, so it can't be wrapped.
+    .map_err(|err| std::io::Error::other(err.to_string()))?;
+
+    let render_params = vello::RenderParams {
+        base_color,
+        width,
+        height,
+        antialiasing_method: vello::AaConfig::Area,
+    };
+
+    let size = Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let target = device.create_texture(&TextureDescriptor {
+        label: Some("Target texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    renderer
+        .render_to_texture(device, queue, scene, &view, &render_params)
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    let padded_byte_width = (width * 4).next_multiple_of(256);
+    let buffer_size = padded_byte_width as u64 * height as u64;
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("val"),
+        size: buffer_size,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("Copy out buffer"),
+    });
+    encoder.copy_texture_to_buffer(
+        target.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_byte_width),
+                rows_per_image: None,
+            },
+        },
+        size,
+    );
+
+    queue.submit([encoder.finish()]);
+    let buf_slice = buffer.slice(..);
+
+    let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
+    buf_slice.map_async(wgpu::MapMode::Read, move |v| sender.send(v).unwrap());
+    let recv_result = block_on_wgpu(device, receiver.receive())
+        .ok_or_else(|| std::io::Error::other("channel was closed"))?;
+    recv_result.map_err(std::io::Error::other)?;
+
+    let data = buf_slice.get_mapped_range();
+    let mut result_unpadded = Vec::<u8>::with_capacity((width * height * 4).try_into().unwrap());
+    for row in 0..height {
+        let start = (row * padded_byte_width).try_into().unwrap();
+        result_unpadded.extend(&data[start..start + (width * 4) as usize]);
+    }
+
+    RgbaImage::from_vec(width, height, result_unpadded)
+        .ok_or_else(|| std::io::Error::other("failed to create image"))
+}
 
 pub(crate) fn get_image_diff(ref_image: &RgbImage, new_image: &RgbImage) -> Option<RgbImage> {
     assert_eq!(
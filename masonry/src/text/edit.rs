@@ -12,7 +12,9 @@ use winit::keyboard::{Key, NamedKey};
 
 use crate::event::{PointerButton, PointerState};
 use crate::text::selection::{Affinity, Selection};
-use crate::text::{offset_for_delete_backwards, Selectable, TextBrush, TextWithSelection};
+use crate::text::{
+    offset_for_delete_backwards, CaretShape, Selectable, TextBrush, TextWithSelection,
+};
 use crate::{Action, EventCtx, Handled, TextEvent};
 
 /// A region of text which can support editing operations
@@ -34,6 +36,14 @@ impl TextEditor {
         self.preedit_range = None;
     }
 
+    /// Replace the text, and put the caret at its end.
+    pub fn set_text_with_caret_at_end(&mut self, text: String) {
+        let len = text.len();
+        self.preedit_range = None;
+        self.inner.set_text(text);
+        self.inner.selection = Selection::caret(len, Affinity::Upstream);
+    }
+
     /// Rebuild the text.
     ///
     /// See also [`TextLayout::rebuild`](crate::text::TextLayout::rebuild) for more comprehensive docs.
@@ -72,6 +82,9 @@ impl TextEditor {
     pub fn text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) -> Handled {
         let inner_handled = self.inner.text_event(event);
         if inner_handled.is_handled() {
+            if let Some(text) = self.inner.take_copied_text() {
+                ctx.copy_to_clipboard(&text);
+            }
             return inner_handled;
         }
         match event {
@@ -125,20 +138,7 @@ impl TextEditor {
                             }
                             Handled::Yes
                         }
-                        Key::Named(NamedKey::Space) => {
-                            let selection = self.inner.selection;
-                            let c = ' ';
-                            self.text_mut()
-                                .replace_range(selection.range(), &c.to_string());
-                            self.inner.selection = Selection::caret(
-                                selection.min() + c.len_utf8(),
-                                // We have just added this character, so we are "affined" with it
-                                Affinity::Downstream,
-                            );
-                            let contents = self.text().clone();
-                            ctx.submit_action(Action::TextChanged(contents));
-                            Handled::Yes
-                        }
+                        Key::Named(NamedKey::Space) => self.insert_text(" ", ctx),
                         Key::Named(NamedKey::Enter) => {
                             let contents = self.text().clone();
                             ctx.submit_action(Action::TextEntered(contents));
@@ -194,6 +194,23 @@ impl TextEditor {
                             ctx.submit_action(Action::TextChanged(contents));
                             Handled::Yes
                         }
+                        Key::Character(c) if c == "v" => {
+                            ctx.request_paste();
+                            Handled::Yes
+                        }
+                        Key::Character(c) if c == "x" => {
+                            let selection = self.inner.selection;
+                            if !selection.is_caret() {
+                                ctx.copy_to_clipboard(&self.text()[selection.range()]);
+                                self.text_mut().replace_range(selection.range(), "");
+                                self.inner.selection =
+                                    Selection::caret(selection.min(), Affinity::Downstream);
+
+                                let contents = self.text().clone();
+                                ctx.submit_action(Action::TextChanged(contents));
+                            }
+                            Handled::Yes
+                        }
                         _ => Handled::No,
                     }
                 } else {
@@ -203,7 +220,7 @@ impl TextEditor {
             TextEvent::KeyboardKey(_, _) => Handled::No,
             TextEvent::Ime(ime) => match ime {
                 Ime::Commit(text) => {
-                    let selection_range = self.selection.range();
+                    let selection_range = self.replaced_range();
                     self.text_mut().replace_range(selection_range.clone(), text);
                     self.selection =
                         Selection::caret(selection_range.start + text.len(), Affinity::Upstream);
@@ -276,12 +293,38 @@ impl TextEditor {
             },
             TextEvent::ModifierChange(_) => Handled::No,
             TextEvent::FocusChange(_) => Handled::No,
+            TextEvent::Paste(text) => {
+                let selection_range = self.replaced_range();
+                self.text_mut().replace_range(selection_range.clone(), text);
+                self.selection =
+                    Selection::caret(selection_range.start + text.len(), Affinity::Upstream);
+
+                let contents = self.text().clone();
+                ctx.submit_action(Action::TextChanged(contents));
+                Handled::Yes
+            }
+        }
+    }
+
+    /// The range of text replaced by typed text.
+    fn replaced_range(&self) -> Range<usize> {
+        let selection = self.inner.selection;
+        let mut range = selection.range();
+        // A block caret overwrites the character it covers, but not the end of the line.
+        if self.caret_style().shape == CaretShape::Block && selection.is_caret() {
+            if let Some(next) = self.text().next_grapheme_offset(selection.active) {
+                if !self.text()[range.start..next].ends_with(['\n', '\r']) {
+                    range.end = next;
+                }
+            }
         }
+        range
     }
 
-    fn insert_text(&mut self, c: &winit::keyboard::SmolStr, ctx: &mut EventCtx) -> Handled {
+    fn insert_text(&mut self, c: &str, ctx: &mut EventCtx) -> Handled {
         let selection = self.inner.selection;
-        self.text_mut().replace_range(selection.range(), c);
+        let range = self.replaced_range();
+        self.text_mut().replace_range(range, c);
         self.inner.selection = Selection::caret(
             selection.min() + c.len(),
             // We have just added this character, so we are "affined" with it
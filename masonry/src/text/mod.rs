@@ -14,13 +14,19 @@ mod backspace;
 mod edit;
 mod render_text;
 mod selection;
+mod style;
 mod text_layout;
 
 pub use backspace::offset_for_delete_backwards;
 pub use edit::TextEditor;
 pub use render_text::render_text;
-pub use selection::{len_utf8_from_first_byte, Selectable, StringCursor, TextWithSelection};
-pub use text_layout::{Hinting, LayoutMetrics, TextBrush, TextLayout};
+pub use selection::{
+    len_utf8_from_first_byte, CaretShape, CaretStyle, Selectable, StringCursor, TextWithSelection,
+};
+pub use style::{measure_text, text_metrics, TextMetrics, TextStyle};
+pub use text_layout::{
+    GlyphSnapping, Hinting, LayoutMetrics, TextBrush, TextLayout, TextPositioning,
+};
 
 /// A reference counted string slice.
 ///
@@ -8,16 +8,36 @@ use vello::kurbo::{Affine, Line, Rect, Stroke};
 use vello::peniko::Fill;
 use vello::Scene;
 
-use crate::text::TextBrush;
+use crate::text::{GlyphSnapping, TextBrush};
 
 /// A function that renders laid out glyphs to a [`Scene`].
+///
+/// If `snapping` is `Some`, each glyph is moved to the nearest physical pixel of the window.
+/// This avoids blurry text, but makes text "swim" when it moves by fractions of a pixel,
+/// e.g. during an animation. Snapping is only applied if `transform` is a translation.
 pub fn render_text(
     scene: &mut Scene,
     scratch_scene: &mut Scene,
     transform: Affine,
     layout: &Layout<TextBrush>,
+    snapping: Option<GlyphSnapping>,
 ) {
     scratch_scene.reset();
+    let [a, b, c, d, tx, ty] = transform.as_coeffs();
+    let snapping = snapping.filter(|_| (a, b, c, d) == (1.0, 0.0, 0.0, 1.0));
+    // Glyph positions are relative to the transform, which is relative to the origin
+    // of the widget in the window, so we snap their sum.
+    let snap = |value: f32, translation: f64, origin: f64| match snapping {
+        Some(GlyphSnapping { scale, .. }) => {
+            let offset = translation + origin;
+            let snapped = ((value as f64 + offset) * scale).round() / scale;
+            (snapped - offset) as f32
+        }
+        None => value,
+    };
+    let (origin_x, origin_y) = snapping.map_or((0., 0.), |snapping| {
+        (snapping.window_origin.x, snapping.window_origin.y)
+    });
     for line in layout.lines() {
         let metrics = &line.metrics();
         for glyph_run in line.glyph_runs() {
@@ -78,8 +98,8 @@ pub fn render_text(
                         x += glyph.advance;
                         vello::glyph::Glyph {
                             id: glyph.id as _,
-                            x: gx,
-                            y: gy,
+                            x: snap(gx, tx, origin_x),
+                            y: snap(gy, ty, origin_y),
                         }
                     }),
                 );
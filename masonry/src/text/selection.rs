@@ -11,14 +11,67 @@ use parley::context::RangedBuilder;
 use parley::{FontContext, LayoutContext};
 use tracing::debug;
 use unicode_segmentation::{GraphemeCursor, UnicodeSegmentation};
-use vello::kurbo::{Affine, Line, Point, Stroke};
+use vello::kurbo::{Affine, Line, Point, Rect, Stroke};
 use vello::peniko::{Brush, Color};
 use vello::Scene;
 use winit::keyboard::NamedKey;
 
 use crate::event::{AccessEvent, PointerButton, PointerState};
 use crate::text::{TextBrush, TextLayout};
-use crate::{Handled, TextEvent};
+use crate::{theme, Env, Handled, TextEvent};
+
+/// The shape of the caret drawn at the active end of a selection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CaretShape {
+    /// A vertical line between characters.
+    #[default]
+    Bar,
+    /// A box covering the character after the caret, which is drawn in a contrasting color.
+    ///
+    /// In a [`TextEditor`](crate::text::TextEditor), typed text overwrites that character
+    /// instead of being inserted before it.
+    Block,
+}
+
+/// How the caret of a text widget is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CaretStyle {
+    pub shape: CaretShape,
+    pub color: Color,
+    /// The width of a [`CaretShape::Bar`] caret.
+    pub width: f64,
+}
+
+impl CaretStyle {
+    /// A bar caret with the [`CURSOR_COLOR`](theme::CURSOR_COLOR) and
+    /// [`CURSOR_WIDTH`](theme::CURSOR_WIDTH) of `env`.
+    pub fn from_env(env: &Env) -> Self {
+        Self {
+            shape: CaretShape::Bar,
+            color: env.get(theme::CURSOR_COLOR),
+            width: env.get(theme::CURSOR_WIDTH),
+        }
+    }
+
+    /// The color of the character covered by a [`CaretShape::Block`] caret.
+    ///
+    /// This is black or white, whichever contrasts more with the color of the caret.
+    pub fn block_text_color(&self) -> Color {
+        let Color { r, g, b, .. } = self.color;
+        let luma = 0.299 * f64::from(r) + 0.587 * f64::from(g) + 0.114 * f64::from(b);
+        if luma > 127.5 {
+            Color::BLACK
+        } else {
+            Color::WHITE
+        }
+    }
+}
+
+impl Default for CaretStyle {
+    fn default() -> Self {
+        Self::from_env(&Env::new())
+    }
+}
 
 pub struct TextWithSelection<T: Selectable> {
     text: T,
@@ -29,10 +82,14 @@ pub struct TextWithSelection<T: Selectable> {
     pub selection: Selection,
     pub selection_visible: bool,
     highlight_brush: TextBrush,
+    caret_style: CaretStyle,
     needs_selection_update: bool,
     selecting_with_mouse: bool,
+    caret_browsing: bool,
     // TODO: Cache cursor line, selection boxes
     cursor_line: Option<Line>,
+    cursor_rect: Option<Rect>,
+    copied_text: Option<String>,
 }
 
 impl<T: Selectable> TextWithSelection<T> {
@@ -45,12 +102,16 @@ impl<T: Selectable> TextWithSelection<T> {
             selection_visible: false,
             needs_selection_update: false,
             selecting_with_mouse: false,
+            caret_browsing: false,
             cursor_line: None,
+            cursor_rect: None,
+            copied_text: None,
             highlight_brush: TextBrush::Highlight {
                 text: Color::WHITE.into(),
-                fill: Color::LIGHT_BLUE.into(),
+                fill: (*theme::SELECTED_TEXT_BACKGROUND_COLOR.default_value()).into(),
                 hinting: Default::default(),
             },
+            caret_style: CaretStyle::default(),
         }
     }
 
@@ -70,6 +131,61 @@ impl<T: Selectable> TextWithSelection<T> {
         self.text = text;
     }
 
+    pub fn caret_style(&self) -> CaretStyle {
+        self.caret_style
+    }
+
+    /// Set how the caret is drawn.
+    ///
+    /// This requires a rebuild if the caret is or was a [`CaretShape::Block`], since the
+    /// character it covers is drawn in a different color.
+    pub fn set_caret_style(&mut self, style: CaretStyle) {
+        if self.caret_style.shape == CaretShape::Block || style.shape == CaretShape::Block {
+            self.needs_selection_update = true;
+        }
+        self.caret_style = style;
+    }
+
+    /// Set the color behind selected text.
+    pub fn set_selection_color(&mut self, color: Color) {
+        if let TextBrush::Highlight { fill, .. } = &mut self.highlight_brush {
+            *fill = color.into();
+        }
+        self.needs_selection_update = true;
+    }
+
+    /// Enable the keys used to read text with a caret, `false` by default.
+    ///
+    /// When enabled, holding <kbd>Shift</kbd> extends the selection with the arrow keys, and
+    /// <kbd>Up</kbd>, <kbd>Down</kbd>, <kbd>Home</kbd> and <kbd>End</kbd> move the caret.
+    /// Widgets which handle these keys themselves, e.g. text editors, leave it disabled.
+    pub fn set_caret_browsing(&mut self, enabled: bool) {
+        self.caret_browsing = enabled;
+    }
+
+    /// See [`TextLayout::soft_wrap_rects`].
+    pub fn soft_wrap_rects(&self) -> Vec<Rect> {
+        self.layout.soft_wrap_rects(self.text.as_ref())
+    }
+
+    /// The area covered by the caret, relative to the text, if it is visible.
+    pub fn caret_rect(&self) -> Option<Rect> {
+        if !self.selection_visible {
+            return None;
+        }
+        match self.caret_style.shape {
+            CaretShape::Bar => self
+                .layout
+                .caret_line_from_byte_index(self.selection.active)
+                .map(|line| {
+                    Rect::from_points(line.p0, line.p1).inflate(self.caret_style.width / 2., 0.)
+                }),
+            CaretShape::Block => self
+                .layout
+                .block_caret_from_byte_index(self.selection.active),
+        }
+    }
+
     pub fn needs_rebuild(&self) -> bool {
         self.layout.needs_rebuild() || self.needs_selection_update || self.text_changed
     }
@@ -128,35 +244,74 @@ impl<T: Selectable> TextWithSelection<T> {
     pub fn text_event(&mut self, event: &TextEvent) -> Handled {
         match event {
             TextEvent::KeyboardKey(key, mods) if key.state.is_pressed() => {
-                match shortcut_key(key) {
+                match key.key_without_modifiers.clone() {
+                    winit::keyboard::Key::Named(NamedKey::ArrowLeft | NamedKey::ArrowRight)
+                        if mods.shift_key() && !self.caret_browsing =>
+                    {
+                        // TODO: Expand selection
+                        Handled::Yes
+                    }
                     winit::keyboard::Key::Named(NamedKey::ArrowLeft) => {
-                        if mods.shift_key() {
+                        let t = &self.text;
+                        let active = self.selection.active;
+                        let offset = if mods.control_key() {
+                            t.prev_word_offset(active)
                         } else {
-                            let selection = self.selection;
-                            let t = &self.text;
-                            if mods.control_key() {
-                                let offset = t.prev_word_offset(selection.active).unwrap_or(0);
-                                self.selection = Selection::caret(offset, Affinity::Downstream);
-                            } else {
-                                let offset = t.prev_grapheme_offset(selection.active).unwrap_or(0);
-                                self.selection = Selection::caret(offset, Affinity::Downstream);
-                            };
-                        }
+                            t.prev_grapheme_offset(active)
+                        };
+                        self.move_caret(
+                            offset.unwrap_or(0),
+                            Affinity::Downstream,
+                            mods.shift_key(),
+                        );
                         Handled::Yes
                     }
                     winit::keyboard::Key::Named(NamedKey::ArrowRight) => {
-                        if mods.shift_key() {
-                            // TODO: Expand selection
+                        let t = &self.text;
+                        let active = self.selection.active;
+                        let offset = if mods.control_key() {
+                            t.next_word_offset(active)
                         } else {
-                            let t = &self.text;
-                            let selection = self.selection;
-                            if mods.control_key() {
-                                if let Some(o) = t.next_word_offset(selection.active) {
-                                    self.selection = Selection::caret(o, Affinity::Upstream);
-                                }
-                            } else if let Some(o) = t.next_grapheme_offset(selection.active) {
-                                self.selection = Selection::caret(o, Affinity::Upstream);
+                            t.next_grapheme_offset(active)
+                        };
+                        let offset = offset.unwrap_or(t.len());
+                        self.move_caret(offset, Affinity::Upstream, mods.shift_key());
+                        Handled::Yes
+                    }
+                    winit::keyboard::Key::Named(
+                        key @ (NamedKey::ArrowUp
+                        | NamedKey::ArrowDown
+                        | NamedKey::Home
+                        | NamedKey::End),
+                    ) if self.caret_browsing => {
+                        if mods.control_key() && matches!(key, NamedKey::Home | NamedKey::End) {
+                            let offset = if key == NamedKey::Home {
+                                0
+                            } else {
+                                self.text.len()
                             };
+                            self.move_caret(offset, Affinity::Downstream, mods.shift_key());
+                            return Handled::Yes;
+                        }
+                        let Some(line) = self
+                            .layout
+                            .caret_line_from_byte_index(self.selection.active)
+                        else {
+                            return Handled::No;
+                        };
+                        // Vertical movement keeps the caret at the same horizontal position,
+                        // even when it crosses shorter lines.
+                        let h_pos = self.selection.h_pos.unwrap_or(line.p0.x as f32);
+                        let point = match key {
+                            NamedKey::ArrowUp => Point::new(h_pos.into(), line.p0.y - 1.),
+                            NamedKey::ArrowDown => Point::new(h_pos.into(), line.p1.y + 1.),
+                            NamedKey::Home => Point::new(0., line.midpoint().y),
+                            _ => Point::new(f64::INFINITY, line.midpoint().y),
+                        };
+                        let offset = self.layout.cursor_for_point(point).insert_point;
+                        self.move_caret(offset, Affinity::Downstream, mods.shift_key());
+                        if matches!(key, NamedKey::ArrowUp | NamedKey::ArrowDown) {
+                            self.selection.h_pos = Some(h_pos);
                         }
                         Handled::Yes
                     }
@@ -175,6 +330,7 @@ impl<T: Selectable> TextWithSelection<T> {
                             // e.g. to put HTML code if supported by the rich text kind
                             if let Some(text) = self.text.slice(selection.min()..selection.max()) {
                                 debug!(r#"Copying "{text}""#);
+                                self.copied_text = Some(text.into_owned());
                             } else {
                                 debug_panic!("Had invalid selection");
                             }
@@ -197,9 +353,27 @@ impl<T: Selectable> TextWithSelection<T> {
                 // TODO: Set our highlighting colour to a lighter blue if window unfocused
                 Handled::No
             }
+            TextEvent::Paste(_) => Handled::No,
         }
     }
 
+    /// The text copied with <kbd>Ctrl</kbd>+<kbd>C</kbd> since the last call, which the
+    /// widget should put in the clipboard.
+    pub fn take_copied_text(&mut self) -> Option<String> {
+        self.copied_text.take()
+    }
+
+    /// Move the active end of the selection to `offset`, and collapse the selection to
+    /// a caret unless `extend` is true.
+    fn move_caret(&mut self, offset: usize, affinity: Affinity, extend: bool) {
+        self.selection = if extend {
+            Selection::new(self.selection.anchor, offset, affinity)
+        } else {
+            Selection::caret(offset, affinity)
+        };
+        self.needs_selection_update = true;
+    }
+
     /// Call when this widget becomes focused
     pub fn focus_gained(&mut self) {
         self.selection_visible = true;
@@ -255,7 +429,21 @@ impl<T: Selectable> TextWithSelection<T> {
                             );
                         }
                     }
-                    attributes(builder)
+                    let mut builder = attributes(builder);
+                    // The block caret is drawn behind the text, so the character it covers
+                    // needs a color which stands out against it.
+                    if self.selection_visible && self.caret_style.shape == CaretShape::Block {
+                        let active = self.selection.active;
+                        if let Some(end) = self.text.next_grapheme_offset(active) {
+                            builder.push(
+                                &parley::style::StyleProperty::Brush(
+                                    self.caret_style.block_text_color().into(),
+                                ),
+                                active..end,
+                            );
+                        }
+                    }
+                    builder
                 },
             );
             self.needs_selection_update = false;
@@ -266,22 +454,38 @@ impl<T: Selectable> TextWithSelection<T> {
     pub fn draw(&mut self, scene: &mut Scene, point: impl Into<Point>) {
         // TODO: Calculate the location for this in layout lazily?
         if self.selection_visible {
-            self.cursor_line = self
-                .layout
-                .caret_line_from_byte_index(self.selection.active);
+            self.cursor_line = match self.caret_style.shape {
+                CaretShape::Bar => self
+                    .layout
+                    .caret_line_from_byte_index(self.selection.active),
+                CaretShape::Block => None,
+            };
+            self.cursor_rect = match self.caret_style.shape {
+                CaretShape::Bar => None,
+                CaretShape::Block => self
+                    .layout
+                    .block_caret_from_byte_index(self.selection.active),
+            };
         } else {
             self.cursor_line = None;
+            self.cursor_rect = None;
         }
         let point: Point = point.into();
+        let transform = Affine::translate((point.x, point.y));
+        let brush = Brush::Solid(self.caret_style.color);
         if let Some(line) = self.cursor_line {
             scene.stroke(
-                &Stroke::new(2.),
-                Affine::translate((point.x, point.y)),
-                &Brush::Solid(Color::WHITE),
+                &Stroke::new(self.caret_style.width),
+                transform,
+                &brush,
                 None,
                 &line,
             );
         }
+        // The block is drawn below the text, which gives the character it covers a contrasting color.
+        if let Some(rect) = self.cursor_rect {
+            scene.fill(vello::peniko::Fill::NonZero, transform, &brush, None, &rect);
+        }
         self.layout.draw(scene, point);
     }
 
@@ -403,21 +607,6 @@ impl<T: Selectable> TextWithSelection<T> {
     }
 }
 
-/// Get the key which should be used for shortcuts from the underlying event
-///
-/// `key_without_modifiers` is only available on some platforms
-fn shortcut_key(key: &winit::event::KeyEvent) -> winit::keyboard::Key {
-    #[cfg(not(any(target_os = "android", target_os = "ios")))]
-    {
-        use winit::platform::modifier_supplement::KeyEventExtModifierSupplement;
-        key.key_without_modifiers()
-    }
-    #[cfg(any(target_os = "android", target_os = "ios"))]
-    // We think it will be rare that users are using a physical keyboard with Android,
-    // and so we don't really need to worry *too much* about the text selection shortcuts
-    key.logical_key.clone()
-}
-
 impl<T: Selectable> Deref for TextWithSelection<T> {
     type Target = TextLayout;
 
@@ -956,4 +1145,17 @@ mod tests {
         assert_eq!(b.len(), b.next_line_break(13));
         assert_eq!(b.len(), b.next_line_break(19));
     }
+
+    #[test]
+    fn block_text_color() {
+        let style = |color| CaretStyle {
+            shape: CaretShape::Block,
+            color,
+            width: 1.,
+        };
+        assert_eq!(style(Color::WHITE).block_text_color(), Color::BLACK);
+        assert_eq!(style(Color::LIGHT_GRAY).block_text_color(), Color::BLACK);
+        assert_eq!(style(Color::BLACK).block_text_color(), Color::WHITE);
+        assert_eq!(style(Color::NAVY).block_text_color(), Color::WHITE);
+    }
 }
@@ -0,0 +1,171 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inherited text styling.
+
+use parley::fontique::Weight;
+use parley::style::{FontFamily, FontStack, GenericFamily};
+use parley::{FontContext, LayoutContext};
+use vello::kurbo::Size;
+
+use crate::text::{TextBrush, TextLayout};
+use crate::{theme, Env};
+
+/// A set of text properties which are inherited by the descendants of a widget.
+///
+/// Any property left as `None` is inherited from the closest ancestor which sets it,
+/// and falls back to the theme values of the widget's [`Env`] if no ancestor does.
+///
+/// A widget sets the style for its subtree with [`EventCtx::set_text_style`], and reads
+/// the resolved style with [`EventCtx::text_style`]. Text widgets are notified of changes
+/// with [`Update::TextStyleChanged`].
+///
+/// [`EventCtx::set_text_style`]: crate::EventCtx::set_text_style
+/// [`EventCtx::text_style`]: crate::EventCtx::text_style
+/// [`Update::TextStyleChanged`]: crate::Update::TextStyleChanged
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    pub font: Option<FontStack<'static>>,
+    pub text_size: Option<f32>,
+    pub weight: Option<Weight>,
+    pub brush: Option<TextBrush>,
+}
+
+impl TextStyle {
+    /// Create a style which inherits every property.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_font(mut self, font: FontStack<'static>) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn with_font_family(self, font: FontFamily<'static>) -> Self {
+        self.with_font(FontStack::Single(font))
+    }
+
+    #[doc(alias = "with_font_size")]
+    pub fn with_text_size(mut self, size: f32) -> Self {
+        self.text_size = Some(size);
+        self
+    }
+
+    pub fn with_weight(mut self, weight: Weight) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    #[doc(alias = "with_text_color")]
+    pub fn with_text_brush(mut self, brush: impl Into<TextBrush>) -> Self {
+        self.brush = Some(brush.into());
+        self
+    }
+
+    /// Returns a copy of this style where unset properties are taken from `parent`.
+    pub fn inherit_from(&self, parent: &TextStyle) -> TextStyle {
+        TextStyle {
+            font: self.font.or(parent.font),
+            text_size: self.text_size.or(parent.text_size),
+            weight: self.weight.or(parent.weight),
+            brush: self.brush.clone().or_else(|| parent.brush.clone()),
+        }
+    }
+
+    /// The brush text should be drawn with when enabled.
+    ///
+    /// Falls back to the [`TEXT_COLOR`](theme::TEXT_COLOR) of `env`.
+    pub fn brush_or_default(&self, env: &Env) -> TextBrush {
+        self.brush
+            .clone()
+            .unwrap_or_else(|| env.get(theme::TEXT_COLOR).into())
+    }
+
+    /// Apply the font, size and weight of this style to `layout`, using the theme
+    /// values of `env` for unset properties.
+    ///
+    /// The brush isn't applied, since text widgets pick it depending on their
+    /// disabled state; see [`brush_or_default`](Self::brush_or_default).
+    pub fn apply_to_layout(&self, layout: &mut TextLayout, env: &Env) {
+        layout.set_font(self.font.unwrap_or(FontStack::Single(FontFamily::Generic(
+            GenericFamily::SansSerif,
+        ))));
+        layout.set_text_size(
+            self.text_size
+                .unwrap_or(env.get(theme::TEXT_SIZE_NORMAL) as f32),
+        );
+        layout.set_weight(self.weight.unwrap_or(Weight::NORMAL));
+    }
+}
+
+/// Measure the size `text` would have if displayed with `style`, without creating a widget.
+///
+/// Properties which `style` leaves unset are taken from the theme values of `env`.
+/// Lines are broken at `max_advance`, if provided. The returned size excludes trailing
+/// whitespace, like [`TextLayout::size`].
+///
+/// This is useful for layout decisions which depend on data, such as picking column widths.
+/// Most code should use [`LayoutCtx::measure_text`] or [`RenderRoot::measure_text`] instead of
+/// calling this directly.
+///
+/// [`LayoutCtx::measure_text`]: crate::LayoutCtx::measure_text
+/// [`RenderRoot::measure_text`]: crate::RenderRoot::measure_text
+pub fn measure_text(
+    font_ctx: &mut FontContext,
+    layout_ctx: &mut LayoutContext<TextBrush>,
+    text: &str,
+    style: &TextStyle,
+    env: &Env,
+    max_advance: Option<f32>,
+) -> Size {
+    let mut layout = TextLayout::default();
+    style.apply_to_layout(&mut layout, env);
+    layout.set_max_advance(max_advance);
+    layout.rebuild(font_ctx, layout_ctx, text, true);
+    layout.size()
+}
+
+/// The vertical metrics of a line of text displayed with a given style.
+///
+/// These are in logical pixels, so they don't depend on the scale factor of the window.
+/// See [`text_metrics`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextMetrics {
+    /// The height of a line, which is also the distance between the baselines of
+    /// consecutive lines.
+    pub line_height: f64,
+    /// The distance from the top of a line to its baseline.
+    pub ascent: f64,
+    /// The distance from the baseline of a line to its bottom, as a positive number.
+    pub descent: f64,
+}
+
+/// Resolve the metrics of a line of text displayed with `style`, and the theme values of `env`.
+///
+/// This lets embedders align native overlays, such as IME candidate windows or native
+/// dropdowns, with the text of a widget. Most code should use [`LayoutCtx::text_metrics`]
+/// or [`RenderRoot::text_metrics`] instead of calling this directly.
+///
+/// [`LayoutCtx::text_metrics`]: crate::LayoutCtx::text_metrics
+/// [`RenderRoot::text_metrics`]: crate::RenderRoot::text_metrics
+pub fn text_metrics(
+    font_ctx: &mut FontContext,
+    layout_ctx: &mut LayoutContext<TextBrush>,
+    style: &TextStyle,
+    env: &Env,
+) -> TextMetrics {
+    let mut layout = TextLayout::default();
+    style.apply_to_layout(&mut layout, env);
+    // A space has the metrics of the font, without depending on the glyphs of the text.
+    layout.rebuild(font_ctx, layout_ctx, " ", true);
+    let Some(line) = layout.layout().get(0) else {
+        return TextMetrics::default();
+    };
+    let metrics = line.metrics();
+    TextMetrics {
+        line_height: metrics.size().into(),
+        ascent: metrics.ascent.into(),
+        descent: metrics.descent.into(),
+    }
+}
@@ -12,7 +12,7 @@ use parley::layout::{Alignment, Cursor};
 use parley::style::{FontFamily, FontStack, GenericFamily, StyleProperty};
 use parley::{FontContext, Layout, LayoutContext};
 use unicode_segmentation::UnicodeSegmentation;
-use vello::kurbo::{Affine, Line, Point, Size};
+use vello::kurbo::{Affine, Line, Point, Rect, Size};
 use vello::peniko::{self, Color, Gradient};
 use vello::Scene;
 
@@ -48,10 +48,21 @@ pub struct TextLayout {
     text_size: f32,
     weight: Weight,
     style: Style,
+    underline: bool,
 
     alignment: Alignment,
     max_advance: Option<f32>,
 
+    /// How to snap glyphs to physical pixels, if at all.
+    glyph_snapping: Option<GlyphSnapping>,
+    /// The unit line heights are rounded up to a multiple of, if any.
+    line_height_grid: Option<f32>,
+    /// The line height factor which puts lines on `line_height_grid`.
+    ///
+    /// This is cached since computing it requires a layout of its own. It is reset
+    /// whenever the font metrics or the grid change.
+    grid_line_height: Option<f32>,
+
     needs_layout: bool,
     needs_line_breaks: bool,
     pub(crate) layout: Layout<TextBrush>,
@@ -103,6 +114,34 @@ impl Hinting {
     }
 }
 
+/// Whether text is positioned at subpixel offsets, or snapped to physical pixels.
+///
+/// Subpixel positioning keeps text moving smoothly, but can make it look blurry.
+/// Snapped text is crisp, but "swims" when it moves by fractions of a pixel.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TextPositioning {
+    /// Always position glyphs at subpixel offsets.
+    Subpixel,
+    /// Always snap glyphs to physical pixels.
+    Snapped,
+    /// Position glyphs at subpixel offsets while an animation is running,
+    /// and snap them to physical pixels once it ends.
+    #[default]
+    Auto,
+}
+
+/// How glyphs are snapped to physical pixels, see [`TextLayout::set_glyph_snapping`].
+///
+/// Glyphs are drawn in the local coordinates of a widget, so snapping them to the pixel
+/// grid of the window requires knowing where that widget is.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlyphSnapping {
+    /// The scale factor of the window.
+    pub scale: f64,
+    /// The origin of the widget the text is drawn in, in window coordinates.
+    pub window_origin: Point,
+}
+
 /// A custom brush for `Parley`, enabling using Parley to pass-through
 /// which glyphs are selected/highlighted
 #[derive(Clone, Debug, PartialEq)]
@@ -172,15 +211,20 @@ impl TextLayout {
         TextLayout {
             scale: 1.0,
 
-            brush: crate::theme::TEXT_COLOR.into(),
+            brush: (*crate::theme::TEXT_COLOR.default_value()).into(),
             font: FontStack::Single(FontFamily::Generic(GenericFamily::SansSerif)),
             text_size,
             weight: Weight::NORMAL,
             style: Style::Normal,
+            underline: false,
 
             max_advance: None,
             alignment: Default::default(),
 
+            glyph_snapping: None,
+            line_height_grid: None,
+            grid_line_height: None,
+
             needs_layout: true,
             needs_line_breaks: true,
             layout: Layout::new(),
@@ -204,6 +248,34 @@ impl TextLayout {
     pub fn set_scale(&mut self, scale: f32) {
         if scale != self.scale {
             self.scale = scale;
+            self.grid_line_height = None;
+            self.invalidate();
+        }
+    }
+
+    /// Set whether glyphs are snapped to physical pixels when drawn.
+    ///
+    /// `None` positions glyphs at subpixel offsets. Widgets should generally pass
+    /// [`PaintCtx::glyph_snapping`].
+    ///
+    /// This doesn't require the layout to be rebuilt.
+    ///
+    /// [`PaintCtx::glyph_snapping`]: crate::PaintCtx::glyph_snapping
+    pub fn set_glyph_snapping(&mut self, snapping: Option<GlyphSnapping>) {
+        self.glyph_snapping = snapping;
+    }
+
+    /// Round the height of lines up to a multiple of `unit`, or use the natural line height
+    /// of the font with `None`.
+    ///
+    /// Widgets should generally pass [`LayoutCtx::baseline_grid`].
+    ///
+    /// [`LayoutCtx::baseline_grid`]: crate::LayoutCtx::baseline_grid
+    pub fn set_line_height_grid(&mut self, unit: Option<f64>) {
+        let unit = unit.map(|unit| unit as f32);
+        if unit != self.line_height_grid {
+            self.line_height_grid = unit;
+            self.grid_line_height = None;
             self.invalidate();
         }
     }
@@ -225,6 +297,7 @@ impl TextLayout {
     pub fn set_font(&mut self, font: FontStack<'static>) {
         if font != self.font {
             self.font = font;
+            self.grid_line_height = None;
             self.invalidate();
         }
     }
@@ -234,6 +307,7 @@ impl TextLayout {
     pub fn set_text_size(&mut self, size: f32) {
         if size != self.text_size {
             self.text_size = size;
+            self.grid_line_height = None;
             self.invalidate();
         }
     }
@@ -242,6 +316,7 @@ impl TextLayout {
     pub fn set_weight(&mut self, weight: Weight) {
         if weight != self.weight {
             self.weight = weight;
+            self.grid_line_height = None;
             self.invalidate();
         }
     }
@@ -250,6 +325,15 @@ impl TextLayout {
     pub fn set_style(&mut self, style: Style) {
         if style != self.style {
             self.style = style;
+            self.grid_line_height = None;
+            self.invalidate();
+        }
+    }
+
+    /// Set whether the text is underlined.
+    pub fn set_underline(&mut self, underline: bool) {
+        if underline != self.underline {
+            self.underline = underline;
             self.invalidate();
         }
     }
@@ -377,6 +461,64 @@ impl TextLayout {
         Some(Line::new(p1, p2))
     }
 
+    /// Given the utf-8 position of a character boundary in the underlying text,
+    /// return a `Rect` suitable for drawing a block cursor over the character after it.
+    ///
+    /// At the end of a line, the block is half as wide as the line is tall.
+    ///
+    /// This is not meaningful until [`Self::rebuild`] has been called.
+    pub fn block_caret_from_byte_index(&self, byte_index: usize) -> Option<Rect> {
+        let caret = Cursor::from_position(&self.layout, byte_index, true);
+
+        let line = caret.path.line(&self.layout)?;
+        let line_metrics = line.metrics();
+
+        let baseline = line_metrics.baseline + line_metrics.descent;
+        let line_size = line_metrics.size();
+        let at_line_end = caret.text_start >= line.text_range().end;
+        let width = if at_line_end || caret.advance <= 0.0 {
+            line_size / 2.0
+        } else {
+            caret.advance
+        };
+        Some(Rect::new(
+            caret.offset as f64,
+            (baseline - line_size) as f64,
+            (caret.offset + width) as f64,
+            baseline as f64,
+        ))
+    }
+
+    /// Return the area after the end of each line which was wrapped to fit the max advance,
+    /// rather than ending with a line break in `text`.
+    ///
+    /// Each `Rect` spans from the end of the line's text to the max advance, and covers the
+    /// height of the line. This is meant for drawing soft wrap indicators, as in code editors.
+    ///
+    /// This is not meaningful until [`Self::rebuild`] has been called.
+    pub fn soft_wrap_rects(&self, text: &str) -> Vec<Rect> {
+        self.assert_rebuilt("soft_wrap_rects");
+
+        let width = self.max_advance.unwrap_or(self.layout.width());
+        let line_count = self.layout.len();
+        self.layout
+            .lines()
+            .take(line_count.saturating_sub(1))
+            .filter(|line| !text[line.text_range()].ends_with(['\n', '\r']))
+            .map(|line| {
+                let metrics = line.metrics();
+                let bottom = metrics.baseline + metrics.descent;
+                let end = metrics.offset + metrics.advance - metrics.trailing_whitespace;
+                Rect::new(
+                    end as f64,
+                    (bottom - metrics.size()) as f64,
+                    width.max(end) as f64,
+                    bottom as f64,
+                )
+            })
+            .collect()
+    }
+
     /// Rebuild the inner layout as needed.
     ///
     /// This `TextLayout` object manages a lower-level layout object that may
@@ -420,12 +562,24 @@ impl TextLayout {
             // Workaround for how parley treats empty lines.
             //let text = if !text.is_empty() { text } else { " " };
 
+            let line_height = match (self.line_height_grid, self.grid_line_height) {
+                (Some(_), Some(factor)) => factor,
+                (Some(unit), None) => {
+                    let factor = self.line_height_on_grid(font_ctx, layout_ctx, unit);
+                    self.grid_line_height = Some(factor);
+                    factor
+                }
+                (None, _) => 1.,
+            };
+
             let mut builder = layout_ctx.ranged_builder(font_ctx, text, self.scale);
             builder.push_default(&StyleProperty::Brush(self.brush.clone()));
             builder.push_default(&StyleProperty::FontSize(self.text_size));
             builder.push_default(&StyleProperty::FontStack(self.font));
             builder.push_default(&StyleProperty::FontWeight(self.weight));
             builder.push_default(&StyleProperty::FontStyle(self.style));
+            builder.push_default(&StyleProperty::Underline(self.underline));
+            builder.push_default(&StyleProperty::LineHeight(line_height));
 
             // Currently, this is used for:
             // - underlining IME suggestions
@@ -442,6 +596,53 @@ impl TextLayout {
         }
     }
 
+    /// The line height factor which rounds the natural line height of the font up to a
+    /// multiple of `unit`.
+    fn line_height_on_grid(
+        &self,
+        font_ctx: &mut FontContext,
+        layout_ctx: &mut LayoutContext<TextBrush>,
+        unit: f32,
+    ) -> f32 {
+        // A space has the metrics of the font, without depending on the glyphs of the text.
+        let mut builder = layout_ctx.ranged_builder(font_ctx, " ", self.scale);
+        builder.push_default(&StyleProperty::FontSize(self.text_size));
+        builder.push_default(&StyleProperty::FontStack(self.font));
+        builder.push_default(&StyleProperty::FontWeight(self.weight));
+        builder.push_default(&StyleProperty::FontStyle(self.style));
+        let mut layout = builder.build();
+        layout.break_all_lines(None, Alignment::Start);
+        let Some(run) = layout.get(0).and_then(|line| line.runs().next()) else {
+            return 1.;
+        };
+        let metrics = run.metrics();
+        let (ascent, descent, leading) = (metrics.ascent, metrics.descent, metrics.leading);
+
+        // Parley rounds the metrics of each line separately, so we search for the smallest
+        // factor whose rounded line height reaches the next multiple of the unit.
+        let line_height = |factor: f32| {
+            (ascent * factor).round()
+                + (descent * factor).round()
+                + (leading * factor * 0.5).round() * 2.
+        };
+        let natural = line_height(1.);
+        if natural <= 0. {
+            return 1.;
+        }
+        let unit = unit * self.scale;
+        let target = (natural / unit).ceil() * unit;
+        let (mut low, mut high) = (1_f32, (target + 2.) / (ascent + descent + leading));
+        for _ in 0..32 {
+            let mid = (low + high) / 2.;
+            if line_height(mid) >= target {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        high
+    }
+
     /// Draw the layout at the provided `Point`.
     ///
     /// The origin of the layout is the top-left corner.
@@ -457,6 +658,7 @@ impl TextLayout {
             &mut self.scratch_scene,
             Affine::translate((p.x, p.y)),
             &self.layout,
+            self.glyph_snapping,
         );
     }
 
@@ -570,8 +772,10 @@ impl std::fmt::Debug for TextLayout {
             .field("text_size", &self.text_size)
             .field("weight", &self.weight)
             .field("style", &self.style)
+            .field("underline", &self.underline)
             .field("alignment", &self.alignment)
             .field("wrap_width", &self.max_advance)
+            .field("glyph_snapping", &self.glyph_snapping)
             .field("outdated?", &self.needs_rebuild())
             .field("width", &self.layout.width())
             .field("height", &self.layout.height())
@@ -581,6 +785,6 @@ impl std::fmt::Debug for TextLayout {
 
 impl Default for TextLayout {
     fn default() -> Self {
-        Self::new(crate::theme::TEXT_SIZE_NORMAL as f32)
+        Self::new(*crate::theme::TEXT_SIZE_NORMAL.default_value() as f32)
     }
 }
@@ -7,56 +7,213 @@
 
 use vello::peniko::Color;
 
-use crate::Insets;
+use crate::{Env, Insets, Key};
 
 // Colors are from https://sashat.me/2017/01/11/list-of-20-simple-distinct-colors/
 // They're picked for visual distinction and accessibility (99 percent)
 
-pub const WINDOW_BACKGROUND_COLOR: Color = Color::rgb8(0x29, 0x29, 0x29);
-pub const TEXT_COLOR: Color = Color::rgb8(0xf0, 0xf0, 0xea);
-pub const DISABLED_TEXT_COLOR: Color = Color::rgb8(0xa0, 0xa0, 0x9a);
-pub const PLACEHOLDER_COLOR: Color = Color::rgb8(0x80, 0x80, 0x80);
-pub const PRIMARY_LIGHT: Color = Color::rgb8(0x5c, 0xc4, 0xff);
-pub const PRIMARY_DARK: Color = Color::rgb8(0x00, 0x8d, 0xdd);
-pub const PROGRESS_BAR_RADIUS: f64 = 4.;
-pub const BACKGROUND_LIGHT: Color = Color::rgb8(0x3a, 0x3a, 0x3a);
-pub const BACKGROUND_DARK: Color = Color::rgb8(0x31, 0x31, 0x31);
-pub const FOREGROUND_LIGHT: Color = Color::rgb8(0xf9, 0xf9, 0xf9);
-pub const FOREGROUND_DARK: Color = Color::rgb8(0xbf, 0xbf, 0xbf);
-pub const DISABLED_FOREGROUND_LIGHT: Color = Color::rgb8(0x89, 0x89, 0x89);
-pub const DISABLED_FOREGROUND_DARK: Color = Color::rgb8(0x6f, 0x6f, 0x6f);
-pub const BUTTON_DARK: Color = Color::BLACK;
-pub const BUTTON_LIGHT: Color = Color::rgb8(0x21, 0x21, 0x21);
-pub const DISABLED_BUTTON_DARK: Color = Color::rgb8(0x28, 0x28, 0x28);
-pub const DISABLED_BUTTON_LIGHT: Color = Color::rgb8(0x38, 0x38, 0x38);
-pub const BUTTON_BORDER_RADIUS: f64 = 4.;
-pub const BUTTON_BORDER_WIDTH: f64 = 2.;
-pub const BORDER_DARK: Color = Color::rgb8(0x3a, 0x3a, 0x3a);
-pub const BORDER_LIGHT: Color = Color::rgb8(0xa1, 0xa1, 0xa1);
-pub const SELECTED_TEXT_BACKGROUND_COLOR: Color = Color::rgb8(0x43, 0x70, 0xA8);
-pub const SELECTED_TEXT_INACTIVE_BACKGROUND_COLOR: Color = Color::rgb8(0x74, 0x74, 0x74);
-pub const SELECTION_TEXT_COLOR: Color = Color::rgb8(0x00, 0x00, 0x00);
-pub const CURSOR_COLOR: Color = Color::WHITE;
-pub const TEXT_SIZE_NORMAL: f64 = 15.0;
-pub const TEXT_SIZE_LARGE: f64 = 24.0;
-pub const BASIC_WIDGET_HEIGHT: f64 = 18.0;
-pub const WIDE_WIDGET_WIDTH: f64 = 100.;
-pub const BORDERED_WIDGET_HEIGHT: f64 = 24.0;
-pub const TEXTBOX_BORDER_RADIUS: f64 = 2.;
-pub const TEXTBOX_BORDER_WIDTH: f64 = 1.;
-pub const TEXTBOX_INSETS: Insets = Insets::new(4.0, 4.0, 4.0, 4.0);
-pub const SCROLLBAR_COLOR: Color = Color::rgb8(0xff, 0xff, 0xff);
-pub const SCROLLBAR_BORDER_COLOR: Color = Color::rgb8(0x77, 0x77, 0x77);
+// Theme values are read from the [`Env`](crate::Env) of each widget, e.g. with
+// `ctx.env().get(theme::TEXT_COLOR)`, so that they can be overridden for the whole window or a
+// subtree. The values below are the defaults.
+
+pub const WINDOW_BACKGROUND_COLOR: Key<Color> = Key::new(
+    "masonry.theme.window_background_color",
+    Color::rgb8(0x29, 0x29, 0x29),
+);
+pub const TEXT_COLOR: Key<Color> =
+    Key::new("masonry.theme.text_color", Color::rgb8(0xf0, 0xf0, 0xea));
+pub const DISABLED_TEXT_COLOR: Key<Color> = Key::new(
+    "masonry.theme.disabled_text_color",
+    Color::rgb8(0xa0, 0xa0, 0x9a),
+);
+pub const PLACEHOLDER_COLOR: Key<Color> = Key::new(
+    "masonry.theme.placeholder_color",
+    Color::rgb8(0x80, 0x80, 0x80),
+);
+pub const PRIMARY_LIGHT: Key<Color> =
+    Key::new("masonry.theme.primary_light", Color::rgb8(0x5c, 0xc4, 0xff));
+pub const PRIMARY_DARK: Key<Color> =
+    Key::new("masonry.theme.primary_dark", Color::rgb8(0x00, 0x8d, 0xdd));
+pub const LINK_COLOR: Key<Color> =
+    Key::new("masonry.theme.link_color", Color::rgb8(0x5c, 0xc4, 0xff));
+pub const VISITED_LINK_COLOR: Key<Color> = Key::new(
+    "masonry.theme.visited_link_color",
+    Color::rgb8(0xb3, 0x8c, 0xff),
+);
+pub const PROGRESS_BAR_RADIUS: Key<f64> = Key::new("masonry.theme.progress_bar_radius", 4.);
+pub const BACKGROUND_LIGHT: Key<Color> = Key::new(
+    "masonry.theme.background_light",
+    Color::rgb8(0x3a, 0x3a, 0x3a),
+);
+pub const BACKGROUND_DARK: Key<Color> = Key::new(
+    "masonry.theme.background_dark",
+    Color::rgb8(0x31, 0x31, 0x31),
+);
+pub const FOREGROUND_LIGHT: Key<Color> = Key::new(
+    "masonry.theme.foreground_light",
+    Color::rgb8(0xf9, 0xf9, 0xf9),
+);
+pub const FOREGROUND_DARK: Key<Color> = Key::new(
+    "masonry.theme.foreground_dark",
+    Color::rgb8(0xbf, 0xbf, 0xbf),
+);
+pub const DISABLED_FOREGROUND_LIGHT: Key<Color> = Key::new(
+    "masonry.theme.disabled_foreground_light",
+    Color::rgb8(0x89, 0x89, 0x89),
+);
+pub const DISABLED_FOREGROUND_DARK: Key<Color> = Key::new(
+    "masonry.theme.disabled_foreground_dark",
+    Color::rgb8(0x6f, 0x6f, 0x6f),
+);
+/// The color at the top of the background gradient of secondary buttons.
+pub const BUTTON_COLOR: Key<Color> =
+    Key::new("masonry.theme.button_color", Color::rgb8(0x21, 0x21, 0x21));
+/// The color at the bottom of the background gradient of secondary buttons.
+pub const BUTTON_DARK_COLOR: Key<Color> = Key::new("masonry.theme.button_dark_color", Color::BLACK);
+pub const DISABLED_BUTTON_DARK: Key<Color> = Key::new(
+    "masonry.theme.disabled_button_dark",
+    Color::rgb8(0x28, 0x28, 0x28),
+);
+pub const DISABLED_BUTTON_LIGHT: Key<Color> = Key::new(
+    "masonry.theme.disabled_button_light",
+    Color::rgb8(0x38, 0x38, 0x38),
+);
+pub const DESTRUCTIVE_LIGHT: Key<Color> = Key::new(
+    "masonry.theme.destructive_light",
+    Color::rgb8(0xe0, 0x4f, 0x4f),
+);
+pub const DESTRUCTIVE_DARK: Key<Color> = Key::new(
+    "masonry.theme.destructive_dark",
+    Color::rgb8(0xb0, 0x2a, 0x2a),
+);
+/// The corner radius of buttons, and of the controls drawn like them, e.g. menu buttons
+/// and segmented controls.
+pub const BUTTON_RADIUS: Key<f64> = Key::new("masonry.theme.button_radius", 4.);
+pub const BUTTON_BORDER_WIDTH: Key<f64> = Key::new("masonry.theme.button_border_width", 2.);
+pub const BORDER_DARK: Key<Color> =
+    Key::new("masonry.theme.border_dark", Color::rgb8(0x3a, 0x3a, 0x3a));
+pub const BORDER_LIGHT: Key<Color> =
+    Key::new("masonry.theme.border_light", Color::rgb8(0xa1, 0xa1, 0xa1));
+pub const SELECTED_TEXT_BACKGROUND_COLOR: Key<Color> = Key::new(
+    "masonry.theme.selected_text_background_color",
+    Color::rgb8(0x43, 0x70, 0xA8),
+);
+pub const SELECTED_TEXT_INACTIVE_BACKGROUND_COLOR: Key<Color> = Key::new(
+    "masonry.theme.selected_text_inactive_background_color",
+    Color::rgb8(0x74, 0x74, 0x74),
+);
+pub const SELECTION_TEXT_COLOR: Key<Color> = Key::new(
+    "masonry.theme.selection_text_color",
+    Color::rgb8(0x00, 0x00, 0x00),
+);
+pub const CURSOR_COLOR: Key<Color> = Key::new("masonry.theme.cursor_color", Color::WHITE);
+pub const CURSOR_WIDTH: Key<f64> = Key::new("masonry.theme.cursor_width", 2.);
+pub const TEXT_SIZE_NORMAL: Key<f64> = Key::new("masonry.theme.text_size_normal", 15.0);
+pub const TEXT_SIZE_LARGE: Key<f64> = Key::new("masonry.theme.text_size_large", 24.0);
+pub const ICON_SIZE: Key<f64> = Key::new("masonry.theme.icon_size", 16.0);
+pub const BASIC_WIDGET_HEIGHT: Key<f64> = Key::new("masonry.theme.basic_widget_height", 18.0);
+pub const WIDE_WIDGET_WIDTH: Key<f64> = Key::new("masonry.theme.wide_widget_width", 100.);
+pub const BORDERED_WIDGET_HEIGHT: Key<f64> = Key::new("masonry.theme.bordered_widget_height", 24.0);
+pub const TEXTBOX_BORDER_RADIUS: Key<f64> = Key::new("masonry.theme.textbox_border_radius", 2.);
+pub const TEXTBOX_BORDER_WIDTH: Key<f64> = Key::new("masonry.theme.textbox_border_width", 1.);
+/// The gap between a widget focused with the keyboard and its focus ring.
+pub const FOCUS_RING_OFFSET: Key<f64> = Key::new("masonry.theme.focus_ring_offset", 1.);
+pub const FOCUS_RING_RADIUS: Key<f64> = Key::new("masonry.theme.focus_ring_radius", 4.);
+/// The color of the ring painted around the widget focused with the keyboard.
+pub const FOCUS_RING_COLOR: Key<Color> = Key::new(
+    "masonry.theme.focus_ring_color",
+    Color::rgb8(0x5c, 0xc4, 0xff),
+);
+pub const FOCUS_RING_WIDTH: Key<f64> = Key::new("masonry.theme.focus_ring_width", 2.);
+pub const TEXTBOX_INSETS: Key<Insets> = Key::new(
+    "masonry.theme.textbox_insets",
+    Insets::new(4.0, 4.0, 4.0, 4.0),
+);
+pub const SCRIM_COLOR: Key<Color> = Key::new(
+    "masonry.theme.scrim_color",
+    Color::rgba8(0x00, 0x00, 0x00, 0x99),
+);
+pub const SCROLLBAR_COLOR: Key<Color> = Key::new(
+    "masonry.theme.scrollbar_color",
+    Color::rgb8(0xff, 0xff, 0xff),
+);
+pub const SCROLLBAR_BORDER_COLOR: Key<Color> = Key::new(
+    "masonry.theme.scrollbar_border_color",
+    Color::rgb8(0x77, 0x77, 0x77),
+);
+pub const TOOLTIP_INSETS: Key<Insets> = Key::new(
+    "masonry.theme.tooltip_insets",
+    Insets::new(6.0, 3.0, 6.0, 3.0),
+);
+/// The distance between the pointer and the top of the tooltip.
+pub const TOOLTIP_OFFSET: Key<f64> = Key::new("masonry.theme.tooltip_offset", 18.0);
+pub const SCROLLBAR_WIDTH: Key<f64> = Key::new("masonry.theme.scrollbar_width", 8.);
+pub const SCROLLBAR_PAD: Key<f64> = Key::new("masonry.theme.scrollbar_pad", 2.);
+pub const SCROLLBAR_MIN_SIZE: Key<f64> = Key::new("masonry.theme.scrollbar_min_size", 45.);
+pub const SCROLLBAR_RADIUS: Key<f64> = Key::new("masonry.theme.scrollbar_radius", 5.);
+pub const SCROLLBAR_EDGE_WIDTH: Key<f64> = Key::new("masonry.theme.scrollbar_edge_width", 1.);
+pub const MINIMAP_WIDTH: Key<f64> = Key::new("masonry.theme.minimap_width", 100.);
+pub const WIDGET_PADDING_VERTICAL: Key<f64> =
+    Key::new("masonry.theme.widget_padding_vertical", 10.0);
+pub const WIDGET_PADDING_HORIZONTAL: Key<f64> =
+    Key::new("masonry.theme.widget_padding_horizontal", 8.0);
+pub const WIDGET_CONTROL_COMPONENT_PADDING: Key<f64> =
+    Key::new("masonry.theme.widget_control_component_padding", 4.0);
+
+// --- MARK: LIGHT SCHEME ---
+
+/// The theme values which differ from the defaults in the light color scheme.
+///
+/// While the system is in the [light](crate::WindowTheme::Light) color scheme, the root env
+/// takes these values for the keys which [`RenderRoot::set_env`](crate::RenderRoot::set_env)
+/// doesn't set.
+pub fn light_env() -> Env {
+    Env::new()
+        .with(WINDOW_BACKGROUND_COLOR, Color::rgb8(0xf5, 0xf5, 0xf5))
+        .with(TEXT_COLOR, Color::rgb8(0x1a, 0x1a, 0x1a))
+        .with(DISABLED_TEXT_COLOR, Color::rgb8(0x80, 0x80, 0x7a))
+        .with(PLACEHOLDER_COLOR, Color::rgb8(0x90, 0x90, 0x90))
+        .with(PRIMARY_LIGHT, Color::rgb8(0x00, 0x8d, 0xdd))
+        .with(PRIMARY_DARK, Color::rgb8(0x00, 0x6a, 0xa6))
+        .with(LINK_COLOR, Color::rgb8(0x00, 0x6a, 0xc4))
+        .with(VISITED_LINK_COLOR, Color::rgb8(0x6e, 0x3f, 0xc8))
+        .with(BACKGROUND_LIGHT, Color::rgb8(0xff, 0xff, 0xff))
+        .with(BACKGROUND_DARK, Color::rgb8(0xf0, 0xf0, 0xf0))
+        .with(FOREGROUND_LIGHT, Color::rgb8(0x1a, 0x1a, 0x1a))
+        .with(FOREGROUND_DARK, Color::rgb8(0x50, 0x50, 0x50))
+        .with(DISABLED_FOREGROUND_LIGHT, Color::rgb8(0xa0, 0xa0, 0xa0))
+        .with(DISABLED_FOREGROUND_DARK, Color::rgb8(0xc0, 0xc0, 0xc0))
+        .with(BUTTON_COLOR, Color::rgb8(0xfa, 0xfa, 0xfa))
+        .with(BUTTON_DARK_COLOR, Color::rgb8(0xdc, 0xdc, 0xdc))
+        .with(DISABLED_BUTTON_DARK, Color::rgb8(0xe0, 0xe0, 0xe0))
+        .with(DISABLED_BUTTON_LIGHT, Color::rgb8(0xec, 0xec, 0xec))
+        .with(BORDER_DARK, Color::rgb8(0xd0, 0xd0, 0xd0))
+        .with(BORDER_LIGHT, Color::rgb8(0x70, 0x70, 0x70))
+        .with(
+            SELECTED_TEXT_BACKGROUND_COLOR,
+            Color::rgb8(0xac, 0xce, 0xf7),
+        )
+        .with(
+            SELECTED_TEXT_INACTIVE_BACKGROUND_COLOR,
+            Color::rgb8(0xd4, 0xd4, 0xd4),
+        )
+        .with(CURSOR_COLOR, Color::BLACK)
+        .with(FOCUS_RING_COLOR, Color::rgb8(0x00, 0x8d, 0xdd))
+        .with(SCRIM_COLOR, Color::rgba8(0x00, 0x00, 0x00, 0x55))
+        .with(SCROLLBAR_COLOR, Color::rgb8(0x30, 0x30, 0x30))
+        .with(SCROLLBAR_BORDER_COLOR, Color::rgb8(0x90, 0x90, 0x90))
+}
+
+// --- MARK: CONSTANTS ---
+// Theme values which aren't in the env.
+
 pub const SCROLLBAR_MAX_OPACITY: f64 = 0.7;
 pub const SCROLLBAR_FADE_DELAY: u64 = 1500;
-pub const SCROLLBAR_WIDTH: f64 = 8.;
-pub const SCROLLBAR_PAD: f64 = 2.;
-pub const SCROLLBAR_MIN_SIZE: f64 = 45.;
-pub const SCROLLBAR_RADIUS: f64 = 5.;
-pub const SCROLLBAR_EDGE_WIDTH: f64 = 1.;
-pub const WIDGET_PADDING_VERTICAL: f64 = 10.0;
-pub const WIDGET_PADDING_HORIZONTAL: f64 = 8.0;
-pub const WIDGET_CONTROL_COMPONENT_PADDING: f64 = 4.0;
+/// How long the pointer has to rest on a widget before its tooltip is shown, in milliseconds.
+pub const TOOLTIP_DELAY: u64 = 600;
+/// The rhythm unit text line heights and vertical paddings are rounded to when the
+/// baseline grid is enabled; see [`RenderRoot::set_baseline_grid`](crate::RenderRoot::set_baseline_grid).
+pub const BASELINE_GRID_UNIT: f64 = 4.0;
 
 static DEBUG_COLOR: &[Color] = &[
     Color::rgb8(230, 25, 75),
@@ -18,7 +18,21 @@ use std::collections::HashMap;
 struct TreeNode<Item> {
     id: u64,
     item: Item,
-    children: Vec<TreeNode<Item>>,
+    children: TreeChildren<Item>,
+}
+
+/// Above this number of children, [`TreeChildren`] maintains an index map
+/// from child id to position.
+const CHILD_INDEX_THRESHOLD: usize = 16;
+
+/// The children of a node, stored contiguously.
+///
+/// Lookup by id is a linear scan for nodes with few children, which is the common
+/// case; nodes with a large fan-out also maintain a map from id to index so that
+/// child access stays O(1).
+struct TreeChildren<Item> {
+    nodes: Vec<TreeNode<Item>>,
+    index: HashMap<u64, usize>,
 }
 
 // TODO - ArenaRefChildren and ArenaMutChildren might be easier to document if they were
@@ -32,7 +46,7 @@ struct TreeNode<Item> {
 /// and its children.
 #[derive(Default)]
 pub struct TreeArena<Item> {
-    roots: Vec<TreeNode<Item>>,
+    roots: TreeChildren<Item>,
     parents_map: HashMap<u64, Option<u64>>,
 }
 
@@ -69,7 +83,7 @@ pub struct ArenaMut<'a, Item> {
 /// See [`ArenaRef`] for more information.
 pub struct ArenaRefChildren<'a, Item> {
     id: Option<u64>,
-    children: &'a Vec<TreeNode<Item>>,
+    children: &'a TreeChildren<Item>,
     parents_map: ArenaMapRef<'a>,
 }
 
@@ -78,7 +92,7 @@ pub struct ArenaRefChildren<'a, Item> {
 /// See [`ArenaMut`] for more information.
 pub struct ArenaMutChildren<'a, Item> {
     id: Option<u64>,
-    children: &'a mut Vec<TreeNode<Item>>,
+    children: &'a mut TreeChildren<Item>,
     parents_map: ArenaMapMut<'a>,
 }
 
@@ -109,11 +123,20 @@ impl<'a, Item> Clone for ArenaRefChildren<'a, Item> {
 
 impl<'a, Item> Copy for ArenaRefChildren<'a, Item> {}
 
+impl<Item> Default for TreeChildren<Item> {
+    fn default() -> Self {
+        TreeChildren {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
 impl<Item> TreeArena<Item> {
     /// Create an empty tree.
     pub fn new() -> Self {
         TreeArena {
-            roots: Vec::new(),
+            roots: TreeChildren::default(),
             parents_map: HashMap::new(),
         }
     }
@@ -179,6 +202,57 @@ impl<Item> TreeArena<Item> {
     }
 }
 
+impl<Item> TreeChildren<Item> {
+    fn position(&self, id: u64) -> Option<usize> {
+        if self.nodes.len() > CHILD_INDEX_THRESHOLD {
+            self.index.get(&id).copied()
+        } else {
+            self.nodes.iter().position(|child| child.id == id)
+        }
+    }
+
+    fn get(&self, id: u64) -> Option<&TreeNode<Item>> {
+        let i = self.position(id)?;
+        Some(&self.nodes[i])
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut TreeNode<Item>> {
+        let i = self.position(id)?;
+        Some(&mut self.nodes[i])
+    }
+
+    fn push(&mut self, node: TreeNode<Item>) {
+        self.nodes.push(node);
+        if self.nodes.len() > CHILD_INDEX_THRESHOLD {
+            if self.index.is_empty() {
+                self.rebuild_index(0);
+            } else {
+                self.index
+                    .insert(self.nodes.last().unwrap().id, self.nodes.len() - 1);
+            }
+        }
+    }
+
+    fn remove(&mut self, id: u64) -> Option<TreeNode<Item>> {
+        let i = self.position(id)?;
+        let node = self.nodes.remove(i);
+        if self.nodes.len() > CHILD_INDEX_THRESHOLD {
+            self.index.remove(&id);
+            self.rebuild_index(i);
+        } else {
+            self.index.clear();
+        }
+        Some(node)
+    }
+
+    /// Update the index map for all nodes starting at `start`.
+    fn rebuild_index(&mut self, start: usize) {
+        for (i, node) in self.nodes.iter().enumerate().skip(start) {
+            self.index.insert(node.id, i);
+        }
+    }
+}
+
 impl<Item> TreeNode<Item> {
     fn arena_ref<'a>(
         &'a self,
@@ -253,7 +327,7 @@ impl<'a, Item> ArenaRefChildren<'a, Item> {
     /// Returns true if the handle has a child with the given id.
     pub fn has_child(self, id: impl Into<u64>) -> bool {
         let id = id.into();
-        self.children.iter().any(|child| child.id == id)
+        self.children.position(id).is_some()
     }
 
     /// Get the child of the item this handle is associated with, which has the given id.
@@ -263,8 +337,7 @@ impl<'a, Item> ArenaRefChildren<'a, Item> {
     pub fn get_child(&self, id: impl Into<u64>) -> Option<ArenaRef<'_, Item>> {
         let id = id.into();
         self.children
-            .iter()
-            .find(|child| child.id == id)
+            .get(id)
             .map(|child| child.arena_ref(self.id, self.parents_map.parents_map))
     }
 
@@ -275,8 +348,7 @@ impl<'a, Item> ArenaRefChildren<'a, Item> {
     pub fn into_child(self, id: impl Into<u64>) -> Option<ArenaRef<'a, Item>> {
         let id = id.into();
         self.children
-            .iter()
-            .find(|child| child.id == id)
+            .get(id)
             .map(|child| child.arena_ref(self.id, self.parents_map.parents_map))
     }
 
@@ -304,14 +376,10 @@ impl<'a, Item> ArenaRefChildren<'a, Item> {
         let mut node_children = self.children;
         while let Some((id, new_id_path)) = id_path.split_last() {
             id_path = new_id_path;
-            node_children = &node_children
-                .iter()
-                .find(|child| child.id == *id)
-                .unwrap()
-                .children;
+            node_children = &node_children.get(*id).unwrap().children;
         }
 
-        let node = node_children.iter().find(|child| child.id == id).unwrap();
+        let node = node_children.get(id).unwrap();
         Some(node.arena_ref(*parent_id, self.parents_map.parents_map))
     }
 }
@@ -324,8 +392,7 @@ impl<'a, Item> ArenaMutChildren<'a, Item> {
     pub fn get_child(&self, id: impl Into<u64>) -> Option<ArenaRef<'_, Item>> {
         let id = id.into();
         self.children
-            .iter()
-            .find(|child| child.id == id)
+            .get(id)
             .map(|child| child.arena_ref(self.id, self.parents_map.parents_map))
     }
 
@@ -336,8 +403,7 @@ impl<'a, Item> ArenaMutChildren<'a, Item> {
     pub fn get_child_mut(&mut self, id: impl Into<u64>) -> Option<ArenaMut<'_, Item>> {
         let id = id.into();
         self.children
-            .iter_mut()
-            .find(|child| child.id == id)
+            .get_mut(id)
             .map(|child| child.arena_mut(self.id, self.parents_map.parents_map))
     }
 
@@ -348,8 +414,7 @@ impl<'a, Item> ArenaMutChildren<'a, Item> {
     pub fn into_child(self, id: impl Into<u64>) -> Option<ArenaRef<'a, Item>> {
         let id = id.into();
         self.children
-            .iter()
-            .find(|child| child.id == id)
+            .get(id)
             .map(|child| child.arena_ref(self.id, self.parents_map.parents_map))
     }
 
@@ -360,8 +425,7 @@ impl<'a, Item> ArenaMutChildren<'a, Item> {
     pub fn into_child_mut(self, id: impl Into<u64>) -> Option<ArenaMut<'a, Item>> {
         let id = id.into();
         self.children
-            .iter_mut()
-            .find(|child| child.id == id)
+            .get_mut(id)
             .map(|child| child.arena_mut(self.id, self.parents_map.parents_map))
     }
 
@@ -384,7 +448,7 @@ impl<'a, Item> ArenaMutChildren<'a, Item> {
         self.children.push(TreeNode {
             id: child_id,
             item: value,
-            children: Vec::new(),
+            children: TreeChildren::default(),
         });
     }
 
@@ -398,19 +462,14 @@ impl<'a, Item> ArenaMutChildren<'a, Item> {
     #[must_use]
     pub fn remove_child(&mut self, child_id: impl Into<u64>) -> Option<Item> {
         let child_id = child_id.into();
-        let i = self
-            .children
-            .iter()
-            .position(|child| child.id == child_id)?;
-
         fn remove_children<I>(node: &TreeNode<I>, parents_map: &mut HashMap<u64, Option<u64>>) {
             parents_map.remove(&node.id);
-            for child in &node.children {
+            for child in &node.children.nodes {
                 remove_children(child, parents_map);
             }
         }
 
-        let child = self.children.remove(i);
+        let child = self.children.remove(child_id)?;
         remove_children(&child, self.parents_map.parents_map);
 
         Some(child.item)
@@ -471,17 +530,10 @@ impl<'a, Item> ArenaMutChildren<'a, Item> {
         let mut node_children: &'a mut _ = &mut *self.children;
         while let Some((id, new_id_path)) = id_path.split_last() {
             id_path = new_id_path;
-            node_children = &mut node_children
-                .iter_mut()
-                .find(|child| child.id == *id)
-                .unwrap()
-                .children;
+            node_children = &mut node_children.get_mut(*id).unwrap().children;
         }
 
-        let node = node_children
-            .iter_mut()
-            .find(|child| child.id == id)
-            .unwrap();
+        let node = node_children.get_mut(id).unwrap();
         Some(node.arena_mut(*parent_id, &mut *self.parents_map.parents_map))
     }
 }
@@ -549,6 +601,54 @@ impl<'a> ArenaMapMut<'a> {
     }
 }
 
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_fan_out() {
+        let mut tree: TreeArena<u64> = TreeArena::new();
+        let mut roots = tree.root_token_mut();
+        roots.insert_child(1_u64, 1);
+        let mut root = roots.get_child_mut(1_u64).unwrap();
+
+        const CHILD_COUNT: u64 = CHILD_INDEX_THRESHOLD as u64 * 2;
+        for id in 2..2 + CHILD_COUNT {
+            root.children.insert_child(id, id * 10);
+        }
+        for id in 2..2 + CHILD_COUNT {
+            assert_eq!(*root.children.get_child(id).unwrap().item, id * 10);
+        }
+
+        // Removing children shifts the ones after them, which must still be found.
+        assert_eq!(root.children.remove_child(2_u64), Some(20));
+        assert_eq!(root.children.remove_child(10_u64), Some(100));
+        assert!(root.children.get_child(2_u64).is_none());
+        assert!(root.children.get_child(10_u64).is_none());
+        for id in (3..2 + CHILD_COUNT).filter(|id| *id != 10) {
+            assert_eq!(*root.children.get_child_mut(id).unwrap().item, id * 10);
+        }
+
+        // Dropping back below the threshold falls back to a linear scan.
+        for id in 3..2 + CHILD_COUNT - 4 {
+            let _ = root.children.remove_child(id);
+        }
+        for id in 2 + CHILD_COUNT - 4..2 + CHILD_COUNT {
+            assert_eq!(*root.children.get_child(id).unwrap().item, id * 10);
+        }
+
+        assert_eq!(
+            tree.get_id_path(2 + CHILD_COUNT - 1),
+            vec![2 + CHILD_COUNT - 1, 1]
+        );
+        assert_eq!(
+            *tree.find(2 + CHILD_COUNT - 1).unwrap().item,
+            (2 + CHILD_COUNT - 1) * 10
+        );
+    }
+}
+
 // This is a sketch of what the unsafe version of this code would look like,
 // one with an actual arena.
 #[cfg(FALSE)]
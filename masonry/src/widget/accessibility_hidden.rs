@@ -0,0 +1,134 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A widget which leaves its child out of the accessibility tree.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point, PointerEvent,
+    RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A widget which leaves its child, and the child's descendants, out of the accessibility tree.
+///
+/// This is meant for purely decorative content, such as backgrounds, dividers and shadows.
+/// The child is still painted and gets events.
+///
+/// See [`set_accessibility_hidden`](crate::EventCtx::set_accessibility_hidden) for details.
+pub struct AccessibilityHidden<W: Widget> {
+    child: WidgetPod<W>,
+    hidden: bool,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> AccessibilityHidden<W> {
+    /// Create a new widget which hides `child` from assistive technologies.
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    /// Create a new widget which hides `child` from assistive technologies.
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            hidden: true,
+        }
+    }
+
+    /// Builder-style method for setting whether the child is left out of the
+    /// accessibility tree, `true` by default.
+    pub fn with_hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> AccessibilityHidden<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    /// Set whether the child is left out of the accessibility tree.
+    pub fn set_hidden(this: &mut WidgetMut<'_, Self>, hidden: bool) {
+        this.widget.hidden = hidden;
+        this.ctx.set_accessibility_hidden(hidden);
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for AccessibilityHidden<W> {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded = event {
+            // The whole subtree, including this widget, is hidden.
+            ctx.set_accessibility_hidden(self.hidden);
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let baseline = ctx.child_baseline_offset(&self.child);
+        ctx.set_baseline_offset(baseline);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("AccessibilityHidden")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness};
+    use crate::widget::{Flex, Label, SizedBox};
+
+    #[test]
+    fn child_is_hidden() {
+        let [hidden_id, label_id] = widget_ids();
+        let widget = Flex::column()
+            .with_child_id(
+                AccessibilityHidden::new(SizedBox::empty().height(1.)),
+                hidden_id,
+            )
+            .with_child_id(Label::new("Hello"), label_id);
+        let mut harness = TestHarness::create(widget);
+        assert!(harness.access_node(hidden_id).is_none());
+        assert!(harness.access_node(label_id).is_some());
+
+        harness.edit_widget(hidden_id, |mut hidden| {
+            let mut hidden = hidden.downcast::<AccessibilityHidden<SizedBox>>();
+            AccessibilityHidden::set_hidden(&mut hidden, false);
+        });
+        assert!(harness.access_node(hidden_id).is_some());
+    }
+}
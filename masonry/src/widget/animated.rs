@@ -0,0 +1,291 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A container which animates the opacity, offset and size of its child.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::animation::{AnimatedValue, Transition};
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, BoxConstraints, ComposeCtx, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Vec2, Widget, WidgetId,
+};
+
+/// A property of [`Animated`] which can have a transition.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AnimatedProperty {
+    /// The opacity the child is painted with.
+    Opacity,
+    /// The offset of the child from its layout position.
+    Offset,
+    /// The size of the container, which follows the size of the child.
+    Size,
+}
+
+/// A container which smoothly animates changes to the opacity and offset of its child,
+/// and to its own size when the child is resized.
+///
+/// Each property only animates if it has a [`Transition`], and jumps to its new value
+/// otherwise. While the size animates, the child is laid out at its new size, and
+/// clipped to the animated size. The size doesn't animate until the widget is first painted,
+/// so that it appears at the size of its child.
+pub struct Animated<W: Widget> {
+    child: WidgetPod<W>,
+    opacity: AnimatedValue<f64>,
+    offset: AnimatedValue<Vec2>,
+    size: AnimatedValue<Size>,
+    /// Whether the widget was painted, before which size changes don't animate.
+    painted: bool,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> Animated<W> {
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            opacity: AnimatedValue::new(1.),
+            offset: AnimatedValue::new(Vec2::ZERO),
+            size: AnimatedValue::new(Size::ZERO),
+            painted: false,
+        }
+    }
+
+    /// Builder-style method to set the opacity of the child, between 0 and 1.
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity.jump_to(opacity.clamp(0., 1.));
+        self
+    }
+
+    /// Builder-style method to set the offset of the child from its layout position.
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset.jump_to(offset);
+        self
+    }
+
+    /// Builder-style method to set the transition of a property.
+    pub fn with_transition(mut self, property: AnimatedProperty, transition: Transition) -> Self {
+        self.set_transition_of(property, Some(transition));
+        self
+    }
+
+    /// The current opacity of the child, which may be moving towards the one set.
+    pub fn opacity(&self) -> f64 {
+        self.opacity.value()
+    }
+
+    /// The current offset of the child, which may be moving towards the one set.
+    pub fn offset(&self) -> Vec2 {
+        self.offset.value()
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> Animated<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    /// Set the opacity of the child, between 0 and 1.
+    ///
+    /// Non-finite values are treated as 1.
+    pub fn set_opacity(this: &mut WidgetMut<'_, Self>, opacity: f64) {
+        let opacity = if opacity.is_finite() {
+            opacity.clamp(0., 1.)
+        } else {
+            1.
+        };
+        if this.widget.opacity.set_target(opacity) {
+            this.ctx.request_anim_frame();
+        }
+        this.ctx.set_opacity(this.widget.opacity.value() as f32);
+    }
+
+    /// Set the offset of the child from its layout position.
+    pub fn set_offset(this: &mut WidgetMut<'_, Self>, offset: Vec2) {
+        if this.widget.offset.set_target(offset) {
+            this.ctx.request_anim_frame();
+        }
+        this.ctx.request_compose();
+    }
+
+    /// Set the transition of a property, or remove it with `None`.
+    ///
+    /// The current transition of the property, if any, isn't interrupted.
+    pub fn set_transition(
+        this: &mut WidgetMut<'_, Self>,
+        property: AnimatedProperty,
+        transition: Option<Transition>,
+    ) {
+        this.widget.set_transition_of(property, transition);
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl<W: Widget> Animated<W> {
+    fn set_transition_of(&mut self, property: AnimatedProperty, transition: Option<Transition>) {
+        match property {
+            AnimatedProperty::Opacity => self.opacity.set_transition(transition),
+            AnimatedProperty::Offset => self.offset.set_transition(transition),
+            AnimatedProperty::Size => self.size.set_transition(transition),
+        }
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for Animated<W> {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
+        if self.opacity.advance(interval) {
+            ctx.set_opacity(self.opacity.value() as f32);
+        }
+        if self.offset.advance(interval) {
+            ctx.request_compose();
+        }
+        if self.size.advance(interval) {
+            ctx.request_layout();
+        }
+        if self.opacity.is_animating() || self.offset.is_animating() || self.size.is_animating() {
+            ctx.request_anim_frame();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded = event {
+            ctx.set_opacity(self.opacity.value() as f32);
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let child_size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let baseline = ctx.child_baseline_offset(&self.child);
+        ctx.set_baseline_offset(baseline);
+
+        if !self.painted {
+            self.size.jump_to(child_size);
+        } else if self.size.set_target(child_size) {
+            ctx.request_anim_frame();
+        }
+        if self.size.is_animating() {
+            let size = bc.constrain(self.size.value());
+            ctx.set_clip_path(size.to_rect());
+            size
+        } else {
+            ctx.clear_clip_path();
+            child_size
+        }
+    }
+
+    fn compose(&mut self, ctx: &mut ComposeCtx) {
+        ctx.set_child_translation(&mut self.child, self.offset.value());
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {
+        self.painted = true;
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Animated")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::animation::Easing;
+    use crate::testing::{widget_ids, TestHarness};
+    use crate::widget::{Flex, SizedBox};
+
+    #[test]
+    fn animate_opacity_and_offset() {
+        let [animated_id] = widget_ids();
+        let transition = Transition::new(Duration::from_millis(100), Easing::Linear);
+        let animated = Animated::new(SizedBox::empty().width(20.).height(20.))
+            .with_transition(AnimatedProperty::Opacity, transition)
+            .with_transition(AnimatedProperty::Offset, transition);
+        let mut harness = TestHarness::create(Flex::row().with_child_id(animated, animated_id));
+
+        harness.edit_widget(animated_id, |mut animated| {
+            let mut animated = animated.downcast::<Animated<SizedBox>>();
+            Animated::set_opacity(&mut animated, 0.);
+            Animated::set_offset(&mut animated, Vec2::new(40., 0.));
+        });
+        harness.animate_ms(50);
+        let animated = harness.get_widget(animated_id);
+        assert_eq!(animated.ctx().opacity(), 0.5);
+        let child = animated.children()[0].ctx().window_layout_rect();
+        let origin = animated.ctx().window_layout_rect().origin();
+        assert_eq!(child.origin() - origin, Vec2::new(20., 0.));
+
+        harness.animate_ms(100);
+        let animated = harness.get_widget(animated_id);
+        assert_eq!(animated.ctx().opacity(), 0.);
+        let widget = animated.downcast::<Animated<SizedBox>>().unwrap();
+        assert_eq!(widget.offset(), Vec2::new(40., 0.));
+    }
+
+    #[test]
+    fn animate_size() {
+        let [animated_id] = widget_ids();
+        let transition = Transition::new(Duration::from_millis(100), Easing::Linear);
+        let animated = Animated::new(SizedBox::empty().width(20.).height(20.))
+            .with_transition(AnimatedProperty::Size, transition);
+        let mut harness = TestHarness::create(Flex::row().with_child_id(animated, animated_id));
+        assert_eq!(
+            harness.get_widget(animated_id).ctx().size(),
+            Size::new(20., 20.)
+        );
+        // Size changes only animate once the widget was painted.
+        let _ = harness.capture_widget_scene(animated_id);
+
+        harness.edit_widget(animated_id, |mut animated| {
+            let mut animated = animated.downcast::<Animated<SizedBox>>();
+            let mut child = Animated::child_mut(&mut animated);
+            SizedBox::set_width(&mut child, 60.);
+        });
+        assert_eq!(
+            harness.get_widget(animated_id).ctx().size(),
+            Size::new(20., 20.)
+        );
+        harness.animate_ms(50);
+        assert_eq!(
+            harness.get_widget(animated_id).ctx().size(),
+            Size::new(40., 20.)
+        );
+        harness.animate_ms(100);
+        assert_eq!(
+            harness.get_widget(animated_id).ctx().size(),
+            Size::new(60., 20.)
+        );
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A widget which shows its child as busy.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::widget::drawer::Scrim;
+use crate::widget::{Spinner, WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point, PointerEvent,
+    RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// The largest size of the spinner shown above a busy child.
+const MAX_SPINNER_SIZE: f64 = 36.;
+
+/// A widget which can mark its child as busy, e.g. while the data it shows is reloading.
+///
+/// While busy, the child is dimmed, with a spinner above it, and disabled: it doesn't
+/// receive pointer events, and its widgets can't be focused. Whether the app disabled the
+/// child is left as is. The widget is also marked as
+/// busy in the accessibility tree, so that assistive technologies wait for it to be ready.
+pub struct Busy<W: Widget> {
+    child: WidgetPod<W>,
+    scrim: WidgetPod<Scrim>,
+    spinner: WidgetPod<Spinner>,
+    busy: bool,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> Busy<W> {
+    /// Create a new widget wrapping `child`, which isn't busy yet.
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            scrim: WidgetPod::new(Scrim { alpha: 1. }),
+            spinner: WidgetPod::new(Spinner::new()),
+            busy: false,
+        }
+    }
+
+    /// Builder-style method to set whether the child is busy.
+    pub fn busy(mut self, busy: bool) -> Self {
+        self.busy = busy;
+        self
+    }
+
+    /// Whether the child is busy.
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> Busy<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    /// Set whether the child is busy.
+    pub fn set_busy(this: &mut WidgetMut<'_, Self>, busy: bool) {
+        if this.widget.busy == busy {
+            return;
+        }
+        this.widget.busy = busy;
+        this.ctx.set_stashed(&mut this.widget.scrim, !busy);
+        this.ctx.set_stashed(&mut this.widget.spinner, !busy);
+        this.ctx.set_child_disabled(&mut this.widget.child, busy);
+        this.ctx.request_layout();
+        this.ctx.request_accessibility_update();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for Busy<W> {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::WidgetAdded => {
+                ctx.set_stashed(&mut self.scrim, !self.busy);
+                ctx.set_stashed(&mut self.spinner, !self.busy);
+                ctx.set_child_disabled(&mut self.child, self.busy);
+            }
+            _ => {}
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+        ctx.register_child(&mut self.scrim);
+        ctx.register_child(&mut self.spinner);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let baseline = ctx.child_baseline_offset(&self.child);
+        ctx.set_baseline_offset(baseline);
+
+        if !self.busy {
+            return size;
+        }
+        ctx.run_layout(&mut self.scrim, &BoxConstraints::tight(size));
+        ctx.place_child(&mut self.scrim, Point::ORIGIN);
+
+        let spinner_size = size.width.min(size.height).min(MAX_SPINNER_SIZE);
+        ctx.run_layout(
+            &mut self.spinner,
+            &BoxConstraints::tight(Size::new(spinner_size, spinner_size)),
+        );
+        ctx.place_child(
+            &mut self.spinner,
+            Point::new(
+                (size.width - spinner_size) / 2.,
+                (size.height - spinner_size) / 2.,
+            ),
+        );
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        if self.busy {
+            node.set_busy();
+        }
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id(), self.scrim.id(), self.spinner.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Busy")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::widget::{Button, SizedBox};
+    use crate::Action;
+
+    #[test]
+    fn busy_blocks_child() {
+        let [button_id] = widget_ids();
+        let widget = Busy::new(Button::new("Reload").with_id(button_id)).busy(true);
+        let mut harness = TestHarness::create(widget);
+        assert!(harness.get_widget(button_id).ctx().is_disabled());
+
+        harness.mouse_click_on(button_id);
+        assert_eq!(harness.pop_action(), None);
+
+        harness.edit_root_widget(|mut busy| {
+            let mut busy = busy.downcast::<Busy<SizedBox>>();
+            Busy::set_busy(&mut busy, false);
+        });
+        assert!(!harness.get_widget(button_id).ctx().is_disabled());
+        harness.mouse_click_on(button_id);
+        assert!(matches!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(_), id)) if id == button_id
+        ));
+    }
+
+    #[test]
+    fn idle_keeps_disabled_child_disabled() {
+        let [button_id] = widget_ids();
+        let widget = Busy::new(Button::new("Reload").with_id(button_id));
+        let mut harness = TestHarness::create(widget);
+        harness.edit_widget(button_id, |mut button| {
+            button.ctx.set_disabled(true);
+        });
+
+        for busy in [true, false] {
+            harness.edit_root_widget(|mut widget| {
+                let mut widget = widget.downcast::<Busy<SizedBox>>();
+                Busy::set_busy(&mut widget, busy);
+            });
+        }
+        assert!(harness.get_widget(button_id).ctx().is_disabled());
+    }
+}
@@ -12,10 +12,10 @@ use crate::action::Action;
 use crate::event::PointerButton;
 use crate::paint_scene_helpers::{fill_lin_gradient, stroke, UnitPoint};
 use crate::text::ArcStr;
-use crate::widget::{Label, WidgetMut, WidgetPod};
+use crate::widget::{Icon, Label, Spinner, WidgetMut, WidgetPod};
 use crate::{
-    theme, AccessCtx, AccessEvent, BoxConstraints, EventCtx, Insets, LayoutCtx, PaintCtx,
-    PointerEvent, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+    theme, AccessCtx, AccessEvent, AllowRawMut, BoxConstraints, Color, Env, EventCtx, Insets,
+    LayoutCtx, PaintCtx, Point, PointerEvent, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
 };
 
 // the minimum padding added to a button.
@@ -23,11 +23,74 @@ use crate::{
 // should be reevaluated at some point.
 const LABEL_INSETS: Insets = Insets::uniform_xy(8., 2.);
 
-/// A button with a text label.
+/// The visual style of a [`Button`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ButtonVariant {
+    /// The main action of a view.
+    Primary,
+    /// The default style.
+    #[default]
+    Secondary,
+    /// An action which deletes data or can't be undone.
+    Destructive,
+    /// A button without background or border until it's hovered.
+    Ghost,
+}
+
+impl ButtonVariant {
+    /// The top and bottom colors of the background gradient, if any.
+    pub(crate) fn background(
+        self,
+        env: &Env,
+        is_hovered: bool,
+        is_active: bool,
+        is_disabled: bool,
+    ) -> Option<[Color; 2]> {
+        let [light, dark] = match self {
+            ButtonVariant::Primary => [env.get(theme::PRIMARY_LIGHT), env.get(theme::PRIMARY_DARK)],
+            ButtonVariant::Secondary => [
+                env.get(theme::BUTTON_COLOR),
+                env.get(theme::BUTTON_DARK_COLOR),
+            ],
+            ButtonVariant::Destructive => [
+                env.get(theme::DESTRUCTIVE_LIGHT),
+                env.get(theme::DESTRUCTIVE_DARK),
+            ],
+            ButtonVariant::Ghost if is_hovered || is_active => [
+                env.get(theme::BACKGROUND_LIGHT),
+                env.get(theme::BACKGROUND_DARK),
+            ],
+            ButtonVariant::Ghost => return None,
+        };
+        if is_disabled {
+            Some([
+                env.get(theme::DISABLED_BUTTON_LIGHT),
+                env.get(theme::DISABLED_BUTTON_DARK),
+            ])
+        } else if is_active {
+            Some([dark, light])
+        } else {
+            Some([light, dark])
+        }
+    }
+}
+
+/// A button with a text label and an optional leading icon.
 ///
 /// Emits [`Action::ButtonPressed`] when pressed.
+///
+/// While loading, the content of the button is replaced by a spinner and the
+/// button can't be activated.
 pub struct Button {
     label: WidgetPod<Label>,
+    icon: Option<WidgetPod<Icon>>,
+    spinner: Option<WidgetPod<Spinner>>,
+    variant: ButtonVariant,
+    /// The size of the icon and label from the last layout before loading started,
+    /// used to keep the button the same size while loading.
+    content_size: Option<Size>,
+    /// Whether the button was pressed since the last call to `take_pressed`.
+    pressed: bool,
 }
 
 // --- MARK: BUILDERS ---
@@ -59,8 +122,43 @@ impl Button {
     pub fn from_label(label: Label) -> Button {
         Button {
             label: WidgetPod::new(label),
+            icon: None,
+            spinner: None,
+            variant: ButtonVariant::default(),
+            content_size: None,
+            pressed: false,
         }
     }
+
+    /// Builder-style method for setting the visual style of the button.
+    pub fn with_variant(mut self, variant: ButtonVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Builder-style method for adding an icon before the label.
+    pub fn with_icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(WidgetPod::new(icon));
+        self
+    }
+
+    /// Builder-style method for setting whether the button is loading.
+    pub fn with_loading(mut self, loading: bool) -> Self {
+        self.spinner = loading.then(|| WidgetPod::new(Spinner::new()));
+        self
+    }
+
+    /// Whether the button is loading.
+    pub fn is_loading(&self) -> bool {
+        self.spinner.is_some()
+    }
+
+    /// Whether the button was pressed since the last call.
+    ///
+    /// This lets a parent react to the press while the event bubbles up to it.
+    pub(crate) fn take_pressed(&mut self) -> bool {
+        std::mem::take(&mut self.pressed)
+    }
 }
 
 // --- MARK: WIDGETMUT ---
@@ -73,6 +171,47 @@ impl Button {
     pub fn label_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Label> {
         this.ctx.get_mut(&mut this.widget.label)
     }
+
+    /// Set the visual style of the button.
+    pub fn set_variant(this: &mut WidgetMut<'_, Self>, variant: ButtonVariant) {
+        this.widget.variant = variant;
+        this.ctx.request_paint_only();
+    }
+
+    /// Set or remove the icon before the label.
+    pub fn set_icon(this: &mut WidgetMut<'_, Self>, icon: Option<Icon>) {
+        if let Some(old_icon) = this.widget.icon.take() {
+            this.ctx.remove_child(old_icon);
+        }
+        // The icon is stashed when it's registered if the button is loading.
+        this.widget.icon = icon.map(WidgetPod::new);
+        this.ctx.children_changed();
+    }
+
+    pub fn icon_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> Option<WidgetMut<'t, Icon>> {
+        let icon = this.widget.icon.as_mut()?;
+        Some(this.ctx.get_mut(icon))
+    }
+
+    /// Set whether the button is loading.
+    ///
+    /// A loading button shows a spinner instead of its content, and doesn't emit actions.
+    pub fn set_loading(this: &mut WidgetMut<'_, Self>, loading: bool) {
+        if loading == this.widget.is_loading() {
+            return;
+        }
+        if loading {
+            this.widget.spinner = Some(WidgetPod::new(Spinner::new()));
+        } else if let Some(spinner) = this.widget.spinner.take() {
+            this.ctx.remove_child(spinner);
+        }
+        this.ctx.set_stashed(&mut this.widget.label, loading);
+        if let Some(icon) = &mut this.widget.icon {
+            this.ctx.set_stashed(icon, loading);
+        }
+        this.ctx.children_changed();
+        this.ctx.request_accessibility_update();
+    }
 }
 
 // --- MARK: IMPL WIDGET ---
@@ -80,7 +219,7 @@ impl Widget for Button {
     fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
         match event {
             PointerEvent::PointerDown(_, _) => {
-                if !ctx.is_disabled() {
+                if !ctx.is_disabled() && !self.is_loading() {
                     ctx.capture_pointer();
                     // Changes in pointer capture impact appearance, but not accessibility node
                     ctx.request_paint_only();
@@ -88,8 +227,13 @@ impl Widget for Button {
                 }
             }
             PointerEvent::PointerUp(button, _) => {
-                if ctx.has_pointer_capture() && ctx.is_hovered() && !ctx.is_disabled() {
+                if ctx.has_pointer_capture()
+                    && ctx.is_hovered()
+                    && !ctx.is_disabled()
+                    && !self.is_loading()
+                {
                     ctx.submit_action(Action::ButtonPressed(*button));
+                    self.pressed = true;
                     trace!("Button {:?} released", ctx.widget_id());
                 }
                 // Changes in pointer capture impact appearance, but not accessibility node
@@ -102,10 +246,11 @@ impl Widget for Button {
     fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
 
     fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
-        if ctx.target() == ctx.widget_id() {
+        if ctx.target() == ctx.widget_id() && !self.is_loading() {
             match event.action {
                 accesskit::Action::Default => {
                     ctx.submit_action(Action::ButtonPressed(PointerButton::Primary));
+                    self.pressed = true;
                 }
                 _ => {}
             }
@@ -122,29 +267,80 @@ impl Widget for Button {
     }
 
     fn register_children(&mut self, ctx: &mut crate::RegisterCtx) {
-        ctx.register_child(&mut self.label);
+        let loading = self.is_loading();
+        ctx.register_child_stashed(&mut self.label, loading);
+        if let Some(icon) = &mut self.icon {
+            ctx.register_child_stashed(icon, loading);
+        }
+        if let Some(spinner) = &mut self.spinner {
+            ctx.register_child(spinner);
+        }
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         let padding = Size::new(LABEL_INSETS.x_value(), LABEL_INSETS.y_value());
-        let label_bc = bc.shrink(padding).loosen();
+        let content_bc = bc.shrink(padding).loosen();
+
+        // HACK: to make sure we look okay at default sizes when beside a textbox,
+        // we make sure we will have at least the same height as the default textbox.
+        let min_height = ctx.env().get(theme::BORDERED_WIDGET_HEIGHT);
+
+        if let Some(spinner) = &mut self.spinner {
+            // Keep the size the content had before loading started, so that
+            // the surrounding layout doesn't jump.
+            let icon_size = ctx.env().get(theme::ICON_SIZE);
+            let content_size =
+                content_bc.constrain(self.content_size.unwrap_or(Size::new(icon_size, icon_size)));
+            let side = content_size.width.min(content_size.height);
+            let spinner_size =
+                ctx.run_layout(spinner, &BoxConstraints::tight(Size::new(side, side)));
+
+            let button_size = bc.constrain(Size::new(
+                content_size.width + padding.width,
+                ctx.snap_to_baseline_grid((content_size.height + padding.height).max(min_height)),
+            ));
+            let spinner_offset = (button_size.to_vec2() - spinner_size.to_vec2()) / 2.0;
+            ctx.place_child(spinner, spinner_offset.to_point());
+            return button_size;
+        }
 
+        let (icon_size, icon_width) = match &mut self.icon {
+            Some(icon) => {
+                let icon_size = ctx.run_layout(icon, &content_bc);
+                (
+                    icon_size,
+                    icon_size.width + ctx.env().get(theme::WIDGET_CONTROL_COMPONENT_PADDING),
+                )
+            }
+            None => (Size::ZERO, 0.0),
+        };
+        let label_bc = content_bc.shrink((icon_width, 0.0));
         let label_size = ctx.run_layout(&mut self.label, &label_bc);
 
         let baseline = ctx.child_baseline_offset(&self.label);
         ctx.set_baseline_offset(baseline + LABEL_INSETS.y1);
 
-        // HACK: to make sure we look okay at default sizes when beside a textbox,
-        // we make sure we will have at least the same height as the default textbox.
-        let min_height = theme::BORDERED_WIDGET_HEIGHT;
+        let content_size = Size::new(
+            icon_width + label_size.width,
+            icon_size.height.max(label_size.height),
+        );
+        self.content_size = Some(content_size);
 
         let button_size = bc.constrain(Size::new(
-            label_size.width + padding.width,
-            (label_size.height + padding.height).max(min_height),
+            content_size.width + padding.width,
+            ctx.snap_to_baseline_grid((content_size.height + padding.height).max(min_height)),
         ));
 
-        let label_offset = (button_size.to_vec2() - label_size.to_vec2()) / 2.0;
-        ctx.place_child(&mut self.label, label_offset.to_point());
+        let content_offset = (button_size.to_vec2() - content_size.to_vec2()) / 2.0;
+        if let Some(icon) = &mut self.icon {
+            let icon_y = content_offset.y + (content_size.height - icon_size.height) / 2.0;
+            ctx.place_child(icon, Point::new(content_offset.x, icon_y));
+        }
+        let label_y = content_offset.y + (content_size.height - label_size.height) / 2.0;
+        ctx.place_child(
+            &mut self.label,
+            Point::new(content_offset.x + icon_width, label_y),
+        );
 
         button_size
     }
@@ -153,35 +349,35 @@ impl Widget for Button {
         let is_active = ctx.has_pointer_capture() && !ctx.is_disabled();
         let is_hovered = ctx.is_hovered();
         let size = ctx.size();
-        let stroke_width = theme::BUTTON_BORDER_WIDTH;
+        let stroke_width = ctx.env().get(theme::BUTTON_BORDER_WIDTH);
 
         let rounded_rect = size
             .to_rect()
             .inset(-stroke_width / 2.0)
-            .to_rounded_rect(theme::BUTTON_BORDER_RADIUS);
+            .to_rounded_rect(ctx.env().get(theme::BUTTON_RADIUS));
 
-        let bg_gradient = if ctx.is_disabled() {
-            [theme::DISABLED_BUTTON_LIGHT, theme::DISABLED_BUTTON_DARK]
-        } else if is_active {
-            [theme::BUTTON_DARK, theme::BUTTON_LIGHT]
-        } else {
-            [theme::BUTTON_LIGHT, theme::BUTTON_DARK]
-        };
+        let bg_gradient =
+            self.variant
+                .background(ctx.env(), is_hovered, is_active, ctx.is_disabled());
 
         let border_color = if is_hovered && !ctx.is_disabled() {
-            theme::BORDER_LIGHT
+            ctx.env().get(theme::BORDER_LIGHT)
         } else {
-            theme::BORDER_DARK
+            ctx.env().get(theme::BORDER_DARK)
         };
 
-        stroke(scene, &rounded_rect, border_color, stroke_width);
-        fill_lin_gradient(
-            scene,
-            &rounded_rect,
-            bg_gradient,
-            UnitPoint::TOP,
-            UnitPoint::BOTTOM,
-        );
+        if self.variant != ButtonVariant::Ghost || bg_gradient.is_some() {
+            stroke(scene, &rounded_rect, border_color, stroke_width);
+        }
+        if let Some(bg_gradient) = bg_gradient {
+            fill_lin_gradient(
+                scene,
+                &rounded_rect,
+                bg_gradient,
+                UnitPoint::TOP,
+                UnitPoint::BOTTOM,
+            );
+        }
     }
 
     fn accessibility_role(&self) -> Role {
@@ -189,6 +385,12 @@ impl Widget for Button {
     }
 
     fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        if self.is_loading() {
+            // The label is stashed while loading, so it isn't in the accessibility tree.
+            let label = ctx.get_raw_ref(&self.label);
+            node.set_name(label.widget().text().as_ref().to_string());
+            node.set_busy();
+        }
         // IMPORTANT: We don't want to merge this code in practice, because
         // the child label already has a 'name' property.
         // This is more of a proof of concept of `get_raw_ref()`.
@@ -201,7 +403,10 @@ impl Widget for Button {
     }
 
     fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
-        smallvec![self.label.id()]
+        let mut ids = smallvec![self.label.id()];
+        ids.extend(self.icon.as_ref().map(WidgetPod::id));
+        ids.extend(self.spinner.as_ref().map(WidgetPod::id));
+        ids
     }
 
     fn make_trace_span(&self) -> Span {
@@ -215,6 +420,8 @@ impl Widget for Button {
     }
 }
 
+impl AllowRawMut for Button {}
+
 // --- MARK: TESTS ---
 #[cfg(test)]
 mod tests {
@@ -224,6 +431,7 @@ mod tests {
     use crate::assert_render_snapshot;
     use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
     use crate::theme::PRIMARY_LIGHT;
+    use crate::widget::StandardIcon;
 
     #[test]
     fn simple_button() {
@@ -248,7 +456,7 @@ mod tests {
     fn edit_button() {
         let image_1 = {
             let label = Label::new("The quick brown fox jumps over the lazy dog")
-                .with_text_brush(PRIMARY_LIGHT)
+                .with_text_brush(*PRIMARY_LIGHT.default_value())
                 .with_text_size(20.0);
             let button = Button::from_label(label);
 
@@ -268,7 +476,7 @@ mod tests {
 
                 let mut label = Button::label_mut(&mut button);
                 Label::set_text_properties(&mut label, |props| {
-                    props.set_brush(PRIMARY_LIGHT);
+                    props.set_brush(*PRIMARY_LIGHT.default_value());
                     props.set_text_size(20.0);
                 });
             });
@@ -279,4 +487,55 @@ mod tests {
         // We don't use assert_eq because we don't want rich assert
         assert!(image_1 == image_2);
     }
+
+    #[test]
+    fn loading_button() {
+        let [button_id] = widget_ids();
+        let widget = Button::new("Hello")
+            .with_variant(ButtonVariant::Primary)
+            .with_id(button_id);
+
+        let mut harness = TestHarness::create(widget);
+        let size = harness.get_widget(button_id).ctx().size();
+
+        harness.edit_widget(button_id, |mut button| {
+            let mut button = button.downcast::<Button>();
+            Button::set_loading(&mut button, true);
+        });
+        assert_eq!(harness.get_widget(button_id).ctx().size(), size);
+
+        harness.mouse_click_on(button_id);
+        assert_eq!(harness.pop_action(), None);
+
+        harness.edit_widget(button_id, |mut button| {
+            let mut button = button.downcast::<Button>();
+            Button::set_loading(&mut button, false);
+        });
+        harness.mouse_click_on(button_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(PointerButton::Primary), button_id))
+        );
+    }
+
+    #[test]
+    fn set_icon_while_loading() {
+        let [button_id] = widget_ids();
+        let widget = Button::new("Hello").with_id(button_id);
+
+        let mut harness = TestHarness::create(widget);
+        let icon_id = harness.edit_widget(button_id, |mut button| {
+            let mut button = button.downcast::<Button>();
+            Button::set_loading(&mut button, true);
+            Button::set_icon(&mut button, Some(Icon::standard(StandardIcon::Add)));
+            button.widget.icon.as_ref().unwrap().id()
+        });
+        assert!(harness.get_widget(icon_id).ctx().is_stashed());
+
+        harness.edit_widget(button_id, |mut button| {
+            let mut button = button.downcast::<Button>();
+            Button::set_loading(&mut button, false);
+        });
+        assert!(!harness.get_widget(icon_id).ctx().is_stashed());
+    }
 }
@@ -18,38 +18,103 @@ use crate::{
     RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId, WidgetPod,
 };
 
+/// The states a [`Checkbox`] goes through when toggled by the user.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToggleCycle {
+    /// Unchecked and checked alternate. An indeterminate checkbox becomes checked.
+    #[default]
+    TwoState,
+    /// Unchecked, checked and indeterminate, in that order.
+    ThreeState,
+}
+
+impl ToggleCycle {
+    fn next(self, value: Option<bool>) -> Option<bool> {
+        match (self, value) {
+            (_, Some(false)) => Some(true),
+            (ToggleCycle::TwoState, Some(true)) => Some(false),
+            (ToggleCycle::TwoState, None) => Some(true),
+            (ToggleCycle::ThreeState, Some(true)) => None,
+            (ToggleCycle::ThreeState, None) => Some(false),
+        }
+    }
+}
+
 /// A checkbox that can be toggled.
+///
+/// Besides checked and unchecked, a checkbox can be indeterminate (`None`), e.g. for
+/// a "select all" checkbox when only some items are selected.
+///
+/// Emits [`Action::CheckboxChecked`] when toggled to checked or unchecked, and
+/// [`Action::CheckboxIndeterminate`] when toggled to indeterminate.
 pub struct Checkbox {
-    checked: bool,
+    value: Option<bool>,
+    toggle_cycle: ToggleCycle,
     label: WidgetPod<Label>,
 }
 
 impl Checkbox {
     /// Create a new `Checkbox` with a text label.
     pub fn new(checked: bool, text: impl Into<ArcStr>) -> Checkbox {
-        Checkbox {
-            checked,
-            label: WidgetPod::new(Label::new(text)),
-        }
+        Self::from_label(checked, Label::new(text))
     }
 
     /// Create a new `Checkbox` with the given label.
     pub fn from_label(checked: bool, label: Label) -> Checkbox {
+        Self::tri_state_from_label(Some(checked), label)
+    }
+
+    /// Create a new `Checkbox` with a text label, which may be indeterminate (`None`).
+    pub fn tri_state(value: Option<bool>, text: impl Into<ArcStr>) -> Checkbox {
+        Self::tri_state_from_label(value, Label::new(text))
+    }
+
+    /// Create a new `Checkbox` with the given label, which may be indeterminate (`None`).
+    pub fn tri_state_from_label(value: Option<bool>, label: Label) -> Checkbox {
         Checkbox {
-            checked,
+            value,
+            toggle_cycle: ToggleCycle::default(),
             label: WidgetPod::new(label),
         }
     }
+
+    /// Builder-style method for setting the states the checkbox goes through when toggled.
+    pub fn with_toggle_cycle(mut self, toggle_cycle: ToggleCycle) -> Self {
+        self.toggle_cycle = toggle_cycle;
+        self
+    }
+
+    /// The state of the checkbox, `None` if indeterminate.
+    pub fn value(&self) -> Option<bool> {
+        self.value
+    }
+
+    fn toggle(&mut self, ctx: &mut EventCtx) {
+        self.value = self.toggle_cycle.next(self.value);
+        match self.value {
+            Some(checked) => ctx.submit_action(Action::CheckboxChecked(checked)),
+            None => ctx.submit_action(Action::CheckboxIndeterminate),
+        }
+    }
 }
 
 // --- MARK: WIDGETMUT ---
 impl Checkbox {
     pub fn set_checked(this: &mut WidgetMut<'_, Self>, checked: bool) {
-        this.widget.checked = checked;
+        Self::set_value(this, Some(checked));
+    }
+
+    /// Set the state of the checkbox, `None` if indeterminate.
+    pub fn set_value(this: &mut WidgetMut<'_, Self>, value: Option<bool>) {
+        this.widget.value = value;
         // Checked state impacts appearance and accessibility node
         this.ctx.request_render();
     }
 
+    pub fn set_toggle_cycle(this: &mut WidgetMut<'_, Self>, toggle_cycle: ToggleCycle) {
+        this.widget.toggle_cycle = toggle_cycle;
+    }
+
     /// Set the text.
     ///
     /// We enforce this to be an `ArcStr` to make the allocation explicit.
@@ -76,8 +141,7 @@ impl Widget for Checkbox {
             }
             PointerEvent::PointerUp(_, _) => {
                 if ctx.has_pointer_capture() && ctx.is_hovered() && !ctx.is_disabled() {
-                    self.checked = !self.checked;
-                    ctx.submit_action(Action::CheckboxChecked(self.checked));
+                    self.toggle(ctx);
                     trace!("Checkbox {:?} released", ctx.widget_id());
                 }
                 // Checked state impacts appearance and accessibility node
@@ -93,8 +157,7 @@ impl Widget for Checkbox {
         if ctx.target() == ctx.widget_id() {
             match event.action {
                 accesskit::Action::Default => {
-                    self.checked = !self.checked;
-                    ctx.submit_action(Action::CheckboxChecked(self.checked));
+                    self.toggle(ctx);
                     // Checked state impacts appearance and accessibility node
                     ctx.request_render();
                 }
@@ -118,8 +181,8 @@ impl Widget for Checkbox {
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
-        let x_padding = theme::WIDGET_CONTROL_COMPONENT_PADDING;
-        let check_size = theme::BASIC_WIDGET_HEIGHT;
+        let x_padding = ctx.env().get(theme::WIDGET_CONTROL_COMPONENT_PADDING);
+        let check_size = ctx.env().get(theme::BASIC_WIDGET_HEIGHT);
 
         let label_size = ctx.run_layout(&mut self.label, bc);
         ctx.place_child(&mut self.label, (check_size + x_padding, 0.0).into());
@@ -136,7 +199,7 @@ impl Widget for Checkbox {
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
-        let check_size = theme::BASIC_WIDGET_HEIGHT;
+        let check_size = ctx.env().get(theme::BASIC_WIDGET_HEIGHT);
         let border_width = 1.;
 
         let rect = Size::new(check_size, check_size)
@@ -147,26 +210,39 @@ impl Widget for Checkbox {
         fill_lin_gradient(
             scene,
             &rect,
-            [theme::BACKGROUND_LIGHT, theme::BACKGROUND_DARK],
+            [
+                ctx.env().get(theme::BACKGROUND_LIGHT),
+                ctx.env().get(theme::BACKGROUND_DARK),
+            ],
             UnitPoint::TOP,
             UnitPoint::BOTTOM,
         );
 
         let border_color = if ctx.is_hovered() && !ctx.is_disabled() {
-            theme::BORDER_LIGHT
+            ctx.env().get(theme::BORDER_LIGHT)
         } else {
-            theme::BORDER_DARK
+            ctx.env().get(theme::BORDER_DARK)
         };
 
         stroke(scene, &rect, border_color, border_width);
 
-        if self.checked {
-            // Paint the checkmark
-            let mut path = BezPath::new();
-            path.move_to((4.0, 9.0));
-            path.line_to((8.0, 13.0));
-            path.line_to((14.0, 5.0));
+        let mut path = BezPath::new();
+        match self.value {
+            Some(true) => {
+                // Paint the checkmark
+                path.move_to((4.0, 9.0));
+                path.line_to((8.0, 13.0));
+                path.line_to((14.0, 5.0));
+            }
+            None => {
+                // Paint a dash
+                path.move_to((5.0, 9.0));
+                path.line_to((13.0, 9.0));
+            }
+            Some(false) => {}
+        }
 
+        if !path.elements().is_empty() {
             let style = Stroke {
                 width: 2.0,
                 join: Join::Round,
@@ -178,9 +254,9 @@ impl Widget for Checkbox {
             };
 
             let brush = if ctx.is_disabled() {
-                theme::DISABLED_TEXT_COLOR
+                ctx.env().get(theme::DISABLED_TEXT_COLOR)
             } else {
-                theme::TEXT_COLOR
+                ctx.env().get(theme::TEXT_COLOR)
             };
 
             scene.stroke(&style, Affine::IDENTITY, brush, None, &path);
@@ -200,12 +276,19 @@ impl Widget for Checkbox {
             let name = label.widget().text().as_ref().to_string();
             node.set_name(name);
         }
-        if self.checked {
-            node.set_toggled(Toggled::True);
-            node.set_default_action_verb(DefaultActionVerb::Uncheck);
-        } else {
-            node.set_toggled(Toggled::False);
-            node.set_default_action_verb(DefaultActionVerb::Check);
+        match self.value {
+            Some(true) => {
+                node.set_toggled(Toggled::True);
+                node.set_default_action_verb(DefaultActionVerb::Uncheck);
+            }
+            Some(false) => {
+                node.set_toggled(Toggled::False);
+                node.set_default_action_verb(DefaultActionVerb::Check);
+            }
+            None => {
+                node.set_toggled(Toggled::Mixed);
+                node.set_default_action_verb(DefaultActionVerb::Check);
+            }
         }
     }
 
@@ -218,10 +301,10 @@ impl Widget for Checkbox {
     }
 
     fn get_debug_text(&self) -> Option<String> {
-        if self.checked {
-            Some("[X]".to_string())
-        } else {
-            Some("[ ]".to_string())
+        match self.value {
+            Some(true) => Some("[X]".to_string()),
+            Some(false) => Some("[ ]".to_string()),
+            None => Some("[-]".to_string()),
         }
     }
 }
@@ -270,7 +353,7 @@ mod tests {
             let checkbox = Checkbox::from_label(
                 true,
                 Label::new("The quick brown fox jumps over the lazy dog")
-                    .with_text_brush(PRIMARY_LIGHT)
+                    .with_text_brush(*PRIMARY_LIGHT.default_value())
                     .with_text_size(20.0),
             );
 
@@ -293,7 +376,7 @@ mod tests {
                 );
 
                 let mut label = Checkbox::label_mut(&mut checkbox);
-                Label::set_text_brush(&mut label, PRIMARY_LIGHT);
+                Label::set_text_brush(&mut label, *PRIMARY_LIGHT.default_value());
                 Label::set_text_size(&mut label, 20.0);
             });
 
@@ -303,4 +386,34 @@ mod tests {
         // We don't use assert_eq because we don't want rich assert
         assert!(image_1 == image_2);
     }
+
+    #[test]
+    fn tri_state_checkbox() {
+        let [checkbox_id] = widget_ids();
+        let widget = Checkbox::tri_state(None, "Select all").with_id(checkbox_id);
+
+        let mut harness = TestHarness::create(widget);
+
+        harness.mouse_click_on(checkbox_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::CheckboxChecked(true), checkbox_id))
+        );
+
+        harness.edit_widget(checkbox_id, |mut checkbox| {
+            let mut checkbox = checkbox.downcast::<Checkbox>();
+            Checkbox::set_toggle_cycle(&mut checkbox, ToggleCycle::ThreeState);
+        });
+
+        harness.mouse_click_on(checkbox_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::CheckboxIndeterminate, checkbox_id))
+        );
+        harness.mouse_click_on(checkbox_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::CheckboxChecked(false), checkbox_id))
+        );
+    }
 }
@@ -0,0 +1,335 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A column chart, with an accessible data table.
+
+use accesskit::{NodeBuilder, NodeId, Role};
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::kurbo::Line;
+use vello::Scene;
+
+use crate::locale::{BasicFormatter, LocaleFormatter};
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::text::ArcStr;
+use crate::widget::WidgetMut;
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, Color, EventCtx, LayoutCtx, PaintCtx,
+    PointerEvent, Rect, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+const DEFAULT_SIZE: Size = Size::new(300., 200.);
+/// The fraction of the width of a category taken by its columns.
+const GROUP_WIDTH: f64 = 0.8;
+/// The colors of series which don't set one, in order, after the
+/// [`PRIMARY_LIGHT`](theme::PRIMARY_LIGHT) color of the env.
+const SERIES_COLORS: [Color; 3] = [
+    Color::rgb8(0xf5, 0xa6, 0x23),
+    Color::rgb8(0x7e, 0xd3, 0x21),
+    Color::rgb8(0xd0, 0x5c, 0xe3),
+];
+
+/// A named series of values in a [`ColumnChart`], with one value per category.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Series {
+    pub name: ArcStr,
+    pub values: Vec<f64>,
+    pub color: Option<Color>,
+}
+
+/// A chart which shows one column per value, with the columns of each category side by side.
+///
+/// Screen readers can't see the columns, so the chart exposes its data to accessibility
+/// tools instead: its node has a summary of each series as description, and a child table
+/// with a row per series and a column per category.
+pub struct ColumnChart {
+    label: Option<ArcStr>,
+    categories: Vec<ArcStr>,
+    series: Vec<Series>,
+    /// The ids of the nodes of the accessible data table, which are kept stable so that
+    /// screen readers don't lose their position when the data changes.
+    access_ids: Vec<NodeId>,
+}
+
+// --- MARK: BUILDERS ---
+impl Series {
+    /// Create a series, whose color is picked by the chart.
+    pub fn new(name: impl Into<ArcStr>, values: Vec<f64>) -> Self {
+        Self {
+            name: name.into(),
+            values,
+            color: None,
+        }
+    }
+
+    /// Builder-style method to set the color of the columns.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+impl ColumnChart {
+    /// Create a chart of `series` over `categories`.
+    ///
+    /// Series with fewer values than there are categories have no column in the
+    /// remaining categories.
+    pub fn new(categories: Vec<ArcStr>, series: Vec<Series>) -> Self {
+        Self {
+            label: None,
+            categories,
+            series,
+            access_ids: Vec::new(),
+        }
+    }
+
+    /// Builder-style method to set the name of the chart read by screen readers.
+    pub fn with_label(mut self, label: impl Into<ArcStr>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// A description of the data, e.g. "Sales: from 3 (Q1) to 10 (Q4), total 25.", with
+    /// the numbers formatted by `formatter`.
+    pub fn summary(&self, formatter: &dyn LocaleFormatter) -> String {
+        let mut summary = format!(
+            "Column chart of {} series over {} categories.",
+            self.series.len(),
+            self.categories.len()
+        );
+        for series in &self.series {
+            let values = || {
+                (series.values.iter().zip(&self.categories)).filter(|(value, _)| value.is_finite())
+            };
+            let min = values().min_by(|a, b| a.0.total_cmp(b.0));
+            let max = values().max_by(|a, b| a.0.total_cmp(b.0));
+            let (Some((min, min_category)), Some((max, max_category))) = (min, max) else {
+                summary.push_str(&format!(" {}: no data.", series.name));
+                continue;
+            };
+            let total: f64 = values().map(|(value, _)| value).sum();
+            let [min, max, total] = [*min, *max, total].map(|v| formatter.format_shortest(v));
+            summary.push_str(&format!(
+                " {}: from {min} ({min_category}) to {max} ({max_category}), total {total}.",
+                series.name
+            ));
+        }
+        summary
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl ColumnChart {
+    pub fn set_label(this: &mut WidgetMut<'_, Self>, label: Option<ArcStr>) {
+        this.widget.label = label;
+        this.ctx.request_accessibility_update();
+    }
+
+    pub fn set_categories(this: &mut WidgetMut<'_, Self>, categories: Vec<ArcStr>) {
+        this.widget.categories = categories;
+        this.ctx.request_render();
+    }
+
+    pub fn set_series(this: &mut WidgetMut<'_, Self>, series: Vec<Series>) {
+        this.widget.series = series;
+        this.ctx.request_render();
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl ColumnChart {
+    /// The ids of the first `count` nodes of the data table, allocated on first use.
+    fn access_ids(&mut self, count: usize) -> &[NodeId] {
+        while self.access_ids.len() < count {
+            self.access_ids.push(WidgetId::next().into());
+        }
+        &self.access_ids[..count]
+    }
+
+    /// The range of the values axis, which always includes zero.
+    fn value_range(&self) -> (f64, f64) {
+        let values = self.series.iter().flat_map(|s| &s.values);
+        values
+            .filter(|value| value.is_finite())
+            .fold((0., 0.), |(min, max), &value| {
+                (value.min(min), value.max(max))
+            })
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for ColumnChart {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::LocaleChanged = event {
+            ctx.request_accessibility_update();
+        }
+    }
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        bc.constrain(DEFAULT_SIZE)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let size = ctx.size();
+        let (min, max) = self.value_range();
+        if self.categories.is_empty() || self.series.is_empty() || min == max {
+            return;
+        }
+        let y_of = |value: f64| size.height * (max - value) / (max - min);
+        let group_width = size.width / self.categories.len() as f64;
+        let column_width = group_width * GROUP_WIDTH / self.series.len() as f64;
+        for (s, series) in self.series.iter().enumerate() {
+            let color = series
+                .color
+                .unwrap_or_else(|| match s % (SERIES_COLORS.len() + 1) {
+                    0 => ctx.env().get(theme::PRIMARY_LIGHT),
+                    i => SERIES_COLORS[i - 1],
+                });
+            for (c, value) in series.values.iter().take(self.categories.len()).enumerate() {
+                if !value.is_finite() {
+                    continue;
+                }
+                let x =
+                    group_width * (c as f64 + (1. - GROUP_WIDTH) / 2.) + column_width * s as f64;
+                let column = Rect::new(x, y_of(*value), x + column_width, y_of(0.));
+                fill_color(scene, &column.abs(), color);
+            }
+        }
+        let baseline = Line::new((0., y_of(0.)), (size.width, y_of(0.)));
+        stroke(scene, &baseline, ctx.env().get(theme::BORDER_LIGHT), 1.);
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::Figure
+    }
+
+    fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        if let Some(label) = &self.label {
+            node.set_name(label.to_string());
+        }
+        node.set_description(self.summary(ctx.formatter()));
+
+        // The table has a header row with the categories, then one row per series,
+        // whose first cell is the name of the series.
+        let mut rows: Vec<Vec<(Role, String, Option<f64>)>> = vec![std::iter::once("Series")
+            .chain(self.categories.iter().map(|c| &**c))
+            .map(|name| (Role::ColumnHeader, name.to_string(), None))
+            .collect()];
+        for series in &self.series {
+            let mut cells = vec![(Role::RowHeader, series.name.to_string(), None)];
+            for c in 0..self.categories.len() {
+                let value = series.values.get(c).copied();
+                let text = value
+                    .map(|v| ctx.formatter().format_shortest(v))
+                    .unwrap_or_default();
+                cells.push((Role::Cell, text, value));
+            }
+            rows.push(cells);
+        }
+
+        let mut table = NodeBuilder::new(Role::Table);
+        table.set_row_count(rows.len());
+        table.set_column_count(self.categories.len() + 1);
+        let count = 1 + rows.iter().map(|cells| cells.len() + 1).sum::<usize>();
+        let mut ids = self.access_ids(count).iter().copied();
+        let table_id = ids.next().unwrap();
+        for (row_index, cells) in rows.into_iter().enumerate() {
+            let row_id = ids.next().unwrap();
+            let mut row = NodeBuilder::new(Role::Row);
+            row.set_row_index(row_index);
+            for (column_index, (role, text, value)) in cells.into_iter().enumerate() {
+                let cell_id = ids.next().unwrap();
+                let mut cell = NodeBuilder::new(role);
+                cell.set_row_index(row_index);
+                cell.set_column_index(column_index);
+                cell.set_value(text);
+                if let Some(value) = value {
+                    cell.set_numeric_value(value);
+                }
+                ctx.tree_update.nodes.push((cell_id, cell.build()));
+                row.push_child(cell_id);
+            }
+            ctx.tree_update.nodes.push((row_id, row.build()));
+            table.push_child(row_id);
+        }
+        ctx.tree_update.nodes.push((table_id, table.build()));
+        node.push_child(table_id);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        SmallVec::new()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("ColumnChart")
+    }
+
+    fn get_debug_text(&self) -> Option<String> {
+        Some(self.summary(&BasicFormatter::default()))
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locale::Locale;
+    use crate::testing::TestHarness;
+
+    fn chart() -> ColumnChart {
+        ColumnChart::new(
+            vec!["Q1".into(), "Q2".into(), "Q3".into()],
+            vec![
+                Series::new("Sales", vec![3., 10., 5.]),
+                Series::new("Costs", vec![-2., f64::NAN]),
+            ],
+        )
+    }
+
+    #[test]
+    fn summary_describes_each_series() {
+        assert_eq!(
+            chart().summary(&BasicFormatter::default()),
+            "Column chart of 2 series over 3 categories. \
+             Sales: from 3 (Q1) to 10 (Q2), total 18. \
+             Costs: from -2 (Q1) to -2 (Q1), total -2."
+        );
+        let mut localized = chart();
+        localized.series = vec![Series::new("Sales", vec![1234.5])];
+        let de = BasicFormatter::new(Locale::new("de", Some("DE")));
+        assert_eq!(
+            localized.summary(&de),
+            "Column chart of 1 series over 3 categories. \
+             Sales: from 1.234,5 (Q1) to 1.234,5 (Q1), total 1.234,5."
+        );
+        assert_eq!(chart().value_range(), (-2., 10.));
+    }
+
+    #[test]
+    fn edit_data() {
+        let mut harness = TestHarness::create(chart().with_label("Revenue"));
+        harness.edit_root_widget(|mut chart| {
+            let mut chart = chart.downcast::<ColumnChart>();
+            ColumnChart::set_series(&mut chart, vec![Series::new("Sales", vec![1.])]);
+        });
+        let root = harness.root_widget();
+        let edited = root.downcast::<ColumnChart>().unwrap();
+        assert_eq!(
+            edited.summary(&BasicFormatter::default()),
+            "Column chart of 1 series over 3 categories. Sales: from 1 (Q1) to 1 (Q1), total 1."
+        );
+
+        let mut chart = chart();
+        let ids = chart.access_ids(4).to_vec();
+        assert_eq!(chart.access_ids(2), &ids[..2]);
+        assert_eq!(chart.access_ids.len(), 4);
+    }
+}
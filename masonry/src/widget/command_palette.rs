@@ -0,0 +1,723 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A searchable list of commands, shown in a modal overlay above some content.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use crate::action::Action;
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::text::{ArcStr, TextEditor};
+use crate::widget::drawer::Scrim;
+use crate::widget::menu::{Menu, MenuKeyResult};
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, AllowRawMut, BoxConstraints, CursorIcon, EventCtx, LayoutCtx,
+    PaintCtx, Point, PointerEvent, QueryCtx, Rect, RegisterCtx, Size, TextEvent, Update, UpdateCtx,
+    Widget, WidgetId,
+};
+
+/// The most commands listed at once.
+const MAX_MATCHES: usize = 10;
+/// The widest the panel gets.
+const PANEL_WIDTH: f64 = 500.;
+/// The space above the panel, and around it in narrow windows.
+const PANEL_MARGIN: f64 = 60.;
+const PADDING: f64 = 8.;
+const FIELD_HEIGHT: f64 = 32.;
+
+/// A command listed in a [`CommandPalette`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Command {
+    /// The identifier sent in [`Action::CommandInvoked`].
+    pub id: ArcStr,
+    /// The text shown in the list, which is searched.
+    pub title: ArcStr,
+    /// The keyboard shortcut which also runs the command, shown next to the title.
+    pub shortcut: Option<ArcStr>,
+}
+
+impl Command {
+    pub fn new(id: impl Into<ArcStr>, title: impl Into<ArcStr>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            shortcut: None,
+        }
+    }
+
+    /// Builder-style method to set the shortcut shown next to the title.
+    ///
+    /// This is only a hint for the user: the palette doesn't handle the shortcut.
+    pub fn with_shortcut(mut self, shortcut: impl Into<ArcStr>) -> Self {
+        self.shortcut = Some(shortcut.into());
+        self
+    }
+
+    fn menu_text(&self) -> ArcStr {
+        match &self.shortcut {
+            Some(shortcut) => format!("{}    {shortcut}", self.title).into(),
+            None => self.title.clone(),
+        }
+    }
+}
+
+/// A container which shows a searchable list of commands above its content.
+///
+/// The palette is opened by pressing <kbd>Ctrl</kbd>+<kbd>Shift</kbd>+<kbd>P</kbd> while
+/// the content has focus, or with [`set_open`](Self::set_open). While it is open, the
+/// content is dimmed and blocked, without changing whether the app disabled it, and the
+/// palette has focus: typed text filters the commands by fuzzy matching their titles, Up
+/// and Down move through them, and Enter runs the highlighted one. Escape, or a click
+/// outside of the panel, closes it. The commands are exposed to assistive technologies
+/// as a list box.
+///
+/// Running a command, with the keyboard or a click, submits an
+/// [`Action::CommandInvoked`] with its id and closes the palette.
+pub struct CommandPalette {
+    open: bool,
+    commands: Vec<Command>,
+    /// The indices of the listed commands, best match first.
+    matches: Vec<usize>,
+    content: WidgetPod<Box<dyn Widget>>,
+    scrim: WidgetPod<Scrim>,
+    search: WidgetPod<SearchPanel>,
+    menu: WidgetPod<Menu>,
+}
+
+// --- MARK: BUILDERS ---
+impl CommandPalette {
+    /// Create a closed palette listing `commands` above `content`.
+    pub fn new(content: impl Widget, commands: impl IntoIterator<Item = Command>) -> Self {
+        Self::new_pod(WidgetPod::new(content).boxed(), commands)
+    }
+
+    pub fn new_pod(
+        content: WidgetPod<Box<dyn Widget>>,
+        commands: impl IntoIterator<Item = Command>,
+    ) -> Self {
+        Self {
+            open: false,
+            commands: commands.into_iter().collect(),
+            matches: Vec::new(),
+            content,
+            scrim: WidgetPod::new(Scrim { alpha: 1. }),
+            search: WidgetPod::new(SearchPanel::new()),
+            menu: WidgetPod::new(Menu::list_box(Default::default())),
+        }
+    }
+
+    /// Whether the palette is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// The commands listed in the palette.
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl CommandPalette {
+    pub fn content_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.content)
+    }
+
+    /// Open or close the palette.
+    ///
+    /// Opening the palette clears the search text and gives it focus.
+    pub fn set_open(this: &mut WidgetMut<'_, Self>, open: bool) {
+        if this.widget.open == open {
+            return;
+        }
+        this.widget.open = open;
+        this.ctx.set_stashed(&mut this.widget.scrim, !open);
+        this.ctx.set_stashed(&mut this.widget.search, !open);
+        this.ctx.set_child_disabled(&mut this.widget.content, open);
+        if open {
+            {
+                let mut search = this.ctx.get_mut(&mut this.widget.search);
+                search
+                    .widget
+                    .editor
+                    .set_text_with_caret_at_end(String::new());
+                search.ctx.request_layout();
+            }
+            Self::update_matches(this);
+            // The palette handles the keyboard while it's open, for the search field and the list.
+            this.ctx.global_state.next_focused_widget = Some(this.ctx.widget_id());
+        } else {
+            this.ctx.set_stashed(&mut this.widget.menu, true);
+        }
+        this.ctx.request_layout();
+    }
+
+    /// Replace the listed commands.
+    pub fn set_commands(
+        this: &mut WidgetMut<'_, Self>,
+        commands: impl IntoIterator<Item = Command>,
+    ) {
+        this.widget.commands = commands.into_iter().collect();
+        if this.widget.open {
+            Self::update_matches(this);
+        }
+    }
+
+    /// List the commands matching the search text, and highlight the best match.
+    fn update_matches(this: &mut WidgetMut<'_, Self>) {
+        let query = {
+            let search = this.ctx.get_mut(&mut this.widget.search);
+            search.widget.editor.text().clone()
+        };
+        let mut scored: Vec<(usize, u32)> = this
+            .widget
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(index, command)| Some((index, fuzzy_score(&query, &command.title)?)))
+            .collect();
+        // The sort is stable, so commands which match equally well keep their order.
+        scored.sort_by(|(_, a), (_, b)| b.cmp(a));
+        this.widget.matches = scored
+            .into_iter()
+            .take(MAX_MATCHES)
+            .map(|(index, _)| index)
+            .collect();
+
+        let items: Vec<ArcStr> = this
+            .widget
+            .matches
+            .iter()
+            .map(|&index| this.widget.commands[index].menu_text())
+            .collect();
+        let is_empty = items.is_empty();
+        {
+            let mut menu = this.ctx.get_mut(&mut this.widget.menu);
+            Menu::set_items(&mut menu, items.into_iter().collect());
+            menu.widget.highlight(Some(0));
+        }
+        this.ctx.set_stashed(&mut this.widget.menu, is_empty);
+        this.ctx.request_layout();
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl CommandPalette {
+    fn set_open_later(ctx: &mut EventCtx, open: bool) {
+        ctx.mutate_self_later(move |mut palette| {
+            let mut palette = palette.downcast::<Self>();
+            Self::set_open(&mut palette, open);
+        });
+    }
+
+    /// Run the command at `index` in the list, and close the palette.
+    fn invoke(&mut self, ctx: &mut EventCtx, index: usize) {
+        if let Some(&command) = self.matches.get(index) {
+            let id = self.commands[command].id.clone();
+            ctx.submit_action(Action::CommandInvoked(id));
+        }
+        Self::set_open_later(ctx, false);
+    }
+}
+
+fn is_toggle_shortcut(key: &Key, mods: ModifiersState) -> bool {
+    let Key::Character(c) = key else {
+        return false;
+    };
+    c.eq_ignore_ascii_case("p") && mods.control_key() && mods.shift_key()
+}
+
+/// Score how well `query` matches `text`, if all the characters of `query`, except
+/// whitespace, appear in `text` in the same order, ignoring case.
+///
+/// Higher is better. Consecutive characters and characters at the start of words score
+/// more, so that "opf" ranks "Open File" above "Copy Path of File".
+fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let mut query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in text.chars() {
+        let Some(&wanted) = query.peek() else {
+            break;
+        };
+        let matched = c.to_lowercase().next() == Some(wanted);
+        if matched {
+            score += 1;
+            if previous_matched {
+                score += 2;
+            }
+            if previous.map_or(true, |previous| !previous.is_alphanumeric()) {
+                score += 3;
+            }
+            query.next();
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+    query.peek().is_none().then_some(score)
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for CommandPalette {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        if !self.open {
+            return;
+        }
+        match event {
+            PointerEvent::PointerDown(_, _) if ctx.target() == self.scrim.id() => {
+                Self::set_open_later(ctx, false);
+                ctx.set_handled();
+            }
+            PointerEvent::PointerUp(_, _) => {
+                let activated = ctx.get_raw_mut(&mut self.menu).widget().take_activated();
+                if let Some(index) = activated {
+                    self.invoke(ctx, index);
+                    ctx.set_handled();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        if let TextEvent::KeyboardKey(key_event, mods) = event {
+            if key_event.state.is_pressed() {
+                let key = &key_event.logical_key;
+                if is_toggle_shortcut(key, *mods) {
+                    Self::set_open_later(ctx, !self.open);
+                    ctx.set_handled();
+                    return;
+                }
+                if !self.open {
+                    return;
+                }
+                match key {
+                    Key::Named(NamedKey::Escape) => {
+                        Self::set_open_later(ctx, false);
+                        ctx.set_handled();
+                        return;
+                    }
+                    Key::Named(NamedKey::ArrowUp | NamedKey::ArrowDown | NamedKey::Enter) => {
+                        let result = {
+                            let mut menu = ctx.get_raw_mut(&mut self.menu);
+                            let result = menu.widget().on_key(key);
+                            if result == MenuKeyResult::Highlighted {
+                                menu.ctx().request_render();
+                            }
+                            result
+                        };
+                        if let MenuKeyResult::Activated(index) = result {
+                            self.invoke(ctx, index);
+                        }
+                        ctx.set_handled();
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if !self.open || ctx.target() != ctx.widget_id() {
+            return;
+        }
+
+        // The editor is borrowed from the search panel, so that the text changes are
+        // reported by the palette itself.
+        let mut editor = std::mem::replace(
+            &mut ctx.get_raw_mut(&mut self.search).widget().editor,
+            SearchPanel::new_editor(),
+        );
+        let handled = editor.text_event(ctx, event);
+        let mut search = ctx.get_raw_mut(&mut self.search);
+        search.widget().editor = editor;
+        if handled.is_handled() {
+            search.ctx().request_layout();
+            search.ctx().request_render();
+            drop(search);
+            ctx.mutate_self_later(|mut palette| {
+                let mut palette = palette.downcast::<Self>();
+                Self::update_matches(&mut palette);
+            });
+            ctx.set_handled();
+        }
+    }
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, _event: &AccessEvent) {
+        if !self.open {
+            return;
+        }
+        let activated = ctx.get_raw_mut(&mut self.menu).widget().take_activated();
+        if let Some(index) = activated {
+            self.invoke(ctx, index);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::WidgetAdded => {
+                ctx.set_stashed(&mut self.scrim, true);
+                ctx.set_stashed(&mut self.search, true);
+                ctx.set_stashed(&mut self.menu, true);
+            }
+            Update::FocusChanged(focused) => {
+                {
+                    let mut search = ctx.get_raw_mut(&mut self.search);
+                    if *focused {
+                        search.widget().editor.focus_gained();
+                    } else {
+                        search.widget().editor.focus_lost();
+                    }
+                    search.ctx().request_render();
+                }
+                if !focused && self.open {
+                    ctx.mutate_self_later(|mut palette| {
+                        let mut palette = palette.downcast::<Self>();
+                        Self::set_open(&mut palette, false);
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn accepts_text_input(&self) -> bool {
+        true
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.content);
+        ctx.register_child(&mut self.scrim);
+        ctx.register_child(&mut self.search);
+        ctx.register_child(&mut self.menu);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let content_size = ctx.run_layout(&mut self.content, bc);
+        ctx.place_child(&mut self.content, Point::ORIGIN);
+        // The panel is centered in the available space, not in the content.
+        let size = if bc.is_width_bounded() && bc.is_height_bounded() {
+            bc.max()
+        } else {
+            content_size
+        };
+        if !self.open {
+            return size;
+        }
+
+        ctx.run_layout(&mut self.scrim, &BoxConstraints::tight(size));
+        ctx.place_child(&mut self.scrim, Point::ORIGIN);
+
+        let width = PANEL_WIDTH.min(size.width - 2. * PADDING).max(0.);
+        let origin = Point::new(
+            (size.width - width) / 2.,
+            PANEL_MARGIN.min(size.height / 10.),
+        );
+        let list_width = (width - 2. * PADDING).max(0.);
+        let list_height = if self.matches.is_empty() {
+            0.
+        } else {
+            let list_bc = BoxConstraints::new(
+                Size::new(list_width, 0.),
+                Size::new(list_width, f64::INFINITY),
+            );
+            let list_size = ctx.run_layout(&mut self.menu, &list_bc);
+            ctx.place_child(
+                &mut self.menu,
+                origin + (PADDING, 2. * PADDING + FIELD_HEIGHT),
+            );
+            list_size.height + PADDING
+        };
+
+        let panel_size = Size::new(width, 2. * PADDING + FIELD_HEIGHT + list_height);
+        ctx.run_layout(&mut self.search, &BoxConstraints::tight(panel_size));
+        ctx.place_child(&mut self.search, origin);
+
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![
+            self.content.id(),
+            self.scrim.id(),
+            self.search.id(),
+            self.menu.id()
+        ]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("CommandPalette")
+    }
+}
+
+// --- MARK: SEARCH PANEL ---
+/// The background of the panel of a [`CommandPalette`], with the search field at its top.
+///
+/// The palette handles the keyboard, and edits the text of the field through
+/// raw access. The list of commands is a sibling, painted above the panel.
+struct SearchPanel {
+    editor: TextEditor,
+    editor_origin: Point,
+}
+
+impl SearchPanel {
+    fn new() -> Self {
+        Self {
+            editor: Self::new_editor(),
+            editor_origin: Point::ZERO,
+        }
+    }
+
+    fn new_editor() -> TextEditor {
+        TextEditor::new(
+            String::new(),
+            *theme::TEXT_SIZE_NORMAL.default_value() as f32,
+        )
+    }
+
+    fn field_rect(&self, size: Size) -> Rect {
+        Rect::new(
+            PADDING,
+            PADDING,
+            size.width - PADDING,
+            PADDING + FIELD_HEIGHT,
+        )
+    }
+}
+
+impl AllowRawMut for SearchPanel {}
+
+impl Widget for SearchPanel {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let editor_origin = ctx.window_origin() + self.editor_origin.to_vec2();
+        match event {
+            PointerEvent::PointerDown(button, state)
+                if self.editor.pointer_down(editor_origin, state, *button) =>
+            {
+                ctx.capture_pointer();
+                ctx.request_layout();
+                ctx.request_render();
+            }
+            PointerEvent::PointerMove(state)
+                if ctx.has_pointer_capture() && self.editor.pointer_move(editor_origin, state) =>
+            {
+                ctx.request_layout();
+                ctx.request_render();
+            }
+            PointerEvent::PointerUp(button, state) if ctx.has_pointer_capture() => {
+                self.editor.pointer_up(editor_origin, state, *button);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = bc.max();
+        let field = self.field_rect(size);
+        self.editor
+            .set_text_size(ctx.env().get(theme::TEXT_SIZE_NORMAL) as f32);
+        self.editor.set_brush(ctx.env().get(theme::TEXT_COLOR));
+        self.editor
+            .set_max_advance(Some((field.width() - 2. * PADDING).max(0.) as f32));
+        if self.editor.needs_rebuild() {
+            let (font_ctx, layout_ctx) = ctx.text_contexts();
+            self.editor.rebuild(font_ctx, layout_ctx);
+        }
+        let editor_height = self.editor.size().height;
+        self.editor_origin = Point::new(
+            field.x0 + PADDING,
+            field.y0 + (field.height() - editor_height) / 2.,
+        );
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let panel = ctx
+            .size()
+            .to_rect()
+            .to_rounded_rect(ctx.env().get(theme::BUTTON_RADIUS));
+        fill_color(scene, &panel, ctx.env().get(theme::BACKGROUND_DARK));
+        stroke(scene, &panel, ctx.env().get(theme::BORDER_LIGHT), 1.);
+
+        let border_width = ctx.env().get(theme::TEXTBOX_BORDER_WIDTH);
+        let field = self
+            .field_rect(ctx.size())
+            .inset(-border_width / 2.)
+            .to_rounded_rect(ctx.env().get(theme::TEXTBOX_BORDER_RADIUS));
+        fill_color(scene, &field, ctx.env().get(theme::BACKGROUND_LIGHT));
+        stroke(
+            scene,
+            &field,
+            ctx.env().get(theme::PRIMARY_LIGHT),
+            border_width,
+        );
+
+        self.editor.set_glyph_snapping(ctx.glyph_snapping());
+        self.editor.draw(scene, self.editor_origin);
+    }
+
+    fn get_cursor(&self, _ctx: &QueryCtx, _pos: Point) -> CursorIcon {
+        CursorIcon::Text
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::SearchInput
+    }
+
+    fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        self.editor.accessibility(ctx.tree_update, node);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("SearchPanel")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::widget::{Button, WidgetRef};
+    use crate::PointerButton;
+
+    fn commands() -> Vec<Command> {
+        vec![
+            Command::new("copy-path", "Copy Path of File"),
+            Command::new("open", "Open File").with_shortcut("Ctrl+O"),
+            Command::new("save", "Save").with_shortcut("Ctrl+S"),
+        ]
+    }
+
+    #[test]
+    fn fuzzy_matching() {
+        assert!(fuzzy_score("opf", "Open File") > fuzzy_score("opf", "Copy Path of File"));
+        assert!(fuzzy_score("op fi", "Open File").is_some());
+        assert_eq!(fuzzy_score("fo", "Open File"), None);
+        assert_eq!(fuzzy_score("", "Save"), Some(0));
+    }
+
+    fn palette(harness: &TestHarness) -> WidgetRef<'_, CommandPalette> {
+        harness.root_widget().downcast::<CommandPalette>().unwrap()
+    }
+
+    fn click(harness: &mut TestHarness, pos: impl Into<Point>) {
+        harness.mouse_move(pos);
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+    }
+
+    #[test]
+    fn search_and_invoke() {
+        let [content_id] = widget_ids();
+        let widget = CommandPalette::new(Button::new("Content").with_id(content_id), commands());
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+        let palette_id = harness.root_widget().id();
+
+        harness.edit_root_widget(|mut palette| {
+            let mut palette = palette.downcast::<CommandPalette>();
+            CommandPalette::set_open(&mut palette, true);
+        });
+        assert_eq!(harness.focused_widget().map(|w| w.id()), Some(palette_id));
+        assert!(harness.get_widget(content_id).ctx().is_disabled());
+
+        harness.keyboard_type_chars("opf");
+        assert_eq!(palette(&harness).matches, vec![1, 0]);
+        for text in ["o", "op", "opf"] {
+            assert_eq!(
+                harness.pop_action(),
+                Some((Action::TextChanged(text.into()), palette_id))
+            );
+        }
+
+        // The best match is listed first.
+        let menu_id = palette(&harness).menu.id();
+        let menu_rect = harness.get_widget(menu_id).ctx().window_layout_rect();
+        click(&mut harness, (menu_rect.x0 + 10., menu_rect.y0 + 10.));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::CommandInvoked("open".into()), palette_id))
+        );
+        assert!(!palette(&harness).is_open());
+        assert!(!harness.get_widget(content_id).ctx().is_disabled());
+    }
+
+    #[test]
+    fn commands_are_list_box_options() {
+        let widget = CommandPalette::new(Button::new("Content"), commands());
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+        harness.edit_root_widget(|mut palette| {
+            let mut palette = palette.downcast::<CommandPalette>();
+            CommandPalette::set_open(&mut palette, true);
+        });
+
+        let menu_id = palette(&harness).menu.id();
+        let menu = harness.get_widget(menu_id);
+        assert_eq!(menu.accessibility_role(), Role::ListBox);
+        let items = menu.children();
+        assert_eq!(items.len(), 3);
+        for (index, item) in items.iter().enumerate() {
+            assert_eq!(item.accessibility_role(), Role::ListBoxOption);
+            assert_eq!(item.ctx().position_in_set(), Some((index, 3)));
+        }
+    }
+
+    #[test]
+    fn closing_keeps_disabled_content_disabled() {
+        let [content_id] = widget_ids();
+        let widget = CommandPalette::new(Button::new("Content").with_id(content_id), commands());
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+        harness.edit_widget(content_id, |mut button| {
+            button.ctx.set_disabled(true);
+        });
+
+        for open in [true, false] {
+            harness.edit_root_widget(|mut palette| {
+                let mut palette = palette.downcast::<CommandPalette>();
+                CommandPalette::set_open(&mut palette, open);
+            });
+        }
+        assert!(harness.get_widget(content_id).ctx().is_disabled());
+    }
+
+    #[test]
+    fn click_outside_closes() {
+        let widget = CommandPalette::new(Button::new("Content"), commands());
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+        harness.edit_root_widget(|mut palette| {
+            let mut palette = palette.downcast::<CommandPalette>();
+            CommandPalette::set_open(&mut palette, true);
+        });
+        assert_eq!(palette(&harness).matches, vec![0, 1, 2]);
+
+        click(&mut harness, (10., 390.));
+        assert!(!palette(&harness).is_open());
+        assert_eq!(harness.pop_action(), None);
+    }
+}
@@ -0,0 +1,283 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A modal dialog shown above the rest of the content.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::widget::drawer::Scrim;
+use crate::widget::widget::get_child_at_pos;
+use crate::widget::{FocusGroup, WidgetMut, WidgetPod, WidgetRef};
+use crate::{
+    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point, PointerEvent,
+    QueryCtx, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A container which shows a modal dialog centered above its content.
+///
+/// While the dialog is open, the content is dimmed and blocked: it doesn't receive pointer
+/// events, its widgets can't be focused, and it's hidden from assistive technologies, which
+/// are told that the dialog is modal. Whether the app disabled the content is left as is.
+/// The first focusable widget of the dialog is focused when it opens, and <kbd>Tab</kbd>
+/// cycles through the widgets of the dialog without leaving it, as the dialog is wrapped in
+/// a [`FocusGroup`]. When the dialog closes, the widget which was focused before it opened
+/// is focused again.
+pub struct Dialog {
+    open: bool,
+    /// The widget which was focused when the dialog opened.
+    previously_focused: Option<WidgetId>,
+    /// Whether the first widget of the dialog should be focused at the next layout,
+    /// once the focus chain includes it.
+    focus_pending: bool,
+    content: WidgetPod<Box<dyn Widget>>,
+    scrim: WidgetPod<Scrim>,
+    dialog: WidgetPod<FocusGroup<Box<dyn Widget>>>,
+}
+
+// --- MARK: BUILDERS ---
+impl Dialog {
+    /// Create a new container, whose closed `dialog` can be shown above `content`.
+    pub fn new(content: impl Widget, dialog: impl Widget) -> Self {
+        Self::new_pod(
+            WidgetPod::new(content).boxed(),
+            WidgetPod::new(dialog).boxed(),
+        )
+    }
+
+    pub fn new_pod(
+        content: WidgetPod<Box<dyn Widget>>,
+        dialog: WidgetPod<Box<dyn Widget>>,
+    ) -> Self {
+        Self {
+            open: false,
+            previously_focused: None,
+            focus_pending: false,
+            content,
+            scrim: WidgetPod::new(Scrim { alpha: 1. }),
+            dialog: WidgetPod::new(FocusGroup::new_pod(dialog).with_wrap(true)),
+        }
+    }
+
+    /// Builder-style method to set whether the dialog is open.
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl Dialog {
+    pub fn content_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.content)
+    }
+
+    /// The focus group wrapping the dialog. Use [`FocusGroup::child_mut`] to edit the dialog.
+    pub fn dialog_mut<'t>(
+        this: &'t mut WidgetMut<'_, Self>,
+    ) -> WidgetMut<'t, FocusGroup<Box<dyn Widget>>> {
+        this.ctx.get_mut(&mut this.widget.dialog)
+    }
+
+    /// Open or close the dialog.
+    pub fn set_open(this: &mut WidgetMut<'_, Self>, open: bool) {
+        if this.widget.open == open {
+            return;
+        }
+        this.widget.open = open;
+        this.ctx.set_stashed(&mut this.widget.scrim, !open);
+        this.ctx.set_stashed(&mut this.widget.dialog, !open);
+        this.ctx.set_child_disabled(&mut this.widget.content, open);
+        this.ctx
+            .set_child_accessibility_hidden(&mut this.widget.content, open);
+        if open {
+            this.widget.previously_focused = this.ctx.global_state.focused_widget;
+            this.widget.focus_pending = true;
+        } else {
+            this.widget.focus_pending = false;
+            // The content is enabled again before the focus is updated, so the widget
+            // can take the focus back unless it was removed or disabled in the meantime.
+            if let Some(id) = this.widget.previously_focused.take() {
+                this.ctx.global_state.next_focused_widget = Some(id);
+            }
+        }
+        this.ctx.request_layout();
+        this.ctx.request_accessibility_update();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for Dialog {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded = event {
+            ctx.set_stashed(&mut self.scrim, !self.open);
+            ctx.set_stashed(&mut self.dialog, !self.open);
+            if self.open {
+                self.previously_focused = ctx.global_state.focused_widget;
+                self.focus_pending = true;
+                ctx.set_child_disabled(&mut self.content, true);
+                ctx.set_child_accessibility_hidden(&mut self.content, true);
+            }
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.content);
+        ctx.register_child(&mut self.scrim);
+        ctx.register_child(&mut self.dialog);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let content_size = ctx.run_layout(&mut self.content, bc);
+        ctx.place_child(&mut self.content, Point::ORIGIN);
+        // The dialog is centered in the available space, not in the content.
+        let size = if bc.is_width_bounded() && bc.is_height_bounded() {
+            bc.max()
+        } else {
+            content_size
+        };
+
+        if !self.open {
+            return size;
+        }
+
+        ctx.run_layout(&mut self.scrim, &BoxConstraints::tight(size));
+        ctx.place_child(&mut self.scrim, Point::ORIGIN);
+
+        let dialog_size = ctx.run_layout(&mut self.dialog, &BoxConstraints::new(Size::ZERO, size));
+        let origin = ((size - dialog_size) / 2.).to_vec2().to_point();
+        ctx.place_child(&mut self.dialog, origin);
+
+        // The focus chain of the dialog is up to date by the time it is laid out.
+        if std::mem::take(&mut self.focus_pending) {
+            let first = ctx
+                .get_child_state(&self.dialog)
+                .focus_chain
+                .first()
+                .copied();
+            ctx.global_state.next_focused_widget = first;
+        }
+
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        // The content is hidden while the dialog is open, so the container only holds the dialog.
+        if self.open {
+            Role::Dialog
+        } else {
+            Role::GenericContainer
+        }
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        if self.open {
+            node.set_modal();
+        }
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.content.id(), self.scrim.id(), self.dialog.id()]
+    }
+
+    fn get_child_at_pos<'c>(
+        &self,
+        ctx: QueryCtx<'c>,
+        pos: Point,
+    ) -> Option<WidgetRef<'c, dyn Widget>> {
+        if !self.open {
+            return get_child_at_pos(self, ctx, pos);
+        }
+        // The content is blocked while the dialog is open, so pointer events outside of the
+        // dialog are sent to the container itself.
+        let dialog = ctx.get(self.dialog.id());
+        dialog
+            .ctx()
+            .window_layout_rect()
+            .contains(pos)
+            .then_some(dialog)
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Dialog")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::widget::{Button, Flex, SimpleWidget};
+    use crate::Action;
+
+    #[test]
+    fn open_traps_and_restores_focus() {
+        let [content_id, first_id, second_id] = widget_ids();
+        let focusable = || SimpleWidget::new(()).accepts_focus(true);
+        let dialog = Dialog::new(
+            Button::new("Behind").with_id(content_id),
+            Flex::column()
+                .with_child_id(focusable(), first_id)
+                .with_child_id(focusable(), second_id),
+        );
+        let mut harness = TestHarness::create(dialog);
+        harness.focus_on(Some(content_id));
+
+        harness.edit_root_widget(|mut dialog| {
+            let mut dialog = dialog.downcast::<Dialog>();
+            Dialog::set_open(&mut dialog, true);
+        });
+        assert!(harness.get_widget(content_id).ctx().is_disabled());
+        assert_eq!(harness.focused_widget().map(|w| w.id()), Some(first_id));
+
+        // Clicking the dimmed content doesn't press the button.
+        harness.mouse_click_on(content_id);
+        assert_eq!(harness.pop_action(), None);
+
+        harness.edit_root_widget(|mut dialog| {
+            let mut dialog = dialog.downcast::<Dialog>();
+            Dialog::set_open(&mut dialog, false);
+        });
+        assert_eq!(harness.focused_widget().map(|w| w.id()), Some(content_id));
+        harness.mouse_click_on(content_id);
+        assert!(matches!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(_), _))
+        ));
+    }
+
+    #[test]
+    fn closing_keeps_disabled_content_disabled() {
+        let [content_id] = widget_ids();
+        let dialog = Dialog::new(
+            Button::new("Behind").with_id(content_id),
+            SimpleWidget::new(()).accepts_focus(true),
+        );
+        let mut harness = TestHarness::create(dialog);
+        harness.edit_widget(content_id, |mut button| {
+            button.ctx.set_disabled(true);
+        });
+
+        harness.edit_root_widget(|mut dialog| {
+            let mut dialog = dialog.downcast::<Dialog>();
+            Dialog::set_open(&mut dialog, true);
+        });
+        assert!(harness.get_widget(content_id).ctx().is_disabled());
+        harness.edit_root_widget(|mut dialog| {
+            let mut dialog = dialog.downcast::<Dialog>();
+            Dialog::set_open(&mut dialog, false);
+        });
+        assert!(harness.get_widget(content_id).ctx().is_disabled());
+    }
+}
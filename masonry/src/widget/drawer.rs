@@ -0,0 +1,583 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A panel which slides in from an edge of its container, above the rest of the content.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::gesture::{Gesture, GestureRecognizer};
+use crate::paint_scene_helpers::fill_color;
+use crate::widget::widget::get_child_at_pos;
+use crate::widget::{WidgetMut, WidgetPod, WidgetRef};
+use crate::{
+    theme, AccessCtx, AccessEvent, Action, AllowRawMut, BoxConstraints, EventCtx, LayoutCtx,
+    PaintCtx, Point, PointerButton, PointerEvent, QueryCtx, RegisterCtx, Size, TextEvent, Update,
+    UpdateCtx, Vec2, Widget, WidgetId,
+};
+
+/// How fast the panel settles into place, as the fraction of the remaining distance covered
+/// per second, on a logarithmic scale.
+const SETTLE_RATE: f64 = 20.0;
+
+/// How far ahead the panel is projected when a drag ends, in seconds.
+///
+/// A faster drag carries the panel further, so that a quick flick is enough to dismiss it.
+const PROJECTION_TIME: f64 = 0.2;
+
+/// The edge of a [`Drawer`] that its panel slides in from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrawerEdge {
+    Left,
+    Right,
+    Top,
+    /// The panel is a bottom sheet.
+    #[default]
+    Bottom,
+}
+
+impl DrawerEdge {
+    /// The length of `size` along the axis the panel slides on.
+    fn length(self, size: Size) -> f64 {
+        match self {
+            Self::Left | Self::Right => size.width,
+            Self::Top | Self::Bottom => size.height,
+        }
+    }
+
+    /// How much `delta` moves away from the edge, which shows more of the panel.
+    fn outward(self, delta: Vec2) -> f64 {
+        match self {
+            Self::Left => delta.x,
+            Self::Right => -delta.x,
+            Self::Top => delta.y,
+            Self::Bottom => -delta.y,
+        }
+    }
+}
+
+/// A container which shows a panel sliding in from one of its edges, above its content.
+///
+/// While the panel is open, the content is dimmed and disabled: it doesn't receive pointer
+/// events, and its widgets can't be focused, so that Tab only cycles through the panel.
+///
+/// The panel can be dragged away from the edge to resize it, up to its
+/// [maximum extent](Self::max_extent), and towards the edge to dismiss it. When the drag ends,
+/// the panel settles at the closest of these sizes, taking the speed of the drag into account.
+/// The panel is also dismissed by clicking on the dimmed content, or by pressing Escape.
+/// Dismissing the panel submits an [`Action::DrawerDismissed`].
+pub struct Drawer {
+    edge: DrawerEdge,
+    open: bool,
+    extent: f64,
+    max_extent: f64,
+    /// The length of the panel which is currently visible.
+    shown: f64,
+    /// The length the panel is animating to, if any.
+    target: Option<f64>,
+    /// Whether the panel is entirely hidden, in which case it is stashed along with the scrim.
+    hidden: bool,
+    recognizer: GestureRecognizer,
+    pressed_on_scrim: bool,
+    content: WidgetPod<Box<dyn Widget>>,
+    scrim: WidgetPod<Scrim>,
+    panel: WidgetPod<Box<dyn Widget>>,
+}
+
+// --- MARK: BUILDERS ---
+impl Drawer {
+    /// Create a new closed drawer, whose `panel` slides in from `edge` above `content`.
+    pub fn new(edge: DrawerEdge, content: impl Widget, panel: impl Widget) -> Self {
+        Self::new_pod(
+            edge,
+            WidgetPod::new(content).boxed(),
+            WidgetPod::new(panel).boxed(),
+        )
+    }
+
+    pub fn new_pod(
+        edge: DrawerEdge,
+        content: WidgetPod<Box<dyn Widget>>,
+        panel: WidgetPod<Box<dyn Widget>>,
+    ) -> Self {
+        Self {
+            edge,
+            open: false,
+            extent: 250.,
+            max_extent: 250.,
+            shown: 0.,
+            target: None,
+            hidden: true,
+            recognizer: GestureRecognizer::new(),
+            pressed_on_scrim: false,
+            content,
+            scrim: WidgetPod::new(Scrim { alpha: 1. }),
+            panel,
+        }
+    }
+
+    /// Create a new closed bottom sheet.
+    pub fn sheet(content: impl Widget, panel: impl Widget) -> Self {
+        Self::new(DrawerEdge::Bottom, content, panel)
+    }
+
+    /// Builder-style method to set whether the panel is open.
+    ///
+    /// A drawer built open shows its panel immediately, without sliding it in.
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = open;
+        self.shown = if open { self.extent } else { 0. };
+        self.hidden = !open;
+        self
+    }
+
+    /// Builder-style method to set the length of the open panel, along the axis it slides on.
+    ///
+    /// The default extent is `250.0`. This also raises the maximum extent if needed.
+    pub fn extent(mut self, extent: f64) -> Self {
+        assert!(extent > 0., "extent must be positive!");
+        self.extent = extent;
+        self.max_extent = self.max_extent.max(extent);
+        if self.open {
+            self.shown = extent;
+        }
+        self
+    }
+
+    /// Builder-style method to set the length the panel can be resized to by dragging it.
+    ///
+    /// By default, this is the same as the [extent](Self::extent), so the panel can't be resized.
+    pub fn max_extent(mut self, max_extent: f64) -> Self {
+        self.max_extent = max_extent.max(self.extent);
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl Drawer {
+    pub fn content_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.content)
+    }
+
+    pub fn panel_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.panel)
+    }
+
+    /// Open or close the panel, sliding it in or out.
+    pub fn set_open(this: &mut WidgetMut<'_, Self>, open: bool) {
+        if this.widget.open == open {
+            return;
+        }
+        this.widget.open = open;
+        this.widget.target = Some(if open { this.widget.extent } else { 0. });
+        if open && this.widget.hidden {
+            this.widget.hidden = false;
+            this.ctx.set_stashed(&mut this.widget.scrim, false);
+            this.ctx.set_stashed(&mut this.widget.panel, false);
+        }
+        Self::content_mut(this).ctx.set_disabled(open);
+        this.ctx.request_anim_frame();
+        this.ctx.request_layout();
+    }
+
+    pub fn set_edge(this: &mut WidgetMut<'_, Self>, edge: DrawerEdge) {
+        this.widget.edge = edge;
+        this.ctx.request_layout();
+    }
+
+    /// Set the length of the open panel, along the axis it slides on.
+    ///
+    /// This also raises the maximum extent if needed.
+    pub fn set_extent(this: &mut WidgetMut<'_, Self>, extent: f64) {
+        assert!(extent > 0., "extent must be positive!");
+        this.widget.extent = extent;
+        this.widget.max_extent = this.widget.max_extent.max(extent);
+        if this.widget.open {
+            this.widget.target = Some(extent);
+            this.ctx.request_anim_frame();
+        }
+    }
+
+    /// Set the length the panel can be resized to by dragging it.
+    pub fn set_max_extent(this: &mut WidgetMut<'_, Self>, max_extent: f64) {
+        this.widget.max_extent = max_extent.max(this.widget.extent);
+        if this.widget.shown > this.widget.max_extent {
+            this.widget.target = Some(this.widget.max_extent);
+            this.ctx.request_anim_frame();
+        }
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl Drawer {
+    /// The opacity of the scrim, which fades in as the panel slides in.
+    fn scrim_alpha(&self) -> f64 {
+        (self.shown / self.extent).min(1.)
+    }
+
+    fn animate_to(&mut self, ctx: &mut EventCtx, target: f64) {
+        self.target = Some(target);
+        ctx.request_anim_frame();
+    }
+
+    fn dismiss(&mut self, ctx: &mut EventCtx) {
+        self.open = false;
+        self.animate_to(ctx, 0.);
+        ctx.mutate_self_later(|mut drawer| {
+            let mut drawer = drawer.downcast::<Self>();
+            Self::content_mut(&mut drawer).ctx.set_disabled(false);
+        });
+        ctx.submit_action(Action::DrawerDismissed);
+    }
+
+    /// Move the panel to the closest of the closed, open and maximum extents, once it has
+    /// been released while moving at `velocity` away from the edge.
+    fn settle(&mut self, ctx: &mut EventCtx, velocity: f64) {
+        let projected = self.shown + velocity * PROJECTION_TIME;
+        let closest = [0., self.extent, self.max_extent]
+            .into_iter()
+            .min_by(|a, b| (a - projected).abs().total_cmp(&(b - projected).abs()))
+            .unwrap();
+        if closest == 0. {
+            self.dismiss(ctx);
+        } else {
+            self.animate_to(ctx, closest);
+        }
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for Drawer {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        if !self.open {
+            return;
+        }
+        if let PointerEvent::PointerDown(PointerButton::Primary, _) = event {
+            // Presses on the widgets of the panel are left to them, only presses on the
+            // dimmed content or on the background of the panel dismiss or drag it.
+            let target = ctx.target();
+            if target != ctx.widget_id() && target != self.panel.id() {
+                return;
+            }
+            ctx.capture_pointer();
+            self.pressed_on_scrim = target == ctx.widget_id();
+        }
+        let gesture =
+            self.recognizer
+                .handle_pointer_event(event, ctx.window_origin(), Instant::now());
+        match gesture {
+            Some(Gesture::Tap { .. }) if self.pressed_on_scrim => self.dismiss(ctx),
+            Some(Gesture::DragStart { .. }) => self.target = None,
+            Some(Gesture::DragUpdate { delta, .. }) => {
+                self.shown = (self.shown + self.edge.outward(delta)).clamp(0., self.max_extent);
+                let alpha = self.scrim_alpha();
+                {
+                    let mut scrim = ctx.get_raw_mut(&mut self.scrim);
+                    scrim.widget().alpha = alpha;
+                    scrim.ctx().request_paint_only();
+                }
+                ctx.request_layout();
+            }
+            Some(Gesture::DragEnd { velocity, .. }) => {
+                let velocity = self.edge.outward(velocity);
+                self.settle(ctx, velocity);
+            }
+            Some(Gesture::DragCancel) => self.settle(ctx, 0.),
+            _ => return,
+        }
+        ctx.set_handled();
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        match event {
+            TextEvent::KeyboardKey(event, _)
+                if self.open
+                    && event.state.is_pressed()
+                    && event.logical_key == Key::Named(NamedKey::Escape) =>
+            {
+                self.dismiss(ctx);
+                ctx.set_handled();
+            }
+            _ => {}
+        }
+    }
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
+        let Some(target) = self.target else {
+            return;
+        };
+        let progress = 1. - (-(interval as f64) * 1e-9 * SETTLE_RATE).exp();
+        self.shown += (target - self.shown) * progress;
+        if (target - self.shown).abs() < 0.5 {
+            self.shown = target;
+            self.target = None;
+            if !self.open && self.shown == 0. {
+                self.hidden = true;
+                ctx.set_stashed(&mut self.scrim, true);
+                ctx.set_stashed(&mut self.panel, true);
+            }
+        } else {
+            ctx.request_anim_frame();
+        }
+        let alpha = self.scrim_alpha();
+        {
+            let mut scrim = ctx.get_raw_mut(&mut self.scrim);
+            scrim.widget().alpha = alpha;
+            scrim.ctx().request_paint_only();
+        }
+        ctx.request_layout();
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::WidgetAdded => {
+                ctx.set_stashed(&mut self.scrim, self.hidden);
+                ctx.set_stashed(&mut self.panel, self.hidden);
+                if self.open {
+                    ctx.mutate_self_later(|mut drawer| {
+                        let mut drawer = drawer.downcast::<Self>();
+                        Self::content_mut(&mut drawer).ctx.set_disabled(true);
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.content);
+        ctx.register_child(&mut self.scrim);
+        ctx.register_child(&mut self.panel);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let content_size = ctx.run_layout(&mut self.content, bc);
+        ctx.place_child(&mut self.content, Point::ORIGIN);
+        // The panel slides in from the edges of the available space, not of the content.
+        let size = if bc.is_width_bounded() && bc.is_height_bounded() {
+            bc.max()
+        } else {
+            content_size
+        };
+
+        if self.hidden {
+            return size;
+        }
+
+        ctx.run_layout(&mut self.scrim, &BoxConstraints::tight(size));
+        ctx.place_child(&mut self.scrim, Point::ORIGIN);
+
+        let length = self.edge.length(size);
+        let shown = self.shown.min(length);
+        // The panel keeps its open size while it slides out, and grows past it when resized.
+        let panel_length = shown.max(self.extent).min(length);
+        let (panel_size, origin) = match self.edge {
+            DrawerEdge::Left => (
+                Size::new(panel_length, size.height),
+                Point::new(shown - panel_length, 0.),
+            ),
+            DrawerEdge::Right => (
+                Size::new(panel_length, size.height),
+                Point::new(size.width - shown, 0.),
+            ),
+            DrawerEdge::Top => (
+                Size::new(size.width, panel_length),
+                Point::new(0., shown - panel_length),
+            ),
+            DrawerEdge::Bottom => (
+                Size::new(size.width, panel_length),
+                Point::new(0., size.height - shown),
+            ),
+        };
+        ctx.run_layout(&mut self.panel, &BoxConstraints::tight(panel_size));
+        ctx.place_child(&mut self.panel, origin);
+
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.content.id(), self.scrim.id(), self.panel.id()]
+    }
+
+    fn get_child_at_pos<'c>(
+        &self,
+        ctx: QueryCtx<'c>,
+        pos: Point,
+    ) -> Option<WidgetRef<'c, dyn Widget>> {
+        if self.hidden {
+            return get_child_at_pos(self, ctx, pos);
+        }
+        // The content is blocked while the panel is shown, so pointer events outside of the
+        // panel are sent to the drawer itself.
+        let panel = ctx.get(self.panel.id());
+        panel
+            .ctx()
+            .window_layout_rect()
+            .contains(pos)
+            .then_some(panel)
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Drawer")
+    }
+}
+
+// --- MARK: SCRIM ---
+/// The layer dimming the content of a [`Drawer`] while its panel is shown, also used
+/// by [`Busy`](super::Busy).
+pub(crate) struct Scrim {
+    /// The opacity of the layer, relative to [`theme::SCRIM_COLOR`].
+    pub(crate) alpha: f64,
+}
+
+// The drawer updates the opacity as the panel moves.
+impl AllowRawMut for Scrim {}
+
+impl Widget for Scrim {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        bc.max()
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let color = ctx
+            .env()
+            .get(theme::SCRIM_COLOR)
+            .with_alpha_factor(self.alpha as f32);
+        fill_color(scene, &ctx.size().to_rect(), color);
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Scrim")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::widget::{Button, SizedBox};
+
+    fn sheet(open: bool) -> (Drawer, WidgetId, WidgetId) {
+        let [content_id, panel_id] = widget_ids();
+        let drawer = Drawer::sheet(
+            Button::new("Behind").with_id(content_id),
+            SizedBox::empty().with_id(panel_id),
+        )
+        .extent(100.)
+        .max_extent(300.)
+        .open(open);
+        (drawer, content_id, panel_id)
+    }
+
+    #[test]
+    fn open_blocks_content() {
+        let (drawer, content_id, panel_id) = sheet(false);
+        let mut harness = TestHarness::create(drawer);
+        assert!(harness.get_widget(panel_id).ctx().is_stashed());
+
+        harness.edit_root_widget(|mut drawer| {
+            let mut drawer = drawer.downcast::<Drawer>();
+            Drawer::set_open(&mut drawer, true);
+        });
+        harness.animate_ms(1000);
+        assert!(harness.get_widget(content_id).ctx().is_disabled());
+        let panel_rect = harness.get_widget(panel_id).ctx().window_layout_rect();
+        assert_eq!(panel_rect.y0, 300.);
+
+        // Clicking the dimmed content dismisses the panel instead of pressing the button.
+        harness.mouse_click_on(content_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::DrawerDismissed, harness.root_widget().id()))
+        );
+        assert_eq!(harness.pop_action(), None);
+        harness.animate_ms(1000);
+        assert!(!harness.get_widget(content_id).ctx().is_disabled());
+        assert!(harness.get_widget(panel_id).ctx().is_stashed());
+    }
+
+    #[test]
+    fn click_widget_in_panel() {
+        let [button_id] = widget_ids();
+        let drawer = Drawer::sheet(
+            SizedBox::empty(),
+            SizedBox::new(Button::new("Inside").with_id(button_id)).expand(),
+        )
+        .extent(100.)
+        .open(true);
+        let mut harness = TestHarness::create(drawer);
+
+        // The button keeps the pointer it captured, and the panel stays open.
+        harness.mouse_click_on(button_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(PointerButton::Primary), button_id))
+        );
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn drag_to_resize_and_dismiss() {
+        let (drawer, _, panel_id) = sheet(true);
+        let mut harness = TestHarness::create(drawer);
+
+        // A long drag away from the edge settles at the maximum extent.
+        harness.mouse_move((200., 350.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_move((200., 250.));
+        harness.mouse_move((200., 150.));
+        harness.mouse_button_release(PointerButton::Primary);
+        harness.animate_ms(1000);
+        let panel_rect = harness.get_widget(panel_id).ctx().window_layout_rect();
+        assert_eq!(panel_rect.y0, 100.);
+        assert_eq!(harness.pop_action(), None);
+
+        // A drag towards the edge dismisses the panel.
+        harness.mouse_move((200., 150.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_move((200., 300.));
+        harness.mouse_move((200., 390.));
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::DrawerDismissed, harness.root_widget().id()))
+        );
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A widget which catches panics in its child.
+
+use std::any::Any;
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{error, trace_span, Span};
+use vello::Scene;
+
+use crate::action::Action;
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::passes::layout::run_layout_catching_panic;
+use crate::render_root::{MutateCallback, RenderRootState};
+use crate::widget::{Button, Label, WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetFlags, WidgetId,
+};
+
+const PADDING: f64 = 8.;
+
+/// A widget which catches panics in its child, so that a misbehaving widget doesn't
+/// crash the whole app.
+///
+/// Panics raised while the child or one of its descendants handles an event, computes
+/// its layout, or paints are caught. The child is then hidden and replaced with an error
+/// message and a retry button, and an [`Action::ChildPanicked`] with the panic message
+/// is submitted.
+///
+/// When the retry button is pressed, the child is shown again and an
+/// [`Action::RetryRequested`] is submitted. Since the child may have been left in an
+/// inconsistent state by the panic, it is usually best to replace it with
+/// [`ErrorBoundary::set_child`] at that point.
+///
+/// Panics are caught with [`std::panic::catch_unwind`], which means they are still
+/// reported by the panic hook, and they can't be caught if the app is compiled with
+/// `panic = "abort"`.
+pub struct ErrorBoundary {
+    child: WidgetPod<Box<dyn Widget>>,
+    message: WidgetPod<Label>,
+    retry: WidgetPod<Button>,
+    error: Option<String>,
+}
+
+// --- MARK: BUILDERS ---
+impl ErrorBoundary {
+    /// Create a new error boundary around `child`.
+    pub fn new(child: impl Widget) -> Self {
+        Self::new_pod(WidgetPod::new(child).boxed())
+    }
+
+    /// Create a new error boundary around `child`, which is already in a [`WidgetPod`].
+    pub fn new_pod(child: WidgetPod<Box<dyn Widget>>) -> Self {
+        Self {
+            child,
+            message: WidgetPod::new(Label::new("")),
+            retry: WidgetPod::new(Button::new("Retry")),
+            error: None,
+        }
+    }
+
+    /// The message of the panic which replaced the child, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl ErrorBoundary {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    /// Replace the child.
+    ///
+    /// This clears the error, if any, so the new child is shown.
+    pub fn set_child(this: &mut WidgetMut<'_, Self>, child: impl Widget) {
+        Self::set_child_pod(this, WidgetPod::new(child).boxed());
+    }
+
+    /// Replace the child with a widget which is already in a [`WidgetPod`].
+    ///
+    /// This clears the error, if any, so the new child is shown.
+    pub fn set_child_pod(this: &mut WidgetMut<'_, Self>, child: WidgetPod<Box<dyn Widget>>) {
+        if this.widget.error.take().is_some() {
+            this.ctx.set_stashed(&mut this.widget.message, true);
+            this.ctx.set_stashed(&mut this.widget.retry, true);
+            this.ctx.request_render();
+        }
+        let old_child = std::mem::replace(&mut this.widget.child, child);
+        this.ctx.remove_child(old_child);
+        this.ctx.children_changed();
+        this.ctx.request_layout();
+    }
+
+    /// Replace the child with an error message, as if it had panicked with `message`.
+    ///
+    /// This is what happens when a panic is caught in the child.
+    pub fn show_error(this: &mut WidgetMut<'_, Self>, message: String) {
+        this.ctx.set_stashed(&mut this.widget.child, true);
+        this.ctx.set_stashed(&mut this.widget.message, false);
+        this.ctx.set_stashed(&mut this.widget.retry, false);
+        Label::set_text(
+            &mut this.ctx.get_mut(&mut this.widget.message),
+            message.clone(),
+        );
+        this.ctx
+            .submit_action(Action::ChildPanicked(message.clone()));
+        this.widget.error = Some(message);
+        this.ctx.request_layout();
+        this.ctx.request_render();
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl ErrorBoundary {
+    /// Show the child again after its panic.
+    fn retry(&mut self, ctx: &mut EventCtx) {
+        self.error = None;
+        ctx.set_stashed(&mut self.child, false);
+        ctx.set_stashed(&mut self.message, true);
+        ctx.set_stashed(&mut self.retry, true);
+        ctx.submit_action(Action::RetryRequested);
+        ctx.request_layout();
+        ctx.request_render();
+    }
+}
+
+/// The message of a caught panic, from its payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
+/// Report a panic caught in a descendant of the [`ErrorBoundary`] `boundary`, which will
+/// show the error during the next mutate pass.
+pub(crate) fn report_caught_panic(
+    global_state: &mut RenderRootState,
+    boundary: WidgetId,
+    payload: &(dyn Any + Send),
+) {
+    let message = panic_message(payload);
+    error!("Caught panic in the child of error boundary {boundary}: {message}");
+    global_state.mutate_callbacks.push(MutateCallback {
+        id: boundary,
+        callback: Box::new(move |mut boundary| {
+            ErrorBoundary::show_error(&mut boundary.downcast(), message);
+        }),
+    });
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for ErrorBoundary {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, _event: &PointerEvent) {
+        if self.error.is_some() && ctx.get_raw_mut(&mut self.retry).widget().take_pressed() {
+            self.retry(ctx);
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, _event: &AccessEvent) {
+        if self.error.is_some() && ctx.get_raw_mut(&mut self.retry).widget().take_pressed() {
+            self.retry(ctx);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::WidgetAdded => {
+                ctx.widget_state
+                    .flags
+                    .insert(WidgetFlags::IS_ERROR_BOUNDARY);
+                ctx.set_stashed(&mut self.message, true);
+                ctx.set_stashed(&mut self.retry, true);
+            }
+            _ => {}
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+        ctx.register_child(&mut self.message);
+        ctx.register_child(&mut self.retry);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        if self.error.is_none() {
+            match run_layout_catching_panic(ctx, &mut self.child, bc) {
+                Ok(size) => {
+                    ctx.place_child(&mut self.child, Point::ORIGIN);
+                    let baseline = ctx.child_baseline_offset(&self.child);
+                    ctx.set_baseline_offset(baseline);
+                    ctx.clear_clip_path();
+                    return size;
+                }
+                Err(payload) => {
+                    // The child isn't placed. The error message replaces it during
+                    // the next mutate pass.
+                    let id = ctx.widget_id();
+                    report_caught_panic(ctx.global_state, id, &*payload);
+                    return bc.min();
+                }
+            }
+        }
+
+        let content_bc = bc.shrink((2. * PADDING, 2. * PADDING)).loosen();
+        let message_size = ctx.run_layout(&mut self.message, &content_bc);
+        let retry_size = ctx.run_layout(&mut self.retry, &content_bc);
+
+        let content_size = Size::new(
+            message_size.width.max(retry_size.width),
+            message_size.height + PADDING + retry_size.height,
+        );
+        let size = bc.constrain(Size::new(
+            content_size.width + 2. * PADDING,
+            content_size.height + 2. * PADDING,
+        ));
+
+        ctx.place_child(&mut self.message, Point::new(PADDING, PADDING));
+        ctx.place_child(
+            &mut self.retry,
+            Point::new(PADDING, PADDING + message_size.height + PADDING),
+        );
+        // The child is hidden, but its paint rect may be stale.
+        ctx.set_clip_path(size.to_rect());
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        if self.error.is_none() {
+            return;
+        }
+        let bounds = ctx.size().to_rect();
+        fill_color(scene, &bounds, ctx.env().get(theme::BACKGROUND_DARK));
+        stroke(
+            scene,
+            &bounds.inset(-0.5),
+            ctx.env().get(theme::DESTRUCTIVE_DARK),
+            1.,
+        );
+    }
+
+    fn accessibility_role(&self) -> Role {
+        if self.error.is_some() {
+            Role::Alert
+        } else {
+            Role::GenericContainer
+        }
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id(), self.message.id(), self.retry.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("ErrorBoundary")
+    }
+
+    fn get_debug_text(&self) -> Option<String> {
+        self.error.clone()
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, ModularWidget, TestHarness, TestWidgetExt};
+    use crate::widget::SizedBox;
+    use crate::PointerButton;
+
+    #[test]
+    fn panic_in_event_shows_error() {
+        let [boundary_id, child_id] = widget_ids();
+        let child = ModularWidget::new(()).pointer_event_fn(|_, _, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                panic!("clicked a faulty widget");
+            }
+        });
+        let widget = ErrorBoundary::new(child.with_id(child_id)).with_id(boundary_id);
+        let mut harness = TestHarness::create(widget);
+
+        harness.mouse_click_on(child_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((
+                Action::ChildPanicked("clicked a faulty widget".into()),
+                boundary_id
+            ))
+        );
+        assert!(harness.get_widget(child_id).ctx().is_stashed());
+
+        let boundary = harness.get_widget(boundary_id);
+        let boundary = boundary.downcast::<ErrorBoundary>().unwrap();
+        assert_eq!(boundary.error(), Some("clicked a faulty widget"));
+        let retry_id = boundary.retry.id();
+
+        harness.mouse_click_on(retry_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(PointerButton::Primary), retry_id))
+        );
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::RetryRequested, boundary_id))
+        );
+        assert!(!harness.get_widget(child_id).ctx().is_stashed());
+    }
+
+    #[test]
+    fn panic_in_layout_shows_error() {
+        let [boundary_id] = widget_ids();
+        let child = ModularWidget::new(()).layout_fn(|_, _, _| panic!("faulty layout"));
+        let widget = ErrorBoundary::new(child).with_id(boundary_id);
+        let mut harness = TestHarness::create(widget);
+
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ChildPanicked("faulty layout".into()), boundary_id))
+        );
+        harness.edit_widget(boundary_id, |mut boundary| {
+            let mut boundary = boundary.downcast::<ErrorBoundary>();
+            assert_eq!(boundary.widget.error(), Some("faulty layout"));
+            ErrorBoundary::set_child(&mut boundary, SizedBox::empty());
+            assert_eq!(boundary.widget.error(), None);
+        });
+    }
+
+    #[test]
+    fn retry_after_panic_in_layout() {
+        let [boundary_id, child_id] = widget_ids();
+        let child = ModularWidget::new(true).layout_fn(|should_panic, _, bc| {
+            if std::mem::take(should_panic) {
+                panic!("faulty layout");
+            }
+            bc.constrain(Size::new(40., 20.))
+        });
+        let widget = ErrorBoundary::new(child.with_id(child_id)).with_id(boundary_id);
+        let mut harness = TestHarness::create(widget);
+
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ChildPanicked("faulty layout".into()), boundary_id))
+        );
+        assert!(harness.get_widget(child_id).ctx().is_stashed());
+
+        let retry_id = harness
+            .get_widget(boundary_id)
+            .downcast::<ErrorBoundary>()
+            .unwrap()
+            .retry
+            .id();
+        harness.mouse_click_on(retry_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(PointerButton::Primary), retry_id))
+        );
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::RetryRequested, boundary_id))
+        );
+
+        // The child is laid out from scratch once it's shown again.
+        let child = harness.get_widget(child_id);
+        assert!(!child.ctx().is_stashed());
+        assert_eq!(child.ctx().size(), Size::new(40., 20.));
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A container which scales its child to fit its own size.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::widget::{ObjectFit, WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, BoxConstraints, ComposeCtx, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, RegisterCtx, Size, TextEvent, Widget, WidgetId,
+};
+
+/// A container which lays out its child at its natural size, then scales it to fit
+/// the container, e.g. for thumbnails, slide previews or embedded content.
+///
+/// The container takes the maximum size allowed by its constraints, or if they are
+/// unbounded, the size of its child with the same aspect ratio. The child is scaled
+/// according to the [`ObjectFit`] policy, centered, and clipped to the container.
+///
+/// The child is always scaled uniformly, so [`ObjectFit::Fill`] behaves like
+/// [`ObjectFit::Contain`].
+///
+/// The scale only changes how the child is presented: it is painted, hit-tested and
+/// receives pointer events at its scaled size, but is laid out at its natural size.
+pub struct FitBox<W: Widget> {
+    child: WidgetPod<W>,
+    fit: ObjectFit,
+    /// The scale of the child computed during layout.
+    scale: f64,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> FitBox<W> {
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            fit: ObjectFit::default(),
+            scale: 1.,
+        }
+    }
+
+    /// Builder-style method for specifying the fit policy.
+    pub fn fit_mode(mut self, fit: ObjectFit) -> Self {
+        self.fit = fit;
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> FitBox<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    /// Modify the widget's fit policy.
+    pub fn set_fit_mode(this: &mut WidgetMut<'_, Self>, fit: ObjectFit) {
+        this.widget.fit = fit;
+        this.ctx.request_layout();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for FitBox<W> {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let child_size = ctx.run_layout(&mut self.child, &BoxConstraints::UNBOUNDED);
+        let size = if child_size.is_zero_area() {
+            bc.min()
+        } else if bc.is_width_bounded() && bc.is_height_bounded() {
+            bc.max()
+        } else {
+            bc.constrain_aspect_ratio(child_size.height / child_size.width, child_size.width)
+        };
+        if child_size.is_zero_area() || size.is_zero_area() {
+            // There is nothing to scale, or nothing to scale it to.
+            self.scale = 1.;
+            ctx.place_child(&mut self.child, Point::ORIGIN);
+            ctx.set_clip_path(size.to_rect());
+            return size;
+        }
+
+        let [scale_x, _, _, scale_y, _, _] = self.fit.affine_to_fill(size, child_size).as_coeffs();
+        self.scale = scale_x.min(scale_y);
+
+        let scaled_size = child_size * self.scale;
+        let origin = Point::new(
+            (size.width - scaled_size.width) / 2.,
+            (size.height - scaled_size.height) / 2.,
+        );
+        ctx.place_child(&mut self.child, origin);
+        ctx.set_clip_path(size.to_rect());
+        size
+    }
+
+    fn compose(&mut self, ctx: &mut ComposeCtx) {
+        ctx.set_child_scale(&mut self.child, self.scale);
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("FitBox")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, Record, Recording, TestHarness, TestWidgetExt};
+    use crate::widget::SizedBox;
+    use crate::{Rect, Vec2};
+
+    #[test]
+    fn child_is_scaled_to_fit() {
+        let [child_id] = widget_ids();
+        let recording = Recording::default();
+        let child = SizedBox::empty()
+            .width(200.)
+            .height(100.)
+            .record(&recording)
+            .with_id(child_id);
+        let widget = FitBox::new(child);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100., 100.));
+        let child = harness.get_widget(child_id);
+        assert_eq!(child.ctx().size(), Size::new(200., 100.));
+        assert_eq!(child.ctx().window_scale(), 0.5);
+        assert_eq!(
+            child.ctx().window_layout_rect(),
+            Rect::new(0., 25., 100., 75.)
+        );
+
+        // The pointer at the center of the scaled child is at the center of its local space.
+        recording.clear();
+        harness.mouse_move((50., 50.));
+        let pos = recording.drain().into_iter().find_map(|event| match event {
+            Record::PE(PointerEvent::PointerMove(state)) => Some(state.position),
+            _ => None,
+        });
+        let origin = harness.get_widget(child_id).ctx().window_origin();
+        let pos = pos.unwrap();
+        assert_eq!(Point::new(pos.x, pos.y) - origin, Vec2::new(100., 50.));
+
+        harness.edit_root_widget(|mut root| {
+            let mut fit_box = root.downcast::<FitBox<SizedBox>>();
+            FitBox::set_fit_mode(&mut fit_box, ObjectFit::Cover);
+        });
+        harness.mouse_move((0., 0.));
+        assert_eq!(harness.get_widget(child_id).ctx().window_scale(), 1.);
+    }
+}
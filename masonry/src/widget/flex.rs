@@ -13,8 +13,8 @@ use vello::Scene;
 use crate::theme::get_debug_color;
 use crate::widget::WidgetMut;
 use crate::{
-    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point, PointerEvent,
-    Rect, Size, TextEvent, Widget, WidgetId, WidgetPod,
+    AccessCtx, AccessEvent, BoxConstraints, Env, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, Rect, Size, TextEvent, Widget, WidgetId, WidgetPod,
 };
 
 /// A container with either horizontal or vertical layout.
@@ -257,9 +257,10 @@ impl Flex {
     /// Builder-style method to add a spacer widget with a standard size.
     ///
     /// The actual value of this spacer depends on whether this container is
-    /// a row or column, as well as theme settings.
+    /// a row or column, as well as theme settings. Since the env of the container
+    /// isn't known yet, the default theme values are used.
     pub fn with_default_spacer(self) -> Self {
-        let key = axis_default_spacer(self.direction);
+        let key = axis_default_spacer(self.direction, &Env::new());
         self.with_spacer(key)
     }
 
@@ -414,7 +415,7 @@ impl Flex {
     /// The actual value of this spacer depends on whether this container is
     /// a row or column, as well as theme settings.
     pub fn add_default_spacer(this: &mut WidgetMut<'_, Self>) {
-        let key = axis_default_spacer(this.widget.direction);
+        let key = axis_default_spacer(this.widget.direction, this.ctx.env());
         Flex::add_spacer(this, key);
         this.ctx.request_layout();
     }
@@ -498,7 +499,7 @@ impl Flex {
     /// The actual value of this spacer depends on whether this container is
     /// a row or column, as well as theme settings.
     pub fn insert_default_spacer(this: &mut WidgetMut<'_, Self>, idx: usize) {
-        let key = axis_default_spacer(this.widget.direction);
+        let key = axis_default_spacer(this.widget.direction, this.ctx.env());
         Self::insert_spacer(this, idx, key);
         this.ctx.request_layout();
     }
@@ -878,10 +879,10 @@ impl Child {
 }
 
 /// The size in logical pixels of the default spacer for an axis.
-fn axis_default_spacer(axis: Axis) -> f64 {
+fn axis_default_spacer(axis: Axis, env: &Env) -> f64 {
     match axis {
-        Axis::Vertical => crate::theme::WIDGET_PADDING_VERTICAL,
-        Axis::Horizontal => crate::theme::WIDGET_PADDING_HORIZONTAL,
+        Axis::Vertical => env.get(crate::theme::WIDGET_PADDING_VERTICAL),
+        Axis::Horizontal => env.get(crate::theme::WIDGET_PADDING_HORIZONTAL),
     }
 }
 
@@ -939,7 +940,13 @@ impl Widget for Flex {
         let mut any_changed = bc_changed;
         self.old_bc = *bc;
 
-        let gap = self.gap.unwrap_or(axis_default_spacer(self.direction));
+        let gap = match (self.gap, self.direction) {
+            (Some(gap), _) => gap,
+            (None, Axis::Vertical) => {
+                ctx.snap_to_baseline_grid(axis_default_spacer(self.direction, ctx.env()))
+            }
+            (None, Axis::Horizontal) => axis_default_spacer(self.direction, ctx.env()),
+        };
         // The gaps are only between the items, so 2 children means 1 gap.
         let total_gap = self.children.len().saturating_sub(1) as f64 * gap;
         // Measure non-flex children.
@@ -1075,6 +1082,7 @@ impl Widget for Flex {
 
         let mut major = spacing.next().unwrap_or(0.);
         let mut child_paint_rect = Rect::ZERO;
+        let mut snap_points = Vec::new();
 
         for child in &mut self.children {
             match child {
@@ -1116,6 +1124,7 @@ impl Widget for Flex {
 
                     let child_pos: Point = self.direction.pack(major, child_minor_offset).into();
                     ctx.place_child(widget, child_pos);
+                    snap_points.push(child_pos);
                     child_paint_rect = child_paint_rect.union(ctx.widget_state.paint_rect());
                     major += self.direction.major(child_size).expand();
                     major += spacing.next().unwrap_or(0.);
@@ -1173,6 +1182,8 @@ impl Widget for Flex {
         };
 
         ctx.set_baseline_offset(baseline_offset);
+        // A scrolling ancestor can snap to the start of each child.
+        ctx.set_snap_points(snap_points);
         my_size
     }
 
@@ -0,0 +1,301 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A widget which configures keyboard focus traversal among its descendants.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+use winit::event::ElementState;
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point, PointerEvent,
+    RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A widget which groups the focusable widgets in its child, e.g. the fields of a dialog,
+/// the items of a menu or the buttons of a toolbar.
+///
+/// By default, the group doesn't change how focus moves. It can be configured to:
+/// - [Wrap](Self::with_wrap) keyboard traversal, so that <kbd>Tab</kbd> on the last widget of
+///   the group focuses the first one, and <kbd>Shift</kbd>+<kbd>Tab</kbd> on the first one
+///   focuses the last one, instead of leaving the group.
+/// - [Remember](Self::with_remember_last_focused) the widget which was focused when focus
+///   left the group, and focus it again when focus comes back.
+/// - Focus an [initial widget](Self::with_initial_focus) the first time focus enters the group.
+/// - [Scope](Self::with_focus_scope) arrow-key navigation, so that the arrow keys only move
+///   focus between the widgets of the group, e.g. the cells of a grid.
+///
+/// Focus is only redirected when it enters the group by keyboard traversal or programmatically.
+/// Clicking a widget, or focusing it with assistive technology, focuses that widget.
+pub struct FocusGroup<W: Widget> {
+    child: WidgetPod<W>,
+    wrap: bool,
+    remember_last_focused: bool,
+    focus_scope: bool,
+    initial_focus: Option<WidgetId>,
+    /// The widget which was focused when focus last left the group.
+    last_focused: Option<WidgetId>,
+    /// Whether the user is focusing a specific widget of the group, which we shouldn't redirect.
+    focus_requested_directly: bool,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> FocusGroup<W> {
+    /// Create a new group around `child`, which doesn't change how focus moves.
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    /// Create a new group around a child wrapped in a [`WidgetPod`].
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            wrap: false,
+            remember_last_focused: false,
+            focus_scope: false,
+            initial_focus: None,
+            last_focused: None,
+            focus_requested_directly: false,
+        }
+    }
+
+    /// Builder-style method for setting whether keyboard traversal wraps around within the group.
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Builder-style method for setting whether the group focuses the widget which was
+    /// last focused in it when focus comes back.
+    pub fn with_remember_last_focused(mut self, remember: bool) -> Self {
+        self.remember_last_focused = remember;
+        self
+    }
+
+    /// Builder-style method for setting whether arrow-key navigation stays within the group.
+    ///
+    /// See [`EventCtx::focus_in_direction`] for how the arrow keys move focus.
+    pub fn with_focus_scope(mut self, focus_scope: bool) -> Self {
+        self.focus_scope = focus_scope;
+        self
+    }
+
+    /// Builder-style method for setting the widget focused when focus enters the group,
+    /// if there is no widget to [remember](Self::with_remember_last_focused).
+    pub fn with_initial_focus(mut self, id: WidgetId) -> Self {
+        self.initial_focus = Some(id);
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> FocusGroup<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    /// Set whether keyboard traversal wraps around within the group.
+    pub fn set_wrap(this: &mut WidgetMut<'_, Self>, wrap: bool) {
+        this.widget.wrap = wrap;
+    }
+
+    /// Set whether the group focuses the widget which was last focused in it when
+    /// focus comes back.
+    pub fn set_remember_last_focused(this: &mut WidgetMut<'_, Self>, remember: bool) {
+        this.widget.remember_last_focused = remember;
+        if !remember {
+            this.widget.last_focused = None;
+        }
+    }
+
+    /// Set whether arrow-key navigation stays within the group.
+    pub fn set_focus_scope(this: &mut WidgetMut<'_, Self>, focus_scope: bool) {
+        this.widget.focus_scope = focus_scope;
+        this.ctx.set_focus_scope(focus_scope);
+    }
+
+    /// Set the widget focused when focus enters the group.
+    pub fn set_initial_focus(this: &mut WidgetMut<'_, Self>, id: Option<WidgetId>) {
+        this.widget.initial_focus = id;
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for FocusGroup<W> {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, event: &PointerEvent) {
+        match event {
+            PointerEvent::PointerDown(..) => self.focus_requested_directly = true,
+            PointerEvent::PointerUp(..) | PointerEvent::PointerLeave(..) => {
+                self.focus_requested_directly = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        let TextEvent::KeyboardKey(key, mods) = event else {
+            return;
+        };
+        if !self.wrap
+            || key.physical_key != PhysicalKey::Code(KeyCode::Tab)
+            || key.state != ElementState::Pressed
+        {
+            return;
+        }
+        let chain = &ctx.widget_state.focus_chain;
+        let (Some(&first), Some(&last)) = (chain.first(), chain.last()) else {
+            return;
+        };
+        let focused = ctx.global_state.focused_widget;
+        let target = if !mods.shift_key() && focused == Some(last) {
+            first
+        } else if mods.shift_key() && focused == Some(first) {
+            last
+        } else {
+            // The default traversal stays within the group.
+            return;
+        };
+        ctx.set_focus(target);
+        ctx.set_handled();
+    }
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, event: &AccessEvent) {
+        if event.action == accesskit::Action::Focus {
+            self.focus_requested_directly = true;
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded = event {
+            ctx.set_focus_scope(self.focus_scope);
+        }
+        let Update::ChildFocusChanged(has_focus) = event else {
+            return;
+        };
+        let focus_requested_directly = std::mem::take(&mut self.focus_requested_directly);
+        if !*has_focus {
+            // The widget losing focus is still the focused widget during this update.
+            if self.remember_last_focused {
+                self.last_focused = ctx.global_state.focused_widget;
+            }
+            return;
+        }
+        if focus_requested_directly {
+            return;
+        }
+        let target = self
+            .last_focused
+            .filter(|_| self.remember_last_focused)
+            .or(self.initial_focus)
+            // The target may have been removed or disabled since.
+            .filter(|target| ctx.widget_state.focus_chain.contains(target));
+        if let Some(target) = target {
+            // The update_focus pass runs again, since focus changed during it.
+            ctx.global_state.next_focused_widget = Some(target);
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let baseline = ctx.child_baseline_offset(&self.child);
+        ctx.set_baseline_offset(baseline);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::Group
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("FocusGroup")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness};
+    use crate::widget::{Flex, SimpleWidget};
+    use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+    #[test]
+    fn initial_and_remembered_focus() {
+        let [outside, first, second, third] = widget_ids();
+        let focusable = || SimpleWidget::new(()).accepts_focus(true);
+        let group = FocusGroup::new(
+            Flex::column()
+                .with_child_id(focusable(), first)
+                .with_child_id(focusable(), second)
+                .with_child_id(focusable(), third),
+        )
+        .with_initial_focus(second)
+        .with_remember_last_focused(true);
+        let widget = Flex::column()
+            .with_child_id(focusable(), outside)
+            .with_child(group);
+
+        let mut harness = TestHarness::create(widget);
+        let focused = |harness: &TestHarness| harness.focused_widget().map(|widget| widget.id());
+
+        harness.focus_on(Some(outside));
+        harness.focus_on(Some(first));
+        assert_eq!(focused(&harness), Some(second));
+
+        // Moving focus within the group isn't redirected.
+        harness.focus_on(Some(third));
+        assert_eq!(focused(&harness), Some(third));
+
+        harness.focus_on(Some(outside));
+        harness.focus_on(Some(first));
+        assert_eq!(focused(&harness), Some(third));
+    }
+
+    #[test]
+    fn tab_wraps_within_group() {
+        let [outside, first, second] = widget_ids();
+        let focusable = || SimpleWidget::new(()).accepts_focus(true);
+        let group = FocusGroup::new(
+            Flex::column()
+                .with_child_id(focusable(), first)
+                .with_child_id(focusable(), second),
+        )
+        .with_wrap(true);
+        let widget = Flex::column()
+            .with_child(group)
+            .with_child_id(focusable(), outside);
+
+        let mut harness = TestHarness::create(widget);
+        let focused = |harness: &TestHarness| harness.focused_widget().map(|widget| widget.id());
+        let tab = || Key::Named(NamedKey::Tab);
+
+        harness.focus_on(Some(first));
+        harness.keyboard_key(tab());
+        assert_eq!(focused(&harness), Some(second));
+
+        // Tab on the last widget goes back to the first one instead of leaving the group.
+        harness.keyboard_key(tab());
+        assert_eq!(focused(&harness), Some(first));
+
+        harness.set_modifiers(ModifiersState::SHIFT);
+        harness.keyboard_key(tab());
+        assert_eq!(focused(&harness), Some(second));
+    }
+}
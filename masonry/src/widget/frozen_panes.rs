@@ -0,0 +1,441 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A scrollable area whose leading rows and columns stay in view.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::kurbo::{Point, Size, Vec2};
+use vello::Scene;
+
+use crate::widget::{Axis, ScrollBar, WidgetMut};
+use crate::{
+    AccessCtx, AccessEvent, AllowRawMut, BoxConstraints, ComposeCtx, EventCtx, LayoutCtx, PaintCtx,
+    PointerEvent, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId, WidgetPod,
+};
+
+const SCROLLING_SPEED: f64 = 10.0;
+
+/// A scrollable area split into four panes, like the frozen panes of a spreadsheet.
+///
+/// ```text
+/// +--------+-----------------+
+/// | corner | header          |
+/// +--------+-----------------+
+/// | side   | body            |
+/// |        |                 |
+/// +--------+-----------------+
+/// ```
+///
+/// The corner always stays in place. The body scrolls in both directions, the header
+/// scrolls horizontally with it, and the side scrolls vertically with it. This keeps the
+/// header rows and leading columns of a table in view.
+///
+/// Each pane is laid out with unbounded constraints, so the panes must choose their own
+/// size. The corner's size sets the size of the frozen area: the header should be as tall
+/// as the corner, and the side as wide.
+pub struct FrozenPanes {
+    corner: WidgetPod<Box<dyn Widget>>,
+    header: WidgetPod<Pane>,
+    side: WidgetPod<Pane>,
+    body: WidgetPod<Pane>,
+    spacing: f64,
+    viewport_pos: Point,
+    /// The size of the visible part of the body.
+    viewport_size: Size,
+    scrollbar_horizontal: WidgetPod<ScrollBar>,
+    scrollbar_vertical: WidgetPod<ScrollBar>,
+}
+
+/// One of the scrolling panes of [`FrozenPanes`], which clips its child.
+pub struct Pane {
+    child: WidgetPod<Box<dyn Widget>>,
+    offset: Vec2,
+    content_size: Size,
+}
+
+// --- MARK: BUILDERS ---
+impl FrozenPanes {
+    pub fn new(
+        corner: impl Widget,
+        header: impl Widget,
+        side: impl Widget,
+        body: impl Widget,
+    ) -> Self {
+        Self::new_pod(
+            WidgetPod::new(corner).boxed(),
+            WidgetPod::new(header).boxed(),
+            WidgetPod::new(side).boxed(),
+            WidgetPod::new(body).boxed(),
+        )
+    }
+
+    pub fn new_pod(
+        corner: WidgetPod<Box<dyn Widget>>,
+        header: WidgetPod<Box<dyn Widget>>,
+        side: WidgetPod<Box<dyn Widget>>,
+        body: WidgetPod<Box<dyn Widget>>,
+    ) -> Self {
+        Self {
+            corner,
+            header: WidgetPod::new(Pane::new(header)),
+            side: WidgetPod::new(Pane::new(side)),
+            body: WidgetPod::new(Pane::new(body)),
+            spacing: 0.,
+            viewport_pos: Point::ORIGIN,
+            viewport_size: Size::ZERO,
+            scrollbar_horizontal: WidgetPod::new(ScrollBar::new(Axis::Horizontal, 1.0, 1.0)),
+            scrollbar_vertical: WidgetPod::new(ScrollBar::new(Axis::Vertical, 1.0, 1.0)),
+        }
+    }
+
+    /// Builder-style method to set the space between the frozen panes and the scrolling ones.
+    ///
+    /// The default spacing is `0.0`.
+    pub fn with_spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// How far the body is scrolled.
+    pub fn viewport_pos(&self) -> Point {
+        self.viewport_pos
+    }
+}
+
+impl Pane {
+    fn new(child: WidgetPod<Box<dyn Widget>>) -> Self {
+        Self {
+            child,
+            offset: Vec2::ZERO,
+            content_size: Size::ZERO,
+        }
+    }
+}
+
+impl AllowRawMut for Pane {}
+
+impl Pane {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl FrozenPanes {
+    pub fn corner_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.corner)
+    }
+
+    pub fn header_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Pane> {
+        this.ctx.get_mut(&mut this.widget.header)
+    }
+
+    pub fn side_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Pane> {
+        this.ctx.get_mut(&mut this.widget.side)
+    }
+
+    pub fn body_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Pane> {
+        this.ctx.get_mut(&mut this.widget.body)
+    }
+
+    /// Set the space between the frozen panes and the scrolling ones.
+    pub fn set_spacing(this: &mut WidgetMut<'_, Self>, spacing: f64) {
+        this.widget.spacing = spacing;
+        this.ctx.request_layout();
+    }
+
+    /// Scroll the body, and the header and side with it, to `position`.
+    ///
+    /// The position is clamped so that the body stays in view.
+    pub fn set_viewport_pos(this: &mut WidgetMut<'_, Self>, position: Point) {
+        this.widget.viewport_pos = position;
+        this.ctx.request_layout();
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl FrozenPanes {
+    fn content_size(&mut self, ctx: &mut EventCtx) -> Size {
+        ctx.get_raw_ref(&mut self.body).widget().content_size
+    }
+
+    fn max_viewport_pos(&self, content_size: Size) -> Point {
+        let max = content_size - self.viewport_size;
+        Point::new(max.width.max(0.), max.height.max(0.))
+    }
+
+    /// Clamp the scroll position, and return whether it changed.
+    fn set_viewport_pos_raw(&mut self, content_size: Size, pos: Point) -> bool {
+        let max = self.max_viewport_pos(content_size);
+        let pos = Point::new(pos.x.clamp(0., max.x), pos.y.clamp(0., max.y));
+        let changed = pos != self.viewport_pos;
+        self.viewport_pos = pos;
+        changed
+    }
+
+    /// Move the panes to the scroll position of the body, and the scrollbars with them.
+    ///
+    /// The panes apply their offset in the compose pass, so scrolling doesn't lay them out again.
+    fn sync_panes(&mut self, ctx: &mut LayoutCtx, content_size: Size) {
+        let pos = self.viewport_pos.to_vec2();
+        for (pane, offset) in [
+            (&mut self.header, Vec2::new(pos.x, 0.)),
+            (&mut self.side, Vec2::new(0., pos.y)),
+            (&mut self.body, pos),
+        ] {
+            if ctx.get_raw_ref(pane).widget().offset != offset {
+                ctx.mutate_later(pane, move |mut pane| {
+                    pane.widget.offset = offset;
+                    pane.ctx.request_compose();
+                });
+            }
+        }
+
+        let max = self.max_viewport_pos(content_size);
+        for (scrollbar, progress) in [
+            (&mut self.scrollbar_horizontal, pos.x / max.x),
+            (&mut self.scrollbar_vertical, pos.y / max.y),
+        ] {
+            let progress = if progress.is_finite() { progress } else { 0. };
+            if ctx.get_raw_ref(scrollbar).widget().cursor_progress != progress {
+                ctx.mutate_later(scrollbar, move |mut scrollbar| {
+                    scrollbar.widget.cursor_progress = progress;
+                    scrollbar.ctx.request_render();
+                });
+            }
+        }
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for FrozenPanes {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let content_size = self.content_size(ctx);
+        let mut moved = false;
+
+        if let PointerEvent::MouseWheel(delta, _, _) = event {
+            let delta = Vec2::new(delta.x * -SCROLLING_SPEED, delta.y * -SCROLLING_SPEED);
+            moved = self.set_viewport_pos_raw(content_size, self.viewport_pos + delta);
+        }
+
+        // Events bubble up, so the scrollbars have already handled the event.
+        let max = self.max_viewport_pos(content_size);
+        for axis in [Axis::Horizontal, Axis::Vertical] {
+            let scrollbar = match axis {
+                Axis::Horizontal => &mut self.scrollbar_horizontal,
+                Axis::Vertical => &mut self.scrollbar_vertical,
+            };
+            let mut scrollbar = ctx.get_raw_mut(scrollbar);
+            if scrollbar.widget().moved {
+                scrollbar.widget().moved = false;
+                let progress = scrollbar.widget().cursor_progress;
+                let pos = self.viewport_pos;
+                self.viewport_pos = match axis {
+                    Axis::Horizontal => Point::new(progress * max.x, pos.y),
+                    Axis::Vertical => Point::new(pos.x, progress * max.y),
+                };
+                moved = true;
+            }
+        }
+
+        if moved {
+            ctx.request_layout();
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.corner);
+        ctx.register_child(&mut self.header);
+        ctx.register_child(&mut self.side);
+        ctx.register_child(&mut self.body);
+        ctx.register_child(&mut self.scrollbar_horizontal);
+        ctx.register_child(&mut self.scrollbar_vertical);
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _event: &Update) {}
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let corner_size = ctx.run_layout(&mut self.corner, &BoxConstraints::UNBOUNDED);
+        ctx.place_child(&mut self.corner, Point::ORIGIN);
+
+        let frozen = corner_size + Size::new(self.spacing, self.spacing);
+        let max = bc.max();
+        let available = Size::new(
+            (max.width - frozen.width).max(0.),
+            (max.height - frozen.height).max(0.),
+        );
+
+        let header_bc =
+            BoxConstraints::new(Size::ZERO, Size::new(available.width, corner_size.height));
+        let _ = ctx.run_layout(&mut self.header, &header_bc);
+        ctx.place_child(&mut self.header, Point::new(frozen.width, 0.));
+
+        let side_bc =
+            BoxConstraints::new(Size::ZERO, Size::new(corner_size.width, available.height));
+        let _ = ctx.run_layout(&mut self.side, &side_bc);
+        ctx.place_child(&mut self.side, Point::new(0., frozen.height));
+
+        let body_bc = BoxConstraints::new(Size::ZERO, available);
+        self.viewport_size = ctx.run_layout(&mut self.body, &body_bc);
+        ctx.place_child(&mut self.body, frozen.to_vec2().to_point());
+
+        let size = bc.constrain(frozen + self.viewport_size);
+        ctx.set_clip_path(size.to_rect());
+
+        let content_size = ctx.get_raw_ref(&mut self.body).widget().content_size;
+        self.set_viewport_pos_raw(content_size, self.viewport_pos);
+        self.sync_panes(ctx, content_size);
+
+        let scrollbar_horizontal_visible = self.viewport_size.width < content_size.width;
+        let scrollbar_vertical_visible = self.viewport_size.height < content_size.height;
+        ctx.set_stashed(
+            &mut self.scrollbar_horizontal,
+            !scrollbar_horizontal_visible,
+        );
+        ctx.set_stashed(&mut self.scrollbar_vertical, !scrollbar_vertical_visible);
+
+        // The scrollbars span the body, so the frozen panes are never covered.
+        let viewport_bc = BoxConstraints::tight(self.viewport_size).loosen();
+        if scrollbar_horizontal_visible {
+            let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_horizontal);
+            scrollbar.widget().portal_size = self.viewport_size.width;
+            scrollbar.widget().content_size = content_size.width;
+            drop(scrollbar);
+
+            let scrollbar_size = ctx.run_layout(&mut self.scrollbar_horizontal, &viewport_bc);
+            ctx.place_child(
+                &mut self.scrollbar_horizontal,
+                Point::new(frozen.width, size.height - scrollbar_size.height),
+            );
+        } else {
+            ctx.skip_layout(&mut self.scrollbar_horizontal);
+        }
+        if scrollbar_vertical_visible {
+            let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_vertical);
+            scrollbar.widget().portal_size = self.viewport_size.height;
+            scrollbar.widget().content_size = content_size.height;
+            drop(scrollbar);
+
+            let scrollbar_size = ctx.run_layout(&mut self.scrollbar_vertical, &viewport_bc);
+            ctx.place_child(
+                &mut self.scrollbar_vertical,
+                Point::new(size.width - scrollbar_size.width, frozen.height),
+            );
+        } else {
+            ctx.skip_layout(&mut self.scrollbar_vertical);
+        }
+
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        node.set_scroll_x(self.viewport_pos.x);
+        node.set_scroll_y(self.viewport_pos.y);
+        node.set_clips_children();
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![
+            self.body.id(),
+            self.side.id(),
+            self.header.id(),
+            self.corner.id(),
+            self.scrollbar_horizontal.id(),
+            self.scrollbar_vertical.id(),
+        ]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("FrozenPanes")
+    }
+}
+
+impl Widget for Pane {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        self.content_size = ctx.run_layout(&mut self.child, &BoxConstraints::UNBOUNDED);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let size = bc.constrain(self.content_size);
+        ctx.set_clip_path(size.to_rect());
+        size
+    }
+
+    fn compose(&mut self, ctx: &mut ComposeCtx) {
+        ctx.set_child_translation(&mut self.child, -self.offset);
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        node.set_clips_children();
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Pane")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::widget::SizedBox;
+
+    fn pane(width: f64, height: f64) -> SizedBox {
+        SizedBox::empty().width(width).height(height)
+    }
+
+    #[test]
+    fn panes_scroll_together() {
+        let [corner_id, header_id, side_id, body_id] = widget_ids();
+        let widget = FrozenPanes::new(
+            pane(50., 20.).with_id(corner_id),
+            pane(500., 20.).with_id(header_id),
+            pane(50., 500.).with_id(side_id),
+            pane(500., 500.).with_id(body_id),
+        );
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 200.));
+
+        harness.edit_root_widget(|mut panes| {
+            let mut panes = panes.downcast::<FrozenPanes>();
+            FrozenPanes::set_viewport_pos(&mut panes, Point::new(30., 1000.));
+        });
+
+        let origin = |id| harness.get_widget(id).ctx().window_origin();
+        assert_eq!(origin(corner_id), Point::new(0., 0.));
+        assert_eq!(origin(header_id), Point::new(20., 0.));
+        // The position is clamped so that the body stays in view.
+        assert_eq!(origin(side_id), Point::new(0., -300.));
+        assert_eq!(origin(body_id), Point::new(20., -300.));
+    }
+}
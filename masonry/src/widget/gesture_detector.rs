@@ -0,0 +1,237 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A widget which reports the gestures performed on its child.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::gesture::GestureRecognizer;
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, Action, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerButton, PointerEvent, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A widget which submits an [`Action::Gesture`] for each [`Gesture`](crate::gesture::Gesture)
+/// performed on its child.
+///
+/// This lets apps implement custom interactions, like swipe-to-dismiss or pull-down sheets,
+/// by reacting to taps, long presses, drags, pinches, rotations and pans, with their positions and velocities.
+/// The thresholds of the gestures are shared by all widgets, see
+/// [`RenderRoot::set_gesture_config`](crate::RenderRoot::set_gesture_config).
+///
+/// The actions hold the id of the child. Positions are relative to this widget.
+/// Pointer events which the child handles itself, e.g. a click on a [`Button`](super::Button),
+/// aren't part of any gesture.
+pub struct GestureDetector<W: Widget> {
+    child: WidgetPod<W>,
+    recognizer: GestureRecognizer,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> GestureDetector<W> {
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            recognizer: GestureRecognizer::new(),
+        }
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> GestureDetector<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for GestureDetector<W> {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        if let PointerEvent::PointerDown(PointerButton::Primary, _) = event {
+            // A descendant which captured the pointer, e.g. a button, handles the press,
+            // and the recognizer stays idle until the next one.
+            if ctx.is_pointer_captured_by_other() {
+                return;
+            }
+            // Keep receiving pointer moves when the pointer is dragged outside of the widget.
+            ctx.capture_pointer();
+        }
+        let gesture =
+            self.recognizer
+                .handle_pointer_event(event, ctx.window_origin(), Instant::now());
+        if let Some(gesture) = gesture {
+            ctx.submit_action(Action::Gesture(gesture, self.child.id()));
+            ctx.set_handled();
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _event: &Update) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let baseline = ctx.child_baseline_offset(&self.child);
+        ctx.set_baseline_offset(baseline);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("GestureDetector")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::gesture::Gesture;
+    use crate::testing::{widget_ids, TestHarness};
+    use crate::widget::{Button, SizedBox};
+
+    #[test]
+    fn tap_and_drag() {
+        let [child_id] = widget_ids();
+        let widget = GestureDetector::new_pod(WidgetPod::new_with_id(
+            SizedBox::empty().width(100.).height(100.),
+            child_id,
+        ));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100., 100.));
+        harness.mouse_move((50., 50.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        let detector_id = harness.root_widget().id();
+        assert_eq!(
+            harness.pop_action(),
+            Some((
+                Action::Gesture(
+                    Gesture::Tap {
+                        position: Point::new(50., 50.),
+                        count: 1,
+                    },
+                    child_id
+                ),
+                detector_id
+            ))
+        );
+        // A second tap right away is a double tap.
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        assert!(matches!(
+            harness.pop_action(),
+            Some((Action::Gesture(Gesture::Tap { count: 2, .. }, _), _))
+        ));
+
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_move((80., 50.));
+        assert_eq!(
+            harness.pop_action(),
+            Some((
+                Action::Gesture(
+                    Gesture::DragStart {
+                        position: Point::new(50., 50.),
+                    },
+                    child_id
+                ),
+                detector_id
+            ))
+        );
+        let Some((Action::Gesture(Gesture::DragUpdate { delta, .. }, _), _)) = harness.pop_action()
+        else {
+            panic!("expected a drag update");
+        };
+        assert_eq!(delta.x, 30.);
+        // The pointer is captured, so the drag continues outside of the widget.
+        harness.mouse_move((150., 50.));
+        let Some((Action::Gesture(Gesture::DragUpdate { delta, .. }, _), _)) = harness.pop_action()
+        else {
+            panic!("expected a drag update");
+        };
+        assert_eq!(delta.x, 70.);
+        harness.mouse_button_release(PointerButton::Primary);
+        let Some((Action::Gesture(Gesture::DragEnd { velocity, .. }, _), _)) = harness.pop_action()
+        else {
+            panic!("expected the drag to end");
+        };
+        assert!(velocity.x > 0.);
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn child_button_keeps_its_press() {
+        let [button_id] = widget_ids();
+        let widget = GestureDetector::new_pod(WidgetPod::new_with_id(Button::new("Hi"), button_id));
+
+        let mut harness = TestHarness::create(widget);
+        harness.mouse_click_on(button_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(PointerButton::Primary), button_id))
+        );
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn long_press() {
+        let widget = GestureDetector::new(SizedBox::empty().width(100.).height(100.));
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(100., 100.));
+        harness.mouse_move((50., 50.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.move_timers_forward(Duration::from_millis(400));
+        assert_eq!(harness.pop_action(), None);
+        harness.move_timers_forward(Duration::from_millis(100));
+        assert!(matches!(
+            harness.pop_action(),
+            Some((Action::Gesture(Gesture::LongPress { .. }, _), _))
+        ));
+        // The release doesn't report a tap.
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_eq!(harness.pop_action(), None);
+
+        // A short press doesn't become a long press later.
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        assert!(matches!(
+            harness.pop_action(),
+            Some((Action::Gesture(Gesture::Tap { .. }, _), _))
+        ));
+        harness.move_timers_forward(Duration::from_millis(500));
+        assert_eq!(harness.pop_action(), None);
+    }
+}
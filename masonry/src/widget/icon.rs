@@ -0,0 +1,398 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An icon widget.
+
+use accesskit::{NodeBuilder, Role};
+use parley::style::FontStack;
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::kurbo::{Affine, BezPath, Point, Size, SvgParseError, Vec2};
+use vello::peniko::{Brush, Fill};
+use vello::Scene;
+
+use crate::text::{ArcStr, TextBrush, TextLayout, TextStyle};
+use crate::widget::WidgetMut;
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, Env, EventCtx, LayoutCtx, PaintCtx,
+    PointerEvent, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// The side length of the square coordinate space icon paths are drawn in.
+///
+/// This matches the view box of most icon sets, e.g. Material Symbols.
+pub const ICON_VIEWBOX_SIZE: f64 = 24.0;
+
+enum IconSource {
+    /// A single glyph from an icon font.
+    Glyph {
+        text: ArcStr,
+        layout: Box<TextLayout>,
+    },
+    /// A filled path in a [`ICON_VIEWBOX_SIZE`] square.
+    Path(BezPath),
+}
+
+/// A widget displaying a single icon, either a glyph from an icon font or a vector path.
+///
+/// Icons are square, [`theme::ICON_SIZE`] wide by default, and are tinted with the
+/// current text color (see [`TextStyle`]) unless given an explicit brush.
+///
+/// Icons are exposed to accessibility as images labelled with their name.
+pub struct Icon {
+    source: IconSource,
+    name: ArcStr,
+    /// The side length set on this widget, which takes precedence over the env.
+    size: Option<f64>,
+    /// The brush set on this widget, which takes precedence over the inherited text color.
+    brush: Option<TextBrush>,
+    tint: TextBrush,
+}
+
+// --- MARK: BUILDERS ---
+impl Icon {
+    /// Create an icon from a glyph of an icon font.
+    ///
+    /// `name` is the accessible name of the icon.
+    pub fn from_glyph(name: impl Into<ArcStr>, glyph: char, font: FontStack<'static>) -> Self {
+        let mut layout = TextLayout::new(*theme::ICON_SIZE.default_value() as f32);
+        layout.set_font(font);
+        let source = IconSource::Glyph {
+            text: glyph.to_string().into(),
+            layout: Box::new(layout),
+        };
+        Self::with_source(name.into(), source)
+    }
+
+    /// Create an icon from a path in a [`ICON_VIEWBOX_SIZE`] square.
+    ///
+    /// `name` is the accessible name of the icon.
+    pub fn from_path(name: impl Into<ArcStr>, path: BezPath) -> Self {
+        Self::with_source(name.into(), IconSource::Path(path))
+    }
+
+    /// Create an icon from SVG path data, as found in the `d` attribute of a `<path>` element.
+    ///
+    /// The path is drawn in a [`ICON_VIEWBOX_SIZE`] square.
+    pub fn from_svg_path(name: impl Into<ArcStr>, data: &str) -> Result<Self, SvgParseError> {
+        Ok(Self::from_path(name, BezPath::from_svg(data)?))
+    }
+
+    /// Create an icon from the built-in icon set.
+    #[cfg(feature = "standard-icons")]
+    pub fn standard(icon: StandardIcon) -> Self {
+        Self::from_svg_path(icon.name(), icon.svg_path()).expect("invalid built-in icon")
+    }
+
+    fn with_source(name: ArcStr, source: IconSource) -> Self {
+        Self {
+            source,
+            name,
+            size: None,
+            brush: None,
+            tint: (*theme::TEXT_COLOR.default_value()).into(),
+        }
+    }
+
+    /// Builder-style method for setting the side length of the icon.
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Builder-style method for setting the icon's brush, instead of the inherited text color.
+    #[doc(alias = "with_color")]
+    pub fn with_brush(mut self, brush: impl Into<TextBrush>) -> Self {
+        let brush = brush.into();
+        self.tint = brush.clone();
+        self.brush = Some(brush);
+        self
+    }
+
+    /// The accessible name of the icon.
+    pub fn name(&self) -> &ArcStr {
+        &self.name
+    }
+
+    fn resolve_tint(&mut self, text_style: &TextStyle, env: &Env, disabled: bool) {
+        self.tint = if disabled {
+            env.get(theme::DISABLED_TEXT_COLOR).into()
+        } else if let Some(brush) = &self.brush {
+            brush.clone()
+        } else {
+            text_style.brush_or_default(env)
+        };
+        if let IconSource::Glyph { layout, .. } = &mut self.source {
+            layout.set_brush(self.tint.clone());
+        }
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl Icon {
+    /// Set the side length of the icon.
+    pub fn set_size(this: &mut WidgetMut<'_, Self>, size: f64) {
+        this.widget.size = Some(size);
+        this.ctx.request_layout();
+    }
+
+    /// Set the accessible name of the icon.
+    pub fn set_name(this: &mut WidgetMut<'_, Self>, name: impl Into<ArcStr>) {
+        this.widget.name = name.into();
+        this.ctx.request_accessibility_update();
+    }
+
+    /// Set the icon's brush, instead of the inherited text color.
+    #[doc(alias = "set_color")]
+    pub fn set_brush(this: &mut WidgetMut<'_, Self>, brush: impl Into<TextBrush>) {
+        this.widget.brush = Some(brush.into());
+        Self::update_tint(this);
+    }
+
+    /// Tint the icon with the inherited text color again.
+    pub fn reset_brush(this: &mut WidgetMut<'_, Self>) {
+        this.widget.brush = None;
+        Self::update_tint(this);
+    }
+
+    fn update_tint(this: &mut WidgetMut<'_, Self>) {
+        let disabled = this.ctx.is_disabled();
+        this.widget
+            .resolve_tint(this.ctx.text_style(), this.ctx.env(), disabled);
+        // TODO: Parley seems to require a relayout when colours change
+        this.ctx.request_layout();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for Icon {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::DisabledChanged(disabled) => {
+                self.resolve_tint(ctx.text_style(), ctx.env(), *disabled);
+                ctx.request_layout();
+            }
+            Update::TextStyleChanged | Update::EnvChanged => {
+                let disabled = ctx.is_disabled();
+                self.resolve_tint(ctx.text_style(), ctx.env(), disabled);
+                ctx.request_layout();
+            }
+            _ => {}
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = self.size.unwrap_or_else(|| ctx.env().get(theme::ICON_SIZE));
+        if let IconSource::Glyph { text, layout } = &mut self.source {
+            layout.set_text_size(size as f32);
+            if layout.needs_rebuild() {
+                let (font_ctx, layout_ctx) = ctx.text_contexts();
+                layout.rebuild(font_ctx, layout_ctx, text, false);
+            }
+        }
+        bc.constrain(Size::new(size, size))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let size = ctx.size();
+        match &mut self.source {
+            IconSource::Glyph { layout, .. } => {
+                let glyph_size = layout.size();
+                let origin = Point::new(
+                    (size.width - glyph_size.width) / 2.0,
+                    (size.height - glyph_size.height) / 2.0,
+                );
+                layout.set_glyph_snapping(ctx.glyph_snapping());
+                layout.draw(scene, origin);
+            }
+            IconSource::Path(path) => {
+                let scale = size.width.min(size.height) / ICON_VIEWBOX_SIZE;
+                let offset = Vec2::new(
+                    (size.width - ICON_VIEWBOX_SIZE * scale) / 2.0,
+                    (size.height - ICON_VIEWBOX_SIZE * scale) / 2.0,
+                );
+                let brush: &Brush = match &self.tint {
+                    TextBrush::Normal(brush, _) => brush,
+                    TextBrush::Highlight { text, .. } => text,
+                };
+                let transform = Affine::translate(offset) * Affine::scale(scale);
+                scene.fill(Fill::NonZero, transform, brush, None, path);
+            }
+        }
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::Image
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        node.set_name(self.name.as_ref().to_string());
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        SmallVec::new()
+    }
+
+    fn accepts_pointer_interaction(&self) -> bool {
+        false
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Icon")
+    }
+
+    fn get_debug_text(&self) -> Option<String> {
+        Some(self.name.to_string())
+    }
+}
+
+// --- MARK: STANDARD ICONS ---
+/// The built-in icon set.
+///
+/// Requires the `standard-icons` feature.
+#[cfg(feature = "standard-icons")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StandardIcon {
+    Add,
+    Remove,
+    Check,
+    Close,
+    Menu,
+    ChevronLeft,
+    ChevronRight,
+    ChevronUp,
+    ChevronDown,
+}
+
+#[cfg(feature = "standard-icons")]
+impl StandardIcon {
+    /// Every icon in the set.
+    pub const ALL: &'static [StandardIcon] = &[
+        StandardIcon::Add,
+        StandardIcon::Remove,
+        StandardIcon::Check,
+        StandardIcon::Close,
+        StandardIcon::Menu,
+        StandardIcon::ChevronLeft,
+        StandardIcon::ChevronRight,
+        StandardIcon::ChevronUp,
+        StandardIcon::ChevronDown,
+    ];
+
+    /// The default accessible name of the icon.
+    pub fn name(self) -> &'static str {
+        match self {
+            StandardIcon::Add => "Add",
+            StandardIcon::Remove => "Remove",
+            StandardIcon::Check => "Check",
+            StandardIcon::Close => "Close",
+            StandardIcon::Menu => "Menu",
+            StandardIcon::ChevronLeft => "Chevron left",
+            StandardIcon::ChevronRight => "Chevron right",
+            StandardIcon::ChevronUp => "Chevron up",
+            StandardIcon::ChevronDown => "Chevron down",
+        }
+    }
+
+    /// The SVG path data of the icon, in a [`ICON_VIEWBOX_SIZE`] square.
+    pub fn svg_path(self) -> &'static str {
+        match self {
+            StandardIcon::Add => "M11 5H13V11H19V13H13V19H11V13H5V11H11Z",
+            StandardIcon::Remove => "M5 11H19V13H5Z",
+            StandardIcon::Check => "M9 14.2L19.6 3.6L21 5L9 17L3 11L4.4 9.6Z",
+            StandardIcon::Close => {
+                "M5 6.4L6.4 5L12 10.6L17.6 5L19 6.4L13.4 12L19 17.6L17.6 19L12 13.4L6.4 19L5 17.6L10.6 12Z"
+            }
+            StandardIcon::Menu => "M3 6H21V8H3ZM3 11H21V13H3ZM3 16H21V18H3Z",
+            StandardIcon::ChevronLeft => "M15.4 7.4L14 6L8 12L14 18L15.4 16.6L10.8 12Z",
+            StandardIcon::ChevronRight => "M8.6 7.4L10 6L16 12L10 18L8.6 16.6L13.2 12Z",
+            StandardIcon::ChevronUp => "M7.4 15.4L6 14L12 8L18 14L16.6 15.4L12 10.8Z",
+            StandardIcon::ChevronDown => "M7.4 8.6L6 10L12 16L18 10L16.6 8.6L12 13.2Z",
+        }
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::widget::Flex;
+
+    const SQUARE: &str = "M4 4H20V20H4Z";
+
+    #[test]
+    fn icon_size() {
+        let default_id = WidgetId::next();
+        let sized_id = WidgetId::next();
+        let widget = Flex::row()
+            .with_child_id(Icon::from_svg_path("Square", SQUARE).unwrap(), default_id)
+            .with_child_id(
+                Icon::from_svg_path("Square", SQUARE)
+                    .unwrap()
+                    .with_size(40.0),
+                sized_id,
+            );
+
+        let mut harness = TestHarness::create(widget);
+
+        assert_eq!(
+            harness.get_widget(default_id).ctx().size(),
+            Size::new(
+                *theme::ICON_SIZE.default_value(),
+                *theme::ICON_SIZE.default_value()
+            )
+        );
+        assert_eq!(
+            harness.get_widget(sized_id).ctx().size(),
+            Size::new(40.0, 40.0)
+        );
+
+        harness.edit_widget(sized_id, |mut icon| {
+            let mut icon = icon.downcast::<Icon>();
+            Icon::set_size(&mut icon, 20.0);
+        });
+        assert_eq!(
+            harness.get_widget(sized_id).ctx().size(),
+            Size::new(20.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn icon_tint_follows_text_style() {
+        let icon_id = WidgetId::next();
+        let widget =
+            Flex::row().with_child_id(Icon::from_svg_path("Square", SQUARE).unwrap(), icon_id);
+
+        let mut harness = TestHarness::create(widget);
+        harness.edit_root_widget(|mut flex| {
+            flex.ctx.set_text_style(
+                TextStyle::new().with_text_brush(*theme::PRIMARY_LIGHT.default_value()),
+            );
+        });
+
+        let icon = harness.get_widget(icon_id);
+        let icon = icon.downcast::<Icon>().unwrap();
+        assert_eq!(
+            icon.widget.tint,
+            (*theme::PRIMARY_LIGHT.default_value()).into()
+        );
+    }
+
+    #[cfg(feature = "standard-icons")]
+    #[test]
+    fn standard_icons_parse() {
+        for icon in StandardIcon::ALL {
+            BezPath::from_svg(icon.svg_path()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A widget which reports clicks, hovering, key presses and pastes on its child.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+use crate::clipboard::ClipboardContent;
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, Action, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A widget which submits actions when its child is clicked, hovered, or receives a key press,
+/// so that any widget can be made interactive without writing a new widget.
+///
+/// Each kind of action is only submitted once enabled with the matching builder method:
+/// - [`Action::Clicked`], with [`on_click`](Self::on_click).
+/// - [`Action::HoverChanged`], with [`on_hover`](Self::on_hover).
+/// - [`Action::KeyPressed`], with [`on_key`](Self::on_key).
+/// - [`Action::Pasted`], with [`on_paste`](Self::on_paste).
+///
+/// The actions hold the id of the child. Events which the child handles itself, e.g. a click
+/// on a [`Button`](super::Button), don't produce actions. Key presses are only reported while
+/// this widget has focus, and pressing Tab still moves the focus.
+///
+/// The widget can also copy content to the clipboard, which is set with
+/// [`with_copy_content`](Self::with_copy_content).
+pub struct Interactive<W: Widget> {
+    child: WidgetPod<W>,
+    click: bool,
+    hover: bool,
+    key: bool,
+    paste: bool,
+    copy_content: Option<ClipboardContent>,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> Interactive<W> {
+    /// Create a new widget wrapping `child`, which doesn't submit any actions yet.
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            click: false,
+            hover: false,
+            key: false,
+            paste: false,
+            copy_content: None,
+        }
+    }
+
+    /// Builder-style method to submit an [`Action::Clicked`] when the child is clicked.
+    pub fn on_click(mut self) -> Self {
+        self.click = true;
+        self
+    }
+
+    /// Builder-style method to submit an [`Action::HoverChanged`] when the pointer enters
+    /// or leaves the child.
+    pub fn on_hover(mut self) -> Self {
+        self.hover = true;
+        self
+    }
+
+    /// Builder-style method to submit an [`Action::KeyPressed`] when a key is pressed while
+    /// the widget has focus.
+    ///
+    /// This makes the widget accept focus, so that children which don't accept focus can
+    /// still be used with the keyboard. The widget is focused when it is clicked.
+    pub fn on_key(mut self) -> Self {
+        self.key = true;
+        self
+    }
+
+    /// Builder-style method to submit an [`Action::Pasted`] when Ctrl+V (Cmd+V on macOS)
+    /// is pressed while the widget has focus, with the text of the clipboard.
+    ///
+    /// Like [`on_key`](Self::on_key), this makes the widget accept focus.
+    pub fn on_paste(mut self) -> Self {
+        self.paste = true;
+        self
+    }
+
+    /// Builder-style method to copy `content` to the clipboard when Ctrl+C (Cmd+C on macOS)
+    /// is pressed while the widget has focus.
+    ///
+    /// Like [`on_key`](Self::on_key), this makes the widget accept focus.
+    pub fn with_copy_content(mut self, content: ClipboardContent) -> Self {
+        self.copy_content = Some(content);
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> Interactive<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    pub fn set_on_click(this: &mut WidgetMut<'_, Self>, enabled: bool) {
+        this.widget.click = enabled;
+    }
+
+    pub fn set_on_hover(this: &mut WidgetMut<'_, Self>, enabled: bool) {
+        this.widget.hover = enabled;
+    }
+
+    /// Set whether key presses are reported.
+    ///
+    /// Whether the widget accepts focus is only decided when it is added to the tree,
+    /// so this doesn't change it.
+    pub fn set_on_key(this: &mut WidgetMut<'_, Self>, enabled: bool) {
+        this.widget.key = enabled;
+    }
+
+    /// Set whether pastes are reported.
+    ///
+    /// Like [`set_on_key`](Self::set_on_key), this doesn't change whether the widget
+    /// accepts focus.
+    pub fn set_on_paste(this: &mut WidgetMut<'_, Self>, enabled: bool) {
+        this.widget.paste = enabled;
+    }
+
+    /// Set the content copied to the clipboard, or `None` to not copy anything.
+    ///
+    /// Like [`set_on_key`](Self::set_on_key), this doesn't change whether the widget
+    /// accepts focus.
+    pub fn set_copy_content(this: &mut WidgetMut<'_, Self>, content: Option<ClipboardContent>) {
+        this.widget.copy_content = content;
+    }
+}
+
+// --- MARK: INTERNALS ---
+/// Whether `key` is the clipboard shortcut using `letter`, e.g. Ctrl+C for `"c"`.
+fn is_clipboard_shortcut(key: &Key, mods: ModifiersState, letter: &str) -> bool {
+    // TODO: Only use the Cmd key on macOS.
+    let Key::Character(c) = key else {
+        return false;
+    };
+    c.eq_ignore_ascii_case(letter) && (mods.control_key() || mods.super_key())
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for Interactive<W> {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        match event {
+            // A descendant which captured the pointer, e.g. a button, handles the press.
+            PointerEvent::PointerDown(_, _) if ctx.is_pointer_captured_by_other() => {}
+            PointerEvent::PointerDown(_, _) if self.click || self.accepts_focus() => {
+                if self.click {
+                    ctx.capture_pointer();
+                }
+                if self.accepts_focus() {
+                    ctx.request_focus();
+                }
+            }
+            PointerEvent::PointerUp(button, _)
+                if self.click && ctx.has_pointer_capture() && ctx.is_hovered() =>
+            {
+                ctx.submit_action(Action::Clicked(*button, self.child.id()));
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        // Events bubbling up from a focused descendant are left to it.
+        if ctx.target() != ctx.widget_id() {
+            return;
+        }
+        match event {
+            TextEvent::KeyboardKey(event, mods)
+                if event.state.is_pressed()
+                    && self.copy_content.is_some()
+                    && is_clipboard_shortcut(&event.logical_key, *mods, "c") =>
+            {
+                ctx.set_clipboard(self.copy_content.clone().unwrap());
+                ctx.set_handled();
+            }
+            TextEvent::KeyboardKey(event, mods)
+                if event.state.is_pressed()
+                    && self.paste
+                    && is_clipboard_shortcut(&event.logical_key, *mods, "v") =>
+            {
+                ctx.request_paste();
+                ctx.set_handled();
+            }
+            TextEvent::Paste(text) if self.paste => {
+                ctx.submit_action(Action::Pasted(text.clone(), self.child.id()));
+                ctx.set_handled();
+            }
+            TextEvent::KeyboardKey(event, mods) if self.key && event.state.is_pressed() => {
+                ctx.submit_action(Action::KeyPressed(
+                    event.logical_key.clone(),
+                    *mods,
+                    self.child.id(),
+                ));
+                // Tab is reported, but still moves the focus.
+                if event.logical_key != Key::Named(NamedKey::Tab) {
+                    ctx.set_handled();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::HoveredChanged(hovered) if self.hover => {
+                ctx.submit_action(Action::HoverChanged(*hovered, self.child.id()));
+            }
+            _ => {}
+        }
+    }
+
+    fn accepts_focus(&self) -> bool {
+        self.key || self.paste || self.copy_content.is_some()
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let baseline = ctx.child_baseline_offset(&self.child);
+        ctx.set_baseline_offset(baseline);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Interactive")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::PointerButton;
+    use crate::testing::{widget_ids, TestHarness};
+    use crate::widget::{Button, Flex, Label, SimpleWidget};
+
+    #[test]
+    fn click_and_hover() {
+        let [wrapper_id, label_id] = widget_ids();
+        let widget = Flex::column().with_child_id(
+            Interactive::new_pod(WidgetPod::new_with_id(Label::new("Hello"), label_id))
+                .on_click()
+                .on_hover(),
+            wrapper_id,
+        );
+
+        let mut harness = TestHarness::create(widget);
+        assert_eq!(harness.pop_action(), None);
+
+        harness.mouse_move_to(label_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::HoverChanged(true, label_id), wrapper_id))
+        );
+
+        harness.mouse_click_on(label_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((
+                Action::Clicked(PointerButton::Primary, label_id),
+                wrapper_id
+            ))
+        );
+
+        harness.mouse_move((-10., -10.));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::HoverChanged(false, label_id), wrapper_id))
+        );
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn paste_when_focused() {
+        let [wrapper_id, label_id] = widget_ids();
+        let widget = Flex::column().with_child_id(
+            Interactive::new_pod(WidgetPod::new_with_id(Label::new("Hello"), label_id))
+                .on_paste()
+                .with_copy_content(ClipboardContent::text("Hello")),
+            wrapper_id,
+        );
+
+        let mut harness = TestHarness::create(widget);
+        harness.mouse_click_on(label_id);
+        assert_eq!(harness.focused_widget().map(|w| w.id()), Some(wrapper_id));
+
+        harness.process_text_event(TextEvent::Paste("a\tb".into()));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::Pasted("a\tb".into(), label_id), wrapper_id))
+        );
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn child_button_keeps_its_press() {
+        let [button_id] = widget_ids();
+        let widget = Interactive::new_pod(WidgetPod::new_with_id(Button::new("Hi"), button_id))
+            .on_click()
+            .on_key();
+
+        let mut harness = TestHarness::create(widget);
+        harness.mouse_click_on(button_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ButtonPressed(PointerButton::Primary), button_id))
+        );
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn tab_is_reported_and_moves_focus() {
+        let [wrapper_id, label_id, next_id] = widget_ids();
+        let widget = Flex::column()
+            .with_child_id(
+                Interactive::new_pod(WidgetPod::new_with_id(Label::new("Hello"), label_id))
+                    .on_key(),
+                wrapper_id,
+            )
+            .with_child_id(SimpleWidget::new(()).accepts_focus(true), next_id);
+
+        let mut harness = TestHarness::create(widget);
+        harness.focus_on(Some(wrapper_id));
+
+        let tab = Key::Named(NamedKey::Tab);
+        harness.keyboard_key(tab.clone());
+        assert_eq!(
+            harness.pop_action(),
+            Some((
+                Action::KeyPressed(tab, ModifiersState::empty(), label_id),
+                wrapper_id
+            ))
+        );
+        assert_eq!(harness.pop_action(), None);
+        assert_eq!(harness.focused_widget().map(|w| w.id()), Some(next_id));
+    }
+
+    #[test]
+    fn no_actions_unless_enabled() {
+        let [label_id] = widget_ids();
+        let widget =
+            Interactive::new_pod(WidgetPod::new_with_id(Label::new("Hello"), label_id)).on_click();
+
+        let mut harness = TestHarness::create(widget);
+        harness.mouse_move_to(label_id);
+        assert_eq!(harness.pop_action(), None);
+    }
+}
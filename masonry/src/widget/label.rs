@@ -3,20 +3,23 @@
 
 //! A label widget.
 
+use std::borrow::Cow;
+
 use accesskit::{NodeBuilder, Role};
 use parley::fontique::Weight;
 use parley::layout::Alignment;
 use parley::style::{FontFamily, FontStack};
 use smallvec::SmallVec;
 use tracing::{trace_span, Span};
+use unicode_segmentation::UnicodeSegmentation;
 use vello::kurbo::{Affine, Point, Size};
 use vello::peniko::BlendMode;
 use vello::Scene;
 
-use crate::text::{ArcStr, TextBrush, TextLayout};
+use crate::text::{ArcStr, TextBrush, TextLayout, TextStyle};
 use crate::widget::WidgetMut;
 use crate::{
-    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, PointerEvent,
+    theme, AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, PointerEvent,
     RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
 };
 
@@ -28,12 +31,50 @@ pub(super) const LABEL_X_PADDING: f64 = 2.0;
 pub enum LineBreaking {
     /// Lines are broken at word boundaries.
     WordWrap,
+    /// Lines are broken between any characters, even inside words.
+    ///
+    /// Lines are broken between grapheme clusters, so combined characters and emoji
+    /// sequences stay together. In [`Prose`](super::Prose) and [`Textbox`](super::Textbox),
+    /// whose text can be selected, this only breaks the words which are wider than a line.
+    CharWrap,
     /// Lines are truncated to the width of the label.
     Clip,
     /// Lines overflow the label.
+    ///
+    /// Put the widget in a [`Portal`](super::Portal) which doesn't constrain its child
+    /// horizontally to make long lines scrollable.
     Overflow,
 }
 
+impl LineBreaking {
+    /// Whether lines are wrapped to fit the width of the widget.
+    pub fn wraps(self) -> bool {
+        match self {
+            Self::WordWrap | Self::CharWrap => true,
+            Self::Clip | Self::Overflow => false,
+        }
+    }
+
+    /// The text to lay out for `text`, with a break opportunity after each grapheme
+    /// cluster when wrapping characters.
+    ///
+    /// Parley only breaks lines at word boundaries, so a zero width space is inserted
+    /// after each grapheme cluster which isn't whitespace.
+    pub(super) fn layout_text(self, text: &str) -> Cow<'_, str> {
+        if self != Self::CharWrap {
+            return Cow::Borrowed(text);
+        }
+        let mut wrapped = String::with_capacity(text.len() * 2);
+        for grapheme in text.graphemes(true) {
+            wrapped.push_str(grapheme);
+            if !grapheme.chars().all(char::is_whitespace) {
+                wrapped.push('\u{200B}');
+            }
+        }
+        Cow::Owned(wrapped)
+    }
+}
+
 /// A widget displaying non-interactive text.
 ///
 /// This is useful for creating interactive widgets which internally
@@ -44,7 +85,8 @@ pub struct Label {
     text_layout: TextLayout,
     line_break_mode: LineBreaking,
     show_disabled: bool,
-    brush: TextBrush,
+    /// Text properties set on this widget, which take precedence over the inherited style.
+    style: TextStyle,
 }
 
 // --- MARK: BUILDERS ---
@@ -54,10 +96,10 @@ impl Label {
         Self {
             text: text.into(),
             text_changed: false,
-            text_layout: TextLayout::new(crate::theme::TEXT_SIZE_NORMAL as f32),
+            text_layout: TextLayout::new(*theme::TEXT_SIZE_NORMAL.default_value() as f32),
             line_break_mode: LineBreaking::Overflow,
             show_disabled: true,
-            brush: crate::theme::TEXT_COLOR.into(),
+            style: TextStyle::default(),
         }
     }
 
@@ -67,18 +109,22 @@ impl Label {
 
     #[doc(alias = "with_text_color")]
     pub fn with_text_brush(mut self, brush: impl Into<TextBrush>) -> Self {
-        self.text_layout.set_brush(brush);
+        let brush = brush.into();
+        self.text_layout.set_brush(brush.clone());
+        self.style.brush = Some(brush);
         self
     }
 
     #[doc(alias = "with_font_size")]
     pub fn with_text_size(mut self, size: f32) -> Self {
         self.text_layout.set_text_size(size);
+        self.style.text_size = Some(size);
         self
     }
 
     pub fn with_weight(mut self, weight: Weight) -> Self {
         self.text_layout.set_weight(weight);
+        self.style.weight = Some(weight);
         self
     }
 
@@ -89,6 +135,7 @@ impl Label {
 
     pub fn with_font(mut self, font: FontStack<'static>) -> Self {
         self.text_layout.set_font(font);
+        self.style.font = Some(font);
         self
     }
     pub fn with_font_family(self, font: FontFamily<'static>) -> Self {
@@ -129,33 +176,54 @@ impl Label {
     #[doc(alias = "set_text_color")]
     pub fn set_text_brush(this: &mut WidgetMut<'_, Self>, brush: impl Into<TextBrush>) {
         let brush = brush.into();
-        this.widget.brush = brush;
+        this.widget.style.brush = Some(brush.clone());
         if !this.ctx.is_disabled() {
-            let brush = this.widget.brush.clone();
             Self::set_text_properties(this, |layout| layout.set_brush(brush));
         }
     }
     pub fn set_text_size(this: &mut WidgetMut<'_, Self>, size: f32) {
+        this.widget.style.text_size = Some(size);
         Self::set_text_properties(this, |layout| layout.set_text_size(size));
     }
     pub fn set_weight(this: &mut WidgetMut<'_, Self>, weight: Weight) {
+        this.widget.style.weight = Some(weight);
         Self::set_text_properties(this, |layout| layout.set_weight(weight));
     }
     pub fn set_alignment(this: &mut WidgetMut<'_, Self>, alignment: Alignment) {
         Self::set_text_properties(this, |layout| layout.set_text_alignment(alignment));
     }
     pub fn set_font(this: &mut WidgetMut<'_, Self>, font_stack: FontStack<'static>) {
+        this.widget.style.font = Some(font_stack);
         Self::set_text_properties(this, |layout| layout.set_font(font_stack));
     }
     pub fn set_font_family(this: &mut WidgetMut<'_, Self>, family: FontFamily<'static>) {
         Self::set_font(this, FontStack::Single(family));
     }
     pub fn set_line_break_mode(this: &mut WidgetMut<'_, Self>, line_break_mode: LineBreaking) {
+        // The laid out text differs when wrapping characters.
+        if (this.widget.line_break_mode == LineBreaking::CharWrap)
+            != (line_break_mode == LineBreaking::CharWrap)
+        {
+            this.widget.text_changed = true;
+        }
         this.widget.line_break_mode = line_break_mode;
         this.ctx.request_layout();
     }
 }
 
+impl Label {
+    /// The brush of the text, given whether the label is disabled.
+    fn brush(&self, ctx: &UpdateCtx, disabled: bool) -> TextBrush {
+        if self.show_disabled && disabled {
+            ctx.env().get(theme::DISABLED_TEXT_COLOR).into()
+        } else {
+            self.style
+                .inherit_from(ctx.text_style())
+                .brush_or_default(ctx.env())
+        }
+    }
+}
+
 // --- MARK: IMPL WIDGET ---
 impl Widget for Label {
     fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
@@ -169,24 +237,25 @@ impl Widget for Label {
     fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
         match event {
             Update::DisabledChanged(disabled) => {
-                if self.show_disabled {
-                    if *disabled {
-                        self.text_layout
-                            .set_brush(crate::theme::DISABLED_TEXT_COLOR);
-                    } else {
-                        self.text_layout.set_brush(self.brush.clone());
-                    }
-                }
+                let brush = self.brush(ctx, *disabled);
+                self.text_layout.set_brush(brush);
                 // TODO: Parley seems to require a relayout when colours change
                 ctx.request_layout();
             }
+            Update::TextStyleChanged | Update::EnvChanged => {
+                let style = self.style.inherit_from(ctx.text_style());
+                style.apply_to_layout(&mut self.text_layout, ctx.env());
+                let brush = self.brush(ctx, ctx.is_disabled());
+                self.text_layout.set_brush(brush);
+                ctx.request_layout();
+            }
             _ => {}
         }
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         // Compute max_advance from box constraints
-        let max_advance = if self.line_break_mode != LineBreaking::WordWrap {
+        let max_advance = if !self.line_break_mode.wraps() {
             None
         } else if bc.max().width.is_finite() {
             Some(bc.max().width as f32 - 2. * LABEL_X_PADDING as f32)
@@ -196,10 +265,12 @@ impl Widget for Label {
             None
         };
         self.text_layout.set_max_advance(max_advance);
+        self.text_layout.set_line_height_grid(ctx.baseline_grid());
         if self.text_layout.needs_rebuild() || self.text_changed {
             let (font_ctx, layout_ctx) = ctx.text_contexts();
+            let text = self.line_break_mode.layout_text(&self.text);
             self.text_layout
-                .rebuild(font_ctx, layout_ctx, &self.text, self.text_changed);
+                .rebuild(font_ctx, layout_ctx, &text, self.text_changed);
             self.text_changed = false;
         }
         // We ignore trailing whitespace for a label
@@ -222,6 +293,7 @@ impl Widget for Label {
             let clip_rect = ctx.size().to_rect();
             scene.push_layer(BlendMode::default(), 1., Affine::IDENTITY, &clip_rect);
         }
+        self.text_layout.set_glyph_snapping(ctx.glyph_snapping());
         self.text_layout
             .draw(scene, Point::new(LABEL_X_PADDING, 0.0));
 
@@ -258,12 +330,16 @@ impl Widget for Label {
 // --- MARK: TESTS ---
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
     use insta::assert_debug_snapshot;
     use parley::style::GenericFamily;
 
     use super::*;
     use crate::assert_render_snapshot;
-    use crate::testing::TestHarness;
+    use crate::testing::{widget_ids, ModularWidget, TestHarness, TestWidgetExt};
+    use crate::text::TextMetrics;
     use crate::theme::{PRIMARY_DARK, PRIMARY_LIGHT};
     use crate::widget::{Flex, SizedBox};
 
@@ -280,7 +356,7 @@ mod tests {
     #[test]
     fn styled_label() {
         let label = Label::new("The quick brown fox jumps over the lazy dog")
-            .with_text_brush(PRIMARY_LIGHT)
+            .with_text_brush(*PRIMARY_LIGHT.default_value())
             .with_font_family(FontFamily::Generic(GenericFamily::Monospace))
             .with_text_size(20.0)
             .with_line_break_mode(LineBreaking::WordWrap)
@@ -325,11 +401,34 @@ mod tests {
         assert_render_snapshot!(harness, "line_break_modes");
     }
 
+    #[test]
+    fn char_wrap_breaks_inside_words() {
+        // A combining accent stays with its letter.
+        assert_eq!(
+            LineBreaking::CharWrap.layout_text("e\u{301}f g"),
+            "e\u{301}\u{200B}f\u{200B} g\u{200B}"
+        );
+
+        let first_line = |mode| {
+            let [label_id] = widget_ids();
+            let label = Label::new("ab cdefghijklmnopqrstuvwxyz").with_line_break_mode(mode);
+            let widget = Flex::column().with_child_id(label, label_id);
+            let harness = TestHarness::create_with_size(widget, Size::new(100.0, 200.0));
+            let label = harness.get_widget(label_id).downcast::<Label>().unwrap();
+            let layout = label.text_layout.layout();
+            assert!(layout.len() > 1);
+            layout.get(0).unwrap().metrics().advance
+        };
+        // Word wrapping moves the long word to the next line, while character wrapping
+        // fills the first line with its start.
+        assert!(first_line(LineBreaking::CharWrap) > first_line(LineBreaking::WordWrap));
+    }
+
     #[test]
     fn edit_label() {
         let image_1 = {
             let label = Label::new("The quick brown fox jumps over the lazy dog")
-                .with_text_brush(PRIMARY_LIGHT)
+                .with_text_brush(*PRIMARY_LIGHT.default_value())
                 .with_font_family(FontFamily::Generic(GenericFamily::Monospace))
                 .with_text_size(20.0)
                 .with_line_break_mode(LineBreaking::WordWrap)
@@ -342,7 +441,7 @@ mod tests {
 
         let image_2 = {
             let label = Label::new("Hello world")
-                .with_text_brush(PRIMARY_DARK)
+                .with_text_brush(*PRIMARY_DARK.default_value())
                 .with_text_size(40.0);
 
             let mut harness = TestHarness::create_with_size(label, Size::new(50.0, 50.0));
@@ -350,7 +449,7 @@ mod tests {
             harness.edit_root_widget(|mut label| {
                 let mut label = label.downcast::<Label>();
                 Label::set_text(&mut label, "The quick brown fox jumps over the lazy dog");
-                Label::set_text_brush(&mut label, PRIMARY_LIGHT);
+                Label::set_text_brush(&mut label, *PRIMARY_LIGHT.default_value());
                 Label::set_font_family(&mut label, FontFamily::Generic(GenericFamily::Monospace));
                 Label::set_text_size(&mut label, 20.0);
                 Label::set_line_break_mode(&mut label, LineBreaking::WordWrap);
@@ -363,4 +462,92 @@ mod tests {
         // We don't use assert_eq because we don't want rich assert
         assert!(image_1 == image_2);
     }
+
+    #[test]
+    fn inherited_text_style() {
+        let inherited_id = WidgetId::next();
+        let explicit_id = WidgetId::next();
+        let widget = Flex::column()
+            .with_child_id(Label::new("Hello"), inherited_id)
+            .with_child_id(Label::new("Hello").with_text_size(10.0), explicit_id);
+
+        let mut harness = TestHarness::create(widget);
+        let inherited_size = harness.get_widget(inherited_id).ctx().size();
+        let explicit_size = harness.get_widget(explicit_id).ctx().size();
+
+        harness.edit_root_widget(|mut flex| {
+            flex.ctx
+                .set_text_style(TextStyle::new().with_text_size(40.0));
+        });
+
+        // The label without an explicit size picks up the style of its ancestor.
+        assert!(harness.get_widget(inherited_id).ctx().size().height > inherited_size.height);
+        assert_eq!(harness.get_widget(explicit_id).ctx().size(), explicit_size);
+
+        harness.edit_root_widget(|mut flex| {
+            flex.ctx.set_text_style(TextStyle::new());
+        });
+
+        assert_eq!(
+            harness.get_widget(inherited_id).ctx().size(),
+            inherited_size
+        );
+    }
+
+    #[test]
+    fn measure_text_matches_label() {
+        let [label_id] = widget_ids();
+        let style = TextStyle::new().with_text_size(20.0);
+        let measured = Rc::new(Cell::new(Size::ZERO));
+
+        let measuring_widget = ModularWidget::new(measured.clone()).layout_fn({
+            let style = style.clone();
+            move |measured, ctx, _| {
+                measured.set(ctx.measure_text("Hello world", &style, None));
+                Size::ZERO
+            }
+        });
+        let widget = Flex::column().with_child(measuring_widget).with_child(
+            Label::new("Hello world")
+                .with_text_size(20.0)
+                .with_id(label_id),
+        );
+
+        let harness = TestHarness::create(widget);
+
+        // Widget sizes are rounded to whole pixels by the layout pass.
+        let label_size = harness.get_widget(label_id).ctx().size();
+        let measured = measured.get();
+        assert_eq!(measured.height, label_size.height);
+        assert!((label_size.width - 2. * LABEL_X_PADDING - measured.width).abs() < 1.0);
+    }
+
+    #[test]
+    fn text_metrics_match_label_height() {
+        let [label_id] = widget_ids();
+        let style = TextStyle::new().with_text_size(20.0);
+        let metrics = Rc::new(Cell::new(TextMetrics::default()));
+
+        let measuring_widget = ModularWidget::new(metrics.clone()).layout_fn({
+            let style = style.clone();
+            move |metrics, ctx, _| {
+                metrics.set(ctx.text_metrics(&style));
+                Size::ZERO
+            }
+        });
+        let widget = Flex::column().with_child(measuring_widget).with_child(
+            Label::new("Hello world")
+                .with_text_size(20.0)
+                .with_id(label_id),
+        );
+
+        let harness = TestHarness::create(widget);
+
+        let metrics = metrics.get();
+        assert!(metrics.ascent > 0. && metrics.descent > 0.);
+        assert!(metrics.line_height >= metrics.ascent + metrics.descent);
+        // A single line label is as tall as its line, rounded to whole pixels.
+        let label_height = harness.get_widget(label_id).ctx().size().height;
+        assert!((label_height - metrics.line_height).abs() < 1.0);
+    }
 }
@@ -0,0 +1,299 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A hyperlink widget.
+
+use accesskit::{DefaultActionVerb, NodeBuilder, Role};
+use smallvec::SmallVec;
+use tracing::{trace, trace_span, Span};
+use vello::kurbo::{Point, Size};
+use vello::Scene;
+
+use crate::action::Action;
+use crate::text::{ArcStr, TextLayout, TextStyle};
+use crate::widget::label::LABEL_X_PADDING;
+use crate::widget::WidgetMut;
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, CursorIcon, Env, EventCtx, LayoutCtx, PaintCtx,
+    PointerEvent, QueryCtx, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A widget displaying text styled as a hyperlink.
+///
+/// The text is underlined while hovered, and uses a different color once the link
+/// has been visited.
+///
+/// Emits [`Action::LinkActivated`] with the link's target when clicked. Unlike a
+/// [`Button`](super::Button), a link is meant for navigation, and is exposed to
+/// accessibility as such.
+pub struct Link {
+    text: ArcStr,
+    target: ArcStr,
+    text_changed: bool,
+    text_layout: TextLayout,
+    visited: bool,
+    /// Text properties set on this widget, which take precedence over the inherited style.
+    style: TextStyle,
+}
+
+// --- MARK: BUILDERS ---
+impl Link {
+    /// Create a new link displaying `text`, which navigates to `target`.
+    pub fn new(text: impl Into<ArcStr>, target: impl Into<ArcStr>) -> Self {
+        let mut text_layout = TextLayout::new(*theme::TEXT_SIZE_NORMAL.default_value() as f32);
+        text_layout.set_brush(*theme::LINK_COLOR.default_value());
+        Self {
+            text: text.into(),
+            target: target.into(),
+            text_changed: false,
+            text_layout,
+            visited: false,
+            style: TextStyle::default(),
+        }
+    }
+
+    /// Builder-style method for setting whether the link has already been visited.
+    pub fn with_visited(mut self, visited: bool) -> Self {
+        self.visited = visited;
+        self.text_layout.set_brush(link_color(&Env::new(), visited));
+        self
+    }
+
+    #[doc(alias = "with_font_size")]
+    pub fn with_text_size(mut self, size: f32) -> Self {
+        self.text_layout.set_text_size(size);
+        self.style.text_size = Some(size);
+        self
+    }
+
+    pub fn text(&self) -> &ArcStr {
+        &self.text
+    }
+
+    /// The target emitted when the link is activated.
+    pub fn target(&self) -> &ArcStr {
+        &self.target
+    }
+
+    /// Whether the link has been visited.
+    ///
+    /// Links are marked as visited when activated.
+    pub fn is_visited(&self) -> bool {
+        self.visited
+    }
+}
+
+fn link_color(env: &Env, visited: bool) -> crate::Color {
+    if visited {
+        env.get(theme::VISITED_LINK_COLOR)
+    } else {
+        env.get(theme::LINK_COLOR)
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl Link {
+    pub fn set_text(this: &mut WidgetMut<'_, Self>, new_text: impl Into<ArcStr>) {
+        this.widget.text = new_text.into();
+        this.widget.text_changed = true;
+        this.ctx.request_layout();
+    }
+
+    /// Set the target emitted when the link is activated.
+    pub fn set_target(this: &mut WidgetMut<'_, Self>, target: impl Into<ArcStr>) {
+        this.widget.target = target.into();
+        this.ctx.request_accessibility_update();
+    }
+
+    /// Set whether the link has been visited.
+    pub fn set_visited(this: &mut WidgetMut<'_, Self>, visited: bool) {
+        this.widget.visited = visited;
+        if !this.ctx.is_disabled() {
+            let color = link_color(this.ctx.env(), visited);
+            this.widget.text_layout.set_brush(color);
+        }
+        // TODO: Parley seems to require a relayout when colours change
+        this.ctx.request_layout();
+    }
+
+    pub fn set_text_size(this: &mut WidgetMut<'_, Self>, size: f32) {
+        this.widget.style.text_size = Some(size);
+        this.widget.text_layout.set_text_size(size);
+        this.ctx.request_layout();
+    }
+}
+
+impl Link {
+    fn activate(&mut self, ctx: &mut EventCtx) {
+        ctx.submit_action(Action::LinkActivated(self.target.clone()));
+        trace!("Link {:?} activated", ctx.widget_id());
+        if !self.visited {
+            self.visited = true;
+            self.text_layout.set_brush(link_color(ctx.env(), true));
+            ctx.request_layout();
+        }
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for Link {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        match event {
+            PointerEvent::PointerDown(_, _) if !ctx.is_disabled() => {
+                ctx.capture_pointer();
+            }
+            PointerEvent::PointerUp(_, _)
+                if ctx.has_pointer_capture() && ctx.is_hovered() && !ctx.is_disabled() =>
+            {
+                self.activate(ctx);
+            }
+            _ => (),
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
+        if ctx.target() == ctx.widget_id() {
+            match event.action {
+                accesskit::Action::Default => {
+                    self.activate(ctx);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::HoveredChanged(hovered) => {
+                self.text_layout
+                    .set_underline(*hovered && !ctx.is_disabled());
+                ctx.request_layout();
+            }
+            Update::DisabledChanged(disabled) => {
+                if *disabled {
+                    self.text_layout
+                        .set_brush(ctx.env().get(theme::DISABLED_TEXT_COLOR));
+                    self.text_layout.set_underline(false);
+                } else {
+                    self.text_layout
+                        .set_brush(link_color(ctx.env(), self.visited));
+                }
+                // TODO: Parley seems to require a relayout when colours change
+                ctx.request_layout();
+            }
+            Update::TextStyleChanged | Update::EnvChanged => {
+                // Links keep their own color, only the font properties are inherited.
+                let style = self.style.inherit_from(ctx.text_style());
+                style.apply_to_layout(&mut self.text_layout, ctx.env());
+                let color = if ctx.is_disabled() {
+                    ctx.env().get(theme::DISABLED_TEXT_COLOR)
+                } else {
+                    link_color(ctx.env(), self.visited)
+                };
+                self.text_layout.set_brush(color);
+                ctx.request_layout();
+            }
+            _ => {}
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        self.text_layout.set_line_height_grid(ctx.baseline_grid());
+        if self.text_layout.needs_rebuild() || self.text_changed {
+            let (font_ctx, layout_ctx) = ctx.text_contexts();
+            self.text_layout
+                .rebuild(font_ctx, layout_ctx, &self.text, self.text_changed);
+            self.text_changed = false;
+        }
+        let text_size = self.text_layout.size();
+        bc.constrain(Size::new(
+            text_size.width + 2. * LABEL_X_PADDING,
+            text_size.height,
+        ))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        if self.text_layout.needs_rebuild() {
+            debug_panic!(
+                "Called {name}::paint with invalid layout",
+                name = self.short_type_name()
+            );
+        }
+        self.text_layout.set_glyph_snapping(ctx.glyph_snapping());
+        self.text_layout
+            .draw(scene, Point::new(LABEL_X_PADDING, 0.0));
+    }
+
+    fn get_cursor(&self, ctx: &QueryCtx, _pos: Point) -> CursorIcon {
+        if ctx.is_disabled() {
+            CursorIcon::Default
+        } else {
+            CursorIcon::Pointer
+        }
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::Link
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        node.set_name(self.text.as_ref().to_string());
+        node.set_url(self.target.as_ref().to_string());
+        if self.visited {
+            node.set_visited();
+        }
+        node.set_default_action_verb(DefaultActionVerb::Jump);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        SmallVec::new()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Link")
+    }
+
+    fn get_debug_text(&self) -> Option<String> {
+        Some(self.text.to_string())
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+
+    #[test]
+    fn activate_link() {
+        let [link_id] = widget_ids();
+        let widget = Link::new("Xilem", "https://github.com/linebender/xilem").with_id(link_id);
+
+        let mut harness = TestHarness::create(widget);
+        assert!(!harness
+            .get_widget(link_id)
+            .downcast::<Link>()
+            .unwrap()
+            .widget
+            .is_visited());
+
+        harness.mouse_click_on(link_id);
+        assert_eq!(
+            harness.pop_action(),
+            Some((
+                Action::LinkActivated("https://github.com/linebender/xilem".into()),
+                link_id
+            ))
+        );
+        assert!(harness
+            .get_widget(link_id)
+            .downcast::<Link>()
+            .unwrap()
+            .widget
+            .is_visited());
+    }
+}
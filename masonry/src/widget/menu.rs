@@ -0,0 +1,372 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A list of items, shared by the widgets which show menus.
+
+use accesskit::{DefaultActionVerb, NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::kurbo::Line;
+use vello::Scene;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::context_menu;
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::widget::{Label, WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, AllowRawMut, BoxConstraints, EventCtx, Insets, LayoutCtx,
+    PaintCtx, Point, PointerButton, PointerEvent, Rect, RegisterCtx, Size, TextEvent, Update,
+    UpdateCtx, Widget, WidgetId,
+};
+
+const ITEM_INSETS: Insets = Insets::uniform_xy(8., 4.);
+const MENU_PADDING: f64 = 4.;
+const SEPARATOR_HEIGHT: f64 = 9.;
+
+/// What the owner of a [`Menu`] should do after a key press.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MenuKeyResult {
+    /// The key wasn't used by the menu.
+    Ignored,
+    /// The highlighted item changed.
+    Highlighted,
+    /// The item at this index was activated.
+    Activated(usize),
+    /// The menu should be closed.
+    Close,
+}
+
+/// A vertical list of text items, one of which can be highlighted.
+///
+/// The items are described by a [`context_menu::Menu`], so all menus support disabled
+/// items and separators.
+///
+/// Menus are exposed to assistive technologies as a [`Role::Menu`] of [`Role::MenuItem`]s
+/// by default, or as a [`Role::ListBox`] of [`Role::ListBoxOption`]s when created with
+/// [`list_box`](Self::list_box). The highlighted item is reported as the active
+/// descendant, since the focus stays on the owner.
+///
+/// The menu doesn't submit actions itself. Instead, the widget owning it reads the
+/// activated item with [`take_activated`](Self::take_activated) when pointer and
+/// accessibility events bubble up to it, and forwards key presses with
+/// [`on_key`](Self::on_key), since the owner is the one which holds the focus.
+pub(crate) struct Menu {
+    role: Role,
+    item_role: Role,
+    items: Vec<WidgetPod<MenuItem>>,
+    model: context_menu::Menu,
+    item_rects: Vec<Rect>,
+    highlighted: Option<usize>,
+    activated: Option<usize>,
+}
+
+// --- MARK: BUILDERS ---
+impl Menu {
+    pub(crate) fn new(model: context_menu::Menu) -> Self {
+        Self::with_roles(model, Role::Menu, Role::MenuItem)
+    }
+
+    /// Create a menu which is exposed as a list of options, e.g. suggestions for a text field.
+    pub(crate) fn list_box(model: context_menu::Menu) -> Self {
+        Self::with_roles(model, Role::ListBox, Role::ListBoxOption)
+    }
+
+    fn with_roles(model: context_menu::Menu, role: Role, item_role: Role) -> Self {
+        Self {
+            role,
+            item_role,
+            items: make_items(&model, item_role),
+            model,
+            item_rects: Vec::new(),
+            highlighted: None,
+            activated: None,
+        }
+    }
+
+    /// Highlight the item at `index`, or none.
+    ///
+    /// Disabled items can't be highlighted.
+    pub(crate) fn highlight(&mut self, index: Option<usize>) {
+        self.highlighted = index.filter(|&index| self.is_enabled(index));
+    }
+
+    /// Highlight the first enabled item.
+    pub(crate) fn highlight_first(&mut self) {
+        self.highlighted = (0..self.items.len()).find(|&index| self.is_enabled(index));
+    }
+
+    pub(crate) fn highlighted(&self) -> Option<usize> {
+        self.highlighted
+    }
+
+    /// The item activated by a pointer or accessibility event since the last call.
+    pub(crate) fn take_activated(&mut self) -> Option<usize> {
+        self.activated.take()
+    }
+
+    /// Handle a key pressed while the menu is open.
+    ///
+    /// Up and Down move the highlight, wrapping around. Home and End highlight the
+    /// first and last items. Enter and Space activate the highlighted item.
+    /// Disabled items are skipped.
+    pub(crate) fn on_key(&mut self, key: &Key) -> MenuKeyResult {
+        let len = self.items.len();
+        let Key::Named(key) = key else {
+            return MenuKeyResult::Ignored;
+        };
+        let enabled = |index: &usize| self.is_enabled(*index);
+        let highlighted = match key {
+            NamedKey::ArrowDown => {
+                let start = self.highlighted.map_or(0, |i| i + 1);
+                (start..start + len).map(|i| i % len).find(enabled)
+            }
+            NamedKey::ArrowUp => {
+                let start = self.highlighted.unwrap_or(0) + len;
+                (start - len..start).rev().map(|i| i % len).find(enabled)
+            }
+            NamedKey::Home => (0..len).find(enabled),
+            NamedKey::End => (0..len).rev().find(enabled),
+            NamedKey::Enter | NamedKey::Space => {
+                return match self.highlighted {
+                    Some(index) => MenuKeyResult::Activated(index),
+                    None => MenuKeyResult::Close,
+                };
+            }
+            NamedKey::Escape | NamedKey::Tab => return MenuKeyResult::Close,
+            _ => return MenuKeyResult::Ignored,
+        };
+        if highlighted.is_none() {
+            return MenuKeyResult::Ignored;
+        }
+        self.highlighted = highlighted;
+        MenuKeyResult::Highlighted
+    }
+
+    fn is_enabled(&self, index: usize) -> bool {
+        self.model
+            .items()
+            .get(index)
+            .is_some_and(context_menu::MenuItem::is_enabled)
+    }
+
+    /// The enabled item at `pos`, in local coordinates.
+    fn item_at(&self, pos: Point) -> Option<usize> {
+        let index = self.item_rects.iter().position(|rect| rect.contains(pos))?;
+        self.is_enabled(index).then_some(index)
+    }
+}
+
+fn make_items(model: &context_menu::Menu, role: Role) -> Vec<WidgetPod<MenuItem>> {
+    let count = model.items().len();
+    (model.items().iter().enumerate())
+        .map(|(index, item)| {
+            WidgetPod::new(MenuItem {
+                role,
+                position: (index, count),
+                enabled: item.is_enabled(),
+                label: WidgetPod::new(Label::new(item.label())),
+            })
+        })
+        .collect()
+}
+
+// --- MARK: WIDGETMUT ---
+impl Menu {
+    pub(crate) fn set_items(this: &mut WidgetMut<'_, Self>, model: context_menu::Menu) {
+        for item in this.widget.items.drain(..) {
+            this.ctx.remove_child(item);
+        }
+        this.widget.items = make_items(&model, this.widget.item_role);
+        this.widget.model = model;
+        this.widget.highlighted = None;
+        this.widget.activated = None;
+        this.ctx.children_changed();
+        this.ctx.request_accessibility_update();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for Menu {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let Some(pos) = event.position() else {
+            return;
+        };
+        let local_pos = Point::new(pos.x, pos.y) - ctx.window_origin().to_vec2();
+        match event {
+            PointerEvent::PointerMove(_) => {
+                let item = self.item_at(local_pos);
+                if item.is_some() && item != self.highlighted {
+                    self.highlighted = item;
+                    ctx.request_render();
+                }
+            }
+            PointerEvent::PointerUp(PointerButton::Primary, _) => {
+                self.activated = self.item_at(local_pos);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
+        if event.action == accesskit::Action::Default {
+            let target = ctx.target();
+            self.activated = self.items.iter().position(|item| item.id() == target);
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _event: &Update) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        for item in &mut self.items {
+            ctx.register_child(item);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let padding = Size::new(ITEM_INSETS.x_value(), ITEM_INSETS.y_value());
+        let item_bc = bc
+            .loosen()
+            .shrink(padding + Size::new(0., 2. * MENU_PADDING));
+
+        let mut sizes = Vec::with_capacity(self.items.len());
+        for item in &mut self.items {
+            sizes.push(ctx.run_layout(item, &item_bc));
+        }
+        let content_width = sizes.iter().map(|size| size.width).fold(0., f64::max);
+        let width = bc
+            .constrain((content_width + padding.width, 0.))
+            .width
+            .max(bc.min().width);
+
+        self.item_rects.clear();
+        let mut y = MENU_PADDING;
+        for (index, (item, size)) in self.items.iter_mut().zip(sizes).enumerate() {
+            if self.model.has_separator_above(index) {
+                y += SEPARATOR_HEIGHT;
+            }
+            let height =
+                (size.height + padding.height).max(ctx.env().get(theme::BORDERED_WIDGET_HEIGHT));
+            let rect = Rect::new(0., y, width, y + height);
+            ctx.place_child(
+                item,
+                Point::new(ITEM_INSETS.x0, y + (height - size.height) / 2.),
+            );
+            self.item_rects.push(rect);
+            y += height;
+        }
+        bc.constrain((width, y + MENU_PADDING))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let stroke_width = ctx.env().get(theme::TEXTBOX_BORDER_WIDTH);
+        let rect = ctx
+            .size()
+            .to_rect()
+            .inset(-stroke_width / 2.)
+            .to_rounded_rect(ctx.env().get(theme::BUTTON_RADIUS));
+        fill_color(scene, &rect, ctx.env().get(theme::BACKGROUND_DARK));
+        stroke(
+            scene,
+            &rect,
+            ctx.env().get(theme::BORDER_LIGHT),
+            stroke_width,
+        );
+
+        for (index, item) in self.item_rects.iter().enumerate() {
+            if self.model.has_separator_above(index) {
+                let y = item.y0 - SEPARATOR_HEIGHT / 2.;
+                let line = Line::new((ITEM_INSETS.x0, y), (item.x1 - ITEM_INSETS.x1, y));
+                stroke(scene, &line, ctx.env().get(theme::BORDER_DARK), 1.);
+            }
+        }
+
+        let highlighted = self
+            .highlighted
+            .and_then(|index| self.item_rects.get(index));
+        if let Some(highlighted) = highlighted {
+            let highlight = highlighted.inset(-2.).to_rounded_rect(2.);
+            fill_color(
+                scene,
+                &highlight,
+                ctx.env().get(theme::SELECTED_TEXT_BACKGROUND_COLOR),
+            );
+        }
+    }
+
+    fn accessibility_role(&self) -> Role {
+        self.role
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        if let Some(item) = self.highlighted.and_then(|index| self.items.get(index)) {
+            node.set_active_descendant(item.id().into());
+        }
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        self.items.iter().map(WidgetPod::id).collect()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Menu")
+    }
+}
+
+impl AllowRawMut for Menu {}
+
+// --- MARK: MENU ITEM ---
+/// An item of a [`Menu`], which gives its label the role of the item.
+struct MenuItem {
+    role: Role,
+    /// The index of the item in the menu, and the number of items.
+    position: (usize, usize),
+    enabled: bool,
+    label: WidgetPod<Label>,
+}
+
+impl Widget for MenuItem {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded = event {
+            ctx.set_position_in_set(Some(self.position));
+            ctx.set_disabled(!self.enabled);
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.label);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.label, bc);
+        ctx.place_child(&mut self.label, Point::ORIGIN);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        self.role
+    }
+
+    fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        let label = ctx.get_raw_ref(&self.label);
+        node.set_name(label.widget().text().as_ref().to_string());
+        node.set_default_action_verb(DefaultActionVerb::Click);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.label.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("MenuItem")
+    }
+}
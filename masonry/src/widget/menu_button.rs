@@ -0,0 +1,553 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A button with an attached dropdown menu.
+
+use accesskit::{DefaultActionVerb, HasPopup, NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::kurbo::{BezPath, Line};
+use vello::Scene;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::action::Action;
+use crate::context_menu::{self, ContextMenu};
+use crate::paint_scene_helpers::{fill_lin_gradient, stroke, UnitPoint};
+use crate::text::ArcStr;
+use crate::widget::menu::Menu;
+#[cfg(test)]
+use crate::widget::WidgetRef;
+use crate::widget::{ButtonVariant, Label, WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, EventCtx, Insets, LayoutCtx, PaintCtx, Point,
+    PointerButton, PointerEvent, PopupPlacement, Rect, RegisterCtx, Size, TextEvent, Update,
+    UpdateCtx, Widget, WidgetId,
+};
+
+const LABEL_INSETS: Insets = Insets::uniform_xy(8., 2.);
+const CHEVRON_WIDTH: f64 = 20.;
+
+/// The part of a [`MenuButton`] which was pressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    Primary,
+    Chevron,
+}
+
+/// A button which opens a menu of text items.
+///
+/// A [split](Self::split) button has two parts: the main part emits
+/// [`Action::ButtonPressed`] like a [`Button`](super::Button), and the chevron
+/// next to it opens the menu. Otherwise, the whole button opens the menu.
+///
+/// Choosing an item emits [`Action::MenuItemSelected`] with the index of the item,
+/// and closes the menu. When the button has focus, Down opens the menu, then the arrow keys
+/// move through the items, Enter chooses one, and Escape closes the menu.
+///
+/// The open menu is a [popup](EventCtx::show_popup) below the button, like a
+/// [context menu](EventCtx::show_context_menu): it takes the focus while it's open, and
+/// doesn't change the size of the button.
+pub struct MenuButton {
+    label: WidgetPod<Label>,
+    menu: context_menu::Menu,
+    split: bool,
+    /// The popup showing the menu, while it's open.
+    popup: Option<WidgetId>,
+    variant: ButtonVariant,
+    pressed: Option<Part>,
+    button_size: Size,
+}
+
+// --- MARK: BUILDERS ---
+impl MenuButton {
+    /// Create a button which opens a menu with the given items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use masonry::widget::MenuButton;
+    ///
+    /// let button = MenuButton::new("Sort by", ["Name", "Date", "Size"]);
+    /// ```
+    pub fn new(
+        text: impl Into<ArcStr>,
+        items: impl IntoIterator<Item = impl Into<ArcStr>>,
+    ) -> Self {
+        Self::from_menu(text, items.into_iter().collect())
+    }
+
+    /// Create a button which opens the given menu, e.g. to use separators or disabled items.
+    ///
+    /// The same [`Menu`](crate::Menu) can be shown as a context menu with
+    /// [`EventCtx::show_context_menu`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use masonry::widget::MenuButton;
+    /// use masonry::{Menu, MenuItem};
+    ///
+    /// let menu = Menu::new()
+    ///     .with_item(MenuItem::new("Undo"))
+    ///     .with_separator()
+    ///     .with_item(MenuItem::new("Paste").enabled(false));
+    /// let button = MenuButton::from_menu("Edit", menu);
+    /// ```
+    pub fn from_menu(text: impl Into<ArcStr>, menu: context_menu::Menu) -> Self {
+        Self {
+            label: WidgetPod::new(Label::new(text)),
+            menu,
+            split: false,
+            popup: None,
+            variant: ButtonVariant::default(),
+            pressed: None,
+            button_size: Size::ZERO,
+        }
+    }
+
+    /// Create a split button, whose main part emits [`Action::ButtonPressed`], and whose
+    /// chevron opens a menu with the given items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use masonry::widget::MenuButton;
+    ///
+    /// let button = MenuButton::split("Save", ["Save as...", "Save all"]);
+    /// ```
+    pub fn split(
+        text: impl Into<ArcStr>,
+        items: impl IntoIterator<Item = impl Into<ArcStr>>,
+    ) -> Self {
+        Self {
+            split: true,
+            ..Self::new(text, items)
+        }
+    }
+
+    /// Builder-style method for setting the visual style of the button.
+    pub fn with_variant(mut self, variant: ButtonVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Whether the menu is open.
+    pub fn is_open(&self) -> bool {
+        self.popup.is_some()
+    }
+
+    fn button_rect(&self) -> Rect {
+        self.button_size.to_rect()
+    }
+
+    fn chevron_rect(&self) -> Rect {
+        let rect = self.button_rect();
+        Rect::new(rect.x1 - CHEVRON_WIDTH, rect.y0, rect.x1, rect.y1)
+    }
+
+    fn part_at(&self, pos: Point) -> Option<Part> {
+        if !self.button_rect().contains(pos) {
+            None
+        } else if self.split && !self.chevron_rect().contains(pos) {
+            Some(Part::Primary)
+        } else {
+            Some(Part::Chevron)
+        }
+    }
+
+    /// The popup showing the menu, which gives the focus back to this button when it closes.
+    fn menu_popup(&self, id: WidgetId, highlight_first: bool) -> ContextMenu {
+        let mut menu = Menu::new(self.menu.clone());
+        if highlight_first {
+            menu.highlight_first();
+        }
+        ContextMenu::with_menu(menu, id, Some(id))
+    }
+
+    /// Open or close the menu, with its first item highlighted if it's opened from
+    /// the keyboard.
+    fn set_open_internal(&mut self, ctx: &mut EventCtx, open: bool, from_keyboard: bool) {
+        if open == self.is_open() {
+            return;
+        }
+        match self.popup.take() {
+            Some(popup) => ctx.close_popup(popup),
+            None => {
+                let popup = self.menu_popup(ctx.widget_id(), from_keyboard);
+                self.popup = Some(ctx.show_popup(popup, PopupPlacement::Below));
+            }
+        }
+        ctx.request_paint_only();
+        ctx.request_accessibility_update();
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl MenuButton {
+    /// Set the text.
+    pub fn set_text(this: &mut WidgetMut<'_, Self>, new_text: impl Into<ArcStr>) {
+        Label::set_text(&mut Self::label_mut(this), new_text);
+    }
+
+    pub fn label_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Label> {
+        this.ctx.get_mut(&mut this.widget.label)
+    }
+
+    /// Replace the items of the menu.
+    pub fn set_items(
+        this: &mut WidgetMut<'_, Self>,
+        items: impl IntoIterator<Item = impl Into<ArcStr>>,
+    ) {
+        Self::set_menu(this, items.into_iter().collect());
+    }
+
+    /// Replace the menu.
+    ///
+    /// The menu is closed if it's open.
+    pub fn set_menu(this: &mut WidgetMut<'_, Self>, menu: context_menu::Menu) {
+        this.widget.menu = menu;
+        Self::set_open(this, false);
+    }
+
+    /// Set whether the main part of the button is separate from the part opening the menu.
+    pub fn set_split(this: &mut WidgetMut<'_, Self>, split: bool) {
+        this.widget.split = split;
+        this.ctx.request_paint_only();
+        this.ctx.request_accessibility_update();
+    }
+
+    /// Set the visual style of the button.
+    pub fn set_variant(this: &mut WidgetMut<'_, Self>, variant: ButtonVariant) {
+        this.widget.variant = variant;
+        this.ctx.request_paint_only();
+    }
+
+    /// Open or close the menu.
+    pub fn set_open(this: &mut WidgetMut<'_, Self>, open: bool) {
+        if open == this.widget.is_open() {
+            return;
+        }
+        match this.widget.popup.take() {
+            Some(popup) => this.ctx.close_popup(popup),
+            None => {
+                let popup = this.widget.menu_popup(this.ctx.widget_id(), false);
+                this.widget.popup = Some(this.ctx.show_popup(popup, PopupPlacement::Below));
+            }
+        }
+        this.ctx.request_paint_only();
+        this.ctx.request_accessibility_update();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for MenuButton {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let local_pos = event
+            .position()
+            .map(|pos| Point::new(pos.x, pos.y) - ctx.window_origin().to_vec2());
+        match event {
+            PointerEvent::PointerDown(PointerButton::Primary, _) => {
+                let part = local_pos.and_then(|pos| self.part_at(pos));
+                if part.is_some() && !ctx.is_disabled() {
+                    ctx.request_focus();
+                    ctx.request_paint_only();
+                    // The press closes the open menu, and its release doesn't open it again.
+                    if self.is_open() {
+                        self.set_open_internal(ctx, false, false);
+                        return;
+                    }
+                    self.pressed = part;
+                    ctx.capture_pointer();
+                }
+            }
+            PointerEvent::PointerUp(button, _) => {
+                let pressed = self.pressed.take();
+                let part = local_pos.and_then(|pos| self.part_at(pos));
+                if ctx.has_pointer_capture() && pressed.is_some() && pressed == part {
+                    match part {
+                        Some(Part::Primary) => ctx.submit_action(Action::ButtonPressed(*button)),
+                        Some(Part::Chevron) => self.set_open_internal(ctx, true, false),
+                        None => {}
+                    }
+                }
+                ctx.request_paint_only();
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        let TextEvent::KeyboardKey(event, _) = event else {
+            return;
+        };
+        // While the menu is open, it has the focus and handles the keys.
+        if !event.state.is_pressed() || ctx.is_disabled() || self.is_open() {
+            return;
+        }
+        let activates = matches!(
+            event.logical_key,
+            Key::Named(NamedKey::Enter | NamedKey::Space)
+        );
+        if event.logical_key == Key::Named(NamedKey::ArrowDown) || activates && !self.split {
+            self.set_open_internal(ctx, true, true);
+            ctx.set_handled();
+        } else if activates {
+            ctx.submit_action(Action::ButtonPressed(PointerButton::Primary));
+            ctx.set_handled();
+        }
+    }
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
+        if ctx.target() != ctx.widget_id() {
+            return;
+        }
+        match event.action {
+            accesskit::Action::Default if self.split => {
+                ctx.submit_action(Action::ButtonPressed(PointerButton::Primary));
+            }
+            accesskit::Action::Default => {
+                let open = !self.is_open();
+                self.set_open_internal(ctx, open, true);
+            }
+            accesskit::Action::Expand => self.set_open_internal(ctx, true, true),
+            accesskit::Action::Collapse => self.set_open_internal(ctx, false, false),
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            // The menu was closed by the user, e.g. by choosing an item.
+            Update::PopupClosed(id) if self.popup == Some(*id) => {
+                self.popup = None;
+                ctx.request_paint_only();
+                ctx.request_accessibility_update();
+            }
+            // Popups are closed when their anchor is stashed.
+            Update::StashedChanged(true) => self.popup = None,
+            Update::HoveredChanged(_) | Update::FocusChanged(_) | Update::DisabledChanged(_) => {
+                ctx.request_paint_only();
+            }
+            _ => {}
+        }
+    }
+
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.label);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let padding = Size::new(
+            LABEL_INSETS.x_value() + CHEVRON_WIDTH,
+            LABEL_INSETS.y_value(),
+        );
+        let label_bc = bc.shrink(padding).loosen();
+        let label_size = ctx.run_layout(&mut self.label, &label_bc);
+
+        let baseline = ctx.child_baseline_offset(&self.label);
+        ctx.set_baseline_offset(baseline + LABEL_INSETS.y1);
+
+        let button_size = bc.constrain(Size::new(
+            label_size.width + padding.width,
+            (label_size.height + padding.height).max(ctx.env().get(theme::BORDERED_WIDGET_HEIGHT)),
+        ));
+        self.button_size = button_size;
+        let label_x = (button_size.width - CHEVRON_WIDTH - label_size.width) / 2.;
+        let label_y = (button_size.height - label_size.height) / 2.;
+        ctx.place_child(&mut self.label, Point::new(label_x, label_y));
+        button_size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let is_active = ctx.has_pointer_capture() && !ctx.is_disabled();
+        let is_hovered = ctx.is_hovered();
+        let stroke_width = ctx.env().get(theme::BUTTON_BORDER_WIDTH);
+
+        let rounded_rect = self
+            .button_rect()
+            .inset(-stroke_width / 2.0)
+            .to_rounded_rect(ctx.env().get(theme::BUTTON_RADIUS));
+
+        let bg_gradient = self.variant.background(
+            ctx.env(),
+            is_hovered,
+            is_active || self.is_open(),
+            ctx.is_disabled(),
+        );
+        let border_color = if is_hovered && !ctx.is_disabled() {
+            ctx.env().get(theme::BORDER_LIGHT)
+        } else {
+            ctx.env().get(theme::BORDER_DARK)
+        };
+        if self.variant != ButtonVariant::Ghost || bg_gradient.is_some() {
+            stroke(scene, &rounded_rect, border_color, stroke_width);
+        }
+        if let Some(bg_gradient) = bg_gradient {
+            fill_lin_gradient(
+                scene,
+                &rounded_rect,
+                bg_gradient,
+                UnitPoint::TOP,
+                UnitPoint::BOTTOM,
+            );
+        }
+
+        let chevron = self.chevron_rect();
+        if self.split {
+            let divider = Line::new(
+                (chevron.x0, chevron.y0 + stroke_width),
+                (chevron.x0, chevron.y1 - stroke_width),
+            );
+            stroke(scene, &divider, border_color, 1.);
+        }
+        let center = chevron.center();
+        let mut path = BezPath::new();
+        path.move_to((center.x - 4., center.y - 2.));
+        path.line_to((center.x, center.y + 2.));
+        path.line_to((center.x + 4., center.y - 2.));
+        let color = if ctx.is_disabled() {
+            ctx.env().get(theme::DISABLED_TEXT_COLOR)
+        } else {
+            ctx.env().get(theme::TEXT_COLOR)
+        };
+        stroke(scene, &path, color, 1.5);
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::Button
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        node.set_has_popup(HasPopup::Menu);
+        node.set_expanded(self.is_open());
+        if let Some(popup) = self.popup {
+            node.push_controls(popup.into());
+        }
+        node.add_action(if self.is_open() {
+            accesskit::Action::Collapse
+        } else {
+            accesskit::Action::Expand
+        });
+        node.set_default_action_verb(if self.split {
+            DefaultActionVerb::Click
+        } else {
+            DefaultActionVerb::Open
+        });
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.label.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("MenuButton")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+
+    fn click(harness: &mut TestHarness, pos: impl Into<Point>) {
+        harness.mouse_move(pos);
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+    }
+
+    fn button(harness: &TestHarness) -> WidgetRef<'_, MenuButton> {
+        harness.root_widget().downcast::<MenuButton>().unwrap()
+    }
+
+    #[test]
+    fn split_button() {
+        let widget = MenuButton::split("Save", ["Save as...", "Save all"]);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 200.));
+        let button_size = button(&harness).button_size;
+
+        // The main part of the button.
+        click(&mut harness, (10., button_size.height / 2.));
+        assert_eq!(
+            harness.pop_action().map(|(action, _)| action),
+            Some(Action::ButtonPressed(PointerButton::Primary))
+        );
+        assert!(!button(&harness).is_open());
+
+        // The chevron.
+        click(
+            &mut harness,
+            (button_size.width - 5., button_size.height / 2.),
+        );
+        assert_eq!(harness.pop_action(), None);
+        assert!(button(&harness).is_open());
+        // The menu is a popup, so it doesn't change the size of the button.
+        assert_eq!(button(&harness).ctx().size(), button_size);
+
+        // The second item.
+        let popup_id = button(&harness).popup.unwrap();
+        let menu_rect = harness.get_widget(popup_id).ctx().window_layout_rect();
+        assert!(menu_rect.y0 >= button_size.height);
+        click(&mut harness, (menu_rect.x0 + 10., menu_rect.y1 - 10.));
+        assert_eq!(
+            harness.pop_action().map(|(action, _)| action),
+            Some(Action::MenuItemSelected(1))
+        );
+        assert!(!button(&harness).is_open());
+    }
+
+    #[test]
+    fn menu_button_opens_from_anywhere() {
+        let widget = MenuButton::new("Sort by", ["Name", "Date"]);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 200.));
+        let button_size = button(&harness).button_size;
+
+        click(&mut harness, (10., button_size.height / 2.));
+        assert!(button(&harness).is_open());
+        assert_eq!(harness.pop_action(), None);
+        click(&mut harness, (10., button_size.height / 2.));
+        assert!(!button(&harness).is_open());
+    }
+
+    #[test]
+    fn menu_button_keyboard() {
+        let widget = MenuButton::new("Sort by", ["Name", "Date"]);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 200.));
+        let button_id = button(&harness).id();
+        harness.focus_on(Some(button_id));
+
+        // The open menu takes the focus, with its first item highlighted.
+        harness.keyboard_key(Key::Named(NamedKey::ArrowDown));
+        assert!(button(&harness).is_open());
+        assert_ne!(
+            harness.focused_widget().map(|widget| widget.id()),
+            Some(button_id)
+        );
+        harness.keyboard_key(Key::Named(NamedKey::ArrowDown));
+        harness.keyboard_key(Key::Named(NamedKey::Enter));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::MenuItemSelected(1), button_id))
+        );
+        assert!(!button(&harness).is_open());
+        assert_eq!(
+            harness.focused_widget().map(|widget| widget.id()),
+            Some(button_id)
+        );
+
+        // Escape closes the menu without an action.
+        harness.keyboard_key(Key::Named(NamedKey::Enter));
+        assert!(button(&harness).is_open());
+        harness.keyboard_key(Key::Named(NamedKey::Escape));
+        assert!(!button(&harness).is_open());
+        assert_eq!(harness.pop_action(), None);
+        assert_eq!(
+            harness.focused_widget().map(|widget| widget.id()),
+            Some(button_id)
+        );
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A scroll container with a scaled-down preview of its content.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::kurbo::{Affine, Point, Rect, Size, Vec2};
+use vello::peniko::Mix;
+use vello::Scene;
+
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::widget::{Portal, WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, Env, EventCtx, LayoutCtx, PaintCtx,
+    PointerEvent, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A [`Portal`] with a minimap next to it, as seen in code editors.
+///
+/// The minimap is a live, scaled-down preview of the whole content, on which
+/// the visible area is highlighted. Clicking or dragging on the minimap scrolls
+/// the portal to that point.
+///
+/// The content is scaled to fit the width of the minimap. If it is then taller than
+/// the minimap, the preview scrolls along with the portal.
+///
+/// The preview is painted from the cached scenes of the content, and is refreshed when
+/// the content is laid out, scrolled, or receives pointer events.
+pub struct Minimap<W: Widget> {
+    portal: WidgetPod<Portal<W>>,
+    content_id: WidgetId,
+    /// The width set on this widget, which takes precedence over the env.
+    width: Option<f64>,
+    /// The area of the content shown by the portal, in content coordinates.
+    viewport: Rect,
+    content_size: Size,
+    dragging: bool,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> Minimap<W> {
+    /// Create a scroll container for `child` with a minimap.
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            content_id: child.id(),
+            portal: WidgetPod::new(Portal::new_pod(child)),
+            width: None,
+            viewport: Rect::ZERO,
+            content_size: Size::ZERO,
+            dragging: false,
+        }
+    }
+
+    /// Builder-style method for setting the width of the minimap.
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = Some(width);
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> Minimap<W> {
+    pub fn portal_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, Portal<W>> {
+        this.ctx.get_mut(&mut this.widget.portal)
+    }
+
+    /// Set the width of the minimap.
+    pub fn set_width(this: &mut WidgetMut<'_, Self>, width: f64) {
+        this.widget.width = Some(width);
+        this.ctx.request_layout();
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl<W: Widget> Minimap<W> {
+    fn width(&self, env: &Env) -> f64 {
+        self.width.unwrap_or_else(|| env.get(theme::MINIMAP_WIDTH))
+    }
+
+    fn minimap_rect(&self, size: Size, env: &Env) -> Rect {
+        Rect::new(size.width - self.width(env), 0.0, size.width, size.height)
+    }
+
+    /// The scale and offset mapping content coordinates to the minimap.
+    fn preview_transform(&self, minimap_rect: Rect) -> (f64, Vec2) {
+        if self.content_size.width <= 0.0 {
+            return (0.0, minimap_rect.origin().to_vec2());
+        }
+        let scale = (minimap_rect.width() / self.content_size.width).min(1.0);
+
+        // When the preview doesn't fit, scroll it proportionally to the portal.
+        let overflow = (self.content_size.height * scale - minimap_rect.height()).max(0.0);
+        let scroll_range = self.content_size.height - self.viewport.height();
+        let progress = if scroll_range > 0.0 {
+            (self.viewport.y0 / scroll_range).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let offset = minimap_rect.origin().to_vec2() - Vec2::new(0.0, overflow * progress);
+        (scale, offset)
+    }
+
+    /// Scroll the portal so the viewport is centered on `pos`, in local coordinates.
+    fn scroll_to(&mut self, ctx: &mut EventCtx, pos: Point) {
+        let (scale, offset) = self.preview_transform(self.minimap_rect(ctx.size(), ctx.env()));
+        if scale <= 0.0 {
+            return;
+        }
+        let content_pos = ((pos - offset).to_vec2() / scale).to_point();
+        let viewport_pos = content_pos - self.viewport.size().to_vec2() / 2.0;
+        ctx.mutate_later(&mut self.portal, move |mut portal| {
+            Portal::set_viewport_pos(&mut portal, viewport_pos);
+        });
+    }
+
+    /// Update the cached viewport position from the portal.
+    ///
+    /// Returns `true` if the portal was scrolled.
+    fn sync_viewport(&mut self, viewport_pos: Point) -> bool {
+        let viewport = Rect::from_origin_size(viewport_pos, self.viewport.size());
+        let moved = viewport != self.viewport;
+        self.viewport = viewport;
+        moved
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for Minimap<W> {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let local_pos = |ctx: &EventCtx, position: Point| position - ctx.window_origin().to_vec2();
+
+        match event {
+            PointerEvent::PointerDown(_, state) => {
+                let pos = local_pos(ctx, (state.position.x, state.position.y).into());
+                if self.minimap_rect(ctx.size(), ctx.env()).contains(pos) {
+                    ctx.capture_pointer();
+                    self.dragging = true;
+                    self.scroll_to(ctx, pos);
+                }
+            }
+            PointerEvent::PointerMove(state) if self.dragging => {
+                let pos = local_pos(ctx, (state.position.x, state.position.y).into());
+                self.scroll_to(ctx, pos);
+            }
+            PointerEvent::PointerUp(_, _) => {
+                self.dragging = false;
+            }
+            _ => {}
+        }
+
+        // Events on the content may have changed what it paints, and events on the
+        // portal may have scrolled it.
+        let viewport_pos = ctx
+            .get_raw_ref(&mut self.portal)
+            .widget()
+            .get_viewport_pos();
+        self.sync_viewport(viewport_pos);
+        ctx.request_paint_only();
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.portal);
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::RequestPanToChild(_) = event {
+            let viewport_pos = ctx
+                .get_raw_ref(&mut self.portal)
+                .widget()
+                .get_viewport_pos();
+            if self.sync_viewport(viewport_pos) {
+                ctx.request_paint_only();
+            }
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let width = self.width(ctx.env()).min(bc.max().width);
+        let portal_bc = bc.shrink((width, 0.0));
+        let portal_size = ctx.run_layout(&mut self.portal, &portal_bc);
+        ctx.place_child(&mut self.portal, Point::ORIGIN);
+
+        let viewport_pos = ctx
+            .get_raw_ref(&mut self.portal)
+            .widget()
+            .get_viewport_pos();
+        self.viewport = Rect::from_origin_size(viewport_pos, portal_size);
+
+        bc.constrain(Size::new(portal_size.width + width, portal_size.height))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let minimap_rect = self.minimap_rect(ctx.size(), ctx.env());
+        fill_color(scene, &minimap_rect, ctx.env().get(theme::BACKGROUND_DARK));
+
+        let Some(preview) = ctx.paint_descendant_scene(self.content_id) else {
+            return;
+        };
+        self.content_size = preview.size;
+        let (scale, offset) = self.preview_transform(minimap_rect);
+
+        scene.push_layer(Mix::Clip, 1.0, Affine::IDENTITY, &minimap_rect);
+        scene.append(
+            &preview.scene,
+            Some(Affine::translate(offset) * Affine::scale(scale)),
+        );
+
+        let viewport_rect = Rect::from_origin_size(
+            (self.viewport.origin().to_vec2() * scale + offset).to_point(),
+            self.viewport.size() * scale,
+        )
+        .intersect(minimap_rect);
+        fill_color(
+            scene,
+            &viewport_rect,
+            ctx.env()
+                .get(theme::SCROLLBAR_COLOR)
+                .with_alpha_factor(0.15),
+        );
+        stroke(
+            scene,
+            &viewport_rect,
+            ctx.env().get(theme::SCROLLBAR_BORDER_COLOR),
+            1.0,
+        );
+        scene.pop_layer();
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.portal.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Minimap")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, ModularWidget, TestHarness, TestWidgetExt};
+    use crate::PointerButton;
+
+    #[test]
+    fn minimap_navigation() {
+        let [minimap_id] = widget_ids();
+        // The content ignores its constraints, so it overflows the portal.
+        let content = ModularWidget::new(()).layout_fn(|_, _, _| Size::new(300., 1200.));
+        let widget = Minimap::new(content).with_width(100.).with_id(minimap_id);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+        // The content size is known once the minimap has been painted.
+        let _ = harness.render();
+
+        // The content is scaled by a third to fit the minimap, so clicking at the point
+        // matching y=900 in the content centers the 400px high viewport on it.
+        harness.mouse_move((350., 300.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+
+        harness.edit_widget(minimap_id, |mut minimap| {
+            let mut minimap = minimap.downcast::<Minimap<ModularWidget<()>>>();
+            let portal = Minimap::portal_mut(&mut minimap);
+            assert_eq!(portal.widget.get_viewport_pos(), Point::new(0., 700.));
+        });
+
+        // The viewport can't move past the end of the content.
+        harness.mouse_move((350., 390.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+
+        harness.edit_widget(minimap_id, |mut minimap| {
+            let mut minimap = minimap.downcast::<Minimap<ModularWidget<()>>>();
+            let portal = Minimap::portal_mut(&mut minimap);
+            assert_eq!(portal.widget.get_viewport_pos(), Point::new(0., 800.));
+        });
+    }
+}
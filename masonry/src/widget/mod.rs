@@ -13,48 +13,100 @@ mod widget_state;
 #[cfg(test)]
 mod tests;
 
+mod accessibility_hidden;
 mod align;
+mod animated;
+mod busy;
 mod button;
 mod checkbox;
+mod column_chart;
+mod command_palette;
+mod dialog;
+mod drawer;
+mod error_boundary;
+mod fit_box;
 mod flex;
+mod focus_group;
+mod frozen_panes;
+mod gesture_detector;
 mod grid;
+mod icon;
 mod image;
+mod interactive;
 mod label;
+mod link;
+pub(crate) mod menu;
+mod menu_button;
+mod minimap;
 mod portal;
+mod prebuilt;
 mod progress_bar;
 mod prose;
+mod rating;
 mod root_widget;
+mod rubber_band;
 mod scroll_bar;
+mod segmented_control;
+mod simple_widget;
 mod sized_box;
 mod spinner;
 mod split;
+mod tag_input;
 mod textbox;
 mod variable_label;
+mod virtual_list;
 mod widget_arena;
 
 pub use self::image::Image;
+pub use accessibility_hidden::AccessibilityHidden;
 pub use align::Align;
-pub use button::Button;
-pub use checkbox::Checkbox;
+pub use animated::{Animated, AnimatedProperty};
+pub use busy::Busy;
+pub use button::{Button, ButtonVariant};
+pub use checkbox::{Checkbox, ToggleCycle};
+pub use column_chart::{ColumnChart, Series};
+pub use command_palette::{Command, CommandPalette};
+pub use dialog::Dialog;
+pub use drawer::{Drawer, DrawerEdge};
+pub use error_boundary::ErrorBoundary;
+pub use fit_box::FitBox;
 pub use flex::{Axis, CrossAxisAlignment, Flex, FlexParams, MainAxisAlignment};
+pub use focus_group::FocusGroup;
+pub use frozen_panes::{FrozenPanes, Pane};
+pub use gesture_detector::GestureDetector;
 pub use grid::{Grid, GridParams};
+#[cfg(feature = "standard-icons")]
+pub use icon::StandardIcon;
+pub use icon::{Icon, ICON_VIEWBOX_SIZE};
+pub use interactive::Interactive;
 pub use label::{Label, LineBreaking};
-pub use portal::Portal;
+pub use link::Link;
+pub use menu_button::MenuButton;
+pub use minimap::Minimap;
+pub use portal::{Portal, ScrollSnap, SnapPoints, SnapStrictness, DEFAULT_SCROLL_TRANSITION};
+pub use prebuilt::Prebuilt;
 pub use progress_bar::ProgressBar;
 pub use prose::Prose;
+pub use rating::Rating;
 pub use root_widget::RootWidget;
-pub use scroll_bar::ScrollBar;
+pub use rubber_band::RubberBand;
+pub use scroll_bar::{ScrollBar, ScrollBarMode, ScrollBarStyle};
+pub use segmented_control::SegmentedControl;
+pub use simple_widget::SimpleWidget;
 pub use sized_box::SizedBox;
 pub use spinner::Spinner;
 pub use split::Split;
-pub use textbox::Textbox;
+pub use tag_input::TagInput;
+pub use textbox::{SoftWrapIndicator, Textbox};
 pub use variable_label::VariableLabel;
+pub use virtual_list::{ItemBuilder, ItemRecycler, VirtualList};
 pub use widget_mut::WidgetMut;
 pub use widget_pod::WidgetPod;
 pub use widget_ref::WidgetRef;
 
+pub(crate) use error_boundary::report_caught_panic;
 pub(crate) use widget_arena::WidgetArena;
-pub(crate) use widget_state::WidgetState;
+pub(crate) use widget_state::{WidgetFlags, WidgetState};
 
 use crate::{Affine, Size};
 
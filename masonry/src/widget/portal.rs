@@ -4,6 +4,12 @@
 #![allow(missing_docs)]
 
 use std::ops::Range;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
 
 use accesskit::{NodeBuilder, Role};
 use smallvec::{smallvec, SmallVec};
@@ -11,12 +17,57 @@ use tracing::{trace_span, Span};
 use vello::kurbo::{Point, Rect, Size, Vec2};
 use vello::Scene;
 
-use crate::widget::{Axis, ScrollBar, WidgetMut};
+use crate::animation::{AnimatedValue, Easing, Transition};
+use crate::gesture::{Gesture, GestureRecognizer};
+use crate::widget::{Axis, ScrollBar, ScrollBarMode, ScrollBarStyle, WidgetMut};
 use crate::{
     AccessCtx, AccessEvent, BoxConstraints, ComposeCtx, EventCtx, LayoutCtx, PaintCtx,
-    PointerEvent, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId, WidgetPod,
+    PointerEvent, PointerId, RegisterCtx, ScrollDeltaKind, TextEvent, TimerToken, Update,
+    UpdateCtx, Widget, WidgetId, WidgetPod,
 };
 
+/// The transition of wheel scrolls, unless set with [`Portal::with_scroll_transition`].
+pub const DEFAULT_SCROLL_TRANSITION: Transition =
+    Transition::new(Duration::from_millis(150), Easing::EaseOut);
+
+/// How fast a fling slows down: its velocity is divided by `e` every `1 / FLING_FRICTION` seconds.
+const FLING_FRICTION: f64 = 4.0;
+
+/// A fling stops once it's slower than this, in logical pixels per second.
+const FLING_MIN_VELOCITY: f64 = 20.0;
+
+/// How long a touchpad scroll must pause before the viewport settles on a snap point.
+const SNAP_SETTLE_DELAY: Duration = Duration::from_millis(150);
+
+/// Positions a [`Portal`] settles to after it's scrolled, see [`Portal::with_scroll_snap`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollSnap {
+    /// The axis along which the viewport snaps.
+    pub axis: Axis,
+    pub points: SnapPoints,
+    pub strictness: SnapStrictness,
+}
+
+/// The offsets a [`Portal`] can snap to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapPoints {
+    /// The positions reported by the portal's content with
+    /// [`LayoutCtx::set_snap_points`], e.g. the start of each child of a
+    /// [`Flex`](super::Flex).
+    Content,
+    /// Explicit offsets of the viewport along the snap axis.
+    Offsets(Vec<f64>),
+}
+
+/// Whether a [`Portal`] always snaps.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnapStrictness {
+    /// The viewport always settles on a snap point.
+    Mandatory,
+    /// The viewport only snaps to points closer than the given distance, in logical pixels.
+    Proximity(f64),
+}
+
 // TODO - refactor - see https://github.com/linebender/xilem/issues/366
 // TODO - rename "Portal" to "ScrollPortal"?
 // TODO - Document which cases need request_layout, request_compose and request_render
@@ -37,6 +88,12 @@ pub struct Portal<W: Widget> {
     scrollbar_horizontal_visible: bool,
     scrollbar_vertical: WidgetPod<ScrollBar>,
     scrollbar_vertical_visible: bool,
+    motion: ScrollMotion,
+    scroll_snap: Option<ScrollSnap>,
+    /// The offsets of the snap points along the snap axis, sorted, as of the last layout.
+    snap_offsets: Vec<f64>,
+    /// The timer after which a touchpad scroll is considered over, and the viewport snaps.
+    settle_timer: Option<TimerToken>,
 }
 
 // --- MARK: BUILDERS ---
@@ -57,6 +114,10 @@ impl<W: Widget> Portal<W> {
             scrollbar_horizontal_visible: false,
             scrollbar_vertical: WidgetPod::new(ScrollBar::new(Axis::Vertical, 1.0, 1.0)),
             scrollbar_vertical_visible: false,
+            motion: ScrollMotion::new(),
+            scroll_snap: None,
+            snap_offsets: Vec::new(),
+            settle_timer: None,
         }
     }
 
@@ -90,6 +151,51 @@ impl<W: Widget> Portal<W> {
         self
     }
 
+    /// Builder-style method to set how the viewport moves after a wheel event.
+    ///
+    /// The default is [`DEFAULT_SCROLL_TRANSITION`]. With `None`, the viewport jumps
+    /// to its new position.
+    pub fn with_scroll_transition(mut self, transition: Option<Transition>) -> Self {
+        self.motion.animation.set_transition(transition);
+        self
+    }
+
+    /// Builder-style method to make the viewport settle on snap points after it's scrolled,
+    /// e.g. for carousels or paged lists.
+    ///
+    /// Each wheel notch moves to the next snap point. Touchpad scrolls follow the fingers,
+    /// and settle once they pause. Flings and scrollbar drags settle when they end.
+    ///
+    /// The end of the content is always a snap point, so that it can be reached.
+    pub fn with_scroll_snap(mut self, snap: ScrollSnap) -> Self {
+        self.scroll_snap = Some(snap);
+        self
+    }
+
+    /// Builder-style method to set how the scrollbars look.
+    pub fn with_scrollbar_style(self, style: ScrollBarStyle) -> Self {
+        self.map_scrollbars(|scrollbar| scrollbar.with_style(style))
+    }
+
+    /// Builder-style method to set when the scrollbars are shown.
+    ///
+    /// The default is [`ScrollBarMode::Visible`]. With [`ScrollBarMode::AutoHide`], the
+    /// scrollbars fade in while the content scrolls.
+    pub fn with_scrollbar_mode(self, mode: ScrollBarMode) -> Self {
+        self.map_scrollbars(|scrollbar| scrollbar.with_mode(mode))
+    }
+
+    fn map_scrollbars(mut self, f: impl Fn(ScrollBar) -> ScrollBar) -> Self {
+        for scrollbar in [&mut self.scrollbar_horizontal, &mut self.scrollbar_vertical] {
+            let id = scrollbar.id();
+            let widget = scrollbar
+                .take_inner()
+                .expect("Portal builder methods are called before the portal is added");
+            *scrollbar = WidgetPod::new_with_id(f(widget), id);
+        }
+        self
+    }
+
     /// Builder-style method to set whether the child must fill the view.
     ///
     /// If `false` (the default) there is no minimum constraint on the child's
@@ -127,15 +233,171 @@ fn compute_pan_range(mut viewport: Range<f64>, target: Range<f64>) -> Range<f64>
     viewport
 }
 
+pub(crate) fn clamp_viewport_pos(portal_size: Size, content_size: Size, pos: Point) -> Point {
+    let viewport_max_pos =
+        (content_size - portal_size).clamp(Size::ZERO, Size::new(f64::INFINITY, f64::INFINITY));
+    Point::new(
+        pos.x.clamp(0.0, viewport_max_pos.width),
+        pos.y.clamp(0.0, viewport_max_pos.height),
+    )
+}
+
+// --- MARK: SCROLL MOTION ---
+/// The multiplier from wheel deltas to logical pixels.
+pub(crate) const SCROLLING_SPEED: f64 = 10.0;
+
+/// How a viewport moves on its own: smoothly after a wheel notch, and after a touch fling.
+///
+/// This is shared by the widgets which scroll, e.g. [`Portal`] and
+/// [`VirtualList`](super::VirtualList).
+pub(crate) struct ScrollMotion {
+    /// The viewport position while it moves smoothly after a wheel event.
+    pub(crate) animation: AnimatedValue<Vec2>,
+    /// The velocity of the viewport after a touch fling, in logical pixels per second.
+    pub(crate) fling_velocity: Vec2,
+    pub(crate) touch_recognizer: GestureRecognizer,
+}
+
+impl ScrollMotion {
+    pub(crate) fn new() -> Self {
+        let mut animation = AnimatedValue::new(Vec2::ZERO);
+        animation.set_transition(Some(DEFAULT_SCROLL_TRANSITION));
+        Self {
+            animation,
+            fling_velocity: Vec2::ZERO,
+            touch_recognizer: GestureRecognizer::new(),
+        }
+    }
+
+    /// Stop the wheel animation and the fling, leaving the viewport at `pos`.
+    pub(crate) fn stop(&mut self, pos: Point) {
+        self.animation.jump_to(pos.to_vec2());
+        self.fling_velocity = Vec2::ZERO;
+    }
+
+    /// Where a wheel scroll starts from, for a viewport at `pos`.
+    ///
+    /// Wheel events received during the animation add up to its target. A fling stops.
+    pub(crate) fn wheel_start(&mut self, pos: Point) -> Point {
+        if !self.animation.is_animating() {
+            self.animation.jump_to(pos.to_vec2());
+        }
+        self.fling_velocity = Vec2::ZERO;
+        self.animation.target().to_point()
+    }
+
+    /// Advance the motion of a viewport at `pos` by `interval` nanoseconds.
+    ///
+    /// Returns the new position of the viewport, before it's clamped to the content,
+    /// or `None` if the viewport isn't moving.
+    pub(crate) fn advance(&mut self, pos: Point, interval: u64) -> Option<Point> {
+        if self.animation.advance(interval) {
+            return Some(self.animation.value().to_point());
+        }
+        if self.fling_velocity == Vec2::ZERO {
+            return None;
+        }
+        // The velocity decays exponentially, and the distance is its integral over the frame.
+        let dt = interval as f64 * 1e-9;
+        let decay = (-FLING_FRICTION * dt).exp();
+        let pos = pos + self.fling_velocity * (1. - decay) / FLING_FRICTION;
+        self.fling_velocity *= decay;
+        if self.fling_velocity.hypot() < FLING_MIN_VELOCITY {
+            self.fling_velocity = Vec2::ZERO;
+        }
+        Some(pos)
+    }
+
+    /// Stop the fling along the axes where it hit the edge of the content, i.e. where
+    /// `pos` had to be clamped to `clamped`.
+    ///
+    /// Returns whether the viewport keeps moving, and needs another animation frame.
+    pub(crate) fn stop_at_edges(&mut self, pos: Point, clamped: Point) -> bool {
+        if clamped.x != pos.x {
+            self.fling_velocity.x = 0.;
+        }
+        if clamped.y != pos.y {
+            self.fling_velocity.y = 0.;
+        }
+        self.animation.is_animating() || self.fling_velocity != Vec2::ZERO
+    }
+}
+
 impl<W: Widget> Portal<W> {
+    /// Stop the wheel animation and the fling, leaving the viewport where it is.
+    fn stop_scrolling(&mut self) {
+        self.motion.stop(self.viewport_pos);
+    }
+
+    /// Where the viewport settles when it stops at `pos`.
+    fn settle_target(&self, pos: Point) -> Point {
+        self.snap_target(pos, pos)
+    }
+
+    /// Where the viewport settles when it's scrolled from `start` towards `target`.
+    ///
+    /// Scrolling away from a snap point always moves to the next one, so that each
+    /// wheel notch or flick moves by at least one item.
+    fn snap_target(&self, start: Point, target: Point) -> Point {
+        let Some(snap) = &self.scroll_snap else {
+            return target;
+        };
+        let axis = snap.axis;
+        let (start_major, target_major) = (axis.major_pos(start), axis.major_pos(target));
+        let Some(mut nearest) = self.snap_offsets.iter().copied().min_by(|a, b| {
+            (a - target_major)
+                .abs()
+                .total_cmp(&(b - target_major).abs())
+        }) else {
+            return target;
+        };
+        if nearest == start_major && target_major > start_major {
+            let next = self
+                .snap_offsets
+                .iter()
+                .find(|offset| **offset > start_major);
+            nearest = next.copied().unwrap_or(nearest);
+        } else if nearest == start_major && target_major < start_major {
+            let previous = self
+                .snap_offsets
+                .iter()
+                .rfind(|offset| **offset < start_major);
+            nearest = previous.copied().unwrap_or(nearest);
+        }
+        match snap.strictness {
+            SnapStrictness::Proximity(distance) if (nearest - target_major).abs() > distance => {
+                target
+            }
+            _ => axis.pack(nearest, axis.minor_pos(target)).into(),
+        }
+    }
+
+    /// Move the scrollbars to the position of the viewport, from passes other than events.
+    // TODO - There's similar code in `on_pointer_event`. Because this code directly
+    // manipulates child widgets, it's hard to share it between contexts.
+    fn update_scrollbars(&mut self, ctx: &mut UpdateCtx, portal_size: Size, content_size: Size) {
+        let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_vertical);
+        scrollbar.widget().cursor_progress =
+            self.viewport_pos.y / (content_size - portal_size).height;
+        scrollbar.ctx().request_render();
+        if scrollbar.widget().reveal() {
+            scrollbar.ctx().request_anim_frame();
+        }
+
+        std::mem::drop(scrollbar);
+
+        let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_horizontal);
+        scrollbar.widget().cursor_progress =
+            self.viewport_pos.x / (content_size - portal_size).width;
+        scrollbar.ctx().request_render();
+        if scrollbar.widget().reveal() {
+            scrollbar.ctx().request_anim_frame();
+        }
+    }
+
     // TODO - rename
     fn set_viewport_pos_raw(&mut self, portal_size: Size, content_size: Size, pos: Point) -> bool {
-        let viewport_max_pos =
-            (content_size - portal_size).clamp(Size::ZERO, Size::new(f64::INFINITY, f64::INFINITY));
-        let pos = Point::new(
-            pos.x.clamp(0.0, viewport_max_pos.width),
-            pos.y.clamp(0.0, viewport_max_pos.height),
-        );
+        let pos = clamp_viewport_pos(portal_size, content_size, pos);
 
         if (pos - self.viewport_pos).hypot2() > 1e-12 {
             self.viewport_pos = pos;
@@ -208,7 +470,39 @@ impl<W: Widget> Portal<W> {
         this.ctx.request_layout();
     }
 
+    /// Set how the viewport moves after a wheel event.
+    ///
+    /// See [`with_scroll_transition`](Self::with_scroll_transition) for details.
+    pub fn set_scroll_transition(this: &mut WidgetMut<'_, Self>, transition: Option<Transition>) {
+        this.widget.scroll_animation.set_transition(transition);
+    }
+
+    /// Set the snap points of the viewport, see [`with_scroll_snap`](Self::with_scroll_snap).
+    pub fn set_scroll_snap(this: &mut WidgetMut<'_, Self>, snap: Option<ScrollSnap>) {
+        this.widget.scroll_snap = snap;
+        this.ctx.request_layout();
+    }
+
+    /// Set how the scrollbars look.
+    pub fn set_scrollbar_style(this: &mut WidgetMut<'_, Self>, style: ScrollBarStyle) {
+        ScrollBar::set_style(&mut Self::horizontal_scrollbar_mut(this), style);
+        ScrollBar::set_style(&mut Self::vertical_scrollbar_mut(this), style);
+    }
+
+    /// Make the scrollbars look like the theme values of the env again.
+    pub fn reset_scrollbar_style(this: &mut WidgetMut<'_, Self>) {
+        ScrollBar::reset_style(&mut Self::horizontal_scrollbar_mut(this));
+        ScrollBar::reset_style(&mut Self::vertical_scrollbar_mut(this));
+    }
+
+    /// Set when the scrollbars are shown.
+    pub fn set_scrollbar_mode(this: &mut WidgetMut<'_, Self>, mode: ScrollBarMode) {
+        ScrollBar::set_mode(&mut Self::horizontal_scrollbar_mut(this), mode);
+        ScrollBar::set_mode(&mut Self::vertical_scrollbar_mut(this), mode);
+    }
+
     pub fn set_viewport_pos(this: &mut WidgetMut<'_, Self>, position: Point) -> bool {
+        this.widget.stop_scrolling();
         let portal_size = this.ctx.layout_rect().size();
         let content_size = this
             .ctx
@@ -224,9 +518,17 @@ impl<W: Widget> Portal<W> {
             let progress_x = this.widget.viewport_pos.x / (content_size - portal_size).width;
             Self::horizontal_scrollbar_mut(this).widget.cursor_progress = progress_x;
             Self::horizontal_scrollbar_mut(this).ctx.request_render();
+            if Self::horizontal_scrollbar_mut(this).widget.reveal() {
+                Self::horizontal_scrollbar_mut(this)
+                    .ctx
+                    .request_anim_frame();
+            }
             let progress_y = this.widget.viewport_pos.y / (content_size - portal_size).height;
             Self::vertical_scrollbar_mut(this).widget.cursor_progress = progress_y;
             Self::vertical_scrollbar_mut(this).ctx.request_render();
+            if Self::vertical_scrollbar_mut(this).widget.reveal() {
+                Self::vertical_scrollbar_mut(this).ctx.request_anim_frame();
+            }
             this.ctx.request_layout();
         }
         pos_changed
@@ -258,24 +560,115 @@ impl<W: Widget> Portal<W> {
 // --- MARK: IMPL WIDGET ---
 impl<W: Widget> Widget for Portal<W> {
     fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
-        const SCROLLING_SPEED: f64 = 10.0;
-
         let portal_size = ctx.size();
         let content_size = ctx.get_raw_ref(&mut self.child).ctx().layout_rect().size();
 
-        match event {
-            PointerEvent::MouseWheel(delta, _) => {
-                let delta = Vec2::new(delta.x * -SCROLLING_SPEED, delta.y * -SCROLLING_SPEED);
-                self.set_viewport_pos_raw(portal_size, content_size, self.viewport_pos + delta);
-                ctx.request_compose();
+        let mut viewport_moved = false;
+        if let PointerEvent::MouseWheel(delta, kind, _) = event {
+            let delta = Vec2::new(delta.x * -SCROLLING_SPEED, delta.y * -SCROLLING_SPEED);
+            let from = self.motion.wheel_start(self.viewport_pos);
+            let target = clamp_viewport_pos(portal_size, content_size, from + delta);
+            match kind {
+                // Only wheel notches are animated: touchpads already scroll smoothly, and
+                // an animation would make the content lag behind the fingers.
+                ScrollDeltaKind::Line => {
+                    let target = self.snap_target(from, target);
+                    if self.motion.animation.set_target(target.to_vec2()) {
+                        ctx.request_anim_frame();
+                    }
+                }
+                // Touchpad scrolls send many small deltas, so they snap once they pause.
+                ScrollDeltaKind::Pixel => {
+                    self.motion.animation.jump_to(target.to_vec2());
+                    if self.scroll_snap.is_some() {
+                        if let Some(timer) = self.settle_timer.take() {
+                            ctx.cancel_timer(timer);
+                        }
+                        self.settle_timer = Some(ctx.request_timer(SNAP_SETTLE_DELAY));
+                    }
+                }
+            }
+            let pos = self.motion.animation.value().to_point();
+            viewport_moved = self.set_viewport_pos_raw(portal_size, content_size, pos);
+        }
 
-                // TODO - horizontal scrolling?
-                let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_vertical);
-                scrollbar.widget().cursor_progress =
-                    self.viewport_pos.y / (content_size - portal_size).height;
-                scrollbar.ctx().request_render();
+        // The viewport settles once a scrollbar is released.
+        let on_scrollbar =
+            [self.scrollbar_horizontal.id(), self.scrollbar_vertical.id()].contains(&ctx.target());
+        if matches!(event, PointerEvent::PointerUp(..)) && on_scrollbar {
+            let target = self.settle_target(self.viewport_pos);
+            self.motion.animation.jump_to(self.viewport_pos.to_vec2());
+            if self.motion.animation.set_target(target.to_vec2()) {
+                ctx.request_anim_frame();
+            }
+            let pos = self.motion.animation.value().to_point();
+            viewport_moved |= self.set_viewport_pos_raw(portal_size, content_size, pos);
+        }
+
+        // Touches drag the content, and fling it if they're still moving when lifted.
+        let state = event.pointer_state();
+        if matches!(state.pointer_id, PointerId::Touch(_)) && state.is_primary {
+            if let PointerEvent::PointerDown(..) = event {
+                // Touching the content catches the fling.
+                self.motion.fling_velocity = Vec2::ZERO;
+            }
+            let gesture = self.motion.touch_recognizer.handle_pointer_event(
+                event,
+                ctx.window_origin(),
+                Instant::now(),
+            );
+            match gesture {
+                Some(Gesture::DragStart { .. }) => {
+                    // Taking the pointer from the widget under the finger cancels its press.
+                    ctx.release_pointer();
+                    self.stop_scrolling();
+                }
+                Some(Gesture::DragUpdate { delta, .. }) => {
+                    let pos = self.viewport_pos - delta;
+                    viewport_moved = self.set_viewport_pos_raw(portal_size, content_size, pos);
+                    ctx.set_handled();
+                }
+                Some(Gesture::DragEnd { velocity, .. }) if self.scroll_snap.is_some() => {
+                    // The viewport settles on the snap point closest to where the fling would
+                    // stop, which is `velocity / FLING_FRICTION` away.
+                    let projected = self.viewport_pos - velocity / FLING_FRICTION;
+                    let projected = clamp_viewport_pos(portal_size, content_size, projected);
+                    let target = self.snap_target(self.viewport_pos, projected);
+                    self.motion.animation.jump_to(self.viewport_pos.to_vec2());
+                    if self.motion.animation.set_target(target.to_vec2()) {
+                        ctx.request_anim_frame();
+                    }
+                    let pos = self.motion.animation.value().to_point();
+                    viewport_moved = self.set_viewport_pos_raw(portal_size, content_size, pos);
+                }
+                Some(Gesture::DragEnd { velocity, .. }) => {
+                    self.motion.fling_velocity = -velocity;
+                    ctx.request_anim_frame();
+                }
+                _ => {}
+            }
+        }
+
+        if viewport_moved {
+            ctx.request_compose();
+
+            let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_vertical);
+            scrollbar.widget().cursor_progress =
+                self.viewport_pos.y / (content_size - portal_size).height;
+            scrollbar.ctx().request_render();
+            if scrollbar.widget().reveal() {
+                scrollbar.ctx().request_anim_frame();
+            }
+
+            std::mem::drop(scrollbar);
+
+            let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_horizontal);
+            scrollbar.widget().cursor_progress =
+                self.viewport_pos.x / (content_size - portal_size).width;
+            scrollbar.ctx().request_render();
+            if scrollbar.widget().reveal() {
+                scrollbar.ctx().request_anim_frame();
             }
-            _ => (),
         }
 
         // This section works because events are propagated up. So if the scrollbar got
@@ -313,6 +706,7 @@ impl<W: Widget> Widget for Portal<W> {
         }
 
         if scrollbar_moved {
+            self.stop_scrolling();
             ctx.request_compose();
         }
     }
@@ -323,6 +717,24 @@ impl<W: Widget> Widget for Portal<W> {
     // TODO - Handle scroll-related events?
     fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
 
+    fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
+        let portal_size = ctx.size();
+        let content_size = ctx.get_raw_ref(&mut self.child).ctx().layout_rect().size();
+
+        let Some(pos) = self.motion.advance(self.viewport_pos, interval) else {
+            return;
+        };
+        let clamped = clamp_viewport_pos(portal_size, content_size, pos);
+        if self.motion.stop_at_edges(pos, clamped) {
+            ctx.request_anim_frame();
+        }
+        if !self.set_viewport_pos_raw(portal_size, content_size, clamped) {
+            return;
+        }
+        ctx.request_compose();
+        self.update_scrollbars(ctx, portal_size, content_size);
+    }
+
     fn register_children(&mut self, ctx: &mut RegisterCtx) {
         ctx.register_child(&mut self.child);
         ctx.register_child(&mut self.scrollbar_horizontal);
@@ -335,24 +747,26 @@ impl<W: Widget> Widget for Portal<W> {
                 let portal_size = ctx.size();
                 let content_size = ctx.get_raw_ref(&mut self.child).ctx().layout_rect().size();
 
+                self.stop_scrolling();
                 self.pan_viewport_to_raw(portal_size, content_size, *target);
                 ctx.request_compose();
+                self.update_scrollbars(ctx, portal_size, content_size);
+            }
+            Update::Timer(token) if self.settle_timer == Some(*token) => {
+                self.settle_timer = None;
+                let portal_size = ctx.size();
+                let content_size = ctx.get_raw_ref(&mut self.child).ctx().layout_rect().size();
 
-                // TODO - There's a lot of code here that's duplicated from the `MouseWheel`
-                // event in `on_pointer_event`.
-                // Because this code directly manipulates child widgets, it's hard to factor
-                // it out.
-                let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_vertical);
-                scrollbar.widget().cursor_progress =
-                    self.viewport_pos.y / (content_size - portal_size).height;
-                scrollbar.ctx().request_render();
-
-                std::mem::drop(scrollbar);
-
-                let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar_horizontal);
-                scrollbar.widget().cursor_progress =
-                    self.viewport_pos.x / (content_size - portal_size).width;
-                scrollbar.ctx().request_render();
+                let target = self.settle_target(self.viewport_pos);
+                self.motion.animation.jump_to(self.viewport_pos.to_vec2());
+                if self.motion.animation.set_target(target.to_vec2()) {
+                    ctx.request_anim_frame();
+                }
+                let pos = self.motion.animation.value().to_point();
+                if self.set_viewport_pos_raw(portal_size, content_size, pos) {
+                    ctx.request_compose();
+                    self.update_scrollbars(ctx, portal_size, content_size);
+                }
             }
             _ => {}
         }
@@ -368,6 +782,25 @@ impl<W: Widget> Widget for Portal<W> {
         let content_size = ctx.run_layout(&mut self.child, &child_bc);
         let portal_size = bc.constrain(content_size);
 
+        self.snap_offsets.clear();
+        if let Some(snap) = &self.scroll_snap {
+            let max = snap.axis.major(content_size - portal_size).max(0.0);
+            match &snap.points {
+                SnapPoints::Content => self.snap_offsets.extend(
+                    ctx.child_snap_points(&self.child)
+                        .iter()
+                        .map(|point| snap.axis.major_pos(*point)),
+                ),
+                SnapPoints::Offsets(offsets) => self.snap_offsets.extend(offsets),
+            }
+            self.snap_offsets.push(max);
+            for offset in &mut self.snap_offsets {
+                *offset = offset.clamp(0.0, max);
+            }
+            self.snap_offsets.sort_by(f64::total_cmp);
+            self.snap_offsets.dedup();
+        }
+
         // TODO - document better
         // Recompute the portal offset for the new layout
         self.set_viewport_pos_raw(portal_size, content_size, self.viewport_pos);
@@ -426,7 +859,7 @@ impl<W: Widget> Widget for Portal<W> {
     }
 
     fn compose(&mut self, ctx: &mut ComposeCtx) {
-        ctx.set_child_translation(&mut self.child, Vec2::new(0.0, -self.viewport_pos.y));
+        ctx.set_child_translation(&mut self.child, -self.viewport_pos.to_vec2());
     }
 
     fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
@@ -479,8 +912,13 @@ mod tests {
 
     use super::*;
     use crate::assert_render_snapshot;
-    use crate::testing::{widget_ids, TestHarness};
+    use crate::testing::{
+        widget_ids, ModularWidget, Record, Recording, TestHarness, TestWidgetExt as _,
+    };
     use crate::widget::{Button, Flex, SizedBox};
+    use crate::{theme, PointerButton};
+    use winit::event::TouchPhase;
+    use winit::keyboard::ModifiersState;
 
     fn button(text: &'static str) -> impl Widget {
         SizedBox::new(Button::new(text)).width(70.0).height(40.0)
@@ -553,6 +991,196 @@ mod tests {
         assert_render_snapshot!(harness, "button_list_scroll_to_item_13");
     }
 
+    fn big_content() -> ModularWidget<()> {
+        ModularWidget::new(()).layout_fn(|_, _, _| Size::new(1000., 1000.))
+    }
+
+    fn viewport_pos(harness: &TestHarness) -> Point {
+        let portal = harness.root_widget();
+        portal
+            .downcast::<Portal<ModularWidget<()>>>()
+            .unwrap()
+            .get_viewport_pos()
+    }
+
+    #[test]
+    fn smooth_wheel_scrolling() {
+        let transition = Transition::new(Duration::from_millis(100), Easing::Linear);
+        let widget = Portal::new(big_content()).with_scroll_transition(Some(transition));
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        harness.mouse_move(Point::new(200., 200.));
+        harness.mouse_wheel(Vec2::new(0., -3.));
+        assert_eq!(viewport_pos(&harness), Point::ORIGIN);
+        harness.animate_ms(50);
+        assert_eq!(viewport_pos(&harness), Point::new(0., 15.));
+
+        // A second notch during the animation scrolls further.
+        harness.mouse_wheel(Vec2::new(0., -3.));
+        harness.animate_ms(100);
+        assert_eq!(viewport_pos(&harness), Point::new(0., 60.));
+    }
+
+    #[test]
+    fn touchpad_scrolling_is_not_animated() {
+        let transition = Transition::new(Duration::from_millis(100), Easing::Linear);
+        let widget = Portal::new(big_content()).with_scroll_transition(Some(transition));
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        harness.mouse_move(Point::new(200., 200.));
+        harness.touchpad_scroll(Vec2::new(0., -3.));
+        assert_eq!(viewport_pos(&harness), Point::new(0., 30.));
+    }
+
+    #[test]
+    fn touch_drag_and_fling() {
+        let widget = Portal::new(big_content());
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        harness.touch(1, TouchPhase::Started, (200., 300.));
+        harness.touch(1, TouchPhase::Moved, (200., 250.));
+        harness.touch(1, TouchPhase::Moved, (200., 200.));
+        let dragged = viewport_pos(&harness);
+        assert!(dragged.y > 0.);
+
+        // The content keeps moving after the finger is lifted, and slows down.
+        harness.touch(1, TouchPhase::Ended, (200., 200.));
+        harness.animate_ms(16);
+        let flung = viewport_pos(&harness);
+        assert!(flung.y > dragged.y);
+
+        // Touching the content again stops it.
+        harness.touch(2, TouchPhase::Started, (200., 200.));
+        harness.animate_ms(16);
+        assert_eq!(viewport_pos(&harness), flung);
+    }
+
+    #[test]
+    fn wheel_and_fling_snap_to_children() {
+        let mut harness = TestHarness::create_with_size(snapping_list(), Size::new(400., 250.));
+
+        // A single notch moves to the next item, even though it's shorter than an item.
+        harness.mouse_move(Point::new(200., 100.));
+        harness.mouse_wheel(Vec2::new(0., -3.));
+        assert_eq!(list_viewport_pos(&harness), Point::new(0., 100.));
+
+        // A fling settles on an item boundary instead of wherever it would stop.
+        harness.touch(1, TouchPhase::Started, (200., 200.));
+        harness.touch(1, TouchPhase::Moved, (200., 170.));
+        harness.touch(1, TouchPhase::Moved, (200., 140.));
+        harness.touch(1, TouchPhase::Ended, (200., 140.));
+        harness.animate_ms(500);
+        let settled = list_viewport_pos(&harness);
+        assert!(settled.y > 100.);
+        assert_eq!(settled.y % 100., 0.);
+    }
+
+    fn snapping_list() -> Portal<Flex> {
+        let mut list = Flex::column().gap(0.);
+        for _ in 0..100 {
+            list = list.with_child(SizedBox::empty().width(400.).height(100.));
+        }
+        Portal::new(list)
+            .with_scroll_transition(None)
+            .with_scroll_snap(ScrollSnap {
+                axis: Axis::Vertical,
+                points: SnapPoints::Content,
+                strictness: SnapStrictness::Mandatory,
+            })
+    }
+
+    fn list_viewport_pos(harness: &TestHarness) -> Point {
+        let portal = harness.root_widget();
+        portal
+            .downcast::<Portal<Flex>>()
+            .unwrap()
+            .get_viewport_pos()
+    }
+
+    #[test]
+    fn touchpad_scroll_snaps_once_it_pauses() {
+        let mut harness = TestHarness::create_with_size(snapping_list(), Size::new(400., 250.));
+
+        harness.mouse_move(Point::new(200., 100.));
+        harness.touchpad_scroll(Vec2::new(0., -3.));
+        harness.touchpad_scroll(Vec2::new(0., -4.));
+        assert_eq!(list_viewport_pos(&harness), Point::new(0., 70.));
+
+        harness.move_timers_forward(SNAP_SETTLE_DELAY);
+        assert_eq!(list_viewport_pos(&harness), Point::new(0., 100.));
+    }
+
+    #[test]
+    fn scrollbar_drag_snaps_on_release() {
+        let mut harness = TestHarness::create_with_size(snapping_list(), Size::new(400., 250.));
+
+        // Pressing the middle of the track moves the viewport there.
+        harness.mouse_move(Point::new(398., 125.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        let settled = list_viewport_pos(&harness);
+        assert!(settled.y > 0.);
+        assert_eq!(settled.y % 100., 0.);
+    }
+
+    #[test]
+    fn proximity_snap_only_applies_near_offsets() {
+        let widget = Portal::new(big_content())
+            .with_scroll_transition(None)
+            .with_scroll_snap(ScrollSnap {
+                axis: Axis::Vertical,
+                points: SnapPoints::Offsets(vec![0., 500.]),
+                strictness: SnapStrictness::Proximity(20.),
+            });
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        harness.mouse_move(Point::new(200., 200.));
+        harness.mouse_wheel(Vec2::new(0., -3.));
+        assert_eq!(viewport_pos(&harness), Point::new(0., 30.));
+
+        harness.edit_root_widget(|mut portal| {
+            let mut portal = portal.downcast::<Portal<ModularWidget<()>>>();
+            Portal::set_viewport_pos(&mut portal, Point::new(0., 470.));
+        });
+        harness.mouse_wheel(Vec2::new(0., -2.));
+        assert_eq!(viewport_pos(&harness), Point::new(0., 500.));
+    }
+
+    #[test]
+    fn auto_hide_scrollbars_show_while_scrolling() {
+        let widget = Portal::new(big_content())
+            .with_scroll_transition(None)
+            .with_scrollbar_mode(ScrollBarMode::AutoHide);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+        let vertical_opacity = |harness: &TestHarness| {
+            let portal = harness.root_widget();
+            let portal = portal.downcast::<Portal<ModularWidget<()>>>().unwrap();
+            let scrollbar = harness.get_widget(portal.scrollbar_vertical.id());
+            scrollbar.downcast::<ScrollBar>().unwrap().opacity()
+        };
+        assert_eq!(vertical_opacity(&harness), 0.);
+
+        harness.mouse_move(Point::new(200., 200.));
+        harness.mouse_wheel(Vec2::new(0., -3.));
+        harness.animate_ms(200);
+        assert_eq!(vertical_opacity(&harness), theme::SCROLLBAR_MAX_OPACITY);
+    }
+
+    #[test]
+    fn horizontal_wheel_scrolling() {
+        let widget = Portal::new(big_content()).with_scroll_transition(None);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+
+        harness.mouse_move(Point::new(200., 200.));
+        harness.mouse_wheel(Vec2::new(-3., 0.));
+        assert_eq!(viewport_pos(&harness), Point::new(30., 0.));
+
+        // Without a horizontal wheel, Shift turns the vertical wheel horizontal.
+        harness.set_modifiers(ModifiersState::SHIFT);
+        harness.mouse_wheel(Vec2::new(0., -2.));
+        assert_eq!(viewport_pos(&harness), Point::new(50., 0.));
+    }
+
     // Helper function for panning tests
     fn make_range(repr: &str) -> Range<f64> {
         let repr = &repr[repr.find('_').unwrap()..];
@@ -663,4 +1291,44 @@ mod tests {
 
         assert_eq!(compute_pan_range(initial_range, target_range), result_range);
     }
+
+    #[test]
+    fn offscreen_children_not_painted() {
+        let top_rec = Recording::default();
+        let bottom_rec = Recording::default();
+
+        let widget = Portal::new(
+            Flex::column()
+                .with_child(SizedBox::empty().width(70.0).height(40.0).record(&top_rec))
+                .with_spacer(1000.0)
+                .with_child(
+                    SizedBox::empty()
+                        .width(70.0)
+                        .height(40.0)
+                        .record(&bottom_rec),
+                ),
+        );
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+        top_rec.clear();
+        bottom_rec.clear();
+
+        let _ = harness.render();
+        assert!(top_rec.drain().iter().any(|r| matches!(r, Record::Paint)));
+        assert!(!bottom_rec
+            .drain()
+            .iter()
+            .any(|r| matches!(r, Record::Paint)));
+
+        harness.edit_root_widget(|mut portal| {
+            let mut portal = portal.downcast::<Portal<Flex>>();
+            Portal::set_viewport_pos(&mut portal, Point::new(0.0, 1000.0));
+        });
+
+        let _ = harness.render();
+        assert!(bottom_rec
+            .drain()
+            .iter()
+            .any(|r| matches!(r, Record::Paint)));
+    }
 }
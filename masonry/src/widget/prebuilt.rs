@@ -0,0 +1,250 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A container which shows a placeholder until its content is attached.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, Action, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A container which shows a placeholder until its content is attached, e.g. because
+/// the content is expensive to build and is built after the placeholder is displayed.
+///
+/// Once a frame showing the placeholder has been presented, the container submits
+/// [`Action::PlaceholderPresented`], which is the cue to build the content.
+///
+/// Once the content is attached, the placeholder is stashed and the content takes its place.
+/// While the placeholder is shown, the container is marked as busy in the accessibility tree.
+pub struct Prebuilt<W: Widget> {
+    placeholder: WidgetPod<Box<dyn Widget>>,
+    content: Option<WidgetPod<W>>,
+    /// Whether the placeholder was painted since it was last shown.
+    placeholder_painted: bool,
+}
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> Prebuilt<W> {
+    /// Create a new widget showing `placeholder`, until the content is attached.
+    pub fn new(placeholder: impl Widget) -> Self {
+        Self::new_pod(WidgetPod::new(placeholder).boxed())
+    }
+
+    pub fn new_pod(placeholder: WidgetPod<Box<dyn Widget>>) -> Self {
+        Self {
+            placeholder,
+            content: None,
+            placeholder_painted: false,
+        }
+    }
+
+    /// Builder-style method to attach the content right away.
+    pub fn with_content(self, content: W) -> Self {
+        self.with_content_pod(WidgetPod::new(content))
+    }
+
+    pub fn with_content_pod(mut self, content: WidgetPod<W>) -> Self {
+        self.content = Some(content);
+        self
+    }
+
+    /// Whether the content is attached.
+    pub fn is_ready(&self) -> bool {
+        self.content.is_some()
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> Prebuilt<W> {
+    pub fn placeholder_mut<'t>(
+        this: &'t mut WidgetMut<'_, Self>,
+    ) -> WidgetMut<'t, Box<dyn Widget>> {
+        this.ctx.get_mut(&mut this.widget.placeholder)
+    }
+
+    /// The content, if it's attached.
+    pub fn content_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> Option<WidgetMut<'t, W>> {
+        let content = this.widget.content.as_mut()?;
+        Some(this.ctx.get_mut(content))
+    }
+
+    /// Attach the content, replacing the placeholder, or the previous content.
+    pub fn set_content(this: &mut WidgetMut<'_, Self>, content: W) {
+        Self::set_content_pod(this, WidgetPod::new(content));
+    }
+
+    pub fn set_content_pod(this: &mut WidgetMut<'_, Self>, content: WidgetPod<W>) {
+        if let Some(previous) = this.widget.content.replace(content) {
+            this.ctx.remove_child(previous);
+        }
+        this.ctx.set_stashed(&mut this.widget.placeholder, true);
+        this.ctx.children_changed();
+        this.ctx.request_layout();
+        this.ctx.request_accessibility_update();
+    }
+
+    /// Detach the content, and show the placeholder again.
+    pub fn remove_content(this: &mut WidgetMut<'_, Self>) {
+        if let Some(content) = this.widget.content.take() {
+            this.ctx.remove_child(content);
+            this.ctx.set_stashed(&mut this.widget.placeholder, false);
+            this.widget.placeholder_painted = false;
+            this.ctx.request_anim_frame();
+            this.ctx.request_layout();
+            this.ctx.request_accessibility_update();
+        }
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for Prebuilt<W> {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, _interval: u64) {
+        if self.content.is_some() {
+            return;
+        }
+        // Animation frames are run before painting, so the frame the placeholder
+        // was painted in has been presented by the time we see it painted here.
+        if self.placeholder_painted {
+            ctx.submit_action(Action::PlaceholderPresented);
+        } else {
+            ctx.request_anim_frame();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded = event {
+            ctx.set_stashed(&mut self.placeholder, self.content.is_some());
+            if self.content.is_none() {
+                ctx.request_anim_frame();
+            }
+        }
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.placeholder);
+        if let Some(content) = &mut self.content {
+            ctx.register_child(content);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        if let Some(content) = &mut self.content {
+            let size = ctx.run_layout(content, bc);
+            ctx.place_child(content, Point::ORIGIN);
+            let baseline = ctx.child_baseline_offset(content);
+            ctx.set_baseline_offset(baseline);
+            size
+        } else {
+            let size = ctx.run_layout(&mut self.placeholder, bc);
+            ctx.place_child(&mut self.placeholder, Point::ORIGIN);
+            let baseline = ctx.child_baseline_offset(&self.placeholder);
+            ctx.set_baseline_offset(baseline);
+            size
+        }
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {
+        if self.content.is_none() {
+            self.placeholder_painted = true;
+        }
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        if self.content.is_none() {
+            node.set_busy();
+        }
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        let mut ids = SmallVec::new();
+        ids.push(self.placeholder.id());
+        if let Some(content) = &self.content {
+            ids.push(content.id());
+        }
+        ids
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Prebuilt")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::widget::{Flex, Label, SizedBox};
+
+    #[test]
+    fn content_replaces_placeholder() {
+        let [prebuilt_id, placeholder_id, content_id] = widget_ids();
+        let prebuilt = Prebuilt::<SizedBox>::new(Label::new("Loading").with_id(placeholder_id));
+        let mut harness = TestHarness::create(Flex::row().with_child_id(prebuilt, prebuilt_id));
+        assert!(!harness.get_widget(placeholder_id).ctx().is_stashed());
+
+        harness.edit_widget(prebuilt_id, |mut prebuilt| {
+            let mut prebuilt = prebuilt.downcast::<Prebuilt<SizedBox>>();
+            let content = SizedBox::empty().width(40.).height(30.);
+            Prebuilt::set_content_pod(&mut prebuilt, WidgetPod::new_with_id(content, content_id));
+        });
+        assert!(harness.get_widget(placeholder_id).ctx().is_stashed());
+        assert!(!harness.get_widget(content_id).ctx().is_stashed());
+        assert_eq!(
+            harness.get_widget(prebuilt_id).ctx().size(),
+            Size::new(40., 30.)
+        );
+    }
+
+    #[test]
+    fn placeholder_presented_once_painted() {
+        let [prebuilt_id] = widget_ids();
+        let prebuilt = Prebuilt::<SizedBox>::new(Label::new("Loading"));
+        let mut harness = TestHarness::create(Flex::row().with_child_id(prebuilt, prebuilt_id));
+
+        // The frame showing the placeholder hasn't been painted yet.
+        harness.animate_ms(16);
+        assert_eq!(harness.pop_action(), None);
+
+        harness.paint();
+        harness.animate_ms(16);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::PlaceholderPresented, prebuilt_id))
+        );
+        harness.animate_ms(16);
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn removing_content_shows_placeholder() {
+        let [prebuilt_id, placeholder_id] = widget_ids();
+        let prebuilt = Prebuilt::new(Label::new("Loading").with_id(placeholder_id))
+            .with_content(SizedBox::empty().width(40.).height(30.));
+        let mut harness = TestHarness::create(Flex::row().with_child_id(prebuilt, prebuilt_id));
+        assert!(harness.get_widget(placeholder_id).ctx().is_stashed());
+
+        harness.edit_widget(prebuilt_id, |mut prebuilt| {
+            let mut prebuilt = prebuilt.downcast::<Prebuilt<SizedBox>>();
+            Prebuilt::remove_content(&mut prebuilt);
+        });
+        assert!(!harness.get_widget(placeholder_id).ctx().is_stashed());
+        assert_eq!(harness.get_widget(prebuilt_id).children().len(), 1);
+    }
+}
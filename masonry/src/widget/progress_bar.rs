@@ -9,6 +9,7 @@ use tracing::{trace_span, Span};
 use vello::Scene;
 
 use crate::kurbo::Size;
+use crate::locale::LocaleFormatter;
 use crate::paint_scene_helpers::{fill_lin_gradient, stroke, UnitPoint};
 use crate::text::{ArcStr, TextLayout};
 use crate::widget::WidgetMut;
@@ -26,6 +27,8 @@ pub struct ProgressBar {
     progress: Option<f64>,
     progress_changed: bool,
     label: TextLayout,
+    /// The text of the label, formatted for the locale.
+    text: ArcStr,
 }
 
 impl ProgressBar {
@@ -43,7 +46,8 @@ impl ProgressBar {
         Self {
             progress: None,
             progress_changed: false,
-            label: TextLayout::new(crate::theme::TEXT_SIZE_NORMAL as f32),
+            label: TextLayout::new(*theme::TEXT_SIZE_NORMAL.default_value() as f32),
+            text: "".into(),
         }
     }
 
@@ -56,17 +60,17 @@ impl ProgressBar {
         }
     }
 
-    fn value(&self) -> ArcStr {
+    fn value(&self, formatter: &dyn LocaleFormatter) -> ArcStr {
         if let Some(value) = self.progress {
-            format!("{:.0}%", value * 100.).into()
+            formatter.format_percent(value).into()
         } else {
             "".into()
         }
     }
 
     fn value_accessibility(&self) -> Box<str> {
-        if let Some(value) = self.progress {
-            format!("{:.0}%", value * 100.).into()
+        if self.progress.is_some() {
+            (*self.text).into()
         } else {
             "progress unspecified".into()
         }
@@ -77,8 +81,10 @@ impl ProgressBar {
 impl ProgressBar {
     pub fn set_progress(this: &mut WidgetMut<'_, Self>, progress: Option<f64>) {
         this.widget.set_progress_inner(progress);
+        this.widget.text = this.widget.value(this.ctx.formatter());
         this.ctx.request_layout();
         this.ctx.request_render();
+        this.ctx.request_accessibility_update();
     }
 }
 
@@ -105,22 +111,35 @@ impl Widget for ProgressBar {
 
     fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
 
-    fn update(&mut self, _ctx: &mut UpdateCtx, _event: &Update) {}
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded | Update::LocaleChanged = event {
+            self.text = self.value(ctx.formatter());
+            self.progress_changed = true;
+            ctx.request_layout();
+            ctx.request_accessibility_update();
+        }
+    }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         const DEFAULT_WIDTH: f64 = 400.;
 
+        // The label is drawn with the text size and color of the env.
+        self.label
+            .set_text_size(ctx.env().get(theme::TEXT_SIZE_NORMAL) as f32);
+        self.label.set_brush(ctx.env().get(theme::TEXT_COLOR));
         if self.label.needs_rebuild() || self.progress_changed {
             let (font_ctx, layout_ctx) = ctx.text_contexts();
             self.label
-                .rebuild(font_ctx, layout_ctx, &self.value(), self.progress_changed);
+                .rebuild(font_ctx, layout_ctx, &self.text, self.progress_changed);
             self.progress_changed = false;
         }
         let label_size = self.label.size();
 
         let desired_size = Size::new(
             DEFAULT_WIDTH.max(label_size.width),
-            crate::theme::BASIC_WIDGET_HEIGHT.max(label_size.height),
+            ctx.env()
+                .get(theme::BASIC_WIDGET_HEIGHT)
+                .max(label_size.height),
         );
         bc.constrain(desired_size)
     }
@@ -141,12 +160,20 @@ impl Widget for ProgressBar {
         fill_lin_gradient(
             scene,
             &rect,
-            [theme::BACKGROUND_LIGHT, theme::BACKGROUND_DARK],
+            [
+                ctx.env().get(theme::BACKGROUND_LIGHT),
+                ctx.env().get(theme::BACKGROUND_DARK),
+            ],
             UnitPoint::TOP,
             UnitPoint::BOTTOM,
         );
 
-        stroke(scene, &rect, theme::BORDER_DARK, border_width);
+        stroke(
+            scene,
+            &rect,
+            ctx.env().get(theme::BORDER_DARK),
+            border_width,
+        );
 
         let progress_rect_size = Size::new(
             ctx.size().width * self.progress.unwrap_or(1.),
@@ -160,11 +187,19 @@ impl Widget for ProgressBar {
         fill_lin_gradient(
             scene,
             &progress_rect,
-            [theme::PRIMARY_LIGHT, theme::PRIMARY_DARK],
+            [
+                ctx.env().get(theme::PRIMARY_LIGHT),
+                ctx.env().get(theme::PRIMARY_DARK),
+            ],
             UnitPoint::TOP,
             UnitPoint::BOTTOM,
         );
-        stroke(scene, &progress_rect, theme::BORDER_DARK, border_width);
+        stroke(
+            scene,
+            &progress_rect,
+            ctx.env().get(theme::BORDER_DARK),
+            border_width,
+        );
 
         // center text
         let widget_size = ctx.size();
@@ -173,6 +208,7 @@ impl Widget for ProgressBar {
             ((widget_size.width - label_size.width) * 0.5).max(0.),
             ((widget_size.height - label_size.height) * 0.5).max(0.),
         );
+        self.label.set_glyph_snapping(ctx.glyph_snapping());
         self.label.draw(scene, text_pos);
     }
 
@@ -207,10 +243,52 @@ mod tests {
 
     use super::*;
     use crate::assert_render_snapshot;
+    use crate::locale::{BasicFormatter, Locale};
     use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
 
     // TODO - Add WidgetMut test
 
+    #[test]
+    fn follows_locale() {
+        let mut harness = TestHarness::create(ProgressBar::new(Some(0.5)));
+        let width = |harness: &TestHarness| {
+            let bar = harness.root_widget();
+            bar.downcast::<ProgressBar>().unwrap().label.size().width
+        };
+        let before = width(&harness);
+        assert_eq!(
+            harness.root_widget().get_debug_text().as_deref(),
+            Some("50%")
+        );
+
+        // A formatter which spells out percentages.
+        struct Percent(Locale);
+        impl LocaleFormatter for Percent {
+            fn locale(&self) -> &Locale {
+                &self.0
+            }
+            fn format_number(&self, value: f64, fraction_digits: usize) -> String {
+                BasicFormatter::default().format_number(value, fraction_digits)
+            }
+            fn parse_number(&self, text: &str) -> Option<f64> {
+                BasicFormatter::default().parse_number(text)
+            }
+            fn format_date(&self, date: time::Date) -> String {
+                BasicFormatter::default().format_date(date)
+            }
+            fn format_percent(&self, fraction: f64) -> String {
+                format!("{} percent", self.format_number(fraction * 100., 0))
+            }
+        }
+        harness.set_formatter(Percent(Locale::default()));
+        assert_eq!(
+            harness.root_widget().get_debug_text().as_deref(),
+            Some("50 percent")
+        );
+        // The label was laid out again.
+        assert!(width(&harness) > before);
+    }
+
     #[test]
     fn indeterminate_progressbar() {
         let [progressbar_id] = widget_ids();
@@ -6,15 +6,15 @@ use parley::layout::Alignment;
 use parley::style::{FontFamily, FontStack};
 use smallvec::SmallVec;
 use tracing::{trace_span, Span};
-use vello::kurbo::{Affine, Point, Size};
-use vello::peniko::BlendMode;
+use vello::kurbo::{Affine, Point, Size, Vec2};
+use vello::peniko::{BlendMode, Color};
 use vello::Scene;
 
-use crate::text::{ArcStr, TextBrush, TextWithSelection};
+use crate::text::{ArcStr, CaretStyle, TextBrush, TextStyle, TextWithSelection};
 use crate::widget::label::LABEL_X_PADDING;
 use crate::widget::{LineBreaking, WidgetMut};
 use crate::{
-    AccessCtx, AccessEvent, BoxConstraints, CursorIcon, EventCtx, LayoutCtx, PaintCtx,
+    theme, AccessCtx, AccessEvent, BoxConstraints, CursorIcon, EventCtx, LayoutCtx, PaintCtx,
     PointerEvent, QueryCtx, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
 };
 
@@ -24,22 +24,34 @@ use crate::{
 ///
 /// This should be preferred over [`Label`](super::Label) for most
 /// immutable text, other than that within
+///
+/// With [caret browsing](Self::with_caret_browsing), the text can also be read and
+/// selected with the keyboard alone.
 pub struct Prose {
     // See `Label` for discussion of the choice of text type
     text_layout: TextWithSelection<ArcStr>,
     line_break_mode: LineBreaking,
     show_disabled: bool,
-    brush: TextBrush,
+    caret_browsing: bool,
+    /// Text properties set on this widget, which take precedence over the inherited style.
+    style: TextStyle,
+    /// The selection color set on this widget, which takes precedence over the env.
+    selection_color: Option<Color>,
 }
 
 // --- MARK: BUILDERS ---
 impl Prose {
     pub fn new(text: impl Into<ArcStr>) -> Self {
         Prose {
-            text_layout: TextWithSelection::new(text.into(), crate::theme::TEXT_SIZE_NORMAL as f32),
+            text_layout: TextWithSelection::new(
+                text.into(),
+                *theme::TEXT_SIZE_NORMAL.default_value() as f32,
+            ),
             line_break_mode: LineBreaking::WordWrap,
             show_disabled: true,
-            brush: crate::theme::TEXT_COLOR.into(),
+            caret_browsing: false,
+            style: TextStyle::default(),
+            selection_color: None,
         }
     }
 
@@ -50,14 +62,16 @@ impl Prose {
 
     #[doc(alias = "with_text_color")]
     pub fn with_text_brush(mut self, brush: impl Into<TextBrush>) -> Self {
-        self.brush = brush.into();
-        self.text_layout.set_brush(self.brush.clone());
+        let brush = brush.into();
+        self.text_layout.set_brush(brush.clone());
+        self.style.brush = Some(brush);
         self
     }
 
     #[doc(alias = "with_font_size")]
     pub fn with_text_size(mut self, size: f32) -> Self {
         self.text_layout.set_text_size(size);
+        self.style.text_size = Some(size);
         self
     }
 
@@ -68,6 +82,7 @@ impl Prose {
 
     pub fn with_font(mut self, font: FontStack<'static>) -> Self {
         self.text_layout.set_font(font);
+        self.style.font = Some(font);
         self
     }
     pub fn with_font_family(self, font: FontFamily<'static>) -> Self {
@@ -78,6 +93,26 @@ impl Prose {
         self.line_break_mode = line_break_mode;
         self
     }
+
+    /// Builder-style method for setting the color behind selected text.
+    pub fn with_selection_color(mut self, color: Color) -> Self {
+        self.text_layout.set_selection_color(color);
+        self.selection_color = Some(color);
+        self
+    }
+
+    /// Builder-style method for enabling caret browsing, `false` by default.
+    ///
+    /// With caret browsing, the widget can be focused with <kbd>Tab</kbd>, and shows a
+    /// reading caret when focused. The arrow keys, <kbd>Home</kbd> and <kbd>End</kbd> move
+    /// the caret, holding <kbd>Shift</kbd> selects text, and <kbd>Ctrl</kbd>+<kbd>C</kbd>
+    /// copies the selection. This makes document-style text usable without a pointer,
+    /// e.g. with a screen magnifier following the caret.
+    pub fn with_caret_browsing(mut self, caret_browsing: bool) -> Self {
+        self.caret_browsing = caret_browsing;
+        self.text_layout.set_caret_browsing(caret_browsing);
+        self
+    }
 }
 
 // --- MARK: WIDGETMUT ---
@@ -108,19 +143,20 @@ impl Prose {
     #[doc(alias = "set_text_color")]
     pub fn set_text_brush(this: &mut WidgetMut<'_, Self>, brush: impl Into<TextBrush>) {
         let brush = brush.into();
-        this.widget.brush = brush;
+        this.widget.style.brush = Some(brush.clone());
         if !this.ctx.is_disabled() {
-            let brush = this.widget.brush.clone();
             Self::set_text_properties(this, |layout| layout.set_brush(brush));
         }
     }
     pub fn set_text_size(this: &mut WidgetMut<'_, Self>, size: f32) {
+        this.widget.style.text_size = Some(size);
         Self::set_text_properties(this, |layout| layout.set_text_size(size));
     }
     pub fn set_alignment(this: &mut WidgetMut<'_, Self>, alignment: Alignment) {
         Self::set_text_properties(this, |layout| layout.set_text_alignment(alignment));
     }
     pub fn set_font(this: &mut WidgetMut<'_, Self>, font_stack: FontStack<'static>) {
+        this.widget.style.font = Some(font_stack);
         Self::set_text_properties(this, |layout| layout.set_font(font_stack));
     }
     pub fn set_font_family(this: &mut WidgetMut<'_, Self>, family: FontFamily<'static>) {
@@ -130,6 +166,36 @@ impl Prose {
         this.widget.line_break_mode = line_break_mode;
         this.ctx.request_layout();
     }
+    pub fn set_selection_color(this: &mut WidgetMut<'_, Self>, color: Color) {
+        this.widget.selection_color = Some(color);
+        Self::set_text_properties(this, |layout| layout.set_selection_color(color));
+    }
+
+    /// Enable or disable caret browsing.
+    ///
+    /// See [`with_caret_browsing`](Self::with_caret_browsing).
+    pub fn set_caret_browsing(this: &mut WidgetMut<'_, Self>, caret_browsing: bool) {
+        this.widget.caret_browsing = caret_browsing;
+        this.widget.text_layout.set_caret_browsing(caret_browsing);
+        this.ctx.accepts_focus_changed();
+        if caret_browsing && this.ctx.is_focused() {
+            this.widget.text_layout.focus_gained();
+            this.ctx.request_layout();
+        }
+    }
+}
+
+impl Prose {
+    /// The brush of the text, given whether the widget is disabled.
+    fn brush(&self, ctx: &UpdateCtx, disabled: bool) -> TextBrush {
+        if self.show_disabled && disabled {
+            ctx.env().get(theme::DISABLED_TEXT_COLOR).into()
+        } else {
+            self.style
+                .inherit_from(ctx.text_style())
+                .brush_or_default(ctx.env())
+        }
+    }
 }
 
 // --- MARK: IMPL WIDGET ---
@@ -175,6 +241,13 @@ impl Widget for Prose {
             ctx.set_handled();
             // TODO: only some handlers need this repaint
             ctx.request_layout();
+            if let Some(text) = self.text_layout.take_copied_text() {
+                ctx.copy_to_clipboard(&text);
+            }
+            // Keep the caret visible, e.g. when the text is in a Portal.
+            if let Some(caret) = self.text_layout.caret_rect() {
+                ctx.request_scroll_to(caret + Vec2::new(LABEL_X_PADDING, 0.));
+            }
         }
     }
 
@@ -198,28 +271,37 @@ impl Widget for Prose {
                 ctx.request_layout();
                 // TODO: Stop focusing on any links
             }
-            Update::FocusChanged(true) => {
-                // TODO: Focus on first link
+            // TODO: Focus on first link
+            Update::FocusChanged(true) if self.caret_browsing => {
+                self.text_layout.focus_gained();
+                ctx.request_layout();
             }
             Update::DisabledChanged(disabled) => {
-                if self.show_disabled {
-                    if *disabled {
-                        self.text_layout
-                            .set_brush(crate::theme::DISABLED_TEXT_COLOR);
-                    } else {
-                        self.text_layout.set_brush(self.brush.clone());
-                    }
-                }
+                let brush = self.brush(ctx, *disabled);
+                self.text_layout.set_brush(brush);
                 // TODO: Parley seems to require a relayout when colours change
                 ctx.request_layout();
             }
+            Update::TextStyleChanged | Update::EnvChanged => {
+                let env = ctx.env();
+                let style = self.style.inherit_from(ctx.text_style());
+                style.apply_to_layout(&mut self.text_layout, env);
+                self.text_layout.set_caret_style(CaretStyle::from_env(env));
+                self.text_layout.set_selection_color(
+                    self.selection_color
+                        .unwrap_or_else(|| env.get(theme::SELECTED_TEXT_BACKGROUND_COLOR)),
+                );
+                let brush = self.brush(ctx, ctx.is_disabled());
+                self.text_layout.set_brush(brush);
+                ctx.request_layout();
+            }
             _ => {}
         }
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         // Compute max_advance from box constraints
-        let max_advance = if self.line_break_mode != LineBreaking::WordWrap {
+        let max_advance = if !self.line_break_mode.wraps() {
             None
         } else if bc.max().width.is_finite() {
             // TODO: Does Prose have different needs here?
@@ -230,6 +312,7 @@ impl Widget for Prose {
             None
         };
         self.text_layout.set_max_advance(max_advance);
+        self.text_layout.set_line_height_grid(ctx.baseline_grid());
         if self.text_layout.needs_rebuild() {
             let (font_ctx, layout_ctx) = ctx.text_contexts();
             self.text_layout.rebuild(font_ctx, layout_ctx);
@@ -254,6 +337,7 @@ impl Widget for Prose {
             let clip_rect = ctx.size().to_rect();
             scene.push_layer(BlendMode::default(), 1., Affine::IDENTITY, &clip_rect);
         }
+        self.text_layout.set_glyph_snapping(ctx.glyph_snapping());
         self.text_layout
             .draw(scene, Point::new(LABEL_X_PADDING, 0.0));
 
@@ -262,6 +346,10 @@ impl Widget for Prose {
         }
     }
 
+    fn accepts_focus(&self) -> bool {
+        self.caret_browsing
+    }
+
     fn get_cursor(&self, _ctx: &QueryCtx, _pos: Point) -> CursorIcon {
         // TODO: Set cursor if over link
         CursorIcon::Text
@@ -289,4 +377,104 @@ impl Widget for Prose {
     }
 }
 
-// TODO - Add tests
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+    use super::*;
+    use crate::clipboard::ClipboardContent;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::widget::{Flex, Portal, SizedBox};
+
+    /// The anchor and active ends of the selection of the `Prose` with the given id.
+    fn selection(harness: &TestHarness, id: WidgetId) -> (usize, usize) {
+        let prose = harness.get_widget(id);
+        let selection = prose.downcast::<Prose>().unwrap().text_layout.selection;
+        (selection.anchor, selection.active)
+    }
+
+    #[test]
+    fn caret_browsing_shows_caret_on_focus() {
+        let [plain_id, browsable_id] = widget_ids();
+        let widget = Flex::column()
+            .with_child_id(Prose::new("Plain text"), plain_id)
+            .with_child_id(
+                Prose::new("Browsable text").with_caret_browsing(true),
+                browsable_id,
+            );
+
+        let mut harness = TestHarness::create(widget);
+        assert!(!harness.get_widget(plain_id).ctx().accepts_focus());
+        assert!(harness.get_widget(browsable_id).ctx().accepts_focus());
+
+        harness.focus_on(Some(browsable_id));
+        let prose = harness.get_widget(browsable_id);
+        let prose = prose.downcast::<Prose>().unwrap();
+        assert!(prose.text_layout.caret_rect().is_some());
+    }
+
+    #[test]
+    fn caret_browsing_keys_move_select_and_copy() {
+        let [prose_id] = widget_ids();
+        let widget = Prose::new("Hello world")
+            .with_caret_browsing(true)
+            .with_id(prose_id);
+        let mut harness = TestHarness::create(widget);
+        harness.focus_on(Some(prose_id));
+
+        harness.keyboard_key(Key::Named(NamedKey::ArrowRight));
+        assert_eq!(selection(&harness, prose_id), (1, 1));
+
+        harness.set_modifiers(ModifiersState::SHIFT);
+        for _ in 0..4 {
+            harness.keyboard_key(Key::Named(NamedKey::ArrowRight));
+        }
+        assert_eq!(selection(&harness, prose_id), (1, 5));
+
+        harness.set_modifiers(ModifiersState::CONTROL);
+        harness.keyboard_key(Key::Character("c".into()));
+        assert_eq!(harness.clipboard(), Some(&ClipboardContent::text("ello")));
+
+        harness.set_modifiers(ModifiersState::SHIFT);
+        harness.keyboard_key(Key::Named(NamedKey::End));
+        assert_eq!(selection(&harness, prose_id), (1, 11));
+
+        harness.set_modifiers(ModifiersState::empty());
+        harness.keyboard_key(Key::Named(NamedKey::Home));
+        assert_eq!(selection(&harness, prose_id), (0, 0));
+    }
+
+    #[test]
+    fn caret_browsing_scrolls_to_caret() {
+        let [prose_id, portal_id] = widget_ids();
+        let text = "Line\n".repeat(30);
+        let prose = Prose::new(text).with_caret_browsing(true).with_id(prose_id);
+        let widget = Portal::new(prose).with_id(portal_id);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 100.));
+        harness.focus_on(Some(prose_id));
+
+        harness.set_modifiers(ModifiersState::CONTROL);
+        harness.keyboard_key(Key::Named(NamedKey::End));
+        let portal = harness.get_widget(portal_id);
+        let portal = portal.downcast::<Portal<SizedBox>>().unwrap();
+        assert!(portal.get_viewport_pos().y > 0.);
+    }
+
+    #[test]
+    fn caret_browsing_can_be_toggled() {
+        let [prose_id] = widget_ids();
+        let mut harness = TestHarness::create(Prose::new("Some text").with_id(prose_id));
+        assert!(!harness.get_widget(prose_id).ctx().accepts_focus());
+
+        harness.edit_widget(prose_id, |mut prose| {
+            let mut prose = prose.downcast::<Prose>();
+            Prose::set_caret_browsing(&mut prose, true);
+        });
+        assert!(harness.get_widget(prose_id).ctx().accepts_focus());
+        harness.focus_on(Some(prose_id));
+        harness.set_modifiers(ModifiersState::SHIFT);
+        harness.keyboard_key(Key::Named(NamedKey::End));
+        assert_eq!(selection(&harness, prose_id), (0, 9));
+    }
+}
@@ -0,0 +1,393 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A star rating input.
+
+use std::f64::consts::PI;
+
+use accesskit::{ActionData, NodeBuilder, Role};
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::kurbo::{Affine, BezPath};
+use vello::peniko::BlendMode;
+use vello::Scene;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::action::Action;
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::widget::WidgetMut;
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, Rect, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+const STAR_SIZE: f64 = 20.;
+const STAR_GAP: f64 = 4.;
+/// The radius of the inner corners of a star, relative to the outer ones.
+const STAR_INNER_RATIO: f64 = 0.4;
+
+/// A row of stars, used to pick a rating between zero and a maximum.
+///
+/// Hovering the stars previews the rating they would set, and clicking sets it.
+/// When the widget has focus, the arrow keys change the rating by one step, and
+/// Home and End set it to zero and to the maximum.
+///
+/// With [half stars](Self::with_half_stars), ratings can be set in steps of one half.
+///
+/// Emits [`Action::RatingChanged`] when the rating is changed by the user.
+pub struct Rating {
+    value: f64,
+    max: u32,
+    half_stars: bool,
+    hover_value: Option<f64>,
+}
+
+// --- MARK: BUILDERS ---
+impl Rating {
+    /// Create a new rating out of five stars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use masonry::widget::Rating;
+    ///
+    /// let rating = Rating::new(3.).with_max(10).with_half_stars(true);
+    /// ```
+    pub fn new(value: f64) -> Self {
+        let mut rating = Self {
+            value: 0.,
+            max: 5,
+            half_stars: false,
+            hover_value: None,
+        };
+        rating.value = rating.clamp(value);
+        rating
+    }
+
+    /// Builder-style method for setting the number of stars.
+    pub fn with_max(mut self, max: u32) -> Self {
+        self.max = max;
+        self.value = self.clamp(self.value);
+        self
+    }
+
+    /// Builder-style method for allowing ratings in steps of half a star.
+    pub fn with_half_stars(mut self, half_stars: bool) -> Self {
+        self.half_stars = half_stars;
+        self.value = self.clamp(self.value);
+        self
+    }
+
+    /// The current rating.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn step(&self) -> f64 {
+        if self.half_stars {
+            0.5
+        } else {
+            1.
+        }
+    }
+
+    /// Round `value` to a step, and keep it between zero and the maximum.
+    fn clamp(&self, value: f64) -> f64 {
+        let step = self.step();
+        ((value / step).round() * step).clamp(0., self.max as f64)
+    }
+
+    /// The rating set by clicking at `x`.
+    fn value_at(&self, x: f64) -> f64 {
+        let pitch = STAR_SIZE + STAR_GAP;
+        let star = (x / pitch).floor().max(0.);
+        let within = (x - star * pitch) / STAR_SIZE;
+        let value = if self.half_stars && within <= 0.5 {
+            star + 0.5
+        } else {
+            star + 1.
+        };
+        value.min(self.max as f64)
+    }
+
+    /// The rating after pressing `key`, if the key changes it.
+    fn value_after_key(&self, key: &Key) -> Option<f64> {
+        let Key::Named(key) = key else {
+            return None;
+        };
+        let value = match key {
+            NamedKey::ArrowRight | NamedKey::ArrowUp => self.value + self.step(),
+            NamedKey::ArrowLeft | NamedKey::ArrowDown => self.value - self.step(),
+            NamedKey::Home => 0.,
+            NamedKey::End => self.max as f64,
+            _ => return None,
+        };
+        Some(self.clamp(value))
+    }
+
+    fn set_value_internal(&mut self, ctx: &mut EventCtx, value: f64) {
+        let value = self.clamp(value);
+        if value != self.value {
+            self.value = value;
+            ctx.submit_action(Action::RatingChanged(value));
+            ctx.request_render();
+        }
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl Rating {
+    /// Set the current rating.
+    pub fn set_value(this: &mut WidgetMut<'_, Self>, value: f64) {
+        this.widget.value = this.widget.clamp(value);
+        this.ctx.request_render();
+    }
+
+    /// Set the number of stars.
+    pub fn set_max(this: &mut WidgetMut<'_, Self>, max: u32) {
+        this.widget.max = max;
+        this.widget.value = this.widget.clamp(this.widget.value);
+        this.ctx.request_layout();
+        this.ctx.request_accessibility_update();
+    }
+
+    /// Set whether ratings can be set in steps of half a star.
+    pub fn set_half_stars(this: &mut WidgetMut<'_, Self>, half_stars: bool) {
+        this.widget.half_stars = half_stars;
+        this.widget.value = this.widget.clamp(this.widget.value);
+        this.ctx.request_render();
+    }
+}
+
+/// A five-pointed star filling a square of side [`STAR_SIZE`] at `origin`.
+fn star_path(origin: Point) -> BezPath {
+    let center = origin + (STAR_SIZE / 2., STAR_SIZE / 2.);
+    let outer = STAR_SIZE / 2.;
+    let mut path = BezPath::new();
+    for i in 0..10 {
+        let radius = if i % 2 == 0 {
+            outer
+        } else {
+            outer * STAR_INNER_RATIO
+        };
+        let angle = -PI / 2. + i as f64 * PI / 5.;
+        let point = center + (radius * angle.cos(), radius * angle.sin());
+        if i == 0 {
+            path.move_to(point);
+        } else {
+            path.line_to(point);
+        }
+    }
+    path.close_path();
+    path
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for Rating {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        if ctx.is_disabled() {
+            return;
+        }
+        let local_x = event.position().map(|pos| pos.x - ctx.window_origin().x);
+        match event {
+            PointerEvent::PointerMove(_) => {
+                let hover_value = local_x.map(|x| self.value_at(x));
+                if hover_value != self.hover_value {
+                    self.hover_value = hover_value;
+                    ctx.request_paint_only();
+                }
+            }
+            PointerEvent::PointerDown(_, _) => {
+                ctx.capture_pointer();
+                ctx.request_focus();
+            }
+            PointerEvent::PointerUp(_, _) if ctx.has_pointer_capture() && ctx.is_hovered() => {
+                if let Some(x) = local_x {
+                    self.set_value_internal(ctx, self.value_at(x));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        let TextEvent::KeyboardKey(event, _) = event else {
+            return;
+        };
+        if !event.state.is_pressed() || ctx.is_disabled() {
+            return;
+        }
+        if let Some(value) = self.value_after_key(&event.logical_key) {
+            self.set_value_internal(ctx, value);
+            ctx.set_handled();
+        }
+    }
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
+        if ctx.target() != ctx.widget_id() {
+            return;
+        }
+        match (event.action, &event.data) {
+            (accesskit::Action::Increment, _) => {
+                self.set_value_internal(ctx, self.value + self.step());
+            }
+            (accesskit::Action::Decrement, _) => {
+                self.set_value_internal(ctx, self.value - self.step());
+            }
+            (accesskit::Action::SetValue, Some(ActionData::NumericValue(value))) => {
+                self.set_value_internal(ctx, *value);
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::HoveredChanged(false) => {
+                self.hover_value = None;
+                ctx.request_paint_only();
+            }
+            Update::FocusChanged(_) | Update::DisabledChanged(_) => {
+                ctx.request_paint_only();
+            }
+            Update::LocaleChanged => ctx.request_accessibility_update(),
+            _ => {}
+        }
+    }
+
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let stars = self.max as f64;
+        let width = stars * STAR_SIZE + (stars - 1.).max(0.) * STAR_GAP;
+        bc.constrain(Size::new(width, STAR_SIZE))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let shown_value = self.hover_value.unwrap_or(self.value);
+        let (fill, border) = if ctx.is_disabled() {
+            (
+                ctx.env().get(theme::DISABLED_FOREGROUND_DARK),
+                ctx.env().get(theme::DISABLED_FOREGROUND_DARK),
+            )
+        } else if self.hover_value.is_some() {
+            (
+                ctx.env().get(theme::PRIMARY_DARK),
+                ctx.env().get(theme::BORDER_LIGHT),
+            )
+        } else {
+            (
+                ctx.env().get(theme::PRIMARY_LIGHT),
+                ctx.env().get(theme::BORDER_LIGHT),
+            )
+        };
+
+        for i in 0..self.max {
+            let origin = Point::new(i as f64 * (STAR_SIZE + STAR_GAP), 0.);
+            let star = star_path(origin);
+            let filled = (shown_value - i as f64).clamp(0., 1.);
+            if filled >= 1. {
+                fill_color(scene, &star, fill);
+            } else if filled > 0. {
+                let clip = Rect::from_origin_size(origin, (STAR_SIZE * filled, STAR_SIZE));
+                scene.push_layer(BlendMode::default(), 1., Affine::IDENTITY, &clip);
+                fill_color(scene, &star, fill);
+                scene.pop_layer();
+            }
+            stroke(scene, &star, border, 1.);
+        }
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::Slider
+    }
+
+    fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        node.set_numeric_value(self.value);
+        node.set_min_numeric_value(0.);
+        node.set_max_numeric_value(self.max as f64);
+        node.set_numeric_value_step(self.step());
+        let formatter = ctx.formatter();
+        node.set_value(format!(
+            "{} of {}",
+            formatter.format_shortest(self.value),
+            formatter.format_number(self.max.into(), 0)
+        ));
+        node.add_action(accesskit::Action::Increment);
+        node.add_action(accesskit::Action::Decrement);
+        node.add_action(accesskit::Action::SetValue);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        SmallVec::new()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("Rating")
+    }
+
+    fn get_debug_text(&self) -> Option<String> {
+        Some(format!("{}/{}", self.value, self.max))
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::PointerButton;
+
+    #[test]
+    fn click_and_hover() {
+        let widget = Rating::new(1.).with_half_stars(true);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 40.));
+        let origin = harness.root_widget().ctx().window_origin();
+
+        // The left half of the third star.
+        let pos = origin + (2. * (STAR_SIZE + STAR_GAP) + 5., STAR_SIZE / 2.);
+        harness.mouse_move(pos);
+        {
+            let rating = harness.root_widget();
+            let rating = rating.downcast::<Rating>().unwrap();
+            assert_eq!(rating.hover_value, Some(2.5));
+            assert_eq!(rating.value(), 1.);
+        }
+
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_eq!(
+            harness.pop_action().map(|(action, _)| action),
+            Some(Action::RatingChanged(2.5))
+        );
+    }
+
+    #[test]
+    fn keys_step_and_clamp() {
+        let rating = Rating::new(5.);
+        assert_eq!(
+            rating.value_after_key(&Key::Named(NamedKey::ArrowRight)),
+            Some(5.)
+        );
+        assert_eq!(
+            rating.value_after_key(&Key::Named(NamedKey::ArrowLeft)),
+            Some(4.)
+        );
+        assert_eq!(
+            rating.value_after_key(&Key::Named(NamedKey::Home)),
+            Some(0.)
+        );
+
+        let rating = Rating::new(2.).with_half_stars(true);
+        assert_eq!(
+            rating.value_after_key(&Key::Named(NamedKey::ArrowUp)),
+            Some(2.5)
+        );
+        assert_eq!(rating.value_after_key(&Key::Named(NamedKey::End)), Some(5.));
+        assert_eq!(rating.value_after_key(&Key::Named(NamedKey::Tab)), None);
+    }
+}
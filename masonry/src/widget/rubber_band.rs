@@ -0,0 +1,354 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A container which lets the user select its items by dragging a rectangle over them.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::{smallvec, SmallVec};
+use tracing::{trace_span, Span};
+use vello::Scene;
+use winit::keyboard::ModifiersState;
+
+use crate::gesture::DRAG_THRESHOLD;
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::widget::{WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, Action, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerButton, PointerEvent, Rect, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget,
+    WidgetId,
+};
+
+/// How the items under the rectangle are combined with the previous selection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SelectionMode {
+    /// The items under the rectangle are selected, and no other.
+    Replace,
+    /// The items under the rectangle are added to the selection. This is used with Shift.
+    Extend,
+    /// The items under the rectangle are flipped. This is used with Ctrl, or Cmd on macOS.
+    Toggle,
+}
+
+/// A container which lets the user select the items of its child by dragging a
+/// rectangle over them, like in file managers and canvas editors.
+///
+/// The items are the children of the child widget, e.g. the cells of a [`Grid`](super::Grid),
+/// and are identified by their index. A drag starts on the empty space of the child, so
+/// items can still handle their own presses. The items under the rectangle replace the
+/// selection, or are added to it with Shift, or toggled with Ctrl (Cmd on macOS).
+/// Clicking the empty space without modifiers clears the selection.
+///
+/// Emits [`Action::ItemsSelected`] with all selected indices whenever the selection changes.
+pub struct RubberBand<W: Widget> {
+    child: WidgetPod<W>,
+    band: WidgetPod<BandOverlay>,
+    /// Where the primary button was pressed, in local coordinates.
+    start: Option<Point>,
+    /// The rectangle, in local coordinates, once the pointer moved far enough from `start`.
+    rect: Option<Rect>,
+    selection: Vec<usize>,
+    /// The selection when the drag started, which the items under the rectangle are combined with.
+    base: Vec<usize>,
+    mode: SelectionMode,
+}
+
+/// The translucent rectangle painted over the items.
+struct BandOverlay;
+
+// --- MARK: BUILDERS ---
+impl<W: Widget> RubberBand<W> {
+    pub fn new(child: W) -> Self {
+        Self::new_pod(WidgetPod::new(child))
+    }
+
+    pub fn new_pod(child: WidgetPod<W>) -> Self {
+        Self {
+            child,
+            band: WidgetPod::new(BandOverlay),
+            start: None,
+            rect: None,
+            selection: Vec::new(),
+            base: Vec::new(),
+            mode: SelectionMode::Replace,
+        }
+    }
+
+    /// Builder-style method to set the indices of the selected items.
+    pub fn with_selection(mut self, selection: Vec<usize>) -> Self {
+        self.selection = normalized(selection);
+        self
+    }
+
+    /// The indices of the selected items, in increasing order.
+    pub fn selection(&self) -> &[usize] {
+        &self.selection
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<W: Widget> RubberBand<W> {
+    pub fn child_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> WidgetMut<'t, W> {
+        this.ctx.get_mut(&mut this.widget.child)
+    }
+
+    /// Set the indices of the selected items, without emitting an action.
+    pub fn set_selection(this: &mut WidgetMut<'_, Self>, selection: Vec<usize>) {
+        this.widget.selection = normalized(selection);
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl SelectionMode {
+    fn from_modifiers(mods: ModifiersState) -> Self {
+        if mods.control_key() || mods.super_key() {
+            Self::Toggle
+        } else if mods.shift_key() {
+            Self::Extend
+        } else {
+            Self::Replace
+        }
+    }
+
+    /// The selection resulting from dragging over `items`, starting from `base`.
+    fn combine(self, base: &[usize], items: &[usize]) -> Vec<usize> {
+        match self {
+            Self::Replace => items.to_vec(),
+            Self::Extend => normalized(base.iter().chain(items).copied().collect()),
+            Self::Toggle => {
+                let mut selection: Vec<usize> = base
+                    .iter()
+                    .filter(|index| !items.contains(index))
+                    .chain(items.iter().filter(|index| !base.contains(index)))
+                    .copied()
+                    .collect();
+                selection.sort_unstable();
+                selection
+            }
+        }
+    }
+}
+
+fn normalized(mut selection: Vec<usize>) -> Vec<usize> {
+    selection.sort_unstable();
+    selection.dedup();
+    selection
+}
+
+impl<W: Widget> RubberBand<W> {
+    fn set_selected(&mut self, ctx: &mut EventCtx, selection: Vec<usize>) {
+        if selection != self.selection {
+            self.selection = selection;
+            ctx.submit_action(Action::ItemsSelected(self.selection.clone()));
+        }
+    }
+
+    fn end_drag(&mut self, ctx: &mut EventCtx) {
+        self.start = None;
+        if self.rect.take().is_some() {
+            ctx.request_layout();
+        }
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<W: Widget> Widget for RubberBand<W> {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let local = |ctx: &EventCtx, state: &crate::PointerState| {
+            Point::new(state.position.x, state.position.y) - ctx.window_origin().to_vec2()
+        };
+        match event {
+            PointerEvent::PointerDown(PointerButton::Primary, state) => {
+                // Presses on the items are theirs to handle.
+                if ctx.target() != ctx.widget_id() && ctx.target() != self.child.id() {
+                    return;
+                }
+                ctx.capture_pointer();
+                self.start = Some(local(ctx, state));
+                self.base = self.selection.clone();
+                self.mode = SelectionMode::from_modifiers(state.mods.state());
+            }
+            PointerEvent::PointerMove(state) => {
+                let Some(start) = self.start else {
+                    return;
+                };
+                let position = local(ctx, state);
+                if self.rect.is_none() && (position - start).hypot() <= DRAG_THRESHOLD {
+                    return;
+                }
+                let rect = Rect::from_points(start, position);
+                self.rect = Some(rect);
+                ctx.request_layout();
+                let items = ctx.items_in_rect(&self.child, rect + ctx.window_origin().to_vec2());
+                let selection = self.mode.combine(&self.base, &items);
+                self.set_selected(ctx, selection);
+                ctx.set_handled();
+            }
+            PointerEvent::PointerUp(PointerButton::Primary, _) => {
+                if self.start.is_some()
+                    && self.rect.is_none()
+                    && self.mode == SelectionMode::Replace
+                {
+                    self.set_selected(ctx, Vec::new());
+                }
+                self.end_drag(ctx);
+            }
+            PointerEvent::PointerLeave(_) => self.end_drag(ctx),
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _event: &Update) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        ctx.register_child(&mut self.child);
+        ctx.register_child(&mut self.band);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let size = ctx.run_layout(&mut self.child, bc);
+        ctx.place_child(&mut self.child, Point::ORIGIN);
+        let rect = self.rect.unwrap_or(Rect::ZERO);
+        ctx.run_layout(&mut self.band, &BoxConstraints::tight(rect.size()));
+        ctx.place_child(&mut self.band, rect.origin());
+        let baseline = ctx.child_baseline_offset(&self.child);
+        ctx.set_baseline_offset(baseline);
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        smallvec![self.child.id(), self.band.id()]
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("RubberBand")
+    }
+}
+
+impl Widget for BandOverlay {
+    fn on_pointer_event(&mut self, _ctx: &mut EventCtx, _event: &PointerEvent) {}
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        if let Update::WidgetAdded = event {
+            ctx.set_pointer_transparent(true);
+        }
+    }
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        bc.max()
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let rect = ctx.size().to_rect();
+        if rect.is_zero_area() {
+            return;
+        }
+        fill_color(
+            scene,
+            &rect,
+            ctx.env().get(theme::PRIMARY_LIGHT).with_alpha_factor(0.2),
+        );
+        stroke(
+            scene,
+            &rect.inset(-0.5),
+            ctx.env().get(theme::PRIMARY_LIGHT),
+            1.,
+        );
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::GenericContainer
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, _node: &mut NodeBuilder) {}
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        SmallVec::new()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("BandOverlay")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::widget::{Flex, SizedBox};
+
+    #[test]
+    fn combine_with_modifiers() {
+        let base = [1, 3];
+        let items = [2, 3];
+        assert_eq!(SelectionMode::Replace.combine(&base, &items), vec![2, 3]);
+        assert_eq!(SelectionMode::Extend.combine(&base, &items), vec![1, 2, 3]);
+        assert_eq!(SelectionMode::Toggle.combine(&base, &items), vec![1, 2]);
+        assert_eq!(
+            SelectionMode::from_modifiers(ModifiersState::SHIFT),
+            SelectionMode::Extend
+        );
+        assert_eq!(
+            SelectionMode::from_modifiers(ModifiersState::CONTROL | ModifiersState::SHIFT),
+            SelectionMode::Toggle
+        );
+    }
+
+    #[test]
+    fn drag_selects_items() {
+        let item = || SizedBox::empty().width(40.).height(40.);
+        let items = Flex::row()
+            .with_child(item())
+            .with_spacer(20.)
+            .with_child(item())
+            .with_spacer(20.)
+            .with_child(item())
+            .with_flex_spacer(1.);
+        let widget = RubberBand::new(items).with_selection(vec![4]);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 40.));
+        let band_id = harness.root_widget().id();
+        // A drag from the empty space at the end over the last two items.
+        harness.mouse_move((198., 20.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_move((170., 10.));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ItemsSelected(vec![2]), band_id))
+        );
+        harness.mouse_move((100., 10.));
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ItemsSelected(vec![1, 2]), band_id))
+        );
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_eq!(harness.pop_action(), None);
+
+        // A click on the empty space clears the selection.
+        harness.mouse_move((198., 20.));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_eq!(
+            harness.pop_action(),
+            Some((Action::ItemsSelected(vec![]), band_id))
+        );
+    }
+}
@@ -3,19 +3,27 @@
 
 #![allow(missing_docs)]
 
+use std::time::Duration;
+
 use accesskit::{NodeBuilder, Role};
 use smallvec::SmallVec;
 use tracing::{trace_span, Span};
 use vello::kurbo::Rect;
+use vello::peniko::Color;
 use vello::Scene;
 
+use crate::animation::{AnimatedValue, Easing, Transition};
 use crate::paint_scene_helpers::{fill_color, stroke};
 use crate::widget::{Axis, WidgetMut};
 use crate::{
-    theme, AccessCtx, AccessEvent, AllowRawMut, BoxConstraints, EventCtx, LayoutCtx, PaintCtx,
-    Point, PointerEvent, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+    theme, AccessCtx, AccessEvent, AllowRawMut, BoxConstraints, Env, EventCtx, LayoutCtx, PaintCtx,
+    Point, PointerEvent, RegisterCtx, Size, TextEvent, TimerToken, Update, UpdateCtx, Widget,
+    WidgetId,
 };
 
+/// How auto-hidden scrollbars fade in and out.
+const FADE_TRANSITION: Transition = Transition::new(Duration::from_millis(200), Easing::EaseOut);
+
 // RULES
 // -
 
@@ -25,7 +33,61 @@ use crate::{
 // - _z
 // - _length
 
-// TODO - Fade scrollbars? Find out how Linux/MacOS/Windows do it
+/// How a [`ScrollBar`] looks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollBarStyle {
+    /// The width of the bar, across its axis.
+    pub thickness: f64,
+    /// The width of the bar while it's hovered or dragged, the same as
+    /// [`thickness`](Self::thickness) by default.
+    ///
+    /// The bar can be grabbed anywhere in this width, even when it's thinner.
+    pub hover_thickness: f64,
+    /// The space between the bar and the edges of the scrollbar.
+    pub padding: f64,
+    /// The minimum length of the bar, so that it stays easy to grab over long content.
+    pub min_thumb_length: f64,
+    pub radius: f64,
+    pub color: Color,
+    pub border_color: Color,
+    pub border_width: f64,
+}
+
+impl ScrollBarStyle {
+    /// The style made of the scrollbar theme values of `env`.
+    pub fn from_env(env: &Env) -> Self {
+        Self {
+            thickness: env.get(theme::SCROLLBAR_WIDTH),
+            hover_thickness: env.get(theme::SCROLLBAR_WIDTH),
+            padding: env.get(theme::SCROLLBAR_PAD),
+            min_thumb_length: env.get(theme::SCROLLBAR_MIN_SIZE),
+            radius: env.get(theme::SCROLLBAR_RADIUS),
+            color: env.get(theme::SCROLLBAR_COLOR),
+            border_color: env.get(theme::SCROLLBAR_BORDER_COLOR),
+            border_width: env.get(theme::SCROLLBAR_EDGE_WIDTH),
+        }
+    }
+}
+
+impl Default for ScrollBarStyle {
+    fn default() -> Self {
+        Self::from_env(&Env::new())
+    }
+}
+
+/// When a [`ScrollBar`] is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScrollBarMode {
+    /// The scrollbar is always shown.
+    #[default]
+    Visible,
+    /// The scrollbar is hidden, fades in to
+    /// [`SCROLLBAR_MAX_OPACITY`](theme::SCROLLBAR_MAX_OPACITY) while the content scrolls
+    /// or the scrollbar is hovered, and fades out after [`SCROLLBAR_FADE_DELAY`](theme::SCROLLBAR_FADE_DELAY)
+    /// milliseconds without activity.
+    AutoHide,
+}
+
 // TODO - Rename cursor to oval/rect/bar/grabber/grabbybar
 // TODO - Rename progress to ???
 pub struct ScrollBar {
@@ -35,6 +97,13 @@ pub struct ScrollBar {
     pub(crate) portal_size: f64,
     pub(crate) content_size: f64,
     grab_anchor: Option<f64>,
+    /// The style set on this widget, which takes precedence over the env.
+    style: Option<ScrollBarStyle>,
+    mode: ScrollBarMode,
+    opacity: AnimatedValue<f64>,
+    /// Set when the bar is revealed, until the next animation frame restarts the fade timer.
+    restart_fade_timer: bool,
+    fade_timer: Option<TimerToken>,
 }
 
 // --- MARK: BUILDERS ---
@@ -47,9 +116,30 @@ impl ScrollBar {
             portal_size,
             content_size,
             grab_anchor: None,
+            style: None,
+            mode: ScrollBarMode::Visible,
+            opacity: {
+                let mut opacity = AnimatedValue::new(1.);
+                opacity.set_transition(Some(FADE_TRANSITION));
+                opacity
+            },
+            restart_fade_timer: false,
+            fade_timer: None,
         }
     }
 
+    /// Builder-style method to set how the scrollbar looks.
+    pub fn with_style(mut self, style: ScrollBarStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Builder-style method to set when the scrollbar is shown.
+    pub fn with_mode(mut self, mode: ScrollBarMode) -> Self {
+        self.set_mode_raw(mode);
+        self
+    }
+
     /// Returns how far the scrollbar is from its initial point.
     ///
     /// Values range from 0.0 (beginning) to 1.0 (end).
@@ -59,6 +149,36 @@ impl ScrollBar {
 }
 
 impl ScrollBar {
+    fn style(&self, env: &Env) -> ScrollBarStyle {
+        self.style.unwrap_or_else(|| ScrollBarStyle::from_env(env))
+    }
+
+    fn set_mode_raw(&mut self, mode: ScrollBarMode) {
+        self.mode = mode;
+        self.opacity.jump_to(match mode {
+            ScrollBarMode::Visible => 1.,
+            ScrollBarMode::AutoHide => 0.,
+        });
+    }
+
+    /// Show the scrollbar in [`ScrollBarMode::AutoHide`], and restart the delay
+    /// before it fades out.
+    ///
+    /// Returns `true` if the caller must request an animation frame for the scrollbar.
+    pub(crate) fn reveal(&mut self) -> bool {
+        if self.mode != ScrollBarMode::AutoHide {
+            return false;
+        }
+        self.opacity.set_target(theme::SCROLLBAR_MAX_OPACITY);
+        self.restart_fade_timer = true;
+        true
+    }
+
+    /// The opacity of the scrollbar, which changes in [`ScrollBarMode::AutoHide`].
+    pub fn opacity(&self) -> f64 {
+        self.opacity.value()
+    }
+
     fn get_cursor_rect(&self, layout_size: Size, min_length: f64) -> Rect {
         // TODO - handle invalid sizes
         let size_ratio = self.portal_size / self.content_size;
@@ -111,6 +231,24 @@ impl ScrollBar {
         this.ctx.request_render();
     }
 
+    /// Set how the scrollbar looks.
+    pub fn set_style(this: &mut WidgetMut<'_, Self>, style: ScrollBarStyle) {
+        this.widget.style = Some(style);
+        this.ctx.request_layout();
+    }
+
+    /// Make the scrollbar look like the theme values of its env again.
+    pub fn reset_style(this: &mut WidgetMut<'_, Self>) {
+        this.widget.style = None;
+        this.ctx.request_layout();
+    }
+
+    /// Set when the scrollbar is shown.
+    pub fn set_mode(this: &mut WidgetMut<'_, Self>, mode: ScrollBarMode) {
+        this.widget.set_mode_raw(mode);
+        this.ctx.request_render();
+    }
+
     // TODO - Remove?
     pub fn set_content_size(this: &mut WidgetMut<'_, Self>, content_size: f64) {
         // TODO - cursor_progress
@@ -126,7 +264,7 @@ impl Widget for ScrollBar {
             PointerEvent::PointerDown(_, state) => {
                 ctx.capture_pointer();
 
-                let cursor_min_length = theme::SCROLLBAR_MIN_SIZE;
+                let cursor_min_length = self.style(ctx.env()).min_thumb_length;
                 let cursor_rect = self.get_cursor_rect(ctx.size(), cursor_min_length);
 
                 let mouse_pos =
@@ -147,7 +285,7 @@ impl Widget for ScrollBar {
                 let mouse_pos =
                     Point::new(state.position.x, state.position.y) - ctx.window_origin().to_vec2();
                 if let Some(grab_anchor) = self.grab_anchor {
-                    let cursor_min_length = theme::SCROLLBAR_MIN_SIZE;
+                    let cursor_min_length = self.style(ctx.env()).min_thumb_length;
                     self.cursor_progress = self.progress_from_mouse_pos(
                         ctx.size(),
                         cursor_min_length,
@@ -160,6 +298,9 @@ impl Widget for ScrollBar {
             }
             PointerEvent::PointerUp(_, _) => {
                 self.grab_anchor = None;
+                if self.reveal() {
+                    ctx.request_anim_frame();
+                }
                 ctx.request_render();
             }
             _ => {}
@@ -174,39 +315,86 @@ impl Widget for ScrollBar {
 
     fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
 
-    fn update(&mut self, _ctx: &mut UpdateCtx, _event: &Update) {}
+    fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
+        if self.restart_fade_timer {
+            self.restart_fade_timer = false;
+            if let Some(token) = self.fade_timer.take() {
+                ctx.cancel_timer(token);
+            }
+            let delay = Duration::from_millis(theme::SCROLLBAR_FADE_DELAY);
+            self.fade_timer = Some(ctx.request_timer(delay));
+        }
+        if self.opacity.advance(interval) {
+            ctx.request_render();
+        }
+        if self.opacity.is_animating() {
+            ctx.request_anim_frame();
+        }
+    }
 
-    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::HoveredChanged(_) => {
+                if self.reveal() {
+                    ctx.request_anim_frame();
+                }
+                ctx.request_render();
+            }
+            Update::Timer(token) if self.fade_timer == Some(*token) => {
+                self.fade_timer = None;
+                // The scrollbar stays while it's hovered or dragged.
+                if ctx.is_hovered() || self.grab_anchor.is_some() {
+                    return;
+                }
+                if self.opacity.set_target(0.) {
+                    ctx.request_anim_frame();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         // TODO - handle resize
 
-        let scrollbar_width = theme::SCROLLBAR_WIDTH;
-        let cursor_padding = theme::SCROLLBAR_PAD;
+        let style = self.style(ctx.env());
+        let scrollbar_width = style.thickness.max(style.hover_thickness);
         self.axis
             .pack(
                 self.axis.major(bc.max()),
-                scrollbar_width + cursor_padding * 2.0,
+                scrollbar_width + style.padding * 2.0,
             )
             .into()
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
-        let radius = theme::SCROLLBAR_RADIUS;
-        let edge_width = theme::SCROLLBAR_EDGE_WIDTH;
-        let cursor_padding = theme::SCROLLBAR_PAD;
-        let cursor_min_length = theme::SCROLLBAR_MIN_SIZE;
-
-        let (inset_x, inset_y) = self.axis.pack(0.0, cursor_padding);
-        let cursor_rect = self
-            .get_cursor_rect(ctx.size(), cursor_min_length)
-            .inset((-inset_x, -inset_y))
-            .to_rounded_rect(radius);
-
-        fill_color(scene, &cursor_rect, theme::SCROLLBAR_COLOR);
+        let opacity = self.opacity.value() as f32;
+        if opacity <= 0. {
+            return;
+        }
+        let style = self.style(ctx.env());
+        let thickness = if ctx.is_hovered() || self.grab_anchor.is_some() {
+            style.hover_thickness
+        } else {
+            style.thickness
+        };
+
+        // The bar is painted against the outer edge, and gets thicker towards the content.
+        let cursor_rect = self.get_cursor_rect(ctx.size(), style.min_thumb_length);
+        let (z0, z1) = self.axis.major_span(cursor_rect);
+        let outer = self.axis.minor(ctx.size()) - style.padding;
+        let cursor_rect = Rect::from_points(
+            self.axis.pack(z0, outer - thickness),
+            self.axis.pack(z1, outer),
+        )
+        .to_rounded_rect(style.radius);
+
+        fill_color(scene, &cursor_rect, style.color.with_alpha_factor(opacity));
         stroke(
             scene,
             &cursor_rect,
-            theme::SCROLLBAR_BORDER_COLOR,
-            edge_width,
+            style.border_color.with_alpha_factor(opacity),
+            style.border_width,
         );
     }
 
@@ -286,6 +474,34 @@ mod tests {
         assert_render_snapshot!(harness, "scrollbar_horizontal_middle");
     }
 
+    #[test]
+    fn auto_hide_scrollbar_fades() {
+        let [scrollbar_id] = widget_ids();
+        let widget = ScrollBar::new(Axis::Vertical, 200.0, 600.0)
+            .with_mode(ScrollBarMode::AutoHide)
+            .with_id(scrollbar_id);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(50.0, 200.0));
+        let opacity = |harness: &TestHarness| {
+            let scrollbar = harness.get_widget(scrollbar_id);
+            scrollbar.downcast::<ScrollBar>().unwrap().opacity()
+        };
+        assert_eq!(opacity(&harness), 0.);
+
+        // Hovering the scrollbar reveals it, and it stays while it's hovered.
+        harness.mouse_move(Point::new(10.0, 10.0));
+        harness.animate_ms(200);
+        assert_eq!(opacity(&harness), theme::SCROLLBAR_MAX_OPACITY);
+        harness.move_timers_forward(Duration::from_millis(theme::SCROLLBAR_FADE_DELAY));
+        harness.animate_ms(200);
+        assert_eq!(opacity(&harness), theme::SCROLLBAR_MAX_OPACITY);
+
+        harness.mouse_move(Point::new(100.0, 300.0));
+        harness.animate_ms(16);
+        harness.move_timers_forward(Duration::from_millis(theme::SCROLLBAR_FADE_DELAY));
+        harness.animate_ms(200);
+        assert_eq!(opacity(&harness), 0.);
+    }
+
     // TODO - portal larger than content
 
     #[cfg(FALSE)]
@@ -294,7 +510,7 @@ mod tests {
         let image_1 = {
             let button = Button::from_label(
                 Label::new("The quick brown fox jumps over the lazy dog")
-                    .with_text_color(PRIMARY_LIGHT)
+                    .with_text_color(*PRIMARY_LIGHT.default_value())
                     .with_text_size(20.0),
             );
 
@@ -313,7 +529,7 @@ mod tests {
                 button.set_text("The quick brown fox jumps over the lazy dog");
 
                 let mut label = button.label_mut();
-                label.set_text_color(PRIMARY_LIGHT);
+                label.set_text_color(*PRIMARY_LIGHT.default_value());
                 label.set_text_size(20.0);
             });
 
@@ -0,0 +1,409 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A row of mutually exclusive options.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::kurbo::Line;
+use vello::Scene;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::action::Action;
+use crate::paint_scene_helpers::{fill_lin_gradient, stroke, UnitPoint};
+use crate::text::ArcStr;
+use crate::widget::{ButtonVariant, Label, WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, EventCtx, Insets, LayoutCtx, PaintCtx, Point,
+    PointerButton, PointerEvent, Rect, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget,
+    WidgetId,
+};
+
+const LABEL_INSETS: Insets = Insets::uniform_xy(12., 2.);
+
+/// A row of joined buttons, of which at most one is selected.
+///
+/// All segments have the width of the widest one. When the widget has focus, the
+/// arrow keys select the previous or next segment, and Home and End select the first
+/// and last ones.
+///
+/// Emits [`Action::SegmentSelected`] with the index of the segment when the user
+/// selects a different segment.
+pub struct SegmentedControl {
+    segments: Vec<WidgetPod<Label>>,
+    segment_rects: Vec<Rect>,
+    selected: Option<usize>,
+    pressed: Option<usize>,
+    label: Option<ArcStr>,
+}
+
+// --- MARK: BUILDERS ---
+impl SegmentedControl {
+    /// Create a segmented control with the given segments, none of which is selected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use masonry::widget::SegmentedControl;
+    ///
+    /// let control = SegmentedControl::new(["Day", "Week", "Month"]).with_selected(Some(1));
+    /// ```
+    pub fn new(segments: impl IntoIterator<Item = impl Into<ArcStr>>) -> Self {
+        Self {
+            segments: segments
+                .into_iter()
+                .map(|text| WidgetPod::new(Label::new(text)))
+                .collect(),
+            segment_rects: Vec::new(),
+            selected: None,
+            pressed: None,
+            label: None,
+        }
+    }
+
+    /// Builder-style method for setting the selected segment.
+    pub fn with_selected(mut self, selected: Option<usize>) -> Self {
+        self.selected = selected.filter(|&index| index < self.segments.len());
+        self
+    }
+
+    /// Builder-style method to set the name of the control read by screen readers,
+    /// e.g. "View by".
+    pub fn with_label(mut self, label: impl Into<ArcStr>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// The index of the selected segment, if any.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    fn segment_at(&self, pos: Point) -> Option<usize> {
+        self.segment_rects
+            .iter()
+            .position(|rect| rect.contains(pos))
+    }
+
+    /// The segment selected after pressing `key`, if the key changes the selection.
+    fn selected_after_key(&self, key: &Key) -> Option<usize> {
+        let last = self.segments.len().checked_sub(1)?;
+        let Key::Named(key) = key else {
+            return None;
+        };
+        let selected = match (key, self.selected) {
+            (NamedKey::ArrowRight | NamedKey::ArrowDown, Some(index)) => (index + 1).min(last),
+            (NamedKey::ArrowLeft | NamedKey::ArrowUp, Some(index)) => index.saturating_sub(1),
+            (NamedKey::ArrowRight | NamedKey::ArrowDown, None) => 0,
+            (NamedKey::ArrowLeft | NamedKey::ArrowUp, None) => last,
+            (NamedKey::Home, _) => 0,
+            (NamedKey::End, _) => last,
+            _ => return None,
+        };
+        Some(selected).filter(|&selected| self.selected != Some(selected))
+    }
+
+    fn select(&mut self, ctx: &mut EventCtx, index: usize) {
+        if self.selected != Some(index) {
+            self.selected = Some(index);
+            ctx.submit_action(Action::SegmentSelected(index));
+            ctx.request_render();
+        }
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl SegmentedControl {
+    /// Replace the segments.
+    ///
+    /// The selection is kept if the selected index is still valid.
+    pub fn set_segments(
+        this: &mut WidgetMut<'_, Self>,
+        segments: impl IntoIterator<Item = impl Into<ArcStr>>,
+    ) {
+        for segment in this.widget.segments.drain(..) {
+            this.ctx.remove_child(segment);
+        }
+        this.widget.segments = segments
+            .into_iter()
+            .map(|text| WidgetPod::new(Label::new(text)))
+            .collect();
+        let len = this.widget.segments.len();
+        this.widget.selected = this.widget.selected.filter(|&index| index < len);
+        this.widget.pressed = None;
+        this.ctx.children_changed();
+    }
+
+    /// Set the selected segment.
+    pub fn set_selected(this: &mut WidgetMut<'_, Self>, selected: Option<usize>) {
+        let len = this.widget.segments.len();
+        this.widget.selected = selected.filter(|&index| index < len);
+        this.ctx.request_render();
+    }
+
+    /// Set the name of the control read by screen readers.
+    pub fn set_label(this: &mut WidgetMut<'_, Self>, label: Option<ArcStr>) {
+        this.widget.label = label;
+        this.ctx.request_accessibility_update();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for SegmentedControl {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let local_pos = event
+            .position()
+            .map(|pos| Point::new(pos.x, pos.y) - ctx.window_origin().to_vec2());
+        match event {
+            PointerEvent::PointerDown(PointerButton::Primary, _) => {
+                let segment = local_pos.and_then(|pos| self.segment_at(pos));
+                if segment.is_some() && !ctx.is_disabled() {
+                    self.pressed = segment;
+                    ctx.capture_pointer();
+                    ctx.request_focus();
+                    ctx.request_paint_only();
+                }
+            }
+            PointerEvent::PointerUp(_, _) => {
+                let pressed = self.pressed.take();
+                let segment = local_pos.and_then(|pos| self.segment_at(pos));
+                if ctx.has_pointer_capture() && pressed.is_some() && pressed == segment {
+                    if let Some(index) = segment {
+                        self.select(ctx, index);
+                    }
+                }
+                ctx.request_paint_only();
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        let TextEvent::KeyboardKey(event, _) = event else {
+            return;
+        };
+        if !event.state.is_pressed() || ctx.is_disabled() {
+            return;
+        }
+        if let Some(index) = self.selected_after_key(&event.logical_key) {
+            self.select(ctx, index);
+            ctx.set_handled();
+        }
+    }
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
+        if event.action == accesskit::Action::Default {
+            let target = ctx.target();
+            let index = self
+                .segments
+                .iter()
+                .position(|segment| segment.id() == target);
+            if let Some(index) = index {
+                self.select(ctx, index);
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::HoveredChanged(_) | Update::FocusChanged(_) | Update::DisabledChanged(_) => {
+                ctx.request_paint_only();
+            }
+            _ => {}
+        }
+    }
+
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        for segment in &mut self.segments {
+            ctx.register_child(segment);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let count = self.segments.len().max(1) as f64;
+        let padding = Size::new(LABEL_INSETS.x_value(), LABEL_INSETS.y_value());
+        let label_bc = BoxConstraints::new(
+            Size::ZERO,
+            Size::new(
+                (bc.max().width / count - padding.width).max(0.),
+                (bc.max().height - padding.height).max(0.),
+            ),
+        );
+
+        let mut sizes = Vec::with_capacity(self.segments.len());
+        for segment in &mut self.segments {
+            sizes.push(ctx.run_layout(segment, &label_bc));
+        }
+        let max_label = sizes.iter().fold(Size::ZERO, |max, size| {
+            Size::new(max.width.max(size.width), max.height.max(size.height))
+        });
+        let height =
+            (max_label.height + padding.height).max(ctx.env().get(theme::BORDERED_WIDGET_HEIGHT));
+        let size = bc.constrain(Size::new(
+            (max_label.width + padding.width) * self.segments.len() as f64,
+            height,
+        ));
+        let segment_width = size.width / count;
+
+        self.segment_rects.clear();
+        for (i, (segment, label_size)) in self.segments.iter_mut().zip(sizes).enumerate() {
+            let rect = Rect::new(
+                i as f64 * segment_width,
+                0.,
+                (i + 1) as f64 * segment_width,
+                size.height,
+            );
+            ctx.place_child(
+                segment,
+                Point::new(
+                    rect.x0 + (segment_width - label_size.width) / 2.,
+                    (size.height - label_size.height) / 2.,
+                ),
+            );
+            self.segment_rects.push(rect);
+        }
+        if let Some(first) = self.segments.first() {
+            let baseline = ctx.child_baseline_offset(first);
+            ctx.set_baseline_offset(baseline + LABEL_INSETS.y1);
+        }
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let is_disabled = ctx.is_disabled();
+        let stroke_width = ctx.env().get(theme::BUTTON_BORDER_WIDTH);
+        let radius = ctx.env().get(theme::BUTTON_RADIUS);
+        let outline = ctx
+            .size()
+            .to_rect()
+            .inset(-stroke_width / 2.)
+            .to_rounded_rect(radius);
+
+        if let Some(background) =
+            ButtonVariant::Secondary.background(ctx.env(), false, false, is_disabled)
+        {
+            fill_lin_gradient(
+                scene,
+                &outline,
+                background,
+                UnitPoint::TOP,
+                UnitPoint::BOTTOM,
+            );
+        }
+        for (i, rect) in self.segment_rects.iter().enumerate() {
+            let is_selected = self.selected == Some(i);
+            let is_pressed = self.pressed == Some(i) && ctx.has_pointer_capture();
+            if !is_selected && !is_pressed {
+                continue;
+            }
+            let variant = if is_selected {
+                ButtonVariant::Primary
+            } else {
+                ButtonVariant::Secondary
+            };
+            let highlight = rect.inset(-stroke_width).to_rounded_rect(radius / 2.);
+            if let Some(background) = variant.background(ctx.env(), false, is_pressed, is_disabled)
+            {
+                fill_lin_gradient(
+                    scene,
+                    &highlight,
+                    background,
+                    UnitPoint::TOP,
+                    UnitPoint::BOTTOM,
+                );
+            }
+        }
+
+        let border_color = if ctx.is_hovered() && !is_disabled {
+            ctx.env().get(theme::BORDER_LIGHT)
+        } else {
+            ctx.env().get(theme::BORDER_DARK)
+        };
+        for rect in self.segment_rects.iter().skip(1) {
+            let divider = Line::new(
+                (rect.x0, rect.y0 + stroke_width),
+                (rect.x0, rect.y1 - stroke_width),
+            );
+            stroke(scene, &divider, border_color, 1.);
+        }
+        stroke(scene, &outline, border_color, stroke_width);
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::RadioGroup
+    }
+
+    fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        if let Some(label) = &self.label {
+            node.set_name(label.to_string());
+        }
+        // The segments are labels, so the selection is described here.
+        if let Some(selected) = self.selected.and_then(|index| self.segments.get(index)) {
+            let text = ctx
+                .get_raw_ref(selected)
+                .widget()
+                .text()
+                .as_ref()
+                .to_string();
+            node.set_value(text);
+        }
+        node.set_orientation(accesskit::Orientation::Horizontal);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        self.segments.iter().map(WidgetPod::id).collect()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("SegmentedControl")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestHarness;
+
+    #[test]
+    fn click_selects_segment() {
+        let widget = SegmentedControl::new(["Day", "Week", "Month"]).with_selected(Some(0));
+        let mut harness = TestHarness::create_with_size(widget, Size::new(300., 40.));
+        let rect = harness.root_widget().ctx().window_layout_rect();
+
+        harness.mouse_move((rect.x1 - 5., rect.center().y));
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_eq!(
+            harness.pop_action().map(|(action, _)| action),
+            Some(Action::SegmentSelected(2))
+        );
+
+        // Selecting the same segment again doesn't emit an action.
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_eq!(harness.pop_action(), None);
+    }
+
+    #[test]
+    fn keys_move_selection() {
+        let control = SegmentedControl::new(["A", "B", "C"]);
+        let right = Key::Named(NamedKey::ArrowRight);
+        let left = Key::Named(NamedKey::ArrowLeft);
+        assert_eq!(control.selected_after_key(&right), Some(0));
+        assert_eq!(control.selected_after_key(&left), Some(2));
+
+        let control = control.with_selected(Some(2));
+        assert_eq!(control.selected_after_key(&right), None);
+        assert_eq!(control.selected_after_key(&left), Some(1));
+        assert_eq!(
+            control.selected_after_key(&Key::Named(NamedKey::Home)),
+            Some(0)
+        );
+    }
+}
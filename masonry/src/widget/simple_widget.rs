@@ -0,0 +1,298 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A leaf widget built from closures.
+
+use accesskit::{NodeBuilder, Role};
+use cursor_icon::CursorIcon;
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::Scene;
+
+use crate::testing::ModularWidget;
+use crate::widget::WidgetMut;
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point,
+    PointerEvent, QueryCtx, RegisterCtx, Size, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// A widget without children, whose behavior is supplied as closures.
+///
+/// This is meant for one-off custom widgets, such as a small chart or a decoration,
+/// which don't warrant implementing [`Widget`]. Each closure receives the widget's
+/// `state` and the same context as the matching `Widget` method, so it can request
+/// paints, animation frames, or submit actions like any other widget.
+///
+/// Any behavior left unset uses a default: the widget has a size of
+/// [`BASIC_WIDGET_HEIGHT`](theme::BASIC_WIDGET_HEIGHT) squared (see
+/// [`with_preferred_size`](Self::with_preferred_size)), paints nothing, and ignores events.
+///
+/// This is a thin layer over [`ModularWidget`] with defaults suited to real widgets.
+/// Widgets with children should implement `Widget` instead.
+pub struct SimpleWidget<S> {
+    inner: ModularWidget<SimpleState<S>>,
+    cursor: CursorIcon,
+}
+
+struct SimpleState<S> {
+    state: S,
+    preferred_size: Size,
+}
+
+// --- MARK: BUILDERS ---
+impl<S: 'static> SimpleWidget<S> {
+    /// Create a new widget holding `state`, which is passed to every closure.
+    pub fn new(state: S) -> Self {
+        let state = SimpleState {
+            state,
+            preferred_size: Size::new(
+                *theme::BASIC_WIDGET_HEIGHT.default_value(),
+                *theme::BASIC_WIDGET_HEIGHT.default_value(),
+            ),
+        };
+        Self {
+            inner: ModularWidget::new(state)
+                .layout_fn(|state, _, bc| bc.constrain(state.preferred_size)),
+            cursor: CursorIcon::Default,
+        }
+    }
+
+    /// Builder-style method for setting the size used when there is no layout closure.
+    ///
+    /// The size is constrained to the box constraints given by the parent.
+    pub fn with_preferred_size(mut self, size: Size) -> Self {
+        self.inner.state_mut().preferred_size = size;
+        self
+    }
+
+    /// Builder-style method for setting the accessibility role, [`Role::Unknown`] by default.
+    pub fn with_role(mut self, role: Role) -> Self {
+        self.inner = self.inner.role_fn(move |_| role);
+        self
+    }
+
+    /// Builder-style method for setting the cursor shown when the widget is hovered.
+    pub fn with_cursor(mut self, cursor: CursorIcon) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Builder-style method for setting whether the widget can be focused, e.g. to receive
+    /// keyboard events in [`text_event_fn`](Self::text_event_fn).
+    pub fn accepts_focus(mut self, accepts_focus: bool) -> Self {
+        self.inner = self.inner.accepts_focus(accepts_focus);
+        self
+    }
+
+    /// See [`Widget::on_pointer_event`].
+    pub fn pointer_event_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut EventCtx, &PointerEvent) + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .pointer_event_fn(move |state, ctx, event| f(&mut state.state, ctx, event));
+        self
+    }
+
+    /// See [`Widget::on_text_event`].
+    pub fn text_event_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut EventCtx, &TextEvent) + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .text_event_fn(move |state, ctx, event| f(&mut state.state, ctx, event));
+        self
+    }
+
+    /// See [`Widget::on_access_event`].
+    pub fn access_event_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut EventCtx, &AccessEvent) + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .access_event_fn(move |state, ctx, event| f(&mut state.state, ctx, event));
+        self
+    }
+
+    /// See [`Widget::on_anim_frame`].
+    pub fn anim_frame_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut UpdateCtx, u64) + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .anim_frame_fn(move |state, ctx, interval| f(&mut state.state, ctx, interval));
+        self
+    }
+
+    /// See [`Widget::update`].
+    pub fn update_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut UpdateCtx, &Update) + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .update_fn(move |state, ctx, event| f(&mut state.state, ctx, event));
+        self
+    }
+
+    /// See [`Widget::layout`].
+    ///
+    /// This replaces the [preferred size](Self::with_preferred_size).
+    pub fn layout_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut LayoutCtx, &BoxConstraints) -> Size + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .layout_fn(move |state, ctx, bc| f(&mut state.state, ctx, bc));
+        self
+    }
+
+    /// See [`Widget::paint`].
+    pub fn paint_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut PaintCtx, &mut Scene) + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .paint_fn(move |state, ctx, scene| f(&mut state.state, ctx, scene));
+        self
+    }
+
+    /// See [`Widget::accessibility`].
+    pub fn access_fn(
+        mut self,
+        mut f: impl FnMut(&mut S, &mut AccessCtx, &mut NodeBuilder) + 'static,
+    ) -> Self {
+        self.inner = self
+            .inner
+            .access_fn(move |state, ctx, node| f(&mut state.state, ctx, node));
+        self
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl<S: 'static> SimpleWidget<S> {
+    /// Get a mutable reference to the state, and request a new layout and paint.
+    pub fn state_mut<'t>(this: &'t mut WidgetMut<'_, Self>) -> &'t mut S {
+        this.ctx.request_layout();
+        this.ctx.request_accessibility_update();
+        &mut this.widget.inner.state_mut().state
+    }
+
+    /// Set the size used when there is no layout closure.
+    pub fn set_preferred_size(this: &mut WidgetMut<'_, Self>, size: Size) {
+        this.widget.inner.state_mut().preferred_size = size;
+        this.ctx.request_layout();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl<S: 'static> Widget for SimpleWidget<S> {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        self.inner.on_pointer_event(ctx, event);
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        self.inner.on_text_event(ctx, event);
+    }
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
+        self.inner.on_access_event(ctx, event);
+    }
+
+    fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
+        self.inner.on_anim_frame(ctx, interval);
+    }
+
+    fn register_children(&mut self, _ctx: &mut RegisterCtx) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        self.inner.update(ctx, event);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        self.inner.layout(ctx, bc)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        self.inner.paint(ctx, scene);
+    }
+
+    fn accessibility_role(&self) -> Role {
+        self.inner.accessibility_role()
+    }
+
+    fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        self.inner.accessibility(ctx, node);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        SmallVec::new()
+    }
+
+    fn accepts_focus(&self) -> bool {
+        self.inner.accepts_focus()
+    }
+
+    fn get_cursor(&self, _ctx: &QueryCtx, _pos: Point) -> CursorIcon {
+        self.cursor
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("SimpleWidget")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness};
+    use crate::widget::Flex;
+    use crate::PointerButton;
+
+    #[test]
+    fn default_size_is_constrained() {
+        let [widget_id] = widget_ids();
+        let widget = SimpleWidget::new(()).with_preferred_size(Size::new(500., 20.));
+
+        let harness = TestHarness::create_with_size(
+            Flex::column().with_child_id(widget, widget_id),
+            Size::new(100., 100.),
+        );
+        assert_eq!(
+            harness.get_widget(widget_id).ctx().size(),
+            Size::new(100., 20.)
+        );
+    }
+
+    #[test]
+    fn closures_receive_state() {
+        let [widget_id] = widget_ids();
+        let widget = SimpleWidget::new(0_u32)
+            .pointer_event_fn(|clicks, ctx, event| {
+                if let PointerEvent::PointerUp(PointerButton::Primary, _) = event {
+                    *clicks += 1;
+                    ctx.request_layout();
+                }
+            })
+            .layout_fn(|clicks, _, bc| bc.constrain(Size::new(10. * f64::from(*clicks), 10.)));
+        let mut harness = TestHarness::create(Flex::row().with_child_id(widget, widget_id));
+        assert_eq!(harness.get_widget(widget_id).ctx().size().width, 0.);
+
+        // A zero-sized widget can't be clicked, so we grow it first.
+        harness.edit_widget(widget_id, |mut widget| {
+            let mut widget = widget.downcast::<SimpleWidget<u32>>();
+            *SimpleWidget::state_mut(&mut widget) = 1;
+        });
+        assert_eq!(harness.get_widget(widget_id).ctx().size().width, 10.);
+
+        harness.mouse_click_on(widget_id);
+        assert_eq!(harness.get_widget(widget_id).ctx().size().width, 20.);
+    }
+}
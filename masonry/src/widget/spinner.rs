@@ -25,7 +25,8 @@ use crate::{
 /// [`SizedBox`]: crate::widget::SizedBox
 pub struct Spinner {
     t: f64,
-    color: Color,
+    /// The color set on this widget, which takes precedence over the env.
+    color: Option<Color>,
 }
 
 // --- MARK: BUILDERS ---
@@ -37,18 +38,16 @@ impl Spinner {
 
     /// Builder-style method for setting the spinner's color.
     pub fn with_color(mut self, color: impl Into<Color>) -> Self {
-        self.color = color.into();
+        self.color = Some(color.into());
         self
     }
 }
 
-const DEFAULT_SPINNER_COLOR: Color = theme::TEXT_COLOR;
-
 impl Default for Spinner {
     fn default() -> Self {
         Spinner {
             t: 0.0,
-            color: DEFAULT_SPINNER_COLOR,
+            color: None,
         }
     }
 }
@@ -57,13 +56,14 @@ impl Default for Spinner {
 impl Spinner {
     /// Set the spinner's color.
     pub fn set_color(this: &mut WidgetMut<'_, Self>, color: impl Into<Color>) {
-        this.widget.color = color.into();
+        this.widget.color = Some(color.into());
         this.ctx.request_paint_only();
     }
 
-    /// Reset the spinner's color to its default value.
+    /// Reset the spinner's color to the [`TEXT_COLOR`](theme::TEXT_COLOR) of its env.
     pub fn reset_color(this: &mut WidgetMut<'_, Self>) {
-        Self::set_color(this, DEFAULT_SPINNER_COLOR);
+        this.widget.color = None;
+        this.ctx.request_paint_only();
     }
 }
 
@@ -95,14 +95,12 @@ impl Widget for Spinner {
         }
     }
 
-    fn layout(&mut self, _ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         if bc.is_width_bounded() && bc.is_height_bounded() {
             bc.max()
         } else {
-            bc.constrain(Size::new(
-                theme::BASIC_WIDGET_HEIGHT,
-                theme::BASIC_WIDGET_HEIGHT,
-            ))
+            let side = ctx.env().get(theme::BASIC_WIDGET_HEIGHT);
+            bc.constrain(Size::new(side, side))
         }
     }
 
@@ -111,7 +109,9 @@ impl Widget for Spinner {
         let (width, height) = (ctx.size().width, ctx.size().height);
         let center = Point::new(width / 2.0, height / 2.0);
         let (r, g, b, original_alpha) = {
-            let c = self.color;
+            let c = self
+                .color
+                .unwrap_or_else(|| ctx.env().get(theme::TEXT_COLOR));
             (c.r, c.g, c.b, c.a)
         };
         let scale_factor = width.min(height) / 40.0;
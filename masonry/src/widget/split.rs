@@ -14,7 +14,7 @@ use crate::paint_scene_helpers::{fill_color, stroke};
 use crate::widget::flex::Axis;
 use crate::widget::{WidgetMut, WidgetPod};
 use crate::{
-    theme, AccessCtx, AccessEvent, BoxConstraints, Color, CursorIcon, EventCtx, LayoutCtx,
+    theme, AccessCtx, AccessEvent, BoxConstraints, Color, CursorIcon, Env, EventCtx, LayoutCtx,
     PaintCtx, Point, PointerEvent, QueryCtx, Rect, RegisterCtx, Size, TextEvent, Widget, WidgetId,
 };
 
@@ -225,11 +225,11 @@ impl Split {
     }
 
     /// Returns the color of the splitter bar.
-    fn bar_color(&self) -> Color {
+    fn bar_color(&self, env: &Env) -> Color {
         if self.draggable {
-            theme::BORDER_LIGHT
+            env.get(theme::BORDER_LIGHT)
         } else {
-            theme::BORDER_DARK
+            env.get(theme::BORDER_DARK)
         }
     }
 
@@ -247,7 +247,7 @@ impl Split {
                 Point::new(size.width, edge2 - padding.floor()),
             ),
         };
-        let splitter_color = self.bar_color();
+        let splitter_color = self.bar_color(ctx.env());
         fill_color(scene, &rect, splitter_color);
     }
 
@@ -281,7 +281,7 @@ impl Split {
                 ),
             ),
         };
-        let splitter_color = self.bar_color();
+        let splitter_color = self.bar_color(ctx.env());
         stroke(scene, &line1, splitter_color, line_width);
         stroke(scene, &line2, splitter_color, line_width);
     }
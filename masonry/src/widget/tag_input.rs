@@ -0,0 +1,611 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A text field which turns entries into removable tags.
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::kurbo::{Line, Vec2};
+use vello::Scene;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::action::Action;
+use crate::paint_scene_helpers::{fill_color, stroke};
+use crate::text::{ArcStr, TextEditor};
+use crate::widget::menu::{Menu, MenuKeyResult};
+use crate::widget::{Label, WidgetMut, WidgetPod};
+use crate::{
+    theme, AccessCtx, AccessEvent, BoxConstraints, CursorIcon, EventCtx, Insets, LayoutCtx,
+    PaintCtx, Point, PointerButton, PointerEvent, QueryCtx, Rect, RegisterCtx, Size, TextEvent,
+    Update, UpdateCtx, Widget, WidgetId,
+};
+
+const PADDING: f64 = 4.;
+const CHIP_INSETS: Insets = Insets::uniform_xy(6., 2.);
+const CHIP_GAP: f64 = 4.;
+/// The width of the remove button at the end of each tag.
+const CHIP_CLOSE_WIDTH: f64 = 16.;
+/// The space kept for typing after the last tag.
+const MIN_INPUT_WIDTH: f64 = 60.;
+/// The fallback width for a tag input with infinite provided maximum width.
+const INFINITE_WIDTH: f64 = 300.;
+/// The most suggestions shown at once.
+const MAX_SUGGESTIONS: usize = 8;
+/// The space between the field and its suggestions.
+const MENU_GAP: f64 = 2.;
+
+/// A text field where entries become tags, shown as removable chips.
+///
+/// The typed text becomes a tag when Enter or a comma is pressed. Pasted text is
+/// split on commas and line breaks. Backspace in an empty field removes the last tag,
+/// and clicking the cross of a tag removes it. Empty and duplicate tags are ignored.
+///
+/// With [suggestions](Self::with_suggestions), the suggestions containing the typed
+/// text are listed below the field. Down and Up move through them, and Enter or a
+/// click adds the highlighted one.
+///
+/// Emits [`Action::TagsChanged`] with all the tags when a tag is added or removed,
+/// and [`Action::TextChanged`] when the typed text changes.
+pub struct TagInput {
+    tags: Vec<String>,
+    chips: Vec<WidgetPod<Label>>,
+    chip_rects: Vec<Rect>,
+    editor: TextEditor,
+    editor_origin: Point,
+    suggestions: Vec<ArcStr>,
+    /// The suggestions currently listed in the menu.
+    matches: Vec<ArcStr>,
+    menu: WidgetPod<Menu>,
+    field_size: Size,
+}
+
+// --- MARK: BUILDERS ---
+impl TagInput {
+    /// Create a tag input with the given tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use masonry::widget::TagInput;
+    ///
+    /// let input = TagInput::new(["rust", "gui"]).with_suggestions(["ui", "vello", "xilem"]);
+    /// ```
+    pub fn new(tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let tags: Vec<String> = tags.into_iter().map(Into::into).collect();
+        Self {
+            chips: tags.iter().map(|tag| new_chip(tag)).collect(),
+            tags,
+            chip_rects: Vec::new(),
+            editor: TextEditor::new(
+                String::new(),
+                *theme::TEXT_SIZE_NORMAL.default_value() as f32,
+            ),
+            editor_origin: Point::ZERO,
+            suggestions: Vec::new(),
+            matches: Vec::new(),
+            menu: WidgetPod::new(Menu::list_box(Default::default())),
+            field_size: Size::ZERO,
+        }
+    }
+
+    /// Builder-style method for setting the suggestions listed while typing.
+    pub fn with_suggestions(
+        mut self,
+        suggestions: impl IntoIterator<Item = impl Into<ArcStr>>,
+    ) -> Self {
+        self.suggestions = suggestions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The current tags.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// The text typed after the tags.
+    pub fn text(&self) -> &str {
+        self.editor.text()
+    }
+
+    fn add_tag(&mut self, ctx: &mut EventCtx, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() || self.tags.iter().any(|existing| existing == tag) {
+            return false;
+        }
+        self.tags.push(tag.to_string());
+        self.chips.push(new_chip(tag));
+        ctx.children_changed();
+        true
+    }
+
+    fn remove_tag(&mut self, ctx: &mut EventCtx, index: usize) {
+        self.tags.remove(index);
+        ctx.remove_child(self.chips.remove(index));
+        ctx.submit_action(Action::TagsChanged(self.tags.clone()));
+        ctx.request_layout();
+    }
+
+    /// Turn the typed text before the last separator into tags.
+    fn split_text(&mut self, ctx: &mut EventCtx) {
+        let text = self.editor.text().clone();
+        let Some((complete, rest)) = text.rsplit_once([',', '\n']) else {
+            return;
+        };
+        let mut changed = false;
+        for tag in complete.split([',', '\n']) {
+            changed |= self.add_tag(ctx, tag);
+        }
+        if changed {
+            ctx.submit_action(Action::TagsChanged(self.tags.clone()));
+        }
+        let rest = rest.trim_start().to_string();
+        self.editor.set_text_with_caret_at_end(rest.clone());
+        ctx.submit_action(Action::TextChanged(rest));
+    }
+
+    /// Add the typed text as a tag, and clear it.
+    fn commit_text(&mut self, ctx: &mut EventCtx, tag: &str) {
+        if self.add_tag(ctx, tag) {
+            ctx.submit_action(Action::TagsChanged(self.tags.clone()));
+        }
+        if !self.editor.text().is_empty() {
+            self.editor.set_text_with_caret_at_end(String::new());
+            ctx.submit_action(Action::TextChanged(String::new()));
+        }
+        ctx.request_layout();
+    }
+
+    /// Update the suggestions listed for the typed text.
+    fn update_matches(&mut self, ctx: &mut EventCtx) {
+        let text = self.editor.text().trim().to_lowercase();
+        let matches: Vec<ArcStr> = if text.is_empty() || !ctx.has_focus() {
+            Vec::new()
+        } else {
+            self.suggestions
+                .iter()
+                .filter(|suggestion| suggestion.to_lowercase().contains(&text))
+                .filter(|suggestion| !self.tags.iter().any(|tag| **tag == ***suggestion))
+                .take(MAX_SUGGESTIONS)
+                .cloned()
+                .collect()
+        };
+        if matches == self.matches {
+            return;
+        }
+        self.matches = matches.clone();
+        ctx.set_stashed(&mut self.menu, self.matches.is_empty());
+        ctx.mutate_later(&mut self.menu, move |mut menu| {
+            Menu::set_items(&mut menu, matches.into_iter().collect());
+        });
+        ctx.request_layout();
+    }
+
+    fn take_activated_match(&mut self, ctx: &mut EventCtx) -> Option<ArcStr> {
+        let index = ctx.get_raw_mut(&mut self.menu).widget().take_activated()?;
+        self.matches.get(index).cloned()
+    }
+
+    /// The tag whose remove button is at `pos`.
+    fn chip_close_at(&self, pos: Point) -> Option<usize> {
+        self.chip_rects
+            .iter()
+            .position(|rect| rect.contains(pos) && pos.x >= rect.x1 - CHIP_CLOSE_WIDTH)
+    }
+}
+
+fn new_chip(text: &str) -> WidgetPod<Label> {
+    WidgetPod::new(Label::new(text.to_string()))
+}
+
+// --- MARK: WIDGETMUT ---
+impl TagInput {
+    /// Replace the tags.
+    pub fn set_tags(
+        this: &mut WidgetMut<'_, Self>,
+        tags: impl IntoIterator<Item = impl Into<String>>,
+    ) {
+        let tags: Vec<String> = tags.into_iter().map(Into::into).collect();
+        if tags == this.widget.tags {
+            return;
+        }
+        for chip in this.widget.chips.drain(..) {
+            this.ctx.remove_child(chip);
+        }
+        this.widget.chips = tags.iter().map(|tag| new_chip(tag)).collect();
+        this.widget.tags = tags;
+        this.ctx.children_changed();
+    }
+
+    /// Set the suggestions listed while typing.
+    ///
+    /// They are used from the next change of the typed text.
+    pub fn set_suggestions(
+        this: &mut WidgetMut<'_, Self>,
+        suggestions: impl IntoIterator<Item = impl Into<ArcStr>>,
+    ) {
+        this.widget.suggestions = suggestions.into_iter().map(Into::into).collect();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for TagInput {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        let window_origin = ctx.window_origin();
+        let editor_origin = window_origin + self.editor_origin.to_vec2();
+        let local_pos = event
+            .position()
+            .map(|pos| Point::new(pos.x, pos.y) - window_origin.to_vec2());
+        match event {
+            PointerEvent::PointerDown(button, state) => {
+                let in_field = local_pos.is_some_and(|pos| self.field_size.to_rect().contains(pos));
+                if !in_field || ctx.is_disabled() {
+                    return;
+                }
+                if *button == PointerButton::Primary {
+                    if let Some(index) = local_pos.and_then(|pos| self.chip_close_at(pos)) {
+                        self.remove_tag(ctx, index);
+                        return;
+                    }
+                }
+                if self.editor.pointer_down(editor_origin, state, *button) {
+                    ctx.request_layout();
+                    ctx.request_render();
+                    ctx.request_focus();
+                    ctx.capture_pointer();
+                }
+            }
+            PointerEvent::PointerMove(state)
+                if !ctx.is_disabled()
+                    && ctx.has_pointer_capture()
+                    && self.editor.pointer_move(editor_origin, state) =>
+            {
+                ctx.request_layout();
+                ctx.request_render();
+            }
+            PointerEvent::PointerUp(button, state) => {
+                if let Some(suggestion) = self.take_activated_match(ctx) {
+                    self.commit_text(ctx, &suggestion);
+                    self.update_matches(ctx);
+                    ctx.set_handled();
+                } else if !ctx.is_disabled() && ctx.has_pointer_capture() {
+                    self.editor.pointer_up(editor_origin, state, *button);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_text_event(&mut self, ctx: &mut EventCtx, event: &TextEvent) {
+        if ctx.is_disabled() {
+            return;
+        }
+        if let TextEvent::KeyboardKey(key_event, mods) = event {
+            if key_event.state.is_pressed() {
+                let key = &key_event.logical_key;
+                if !self.matches.is_empty()
+                    && matches!(
+                        key,
+                        Key::Named(
+                            NamedKey::ArrowDown
+                                | NamedKey::ArrowUp
+                                | NamedKey::Escape
+                                | NamedKey::Enter
+                        )
+                    )
+                {
+                    let result = {
+                        let mut menu = ctx.get_raw_mut(&mut self.menu);
+                        let result = menu.widget().on_key(key);
+                        if result == MenuKeyResult::Highlighted {
+                            menu.ctx().request_render();
+                        }
+                        result
+                    };
+                    match result {
+                        MenuKeyResult::Highlighted => {
+                            ctx.set_handled();
+                            return;
+                        }
+                        MenuKeyResult::Activated(index) => {
+                            let suggestion = self.matches[index].clone();
+                            self.commit_text(ctx, &suggestion);
+                            self.update_matches(ctx);
+                            ctx.set_handled();
+                            return;
+                        }
+                        MenuKeyResult::Close if *key == Key::Named(NamedKey::Escape) => {
+                            self.matches.clear();
+                            ctx.set_stashed(&mut self.menu, true);
+                            ctx.request_layout();
+                            ctx.set_handled();
+                            return;
+                        }
+                        MenuKeyResult::Close | MenuKeyResult::Ignored => {}
+                    }
+                }
+                let no_mods = !(mods.control_key() || mods.alt_key() || mods.super_key());
+                match key {
+                    Key::Named(NamedKey::Enter) => {
+                        let text = self.editor.text().clone();
+                        self.commit_text(ctx, &text);
+                        self.update_matches(ctx);
+                        ctx.request_render();
+                        ctx.set_handled();
+                        return;
+                    }
+                    Key::Named(NamedKey::Backspace) if no_mods && self.editor.text().is_empty() => {
+                        if !self.tags.is_empty() {
+                            self.remove_tag(ctx, self.tags.len() - 1);
+                        }
+                        ctx.set_handled();
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if self.editor.text_event(ctx, event).is_handled() {
+            self.split_text(ctx);
+            self.update_matches(ctx);
+            ctx.set_handled();
+            ctx.request_layout();
+            ctx.request_render();
+        }
+    }
+
+    fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {
+        if let Some(suggestion) = self.take_activated_match(ctx) {
+            self.commit_text(ctx, &suggestion);
+            self.update_matches(ctx);
+            return;
+        }
+        if event.action == accesskit::Action::SetTextSelection
+            && self.editor.set_selection_from_access_event(event)
+        {
+            ctx.request_layout();
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, event: &Update) {
+        match event {
+            Update::WidgetAdded => {
+                ctx.set_stashed(&mut self.menu, true);
+            }
+            Update::FocusChanged(false) => {
+                self.editor.focus_lost();
+                if !self.matches.is_empty() {
+                    self.matches.clear();
+                    ctx.set_stashed(&mut self.menu, true);
+                }
+                ctx.request_layout();
+            }
+            Update::FocusChanged(true) => {
+                self.editor.focus_gained();
+                ctx.request_layout();
+            }
+            Update::HoveredChanged(_) | Update::DisabledChanged(_) => {
+                ctx.request_paint_only();
+            }
+            _ => {}
+        }
+    }
+
+    fn accepts_focus(&self) -> bool {
+        true
+    }
+
+    fn accepts_text_input(&self) -> bool {
+        true
+    }
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        for chip in &mut self.chips {
+            ctx.register_child(chip);
+        }
+        ctx.register_child(&mut self.menu);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let width = if bc.max().width.is_finite() {
+            bc.max().width
+        } else {
+            INFINITE_WIDTH
+        };
+        let content_width = width - 2. * PADDING;
+
+        self.editor
+            .set_text_size(ctx.env().get(theme::TEXT_SIZE_NORMAL) as f32);
+        self.editor.set_brush(ctx.env().get(theme::TEXT_COLOR));
+        self.editor.set_max_advance(None);
+        if self.editor.needs_rebuild() {
+            let (font_ctx, layout_ctx) = ctx.text_contexts();
+            self.editor.rebuild(font_ctx, layout_ctx);
+        }
+        let editor_size = self.editor.size();
+
+        let chip_padding = Size::new(
+            CHIP_INSETS.x_value() + CHIP_CLOSE_WIDTH,
+            CHIP_INSETS.y_value(),
+        );
+        let label_bc = BoxConstraints::new(
+            Size::ZERO,
+            Size::new((content_width - chip_padding.width).max(0.), f64::INFINITY),
+        );
+        let mut label_sizes = Vec::with_capacity(self.chips.len());
+        for chip in &mut self.chips {
+            label_sizes.push(ctx.run_layout(chip, &label_bc));
+        }
+        let row_height = label_sizes
+            .iter()
+            .map(|size| size.height + chip_padding.height)
+            .fold(editor_size.height, f64::max);
+
+        // Lay out the tags in rows, followed by the typed text.
+        self.chip_rects.clear();
+        let mut pos = Point::new(PADDING, PADDING);
+        for (chip, label_size) in self.chips.iter_mut().zip(label_sizes) {
+            let chip_width = label_size.width + chip_padding.width;
+            if pos.x > PADDING && pos.x + chip_width > PADDING + content_width {
+                pos = Point::new(PADDING, pos.y + row_height + CHIP_GAP);
+            }
+            let rect = Rect::from_origin_size(pos, (chip_width, row_height));
+            ctx.place_child(
+                chip,
+                pos + Vec2::new(CHIP_INSETS.x0, (row_height - label_size.height) / 2.),
+            );
+            self.chip_rects.push(rect);
+            pos.x += chip_width + CHIP_GAP;
+        }
+        let input_width = editor_size.width.max(MIN_INPUT_WIDTH);
+        if pos.x > PADDING && pos.x + input_width > PADDING + content_width {
+            pos = Point::new(PADDING, pos.y + row_height + CHIP_GAP);
+        }
+        self.editor_origin = pos + Vec2::new(0., (row_height - editor_size.height) / 2.);
+
+        let field_size = bc.constrain(Size::new(width, pos.y + row_height + PADDING));
+        self.field_size = field_size;
+
+        if self.matches.is_empty() {
+            return field_size;
+        }
+        let menu_bc = BoxConstraints::new(Size::ZERO, Size::new(field_size.width, f64::INFINITY));
+        let menu_size = ctx.run_layout(&mut self.menu, &menu_bc);
+        ctx.place_child(&mut self.menu, Point::new(0., field_size.height + MENU_GAP));
+        bc.constrain(Size::new(
+            field_size.width,
+            field_size.height + MENU_GAP + menu_size.height,
+        ))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
+        let border_color = if ctx.is_hovered() && !ctx.is_disabled() {
+            ctx.env().get(theme::BORDER_LIGHT)
+        } else {
+            ctx.env().get(theme::BORDER_DARK)
+        };
+        let border_width = ctx.env().get(theme::TEXTBOX_BORDER_WIDTH);
+        let outline = self
+            .field_size
+            .to_rect()
+            .inset(-border_width / 2.)
+            .to_rounded_rect(ctx.env().get(theme::TEXTBOX_BORDER_RADIUS));
+        stroke(scene, &outline, border_color, border_width);
+
+        let cross_color = if ctx.is_disabled() {
+            ctx.env().get(theme::DISABLED_TEXT_COLOR)
+        } else {
+            ctx.env().get(theme::TEXT_COLOR)
+        };
+        for rect in &self.chip_rects {
+            let chip = rect.to_rounded_rect(rect.height() / 2.);
+            fill_color(scene, &chip, ctx.env().get(theme::BACKGROUND_LIGHT));
+            stroke(scene, &chip, ctx.env().get(theme::BORDER_DARK), 1.);
+
+            let center = Point::new(rect.x1 - CHIP_CLOSE_WIDTH / 2., rect.center().y);
+            let arm = 3.;
+            stroke(
+                scene,
+                &Line::new(center + (-arm, -arm), center + (arm, arm)),
+                cross_color,
+                1.5,
+            );
+            stroke(
+                scene,
+                &Line::new(center + (-arm, arm), center + (arm, -arm)),
+                cross_color,
+                1.5,
+            );
+        }
+
+        self.editor.set_glyph_snapping(ctx.glyph_snapping());
+        self.editor.draw(scene, self.editor_origin);
+    }
+
+    fn get_cursor(&self, _ctx: &QueryCtx, _pos: Point) -> CursorIcon {
+        CursorIcon::Text
+    }
+
+    fn accessibility_role(&self) -> Role {
+        Role::TextInput
+    }
+
+    fn accessibility(&mut self, ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        self.editor.accessibility(ctx.tree_update, node);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        self.chips
+            .iter()
+            .map(WidgetPod::id)
+            .chain(std::iter::once(self.menu.id()))
+            .collect()
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("TagInput")
+    }
+
+    fn get_debug_text(&self) -> Option<String> {
+        Some(self.tags.join(", "))
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+
+    fn tags_changed(harness: &mut TestHarness) -> Vec<Vec<String>> {
+        let mut changes = Vec::new();
+        while let Some((action, _)) = harness.pop_action() {
+            if let Action::TagsChanged(tags) = action {
+                changes.push(tags);
+            }
+        }
+        changes
+    }
+
+    #[test]
+    fn comma_and_paste_make_tags() {
+        let [input_id] = widget_ids();
+        let widget = TagInput::new(["rust"]).with_id(input_id);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(300., 200.));
+        harness.focus_on(Some(input_id));
+
+        harness.keyboard_type_chars("gui,");
+        assert_eq!(tags_changed(&mut harness), vec![vec!["rust", "gui"]]);
+
+        // Pasted text is committed in one event.
+        harness.process_text_event(TextEvent::Ime(winit::event::Ime::Commit(
+            "ui, rust,\nvello, xil".into(),
+        )));
+        assert_eq!(
+            tags_changed(&mut harness),
+            vec![vec!["rust", "gui", "ui", "vello"]]
+        );
+        let input = harness.get_widget(input_id);
+        let input = input.downcast::<TagInput>().unwrap();
+        assert_eq!(input.text(), "xil");
+        assert_eq!(input.chips.len(), 4);
+    }
+
+    #[test]
+    fn suggestions_follow_typed_text() {
+        let [input_id] = widget_ids();
+        let widget = TagInput::new(["vello"])
+            .with_suggestions(["vello", "vector", "xilem"])
+            .with_id(input_id);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(300., 300.));
+        harness.focus_on(Some(input_id));
+
+        harness.keyboard_type_chars("ve");
+        let input = harness.get_widget(input_id);
+        let input = input.downcast::<TagInput>().unwrap();
+        // Existing tags aren't suggested.
+        assert_eq!(input.matches, vec![ArcStr::from("vector")]);
+        assert!(!harness.get_widget(input.menu.id()).ctx().is_stashed());
+    }
+}
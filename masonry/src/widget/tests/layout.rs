@@ -3,10 +3,18 @@
 
 //! Tests related to layout.
 
-use vello::kurbo::{Insets, Size};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
 
-use crate::testing::{widget_ids, ModularWidget, TestHarness, TestWidgetExt};
-use crate::widget::{Flex, SizedBox};
+use dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use smallvec::smallvec;
+use vello::kurbo::{Affine, Insets, Point, Rect, Size, Vec2};
+use winit::window::WindowAttributes;
+
+use crate::testing::{widget_ids, ModularWidget, Record, Recording, TestHarness, TestWidgetExt};
+use crate::widget::{Button, Flex, Label, SizedBox};
+use crate::{MonitorInfo, PointerButton, PointerEvent, WidgetPod, WindowEvent, WindowPlacement};
 
 #[test]
 fn layout_simple() {
@@ -72,3 +80,444 @@ fn layout_insets() {
 // TODO - insets + flex
 // TODO - viewport
 // TODO - insets + viewport
+
+#[test]
+fn layout_skipped_for_clean_subtree() {
+    let [id_1, id_2] = widget_ids();
+    let record_1 = Recording::default();
+    let record_2 = Recording::default();
+
+    let widget = Flex::row()
+        .with_child_id(
+            SizedBox::empty().width(50.).height(50.).record(&record_1),
+            id_1,
+        )
+        .with_child_id(
+            SizedBox::empty().width(50.).height(50.).record(&record_2),
+            id_2,
+        );
+
+    let mut harness = TestHarness::create(widget);
+    record_1.clear();
+    record_2.clear();
+
+    harness.edit_widget(id_2, |mut widget| {
+        widget.ctx.request_layout();
+    });
+
+    // The first box gets the same constraints as before and nothing in it
+    // requested layout, so its cached layout is reused.
+    assert!(!record_1
+        .drain()
+        .iter()
+        .any(|record| matches!(record, Record::Layout(_))));
+    assert!(record_2
+        .drain()
+        .iter()
+        .any(|record| matches!(record, Record::Layout(_))));
+}
+
+#[test]
+fn presented_and_target_rects() {
+    let [parent_id, child_id] = widget_ids();
+    let offset = Rc::new(Cell::new(Vec2::new(0., 50.)));
+
+    let child = WidgetPod::new_with_id(SizedBox::empty().width(20.).height(20.), child_id);
+    let parent = ModularWidget::new((child, offset.clone()))
+        .register_children_fn(|(child, _), ctx| ctx.register_child(child))
+        .layout_fn(|(child, _), ctx, bc| {
+            ctx.run_layout(child, bc);
+            ctx.place_child(child, Point::new(10., 10.));
+            Size::new(100., 100.)
+        })
+        .compose_fn(|(child, offset), ctx| {
+            ctx.set_child_presentation_offset(child, offset.get());
+        })
+        .children_fn(|(child, _)| smallvec![child.id()])
+        .with_id(parent_id);
+    let widget = Flex::column().with_child(parent);
+
+    let mut harness = TestHarness::create(widget);
+
+    let parent_origin = harness.get_widget(parent_id).ctx().window_origin();
+    let child = harness.get_widget(child_id);
+    let target_rect = Rect::new(10., 10., 30., 30.);
+    assert_eq!(child.ctx().layout_rect(), target_rect);
+    assert_eq!(
+        child.ctx().presented_rect(),
+        target_rect + Vec2::new(0., 50.)
+    );
+    assert_eq!(
+        child.ctx().window_target_rect(),
+        target_rect + parent_origin.to_vec2()
+    );
+    assert_eq!(
+        child.ctx().window_layout_rect(),
+        target_rect + parent_origin.to_vec2() + Vec2::new(0., 50.)
+    );
+
+    // Hit testing uses the presented position.
+    let presented_center = child.ctx().window_layout_rect().center();
+    assert_eq!(
+        harness
+            .root_widget()
+            .find_widget_at_pos(presented_center)
+            .map(|widget| widget.id()),
+        Some(child_id)
+    );
+
+    // Once the offset is cleared, both positions match.
+    offset.set(Vec2::ZERO);
+    harness.edit_widget(parent_id, |mut parent| {
+        parent.ctx.request_compose();
+    });
+    let child = harness.get_widget(child_id);
+    assert_eq!(
+        child.ctx().window_layout_rect(),
+        child.ctx().window_target_rect()
+    );
+}
+
+#[test]
+fn z_index_orders_overlapping_children() {
+    let [bottom_id, top_id] = widget_ids();
+    let raised = Rc::new(Cell::new(false));
+
+    let bottom = WidgetPod::new_with_id(SizedBox::empty().width(50.).height(50.), bottom_id);
+    let top = WidgetPod::new_with_id(SizedBox::empty().width(50.).height(50.), top_id);
+    let parent = ModularWidget::new((bottom, top, raised.clone()))
+        .register_children_fn(|(bottom, top, _), ctx| {
+            ctx.register_child(bottom);
+            ctx.register_child(top);
+        })
+        .layout_fn(|(bottom, top, raised), ctx, bc| {
+            ctx.run_layout(bottom, &bc.loosen());
+            ctx.run_layout(top, &bc.loosen());
+            ctx.place_child_with_z(bottom, Point::ORIGIN, i32::from(raised.get()));
+            ctx.place_child(top, Point::new(25., 25.));
+            Size::new(100., 100.)
+        })
+        .children_fn(|(bottom, top, _)| smallvec![bottom.id(), top.id()]);
+
+    let mut harness = TestHarness::create(parent);
+    let widget_at_overlap = |harness: &TestHarness| {
+        (harness.root_widget())
+            .find_widget_at_pos(Point::new(40., 40.))
+            .map(|widget| widget.id())
+    };
+    assert_eq!(widget_at_overlap(&harness), Some(top_id));
+
+    raised.set(true);
+    harness.edit_root_widget(|mut parent| parent.ctx.request_layout());
+    assert_eq!(widget_at_overlap(&harness), Some(bottom_id));
+}
+
+#[test]
+fn rounded_clip_path_hit_testing() {
+    let [parent_id, child_id] = widget_ids();
+
+    let child = WidgetPod::new_with_id(SizedBox::empty().width(100.).height(100.), child_id);
+    let parent = ModularWidget::new(child)
+        .register_children_fn(|child, ctx| ctx.register_child(child))
+        .layout_fn(|child, ctx, bc| {
+            let size = ctx.run_layout(child, &bc.loosen());
+            ctx.place_child(child, Point::ORIGIN);
+            ctx.set_clip_path(size.to_rounded_rect(50.));
+            size
+        })
+        .children_fn(|child| smallvec![child.id()])
+        .with_id(parent_id);
+
+    let harness = TestHarness::create(parent);
+    let widget_at = |pos: Point| {
+        (harness.root_widget())
+            .find_widget_at_pos(pos)
+            .map(|widget| widget.id())
+    };
+    assert_eq!(widget_at(Point::new(50., 50.)), Some(child_id));
+    // The corner of the child is outside of the circle it's clipped to.
+    assert_eq!(widget_at(Point::new(5., 5.)), Some(parent_id));
+}
+
+#[test]
+fn rotated_child_hit_testing() {
+    let [parent_id, child_id] = widget_ids();
+
+    let child = WidgetPod::new_with_id(SizedBox::empty().width(100.).height(20.), child_id);
+    let parent = ModularWidget::new(child)
+        .register_children_fn(|child, ctx| ctx.register_child(child))
+        .layout_fn(|child, ctx, bc| {
+            ctx.run_layout(child, &bc.loosen());
+            ctx.place_child(child, Point::new(100., 100.));
+            bc.max()
+        })
+        .compose_fn(|child, ctx| {
+            // A quarter turn clockwise around the child's origin.
+            ctx.set_child_transform(child, Affine::new([0., 1., -1., 0., 0., 0.]));
+        })
+        .children_fn(|child| smallvec![child.id()])
+        .with_id(parent_id);
+
+    let harness = TestHarness::create(parent);
+    let child = harness.get_widget(child_id);
+    assert_eq!(
+        child.ctx().window_layout_rect(),
+        Rect::new(80., 100., 100., 200.)
+    );
+    assert_eq!(
+        child.ctx().to_window(Point::new(50., 10.)),
+        Point::new(90., 150.)
+    );
+
+    let widget_at = |pos: Point| {
+        (harness.root_widget())
+            .find_widget_at_pos(pos)
+            .map(|widget| widget.id())
+    };
+    assert_eq!(widget_at(Point::new(90., 150.)), Some(child_id));
+    // This is where the child would be without the transform.
+    assert_eq!(widget_at(Point::new(150., 110.)), Some(parent_id));
+}
+
+#[test]
+fn window_aspect_ratio() {
+    let mut harness = TestHarness::create(SizedBox::empty());
+    harness.edit_root_widget(|mut root| {
+        root.ctx.set_window_aspect_ratio(Some(2.0));
+        root.ctx
+            .set_window_resize_increments(Some(LogicalSize::new(8., 16.)));
+    });
+
+    // The user resizes the window.
+    harness.process_window_event(WindowEvent::Resize(PhysicalSize::new(600, 400)));
+    assert_eq!(harness.window_size(), PhysicalSize::new(600, 300));
+    assert_eq!(harness.resize_increments(), Some(LogicalSize::new(8., 16.)));
+    let root_size = harness.root_widget().ctx().size();
+    assert_eq!(root_size, Size::new(600., 300.));
+}
+
+#[test]
+fn place_window_on_monitor() {
+    let mut harness = TestHarness::create(SizedBox::empty());
+    let monitor = MonitorInfo {
+        name: Some("Main".into()),
+        position: PhysicalPosition::new(0, 0),
+        size: PhysicalSize::new(1920, 1080),
+        scale_factor: 1.,
+        is_primary: true,
+    };
+    harness.process_window_event(WindowEvent::MonitorsChanged(vec![monitor.clone()]));
+
+    let placement = WindowPlacement::Centered {
+        monitor: None,
+        size: Some(LogicalSize::new(800., 600.)),
+    };
+    harness.edit_root_widget(|mut root| {
+        assert_eq!(root.ctx.monitors(), [monitor]);
+        root.ctx.place_window(placement.clone());
+    });
+    // Signals are processed with the next event.
+    harness.mouse_move((0., 0.));
+    assert_eq!(harness.window_placement(), Some(&placement));
+}
+
+#[test]
+fn size_window_to_content() {
+    let widget = SizedBox::empty().width(150.).height(80.);
+    let mut harness = TestHarness::create(widget);
+
+    harness.edit_root_widget(|mut root| {
+        root.ctx.request_size_to_content();
+    });
+    // Signals are processed with the next event.
+    harness.mouse_move((0., 0.));
+    assert_eq!(harness.window_size(), PhysicalSize::new(150, 80));
+    assert_eq!(harness.root_widget().ctx().size(), Size::new(150., 80.));
+
+    // The window doesn't grow larger than the monitor it's on, in the logical pixels
+    // of that monitor.
+    let primary = MonitorInfo {
+        name: None,
+        position: PhysicalPosition::new(0, 0),
+        size: PhysicalSize::new(1920, 1080),
+        scale_factor: 1.,
+        is_primary: true,
+    };
+    let secondary = MonitorInfo {
+        name: None,
+        position: PhysicalPosition::new(1920, 0),
+        size: PhysicalSize::new(200, 120),
+        scale_factor: 2.,
+        is_primary: false,
+    };
+    harness.process_window_event(WindowEvent::MonitorsChanged(vec![primary, secondary]));
+    harness.process_window_event(WindowEvent::Moved(PhysicalPosition::new(1930, 0)));
+    harness.edit_root_widget(|mut root| {
+        root.ctx.request_size_to_content();
+    });
+    harness.mouse_move((0., 0.));
+    assert_eq!(harness.window_size(), PhysicalSize::new(100, 60));
+    assert_eq!(harness.root_widget().ctx().size(), Size::new(100., 60.));
+}
+
+#[test]
+fn size_window_to_expanding_content() {
+    let widget = SizedBox::empty().expand_width().height(80.);
+    let mut harness = TestHarness::create(widget);
+
+    // The width of the window is kept, instead of filling the monitor.
+    harness.edit_root_widget(|mut root| {
+        root.ctx.request_size_to_content();
+    });
+    harness.mouse_move((0., 0.));
+    assert_eq!(harness.window_size(), PhysicalSize::new(400, 80));
+    assert_eq!(harness.root_widget().ctx().size(), Size::new(400., 80.));
+}
+
+#[test]
+fn baseline_grid() {
+    let [label_id, button_id] = widget_ids();
+    let widget = Flex::column()
+        .with_child_id(Label::new("First line\nSecond line"), label_id)
+        .with_child_id(Button::new("Button"), button_id);
+
+    let mut harness = TestHarness::create(widget);
+    let label_height = harness.get_widget(label_id).ctx().size().height;
+
+    let unit = 7.;
+    harness.set_baseline_grid(Some(unit));
+    let on_grid = |value: f64| (value / unit - (value / unit).round()).abs() < 1e-3;
+    for id in [label_id, button_id] {
+        let rect = harness.get_widget(id).ctx().layout_rect();
+        assert!(on_grid(rect.height()), "{rect:?}");
+        assert!(on_grid(rect.y0), "{rect:?}");
+    }
+    // Both lines are rounded, not only the label's height.
+    let snapped_height = harness.get_widget(label_id).ctx().size().height;
+    assert!(snapped_height >= label_height);
+    assert!(snapped_height - label_height < 2. * unit);
+
+    harness.set_baseline_grid(None);
+    assert_eq!(
+        harness.get_widget(label_id).ctx().size().height,
+        label_height
+    );
+}
+
+#[test]
+fn close_window_from_widget() {
+    let widget = ModularWidget::new(())
+        .layout_fn(|_, _, _| Size::new(100., 100.))
+        .pointer_event_fn(|_, ctx, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                ctx.close_window();
+            }
+        });
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_move((10., 10.));
+    assert!(!harness.window_closed());
+
+    harness.mouse_button_press(PointerButton::Primary);
+    assert!(harness.window_closed());
+}
+
+#[test]
+fn open_and_close_secondary_window() {
+    let widget = ModularWidget::new(None)
+        .layout_fn(|_, _, _| Size::new(100., 100.))
+        .pointer_event_fn(|window, ctx, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                match window.take() {
+                    Some(id) => ctx.close_app_window(id),
+                    None => {
+                        let attributes = WindowAttributes::default().with_title("Inspector");
+                        *window = Some(ctx.open_window(attributes, Label::new("Details")));
+                    }
+                }
+            }
+        });
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_move((10., 10.));
+
+    harness.mouse_button_press(PointerButton::Primary);
+    let [(_, title)] = harness.open_windows() else {
+        panic!("expected one open window");
+    };
+    assert_eq!(title, "Inspector");
+
+    harness.mouse_button_press(PointerButton::Primary);
+    assert!(harness.open_windows().is_empty());
+    assert!(!harness.window_closed());
+}
+
+#[test]
+fn watchdog_skips_slow_layout() {
+    let [id_1, id_2, id_3] = widget_ids();
+    let layout_count = Rc::new(Cell::new(0));
+    let slow = Rc::new(Cell::new(true));
+
+    let slow_widget = || {
+        let layout_count = layout_count.clone();
+        let slow = slow.clone();
+        ModularWidget::new(()).layout_fn(move |_, ctx, _| {
+            layout_count.set(layout_count.get() + 1);
+            if slow.get() {
+                // The watchdog uses the mock time of the harness.
+                let now = ctx.global_state.now();
+                ctx.global_state.mock_time = Some(now + Duration::from_millis(20));
+            }
+            Size::new(10., 10.)
+        })
+    };
+    let widget = Flex::row()
+        .with_child_id(slow_widget(), id_1)
+        .with_child_id(slow_widget(), id_2)
+        .with_child_id(slow_widget(), id_3);
+
+    let mut harness = TestHarness::create(widget);
+    harness.set_pass_watchdog(Some(Duration::from_millis(10)));
+    layout_count.set(0);
+    harness.edit_root_widget(|mut flex| {
+        let mut flex = flex.downcast::<Flex>();
+        for idx in 0..3 {
+            Flex::child_mut(&mut flex, idx)
+                .unwrap()
+                .ctx
+                .request_layout();
+        }
+    });
+
+    // The first widget exceeds the budget, so the others keep their previous layout.
+    assert_eq!(layout_count.get(), 1);
+    assert_eq!(harness.get_widget(id_3).ctx().size(), Size::new(10., 10.));
+
+    // The skipped widgets are laid out on the next frame.
+    slow.set(false);
+    let _ = harness.paint();
+    assert_eq!(layout_count.get(), 3);
+    let _ = harness.paint();
+    assert_eq!(layout_count.get(), 3);
+}
+
+#[test]
+fn pixel_snapping() {
+    let [id_1, id_2] = widget_ids();
+    let fractional_widget = || ModularWidget::new(()).layout_fn(|_, _, _| Size::new(10.3, 10.3));
+    let widget = Flex::column()
+        .with_child_id(fractional_widget(), id_1)
+        .with_child_id(fractional_widget(), id_2)
+        .with_flex_spacer(1.0);
+
+    let mut harness = TestHarness::create(widget);
+    harness.process_window_event(WindowEvent::Rescale(2.0));
+    harness.set_pixel_snapping(true);
+
+    // At a scale factor of 2, physical pixels are half a logical pixel wide.
+    assert_eq!(harness.get_widget(id_1).ctx().size(), Size::new(10.5, 10.5));
+    let origin = harness.get_widget(id_2).ctx().window_origin();
+    assert_eq!((origin.x * 2.).fract(), 0.);
+    assert_eq!((origin.y * 2.).fract(), 0.);
+
+    harness.set_pixel_snapping(false);
+    assert_eq!(harness.get_widget(id_1).ctx().size(), Size::new(10.3, 10.3));
+}
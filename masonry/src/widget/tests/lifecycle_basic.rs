@@ -3,10 +3,14 @@
 
 #![allow(unused_imports)]
 
+use std::time::Duration;
+
+use vello::Scene;
+
 use insta::assert_debug_snapshot;
 
 use crate::testing::{
-    widget_ids, Record, Recording, ReplaceChild, TestHarness, TestWidgetExt as _,
+    widget_ids, ModularWidget, Record, Recording, ReplaceChild, TestHarness, TestWidgetExt as _,
 };
 use crate::widget::{Flex, Label, SizedBox};
 use crate::*;
@@ -93,3 +97,580 @@ fn register_after_adding_child() {
     assert!(root_state.children.may_contain(&id_2));
     assert!(root_state.children.may_contain(&id_3));
 }
+
+#[test]
+fn platform_events_only_reach_subscribers() {
+    let [subscriber_id, other_id] = widget_ids();
+    let subscriber_record = Recording::default();
+    let other_record = Recording::default();
+
+    let subscriber = ModularWidget::new(())
+        .update_fn(|_, ctx, event| {
+            if let Update::WidgetAdded = event {
+                ctx.set_receives_platform_events(true);
+            }
+        })
+        .record(&subscriber_record)
+        .with_id(subscriber_id);
+    let other = SizedBox::empty().record(&other_record).with_id(other_id);
+    let widget = Flex::row().with_child(subscriber).with_child(other);
+
+    let mut harness = TestHarness::create(widget);
+    subscriber_record.clear();
+    other_record.clear();
+
+    harness.process_platform_event(PlatformEvent::MemoryWarning);
+    assert!(subscriber_record
+        .drain()
+        .iter()
+        .any(|record| matches!(record, Record::Platform(PlatformEvent::MemoryWarning))));
+    assert!(!other_record
+        .drain()
+        .iter()
+        .any(|record| matches!(record, Record::Platform(_))));
+}
+
+#[test]
+fn env_overrides_apply_to_subtree() {
+    const WIDTH: Key<f64> = Key::new("test.width", 1.);
+    let [inner_id, outer_id] = widget_ids();
+    let inner_record = Recording::default();
+    let outer_record = Recording::default();
+    let inner = SizedBox::empty().record(&inner_record).with_id(inner_id);
+    let subtree = ModularWidget::new(WidgetPod::new(inner))
+        .register_children_fn(|child, ctx| ctx.register_child(child))
+        .update_fn(|_, ctx, event| {
+            if let Update::WidgetAdded = event {
+                ctx.set_env(Env::new().with(WIDTH, 3.));
+            }
+        })
+        .layout_fn(|child, ctx, bc| {
+            let size = ctx.run_layout(child, bc);
+            ctx.place_child(child, Point::ORIGIN);
+            size
+        })
+        .children_fn(|child| smallvec::smallvec![child.id()]);
+    let outer = SizedBox::empty().record(&outer_record).with_id(outer_id);
+    let widget = Flex::row().with_child(subtree).with_child(outer);
+
+    let mut harness = TestHarness::create(widget);
+    let width = |harness: &TestHarness, id| harness.get_widget(id).ctx().env().get(WIDTH);
+    assert_eq!(width(&harness, inner_id), 3.);
+    assert_eq!(width(&harness, outer_id), 1.);
+
+    // The override takes precedence over the root env, and the inner widget's env
+    // doesn't change.
+    inner_record.clear();
+    outer_record.clear();
+    harness.set_env(Env::new().with(WIDTH, 2.));
+    assert_eq!(width(&harness, inner_id), 3.);
+    assert_eq!(width(&harness, outer_id), 2.);
+    let env_changed = |record: &Recording| {
+        (record.drain().iter()).any(|record| matches!(record, Record::U(Update::EnvChanged)))
+    };
+    assert!(!env_changed(&inner_record));
+    assert!(env_changed(&outer_record));
+}
+
+#[test]
+fn theme_changed_is_sent_to_all_widgets() {
+    let [child_id] = widget_ids();
+    let record = Recording::default();
+    let child = SizedBox::empty().record(&record).with_id(child_id);
+    let mut harness = TestHarness::create(Flex::row().with_child(child));
+    assert_eq!(
+        harness.root_widget().ctx().color_scheme(),
+        WindowTheme::Dark
+    );
+
+    record.clear();
+    harness.set_color_scheme(WindowTheme::Light);
+    assert_eq!(
+        harness.get_widget(child_id).ctx().color_scheme(),
+        WindowTheme::Light
+    );
+    let theme_changes = |record: &Recording| {
+        (record.drain().iter())
+            .filter(|record| matches!(record, Record::U(Update::ThemeChanged(_))))
+            .count()
+    };
+    assert_eq!(theme_changes(&record), 1);
+
+    // Setting the same scheme again doesn't notify widgets.
+    harness.set_color_scheme(WindowTheme::Light);
+    assert_eq!(theme_changes(&record), 0);
+}
+
+#[test]
+fn color_scheme_changes_default_theme() {
+    let [child_id] = widget_ids();
+    let record = Recording::default();
+    let child = SizedBox::empty().record(&record).with_id(child_id);
+    let mut harness = TestHarness::create(Flex::row().with_child(child));
+    let text_color = |harness: &TestHarness| {
+        harness
+            .get_widget(child_id)
+            .ctx()
+            .env()
+            .get(theme::TEXT_COLOR)
+    };
+    let light_text_color = theme::light_env().get(theme::TEXT_COLOR);
+    assert_eq!(text_color(&harness), *theme::TEXT_COLOR.default_value());
+
+    record.clear();
+    harness.set_color_scheme(WindowTheme::Light);
+    assert_eq!(text_color(&harness), light_text_color);
+    // The env is updated before widgets are told about the new scheme.
+    let updates = record.drain();
+    let position = |f: fn(&Record) -> bool| updates.iter().position(f).unwrap();
+    assert!(
+        position(|record| matches!(record, Record::U(Update::EnvChanged)))
+            < position(|record| matches!(record, Record::U(Update::ThemeChanged(_))))
+    );
+
+    // The values of the app take precedence over the ones of the scheme.
+    harness.set_env(Env::new().with(theme::TEXT_COLOR, Color::RED));
+    assert_eq!(text_color(&harness), Color::RED);
+    harness.set_env(Env::new());
+    assert_eq!(text_color(&harness), light_text_color);
+
+    harness.set_color_scheme(WindowTheme::Dark);
+    assert_eq!(text_color(&harness), *theme::TEXT_COLOR.default_value());
+}
+
+#[test]
+fn timers_fire_after_their_delay() {
+    let record = Recording::default();
+    let widget = ModularWidget::new(0)
+        .update_fn(|repeats, ctx, event| match event {
+            Update::WidgetAdded => {
+                ctx.request_timer(Duration::from_millis(100));
+                ctx.request_repeating_timer(Duration::from_millis(30));
+            }
+            Update::Timer(token) if *token == TimerToken(1) => {
+                *repeats += 1;
+                if *repeats == 2 {
+                    ctx.cancel_timer(*token);
+                }
+            }
+            _ => {}
+        })
+        .record(&record);
+
+    let mut harness = TestHarness::create(widget);
+    let fired = || -> Vec<TimerToken> {
+        record
+            .drain()
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::U(Update::Timer(token)) => Some(token),
+                _ => None,
+            })
+            .collect()
+    };
+    fired();
+
+    harness.move_timers_forward(Duration::from_millis(50));
+    assert_eq!(fired(), [TimerToken(1)]);
+    // Timers fire in the order of their deadlines, and the missed repetition is skipped.
+    harness.move_timers_forward(Duration::from_millis(50));
+    assert_eq!(fired(), [TimerToken(1), TimerToken(0)]);
+    harness.move_timers_forward(Duration::from_millis(100));
+    assert_eq!(fired(), []);
+}
+
+#[test]
+fn zero_interval_timers_repeat_every_millisecond() {
+    let record = Recording::default();
+    let widget = ModularWidget::new(())
+        .update_fn(|_, ctx, event| {
+            if let Update::WidgetAdded = event {
+                ctx.request_repeating_timer(Duration::ZERO);
+            }
+        })
+        .record(&record);
+
+    let mut harness = TestHarness::create(widget);
+    let fired = || {
+        record
+            .drain()
+            .into_iter()
+            .filter(|record| matches!(record, Record::U(Update::Timer(_))))
+            .count()
+    };
+    fired();
+
+    // The timer doesn't fire again until time passes.
+    harness.move_timers_forward(Duration::ZERO);
+    assert_eq!(fired(), 0);
+    harness.move_timers_forward(Duration::from_millis(1));
+    assert_eq!(fired(), 1);
+    harness.move_timers_forward(Duration::ZERO);
+    assert_eq!(fired(), 0);
+}
+
+#[test]
+fn tooltip_shown_after_pointer_rests() {
+    let [button_id] = widget_ids();
+    let button = ModularWidget::new(())
+        .layout_fn(|_, _, _| Size::new(50., 20.))
+        .update_fn(|_, ctx, event| {
+            if let Update::WidgetAdded = event {
+                ctx.set_tooltip("Save the document");
+            }
+        })
+        .with_id(button_id);
+    let widget = Flex::column()
+        .with_child(button)
+        .with_child(Label::new("Plain"));
+    let delay = Duration::from_millis(theme::TOOLTIP_DELAY);
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_move_to(button_id);
+    harness.move_timers_forward(delay / 2);
+    assert_eq!(harness.tooltip(), None);
+    harness.move_timers_forward(delay / 2);
+    assert_eq!(harness.tooltip(), Some("Save the document"));
+
+    // Moving the pointer hides the tooltip, and restarts the delay.
+    let center = harness
+        .get_widget(button_id)
+        .ctx()
+        .window_layout_rect()
+        .center();
+    harness.mouse_move(center + Vec2::new(5., 0.));
+    assert_eq!(harness.tooltip(), None);
+    harness.move_timers_forward(delay);
+    assert_eq!(harness.tooltip(), Some("Save the document"));
+
+    harness.mouse_move(center + Vec2::new(0., 200.));
+    harness.move_timers_forward(delay);
+    assert_eq!(harness.tooltip(), None);
+}
+
+#[test]
+fn context_menu_item_chosen() {
+    let [widget_id] = widget_ids();
+    let record = Recording::default();
+    let widget = ModularWidget::new(())
+        .layout_fn(|_, _, _| Size::new(300., 300.))
+        .pointer_event_fn(|_, ctx, event| {
+            if let PointerEvent::PointerDown(PointerButton::Secondary, state) = event {
+                let menu = Menu::new()
+                    .with_item(MenuItem::new("Cut"))
+                    .with_item(MenuItem::new("Copy").enabled(false))
+                    .with_separator()
+                    .with_item(MenuItem::new("Paste"));
+                let pos = Point::new(state.position.x, state.position.y);
+                ctx.show_context_menu(menu, pos);
+            }
+        })
+        .record(&record)
+        .with_id(widget_id);
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_move((20., 20.));
+    harness.mouse_button_press(PointerButton::Secondary);
+
+    // The menu is a popup which takes the focus, and is exposed as a menu.
+    let menu = harness.focused_widget().unwrap();
+    assert_eq!(menu.accessibility_role(), accesskit::Role::Menu);
+    let items = menu.children();
+    assert_eq!(items.len(), 3);
+    assert!(items
+        .iter()
+        .all(|item| item.accessibility_role() == accesskit::Role::MenuItem));
+    assert_eq!(items[1].ctx().position_in_set(), Some((1, 3)));
+    assert!(items[1].ctx().is_disabled());
+
+    // Releasing the button without moving doesn't choose the item under the pointer.
+    harness.mouse_button_release(PointerButton::Secondary);
+    assert_eq!(harness.pop_action(), None);
+
+    record.clear();
+    harness.mouse_move((30., 28.));
+    harness.mouse_button_press(PointerButton::Primary);
+    harness.mouse_button_release(PointerButton::Primary);
+    assert_eq!(
+        harness.pop_action(),
+        Some((Action::MenuItemSelected(0), widget_id))
+    );
+    // The menu received the pointer events, not the widgets under it.
+    assert!(record
+        .drain()
+        .iter()
+        .all(|event| !matches!(event, Record::PE(PointerEvent::PointerDown(..)))));
+
+    // Clicking outside of the menu closes it without an action.
+    harness.mouse_button_press(PointerButton::Secondary);
+    harness.mouse_move((250., 250.));
+    harness.mouse_button_press(PointerButton::Primary);
+    harness.mouse_button_release(PointerButton::Primary);
+    assert_eq!(harness.pop_action(), None);
+    record.clear();
+    harness.mouse_button_press(PointerButton::Primary);
+    assert!(record
+        .drain()
+        .iter()
+        .any(|event| matches!(event, Record::PE(PointerEvent::PointerDown(..)))));
+}
+
+#[test]
+fn popup_follows_anchor_and_closes_on_outside_click() {
+    let [spacer_id, anchor_id, popup_id] = widget_ids();
+    let record = Recording::default();
+    let anchor = ModularWidget::new(())
+        .layout_fn(|_, _, _| Size::new(100., 20.))
+        .pointer_event_fn(move |_, ctx, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                let popup = SizedBox::empty().height(40.).with_id(popup_id);
+                ctx.show_popup(popup, PopupPlacement::Below);
+            }
+        })
+        .record(&record)
+        .with_id(anchor_id);
+    let widget = Flex::column()
+        .with_child_id(SizedBox::empty().height(50.), spacer_id)
+        .with_child(anchor);
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_click_on(anchor_id);
+    let anchor_rect = harness.get_widget(anchor_id).ctx().window_layout_rect();
+    let popup_rect = harness.get_widget(popup_id).ctx().window_layout_rect();
+    assert_eq!(
+        popup_rect.origin(),
+        Point::new(anchor_rect.x0, anchor_rect.y1)
+    );
+    assert_eq!(popup_rect.width(), anchor_rect.width());
+
+    // The popup moves with its anchor.
+    harness.edit_widget(spacer_id, |mut spacer| {
+        SizedBox::set_height(&mut spacer.downcast(), 80.);
+    });
+    let anchor_rect = harness.get_widget(anchor_id).ctx().window_layout_rect();
+    let popup_rect = harness.get_widget(popup_id).ctx().window_layout_rect();
+    assert_eq!(
+        popup_rect.origin(),
+        Point::new(anchor_rect.x0, anchor_rect.y1)
+    );
+
+    // Clicking outside of the popup and its anchor closes it.
+    record.clear();
+    harness.mouse_click_on(spacer_id);
+    assert!(harness.try_get_widget(popup_id).is_none());
+    assert!(record
+        .drain()
+        .iter()
+        .any(|event| matches!(event, Record::U(Update::PopupClosed(_)))));
+}
+
+#[test]
+fn drag_and_drop() {
+    let [source_id, target_id, other_id] = widget_ids();
+    let source_record = Recording::default();
+    let target_record = Recording::default();
+    let source = ModularWidget::new(())
+        .pointer_event_fn(|_, ctx, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                ctx.start_drag(Box::new(42_u32), Scene::new());
+            }
+        })
+        .record(&source_record)
+        .with_id(source_id);
+    let target = ModularWidget::new(())
+        .pointer_event_fn(|_, ctx, event| match event {
+            PointerEvent::DragOver(_) => {
+                let accepted = ctx
+                    .drag_payload()
+                    .is_some_and(|payload| payload.is::<u32>());
+                ctx.set_drop_accepted(accepted);
+                ctx.set_handled();
+            }
+            PointerEvent::DragDrop(_) => {
+                let payload = ctx.take_drag_payload().unwrap();
+                let payload = payload.downcast::<u32>().unwrap();
+                ctx.submit_action(Action::TextChanged(payload.to_string()));
+            }
+            _ => {}
+        })
+        .record(&target_record)
+        .with_id(target_id);
+    let other = SizedBox::empty().width(10.).height(10.).with_id(other_id);
+    let widget = Flex::row()
+        .with_child(source)
+        .with_child(target)
+        .with_child(other);
+
+    let mut harness = TestHarness::create(widget);
+    let drag_events = |record: &Recording| -> Vec<&'static str> {
+        record
+            .drain()
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::PE(
+                    event @ (PointerEvent::DragOver(_)
+                    | PointerEvent::DragDrop(_)
+                    | PointerEvent::DragLeave(_)),
+                ) => Some(event.short_name()),
+                _ => None,
+            })
+            .collect()
+    };
+
+    harness.mouse_move_to(source_id);
+    source_record.clear();
+    harness.mouse_button_press(PointerButton::Primary);
+    // The drag takes the pointer from the source, which gets the end of its press.
+    let source_events: Vec<_> = source_record
+        .drain()
+        .into_iter()
+        .filter_map(|record| match record {
+            Record::PE(event) => Some(event.short_name()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(source_events, ["PointerDown", "PointerUp"]);
+    harness.mouse_move_to(target_id);
+    harness.mouse_move_to(other_id);
+    assert_eq!(drag_events(&target_record), ["DragOver", "DragLeave"]);
+
+    // Nothing accepts the drop over the other widget.
+    harness.mouse_button_release(PointerButton::Primary);
+    assert_eq!(harness.pop_action(), None);
+
+    harness.mouse_move_to(source_id);
+    harness.mouse_button_press(PointerButton::Primary);
+    harness.mouse_move_to(target_id);
+    harness.mouse_button_release(PointerButton::Primary);
+    assert_eq!(drag_events(&target_record), ["DragOver", "DragDrop"]);
+    assert_eq!(
+        harness.pop_action(),
+        Some((Action::TextChanged("42".into()), target_id))
+    );
+}
+
+#[test]
+fn dropped_files_bubble_from_widget_under_pointer() {
+    let [target_id, other_id] = widget_ids();
+    let row_record = Recording::default();
+    let other_record = Recording::default();
+    let target = SizedBox::empty().width(10.).height(10.).with_id(target_id);
+    let other = SizedBox::empty()
+        .width(10.)
+        .height(10.)
+        .record(&other_record)
+        .with_id(other_id);
+    let widget = Flex::row()
+        .with_child(target)
+        .with_child(other)
+        .record(&row_record);
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_move_to(target_id);
+    row_record.clear();
+    other_record.clear();
+
+    harness.drop_file("notes.txt");
+    assert!(row_record.drain().iter().any(|record| matches!(
+        record,
+        Record::PE(PointerEvent::DropFile(path, _)) if path.ends_with("notes.txt")
+    )));
+    assert!(!other_record
+        .drain()
+        .iter()
+        .any(|record| matches!(record, Record::PE(_))));
+}
+
+#[test]
+fn low_latency_ends_with_widget() {
+    let inking = ModularWidget::new(()).update_fn(|_, ctx, event| {
+        if let Update::WidgetAdded = event {
+            ctx.set_low_latency(true);
+        }
+    });
+    let widget = Flex::row().with_child(inking);
+
+    let mut harness = TestHarness::create(widget);
+    assert!(harness.low_latency());
+
+    harness.edit_root_widget(|mut root| {
+        let mut flex = root.downcast::<Flex>();
+        Flex::remove_child(&mut flex, 0);
+    });
+    // Signals are processed with the next event.
+    harness.mouse_move((0., 0.));
+    assert!(!harness.low_latency());
+}
+
+#[test]
+fn tooltips_are_dropped_with_widget() {
+    let with_tooltip = || {
+        ModularWidget::new(()).update_fn(|_, ctx, event| {
+            if let Update::WidgetAdded = event {
+                ctx.set_tooltip("Hello");
+            }
+        })
+    };
+    // The tooltip of the nested widget is dropped with its removed ancestor.
+    let widget = Flex::row()
+        .with_child(with_tooltip())
+        .with_child(SizedBox::new(with_tooltip()));
+
+    let mut harness = TestHarness::create(widget);
+    let tooltip_count = |harness: &mut TestHarness| {
+        harness.edit_root_widget(|root| root.ctx.global_state.tooltips.len())
+    };
+    assert_eq!(tooltip_count(&mut harness), 2);
+
+    harness.edit_root_widget(|mut root| {
+        let mut flex = root.downcast::<Flex>();
+        Flex::remove_child(&mut flex, 1);
+        Flex::remove_child(&mut flex, 0);
+    });
+    assert_eq!(tooltip_count(&mut harness), 0);
+}
+
+#[test]
+fn actions_are_ordered_by_stage() {
+    let [id] = widget_ids();
+    let widget = ModularWidget::new(false)
+        .pointer_event_fn(|submitted_in_layout, ctx, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                // Actions are ordered by the stage which submits them,
+                // not by the order of the calls.
+                ctx.request_layout();
+                ctx.mutate_self_later(|mut widget| {
+                    widget
+                        .ctx
+                        .submit_action(Action::TextChanged("mutate".into()));
+                });
+                ctx.submit_action(Action::TextChanged("event".into()));
+                *submitted_in_layout = true;
+            }
+        })
+        .layout_fn(|submitted_in_layout, ctx, _| {
+            if std::mem::take(submitted_in_layout) {
+                ctx.submit_action(Action::TextChanged("layout".into()));
+            }
+            Size::new(10., 10.)
+        })
+        .with_id(id);
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_move_to(id);
+    harness.mouse_button_press(PointerButton::Primary);
+
+    let mut actions = Vec::new();
+    while let Some((action, widget_id, sequence)) = harness.pop_action_with_sequence() {
+        actions.push((action, widget_id, sequence));
+    }
+    let expected = ["event", "mutate", "layout"].map(|text| (Action::TextChanged(text.into()), id));
+    assert_eq!(actions.len(), expected.len());
+    for (i, ((action, widget_id, sequence), (expected_action, expected_id))) in
+        actions.into_iter().zip(expected).enumerate()
+    {
+        assert_eq!(action, expected_action);
+        assert_eq!(widget_id, expected_id);
+        assert_eq!(sequence, i as u64);
+    }
+}
@@ -7,9 +7,10 @@ use std::cell::Cell;
 use std::rc::Rc;
 
 use smallvec::smallvec;
+use winit::keyboard::{Key, NamedKey};
 
 use crate::testing::{widget_ids, ModularWidget, ReplaceChild, TestHarness, TestWidgetExt as _};
-use crate::widget::Flex;
+use crate::widget::{Flex, FocusGroup, SimpleWidget};
 use crate::*;
 
 #[cfg(FALSE)]
@@ -238,3 +239,91 @@ fn resign_focus_on_disable() {
     assert_eq!(harness.window().focus_chain(), &[focus_2]);
     assert_eq!(harness.window().focus, None);
 }
+
+#[test]
+fn focus_next_skips_disabled_and_stashed_widgets() {
+    let [id_1, id_2, id_3, id_4, trigger_id] = widget_ids();
+    let focusable = || SimpleWidget::new(()).accepts_focus(true);
+    let forward = Rc::new(Cell::new(true));
+    let trigger = ModularWidget::new(forward.clone())
+        .pointer_event_fn(|forward, ctx, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                if forward.get() {
+                    ctx.focus_next();
+                } else {
+                    ctx.focus_prev();
+                }
+            }
+        })
+        .with_id(trigger_id);
+    let stashed = ModularWidget::new(WidgetPod::new_with_id(focusable(), id_3))
+        .register_children_fn(|child, ctx| ctx.register_child(child))
+        .update_fn(|child, ctx, event| {
+            if let Update::WidgetAdded = event {
+                ctx.set_stashed(child, true);
+            }
+        })
+        .layout_fn(|_, _, _| Size::ZERO)
+        .children_fn(|child| smallvec![child.id()]);
+    let widget = Flex::column()
+        .with_child_id(focusable(), id_1)
+        .with_child_id(focusable(), id_2)
+        .with_child(stashed)
+        .with_child_id(focusable(), id_4)
+        .with_child(trigger);
+
+    let mut harness = TestHarness::create(widget);
+    harness.edit_widget(id_2, |mut widget| widget.ctx.set_disabled(true));
+
+    let focused = |harness: &TestHarness| harness.focused_widget().map(|widget| widget.id());
+    harness.focus_on(Some(id_1));
+    harness.mouse_click_on(trigger_id);
+    assert_eq!(focused(&harness), Some(id_4));
+    // The focus wraps around after the last widget.
+    harness.mouse_click_on(trigger_id);
+    assert_eq!(focused(&harness), Some(id_1));
+    forward.set(false);
+    harness.mouse_click_on(trigger_id);
+    assert_eq!(focused(&harness), Some(id_4));
+}
+
+#[test]
+fn arrow_keys_move_focus_within_scope() {
+    let [top_left, top_right, bottom_left, bottom_right] = widget_ids();
+    let focusable = || {
+        SimpleWidget::new(())
+            .accepts_focus(true)
+            .with_preferred_size(Size::new(50., 50.))
+    };
+    let widget = Flex::column()
+        .with_child(
+            Flex::row()
+                .with_child_id(focusable(), top_left)
+                .with_child_id(focusable(), top_right),
+        )
+        .with_child(
+            FocusGroup::new(
+                Flex::row()
+                    .with_child_id(focusable(), bottom_left)
+                    .with_child_id(focusable(), bottom_right),
+            )
+            .with_focus_scope(true),
+        );
+
+    let mut harness = TestHarness::create(widget);
+    let focused = |harness: &TestHarness| harness.focused_widget().map(|widget| widget.id());
+
+    harness.focus_on(Some(top_left));
+    harness.keyboard_key(Key::Named(NamedKey::ArrowRight));
+    assert_eq!(focused(&harness), Some(top_right));
+    harness.keyboard_key(Key::Named(NamedKey::ArrowDown));
+    assert_eq!(focused(&harness), Some(bottom_right));
+    harness.keyboard_key(Key::Named(NamedKey::ArrowLeft));
+    assert_eq!(focused(&harness), Some(bottom_left));
+
+    // The scope keeps focus in the bottom row.
+    harness.keyboard_key(Key::Named(NamedKey::ArrowUp));
+    assert_eq!(focused(&harness), Some(bottom_left));
+    harness.keyboard_key(Key::Named(NamedKey::ArrowLeft));
+    assert_eq!(focused(&harness), Some(bottom_left));
+}
@@ -9,6 +9,7 @@ mod layout;
 mod lifecycle_basic;
 mod lifecycle_disable;
 mod lifecycle_focus;
+mod paint;
 mod safety_rails;
 mod status_change;
 mod widget_tree;
@@ -0,0 +1,293 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests related to painting.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use smallvec::smallvec;
+use vello::kurbo::{Affine, Point, Rect, Size, Vec2};
+use vello::peniko::Fill;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::testing::{widget_ids, ModularWidget, TestHarness, TestWidgetExt};
+use crate::text::{GlyphSnapping, TextPositioning};
+use crate::theme::PRIMARY_LIGHT;
+use crate::widget::{Flex, Label, SimpleWidget, SizedBox};
+use crate::WidgetPod;
+
+#[test]
+fn capture_widget_scene() {
+    let [row_id, painted_id, missing_id] = widget_ids();
+    let paint_count = Rc::new(Cell::new(0));
+
+    let painted = ModularWidget::new(paint_count.clone())
+        .layout_fn(|_, _, _| Size::new(20., 20.))
+        .paint_fn(|paint_count, _, scene| {
+            paint_count.set(paint_count.get() + 1);
+            let rect = Rect::new(0., 0., 20., 20.);
+            scene.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                *PRIMARY_LIGHT.default_value(),
+                None,
+                &rect,
+            );
+        });
+    // The row is placed below the bottom of the window.
+    let widget = Flex::column().with_spacer(500.).with_child_id(
+        Flex::row()
+            .with_child(painted.with_id(painted_id))
+            .with_child(SizedBox::empty().width(10.)),
+        row_id,
+    );
+
+    let mut harness = TestHarness::create_with_size(widget, Size::new(400., 400.));
+    let _ = harness.render();
+    assert_eq!(paint_count.get(), 0);
+
+    // Offscreen widgets are painted when captured, in local coordinates.
+    let captured = harness.capture_widget_scene(row_id).unwrap();
+    assert_eq!(paint_count.get(), 1);
+    assert_eq!(captured.size, harness.get_widget(row_id).ctx().size());
+    assert_eq!(captured.paint_rect.origin(), (0., 0.).into());
+    assert!(!captured.scene.encoding().is_empty());
+
+    // Cached scenes are reused.
+    let _ = harness.capture_widget_scene(row_id).unwrap();
+    assert_eq!(paint_count.get(), 1);
+
+    harness.edit_widget(painted_id, |mut painted| {
+        painted.ctx.request_paint_only();
+    });
+    let _ = harness.capture_widget_scene(painted_id).unwrap();
+    assert_eq!(paint_count.get(), 2);
+
+    assert!(harness.capture_widget_scene(missing_id).is_none());
+}
+
+#[test]
+fn text_snapped_when_animation_ends() {
+    let [id] = widget_ids();
+    let snapping = Rc::new(Cell::new(None));
+
+    let widget = ModularWidget::new(snapping.clone())
+        .layout_fn(|_, _, _| Size::new(20., 20.))
+        .paint_fn(|snapping, ctx, _| snapping.set(Some(ctx.glyph_snapping())))
+        .with_id(id);
+    let snapped = Some(GlyphSnapping {
+        scale: 1.0,
+        window_origin: Point::ORIGIN,
+    });
+
+    let mut harness = TestHarness::create(widget);
+    harness.set_text_positioning(TextPositioning::Auto);
+    // New widgets get an animation frame.
+    harness.animate_ms(0);
+    let _ = harness.render();
+    assert_eq!(snapping.take(), Some(snapped));
+
+    harness.edit_widget(id, |mut widget| {
+        widget.ctx.request_anim_frame();
+    });
+    let _ = harness.render();
+    assert_eq!(snapping.take(), Some(None));
+
+    // The widget doesn't request a repaint, but the change of positioning does.
+    harness.animate_ms(16);
+    let _ = harness.render();
+    assert_eq!(snapping.take(), Some(snapped));
+
+    harness.set_text_positioning(TextPositioning::Subpixel);
+    let _ = harness.render();
+    assert_eq!(snapping.take(), Some(None));
+}
+
+#[test]
+fn text_snapped_in_window_space() {
+    let snapping = Rc::new(Cell::new(None));
+
+    let widget = ModularWidget::new(snapping.clone())
+        .layout_fn(|_, _, _| Size::new(20., 20.))
+        .paint_fn(|snapping, ctx, _| snapping.set(ctx.glyph_snapping()));
+
+    let mut harness = TestHarness::create(Flex::column().with_spacer(10.25).with_child(widget));
+    harness.set_text_positioning(TextPositioning::Snapped);
+    let _ = harness.render();
+    // Glyphs are snapped to the pixel grid of the window, not the one of the widget.
+    assert_eq!(snapping.take().unwrap().window_origin.y, 10.25);
+}
+
+#[test]
+fn paint_flashing() {
+    let [flashed_id, idle_id] = widget_ids();
+    let square = || ModularWidget::new(()).layout_fn(|_, _, _| Size::new(50., 50.));
+    let widget = Flex::row()
+        .with_child_id(square(), flashed_id)
+        .with_child_id(square(), idle_id);
+
+    let mut harness = TestHarness::create_with_size(widget, Size::new(100., 50.));
+    let _ = harness.render();
+    harness.set_paint_flashing(true);
+
+    harness.edit_widget(flashed_id, |mut square| {
+        square.ctx.request_paint_only();
+    });
+    let image = harness.render();
+    let flashed_origin = harness.get_widget(flashed_id).ctx().window_origin();
+    let idle_origin = harness.get_widget(idle_id).ctx().window_origin();
+    let pixel_at = |origin: Point| *image.get_pixel(origin.x as u32 + 25, origin.y as u32 + 25);
+    assert_ne!(pixel_at(flashed_origin), pixel_at(idle_origin));
+
+    // The flash fades out after a few frames.
+    for _ in 0..10 {
+        let _ = harness.render();
+    }
+    let image = harness.render();
+    let pixel_at = |origin: Point| *image.get_pixel(origin.x as u32 + 25, origin.y as u32 + 25);
+    assert_eq!(pixel_at(flashed_origin), pixel_at(idle_origin));
+}
+
+#[test]
+fn frame_stats() {
+    let [label_id] = widget_ids();
+    let widget = Flex::row()
+        .with_child(SizedBox::empty().width(10.))
+        .with_child_id(Label::new("Hello"), label_id);
+
+    let mut harness = TestHarness::create(widget);
+    let _ = harness.paint();
+    let stats = harness.frame_stats();
+    // The row and its two children.
+    assert_eq!(stats.widgets_painted, 3);
+    assert_eq!(stats.scene_fragments, 3);
+    let passes = stats.pass_durations.iter().map(|(pass, _)| *pass);
+    assert!(passes.clone().any(|pass| pass == "layout"));
+    assert!(passes.clone().any(|pass| pass == "paint"));
+    assert_eq!(stats.present_duration, None);
+
+    // Only the label and its parent are laid out and painted again.
+    harness.edit_widget(label_id, |mut label| {
+        let mut label = label.downcast::<Label>();
+        Label::set_text(&mut label, "World");
+    });
+    let _ = harness.paint();
+    let stats = harness.frame_stats();
+    assert_eq!(stats.widgets_laid_out, 2);
+    assert_eq!(stats.widgets_painted, 2);
+    assert_eq!(stats.scene_fragments, 3);
+}
+
+#[test]
+fn compose_skips_clean_subtrees() {
+    let [parent_id, child_id] = widget_ids();
+    let offset = Rc::new(Cell::new(Vec2::ZERO));
+
+    let child = WidgetPod::new_with_id(SizedBox::empty().width(20.).height(20.), child_id);
+    let parent = ModularWidget::new((child, offset.clone()))
+        .register_children_fn(|(child, _), ctx| ctx.register_child(child))
+        .layout_fn(|(child, _), ctx, bc| {
+            ctx.run_layout(child, bc);
+            ctx.place_child(child, Point::ORIGIN);
+            Size::new(50., 50.)
+        })
+        .compose_fn(|(child, offset), ctx| {
+            ctx.set_child_presentation_offset(child, offset.get());
+        })
+        .children_fn(|(child, _)| smallvec![child.id()])
+        .with_id(parent_id);
+    let widget = Flex::row()
+        .with_child(parent)
+        .with_child(Flex::column().with_child(SizedBox::empty().width(10.)));
+
+    let mut harness = TestHarness::create(widget);
+    let _ = harness.paint();
+
+    offset.set(Vec2::new(0., 10.));
+    harness.edit_widget(parent_id, |mut parent| parent.ctx.request_compose());
+    let _ = harness.paint();
+    // The root, the row, the parent and the child it moved. The column is skipped.
+    assert_eq!(harness.frame_stats().widgets_composed, 4);
+    assert_eq!(
+        harness
+            .get_widget(child_id)
+            .ctx()
+            .window_layout_rect()
+            .origin(),
+        harness.get_widget(parent_id).ctx().window_origin() + Vec2::new(0., 10.)
+    );
+}
+
+#[test]
+fn child_opacity_composites_cached_scene() {
+    let [parent_id, child_id] = widget_ids();
+    let opacity = Rc::new(Cell::new(1.));
+
+    let child = WidgetPod::new_with_id(Label::new("Fading"), child_id);
+    let parent = ModularWidget::new((child, opacity.clone()))
+        .register_children_fn(|(child, _), ctx| ctx.register_child(child))
+        .layout_fn(|(child, _), ctx, bc| {
+            let size = ctx.run_layout(child, bc);
+            ctx.place_child(child, Point::ORIGIN);
+            size
+        })
+        .compose_fn(|(child, opacity), ctx| ctx.set_child_opacity(child, opacity.get()))
+        .children_fn(|(child, _)| smallvec![child.id()])
+        .with_id(parent_id);
+
+    let mut harness = TestHarness::create(parent);
+    let _ = harness.paint();
+
+    opacity.set(0.5);
+    harness.edit_widget(parent_id, |mut parent| parent.ctx.request_compose());
+    let _ = harness.paint();
+    assert_eq!(harness.get_widget(child_id).ctx().opacity(), 0.5);
+    // The label isn't painted again, its scene is only composited with the new opacity.
+    assert_eq!(harness.frame_stats().widgets_painted, 0);
+
+    // Out-of-range values are clamped, and NaN is treated as fully opaque.
+    for (value, expected) in [(-1., 0.), (2., 1.), (f32::NAN, 1.), (f32::NEG_INFINITY, 1.)] {
+        opacity.set(value);
+        harness.edit_widget(parent_id, |mut parent| parent.ctx.request_compose());
+        assert_eq!(harness.get_widget(child_id).ctx().opacity(), expected);
+    }
+}
+
+#[test]
+fn diagnostics_report() {
+    let mut harness = TestHarness::create(Label::new("Hello"));
+    let _ = harness.paint();
+    let report = harness.diagnostics_report();
+    assert!(report.contains("# Renderer\n\nunknown\n"));
+    assert!(report.contains("window: 1\n"));
+    assert!(report.contains("clipboard: "));
+    assert!(report.contains("widgets painted: 1\n"));
+    // The test font is registered.
+    let fonts = report.split("# Fonts\n\n").nth(1).unwrap();
+    assert!(fonts.lines().next().is_some());
+}
+
+#[test]
+fn focus_ring_follows_keyboard_focus() {
+    let [first_id, second_id] = widget_ids();
+    let focusable = || SimpleWidget::new(()).accepts_focus(true);
+    let widget = Flex::row()
+        .with_child_id(focusable(), first_id)
+        .with_child_id(focusable(), second_id);
+
+    let mut harness = TestHarness::create(widget);
+    harness.focus_on(Some(first_id));
+    let paths_without_ring = harness.paint().encoding().n_paths;
+    assert!(!harness.get_widget(first_id).ctx().is_focus_visible());
+
+    harness.keyboard_key(Key::Named(NamedKey::ArrowRight));
+    assert!(harness.get_widget(second_id).ctx().is_focus_visible());
+    assert_eq!(harness.paint().encoding().n_paths, paths_without_ring + 1);
+    // The ring is painted around the cached scene of the widget.
+    assert_eq!(harness.frame_stats().widgets_painted, 0);
+
+    harness.mouse_click_on(second_id);
+    assert!(!harness.get_widget(second_id).ctx().is_focus_visible());
+    assert_eq!(harness.paint().encoding().n_paths, paths_without_ring);
+}
@@ -2,9 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use assert_matches::assert_matches;
-
-use crate::event::{PointerButton, PointerEvent, PointerState};
-use crate::testing::{widget_ids, Record, Recording, TestHarness, TestWidgetExt as _};
+use winit::event::TouchPhase;
+
+use crate::event::{PenState, PointerButton, PointerEvent, PointerId, PointerState};
+use crate::gesture::{LONG_PRESS_DURATION, MULTI_TAP_INTERVAL};
+use crate::kurbo::{Shape as _, Vec2};
+use crate::testing::{
+    widget_ids, ModularWidget, Record, Recording, TestHarness, TestWidgetExt as _,
+};
 use crate::widget::{Button, Flex, SizedBox};
 use crate::*;
 
@@ -249,10 +254,14 @@ fn get_pointer_events_while_active() {
 
     assert_eq!(harness.pointer_capture_target_id(), Some(button));
 
-    // We move the cursor away without releasing the button
+    // We move the cursor away without releasing the button, which starts a drag
 
     harness.mouse_move_to(empty);
 
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::DragStart(_))
+    );
     assert_matches!(
         next_pointer_event(&button_rec),
         Some(PointerEvent::PointerMove(_))
@@ -267,7 +276,7 @@ fn get_pointer_events_while_active() {
 
     assert_matches!(
         next_pointer_event(&button_rec),
-        Some(PointerEvent::MouseWheel(_, _))
+        Some(PointerEvent::MouseWheel(..))
     );
     assert_matches!(next_pointer_event(&button_rec), None);
 
@@ -323,6 +332,10 @@ fn automatically_lose_pointer_on_pointer_leave() {
 
     // The pointer moves to empty space. The button is notified and still holds the capture.
     harness.mouse_move_to(empty);
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::DragStart(_))
+    );
     assert_matches!(
         next_pointer_event(&button_rec),
         Some(PointerEvent::PointerMove(_))
@@ -346,3 +359,353 @@ fn automatically_lose_pointer_on_pointer_leave() {
     harness.process_pointer_event(PointerEvent::PointerLeave(PointerState::empty()));
     assert_matches!(next_pointer_event(&button_rec), None);
 }
+
+#[test]
+fn presses_are_counted_and_become_long_presses_or_drags() {
+    let [button, empty] = widget_ids();
+    let button_rec = Recording::default();
+
+    let widget = Flex::column()
+        .with_child_id(SizedBox::empty().width(10.0).height(10.0), empty)
+        .with_child_id(Button::new("hello").record(&button_rec), button);
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_move_to(button);
+    button_rec.clear();
+
+    // Presses in quick succession at the same place are counted.
+    for expected in 1..=3 {
+        harness.mouse_button_press(PointerButton::Primary);
+        harness.mouse_button_release(PointerButton::Primary);
+        assert_matches!(
+            next_pointer_event(&button_rec),
+            Some(PointerEvent::PointerDown(_, state)) if state.count == expected
+        );
+        assert_matches!(
+            next_pointer_event(&button_rec),
+            Some(PointerEvent::PointerUp(_, state)) if state.count == expected
+        );
+    }
+
+    // Too late to continue the sequence.
+    harness.move_timers_forward(MULTI_TAP_INTERVAL * 2);
+    harness.mouse_button_press(PointerButton::Primary);
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::PointerDown(_, state)) if state.count == 1
+    );
+
+    // Holding the button without moving the pointer is a long press, reported once.
+    harness.move_timers_forward(LONG_PRESS_DURATION);
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::LongPress(state)) if state.count == 1
+    );
+    harness.move_timers_forward(LONG_PRESS_DURATION);
+    assert_matches!(next_pointer_event(&button_rec), None);
+    harness.mouse_button_release(PointerButton::Primary);
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::PointerUp(..))
+    );
+
+    // Moving the pointer far enough while pressed starts a drag, right before the move.
+    harness.mouse_button_press(PointerButton::Primary);
+    harness.mouse_move_to(empty);
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::PointerDown(_, state)) if state.count == 1
+    );
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::DragStart(_))
+    );
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::PointerMove(_))
+    );
+    harness.mouse_move_to(button);
+    assert_matches!(
+        next_pointer_event(&button_rec),
+        Some(PointerEvent::PointerMove(_))
+    );
+
+    // A drag doesn't become a long press.
+    harness.move_timers_forward(LONG_PRESS_DURATION);
+    assert_matches!(next_pointer_event(&button_rec), None);
+}
+
+#[test]
+fn pointer_transparent_widgets_are_skipped() {
+    let [button, parent] = widget_ids();
+
+    let widget = Flex::column()
+        .with_child_id(Button::new("overlay"), button)
+        .with_id(parent);
+
+    let mut harness = TestHarness::create(widget);
+
+    harness.mouse_move_to(button);
+    assert!(is_hovered(&harness, button));
+
+    harness.edit_widget(button, |mut button| {
+        button.ctx.set_pointer_transparent(true)
+    });
+    assert!(!is_hovered(&harness, button));
+    assert!(is_hovered(&harness, parent));
+    assert!(harness.get_widget(button).ctx().is_pointer_transparent());
+
+    harness.edit_widget(button, |mut button| {
+        button.ctx.set_pointer_transparent(false);
+    });
+    assert!(is_hovered(&harness, button));
+}
+
+#[test]
+fn locked_pointer_gets_relative_motion() {
+    let [viewport, button] = widget_ids();
+    let viewport_rec = Recording::default();
+
+    let orbit = ModularWidget::new(())
+        .pointer_event_fn(|_, ctx, event| {
+            if let PointerEvent::PointerDown(..) = event {
+                ctx.lock_pointer();
+            }
+        })
+        .layout_fn(|_, _, _| Size::new(50., 50.));
+    let widget = Flex::row()
+        .with_child_id(orbit.record(&viewport_rec), viewport)
+        .with_child_id(Button::new("button"), button);
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_click_on(viewport);
+    assert!(harness.pointer_locked());
+    assert!(harness.get_widget(viewport).ctx().has_pointer_lock());
+
+    // Motion goes to the locked widget, even when the pointer is over another widget.
+    harness.mouse_move_to(button);
+    viewport_rec.clear();
+    harness.mouse_move_locked(Vec2::new(3., -4.));
+    assert_matches!(
+        next_pointer_event(&viewport_rec),
+        Some(PointerEvent::LockedMove(delta, _)) if delta == Vec2::new(3., -4.)
+    );
+
+    // Losing the window focus releases the lock, and the widget is told with a leave event.
+    harness.process_text_event(TextEvent::FocusChange(false));
+    assert_matches!(
+        next_pointer_event(&viewport_rec),
+        Some(PointerEvent::PointerLeave(_))
+    );
+    assert!(!harness.pointer_locked());
+    assert!(!harness.get_widget(viewport).ctx().has_pointer_lock());
+
+    viewport_rec.clear();
+    harness.mouse_move_locked(Vec2::new(1., 1.));
+    assert!(next_pointer_event(&viewport_rec).is_none());
+}
+
+#[test]
+fn pointer_outside_window_shape_hovers_nothing() {
+    let [button] = widget_ids();
+    let widget = Flex::row().with_child_id(Button::new("button"), button);
+
+    let mut harness = TestHarness::create(widget);
+    let button_rect = harness.get_widget(button).ctx().window_layout_rect();
+    harness.mouse_move_to(button);
+    assert!(is_hovered(&harness, button));
+
+    // Only the left half of the button is part of the window.
+    let shape = Rect::new(0., 0., button_rect.center().x, 1000.).to_path(0.1);
+    harness.edit_root_widget(|mut root| root.ctx.set_window_shape(Some(shape)));
+    assert!(!is_hovered(&harness, button));
+
+    harness.mouse_move((button_rect.x0 + 1., button_rect.center().y));
+    assert!(is_hovered(&harness, button));
+
+    harness.edit_root_widget(|mut root| root.ctx.set_window_shape(None));
+    harness.mouse_move_to(button);
+    assert!(is_hovered(&harness, button));
+}
+
+#[test]
+fn hovering_static_widget_skips_redraw() {
+    let [box_id, button_id] = widget_ids();
+    let widget = Flex::column()
+        .with_child_id(SizedBox::empty().width(50.).height(50.), box_id)
+        .with_child_id(Button::new("Click"), button_id);
+
+    let mut harness = TestHarness::create(widget);
+    let _ = harness.paint();
+
+    // The sized box doesn't repaint when hovered, so only the accessibility tree changes.
+    harness.mouse_move_to(box_id);
+    assert!(is_hovered(&harness, box_id));
+    assert!(!harness.redraw_requested());
+
+    // The button is highlighted when hovered.
+    harness.mouse_move_to(button_id);
+    assert!(is_hovered(&harness, button_id));
+    assert!(harness.redraw_requested());
+}
+
+#[test]
+fn touches_are_routed_and_captured_independently() {
+    let [left, right] = widget_ids();
+    let left_rec = Recording::default();
+    let right_rec = Recording::default();
+    let pad = || {
+        ModularWidget::new(())
+            .pointer_event_fn(|_, ctx, event| {
+                if let PointerEvent::PointerDown(..) = event {
+                    ctx.capture_pointer();
+                }
+            })
+            .layout_fn(|_, _, _| Size::new(100., 100.))
+    };
+    let widget = Flex::row()
+        .with_child_id(pad().record(&left_rec), left)
+        .with_child_id(pad().record(&right_rec), right);
+
+    let mut harness = TestHarness::create(widget);
+    let left_pos = harness.get_widget(left).ctx().window_layout_rect().center();
+    let right_pos = harness
+        .get_widget(right)
+        .ctx()
+        .window_layout_rect()
+        .center();
+
+    harness.touch(1, TouchPhase::Started, left_pos);
+    harness.touch(2, TouchPhase::Started, right_pos);
+    let Some(PointerEvent::PointerDown(_, state)) = next_pointer_event(&right_rec) else {
+        panic!("expected the second finger to press the right pad");
+    };
+    assert_eq!(state.pointer_id, PointerId::Touch(2));
+    assert!(!state.is_primary);
+    // Only the primary touch hovers widgets.
+    assert!(is_hovered(&harness, left));
+    assert!(!is_hovered(&harness, right));
+
+    // Each finger keeps sending its events to the pad it captured.
+    left_rec.clear();
+    harness.touch(2, TouchPhase::Moved, left_pos);
+    harness.touch(1, TouchPhase::Moved, right_pos);
+    assert_matches!(
+        next_pointer_event(&right_rec),
+        Some(PointerEvent::PointerMove(state)) if state.pointer_id == PointerId::Touch(2)
+    );
+    // Two-finger gestures also go to the pad under the fingers' centroid.
+    let mut next_left =
+        std::iter::from_fn(|| next_pointer_event(&left_rec)).filter(|e| !e.is_gesture());
+    // The primary finger moved far enough to start a drag.
+    assert_matches!(next_left.next(), Some(PointerEvent::DragStart(_)));
+    assert_matches!(
+        next_left.next(),
+        Some(PointerEvent::PointerMove(state)) if state.pointer_id == PointerId::Touch(1)
+    );
+    assert_eq!(harness.pointer_capture_target_id(), Some(left));
+
+    harness.touch(2, TouchPhase::Ended, left_pos);
+    harness.touch(1, TouchPhase::Ended, right_pos);
+    assert_eq!(harness.pointer_capture_target_id(), None);
+}
+
+#[test]
+fn two_fingers_pinch_rotate_and_pan() {
+    let recording = Recording::default();
+    let widget = ModularWidget::new(())
+        .layout_fn(|_, _, bc| bc.max())
+        .record(&recording);
+
+    let mut harness = TestHarness::create(widget);
+    let next_gesture = || loop {
+        match next_pointer_event(&recording) {
+            Some(event) if event.is_gesture() => return Some(event),
+            Some(_) => {}
+            None => return None,
+        }
+    };
+
+    harness.touch(1, TouchPhase::Started, (100., 100.));
+    harness.touch(2, TouchPhase::Started, (200., 100.));
+    recording.clear();
+
+    // Spreading the fingers apart zooms in, and moves their centroid.
+    harness.touch(2, TouchPhase::Moved, (300., 100.));
+    assert_matches!(
+        next_gesture(),
+        Some(PointerEvent::Pinch(delta, state)) if delta == 1. && state.position.x == 200.
+    );
+    assert_matches!(
+        next_gesture(),
+        Some(PointerEvent::Pan(delta, _)) if delta == Vec2::new(50., 0.)
+    );
+    assert_matches!(next_gesture(), None);
+
+    // Swinging the second finger downwards turns clockwise.
+    harness.touch(2, TouchPhase::Moved, (100., 300.));
+    let Some(PointerEvent::Rotate(angle, _)) = next_gesture() else {
+        panic!("expected a rotation");
+    };
+    assert!((angle - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    assert_matches!(
+        next_gesture(),
+        Some(PointerEvent::Pan(delta, _)) if delta == Vec2::new(-100., 100.)
+    );
+
+    harness.touch(1, TouchPhase::Ended, (100., 100.));
+    recording.clear();
+    harness.touch(2, TouchPhase::Moved, (100., 200.));
+    assert_matches!(next_gesture(), None);
+}
+
+#[test]
+fn pen_state_reaches_widgets() {
+    let recording = Recording::default();
+    let widget = ModularWidget::new(())
+        .layout_fn(|_, _, bc| bc.max())
+        .record(&recording);
+    let mut harness = TestHarness::create(widget);
+    let next_pointer_down = || {
+        std::iter::from_fn(|| next_pointer_event(&recording))
+            .find(|event| matches!(event, PointerEvent::PointerDown(..)))
+    };
+
+    let pen = PenState {
+        pressure: 0.8,
+        eraser: true,
+        ..Default::default()
+    };
+    harness.pen(1, TouchPhase::Started, (100., 100.), pen);
+    let Some(PointerEvent::PointerDown(_, state)) = next_pointer_down() else {
+        panic!("expected the pen to press the widget");
+    };
+    assert_eq!(state.pen, Some(pen));
+    assert_eq!(state.pressure(), Some(0.8));
+
+    recording.clear();
+    harness.touch(2, TouchPhase::Started, (200., 100.));
+    let Some(PointerEvent::PointerDown(_, state)) = next_pointer_down() else {
+        panic!("expected the finger to press the widget");
+    };
+    assert_eq!(state.pen, None);
+    assert_eq!(state.pressure(), None);
+}
+
+#[test]
+fn pointer_transparent_widgets_keep_focus() {
+    let [button] = widget_ids();
+
+    let widget = Flex::column().with_child_id(Button::new("overlay"), button);
+
+    let mut harness = TestHarness::create(widget);
+    harness.focus_on(Some(button));
+
+    harness.edit_widget(button, |mut button| {
+        button.ctx.set_pointer_transparent(true);
+    });
+    assert_eq!(
+        harness.focused_widget().map(|widget| widget.id()),
+        Some(button)
+    );
+}
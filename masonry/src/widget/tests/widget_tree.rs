@@ -4,7 +4,8 @@
 use insta::assert_debug_snapshot;
 
 use crate::testing::{widget_ids, TestHarness};
-use crate::widget::{Flex, Label};
+use crate::widget::{Button, Flex, Label, SizedBox, WidgetMut};
+use crate::{Size, Widget};
 
 #[test]
 fn access_grandchild_widget() {
@@ -26,3 +27,81 @@ fn access_grandchild_widget() {
 
     assert_debug_snapshot!(harness.root_widget());
 }
+
+#[test]
+fn write_bug_report() {
+    let [id_button] = widget_ids();
+
+    let widget = Flex::column().with_child_id(Button::new("Report"), id_button);
+
+    let mut harness = TestHarness::create(widget);
+    for x in 0..150 {
+        harness.mouse_move((x as f64, 0.));
+    }
+    harness.mouse_click_on(id_button);
+
+    // Only the last 100 events are kept.
+    let events: Vec<_> = harness.event_history().collect();
+    assert_eq!(events.len(), 100);
+    assert!(events.last().unwrap().starts_with("PointerUp"));
+
+    let dir = tempfile::tempdir().unwrap();
+    harness.write_bug_report(dir.path()).unwrap();
+
+    let tree = std::fs::read_to_string(dir.path().join("widget_tree.txt")).unwrap();
+    assert!(tree.contains("Report"));
+    let events = std::fs::read_to_string(dir.path().join("events.txt")).unwrap();
+    assert!(events.lines().any(|event| event.starts_with("PointerDown")));
+    assert!(dir.path().join("frame.png").exists());
+}
+
+#[test]
+fn widget_report() {
+    let [id_row, id_button] = widget_ids();
+
+    let widget = Flex::column()
+        .with_child_id(
+            Flex::row().with_child_id(Button::new("Report"), id_button),
+            id_row,
+        )
+        .with_child(Label::new("Elsewhere"));
+
+    let mut harness = TestHarness::create(widget);
+    harness.mouse_click_on(id_button);
+    assert_eq!(harness.action_history().count(), 0);
+
+    harness.set_record_actions(true);
+    harness.mouse_click_on(id_button);
+    let actions: Vec<_> = harness.action_history().collect();
+    assert_eq!(actions, [format!("#1 ButtonPressed from {id_button}")]);
+
+    let report = harness.widget_report(id_row).unwrap();
+    assert!(report.starts_with(&format!("# Widget {id_row}")));
+    assert!(report.contains("Report"));
+    assert!(!report.contains("Elsewhere"));
+    assert!(report.contains("PointerUp"));
+    assert!(report.contains("ButtonPressed"));
+}
+
+/// Set the width of a `SizedBox` to `10 * step`, then queue the next step.
+fn grow(mut widget: WidgetMut<'_, Box<dyn Widget>>, step: u32) {
+    let mut sized_box = widget.downcast::<SizedBox>();
+    SizedBox::set_width(&mut sized_box, 10. * f64::from(step));
+    if step < 6 {
+        sized_box
+            .ctx
+            .mutate_self_later(move |widget| grow(widget, step + 1));
+    }
+}
+
+#[test]
+fn chained_mutate_callbacks_run_in_one_pass() {
+    let [box_id] = widget_ids();
+    let widget = Flex::row().with_child_id(SizedBox::empty().height(10.), box_id);
+    let mut harness = TestHarness::create(widget);
+
+    // Each callback queues the next one, more times than the rewrite passes are re-run.
+    harness.edit_widget(box_id, |widget| grow(widget, 1));
+    assert!(!harness.run_mutations_now());
+    assert_eq!(harness.get_widget(box_id).ctx().size(), Size::new(60., 10.));
+}
@@ -6,15 +6,15 @@ use parley::layout::Alignment;
 use parley::style::{FontFamily, FontStack};
 use smallvec::SmallVec;
 use tracing::{trace_span, Span};
-use vello::kurbo::{Affine, Point, Size, Stroke};
+use vello::kurbo::{Affine, Point, Rect, Size, Stroke, Vec2};
 use vello::peniko::{BlendMode, Color};
 use vello::Scene;
 use winit::event::Ime;
 
-use crate::text::{TextBrush, TextEditor, TextWithSelection};
+use crate::text::{CaretStyle, TextBrush, TextEditor, TextStyle, TextWithSelection};
 use crate::widget::{LineBreaking, WidgetMut};
 use crate::{
-    AccessCtx, AccessEvent, BoxConstraints, CursorIcon, EventCtx, LayoutCtx, PaintCtx,
+    theme, AccessCtx, AccessEvent, BoxConstraints, CursorIcon, EventCtx, LayoutCtx, PaintCtx,
     PointerEvent, QueryCtx, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
 };
 
@@ -43,17 +43,40 @@ pub struct Textbox {
     editor: TextEditor,
     line_break_mode: LineBreaking,
     show_disabled: bool,
-    brush: TextBrush,
+    /// Text properties set on this widget, which take precedence over the inherited style.
+    style: TextStyle,
+    /// The caret style and selection color set on this widget, which take precedence over
+    /// the theme values of the env.
+    caret_style: Option<CaretStyle>,
+    selection_color: Option<Color>,
+    soft_wrap_indicator: Option<SoftWrapIndicator>,
+    /// Whether the caret should be scrolled into view after the next layout.
+    scroll_to_caret: bool,
 }
 
+/// A function painting an indicator for a line which was wrapped to fit the width of
+/// a [`Textbox`].
+///
+/// It is given the area after the end of the line, as returned by
+/// [`TextLayout::soft_wrap_rects`](crate::text::TextLayout::soft_wrap_rects), in the
+/// local coordinates of the textbox.
+pub type SoftWrapIndicator = Box<dyn Fn(&mut Scene, Rect)>;
+
 // --- MARK: BUILDERS ---
 impl Textbox {
     pub fn new(initial_text: impl Into<String>) -> Self {
         Textbox {
-            editor: TextEditor::new(initial_text.into(), crate::theme::TEXT_SIZE_NORMAL as f32),
+            editor: TextEditor::new(
+                initial_text.into(),
+                *theme::TEXT_SIZE_NORMAL.default_value() as f32,
+            ),
             line_break_mode: LineBreaking::WordWrap,
             show_disabled: true,
-            brush: crate::theme::TEXT_COLOR.into(),
+            style: TextStyle::default(),
+            caret_style: None,
+            selection_color: None,
+            soft_wrap_indicator: None,
+            scroll_to_caret: false,
         }
     }
 
@@ -64,13 +87,15 @@ impl Textbox {
 
     #[doc(alias = "with_text_color")]
     pub fn with_text_brush(mut self, brush: impl Into<TextBrush>) -> Self {
-        self.brush = brush.into();
-        self.editor.set_brush(self.brush.clone());
+        let brush = brush.into();
+        self.editor.set_brush(brush.clone());
+        self.style.brush = Some(brush);
         self
     }
 
     pub fn with_text_size(mut self, size: f32) -> Self {
         self.editor.set_text_size(size);
+        self.style.text_size = Some(size);
         self
     }
 
@@ -81,6 +106,7 @@ impl Textbox {
 
     pub fn with_font(mut self, font: FontStack<'static>) -> Self {
         self.editor.set_font(font);
+        self.style.font = Some(font);
         self
     }
     pub fn with_font_family(self, font: FontFamily<'static>) -> Self {
@@ -91,6 +117,33 @@ impl Textbox {
         self.line_break_mode = line_break_mode;
         self
     }
+
+    /// Builder-style method for setting how the caret is drawn.
+    ///
+    /// A [block](crate::text::CaretShape::Block) caret also makes typing overwrite the character it covers.
+    pub fn with_caret_style(mut self, style: CaretStyle) -> Self {
+        self.editor.set_caret_style(style);
+        self.caret_style = Some(style);
+        self
+    }
+
+    /// Builder-style method for setting the color behind selected text.
+    pub fn with_selection_color(mut self, color: Color) -> Self {
+        self.editor.set_selection_color(color);
+        self.selection_color = Some(color);
+        self
+    }
+
+    /// Builder-style method for painting an indicator on lines which were wrapped.
+    ///
+    /// See [`SoftWrapIndicator`].
+    pub fn with_soft_wrap_indicator(
+        mut self,
+        indicator: impl Fn(&mut Scene, Rect) + 'static,
+    ) -> Self {
+        self.soft_wrap_indicator = Some(Box::new(indicator));
+        self
+    }
 }
 
 // --- MARK: WIDGETMUT ---
@@ -126,19 +179,20 @@ impl Textbox {
     #[doc(alias = "set_text_color")]
     pub fn set_text_brush(this: &mut WidgetMut<'_, Self>, brush: impl Into<TextBrush>) {
         let brush = brush.into();
-        this.widget.brush = brush;
+        this.widget.style.brush = Some(brush.clone());
         if !this.ctx.is_disabled() {
-            let brush = this.widget.brush.clone();
             Self::set_text_properties(this, |layout| layout.set_brush(brush));
         }
     }
     pub fn set_text_size(this: &mut WidgetMut<'_, Self>, size: f32) {
+        this.widget.style.text_size = Some(size);
         Self::set_text_properties(this, |layout| layout.set_text_size(size));
     }
     pub fn set_alignment(this: &mut WidgetMut<'_, Self>, alignment: Alignment) {
         Self::set_text_properties(this, |layout| layout.set_text_alignment(alignment));
     }
     pub fn set_font(this: &mut WidgetMut<'_, Self>, font_stack: FontStack<'static>) {
+        this.widget.style.font = Some(font_stack);
         Self::set_text_properties(this, |layout| layout.set_font(font_stack));
     }
     pub fn set_font_family(this: &mut WidgetMut<'_, Self>, family: FontFamily<'static>) {
@@ -146,10 +200,52 @@ impl Textbox {
     }
     pub fn set_line_break_mode(this: &mut WidgetMut<'_, Self>, line_break_mode: LineBreaking) {
         this.widget.line_break_mode = line_break_mode;
+        this.ctx.request_layout();
+    }
+    pub fn set_caret_style(this: &mut WidgetMut<'_, Self>, style: CaretStyle) {
+        this.widget.caret_style = Some(style);
+        this.widget.editor.set_caret_style(style);
+        this.ctx.request_render();
+    }
+    pub fn set_selection_color(this: &mut WidgetMut<'_, Self>, color: Color) {
+        this.widget.selection_color = Some(color);
+        Self::set_text_properties(this, |layout| layout.set_selection_color(color));
+    }
+    /// Draw the caret with the theme values of the env again.
+    pub fn reset_caret_style(this: &mut WidgetMut<'_, Self>) {
+        this.widget.caret_style = None;
+        let style = CaretStyle::from_env(this.ctx.env());
+        this.widget.editor.set_caret_style(style);
+        this.ctx.request_render();
+    }
+    /// Highlight the selection with the theme color of the env again.
+    pub fn reset_selection_color(this: &mut WidgetMut<'_, Self>) {
+        this.widget.selection_color = None;
+        let color = this.ctx.env().get(theme::SELECTED_TEXT_BACKGROUND_COLOR);
+        Self::set_text_properties(this, |layout| layout.set_selection_color(color));
+    }
+    pub fn set_soft_wrap_indicator(
+        this: &mut WidgetMut<'_, Self>,
+        indicator: Option<SoftWrapIndicator>,
+    ) {
+        this.widget.soft_wrap_indicator = indicator;
         this.ctx.request_render();
     }
 }
 
+impl Textbox {
+    /// The brush of the text, given whether the textbox is disabled.
+    fn brush(&self, ctx: &UpdateCtx, disabled: bool) -> TextBrush {
+        if self.show_disabled && disabled {
+            ctx.env().get(theme::DISABLED_TEXT_COLOR).into()
+        } else {
+            self.style
+                .inherit_from(ctx.text_style())
+                .brush_or_default(ctx.env())
+        }
+    }
+}
+
 // --- MARK: IMPL WIDGET ---
 impl Widget for Textbox {
     fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
@@ -197,8 +293,8 @@ impl Widget for Textbox {
             // Some platforms will send a lot of spurious Preedit events.
             // We only want to request a scroll on user input.
             if !matches!(event, TextEvent::Ime(Ime::Preedit(preedit, ..)) if preedit.is_empty()) {
-                // TODO - Use request_scroll_to with cursor rect
-                ctx.request_scroll_to_this();
+                // The caret is only known after layout.
+                self.scroll_to_caret = true;
             }
             ctx.set_handled();
             // TODO: only some handlers need this repaint
@@ -233,23 +329,34 @@ impl Widget for Textbox {
                 ctx.request_layout();
             }
             Update::DisabledChanged(disabled) => {
-                if self.show_disabled {
-                    if *disabled {
-                        self.editor.set_brush(crate::theme::DISABLED_TEXT_COLOR);
-                    } else {
-                        self.editor.set_brush(self.brush.clone());
-                    }
-                }
+                let brush = self.brush(ctx, *disabled);
+                self.editor.set_brush(brush);
                 // TODO: Parley seems to require a relayout when colours change
                 ctx.request_layout();
             }
+            Update::TextStyleChanged | Update::EnvChanged => {
+                let env = ctx.env();
+                let style = self.style.inherit_from(ctx.text_style());
+                style.apply_to_layout(&mut self.editor, env);
+                self.editor.set_caret_style(
+                    self.caret_style
+                        .unwrap_or_else(|| CaretStyle::from_env(env)),
+                );
+                self.editor.set_selection_color(
+                    self.selection_color
+                        .unwrap_or_else(|| env.get(theme::SELECTED_TEXT_BACKGROUND_COLOR)),
+                );
+                let brush = self.brush(ctx, ctx.is_disabled());
+                self.editor.set_brush(brush);
+                ctx.request_layout();
+            }
             _ => {}
         }
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         // Compute max_advance from box constraints
-        let max_advance = if self.line_break_mode != LineBreaking::WordWrap {
+        let max_advance = if !self.line_break_mode.wraps() {
             None
         } else if bc.max().width.is_finite() {
             Some((bc.max().width - 2. * TEXTBOX_PADDING - 2. * TEXTBOX_MARGIN) as f32)
@@ -259,6 +366,7 @@ impl Widget for Textbox {
             None
         };
         self.editor.set_max_advance(max_advance);
+        self.editor.set_line_height_grid(ctx.baseline_grid());
         if self.editor.needs_rebuild() {
             let (font_ctx, layout_ctx) = ctx.text_contexts();
             self.editor.rebuild(font_ctx, layout_ctx);
@@ -272,11 +380,20 @@ impl Widget for Textbox {
             text_size.width.max(INFINITE_TEXTBOX_WIDTH) + 2. * TEXTBOX_PADDING
         };
         let label_size = Size {
-            height: text_size.height + 2. * TEXTBOX_PADDING,
+            height: ctx.snap_to_baseline_grid(text_size.height + 2. * TEXTBOX_PADDING),
             // TODO: Better heuristic here?
             width,
         };
-        bc.constrain(label_size)
+        let size = bc.constrain(label_size);
+        if std::mem::take(&mut self.scroll_to_caret) {
+            // Scrolling to the caret lets long lines scroll horizontally in a `Portal`.
+            let rect = match self.editor.caret_rect() {
+                Some(rect) => rect + Vec2::new(TEXTBOX_PADDING, TEXTBOX_PADDING),
+                None => size.to_rect(),
+            };
+            ctx.request_scroll_to(rect);
+        }
+        size
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, scene: &mut Scene) {
@@ -291,8 +408,14 @@ impl Widget for Textbox {
             scene.push_layer(BlendMode::default(), 1., Affine::IDENTITY, &clip_rect);
         }
 
+        self.editor.set_glyph_snapping(ctx.glyph_snapping());
         self.editor
             .draw(scene, Point::new(TEXTBOX_PADDING, TEXTBOX_PADDING));
+        if let Some(indicator) = &self.soft_wrap_indicator {
+            for rect in self.editor.soft_wrap_rects() {
+                indicator(scene, rect + Vec2::new(TEXTBOX_PADDING, TEXTBOX_PADDING));
+            }
+        }
 
         if self.line_break_mode == LineBreaking::Clip {
             scene.pop_layer();
@@ -341,4 +464,130 @@ impl Widget for Textbox {
     }
 }
 
-// TODO - Add tests
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+    use super::*;
+    use crate::clipboard::ClipboardContent;
+    use crate::testing::{widget_ids, TestHarness, TestWidgetExt};
+    use crate::text::CaretShape;
+
+    #[test]
+    fn block_caret_overwrites() {
+        let [textbox_id] = widget_ids();
+        let caret_style = CaretStyle {
+            shape: CaretShape::Block,
+            ..Default::default()
+        };
+        let widget = Textbox::new("abc\nd")
+            .with_caret_style(caret_style)
+            .with_id(textbox_id);
+
+        let mut harness = TestHarness::create(widget);
+        harness.focus_on(Some(textbox_id));
+        harness.keyboard_type_chars("xy");
+        assert_eq!(
+            harness
+                .get_widget(textbox_id)
+                .downcast::<Textbox>()
+                .unwrap()
+                .text(),
+            "xyc\nd"
+        );
+
+        // The end of the line isn't overwritten.
+        harness.keyboard_type_chars("zw");
+        assert_eq!(
+            harness
+                .get_widget(textbox_id)
+                .downcast::<Textbox>()
+                .unwrap()
+                .text(),
+            "xyzw\nd"
+        );
+    }
+
+    #[test]
+    fn soft_wrap_rects() {
+        let [textbox_id] = widget_ids();
+        let text = "aaaa bbbb cccc dddd\nee";
+        let widget = Textbox::new(text).with_id(textbox_id);
+
+        let mut harness = TestHarness::create_with_size(widget, Size::new(80., 400.));
+        let textbox = harness.get_widget(textbox_id);
+        let editor = &textbox.downcast::<Textbox>().unwrap().editor;
+        // Only the lines which were wrapped, not the one ending with a line break.
+        let line_count = editor.layout.layout.len();
+        assert!(line_count > 2);
+        assert_eq!(editor.soft_wrap_rects().len(), line_count - 2);
+
+        harness.edit_widget(textbox_id, |mut textbox| {
+            let mut textbox = textbox.downcast::<Textbox>();
+            Textbox::set_line_break_mode(&mut textbox, LineBreaking::Overflow);
+        });
+        let textbox = harness.get_widget(textbox_id);
+        let editor = &textbox.downcast::<Textbox>().unwrap().editor;
+        assert!(editor.soft_wrap_rects().is_empty());
+    }
+
+    #[test]
+    fn caret_browsing_keys_are_left_to_the_editor() {
+        let [textbox_id] = widget_ids();
+        let widget = Textbox::new("abc").with_id(textbox_id);
+        let mut harness = TestHarness::create(widget);
+        harness.focus_on(Some(textbox_id));
+        let selection = |harness: &TestHarness| {
+            let textbox = harness.get_widget(textbox_id);
+            let selection = textbox.downcast::<Textbox>().unwrap().editor.selection;
+            (selection.anchor, selection.active)
+        };
+
+        // Shift doesn't extend the selection, and Home and End don't move the caret.
+        harness.keyboard_key(Key::Named(NamedKey::ArrowRight));
+        harness.set_modifiers(ModifiersState::SHIFT);
+        harness.keyboard_key(Key::Named(NamedKey::ArrowRight));
+        assert_eq!(selection(&harness), (1, 1));
+        harness.set_modifiers(ModifiersState::empty());
+        harness.keyboard_key(Key::Named(NamedKey::End));
+        assert_eq!(selection(&harness), (1, 1));
+    }
+
+    #[test]
+    fn copy_and_cut() {
+        let [textbox_id] = widget_ids();
+        let widget = Textbox::new("Hello world").with_id(textbox_id);
+        let mut harness = TestHarness::create(widget);
+        harness.focus_on(Some(textbox_id));
+        let text = |harness: &TestHarness| {
+            let textbox = harness.get_widget(textbox_id);
+            textbox.downcast::<Textbox>().unwrap().text().to_string()
+        };
+        let ctrl_key = |harness: &mut TestHarness, key: &str| {
+            harness.set_modifiers(ModifiersState::CONTROL);
+            harness.keyboard_key(Key::Character(key.into()));
+            harness.set_modifiers(ModifiersState::empty());
+        };
+
+        ctrl_key(&mut harness, "a");
+        ctrl_key(&mut harness, "c");
+        assert_eq!(
+            harness.clipboard(),
+            Some(&ClipboardContent::text("Hello world"))
+        );
+        assert_eq!(text(&harness), "Hello world");
+
+        harness.keyboard_type_chars("abc");
+        ctrl_key(&mut harness, "a");
+        ctrl_key(&mut harness, "x");
+        assert_eq!(harness.clipboard(), Some(&ClipboardContent::text("abc")));
+        assert_eq!(text(&harness), "");
+
+        // Without a selection, nothing is cut.
+        harness.keyboard_type_chars("q");
+        ctrl_key(&mut harness, "x");
+        assert_eq!(harness.clipboard(), Some(&ClipboardContent::text("abc")));
+        assert_eq!(text(&harness), "q");
+    }
+}
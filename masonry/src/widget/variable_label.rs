@@ -16,10 +16,10 @@ use vello::peniko::BlendMode;
 use vello::Scene;
 
 use crate::text::{ArcStr, Hinting, TextBrush, TextLayout};
-use crate::widget::{LineBreaking, WidgetMut};
+use crate::widget::{LineBreaking, WidgetFlags, WidgetMut};
 use crate::{
-    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, PointerEvent,
-    RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+    theme, AccessCtx, AccessEvent, BoxConstraints, Env, EventCtx, LayoutCtx, PaintCtx,
+    PointerEvent, RegisterCtx, TextEvent, Update, UpdateCtx, Widget, WidgetId,
 };
 
 // added padding between the edges of the widget and the text.
@@ -139,7 +139,8 @@ pub struct VariableLabel {
     text_layout: TextLayout,
     line_break_mode: LineBreaking,
     show_disabled: bool,
-    brush: TextBrush,
+    /// The brush set on this widget, which takes precedence over the env.
+    brush: Option<TextBrush>,
     weight: AnimatedF32,
 }
 
@@ -150,10 +151,10 @@ impl VariableLabel {
         Self {
             text: text.into(),
             text_changed: false,
-            text_layout: TextLayout::new(crate::theme::TEXT_SIZE_NORMAL as f32),
+            text_layout: TextLayout::new(*theme::TEXT_SIZE_NORMAL.default_value() as f32),
             line_break_mode: LineBreaking::Overflow,
             show_disabled: true,
-            brush: crate::theme::TEXT_COLOR.into(),
+            brush: None,
             weight: AnimatedF32::stable(Weight::NORMAL.value()),
         }
     }
@@ -202,11 +203,14 @@ impl VariableLabel {
         Self::new("")
     }
 
-    fn brush(&self, disabled: bool) -> TextBrush {
+    fn brush(&self, env: &Env, disabled: bool) -> TextBrush {
         if disabled {
-            crate::theme::DISABLED_TEXT_COLOR.into()
+            env.get(theme::DISABLED_TEXT_COLOR).into()
         } else {
-            let mut brush = self.brush.clone();
+            let mut brush = self
+                .brush
+                .clone()
+                .unwrap_or_else(|| env.get(theme::TEXT_COLOR).into());
             if !self.weight.is_completed() {
                 brush.set_hinting(Hinting::No);
             }
@@ -244,7 +248,7 @@ impl VariableLabel {
     /// Set the brush of the text, normally used for the colour.
     pub fn set_text_brush(this: &mut WidgetMut<'_, Self>, brush: impl Into<TextBrush>) {
         let brush = brush.into();
-        this.widget.brush = brush;
+        this.widget.brush = Some(brush);
         if !this.ctx.is_disabled() {
             this.widget.text_layout.invalidate();
             this.ctx.request_layout();
@@ -268,6 +272,13 @@ impl VariableLabel {
     }
     /// How to handle overflowing lines.
     pub fn set_line_break_mode(this: &mut WidgetMut<'_, Self>, line_break_mode: LineBreaking) {
+        // The laid out text differs when wrapping characters.
+        if (this.widget.line_break_mode == LineBreaking::CharWrap)
+            != (line_break_mode == LineBreaking::CharWrap)
+        {
+            this.widget.text_changed = true;
+            this.widget.text_layout.invalidate();
+        }
         this.widget.line_break_mode = line_break_mode;
         this.ctx.request_layout();
     }
@@ -324,23 +335,22 @@ impl Widget for VariableLabel {
             }
             Update::DisabledChanged(disabled) => {
                 if self.show_disabled {
-                    if *disabled {
-                        self.text_layout
-                            .set_brush(crate::theme::DISABLED_TEXT_COLOR);
-                    } else {
-                        self.text_layout.set_brush(self.brush.clone());
-                    }
+                    self.text_layout.set_brush(self.brush(ctx.env(), *disabled));
                 }
                 // TODO: Parley seems to require a relayout when colours change
                 ctx.request_layout();
             }
+            Update::EnvChanged => {
+                let disabled = self.show_disabled && ctx.is_disabled();
+                self.text_layout.set_brush(self.brush(ctx.env(), disabled));
+            }
             _ => {}
         }
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
         // Compute max_advance from box constraints
-        let max_advance = if self.line_break_mode != LineBreaking::WordWrap {
+        let max_advance = if !self.line_break_mode.wraps() {
             None
         } else if bc.max().width.is_finite() {
             Some(bc.max().width as f32 - 2. * LABEL_X_PADDING as f32)
@@ -351,13 +361,16 @@ impl Widget for VariableLabel {
         };
         self.text_layout.set_max_advance(max_advance);
         if self.text_layout.needs_rebuild() {
-            self.text_layout
-                .set_brush(self.brush(ctx.widget_state.is_disabled));
+            self.text_layout.set_brush(self.brush(
+                ctx.env(),
+                ctx.widget_state.flags.contains(WidgetFlags::IS_DISABLED),
+            ));
             let (font_ctx, layout_ctx) = ctx.text_contexts();
+            let text = self.line_break_mode.layout_text(&self.text);
             self.text_layout.rebuild_with_attributes(
                 font_ctx,
                 layout_ctx,
-                &self.text,
+                &text,
                 self.text_changed,
                 |mut builder| {
                     builder.push_default(&parley::style::StyleProperty::FontWeight(Weight::new(
@@ -391,6 +404,7 @@ impl Widget for VariableLabel {
             let clip_rect = ctx.size().to_rect();
             scene.push_layer(BlendMode::default(), 1., Affine::IDENTITY, &clip_rect);
         }
+        self.text_layout.set_glyph_snapping(ctx.glyph_snapping());
         self.text_layout
             .draw(scene, Point::new(LABEL_X_PADDING, 0.0));
 
@@ -0,0 +1,736 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A scrolling list which only creates widgets for the items it shows.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use accesskit::{NodeBuilder, Role};
+use smallvec::SmallVec;
+use tracing::{trace_span, Span};
+use vello::kurbo::{Point, Size, Vec2};
+use vello::Scene;
+
+use super::portal::{ScrollMotion, SCROLLING_SPEED};
+use crate::gesture::Gesture;
+use crate::widget::{Axis, ScrollBar, WidgetMut, WidgetPod};
+use crate::{
+    AccessCtx, AccessEvent, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, PointerEvent, PointerId,
+    RegisterCtx, ScrollDeltaKind, TextEvent, Update, UpdateCtx, Widget, WidgetId,
+};
+
+/// How far past the edges of the viewport items are created, in logical pixels.
+///
+/// This keeps items ready a little before they're scrolled into view.
+const OVERSCAN: f64 = 100.0;
+
+/// The height assumed for items which haven't been laid out yet, unless
+/// [`VirtualList::with_estimated_item_height`] is used.
+const DEFAULT_ESTIMATED_ITEM_HEIGHT: f64 = 24.0;
+
+/// Creates the widget of the item at the given index.
+pub type ItemBuilder = Box<dyn FnMut(usize) -> Box<dyn Widget>>;
+
+/// Updates a widget which showed another item to show the item at the given index.
+pub type ItemRecycler = Box<dyn FnMut(usize, WidgetMut<'_, Box<dyn Widget>>)>;
+
+/// The heights of the items, indexed so that the offset of any item can be found, or
+/// updated when an item is laid out, without adding up the heights of all items above it.
+///
+/// This is a [Fenwick tree](https://en.wikipedia.org/wiki/Fenwick_tree) of the heights,
+/// with items which haven't been laid out counting as the estimated height.
+struct ItemHeights {
+    /// The height of each item, once it has been laid out.
+    known: Vec<Option<f64>>,
+    estimate: f64,
+    /// `tree[i]` is the sum of the heights of the `i & -i` items ending with item `i - 1`.
+    tree: Vec<f64>,
+}
+
+impl ItemHeights {
+    fn new(count: usize, estimate: f64) -> Self {
+        let mut heights = Self {
+            known: vec![None; count],
+            estimate,
+            tree: Vec::new(),
+        };
+        heights.rebuild();
+        heights
+    }
+
+    fn rebuild(&mut self) {
+        let len = self.known.len();
+        self.tree = vec![0.0; len + 1];
+        for pos in 1..=len {
+            self.tree[pos] += self.height(pos - 1);
+            let parent = pos + (pos & pos.wrapping_neg());
+            if parent <= len {
+                self.tree[parent] += self.tree[pos];
+            }
+        }
+    }
+
+    fn set_estimate(&mut self, estimate: f64) {
+        self.estimate = estimate;
+        self.rebuild();
+    }
+
+    fn resize(&mut self, count: usize) {
+        self.known.resize(count, None);
+        self.rebuild();
+    }
+
+    fn height(&self, index: usize) -> f64 {
+        self.known[index].unwrap_or(self.estimate)
+    }
+
+    fn set(&mut self, index: usize, height: f64) {
+        let delta = height - self.height(index);
+        self.known[index] = Some(height);
+        if delta == 0.0 {
+            return;
+        }
+        let mut pos = index + 1;
+        while pos < self.tree.len() {
+            self.tree[pos] += delta;
+            pos += pos & pos.wrapping_neg();
+        }
+    }
+
+    /// The top of the item at `index`, i.e. the sum of the heights of the items above it.
+    fn offset(&self, index: usize) -> f64 {
+        let mut pos = index.min(self.known.len());
+        let mut offset = 0.0;
+        while pos > 0 {
+            offset += self.tree[pos];
+            pos &= pos - 1;
+        }
+        offset
+    }
+
+    fn total(&self) -> f64 {
+        self.offset(self.known.len())
+    }
+
+    /// The largest `index` whose [`offset`](Self::offset) passes `predicate`, or 0 if none do.
+    ///
+    /// `predicate` must pass for every offset below one which passes.
+    fn last_index_where(&self, predicate: impl Fn(f64) -> bool) -> usize {
+        let len = self.known.len();
+        let mut pos = 0;
+        let mut offset = 0.0;
+        let mut step = if len == 0 { 0 } else { 1 << len.ilog2() };
+        while step > 0 {
+            if pos + step <= len && predicate(offset + self.tree[pos + step]) {
+                pos += step;
+                offset += self.tree[pos];
+            }
+            step >>= 1;
+        }
+        pos
+    }
+}
+
+/// A vertically scrolling list which only creates and lays out the widgets of the items
+/// in and near its viewport, so that lists with many thousands of items stay fast.
+///
+/// Items are created on demand by an [`ItemBuilder`], and may have different heights.
+/// Items which haven't been laid out yet are assumed to be
+/// [`with_estimated_item_height`](Self::with_estimated_item_height) tall, and the list keeps
+/// its first visible item in place when their actual heights become known.
+///
+/// By default, the widgets of items scrolled out of view are removed. With
+/// [`with_item_recycler`](Self::with_item_recycler), they're stashed instead, and reused
+/// for the next items scrolled into view.
+///
+/// The list scrolls like a [`Portal`](super::Portal): smoothly after a wheel notch, and with
+/// a fling after a touch drag. Each item is reported to assistive technologies with its
+/// position in the whole list.
+///
+/// The list fills the maximum size allowed by its constraints, so it should be given a
+/// bounded height.
+pub struct VirtualList {
+    item_count: usize,
+    build_item: ItemBuilder,
+    recycle_item: Option<ItemRecycler>,
+    /// The widgets of the items in and near the viewport, by index.
+    items: BTreeMap<usize, WidgetPod<Box<dyn Widget>>>,
+    /// Stashed widgets, waiting to be reused by `recycle_item`.
+    recycled: Vec<WidgetPod<Box<dyn Widget>>>,
+    heights: ItemHeights,
+    /// The items whose widget was created since the last layout, and which don't know
+    /// their position in the list yet.
+    unpositioned: Vec<usize>,
+    /// The first visible item, and how far the viewport is scrolled past its top.
+    anchor: (usize, f64),
+    scroll_offset: f64,
+    /// The items which should have a widget, as of the last layout.
+    wanted: Range<usize>,
+    scrollbar: WidgetPod<ScrollBar>,
+    motion: ScrollMotion,
+}
+
+// --- MARK: BUILDERS ---
+impl VirtualList {
+    /// Create a list of `item_count` items, whose widgets are created by `build_item`.
+    pub fn new(
+        item_count: usize,
+        build_item: impl FnMut(usize) -> Box<dyn Widget> + 'static,
+    ) -> Self {
+        Self {
+            item_count,
+            build_item: Box::new(build_item),
+            recycle_item: None,
+            items: BTreeMap::new(),
+            recycled: Vec::new(),
+            heights: ItemHeights::new(item_count, DEFAULT_ESTIMATED_ITEM_HEIGHT),
+            unpositioned: Vec::new(),
+            anchor: (0, 0.0),
+            scroll_offset: 0.0,
+            wanted: 0..0,
+            scrollbar: WidgetPod::new(ScrollBar::new(Axis::Vertical, 0.0, 0.0)),
+            motion: ScrollMotion::new(),
+        }
+    }
+
+    /// Builder-style method to set the height assumed for items which haven't been laid out.
+    ///
+    /// The closer it is to the actual heights, the steadier the scrollbar is.
+    pub fn with_estimated_item_height(mut self, height: f64) -> Self {
+        self.heights.set_estimate(height);
+        self
+    }
+
+    /// Builder-style method to reuse the widgets of items scrolled out of view,
+    /// with `recycle_item` updating them to show their new item.
+    pub fn with_item_recycler(
+        mut self,
+        recycle_item: impl FnMut(usize, WidgetMut<'_, Box<dyn Widget>>) + 'static,
+    ) -> Self {
+        self.recycle_item = Some(Box::new(recycle_item));
+        self
+    }
+
+    /// The number of items in the list.
+    pub fn item_count(&self) -> usize {
+        self.item_count
+    }
+
+    /// The indices of the items which currently have a widget.
+    pub fn realized_range(&self) -> Range<usize> {
+        match (self.items.first_key_value(), self.items.last_key_value()) {
+            (Some((first, _)), Some((last, _))) => *first..*last + 1,
+            _ => 0..0,
+        }
+    }
+
+    /// How far the list is scrolled, in logical pixels.
+    pub fn scroll_offset(&self) -> f64 {
+        self.scroll_offset
+    }
+}
+
+// --- MARK: INTERNALS ---
+impl VirtualList {
+    fn max_offset(&self, viewport_height: f64) -> f64 {
+        (self.heights.total() - viewport_height).max(0.0)
+    }
+
+    /// The item at `offset`, and how far `offset` is past its top.
+    fn anchor_at(&self, offset: f64) -> (usize, f64) {
+        let index = self
+            .heights
+            .last_index_where(|top| top <= offset)
+            .min(self.item_count.saturating_sub(1));
+        (index, offset - self.heights.offset(index))
+    }
+
+    /// The items overlapping the span from `top` to `bottom`.
+    fn items_between(&self, top: f64, bottom: f64) -> Range<usize> {
+        let start = self
+            .heights
+            .last_index_where(|offset| offset <= top)
+            .min(self.item_count);
+        let end = if bottom > 0.0 {
+            self.heights.last_index_where(|offset| offset < bottom) + 1
+        } else {
+            0
+        };
+        start..end.min(self.item_count).max(start)
+    }
+
+    /// Move the viewport of the given height, and return whether it moved.
+    ///
+    /// The items are moved by the next layout pass.
+    fn set_scroll_offset(&mut self, viewport_height: f64, offset: f64) -> bool {
+        let offset = offset.clamp(0.0, self.max_offset(viewport_height));
+        if offset == self.scroll_offset {
+            return false;
+        }
+        self.scroll_offset = offset;
+        self.anchor = self.anchor_at(offset);
+        true
+    }
+
+    /// Scroll by `delta` logical pixels, and return whether the list moved.
+    fn scroll_by(&mut self, ctx: &mut EventCtx, delta: f64) -> bool {
+        let viewport_height = ctx.size().height;
+        if !self.set_scroll_offset(viewport_height, self.scroll_offset + delta) {
+            return false;
+        }
+        ctx.request_layout();
+
+        let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar);
+        scrollbar.widget().cursor_progress = self.scroll_offset / self.max_offset(viewport_height);
+        scrollbar.ctx().request_render();
+        if scrollbar.widget().reveal() {
+            scrollbar.ctx().request_anim_frame();
+        }
+        true
+    }
+
+    /// Create, recycle or remove item widgets so that exactly the wanted items have one.
+    fn realize_items(this: &mut WidgetMut<'_, Self>) {
+        let wanted = this.widget.wanted.clone();
+        let stale: Vec<usize> = this
+            .widget
+            .items
+            .keys()
+            .copied()
+            .filter(|index| !wanted.contains(index))
+            .collect();
+        let mut changed = !stale.is_empty();
+        for index in stale {
+            let mut item = this.widget.items.remove(&index).unwrap();
+            if this.widget.recycle_item.is_some() {
+                this.ctx.set_stashed(&mut item, true);
+                this.widget.recycled.push(item);
+            } else {
+                this.ctx.remove_child(item);
+            }
+        }
+
+        for index in wanted {
+            if this.widget.items.contains_key(&index) {
+                continue;
+            }
+            changed = true;
+            let item = match (&mut this.widget.recycle_item, this.widget.recycled.pop()) {
+                (Some(recycle_item), Some(mut item)) => {
+                    this.ctx.set_stashed(&mut item, false);
+                    recycle_item(index, this.ctx.get_mut(&mut item));
+                    let mut item_mut = this.ctx.get_mut(&mut item);
+                    item_mut
+                        .ctx
+                        .set_position_in_set(Some((index, this.widget.item_count)));
+                    // The new item may not be as tall as the previous one.
+                    item_mut.ctx.request_layout();
+                    item
+                }
+                _ => {
+                    // The widget can only be changed once it's added to the tree.
+                    this.widget.unpositioned.push(index);
+                    WidgetPod::new((this.widget.build_item)(index))
+                }
+            };
+            this.widget.items.insert(index, item);
+        }
+
+        if changed {
+            this.ctx.children_changed();
+            this.ctx.request_accessibility_update();
+        }
+    }
+
+    /// Tell the items created since the last layout their position in the list.
+    fn position_new_items(this: &mut WidgetMut<'_, Self>) {
+        let item_count = this.widget.item_count;
+        for index in std::mem::take(&mut this.widget.unpositioned) {
+            // The item may have been scrolled out of view again.
+            let Some(item) = this.widget.items.get_mut(&index) else {
+                continue;
+            };
+            let mut item = this.ctx.get_mut(item);
+            item.ctx.set_position_in_set(Some((index, item_count)));
+        }
+    }
+}
+
+// --- MARK: WIDGETMUT ---
+impl VirtualList {
+    /// Change the number of items.
+    ///
+    /// Items below the new count lose their widget. The remaining items keep theirs,
+    /// so use [`item_mut`](Self::item_mut) to update them if their content changed.
+    pub fn set_item_count(this: &mut WidgetMut<'_, Self>, item_count: usize) {
+        this.widget.item_count = item_count;
+        this.widget.heights.resize(item_count);
+        this.widget.wanted.end = this.widget.wanted.end.min(item_count);
+        this.widget.wanted.start = this.widget.wanted.start.min(item_count);
+        Self::realize_items(this);
+        for (index, item) in &mut this.widget.items {
+            if this.widget.unpositioned.contains(index) {
+                continue;
+            }
+            let mut item = this.ctx.get_mut(item);
+            item.ctx.set_position_in_set(Some((*index, item_count)));
+        }
+        this.ctx.request_layout();
+        this.ctx.request_accessibility_update();
+    }
+
+    /// The widget of the item at `index`, if it currently has one.
+    pub fn item_mut<'t>(
+        this: &'t mut WidgetMut<'_, Self>,
+        index: usize,
+    ) -> Option<WidgetMut<'t, Box<dyn Widget>>> {
+        let item = this.widget.items.get_mut(&index)?;
+        Some(this.ctx.get_mut(item))
+    }
+
+    /// Scroll the list so that the item at `index` is at its top, or as close as possible.
+    ///
+    /// The item's widget is created by the next layout pass if it doesn't have one yet.
+    pub fn request_scroll_to(this: &mut WidgetMut<'_, Self>, index: usize) {
+        this.widget.anchor = (index.min(this.widget.item_count.saturating_sub(1)), 0.0);
+        this.widget.motion.stop(Point::ZERO);
+        this.ctx.request_layout();
+    }
+}
+
+// --- MARK: IMPL WIDGET ---
+impl Widget for VirtualList {
+    fn on_pointer_event(&mut self, ctx: &mut EventCtx, event: &PointerEvent) {
+        if let PointerEvent::MouseWheel(delta, kind, _) = event {
+            let from = self.motion.wheel_start(Point::new(0.0, self.scroll_offset));
+            let max_offset = self.max_offset(ctx.size().height);
+            let target = (from.y - delta.y * SCROLLING_SPEED).clamp(0.0, max_offset);
+            match kind {
+                // As in a `Portal`, only wheel notches are animated.
+                ScrollDeltaKind::Line => {
+                    if self.motion.animation.set_target(Vec2::new(0.0, target)) {
+                        ctx.request_anim_frame();
+                    }
+                }
+                ScrollDeltaKind::Pixel => self.motion.animation.jump_to(Vec2::new(0.0, target)),
+            }
+            let pos = self.motion.animation.value().y;
+            self.scroll_by(ctx, pos - self.scroll_offset);
+            if target != from.y {
+                ctx.set_handled();
+            }
+        }
+
+        // Touches drag the content, and fling it if they're still moving when lifted.
+        let state = event.pointer_state();
+        if matches!(state.pointer_id, PointerId::Touch(_)) && state.is_primary {
+            if let PointerEvent::PointerDown(..) = event {
+                // Touching the content catches the fling.
+                self.motion.fling_velocity = Vec2::ZERO;
+            }
+            let gesture = self.motion.touch_recognizer.handle_pointer_event(
+                event,
+                ctx.window_origin(),
+                Instant::now(),
+            );
+            match gesture {
+                Some(Gesture::DragStart { .. }) => {
+                    ctx.release_pointer();
+                    self.motion.stop(Point::new(0.0, self.scroll_offset));
+                }
+                Some(Gesture::DragUpdate { delta, .. }) => {
+                    self.scroll_by(ctx, -delta.y);
+                    ctx.set_handled();
+                }
+                Some(Gesture::DragEnd { velocity, .. }) => {
+                    self.motion.fling_velocity = Vec2::new(0.0, -velocity.y);
+                    ctx.request_anim_frame();
+                }
+                _ => {}
+            }
+        }
+
+        // The scrollbar's event method has already run, since events are propagated up.
+        let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar);
+        if scrollbar.widget().moved {
+            scrollbar.widget().moved = false;
+            let progress = scrollbar.widget().cursor_progress;
+            std::mem::drop(scrollbar);
+
+            let max_offset = self.max_offset(ctx.size().height);
+            self.set_scroll_offset(ctx.size().height, progress * max_offset);
+            self.motion.stop(Point::new(0.0, self.scroll_offset));
+            ctx.request_layout();
+        }
+    }
+
+    fn on_text_event(&mut self, _ctx: &mut EventCtx, _event: &TextEvent) {}
+
+    fn on_access_event(&mut self, _ctx: &mut EventCtx, _event: &AccessEvent) {}
+
+    fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
+        let Some(pos) = self
+            .motion
+            .advance(Point::new(0.0, self.scroll_offset), interval)
+        else {
+            return;
+        };
+        let viewport_height = ctx.size().height;
+        let clamped = Point::new(0.0, pos.y.clamp(0.0, self.max_offset(viewport_height)));
+        if self.motion.stop_at_edges(pos, clamped) {
+            ctx.request_anim_frame();
+        }
+        if !self.set_scroll_offset(viewport_height, clamped.y) {
+            return;
+        }
+        ctx.request_layout();
+
+        let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar);
+        scrollbar.widget().cursor_progress = self.scroll_offset / self.max_offset(viewport_height);
+        scrollbar.ctx().request_render();
+        if scrollbar.widget().reveal() {
+            scrollbar.ctx().request_anim_frame();
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut UpdateCtx, _event: &Update) {}
+
+    fn register_children(&mut self, ctx: &mut RegisterCtx) {
+        for item in self.items.values_mut() {
+            ctx.register_child(item);
+        }
+        for item in &mut self.recycled {
+            ctx.register_child(item);
+        }
+        ctx.register_child(&mut self.scrollbar);
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints) -> Size {
+        let width = if bc.is_width_bounded() {
+            bc.max().width
+        } else {
+            bc.min().width
+        };
+        let item_bc = BoxConstraints::new(Size::new(width, 0.0), Size::new(width, f64::INFINITY));
+        for (index, item) in &mut self.items {
+            let size = ctx.run_layout(item, &item_bc);
+            self.heights.set(*index, size.height);
+        }
+        for item in &mut self.recycled {
+            ctx.skip_layout(item);
+        }
+        if !self.unpositioned.is_empty() {
+            ctx.mutate_self_later(|mut this| {
+                let mut this = this.downcast::<Self>();
+                Self::position_new_items(&mut this);
+            });
+        }
+
+        let content_height = self.heights.total();
+        let height = if bc.is_height_bounded() {
+            bc.max().height
+        } else {
+            content_height
+        };
+        let size = bc.constrain(Size::new(width, height));
+
+        // Items above the viewport may have changed height since the last layout, so the
+        // offset is recomputed from the first visible item, which stays in place.
+        let max_offset = (content_height - size.height).max(0.0);
+        let (anchor_index, anchor_delta) = self.anchor;
+        let anchor_top = self.heights.offset(anchor_index);
+        self.scroll_offset = (anchor_top + anchor_delta).clamp(0.0, max_offset);
+        self.anchor = self.anchor_at(self.scroll_offset);
+
+        let wanted = self.items_between(
+            self.scroll_offset - OVERSCAN,
+            self.scroll_offset + size.height + OVERSCAN,
+        );
+        if wanted != self.realized_range() || wanted.len() != self.items.len() {
+            self.wanted = wanted;
+            ctx.mutate_self_later(|mut this| {
+                let mut this = this.downcast::<Self>();
+                Self::realize_items(&mut this);
+            });
+        }
+
+        for (index, item) in &mut self.items {
+            let origin = Point::new(0.0, self.heights.offset(*index) - self.scroll_offset);
+            ctx.place_child(item, origin);
+        }
+        ctx.set_clip_path(size.to_rect());
+
+        let scrollbar_visible = content_height > size.height;
+        ctx.set_stashed(&mut self.scrollbar, !scrollbar_visible);
+        if scrollbar_visible {
+            let mut scrollbar = ctx.get_raw_mut(&mut self.scrollbar);
+            scrollbar.widget().portal_size = size.height;
+            scrollbar.widget().content_size = content_height;
+            scrollbar.widget().cursor_progress = self.scroll_offset / max_offset;
+            std::mem::drop(scrollbar);
+
+            let scrollbar_size = ctx.run_layout(&mut self.scrollbar, bc);
+            ctx.place_child(
+                &mut self.scrollbar,
+                Point::new(size.width - scrollbar_size.width, 0.0),
+            );
+        } else {
+            ctx.skip_layout(&mut self.scrollbar);
+        }
+
+        size
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _scene: &mut Scene) {}
+
+    fn accessibility_role(&self) -> Role {
+        Role::List
+    }
+
+    fn accessibility(&mut self, _ctx: &mut AccessCtx, node: &mut NodeBuilder) {
+        // Most items don't have a widget, so assistive technologies are told how many there are.
+        node.set_size_of_set(self.item_count);
+        node.set_scroll_y(self.scroll_offset);
+    }
+
+    fn children_ids(&self) -> SmallVec<[WidgetId; 16]> {
+        let mut ids: SmallVec<_> = self.items.values().map(|item| item.id()).collect();
+        ids.extend(self.recycled.iter().map(|item| item.id()));
+        ids.push(self.scrollbar.id());
+        ids
+    }
+
+    fn make_trace_span(&self) -> Span {
+        trace_span!("VirtualList")
+    }
+}
+
+// --- MARK: TESTS ---
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::testing::TestHarness;
+    use crate::widget::{Label, SizedBox};
+
+    fn list(harness: &TestHarness) -> (Range<usize>, f64) {
+        let list = harness.root_widget();
+        let list = list.downcast::<VirtualList>().unwrap();
+        (list.realized_range(), list.scroll_offset())
+    }
+
+    #[test]
+    fn only_items_near_the_viewport_are_realized() {
+        let widget = VirtualList::new(10_000, |_| Box::new(SizedBox::empty().height(20.)))
+            .with_estimated_item_height(20.);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 100.));
+        // The viewport and `OVERSCAN` below it.
+        assert_eq!(list(&harness), (0..10, 0.));
+        assert_eq!(harness.root_widget().children().len(), 11);
+
+        harness.mouse_move(Point::new(100., 50.));
+        harness.mouse_wheel(Vec2::new(0., -50.));
+        // Like a `Portal`, the list scrolls smoothly after a wheel notch.
+        assert_eq!(list(&harness), (0..10, 0.));
+        harness.animate_ms(500);
+        assert_eq!(list(&harness), (20..35, 500.));
+    }
+
+    #[test]
+    fn items_know_their_position_in_the_list() {
+        let widget = VirtualList::new(10_000, |_| Box::new(SizedBox::empty().height(20.)))
+            .with_estimated_item_height(20.);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 100.));
+        harness.mouse_move(Point::new(100., 50.));
+        harness.touchpad_scroll(Vec2::new(0., -50.));
+        assert_eq!(list(&harness), (20..35, 500.));
+
+        let list = harness.root_widget();
+        let list = list.downcast::<VirtualList>().unwrap();
+        for (index, item) in &list.items {
+            let item = harness.get_widget(item.id());
+            assert_eq!(item.ctx().position_in_set(), Some((*index, 10_000)));
+        }
+
+        harness.edit_root_widget(|mut list| {
+            let mut list = list.downcast::<VirtualList>();
+            VirtualList::set_item_count(&mut list, 30);
+        });
+        let list = harness.root_widget();
+        let list = list.downcast::<VirtualList>().unwrap();
+        let item = harness.get_widget(list.items[&25].id());
+        assert_eq!(item.ctx().position_in_set(), Some((25, 30)));
+    }
+
+    #[test]
+    fn item_offsets_follow_laid_out_heights() {
+        let mut heights = ItemHeights::new(10, 10.);
+        assert_eq!(heights.total(), 100.);
+        heights.set(3, 25.);
+        heights.set(7, 0.);
+        assert_eq!(heights.offset(3), 30.);
+        assert_eq!(heights.offset(4), 55.);
+        assert_eq!(heights.offset(8), 85.);
+        assert_eq!(heights.total(), 105.);
+        assert_eq!(heights.last_index_where(|offset| offset <= 54.), 3);
+        assert_eq!(heights.last_index_where(|offset| offset <= 55.), 4);
+        assert_eq!(heights.last_index_where(|offset| offset < 0.), 0);
+
+        heights.resize(5);
+        assert_eq!(heights.total(), 65.);
+    }
+
+    #[test]
+    fn scroll_to_item_with_variable_heights() {
+        let widget = VirtualList::new(10_000, |index| {
+            Box::new(SizedBox::empty().height(if index % 2 == 0 { 20. } else { 40. }))
+        })
+        .with_estimated_item_height(10.);
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 100.));
+
+        harness.edit_root_widget(|mut list| {
+            let mut list = list.downcast::<VirtualList>();
+            VirtualList::request_scroll_to(&mut list, 5000);
+        });
+        let list = harness.root_widget();
+        let list = list.downcast::<VirtualList>().unwrap();
+        assert!(list.realized_range().contains(&5000));
+        let item_id = list.items[&5000].id();
+        assert_eq!(harness.get_widget(item_id).ctx().layout_rect().y0, 0.);
+    }
+
+    #[test]
+    fn recycled_widgets_are_reused() {
+        let built = Rc::new(Cell::new(0));
+        let widget = VirtualList::new(1000, {
+            let built = built.clone();
+            move |index| {
+                built.set(built.get() + 1);
+                Box::new(SizedBox::new(Label::new(format!("Item {index}"))).height(20.))
+            }
+        })
+        .with_item_recycler(|index, mut item| {
+            let mut item = item.downcast::<SizedBox>();
+            let mut label = SizedBox::child_mut(&mut item).unwrap();
+            Label::set_text(&mut label.downcast(), format!("Item {index}"));
+        });
+        let mut harness = TestHarness::create_with_size(widget, Size::new(200., 100.));
+        // Once the items above the viewport are realized, the list has all the widgets it needs.
+        harness.mouse_move(Point::new(100., 50.));
+        harness.touchpad_scroll(Vec2::new(0., -10.));
+        let initially_built = built.get();
+
+        for _ in 0..9 {
+            harness.touchpad_scroll(Vec2::new(0., -10.));
+        }
+        assert_eq!(list(&harness).0, 45..60);
+        assert_eq!(built.get(), initially_built);
+    }
+}
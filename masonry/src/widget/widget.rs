@@ -14,11 +14,11 @@ use tracing::{trace_span, Span};
 use vello::Scene;
 
 use crate::contexts::ComposeCtx;
-use crate::event::{AccessEvent, PointerEvent, TextEvent};
+use crate::event::{AccessEvent, PlatformEvent, PointerEvent, TextEvent};
 use crate::widget::WidgetRef;
 use crate::{
-    AccessCtx, AsAny, BoxConstraints, EventCtx, LayoutCtx, PaintCtx, Point, QueryCtx, RegisterCtx,
-    Size, Update, UpdateCtx,
+    AccessCtx, AsAny, BoxConstraints, EventCtx, LayoutCtx, PageBreak, PaintCtx, Point, QueryCtx,
+    RegisterCtx, Size, Update, UpdateCtx,
 };
 
 /// A unique identifier for a single [`Widget`].
@@ -79,6 +79,12 @@ pub trait Widget: AsAny {
     /// Handle an event from the platform's accessibility API.
     fn on_access_event(&mut self, ctx: &mut EventCtx, event: &AccessEvent) {}
 
+    /// Handle a less common event from the platform, such as a memory warning.
+    ///
+    /// This is only called if the widget asked for platform events with
+    /// [`EventCtx::set_receives_platform_events`].
+    fn on_platform_event(&mut self, ctx: &mut EventCtx, event: &PlatformEvent) {}
+
     /// Called at the beginning of a new animation frame.
     ///
     /// An animation frame does not implicitly request a repaint of this widget.
@@ -185,7 +191,8 @@ pub trait Widget: AsAny {
     ///
     /// If true, pressing Tab can focus this widget.
     ///
-    /// **Note:** The value returned by this method is cached at widget creation and can't be changed.
+    /// **Note:** The value returned by this method is cached at widget creation. If it changes,
+    /// the widget must call [`accepts_focus_changed`](crate::EventCtx::accepts_focus_changed).
     fn accepts_focus(&self) -> bool {
         false
     }
@@ -199,6 +206,24 @@ pub trait Widget: AsAny {
         false
     }
 
+    /// Whether this widget paints its own focus indicator. False by default.
+    ///
+    /// When a widget is focused with the keyboard, Masonry paints a focus ring around it,
+    /// in the [`FOCUS_RING_COLOR`](crate::theme::FOCUS_RING_COLOR) of its environment.
+    /// Widgets which show their focus differently can return true to disable the ring,
+    /// and check [`is_focus_visible`](crate::EventCtx::is_focus_visible) when painting.
+    fn paints_own_focus_indicator(&self) -> bool {
+        false
+    }
+
+    /// Where the [`Paginator`](crate::Paginator) may split this widget across pages.
+    ///
+    /// By default, pages may break between the children of a widget, and widgets
+    /// without children are kept on a single page.
+    fn page_break(&self) -> PageBreak {
+        PageBreak::Auto
+    }
+
     // TODO - Write a generic default implementation once
     // `const std::any::type_name` is stable.
     // See https://github.com/rust-lang/rust/issues/63084
@@ -236,7 +261,8 @@ pub trait Widget: AsAny {
 
     /// Return which child, if any, has the given `pos` in its layout rect. In case of overlapping
     /// children, the last child as determined by [`Widget::children_ids`] is chosen. No child is
-    /// returned if `pos` is outside the widget's clip path.
+    /// returned if `pos` is outside the widget's clip path. Children which are
+    /// [transparent to the pointer](EventCtx::set_pointer_transparent) are skipped.
     ///
     /// The child returned is a direct child, not e.g. a grand-child.
     ///
@@ -297,7 +323,7 @@ pub(crate) fn get_child_at_pos<'c>(
     ctx: QueryCtx<'c>,
     pos: Point,
 ) -> Option<WidgetRef<'c, dyn Widget>> {
-    let relative_pos = pos - ctx.window_origin().to_vec2();
+    let relative_pos = ctx.widget_state.to_local(pos);
     if !ctx
         .clip_path()
         .map_or(true, |clip| clip.contains(relative_pos))
@@ -305,16 +331,23 @@ pub(crate) fn get_child_at_pos<'c>(
         return None;
     }
 
-    // Assumes `Self::children_ids` is in increasing "z-order", picking the last child in case
-    // of overlapping children.
-    for child_id in widget.children_ids().iter().rev() {
+    // Children are hit-tested in the reverse of the order they're painted in, picking the
+    // topmost child in case of overlapping children.
+    let mut children_ids = widget.children_ids();
+    children_ids.sort_by_key(|child_id| ctx.get(*child_id).ctx().widget_state.z_index);
+    for child_id in children_ids.iter().rev() {
         let child = ctx.get(*child_id);
 
         // The position must be inside the child's layout and inside the child's clip path (if
         // any).
         if !child.ctx().is_stashed()
             && child.ctx().accepts_pointer_interaction()
-            && child.ctx().window_layout_rect().contains(pos)
+            && !child.ctx().is_pointer_transparent()
+            && child
+                .ctx()
+                .size()
+                .to_rect()
+                .contains(child.ctx().widget_state.to_local(pos))
         {
             return Some(child);
         }
@@ -413,6 +446,10 @@ impl Widget for Box<dyn Widget> {
         self.deref_mut().on_access_event(ctx, event);
     }
 
+    fn on_platform_event(&mut self, ctx: &mut EventCtx, event: &PlatformEvent) {
+        self.deref_mut().on_platform_event(ctx, event);
+    }
+
     fn on_anim_frame(&mut self, ctx: &mut UpdateCtx, interval: u64) {
         self.deref_mut().on_anim_frame(ctx, interval);
     }
@@ -469,6 +506,14 @@ impl Widget for Box<dyn Widget> {
         self.deref().accepts_text_input()
     }
 
+    fn paints_own_focus_indicator(&self) -> bool {
+        self.deref().paints_own_focus_indicator()
+    }
+
+    fn page_break(&self) -> PageBreak {
+        self.deref().page_break()
+    }
+
     fn make_trace_span(&self) -> Span {
         self.deref().make_trace_span()
     }
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::tree_arena::{ArenaMut, ArenaRef, TreeArena};
-use crate::{Widget, WidgetId, WidgetState};
+use crate::{Widget, WidgetFlags, WidgetId, WidgetState};
 
 pub(crate) struct WidgetArena {
     pub(crate) widgets: TreeArena<Box<dyn Widget>>,
@@ -87,4 +87,39 @@ impl WidgetArena {
             .find_mut(widget_id)
             .expect("get_state_mut: widget state not in widget tree")
     }
+
+    /// The closest [`ErrorBoundary`](crate::widget::ErrorBoundary) ancestor of the widget, if any.
+    #[track_caller]
+    pub(crate) fn error_boundary_of(&self, widget_id: WidgetId) -> Option<WidgetId> {
+        let mut id = self.parent_of(widget_id);
+        while let Some(ancestor_id) = id {
+            let state = self
+                .widget_states
+                .find(ancestor_id)
+                .expect("error_boundary_of: widget state not in widget tree");
+            if state.item.flags.contains(WidgetFlags::IS_ERROR_BOUNDARY) {
+                return Some(ancestor_id);
+            }
+            id = self.parent_of(ancestor_id);
+        }
+        None
+    }
+
+    /// The closest ancestor of the widget which [scopes](crate::widget::FocusGroup::with_focus_scope)
+    /// arrow-key navigation, if any.
+    #[track_caller]
+    pub(crate) fn focus_scope_of(&self, widget_id: WidgetId) -> Option<WidgetId> {
+        let mut id = self.parent_of(widget_id);
+        while let Some(ancestor_id) = id {
+            let state = self
+                .widget_states
+                .find(ancestor_id)
+                .expect("focus_scope_of: widget state not in widget tree");
+            if state.item.flags.contains(WidgetFlags::IS_FOCUS_SCOPE) {
+                return Some(ancestor_id);
+            }
+            id = self.parent_of(ancestor_id);
+        }
+        None
+    }
 }
@@ -3,12 +3,124 @@
 
 #![cfg(not(tarpaulin_include))]
 
-use vello::kurbo::{Insets, Point, Rect, Size, Vec2};
+use std::sync::Arc;
 
-use crate::WidgetId;
+use bitflags::bitflags;
+use vello::kurbo::{Affine, Insets, Point, Rect, Size, Vec2};
 
-// TODO - Reduce WidgetState size.
-// See https://github.com/linebender/xilem/issues/706
+use crate::text::TextStyle;
+use crate::{BoxConstraints, ClipPath, Env, WidgetId};
+
+bitflags! {
+    /// Boolean properties of a [`WidgetState`], packed to keep the state small.
+    ///
+    /// Flags follow the same naming scheme as [`WidgetState`] fields.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub(crate) struct WidgetFlags: u64 {
+        // --- PASSES ---
+        /// This widget explicitly requested layout
+        const REQUEST_LAYOUT = 1 << 0;
+        /// This widget or a descendant explicitly requested layout
+        const NEEDS_LAYOUT = 1 << 1;
+        /// The compose method must be called on this widget
+        const REQUEST_COMPOSE = 1 << 2;
+        /// The compose method must be called on this widget or a descendant
+        const NEEDS_COMPOSE = 1 << 3;
+        /// The paint method must be called on this widget
+        const REQUEST_PAINT = 1 << 4;
+        /// The paint method must be called on this widget or a descendant
+        const NEEDS_PAINT = 1 << 5;
+        /// The accessibility method must be called on this widget
+        const REQUEST_ACCESSIBILITY = 1 << 6;
+        /// The accessibility method must be called on this widget or a descendant
+        const NEEDS_ACCESSIBILITY = 1 << 7;
+        /// An animation must run on this widget
+        const REQUEST_ANIM = 1 << 8;
+        /// An animation must run on this widget or a descendant
+        const NEEDS_ANIM = 1 << 9;
+        /// This widget or a descendant changed its `is_explicitly_disabled` value
+        const NEEDS_UPDATE_DISABLED = 1 << 10;
+        /// This widget or a descendant changed its `is_explicitly_stashed` value
+        const NEEDS_UPDATE_STASHED = 1 << 11;
+        const UPDATE_FOCUS_CHAIN = 1 << 12;
+        const CHILDREN_CHANGED = 1 << 13;
+        /// `WidgetAdded` hasn't been sent to this widget yet.
+        const IS_NEW = 1 << 14;
+        /// A flag used to track and debug missing calls to `place_child`.
+        const IS_EXPECTING_PLACE_CHILD_CALL = 1 << 15;
+        /// The translation or the transform of this widget changed.
+        const TRANSLATION_CHANGED = 1 << 16;
+
+        // --- STATUS ---
+        /// Tracks whether widget gets pointer events.
+        /// Should be immutable after `WidgetAdded` event.
+        const ACCEPTS_POINTER_INTERACTION = 1 << 17;
+        /// Tracks whether widget gets text focus.
+        /// Should be immutable after `WidgetAdded` event.
+        const ACCEPTS_FOCUS = 1 << 18;
+        /// Tracks whether widget is eligible for IME events.
+        /// Should be immutable after `WidgetAdded` event.
+        const ACCEPTS_TEXT_INPUT = 1 << 19;
+        /// This widget has been disabled.
+        const IS_EXPLICITLY_DISABLED = 1 << 20;
+        /// This widget or an ancestor has been disabled.
+        const IS_DISABLED = 1 << 21;
+        // TODO - Document concept of "stashing".
+        /// This widget has been stashed.
+        const IS_EXPLICITLY_STASHED = 1 << 22;
+        /// This widget or an ancestor has been stashed.
+        const IS_STASHED = 1 << 23;
+        const IS_HOVERED = 1 << 24;
+        /// In the focused path, starting from window and ending at the focused widget.
+        /// Descendants of the focused widget are not in the focused path.
+        const HAS_FOCUS = 1 << 25;
+
+        /// This widget or a descendant changed its `explicit_text_style` value
+        const NEEDS_UPDATE_TEXT_STYLE = 1 << 26;
+        /// This widget is an [`ErrorBoundary`](crate::widget::ErrorBoundary), which catches
+        /// panics in its descendants.
+        /// Should be immutable after `WidgetAdded` event.
+        const IS_ERROR_BOUNDARY = 1 << 27;
+        /// This widget and its descendants are skipped when hit-testing the pointer.
+        const IS_POINTER_TRANSPARENT = 1 << 28;
+        /// This widget and its descendants are left out of the accessibility tree.
+        const IS_ACCESSIBILITY_HIDDEN = 1 << 29;
+        /// This widget changed its `is_accessibility_hidden` value, or was elided or
+        /// restored, since the last accessibility pass, so its parent's node must list
+        /// its children again.
+        const ACCESSIBILITY_HIDDEN_CHANGED = 1 << 30;
+        /// This widget is an item of a set which is outside the visible area, and is left
+        /// out of the accessibility tree with its descendants.
+        const IS_ACCESSIBILITY_ELIDED = 1 << 31;
+
+        /// This widget or a descendant changed its `explicit_env` value
+        const NEEDS_UPDATE_ENV = 1 << 32;
+        /// This widget keeps arrow-key navigation within its descendants, see
+        /// [`EventCtx::set_focus_scope`](crate::EventCtx::set_focus_scope).
+        const IS_FOCUS_SCOPE = 1 << 33;
+        /// This widget was disabled by its parent, independently of its own disabled state.
+        const IS_DISABLED_BY_PARENT = 1 << 34;
+        /// This widget and its descendants were left out of the accessibility tree by its
+        /// parent, independently of its own hidden state.
+        const IS_ACCESSIBILITY_HIDDEN_BY_PARENT = 1 << 35;
+    }
+}
+
+impl WidgetFlags {
+    /// The flags which are propagated from a child to its parent in [`WidgetState::merge_up`].
+    const MERGE_UP_MASK: Self = Self::NEEDS_LAYOUT
+        .union(Self::NEEDS_COMPOSE)
+        .union(Self::NEEDS_PAINT)
+        .union(Self::NEEDS_ANIM)
+        .union(Self::NEEDS_ACCESSIBILITY)
+        .union(Self::NEEDS_UPDATE_DISABLED)
+        .union(Self::NEEDS_UPDATE_STASHED)
+        .union(Self::NEEDS_UPDATE_TEXT_STYLE)
+        .union(Self::NEEDS_UPDATE_ENV)
+        .union(Self::HAS_FOCUS)
+        .union(Self::CHILDREN_CHANGED)
+        .union(Self::UPDATE_FOCUS_CHAIN);
+}
 
 /// Generic state for all widgets in the hierarchy.
 ///
@@ -26,7 +138,7 @@ use crate::WidgetId;
 ///
 /// ## Naming scheme
 ///
-/// Some fields follow a naming scheme:
+/// Some fields and [`WidgetFlags`] follow a naming scheme:
 /// - `request_xxx`: this specific widget has requested the xxx pass to run on it
 /// - `needs_xxx`: this widget or a descendant has requested the xxx pass to run on it
 /// - `is_xxx`: this widget has the xxx property
@@ -44,8 +156,13 @@ pub(crate) struct WidgetState {
     /// The origin of the widget in the parent's coordinate space; together with
     /// `size` these constitute the widget's layout rect.
     pub(crate) origin: Point,
-    /// The origin of the widget in the window coordinate space;
-    pub(crate) window_origin: Point,
+    /// The transform from the widget's coordinate space to the window's, as presented on screen.
+    pub(crate) window_transform: Affine,
+    /// The transform from the widget's coordinate space to the window's, ignoring
+    /// presentation offsets.
+    ///
+    /// This is where the widget will be once animations settle.
+    pub(crate) window_target_transform: Affine,
     /// The insets applied to the layout rect to generate the paint rect.
     /// In general, these will be zero; the exception is for things like
     /// drop shadows or overflowing text.
@@ -59,90 +176,51 @@ pub(crate) struct WidgetState {
     /// the baseline. Widgets that contain text or controls that expect to be
     /// laid out alongside text can set this as appropriate.
     pub(crate) baseline_offset: f64,
+    /// The constraints passed to the last call to [`Widget::layout`](crate::Widget::layout).
+    ///
+    /// If a widget is laid out again with the same constraints and no widget in its
+    /// subtree requested layout, the layout pass reuses the previous result.
+    pub(crate) layout_cache_bc: Option<BoxConstraints>,
 
-    /// Tracks whether widget gets pointer events.
-    /// Should be immutable after `WidgetAdded` event.
-    pub(crate) accepts_pointer_interaction: bool,
-    /// Tracks whether widget gets text focus.
-    /// Should be immutable after `WidgetAdded` event.
-    pub(crate) accepts_focus: bool,
-
-    /// Tracks whether widget is eligible for IME events.
-    /// Should be immutable after `WidgetAdded` event.
-    pub(crate) accepts_text_input: bool,
-    /// The area of the widget that is being edited by
-    /// an IME, in local coordinates.
-    pub(crate) ime_area: Option<Rect>,
-
-    // TODO - Use general Shape
-    // Currently Kurbo doesn't really provide a type that lets us
-    // efficiently hold an arbitrary shape.
-    pub(crate) clip_path: Option<Rect>,
-
-    // TODO - Handle matrix transforms
-    pub(crate) translation: Vec2,
-    pub(crate) translation_changed: bool,
-
-    // --- PASSES ---
-    /// `WidgetAdded` hasn't been sent to this widget yet.
-    pub(crate) is_new: bool,
-
-    /// A flag used to track and debug missing calls to `place_child`.
-    pub(crate) is_expecting_place_child_call: bool,
-
-    /// This widget explicitly requested layout
-    pub(crate) request_layout: bool,
-    /// This widget or a descendant explicitly requested layout
-    pub(crate) needs_layout: bool,
-
-    /// The compose method must be called on this widget
-    pub(crate) request_compose: bool,
-    /// The compose method must be called on this widget or a descendant
-    pub(crate) needs_compose: bool,
-
-    /// The paint method must be called on this widget
-    pub(crate) request_paint: bool,
-    /// The paint method must be called on this widget or a descendant
-    pub(crate) needs_paint: bool,
-
-    /// The accessibility method must be called on this widget
-    pub(crate) request_accessibility: bool,
-    /// The accessibility method must be called on this widget or a descendant
-    pub(crate) needs_accessibility: bool,
-
-    /// An animation must run on this widget
-    pub(crate) request_anim: bool,
-    /// An animation must run on this widget or a descendant
-    pub(crate) needs_anim: bool,
-
-    /// This widget or a descendant changed its `is_explicitly_disabled` value
-    pub(crate) needs_update_disabled: bool,
-    /// This widget or a descendant changed its `is_explicitly_stashed` value
-    pub(crate) needs_update_stashed: bool,
+    /// The shape the children of this widget are clipped to, if any.
+    pub(crate) clip_path: Option<ClipPath>,
 
-    pub(crate) update_focus_chain: bool,
+    pub(crate) translation: Vec2,
+    /// The offset of the presented position of the widget from its target position.
+    ///
+    /// Animated layouts set this to move widgets smoothly towards their layout position,
+    /// while the layout itself already holds the final values.
+    pub(crate) presentation_offset: Vec2,
+    /// The transform the widget and its descendants are presented with, relative to the
+    /// widget's origin.
+    pub(crate) transform: Affine,
+    /// The opacity the widget and its descendants are composited with.
+    pub(crate) opacity: f32,
+    /// The order the widget is painted and hit-tested in among its siblings, set with
+    /// [`LayoutCtx::place_child_with_z`](crate::LayoutCtx::place_child_with_z).
+    pub(crate) z_index: i32,
+
+    // --- PASSES AND STATUS ---
+    /// Pass invalidation flags and status bits, see [`WidgetFlags`].
+    pub(crate) flags: WidgetFlags,
 
     pub(crate) focus_chain: Vec<WidgetId>,
 
-    pub(crate) children_changed: bool,
-
-    // --- STATUS ---
-    /// This widget has been disabled.
-    pub(crate) is_explicitly_disabled: bool,
-    /// This widget or an ancestor has been disabled.
-    pub(crate) is_disabled: bool,
-
-    // TODO - Document concept of "stashing".
-    /// This widget has been stashed.
-    pub(crate) is_explicitly_stashed: bool,
-    /// This widget or an ancestor has been stashed.
-    pub(crate) is_stashed: bool,
-
-    pub(crate) is_hovered: bool,
+    // --- TEXT STYLE ---
+    /// The text style set on this widget with `set_text_style`, if any.
+    pub(crate) explicit_text_style: Option<Arc<TextStyle>>,
+    /// The text style resolved from this widget and its ancestors.
+    ///
+    /// Widgets which don't set a style share their parent's `Arc`.
+    pub(crate) text_style: Arc<TextStyle>,
 
-    /// In the focused path, starting from window and ending at the focused widget.
-    /// Descendants of the focused widget are not in the focused path.
-    pub(crate) has_focus: bool,
+    // --- ENV ---
+    /// The env values set on this widget with `set_env`, if any.
+    pub(crate) explicit_env: Option<Arc<Env>>,
+    /// The env resolved from this widget, its ancestors and the root env.
+    ///
+    /// Widgets which don't set values share their parent's `Arc`.
+    pub(crate) env: Arc<Env>,
 
     // --- DEBUG INFO ---
     // TODO - document
@@ -155,41 +233,42 @@ impl WidgetState {
         WidgetState {
             id,
             origin: Point::ORIGIN,
-            window_origin: Point::ORIGIN,
+            window_transform: Affine::IDENTITY,
+            window_target_transform: Affine::IDENTITY,
             size: Size::ZERO,
-            is_expecting_place_child_call: false,
             paint_insets: Insets::ZERO,
             local_paint_rect: Rect::ZERO,
-            accepts_pointer_interaction: true,
-            accepts_focus: false,
-            accepts_text_input: false,
-            ime_area: None,
+            baseline_offset: 0.0,
+            layout_cache_bc: None,
             clip_path: Default::default(),
             translation: Vec2::ZERO,
-            translation_changed: false,
-            is_explicitly_disabled: false,
-            is_explicitly_stashed: false,
-            is_disabled: false,
-            is_stashed: false,
-            baseline_offset: 0.0,
-            is_new: true,
-            is_hovered: false,
-            request_layout: true,
-            needs_layout: true,
-            request_compose: true,
-            needs_compose: true,
-            request_paint: true,
-            needs_paint: true,
-            request_accessibility: true,
-            needs_accessibility: true,
-            has_focus: false,
-            request_anim: true,
-            needs_anim: true,
-            needs_update_disabled: true,
-            needs_update_stashed: true,
+            presentation_offset: Vec2::ZERO,
+            transform: Affine::IDENTITY,
+            opacity: 1.,
+            z_index: 0,
+            flags: WidgetFlags::ACCEPTS_POINTER_INTERACTION
+                | WidgetFlags::IS_NEW
+                | WidgetFlags::REQUEST_LAYOUT
+                | WidgetFlags::NEEDS_LAYOUT
+                | WidgetFlags::REQUEST_COMPOSE
+                | WidgetFlags::NEEDS_COMPOSE
+                | WidgetFlags::REQUEST_PAINT
+                | WidgetFlags::NEEDS_PAINT
+                | WidgetFlags::REQUEST_ACCESSIBILITY
+                | WidgetFlags::NEEDS_ACCESSIBILITY
+                | WidgetFlags::REQUEST_ANIM
+                | WidgetFlags::NEEDS_ANIM
+                | WidgetFlags::NEEDS_UPDATE_DISABLED
+                | WidgetFlags::NEEDS_UPDATE_STASHED
+                | WidgetFlags::NEEDS_UPDATE_TEXT_STYLE
+                | WidgetFlags::NEEDS_UPDATE_ENV
+                | WidgetFlags::CHILDREN_CHANGED
+                | WidgetFlags::UPDATE_FOCUS_CHAIN,
             focus_chain: Vec::new(),
-            children_changed: true,
-            update_focus_chain: true,
+            explicit_text_style: None,
+            text_style: Arc::default(),
+            explicit_env: None,
+            env: Arc::default(),
             #[cfg(debug_assertions)]
             widget_name,
         }
@@ -201,20 +280,7 @@ impl WidgetState {
     pub(crate) fn synthetic(id: WidgetId, size: Size) -> WidgetState {
         WidgetState {
             size,
-            is_new: false,
-            needs_layout: false,
-            request_compose: false,
-            needs_compose: false,
-            request_paint: false,
-            needs_paint: false,
-            request_accessibility: false,
-            needs_accessibility: false,
-            request_anim: false,
-            needs_anim: false,
-            needs_update_disabled: false,
-            needs_update_stashed: false,
-            children_changed: false,
-            update_focus_chain: false,
+            flags: WidgetFlags::ACCEPTS_POINTER_INTERACTION | WidgetFlags::REQUEST_LAYOUT,
             ..WidgetState::new(id, "<root>")
         }
     }
@@ -227,16 +293,7 @@ impl WidgetState {
     // mutated anymore. This method may start doing so again in the future, so keep taking &mut for
     // now.
     pub(crate) fn merge_up(&mut self, child_state: &mut WidgetState) {
-        self.needs_layout |= child_state.needs_layout;
-        self.needs_compose |= child_state.needs_compose;
-        self.needs_paint |= child_state.needs_paint;
-        self.needs_anim |= child_state.needs_anim;
-        self.needs_accessibility |= child_state.needs_accessibility;
-        self.needs_update_disabled |= child_state.needs_update_disabled;
-        self.has_focus |= child_state.has_focus;
-        self.children_changed |= child_state.children_changed;
-        self.update_focus_chain |= child_state.update_focus_chain;
-        self.needs_update_stashed |= child_state.needs_update_stashed;
+        self.flags |= child_state.flags & WidgetFlags::MERGE_UP_MASK;
     }
 
     /// The paint region for this widget.
@@ -253,33 +310,72 @@ impl WidgetState {
         Rect::from_origin_size(self.origin, self.size)
     }
 
-    /// The [`layout_rect`](crate::WidgetPod::layout_rect) in window coordinates.
+    /// The [`layout_rect`](Self::layout_rect), moved by the presentation offset.
+    ///
+    /// This is where the widget is shown in its parent while its position is animated.
+    pub fn presented_rect(&self) -> Rect {
+        self.layout_rect() + self.presentation_offset
+    }
+
+    /// The [`layout_rect`](crate::WidgetPod::layout_rect) in window coordinates, as presented on screen.
     ///
     /// This might not map to a visible area of the screen, eg if the widget is scrolled
-    /// away.
+    /// away. If the widget is rotated or skewed, this is the bounding box of its layout rect.
     pub fn window_layout_rect(&self) -> Rect {
-        Rect::from_origin_size(self.window_origin(), self.size)
+        self.to_window_rect(self.size.to_rect())
     }
 
-    /// Returns the area being edited by an IME, in global coordinates.
+    /// The [`layout_rect`](crate::WidgetPod::layout_rect) in window coordinates, ignoring
+    /// the presentation offsets of this widget and its ancestors.
     ///
-    /// By default, returns the same as [`Self::window_layout_rect`].
-    pub(crate) fn get_ime_area(&self) -> Rect {
-        self.ime_area.unwrap_or_else(|| self.size.to_rect()) + self.window_origin.to_vec2()
+    /// This is where the widget will be on screen once animations settle.
+    pub fn window_target_rect(&self) -> Rect {
+        self.window_target_transform
+            .transform_rect_bbox(self.size.to_rect())
     }
 
     pub(crate) fn window_origin(&self) -> Point {
-        self.window_origin
+        self.window_transform.translation().to_point()
+    }
+
+    /// The transform from the widget's coordinate space to the window's.
+    pub(crate) fn window_transform(&self) -> Affine {
+        self.window_transform
+    }
+
+    /// The factor by which lengths in the widget's coordinate space are scaled in the window's.
+    ///
+    /// For transforms which don't scale uniformly, this is the geometric mean of the scales.
+    pub(crate) fn window_scale(&self) -> f64 {
+        self.window_transform.determinant().abs().sqrt()
+    }
+
+    /// Convert a rect from the widget's coordinate space to the window's.
+    ///
+    /// If the widget is rotated or skewed, this returns the bounding box of the converted rect.
+    pub(crate) fn to_window_rect(&self, rect: Rect) -> Rect {
+        self.window_transform.transform_rect_bbox(rect)
+    }
+
+    /// Convert a point from the window's coordinate space to the widget's.
+    pub(crate) fn to_local(&self, window_point: Point) -> Point {
+        self.window_transform.inverse() * window_point
     }
 
     pub(crate) fn needs_rewrite_passes(&self) -> bool {
-        self.needs_layout
-            || self.needs_compose
-            || self.needs_update_disabled
-            || self.needs_update_stashed
+        self.flags.intersects(
+            WidgetFlags::NEEDS_LAYOUT
+                | WidgetFlags::NEEDS_COMPOSE
+                | WidgetFlags::NEEDS_UPDATE_DISABLED
+                | WidgetFlags::NEEDS_UPDATE_STASHED
+                | WidgetFlags::NEEDS_UPDATE_TEXT_STYLE
+                | WidgetFlags::NEEDS_UPDATE_ENV,
+        )
     }
 
     pub(crate) fn needs_render(&self) -> bool {
-        self.needs_anim || self.needs_paint || self.needs_accessibility
+        self.flags.intersects(
+            WidgetFlags::NEEDS_ANIM | WidgetFlags::NEEDS_PAINT | WidgetFlags::NEEDS_ACCESSIBILITY,
+        )
     }
 }
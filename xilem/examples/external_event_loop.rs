@@ -57,7 +57,8 @@ impl ApplicationHandler<MasonryUserEvent> for ExternalApp {
     }
 
     fn about_to_wait(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        self.masonry_state.handle_about_to_wait(event_loop);
+        self.masonry_state
+            .handle_about_to_wait(event_loop, self.app_driver.as_mut());
     }
 
     fn window_event(
@@ -120,7 +121,8 @@ impl ApplicationHandler<MasonryUserEvent> for ExternalApp {
     }
 
     fn memory_warning(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
-        self.masonry_state.handle_memory_warning(event_loop);
+        self.masonry_state
+            .handle_memory_warning(event_loop, self.app_driver.as_mut());
     }
 }
 
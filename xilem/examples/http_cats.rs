@@ -172,7 +172,7 @@ impl Status {
             button("Select", move |state: &mut HttpCats| {
                 state.selected_code = Some(code);
             }),
-            FlexSpacer::Fixed(masonry::theme::SCROLLBAR_WIDTH),
+            FlexSpacer::Fixed(*masonry::theme::SCROLLBAR_WIDTH.default_value()),
         ))
         .direction(Axis::Horizontal)
     }
@@ -120,7 +120,7 @@ impl TimeZone {
                         // TODO: Consider accessibility here.
                         Color::ORANGE
                     } else {
-                        masonry::theme::TEXT_COLOR
+                        *masonry::theme::TEXT_COLOR.default_value()
                     },
                 ),
             ))
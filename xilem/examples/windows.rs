@@ -0,0 +1,56 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An app with a counter in its main window, and any number of inspector windows showing
+//! and changing the same counter.
+
+use winit::error::EventLoopError;
+use xilem::view::{button, flex, label};
+use xilem::{window, EventLoop, WidgetView, WindowView, Xilem};
+
+struct AppState {
+    count: i32,
+    /// The keys of the open inspector windows.
+    inspectors: Vec<u32>,
+    next_inspector: u32,
+}
+
+fn app_logic(state: &mut AppState) -> impl WidgetView<AppState> {
+    flex((
+        label(format!("count: {}", state.count)).text_size(32.),
+        button("+", |state: &mut AppState| state.count += 1),
+        button("Open inspector", |state: &mut AppState| {
+            state.inspectors.push(state.next_inspector);
+            state.next_inspector += 1;
+        }),
+    ))
+}
+
+fn windows_logic(state: &mut AppState) -> Vec<WindowView<AppState>> {
+    state
+        .inspectors
+        .iter()
+        .map(|&key| {
+            let view = flex((
+                label(format!("count: {}", state.count)),
+                button("-", |state: &mut AppState| state.count -= 1),
+                button("Close", move |state: &mut AppState| {
+                    state.inspectors.retain(|&k| k != key);
+                }),
+            ));
+            window(key, format!("Inspector {key}"), view)
+                .on_close(move |state| state.inspectors.retain(|&k| k != key))
+        })
+        .collect()
+}
+
+fn main() -> Result<(), EventLoopError> {
+    let state = AppState {
+        count: 0,
+        inspectors: Vec::new(),
+        next_inspector: 1,
+    };
+    let app = Xilem::new(state, app_logic).with_windows(windows_logic);
+    app.run_windowed(EventLoop::with_user_event(), "Windows".into())?;
+    Ok(())
+}
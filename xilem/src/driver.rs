@@ -4,13 +4,16 @@
 use std::sync::Arc;
 
 use masonry::event_loop_runner::{self, EventLoopProxy, MasonryUserEvent};
+use masonry::locale::LocaleFormatter;
 use masonry::widget::RootWidget;
-use masonry::{AppDriver, WidgetId};
+use masonry::{AppDriver, AppWindowId, CloseReason, CloseResponse, WidgetId};
 
-use crate::core::{DynMessage, Message, MessageResult, ProxyError, RawProxy, ViewId};
-use crate::{ViewCtx, WidgetView};
+use crate::any_view::DynWidget;
+use crate::core::{DynMessage, Message, MessageResult, ProxyError, RawProxy, View as _, ViewId};
+use crate::window::{OpenWindow, WindowMessage, WindowsLogic};
+use crate::{Intercept, Middleware, MiddlewareCtx, ViewCtx, WidgetView};
 
-pub struct MasonryDriver<State, Logic, View, ViewState> {
+pub struct MasonryDriver<State: 'static, Logic, View, ViewState> {
     pub(crate) state: State,
     pub(crate) logic: Logic,
     pub(crate) current_view: View,
@@ -18,8 +21,19 @@ pub struct MasonryDriver<State, Logic, View, ViewState> {
     pub(crate) view_state: ViewState,
     // Fonts which will be registered on startup.
     pub(crate) fonts: Vec<Vec<u8>>,
+    pub(crate) formatter: Arc<dyn LocaleFormatter>,
+    pub(crate) on_close_requested: Option<CloseCallback<State>>,
+    pub(crate) middleware: Vec<Box<dyn Middleware<State>>>,
+    /// Set by [`Xilem::with_change_tracking`](crate::Xilem::with_change_tracking).
+    pub(crate) clear_changes: Option<fn(&mut State)>,
+    /// Set by [`Xilem::with_windows`](crate::Xilem::with_windows).
+    pub(crate) windows_logic: Option<WindowsLogic<State>>,
+    /// The secondary windows opened for the views returned by `windows_logic`.
+    pub(crate) windows: Vec<OpenWindow<State>>,
 }
 
+pub(crate) type CloseCallback<State> = Box<dyn FnMut(&mut State, CloseReason) -> CloseResponse>;
+
 /// The `WidgetId` which async events should be sent to.
 pub const ASYNC_MARKER_WIDGET: WidgetId = WidgetId::reserved(0x1000);
 
@@ -62,37 +76,111 @@ impl MasonryProxy {
     }
 }
 
-impl<State, Logic, View> AppDriver for MasonryDriver<State, Logic, View, View::ViewState>
+impl<State: 'static, Logic, View> MasonryDriver<State, Logic, View, View::ViewState>
 where
     Logic: FnMut(&mut State) -> View,
     View: WidgetView<State>,
 {
-    fn on_action(
+    fn rebuild(&mut self, masonry_ctx: &mut masonry::DriverCtx<'_>) {
+        let next_view = (self.logic)(&mut self.state);
+
+        let mut root = masonry_ctx.get_root::<RootWidget<View::Widget>>();
+
+        next_view.rebuild(
+            &self.current_view,
+            &mut self.view_state,
+            &mut self.ctx,
+            RootWidget::child_mut(&mut root),
+        );
+        drop(root);
+        self.current_view = next_view;
+        self.rebuild_windows(masonry_ctx);
+        if let Some(clear_changes) = self.clear_changes {
+            clear_changes(&mut self.state);
+        }
+    }
+
+    /// Open, rebuild and close the secondary windows, to match the views returned by
+    /// `windows_logic`.
+    fn rebuild_windows(&mut self, masonry_ctx: &mut masonry::DriverCtx<'_>) {
+        let Some(windows_logic) = &mut self.windows_logic else {
+            return;
+        };
+        let next_windows = windows_logic(&mut self.state);
+
+        let ctx = &mut self.ctx;
+        self.windows.retain_mut(|window| {
+            if next_windows.iter().any(|next| next.key == window.view.key) {
+                return true;
+            }
+            masonry_ctx.edit_window_root(window.id, |mut root| {
+                let mut root = root.downcast::<RootWidget<DynWidget>>();
+                window.teardown(ctx, RootWidget::child_mut(&mut root));
+            });
+            masonry_ctx.close_app_window(window.id);
+            false
+        });
+
+        for next in next_windows {
+            let Some(window) = self.windows.iter_mut().find(|w| w.view.key == next.key) else {
+                let window = OpenWindow::open(next, ctx, |attributes, root_widget| {
+                    masonry_ctx.open_window(attributes, root_widget)
+                });
+                self.windows.push(window);
+                continue;
+            };
+            let rebuilt = masonry_ctx.edit_window_root(window.id, |mut root| {
+                let mut root = root.downcast::<RootWidget<DynWidget>>();
+                window.rebuild(next, ctx, RootWidget::child_mut(&mut root));
+            });
+            if rebuilt.is_none() {
+                tracing::debug!(
+                    "Window {:?} isn't created yet, so its view wasn't rebuilt",
+                    window.id
+                );
+            }
+        }
+    }
+
+    /// Send `message` to the view at `path`, through the middleware.
+    ///
+    /// `window` is the key of the secondary window the view is in, or `None` for the main window.
+    fn dispatch(
         &mut self,
         masonry_ctx: &mut masonry::DriverCtx<'_>,
-        widget_id: masonry::WidgetId,
-        action: masonry::Action,
+        window: Option<u64>,
+        path: &[ViewId],
+        message: DynMessage,
     ) {
-        let message_result = if widget_id == ASYNC_MARKER_WIDGET {
-            let masonry::Action::Other(action) = action else {
-                panic!();
-            };
-            let (path, message) = *action.downcast::<MessagePackage>().unwrap();
-            // Handle an async path
-            self.current_view
-                .message(&mut self.view_state, &path, message, &mut self.state)
-        } else if let Some(id_path) = self.ctx.widget_map.get(&widget_id) {
-            self.current_view.message(
-                &mut self.view_state,
-                id_path.as_slice(),
-                Box::new(action),
-                &mut self.state,
-            )
-        } else {
-            tracing::error!("Got action {action:?} for unknown widget. Did you forget to use `with_action_widget`?");
-            return;
+        let mut state_changed = false;
+        for middleware in &mut self.middleware {
+            let mut ctx = MiddlewareCtx::new(&mut self.state);
+            let intercept = middleware.before_message(&mut ctx, path, &*message);
+            state_changed |= ctx.state_changed;
+            if intercept == Intercept::Consume {
+                if state_changed {
+                    self.rebuild(masonry_ctx);
+                }
+                return;
+            }
+        }
+
+        let message_result = match window {
+            None => self
+                .current_view
+                .message(&mut self.view_state, path, message, &mut self.state),
+            Some(key) => match self.windows.iter_mut().find(|w| w.view.key == key) {
+                Some(window) => {
+                    window
+                        .view
+                        .view
+                        .message(&mut window.view_state, path, message, &mut self.state)
+                }
+                // The window was closed since the message was sent.
+                None => MessageResult::Stale(message),
+            },
         };
-        let rebuild = match message_result {
+        let mut rebuild = match message_result {
             MessageResult::Action(()) => {
                 // It's not entirely clear what to do here
                 true
@@ -104,23 +192,77 @@ where
                 false
             }
         };
-        if rebuild {
-            let next_view = (self.logic)(&mut self.state);
-
-            let mut root = masonry_ctx.get_root::<RootWidget<View::Widget>>();
+        let changed = rebuild || state_changed;
+        for middleware in &mut self.middleware {
+            let mut ctx = MiddlewareCtx::new(&mut self.state);
+            middleware.after_message(&mut ctx, path, changed);
+            state_changed |= ctx.state_changed;
+        }
+        rebuild |= state_changed;
 
-            next_view.rebuild(
-                &self.current_view,
-                &mut self.view_state,
-                &mut self.ctx,
-                RootWidget::child_mut(&mut root),
-            );
-            self.current_view = next_view;
+        if rebuild {
+            self.rebuild(masonry_ctx);
         }
         if cfg!(debug_assertions) && rebuild && !masonry_ctx.content_changed() {
             tracing::debug!("Nothing changed as result of action");
         }
     }
+}
+
+impl<State: 'static, Logic, View> AppDriver for MasonryDriver<State, Logic, View, View::ViewState>
+where
+    Logic: FnMut(&mut State) -> View,
+    View: WidgetView<State>,
+{
+    fn on_action(
+        &mut self,
+        masonry_ctx: &mut masonry::DriverCtx<'_>,
+        widget_id: masonry::WidgetId,
+        action: masonry::Action,
+    ) {
+        let (window, path, message): (Option<u64>, Arc<[ViewId]>, DynMessage) = if widget_id
+            == ASYNC_MARKER_WIDGET
+        {
+            let masonry::Action::Other(action) = action else {
+                panic!();
+            };
+            // Handle an async path
+            let (path, message) = *action.downcast::<MessagePackage>().unwrap();
+            if message.as_any().is::<WindowMessage>() {
+                let message = message.into_any().downcast::<WindowMessage>().unwrap();
+                (Some(message.window), path, message.message)
+            } else {
+                (None, path, message)
+            }
+        } else if let Some(id_path) = self.ctx.widget_map.get(&widget_id) {
+            (None, id_path.as_slice().into(), Box::new(action))
+        } else if let Some((key, id_path)) = self
+            .windows
+            .iter()
+            .find_map(|w| Some((w.view.key, w.view_path(widget_id)?)))
+        {
+            (Some(key), id_path.into(), Box::new(action))
+        } else {
+            tracing::error!("Got action {action:?} for unknown widget. Did you forget to use `with_action_widget`?");
+            return;
+        };
+        self.dispatch(masonry_ctx, window, &path, message);
+    }
+    fn on_close_requested(
+        &mut self,
+        masonry_ctx: &mut masonry::DriverCtx<'_>,
+        reason: CloseReason,
+    ) -> CloseResponse {
+        let Some(on_close_requested) = &mut self.on_close_requested else {
+            return CloseResponse::Close;
+        };
+        let response = on_close_requested(&mut self.state, reason);
+        if response == CloseResponse::Veto {
+            // The app state was likely changed, e.g. to show an "unsaved changes" dialog.
+            self.rebuild(masonry_ctx);
+        }
+        response
+    }
     fn on_start(&mut self, state: &mut event_loop_runner::MasonryState) {
         let root = state.get_root();
         // Register all provided fonts
@@ -130,5 +272,31 @@ where
             // because we don't have an easy way to return this to the application.
             drop(root.register_fonts(font));
         }
+        root.set_formatter(self.formatter.clone());
+
+        if let Some(windows_logic) = &mut self.windows_logic {
+            for view in windows_logic(&mut self.state) {
+                let window = OpenWindow::open(view, &mut self.ctx, |attributes, root_widget| {
+                    state.open_window(attributes, root_widget)
+                });
+                self.windows.push(window);
+            }
+        }
+    }
+    fn on_window_closed(&mut self, masonry_ctx: &mut masonry::DriverCtx<'_>, id: AppWindowId) {
+        // Windows closed by `rebuild_windows` were already removed.
+        let Some(index) = self.windows.iter().position(|w| w.id == id) else {
+            return;
+        };
+        let mut window = self.windows.remove(index);
+        let ctx = &mut self.ctx;
+        masonry_ctx.edit_window_root(id, |mut root| {
+            let mut root = root.downcast::<RootWidget<DynWidget>>();
+            window.teardown(ctx, RootWidget::child_mut(&mut root));
+        });
+        if let Some(on_close) = &window.view.on_close {
+            on_close(&mut self.state);
+        }
+        self.rebuild(masonry_ctx);
     }
 }
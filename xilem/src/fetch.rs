@@ -0,0 +1,267 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fetching of assets over HTTP, used by views such as [`image_url`](crate::view::image_url).
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use masonry::assets::{
+    Asset, AssetError, AssetHandle, AssetKind, AssetLoader, AssetRequest, AssetSource,
+};
+use vello::peniko::Blob;
+
+/// The error returned when an asset can't be fetched.
+#[derive(Clone, Debug)]
+pub enum FetchError {
+    /// The request failed, e.g. because the server couldn't be reached.
+    Request(Arc<reqwest::Error>),
+    /// The server responded with an error status.
+    Status(reqwest::StatusCode),
+    /// The response couldn't be decoded.
+    Asset(AssetError),
+    /// The asset loader was shut down before the response was decoded.
+    Cancelled,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(err) => write!(f, "request failed: {err}"),
+            Self::Status(status) => write!(f, "server responded with {status}"),
+            Self::Asset(err) => err.fmt(f),
+            Self::Cancelled => f.write_str("request was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(err) => Some(&**err),
+            Self::Status(_) => None,
+            Self::Asset(err) => Some(err),
+            Self::Cancelled => None,
+        }
+    }
+}
+
+impl FetchError {
+    /// Whether the request may succeed if it is made again.
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Request(err) => err.is_timeout() || err.is_connect(),
+            Self::Status(status) => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Self::Asset(_) | Self::Cancelled => false,
+        }
+    }
+}
+
+/// Fetches assets over HTTP, and keeps the decoded assets in memory.
+///
+/// Failed requests are retried with an exponential backoff when the error may be
+/// transient, e.g. on timeouts or server errors.
+///
+/// The cache holds a bounded number of assets,
+/// see [`with_cache_capacity`](Self::with_cache_capacity).
+pub struct Fetcher {
+    client: reqwest::Client,
+    retries: u32,
+    retry_delay: Duration,
+    cache: Mutex<AssetCache>,
+}
+
+type CacheKey = (String, AssetKind);
+
+/// Decoded assets, which evicts the least recently used asset when full.
+struct AssetCache {
+    capacity: usize,
+    assets: HashMap<CacheKey, Asset>,
+    /// The keys of `assets`, from the least to the most recently used.
+    order: VecDeque<CacheKey>,
+}
+
+impl AssetCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            assets: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<Asset> {
+        let asset = self.assets.get(key)?.clone();
+        self.mark_used(key);
+        Some(asset)
+    }
+
+    fn insert(&mut self, key: CacheKey, asset: Asset) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.assets.insert(key.clone(), asset).is_some() {
+            self.mark_used(&key);
+        } else {
+            self.order.push_back(key);
+        }
+        self.evict();
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn clear(&mut self) {
+        self.assets.clear();
+        self.order.clear();
+    }
+
+    fn mark_used(&mut self, key: &CacheKey) {
+        if let Some(index) = self.order.iter().position(|used| used == key) {
+            let key = self.order.remove(index).unwrap();
+            self.order.push_back(key);
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.assets.len() > self.capacity {
+            let Some(key) = self.order.pop_front() else {
+                break;
+            };
+            self.assets.remove(&key);
+        }
+    }
+}
+
+impl Fetcher {
+    /// Create a fetcher which sends its requests with `client`.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            retries: 2,
+            retry_delay: Duration::from_millis(500),
+            cache: Mutex::new(AssetCache::new(64)),
+        }
+    }
+
+    /// Builder-style method for setting how many times failed requests are retried.
+    ///
+    /// The default is 2.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Builder-style method for setting the delay before the first retry.
+    ///
+    /// The delay doubles on each following retry. The default is 500ms.
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Builder-style method for setting how many decoded assets are kept in memory.
+    ///
+    /// When the cache is full, the least recently used asset is dropped. Assets still
+    /// shown by a widget stay alive, but are fetched again if requested later.
+    /// The default is 64, and 0 disables the cache.
+    pub fn with_cache_capacity(self, capacity: usize) -> Self {
+        self.cache.lock().unwrap().set_capacity(capacity);
+        self
+    }
+
+    /// Fetch the content at `url`.
+    pub async fn fetch(&self, url: &str) -> Result<Blob<u8>, FetchError> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            match self.fetch_once(url).await {
+                Err(err) if attempt < self.retries && err.is_transient() => {
+                    tracing::debug!("Fetching {url} failed, retrying in {delay:?}: {err}");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn fetch_once(&self, url: &str) -> Result<Blob<u8>, FetchError> {
+        let request_error = |err| FetchError::Request(Arc::new(err));
+        let response = self.client.get(url).send().await.map_err(request_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(FetchError::Status(status));
+        }
+        let bytes = response.bytes().await.map_err(request_error)?;
+        Ok(Blob::new(Arc::new(bytes)))
+    }
+
+    /// Fetch the content at `url` and decode it on `loader`.
+    ///
+    /// Decoded assets are cached, and returned without any request when the same
+    /// asset is fetched again, unless it was evicted in the meantime.
+    pub async fn fetch_asset(
+        &self,
+        url: &str,
+        kind: AssetKind,
+        loader: &AssetLoader,
+    ) -> Result<Asset, FetchError> {
+        if let Some(asset) = self.cached(url, kind) {
+            return Ok(asset);
+        }
+        let data = self.fetch(url).await?;
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let handle = loader.load(
+            AssetRequest::new(AssetSource::Bytes(data), kind),
+            move |result| {
+                drop(sender.send(result));
+            },
+        );
+        // If this future is dropped, e.g. because the view which made the request was
+        // removed, the decoding is cancelled too.
+        let _guard = CancelOnDrop(handle);
+        let asset = receiver
+            .await
+            .map_err(|_| FetchError::Cancelled)?
+            .map_err(FetchError::Asset)?;
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert((url.to_string(), kind), asset.clone());
+        Ok(asset)
+    }
+
+    /// The decoded asset fetched from `url`, if it is in the cache.
+    pub fn cached(&self, url: &str, kind: AssetKind) -> Option<Asset> {
+        self.cache.lock().unwrap().get(&(url.to_string(), kind))
+    }
+
+    /// Remove all assets from the cache.
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+}
+
+impl Default for Fetcher {
+    fn default() -> Self {
+        Self::new(reqwest::Client::new())
+    }
+}
+
+struct CancelOnDrop(AssetHandle);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
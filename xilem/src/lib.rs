@@ -9,7 +9,9 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use masonry::assets::AssetLoader;
 use masonry::dpi::LogicalSize;
+use masonry::locale::{system_formatter, LocaleFormatter};
 use masonry::widget::{RootWidget, WidgetMut};
 use masonry::{event_loop_runner, Widget, WidgetId, WidgetPod};
 use winit::error::EventLoopError;
@@ -19,8 +21,10 @@ use crate::core::{
     AsyncCtx, MessageResult, Mut, RawProxy, SuperElement, View, ViewElement, ViewId,
     ViewPathTracker, ViewSequence,
 };
+use crate::driver::CloseCallback;
+use crate::window::WindowsLogic;
 pub use masonry::event_loop_runner::{EventLoop, EventLoopBuilder};
-pub use masonry::{dpi, Color, TextAlignment, TextWeight};
+pub use masonry::{dpi, CloseReason, CloseResponse, Color, TextAlignment, TextWeight};
 pub use xilem_core as core;
 
 /// Tokio is the async runner used with Xilem.
@@ -28,11 +32,22 @@ pub use tokio;
 
 mod any_view;
 mod driver;
+mod middleware;
 mod one_of;
+mod time_travel;
+mod tracking;
+mod window;
+
+#[cfg(feature = "http")]
+pub mod fetch;
 
 pub mod view;
 pub use any_view::AnyWidgetView;
 pub use driver::{async_action, MasonryDriver, MasonryProxy, ASYNC_MARKER_WIDGET};
+pub use middleware::{Intercept, Middleware, MiddlewareCtx};
+pub use time_travel::TimeTravel;
+pub use tracking::{Changes, Tracked};
+pub use window::{window, WindowView};
 
 pub struct Xilem<State, Logic> {
     state: State,
@@ -41,6 +56,14 @@ pub struct Xilem<State, Logic> {
     background_color: Color,
     // Font data to include in loading.
     fonts: Vec<Vec<u8>>,
+    formatter: Arc<dyn LocaleFormatter>,
+    asset_loader: Arc<AssetLoader>,
+    #[cfg(feature = "http")]
+    fetcher: Arc<fetch::Fetcher>,
+    on_close_requested: Option<CloseCallback<State>>,
+    middleware: Vec<Box<dyn Middleware<State>>>,
+    clear_changes: Option<fn(&mut State)>,
+    windows_logic: Option<WindowsLogic<State>>,
 }
 
 impl<State, Logic, View> Xilem<State, Logic>
@@ -56,6 +79,14 @@ where
             runtime,
             background_color: Color::BLACK,
             fonts: Vec::new(),
+            formatter: system_formatter(),
+            asset_loader: Arc::new(AssetLoader::default()),
+            #[cfg(feature = "http")]
+            fetcher: Arc::new(fetch::Fetcher::default()),
+            on_close_requested: None,
+            middleware: Vec::new(),
+            clear_changes: None,
+            windows_logic: None,
         }
     }
 
@@ -67,6 +98,77 @@ where
         self
     }
 
+    /// Set the formatter used to display numbers and dates.
+    ///
+    /// Defaults to the [`system_formatter`] for the locale of the current process.
+    pub fn with_formatter(mut self, formatter: impl LocaleFormatter + 'static) -> Self {
+        self.formatter = Arc::new(formatter);
+        self
+    }
+
+    /// Set the loader used by views such as [`image_asset`](view::image_asset) to load
+    /// assets in the background.
+    ///
+    /// Defaults to an [`AssetLoader`] with one thread per core, up to 4 threads.
+    pub fn with_asset_loader(mut self, loader: AssetLoader) -> Self {
+        self.asset_loader = Arc::new(loader);
+        self
+    }
+
+    /// Set the fetcher used by views such as [`image_url`](view::image_url) to fetch
+    /// assets over HTTP.
+    #[cfg(feature = "http")]
+    pub fn with_fetcher(mut self, fetcher: fetch::Fetcher) -> Self {
+        self.fetcher = Arc::new(fetcher);
+        self
+    }
+
+    /// Call `on_close_requested` when the window is about to close, or the app is asked
+    /// to terminate.
+    ///
+    /// Return [`CloseResponse::Veto`] to keep the app running, e.g. to ask the user whether
+    /// to save their changes first. The view is then rebuilt, to show the changes made
+    /// to the state.
+    pub fn on_close_requested(
+        mut self,
+        on_close_requested: impl FnMut(&mut State, CloseReason) -> CloseResponse + 'static,
+    ) -> Self {
+        self.on_close_requested = Some(Box::new(on_close_requested));
+        self
+    }
+
+    /// Add a [`Middleware`], which every message goes through before it reaches the views.
+    ///
+    /// Middleware is run in the order it was added.
+    pub fn with_middleware(mut self, middleware: impl Middleware<State> + 'static) -> Self {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Clear the [`Changes`] of the state after each rebuild, so that [`reads`](view::reads)
+    /// views are only rebuilt when the paths they read are marked again.
+    ///
+    /// See the [`reads`](view::reads) docs for how to use change tracking.
+    pub fn with_change_tracking(mut self) -> Self
+    where
+        State: Tracked,
+    {
+        self.clear_changes = Some(|state| state.changes_mut().clear());
+        self
+    }
+
+    /// Show secondary windows, described by the [`window`] views `windows_logic` returns.
+    ///
+    /// Like the main view, `windows_logic` is called again after each change to the state,
+    /// so that windows can be opened and closed by changing the state.
+    pub fn with_windows(
+        mut self,
+        windows_logic: impl FnMut(&mut State) -> Vec<WindowView<State>> + 'static,
+    ) -> Self {
+        self.windows_logic = Some(Box::new(windows_logic));
+        self
+    }
+
     /// Sets main window background color.
     pub fn background_color(mut self, color: Color) -> Self {
         self.background_color = color;
@@ -118,16 +220,29 @@ where
     ) -> (
         impl Widget,
         MasonryDriver<State, Logic, View, View::ViewState>,
-    ) {
+    )
+    where
+        State: 'static,
+    {
+        for middleware in &mut self.middleware {
+            middleware.on_start(&mut MiddlewareCtx::new(&mut self.state));
+        }
         let first_view = (self.logic)(&mut self.state);
         let mut ctx = ViewCtx {
             widget_map: WidgetMap::default(),
             id_path: Vec::new(),
             proxy,
             runtime: self.runtime,
+            formatter: self.formatter.clone(),
+            asset_loader: self.asset_loader,
+            #[cfg(feature = "http")]
+            fetcher: self.fetcher,
         };
         let (pod, view_state) = first_view.build(&mut ctx);
         let root_widget = RootWidget::from_pod(pod.inner);
+        if let Some(clear_changes) = self.clear_changes {
+            clear_changes(&mut self.state);
+        }
         let driver = MasonryDriver {
             current_view: first_view,
             logic: self.logic,
@@ -135,6 +250,12 @@ where
             ctx,
             view_state,
             fonts: self.fonts,
+            formatter: self.formatter,
+            on_close_requested: self.on_close_requested,
+            middleware: self.middleware,
+            clear_changes: self.clear_changes,
+            windows_logic: self.windows_logic,
+            windows: Vec::new(),
         };
         (root_widget, driver)
     }
@@ -190,6 +311,110 @@ pub trait WidgetView<State, Action = ()>:
     {
         Box::new(self)
     }
+
+    /// Animate changes to `property` of this view over `duration`, following `easing`.
+    ///
+    /// See [`animated`](view::animated) for more details.
+    fn transition(
+        self,
+        property: view::AnimatedProperty,
+        duration: std::time::Duration,
+        easing: view::Easing,
+    ) -> view::Animated<Self, State, Action>
+    where
+        Self: Sized,
+    {
+        view::animated(self).transition(property, duration, easing)
+    }
+
+    /// Paint this view with `opacity`, between 0 and 1.
+    ///
+    /// See [`animated`](view::animated) for more details.
+    fn opacity(self, opacity: f64) -> view::Animated<Self, State, Action>
+    where
+        Self: Sized,
+    {
+        view::animated(self).opacity(opacity)
+    }
+
+    /// Offset this view from its layout position by `offset`.
+    ///
+    /// See [`animated`](view::animated) for more details.
+    fn offset(self, offset: impl Into<vello::kurbo::Vec2>) -> view::Animated<Self, State, Action>
+    where
+        Self: Sized,
+    {
+        view::animated(self).offset(offset)
+    }
+
+    /// Only handle the last message of this view in each burst, once it has sent no
+    /// message for `duration`.
+    ///
+    /// See [`debounce`](view::debounce) for more details.
+    fn debounce(self, duration: std::time::Duration) -> view::RateLimit<Self>
+    where
+        Self: Sized,
+    {
+        view::debounce(self, duration)
+    }
+
+    /// Handle at most one message of this view every `duration`.
+    ///
+    /// See [`throttle`](view::throttle) for more details.
+    fn throttle(self, duration: std::time::Duration) -> view::RateLimit<Self>
+    where
+        Self: Sized,
+    {
+        view::throttle(self, duration)
+    }
+
+    /// Call `on_click` when this view is clicked.
+    ///
+    /// See [`interactive`](view::interactive) for more details.
+    fn on_click<F>(self, on_click: F) -> view::Interactive<Self, State, Action>
+    where
+        F: Fn(&mut State, masonry::PointerButton) -> Action + Send + Sync + 'static,
+        Self: Sized,
+    {
+        view::interactive(self).on_click(on_click)
+    }
+
+    /// Call `on_hover` when the pointer enters (`true`) or leaves (`false`) this view.
+    ///
+    /// See [`interactive`](view::interactive) for more details.
+    fn on_hover<F>(self, on_hover: F) -> view::Interactive<Self, State, Action>
+    where
+        F: Fn(&mut State, bool) -> Action + Send + Sync + 'static,
+        Self: Sized,
+    {
+        view::interactive(self).on_hover(on_hover)
+    }
+
+    /// Call `on_key` when a key is pressed while this view has focus.
+    ///
+    /// See [`interactive`](view::interactive) for more details.
+    fn on_key<F>(self, on_key: F) -> view::Interactive<Self, State, Action>
+    where
+        F: Fn(&mut State, &winit::keyboard::Key, winit::keyboard::ModifiersState) -> Action
+            + Send
+            + Sync
+            + 'static,
+        Self: Sized,
+    {
+        view::interactive(self).on_key(on_key)
+    }
+
+    /// Call `on_paste` with the text of the clipboard when it is pasted while this view
+    /// has focus.
+    ///
+    /// See [`interactive`](view::interactive) for more details.
+    fn on_paste<F>(self, on_paste: F) -> view::Interactive<Self, State, Action>
+    where
+        F: Fn(&mut State, String) -> Action + Send + Sync + 'static,
+        Self: Sized,
+    {
+        view::interactive(self).on_paste(on_paste)
+    }
 }
 
 impl<V, State, Action, W> WidgetView<State, Action> for V
@@ -234,6 +459,10 @@ pub struct ViewCtx {
     id_path: Vec<ViewId>,
     proxy: Arc<dyn RawProxy>,
     runtime: tokio::runtime::Runtime,
+    formatter: Arc<dyn LocaleFormatter>,
+    asset_loader: Arc<AssetLoader>,
+    #[cfg(feature = "http")]
+    fetcher: Arc<fetch::Fetcher>,
 }
 
 impl ViewPathTracker for ViewCtx {
@@ -285,6 +514,22 @@ impl ViewCtx {
     pub fn runtime(&self) -> &tokio::runtime::Runtime {
         &self.runtime
     }
+
+    /// The formatter used to display numbers and dates, set with [`Xilem::with_formatter`].
+    pub fn formatter(&self) -> &dyn LocaleFormatter {
+        &*self.formatter
+    }
+
+    /// The loader used to load assets in the background, set with [`Xilem::with_asset_loader`].
+    pub fn asset_loader(&self) -> &AssetLoader {
+        &self.asset_loader
+    }
+
+    /// The fetcher used to fetch assets over HTTP, set with [`Xilem::with_fetcher`].
+    #[cfg(feature = "http")]
+    pub fn fetcher(&self) -> &fetch::Fetcher {
+        &self.fetcher
+    }
 }
 
 impl AsyncCtx for ViewCtx {
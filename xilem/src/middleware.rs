@@ -0,0 +1,95 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks which observe and intercept the messages handled by the app.
+
+use crate::core::{Message, ViewId};
+
+/// A hook which every message flows through before it reaches the view tree, and thus
+/// the app state.
+///
+/// Middleware is added with [`Xilem::with_middleware`](crate::Xilem::with_middleware), and
+/// is run in the order it was added. Use cases include logging, persisting the app state,
+/// and time-travel debugging, using [`MiddlewareCtx::rehydrate`] to replace the app state
+/// with a previously saved one.
+pub trait Middleware<State> {
+    #[allow(unused_variables)]
+    // reason: otherwise `ctx` would need to be named `_ctx` which behaves badly when using rust-analyzer to implement the trait
+    /// Called once, before the first view is built.
+    ///
+    /// This is where persisted state should be restored.
+    fn on_start(&mut self, ctx: &mut MiddlewareCtx<'_, State>) {}
+
+    #[allow(unused_variables)]
+    // reason: otherwise `ctx` would need to be named `_ctx` which behaves badly when using rust-analyzer to implement the trait
+    /// Called before `message` is sent to the view at `path`.
+    ///
+    /// Return [`Intercept::Consume`] to stop the message there. It then doesn't reach the
+    /// view tree, nor any middleware added after this one.
+    fn before_message(
+        &mut self,
+        ctx: &mut MiddlewareCtx<'_, State>,
+        path: &[ViewId],
+        message: &dyn Message,
+    ) -> Intercept {
+        Intercept::Continue
+    }
+
+    #[allow(unused_variables)]
+    // reason: otherwise `ctx` would need to be named `_ctx` which behaves badly when using rust-analyzer to implement the trait
+    /// Called after a message has been handled by the view at `path`.
+    ///
+    /// `changed` is `true` if handling the message may have changed the app state.
+    fn after_message(
+        &mut self,
+        ctx: &mut MiddlewareCtx<'_, State>,
+        path: &[ViewId],
+        changed: bool,
+    ) {
+    }
+}
+
+/// What should happen to a message after it went through a [`Middleware`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Intercept {
+    /// Pass the message on to the next middleware, and then to the view tree.
+    Continue,
+    /// Drop the message.
+    Consume,
+}
+
+/// The context given to [`Middleware`] hooks.
+pub struct MiddlewareCtx<'a, State> {
+    state: &'a mut State,
+    pub(crate) state_changed: bool,
+}
+
+impl<'a, State> MiddlewareCtx<'a, State> {
+    pub(crate) fn new(state: &'a mut State) -> Self {
+        Self {
+            state,
+            state_changed: false,
+        }
+    }
+
+    /// The current app state.
+    pub fn state(&self) -> &State {
+        self.state
+    }
+
+    /// Mutate the app state.
+    ///
+    /// The views will be rebuilt.
+    pub fn state_mut(&mut self) -> &mut State {
+        self.state_changed = true;
+        self.state
+    }
+
+    /// Replace the app state, e.g. with one which was saved or recorded earlier.
+    ///
+    /// The views will be rebuilt. Returns the previous state.
+    pub fn rehydrate(&mut self, state: State) -> State {
+        self.state_changed = true;
+        std::mem::replace(self.state, state)
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Time-travel debugging of the app state.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::core::{Message, ViewId};
+use crate::view::{button, flex, label, Axis};
+use crate::{Intercept, Middleware, MiddlewareCtx, WidgetView};
+
+/// The number of snapshots kept by [`TimeTravel::new`].
+const DEFAULT_MAX_SNAPSHOTS: usize = 1000;
+
+/// A [`Middleware`] which records a snapshot of the app state after each message which
+/// changed it, and lets the user step back and forth through these snapshots.
+///
+/// When another snapshot is selected, the app state is replaced with it, and the views are
+/// rebuilt. Handling a message while an older snapshot is selected discards the newer ones.
+///
+/// `TimeTravel` is a cheap handle to shared history: add a clone of it to the app with
+/// [`Xilem::with_middleware`](crate::Xilem::with_middleware), and show its
+/// [`panel`](Self::panel) next to the app's views.
+///
+/// ```ignore
+/// let time_travel = TimeTravel::new();
+/// let panel = time_travel.clone();
+/// Xilem::new(state, move |state| flex((app_logic(state), panel.panel())))
+///     .with_middleware(time_travel)
+/// ```
+pub struct TimeTravel<State> {
+    history: Arc<Mutex<History<State>>>,
+}
+
+struct History<State> {
+    /// The recorded states, with a description of the message which led to each of them.
+    snapshots: Vec<(String, State)>,
+    /// The index of the snapshot matching the app state.
+    cursor: usize,
+    /// The snapshot selected by the user, which is restored once the current message is handled.
+    pending: Option<usize>,
+    /// The description of the message being handled.
+    current_message: Option<String>,
+    max_snapshots: usize,
+}
+
+impl<State> Clone for TimeTravel<State> {
+    fn clone(&self) -> Self {
+        Self {
+            history: self.history.clone(),
+        }
+    }
+}
+
+impl<State> Default for TimeTravel<State> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State> TimeTravel<State> {
+    /// Create an empty history, which keeps up to 1000 snapshots.
+    pub fn new() -> Self {
+        Self::with_max_snapshots(DEFAULT_MAX_SNAPSHOTS)
+    }
+
+    /// Create an empty history, which keeps up to `max_snapshots` snapshots.
+    ///
+    /// Once the limit is reached, the oldest snapshots are discarded.
+    pub fn with_max_snapshots(max_snapshots: usize) -> Self {
+        Self {
+            history: Arc::new(Mutex::new(History {
+                snapshots: Vec::new(),
+                cursor: 0,
+                pending: None,
+                current_message: None,
+                max_snapshots: max_snapshots.max(1),
+            })),
+        }
+    }
+
+    /// The number of recorded snapshots.
+    pub fn len(&self) -> usize {
+        self.lock().snapshots.len()
+    }
+
+    /// Whether no snapshot has been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.lock().snapshots.is_empty()
+    }
+
+    /// The index of the selected snapshot.
+    pub fn cursor(&self) -> usize {
+        self.lock().cursor
+    }
+
+    /// Select the snapshot at `index`, which is clamped to the recorded snapshots.
+    ///
+    /// The app state is replaced once the message being handled, usually the one which
+    /// called this method, has been handled.
+    pub fn go_to(&self, index: usize) {
+        let mut history = self.lock();
+        let last = history.snapshots.len().saturating_sub(1);
+        history.pending = Some(index.min(last));
+    }
+
+    /// Select the snapshot before the selected one.
+    pub fn step_back(&self) {
+        let cursor = self.cursor();
+        self.go_to(cursor.saturating_sub(1));
+    }
+
+    /// Select the snapshot after the selected one.
+    pub fn step_forward(&self) {
+        let cursor = self.cursor();
+        self.go_to(cursor + 1);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, History<State>> {
+        self.history.lock().unwrap()
+    }
+}
+
+impl<State: Send + 'static> TimeTravel<State> {
+    /// A panel with buttons to step through the snapshots, and a description of the
+    /// message which led to the selected one.
+    pub fn panel(&self) -> impl WidgetView<State> {
+        let history = self.lock();
+        let description = match history.snapshots.get(history.cursor) {
+            Some((message, _)) => format!(
+                "{} / {}: {message}",
+                history.cursor + 1,
+                history.snapshots.len()
+            ),
+            None => "No snapshots recorded".into(),
+        };
+        drop(history);
+
+        let back = self.clone();
+        let forward = self.clone();
+        flex((
+            button("◀", move |_: &mut State| back.step_back()),
+            label(description),
+            button("▶", move |_: &mut State| forward.step_forward()),
+        ))
+        .direction(Axis::Horizontal)
+    }
+}
+
+impl<State: Clone> Middleware<State> for TimeTravel<State> {
+    fn on_start(&mut self, ctx: &mut MiddlewareCtx<'_, State>) {
+        let mut history = self.lock();
+        history.snapshots = vec![("Initial state".into(), ctx.state().clone())];
+        history.cursor = 0;
+    }
+
+    fn before_message(
+        &mut self,
+        _ctx: &mut MiddlewareCtx<'_, State>,
+        _path: &[ViewId],
+        message: &dyn Message,
+    ) -> Intercept {
+        self.lock().current_message = Some(format!("{message:?}"));
+        Intercept::Continue
+    }
+
+    fn after_message(
+        &mut self,
+        ctx: &mut MiddlewareCtx<'_, State>,
+        _path: &[ViewId],
+        changed: bool,
+    ) {
+        let mut history = self.lock();
+        let message = history.current_message.take();
+        if let Some(index) = history.pending.take() {
+            // The message selected a snapshot, so it isn't recorded itself.
+            if let Some((_, state)) = history.snapshots.get(index) {
+                ctx.rehydrate(state.clone());
+                history.cursor = index;
+            }
+            return;
+        }
+        if !changed {
+            return;
+        }
+        let cursor = history.cursor;
+        history.snapshots.truncate(cursor + 1);
+        history
+            .snapshots
+            .push((message.unwrap_or_default(), ctx.state().clone()));
+        if history.snapshots.len() > history.max_snapshots {
+            history.snapshots.remove(0);
+        }
+        history.cursor = history.snapshots.len() - 1;
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in tracking of which parts of the app state changed, so that only the views
+//! reading them are rebuilt.
+//!
+//! By default, every message which may have changed the app state rebuilds the whole
+//! view tree. For very large UIs, the app state can instead implement [`Tracked`], and
+//! message handlers [mark](Changes::mark) the paths of the state they change, e.g.
+//! `"todos.3.title"`. Sections of the view tree wrapped in [`reads`](crate::view::reads)
+//! are then only rebuilt when one of the paths they read changed.
+//! Tracking is enabled with [`Xilem::with_change_tracking`](crate::Xilem::with_change_tracking).
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::Location;
+use std::sync::Arc;
+
+/// The paths of the app state which changed since the last rebuild.
+///
+/// Paths are made of segments separated by dots, e.g. `"todos.3.title"`. A change to a
+/// path also changes its ancestors and its descendants, so a view reading `"todos"`
+/// is rebuilt when `"todos.3.title"` changes, and the other way around.
+#[derive(Clone, Debug)]
+pub struct Changes {
+    all: bool,
+    paths: Vec<String>,
+    /// The views built by [`reads`](crate::view::reads), which are reused until their
+    /// paths change.
+    pub(crate) views: KeptViews,
+}
+
+/// App state which records the paths changed by message handlers.
+pub trait Tracked {
+    fn changes(&self) -> &Changes;
+    fn changes_mut(&mut self) -> &mut Changes;
+}
+
+impl Changes {
+    /// Create a set in which everything changed, as is the case before the first build.
+    pub fn new() -> Self {
+        Self {
+            all: true,
+            paths: Vec::new(),
+            views: KeptViews::default(),
+        }
+    }
+
+    /// Record that the state at `path` changed.
+    pub fn mark(&mut self, path: impl Into<String>) {
+        if !self.all {
+            self.paths.push(path.into());
+        }
+    }
+
+    /// Record that the whole state changed, e.g. after loading a new document.
+    pub fn mark_all(&mut self) {
+        self.all = true;
+        self.paths.clear();
+    }
+
+    /// Whether the state at `path` changed.
+    pub fn touches(&self, path: &str) -> bool {
+        self.all
+            || self
+                .paths
+                .iter()
+                .any(|changed| is_prefix(changed, path) || is_prefix(path, changed))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.all && self.paths.is_empty()
+    }
+
+    /// Forget all changes. This is done by the driver after each rebuild.
+    ///
+    /// This also drops the views kept for [`reads`](crate::view::reads) sections which
+    /// weren't part of the last rebuild.
+    pub fn clear(&mut self) {
+        self.all = false;
+        self.paths.clear();
+        self.views.end_cycle();
+    }
+}
+
+/// Identifies a [`reads`](crate::view::reads) section across rebuilds: the place it's
+/// created in, the paths it reads, and how many sections with the same place and paths
+/// were created before it in the same rebuild, e.g. in a loop.
+type SectionKey = (&'static Location<'static>, String, u32);
+
+/// The views of the [`reads`](crate::view::reads) sections, kept between rebuilds.
+#[derive(Clone, Default)]
+pub(crate) struct KeptViews {
+    /// The views, and whether their section was created during the current rebuild.
+    views: HashMap<SectionKey, (Arc<dyn Any>, bool)>,
+    /// The number of sections created so far in the current rebuild, per place and paths.
+    counts: HashMap<(&'static Location<'static>, String), u32>,
+}
+
+impl KeptViews {
+    /// The key of the next section created at `location`, reading `paths`.
+    pub(crate) fn next_key(
+        &mut self,
+        location: &'static Location<'static>,
+        paths: &[&str],
+    ) -> SectionKey {
+        let paths = paths.join("\n");
+        let count = self.counts.entry((location, paths.clone())).or_default();
+        let key = (location, paths, *count);
+        *count += 1;
+        key
+    }
+
+    /// The view kept for `key`, if any, which is kept for the next rebuild too.
+    pub(crate) fn get<V: 'static>(&mut self, key: &SectionKey) -> Option<Arc<V>> {
+        let (view, used) = self.views.get_mut(key)?;
+        let view = view.clone().downcast::<V>().ok()?;
+        *used = true;
+        Some(view)
+    }
+
+    pub(crate) fn insert<V: 'static>(&mut self, key: SectionKey, view: Arc<V>) {
+        self.views.insert(key, (view, true));
+    }
+
+    fn end_cycle(&mut self) {
+        self.views.retain(|_, (_, used)| std::mem::take(used));
+        self.counts.clear();
+    }
+}
+
+impl fmt::Debug for KeptViews {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeptViews")
+            .field("len", &self.views.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Changes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `prefix` is `path`, or one of its ancestors.
+fn is_prefix(prefix: &str, path: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('.'),
+        None => false,
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+/// A view which leaves `child` out of the accessibility tree, while still showing it.
+///
+/// This is meant for purely decorative content, such as backgrounds and dividers.
+/// See [`AccessibilityHidden`](widget::AccessibilityHidden) for more details.
+pub fn accessibility_hidden<State, Action, V>(child: V) -> AccessibilityHidden<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+{
+    AccessibilityHidden {
+        child,
+        hidden: true,
+        phantom: PhantomData,
+    }
+}
+
+/// The [`View`] created by [`accessibility_hidden`].
+pub struct AccessibilityHidden<V, State, Action> {
+    child: V,
+    hidden: bool,
+    phantom: PhantomData<fn() -> (State, Action)>,
+}
+
+impl<V, State, Action> AccessibilityHidden<V, State, Action> {
+    /// Set whether the child is left out of the accessibility tree, `true` by default.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+}
+
+impl<V, State, Action> ViewMarker for AccessibilityHidden<V, State, Action> {}
+impl<V, State, Action> View<State, Action, ViewCtx> for AccessibilityHidden<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::AccessibilityHidden<Box<dyn masonry::Widget>>>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (child, child_state) = self.child.build(ctx);
+        let widget =
+            widget::AccessibilityHidden::new_pod(child.inner.boxed()).with_hidden(self.hidden);
+        (ctx.new_pod(widget), child_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if self.hidden != prev.hidden {
+            widget::AccessibilityHidden::set_hidden(&mut element, self.hidden);
+        }
+        let mut child = widget::AccessibilityHidden::child_mut(&mut element);
+        self.child
+            .rebuild(&prev.child, view_state, ctx, child.downcast());
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        let mut child = widget::AccessibilityHidden::child_mut(&mut element);
+        self.child.teardown(view_state, ctx, child.downcast());
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        self.child.message(view_state, id_path, message, app_state)
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+pub use masonry::animation::Easing;
+use masonry::animation::Transition;
+use masonry::widget;
+pub use masonry::widget::AnimatedProperty;
+use vello::kurbo::Vec2;
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+/// A view which sets the opacity and offset of `child`, and animates changes to them
+/// and to the size of the child.
+///
+/// This is usually created with [`WidgetView::transition`], [`WidgetView::opacity`] or
+/// [`WidgetView::offset`]. Calling more of these methods on the result configures the
+/// same view. Properties without a transition jump to their new value.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use xilem::view::{label, AnimatedProperty, Easing};
+/// use xilem::WidgetView;
+///
+/// # fn view(visible: bool) -> impl WidgetView<()> {
+/// label("Saved")
+///     .opacity(if visible { 1. } else { 0. })
+///     .transition(AnimatedProperty::Opacity, Duration::from_millis(200), Easing::EaseOut)
+/// # }
+/// ```
+pub fn animated<State, Action, V>(child: V) -> Animated<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+{
+    Animated {
+        child,
+        opacity: 1.,
+        offset: Vec2::ZERO,
+        transitions: Vec::new(),
+        phantom: PhantomData,
+    }
+}
+
+/// The [`View`] created by [`animated`].
+///
+/// See `animated`'s docs for more details.
+pub struct Animated<V, State, Action = ()> {
+    child: V,
+    opacity: f64,
+    offset: Vec2,
+    transitions: Vec<(AnimatedProperty, Transition)>,
+    phantom: PhantomData<fn() -> (State, Action)>,
+}
+
+impl<V, State, Action> Animated<V, State, Action> {
+    /// Set the opacity of the child, between 0 and 1.
+    pub fn opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Set the offset of the child from its layout position.
+    pub fn offset(mut self, offset: impl Into<Vec2>) -> Self {
+        self.offset = offset.into();
+        self
+    }
+
+    /// Animate changes to `property` over `duration`, following `easing`.
+    pub fn transition(
+        mut self,
+        property: AnimatedProperty,
+        duration: Duration,
+        easing: Easing,
+    ) -> Self {
+        let transition = Transition::new(duration, easing);
+        self.transitions.retain(|(other, _)| *other != property);
+        self.transitions.push((property, transition));
+        self
+    }
+
+    fn transition_of(&self, property: AnimatedProperty) -> Option<Transition> {
+        self.transitions
+            .iter()
+            .find(|(other, _)| *other == property)
+            .map(|(_, transition)| *transition)
+    }
+}
+
+const PROPERTIES: [AnimatedProperty; 3] = [
+    AnimatedProperty::Opacity,
+    AnimatedProperty::Offset,
+    AnimatedProperty::Size,
+];
+
+impl<V, State, Action> ViewMarker for Animated<V, State, Action> {}
+impl<V, State, Action> View<State, Action, ViewCtx> for Animated<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::Animated<Box<dyn masonry::Widget>>>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (child, child_state) = self.child.build(ctx);
+        let mut widget = widget::Animated::new_pod(child.inner.boxed())
+            .with_opacity(self.opacity)
+            .with_offset(self.offset);
+        for (property, transition) in &self.transitions {
+            widget = widget.with_transition(*property, *transition);
+        }
+        (ctx.new_pod(widget), child_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        // The transitions are set first, so that they apply to the changes below.
+        for property in PROPERTIES {
+            let transition = self.transition_of(property);
+            if transition != prev.transition_of(property) {
+                widget::Animated::set_transition(&mut element, property, transition);
+            }
+        }
+        if self.opacity != prev.opacity {
+            widget::Animated::set_opacity(&mut element, self.opacity);
+        }
+        if self.offset != prev.offset {
+            widget::Animated::set_offset(&mut element, self.offset);
+        }
+        let mut child = widget::Animated::child_mut(&mut element);
+        self.child
+            .rebuild(&prev.child, view_state, ctx, child.downcast());
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        let mut child = widget::Animated::child_mut(&mut element);
+        self.child.teardown(view_state, ctx, child.downcast());
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        self.child.message(view_state, id_path, message, app_state)
+    }
+}
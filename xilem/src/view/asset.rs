@@ -0,0 +1,236 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Views which load assets in the background.
+
+use std::sync::Arc;
+
+use masonry::assets::{
+    Asset, AssetError, AssetHandle, AssetKind, AssetPriority, AssetRequest, AssetSource,
+};
+use masonry::widget::{self, ObjectFit};
+use vello::peniko::{Blob, Format, Image as ImageBuf};
+
+use crate::core::{
+    DynMessage, MessageProxy, MessageResult, Mut, NoElement, View, ViewId, ViewMarker,
+    ViewPathTracker,
+};
+use crate::{Pod, ViewCtx};
+
+/// The message sent by the asset loader to an asset view.
+#[derive(Debug)]
+struct AssetLoaded {
+    /// Distinguishes the results of requests made before the view was rebuilt.
+    generation: u64,
+    result: Result<Asset, AssetError>,
+}
+
+/// The state of the request made by an asset view.
+pub struct AssetViewState {
+    handle: AssetHandle,
+    generation: u64,
+}
+
+impl AssetViewState {
+    fn load(request: &AssetRequest, generation: u64, ctx: &mut ViewCtx) -> Self {
+        let path: Arc<[ViewId]> = ctx.view_path().into();
+        let proxy = MessageProxy::new(ctx.proxy.clone(), path);
+        let handle = ctx.asset_loader().load(request.clone(), move |result| {
+            // If the view has been removed, the message is dropped.
+            drop(proxy.message(AssetLoaded { generation, result }));
+        });
+        Self { handle, generation }
+    }
+
+    /// Cancel the current request and make a new one.
+    fn reload(&mut self, request: &AssetRequest, ctx: &mut ViewCtx) {
+        self.handle.cancel();
+        *self = Self::load(request, self.generation + 1, ctx);
+    }
+
+    /// Returns the result of the current request, or `None` if `message` is stale.
+    fn take_result(&self, message: DynMessage) -> Option<Result<Asset, AssetError>> {
+        let loaded = message.downcast::<AssetLoaded>().unwrap();
+        (loaded.generation == self.generation).then_some(loaded.result)
+    }
+}
+
+/// Load an asset on the [asset loader](ViewCtx::asset_loader) of the app, and call
+/// `on_loaded` with the result.
+///
+/// The request is cancelled when the view is removed from the tree, and made again
+/// when `request` changes.
+pub fn load_asset<State, Action, F>(request: AssetRequest, on_loaded: F) -> LoadAsset<F>
+where
+    F: Fn(&mut State, Result<Asset, AssetError>) -> Action + 'static,
+{
+    LoadAsset { request, on_loaded }
+}
+
+/// The [`View`] created by [`load_asset`].
+pub struct LoadAsset<F> {
+    request: AssetRequest,
+    on_loaded: F,
+}
+
+impl<F> ViewMarker for LoadAsset<F> {}
+impl<State, Action, F> View<State, Action, ViewCtx> for LoadAsset<F>
+where
+    F: Fn(&mut State, Result<Asset, AssetError>) -> Action + 'static,
+{
+    type Element = NoElement;
+
+    type ViewState = AssetViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        (NoElement, AssetViewState::load(&self.request, 0, ctx))
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        (): Mut<Self::Element>,
+    ) {
+        if prev.request != self.request {
+            view_state.reload(&self.request, ctx);
+        }
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, _: &mut ViewCtx, _: Mut<Self::Element>) {
+        view_state.handle.cancel();
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        debug_assert!(
+            id_path.is_empty(),
+            "id path should be empty in LoadAsset::message"
+        );
+        match view_state.take_result(message) {
+            Some(result) => MessageResult::Action((self.on_loaded)(app_state, result)),
+            None => MessageResult::Nop,
+        }
+    }
+}
+
+/// Displays an image which is decoded in the background.
+///
+/// An empty image is shown until the image is loaded, or if it can't be loaded.
+///
+/// By default, the Image will scale to fit its box constraints ([`ObjectFit::Fill`]).
+/// To configure this, call [`fit`](ImageAsset::fit) on the returned value.
+///
+/// Corresponds to the [`Image`](widget::Image) widget.
+pub fn image_asset(source: impl Into<AssetSource>) -> ImageAsset {
+    ImageAsset {
+        request: AssetRequest::new(source, AssetKind::Image),
+        object_fit: ObjectFit::default(),
+    }
+}
+
+/// The [`View`] created by [`image_asset`].
+///
+/// See `image_asset`'s docs for more details.
+pub struct ImageAsset {
+    request: AssetRequest,
+    object_fit: ObjectFit,
+}
+
+impl ImageAsset {
+    /// Specify the object fit.
+    pub fn fit(mut self, fill: ObjectFit) -> Self {
+        self.object_fit = fill;
+        self
+    }
+
+    /// Specify the priority of the request, relative to other assets.
+    pub fn priority(mut self, priority: AssetPriority) -> Self {
+        self.request.priority = priority;
+        self
+    }
+}
+
+/// The state of an [`ImageAsset`] view.
+pub struct ImageAssetState {
+    asset: AssetViewState,
+    /// An image which was loaded since the last rebuild.
+    loaded: Option<ImageBuf>,
+}
+
+pub(super) fn placeholder_image() -> ImageBuf {
+    ImageBuf::new(Blob::new(Arc::new([0_u8; 4])), Format::Rgba8, 1, 1)
+}
+
+impl ViewMarker for ImageAsset {}
+impl<State, Action> View<State, Action, ViewCtx> for ImageAsset {
+    type Element = Pod<widget::Image>;
+    type ViewState = ImageAssetState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let widget = widget::Image::new(placeholder_image()).fit_mode(self.object_fit);
+        let view_state = ImageAssetState {
+            asset: AssetViewState::load(&self.request, 0, ctx),
+            loaded: None,
+        };
+        (ctx.new_pod(widget), view_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.object_fit != self.object_fit {
+            widget::Image::set_fit_mode(&mut element, self.object_fit);
+        }
+        if prev.request != self.request {
+            view_state.asset.reload(&self.request, ctx);
+            view_state.loaded = None;
+            widget::Image::set_image_data(&mut element, placeholder_image());
+        }
+        if let Some(image) = view_state.loaded.take() {
+            widget::Image::set_image_data(&mut element, image);
+        }
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, _: &mut ViewCtx, _: Mut<Self::Element>) {
+        view_state.asset.handle.cancel();
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        _: &mut State,
+    ) -> MessageResult<Action> {
+        debug_assert!(
+            id_path.is_empty(),
+            "id path should be empty in ImageAsset::message"
+        );
+        match view_state.asset.take_result(message) {
+            Some(Ok(Asset::Image(image))) => {
+                view_state.loaded = Some(image);
+                MessageResult::RequestRebuild
+            }
+            Some(Ok(asset)) => {
+                tracing::error!("ImageAsset received a non-image asset: {asset:?}");
+                MessageResult::Nop
+            }
+            Some(Err(err)) => {
+                tracing::warn!("Loading image {:?} failed: {err}", self.request.source);
+                MessageResult::Nop
+            }
+            None => MessageResult::Nop,
+        }
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+/// A view which dims `child` and shows a spinner above it while `busy` is true.
+///
+/// While busy, the child can't be interacted with. See [`Busy`](widget::Busy) for more details.
+pub fn busy<State, Action, V>(busy: bool, child: V) -> Busy<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+{
+    Busy {
+        child,
+        busy,
+        phantom: PhantomData,
+    }
+}
+
+/// The [`View`] created by [`busy`].
+pub struct Busy<V, State, Action> {
+    child: V,
+    busy: bool,
+    phantom: PhantomData<fn() -> (State, Action)>,
+}
+
+impl<V, State, Action> ViewMarker for Busy<V, State, Action> {}
+impl<V, State, Action> View<State, Action, ViewCtx> for Busy<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::Busy<Box<dyn masonry::Widget>>>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (child, child_state) = self.child.build(ctx);
+        let widget = widget::Busy::new_pod(child.inner.boxed()).busy(self.busy);
+        (ctx.new_pod(widget), child_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if self.busy != prev.busy {
+            widget::Busy::set_busy(&mut element, self.busy);
+        }
+        let mut child = widget::Busy::child_mut(&mut element);
+        self.child
+            .rebuild(&prev.child, view_state, ctx, child.downcast());
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        let mut child = widget::Busy::child_mut(&mut element);
+        self.child.teardown(view_state, ctx, child.downcast());
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        self.child.message(view_state, id_path, message, app_state)
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::text::ArcStr;
+use masonry::widget::{self, Series};
+
+use crate::core::{DynMessage, Mut, ViewMarker};
+use crate::{MessageResult, Pod, View, ViewCtx, ViewId};
+
+/// A chart showing each of `series` as columns, with one column per category.
+///
+/// The data is also exposed to screen readers, as a summary and a table.
+/// See [`ColumnChart`](widget::ColumnChart) for more details.
+pub fn column_chart(categories: Vec<ArcStr>, series: Vec<Series>) -> ColumnChart {
+    ColumnChart {
+        categories,
+        series,
+        label: None,
+    }
+}
+
+/// The [`View`] created by [`column_chart`].
+///
+/// See `column_chart`'s docs for more details.
+pub struct ColumnChart {
+    categories: Vec<ArcStr>,
+    series: Vec<Series>,
+    label: Option<ArcStr>,
+}
+
+impl ColumnChart {
+    /// Set the name of the chart read by screen readers.
+    pub fn label(mut self, label: impl Into<ArcStr>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+impl ViewMarker for ColumnChart {}
+impl<State, Action> View<State, Action, ViewCtx> for ColumnChart {
+    type Element = Pod<widget::ColumnChart>;
+    type ViewState = ();
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let mut widget = widget::ColumnChart::new(self.categories.clone(), self.series.clone());
+        if let Some(label) = &self.label {
+            widget = widget.with_label(label.clone());
+        }
+        (ctx.new_pod(widget), ())
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        (): &mut Self::ViewState,
+        _ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.categories != self.categories {
+            widget::ColumnChart::set_categories(&mut element, self.categories.clone());
+        }
+        if prev.series != self.series {
+            widget::ColumnChart::set_series(&mut element, self.series.clone());
+        }
+        if prev.label != self.label {
+            widget::ColumnChart::set_label(&mut element, self.label.clone());
+        }
+    }
+
+    fn teardown(&self, (): &mut Self::ViewState, _: &mut ViewCtx, _: Mut<Self::Element>) {}
+
+    fn message(
+        &self,
+        (): &mut Self::ViewState,
+        _id_path: &[ViewId],
+        message: DynMessage,
+        _app_state: &mut State,
+    ) -> MessageResult<Action> {
+        tracing::error!("Message arrived in ColumnChart::message, but ColumnChart doesn't consume any messages, this is a bug");
+        MessageResult::Stale(message)
+    }
+}
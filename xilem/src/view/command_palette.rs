@@ -0,0 +1,132 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::text::ArcStr;
+use masonry::widget::{self, Command};
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker, ViewPathTracker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+const CONTENT_VIEW_ID: ViewId = ViewId::new(0);
+
+/// A view which shows a searchable list of `commands` above `content`.
+///
+/// The palette is opened with <kbd>Ctrl</kbd>+<kbd>Shift</kbd>+<kbd>P</kbd>, and the user
+/// picks a command by typing part of its title and pressing Enter, or by clicking it.
+/// `on_command` is then called with the id of the command.
+///
+/// See [`CommandPalette`](widget::CommandPalette) for more details.
+pub fn command_palette<State, Action, V, F>(
+    content: V,
+    commands: Vec<Command>,
+    on_command: F,
+) -> CommandPalette<V, F>
+where
+    V: WidgetView<State, Action>,
+    F: Fn(&mut State, ArcStr) -> Action + Send + Sync + 'static,
+{
+    CommandPalette {
+        content,
+        commands,
+        on_command,
+    }
+}
+
+/// The [`View`] created by [`command_palette`].
+///
+/// See `command_palette`'s docs for more details.
+pub struct CommandPalette<V, F> {
+    content: V,
+    commands: Vec<Command>,
+    on_command: F,
+}
+
+impl<V, F> ViewMarker for CommandPalette<V, F> {}
+impl<V, F, State, Action> View<State, Action, ViewCtx> for CommandPalette<V, F>
+where
+    V: WidgetView<State, Action>,
+    F: Fn(&mut State, ArcStr) -> Action + Send + Sync + 'static,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::CommandPalette>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (content, content_state) = ctx.with_id(CONTENT_VIEW_ID, |ctx| self.content.build(ctx));
+        let pod = ctx.with_action_widget(|ctx| {
+            ctx.new_pod(widget::CommandPalette::new_pod(
+                content.inner.boxed(),
+                self.commands.iter().cloned(),
+            ))
+        });
+        (pod, content_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        content_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.commands != self.commands {
+            widget::CommandPalette::set_commands(&mut element, self.commands.iter().cloned());
+        }
+        ctx.with_id(CONTENT_VIEW_ID, |ctx| {
+            let mut content = widget::CommandPalette::content_mut(&mut element);
+            self.content
+                .rebuild(&prev.content, content_state, ctx, content.downcast());
+        });
+    }
+
+    fn teardown(
+        &self,
+        content_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CONTENT_VIEW_ID, |ctx| {
+            let mut content = widget::CommandPalette::content_mut(&mut element);
+            self.content
+                .teardown(content_state, ctx, content.downcast());
+        });
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        content_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        match id_path.split_first() {
+            Some((&CONTENT_VIEW_ID, rest)) => {
+                self.content
+                    .message(content_state, rest, message, app_state)
+            }
+            Some(_) => {
+                tracing::error!("Wrong id path in CommandPalette::message: {id_path:?}");
+                MessageResult::Stale(message)
+            }
+            None => match message.downcast::<masonry::Action>() {
+                Ok(action) => match *action {
+                    masonry::Action::CommandInvoked(id) => {
+                        MessageResult::Action((self.on_command)(app_state, id))
+                    }
+                    // The search text is internal to the palette.
+                    masonry::Action::TextChanged(_) => MessageResult::Nop,
+                    _ => {
+                        tracing::error!("Wrong action type in CommandPalette::message: {action:?}");
+                        MessageResult::Stale(action)
+                    }
+                },
+                Err(message) => {
+                    tracing::error!("Wrong message type in CommandPalette::message");
+                    MessageResult::Stale(message)
+                }
+            },
+        }
+    }
+}
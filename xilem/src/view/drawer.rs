@@ -0,0 +1,207 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::widget::{self, DrawerEdge};
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker, ViewPathTracker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+type DismissCallback<State, Action> = Box<dyn Fn(&mut State) -> Action + Send + Sync + 'static>;
+
+const CONTENT_VIEW_ID: ViewId = ViewId::new(0);
+const PANEL_VIEW_ID: ViewId = ViewId::new(1);
+
+/// A view which slides `panel` in from `edge`, above `content`, while it is [open](Drawer::open).
+///
+/// While the panel is open, the content is dimmed and can't be interacted with.
+/// The user can dismiss the panel by dragging it towards the edge, clicking on the content,
+/// or pressing Escape. It then slides out, and [`on_dismiss`](Drawer::on_dismiss) is called,
+/// which should usually close the panel in the app state.
+///
+/// See [`Drawer`](widget::Drawer) for more details.
+pub fn drawer<State, Action, C, P>(
+    edge: DrawerEdge,
+    content: C,
+    panel: P,
+) -> Drawer<C, P, State, Action>
+where
+    C: WidgetView<State, Action>,
+    P: WidgetView<State, Action>,
+{
+    Drawer {
+        edge,
+        content,
+        panel,
+        open: false,
+        extent: 250.,
+        max_extent: 250.,
+        on_dismiss: None,
+    }
+}
+
+/// A [`drawer`] whose panel slides in from the bottom edge.
+pub fn sheet<State, Action, C, P>(content: C, panel: P) -> Drawer<C, P, State, Action>
+where
+    C: WidgetView<State, Action>,
+    P: WidgetView<State, Action>,
+{
+    drawer(DrawerEdge::Bottom, content, panel)
+}
+
+/// The [`View`] created by [`drawer`] or [`sheet`].
+///
+/// See `drawer`'s docs for more details.
+pub struct Drawer<C, P, State, Action> {
+    edge: DrawerEdge,
+    content: C,
+    panel: P,
+    open: bool,
+    extent: f64,
+    max_extent: f64,
+    on_dismiss: Option<DismissCallback<State, Action>>,
+}
+
+impl<C, P, State, Action> Drawer<C, P, State, Action> {
+    /// Set whether the panel is open.
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Set the length of the open panel, along the axis it slides on.
+    ///
+    /// The default extent is `250.0`. This also raises the maximum extent if needed.
+    pub fn extent(mut self, extent: f64) -> Self {
+        self.extent = extent;
+        self.max_extent = self.max_extent.max(extent);
+        self
+    }
+
+    /// Set the length the panel can be resized to by dragging it.
+    pub fn max_extent(mut self, max_extent: f64) -> Self {
+        self.max_extent = max_extent.max(self.extent);
+        self
+    }
+
+    /// Call `on_dismiss` when the user dismisses the panel.
+    pub fn on_dismiss<F>(mut self, on_dismiss: F) -> Self
+    where
+        F: Fn(&mut State) -> Action + Send + Sync + 'static,
+    {
+        self.on_dismiss = Some(Box::new(on_dismiss));
+        self
+    }
+}
+
+impl<C, P, State, Action> ViewMarker for Drawer<C, P, State, Action> {}
+impl<C, P, State, Action> View<State, Action, ViewCtx> for Drawer<C, P, State, Action>
+where
+    C: WidgetView<State, Action>,
+    P: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::Drawer>;
+    type ViewState = (C::ViewState, P::ViewState);
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (content, content_state) = ctx.with_id(CONTENT_VIEW_ID, |ctx| self.content.build(ctx));
+        let (panel, panel_state) = ctx.with_id(PANEL_VIEW_ID, |ctx| self.panel.build(ctx));
+        let pod = ctx.with_action_widget(|ctx| {
+            ctx.new_pod(
+                widget::Drawer::new_pod(self.edge, content.inner.boxed(), panel.inner.boxed())
+                    .extent(self.extent)
+                    .max_extent(self.max_extent)
+                    .open(self.open),
+            )
+        });
+        (pod, (content_state, panel_state))
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        (content_state, panel_state): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.edge != self.edge {
+            widget::Drawer::set_edge(&mut element, self.edge);
+        }
+        if prev.extent != self.extent {
+            widget::Drawer::set_extent(&mut element, self.extent);
+        }
+        if prev.max_extent != self.max_extent {
+            widget::Drawer::set_max_extent(&mut element, self.max_extent);
+        }
+        if prev.open != self.open {
+            widget::Drawer::set_open(&mut element, self.open);
+        }
+        ctx.with_id(CONTENT_VIEW_ID, |ctx| {
+            let mut content = widget::Drawer::content_mut(&mut element);
+            self.content
+                .rebuild(&prev.content, content_state, ctx, content.downcast());
+        });
+        ctx.with_id(PANEL_VIEW_ID, |ctx| {
+            let mut panel = widget::Drawer::panel_mut(&mut element);
+            self.panel
+                .rebuild(&prev.panel, panel_state, ctx, panel.downcast());
+        });
+    }
+
+    fn teardown(
+        &self,
+        (content_state, panel_state): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CONTENT_VIEW_ID, |ctx| {
+            let mut content = widget::Drawer::content_mut(&mut element);
+            self.content
+                .teardown(content_state, ctx, content.downcast());
+        });
+        ctx.with_id(PANEL_VIEW_ID, |ctx| {
+            let mut panel = widget::Drawer::panel_mut(&mut element);
+            self.panel.teardown(panel_state, ctx, panel.downcast());
+        });
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        (content_state, panel_state): &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        match id_path.split_first() {
+            Some((&CONTENT_VIEW_ID, rest)) => {
+                self.content
+                    .message(content_state, rest, message, app_state)
+            }
+            Some((&PANEL_VIEW_ID, rest)) => {
+                self.panel.message(panel_state, rest, message, app_state)
+            }
+            Some(_) => {
+                tracing::error!("Wrong id path in Drawer::message: {id_path:?}");
+                MessageResult::Stale(message)
+            }
+            None => match message.downcast::<masonry::Action>() {
+                Ok(action) => match *action {
+                    masonry::Action::DrawerDismissed => match &self.on_dismiss {
+                        Some(on_dismiss) => MessageResult::Action(on_dismiss(app_state)),
+                        None => MessageResult::Nop,
+                    },
+                    _ => {
+                        tracing::error!("Wrong action type in Drawer::message: {action:?}");
+                        MessageResult::Stale(action)
+                    }
+                },
+                Err(message) => {
+                    tracing::error!("Wrong message type in Drawer::message");
+                    MessageResult::Stale(message)
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker, ViewPathTracker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+type ErrorCallback<State, Action> =
+    Box<dyn Fn(&mut State, String) -> Action + Send + Sync + 'static>;
+
+/// A view which catches panics in the widgets of `child`, so that they don't crash the app.
+///
+/// When a panic is caught, the child is replaced with an error message and a retry button.
+/// Pressing the button builds the child again from scratch.
+///
+/// See [`ErrorBoundary`](widget::ErrorBoundary) for more details.
+pub fn error_boundary<State, Action, V>(child: V) -> ErrorBoundary<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+{
+    ErrorBoundary {
+        child,
+        on_error: None,
+    }
+}
+
+/// The [`View`] created by [`error_boundary`].
+pub struct ErrorBoundary<V, State, Action> {
+    child: V,
+    on_error: Option<ErrorCallback<State, Action>>,
+}
+
+impl<V, State, Action> ErrorBoundary<V, State, Action> {
+    /// Call `on_error` with the panic message when a panic is caught in the child.
+    pub fn on_error<F>(mut self, on_error: F) -> Self
+    where
+        F: Fn(&mut State, String) -> Action + Send + Sync + 'static,
+    {
+        self.on_error = Some(Box::new(on_error));
+        self
+    }
+}
+
+impl<V, State, Action> ViewMarker for ErrorBoundary<V, State, Action> {}
+impl<V, State, Action> View<State, Action, ViewCtx> for ErrorBoundary<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::ErrorBoundary>;
+    /// The state of the child, the generation of the child, and whether it should be rebuilt.
+    ///
+    /// The generation is used as the child's id, so that messages for a replaced child are ignored.
+    type ViewState = (V::ViewState, u64, bool);
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (child, child_state) = ctx.with_id(ViewId::new(0), |ctx| self.child.build(ctx));
+        let pod = ctx.with_action_widget(|ctx| {
+            ctx.new_pod(widget::ErrorBoundary::new_pod(child.inner.boxed()))
+        });
+        (pod, (child_state, 0, false))
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        (child_state, generation, needs_new_child): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if std::mem::take(needs_new_child) {
+            // The previous child may have been left in an inconsistent state by its panic.
+            ctx.with_id(ViewId::new(*generation), |ctx| {
+                let mut child = widget::ErrorBoundary::child_mut(&mut element);
+                prev.child.teardown(child_state, ctx, child.downcast());
+            });
+            *generation += 1;
+            let (child, new_child_state) =
+                ctx.with_id(ViewId::new(*generation), |ctx| self.child.build(ctx));
+            *child_state = new_child_state;
+            widget::ErrorBoundary::set_child_pod(&mut element, child.inner.boxed());
+            return;
+        }
+        ctx.with_id(ViewId::new(*generation), |ctx| {
+            let mut child = widget::ErrorBoundary::child_mut(&mut element);
+            self.child
+                .rebuild(&prev.child, child_state, ctx, child.downcast());
+        });
+    }
+
+    fn teardown(
+        &self,
+        (child_state, generation, _): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(ViewId::new(*generation), |ctx| {
+            let mut child = widget::ErrorBoundary::child_mut(&mut element);
+            self.child.teardown(child_state, ctx, child.downcast());
+        });
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        (child_state, generation, needs_new_child): &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        match id_path.split_first() {
+            Some((id, rest)) if id.routing_id() == *generation => {
+                self.child.message(child_state, rest, message, app_state)
+            }
+            Some(_) => MessageResult::Stale(message),
+            None => match message.downcast::<masonry::Action>() {
+                Ok(action) => match *action {
+                    masonry::Action::ChildPanicked(panic_message) => match &self.on_error {
+                        Some(on_error) => MessageResult::Action(on_error(app_state, panic_message)),
+                        None => MessageResult::Nop,
+                    },
+                    masonry::Action::RetryRequested => {
+                        *needs_new_child = true;
+                        MessageResult::RequestRebuild
+                    }
+                    _ => {
+                        tracing::error!("Wrong action type in ErrorBoundary::message: {action:?}");
+                        MessageResult::Stale(action)
+                    }
+                },
+                Err(message) => {
+                    tracing::error!("Wrong message type in ErrorBoundary::message");
+                    MessageResult::Stale(message)
+                }
+            },
+        }
+    }
+}
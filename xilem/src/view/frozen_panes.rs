@@ -0,0 +1,177 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::widget::{self, Pane};
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker, ViewPathTracker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+const CORNER_VIEW_ID: ViewId = ViewId::new(0);
+const HEADER_VIEW_ID: ViewId = ViewId::new(1);
+const SIDE_VIEW_ID: ViewId = ViewId::new(2);
+const BODY_VIEW_ID: ViewId = ViewId::new(3);
+
+/// A view which scrolls `body`, with `header` above it and `side` to its left scrolling
+/// along with it, and `corner` always in place.
+///
+/// This keeps the header rows and leading columns of a table in view.
+/// Each pane must choose its own size, e.g. with a [`sized_box`](crate::view::sized_box).
+///
+/// See [`FrozenPanes`](widget::FrozenPanes) for more details.
+pub fn frozen_panes<State, Action, C, H, S, B>(
+    corner: C,
+    header: H,
+    side: S,
+    body: B,
+) -> FrozenPanes<C, H, S, B>
+where
+    C: WidgetView<State, Action>,
+    H: WidgetView<State, Action>,
+    S: WidgetView<State, Action>,
+    B: WidgetView<State, Action>,
+{
+    FrozenPanes {
+        corner,
+        header,
+        side,
+        body,
+        spacing: 0.,
+    }
+}
+
+/// The [`View`] created by [`frozen_panes`].
+///
+/// See `frozen_panes`'s docs for more details.
+pub struct FrozenPanes<C, H, S, B> {
+    corner: C,
+    header: H,
+    side: S,
+    body: B,
+    spacing: f64,
+}
+
+impl<C, H, S, B> FrozenPanes<C, H, S, B> {
+    /// Set the space between the frozen panes and the scrolling ones.
+    ///
+    /// The default spacing is `0.0`.
+    pub fn spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+}
+
+impl<C, H, S, B> ViewMarker for FrozenPanes<C, H, S, B> {}
+impl<C, H, S, B, State, Action> View<State, Action, ViewCtx> for FrozenPanes<C, H, S, B>
+where
+    C: WidgetView<State, Action>,
+    H: WidgetView<State, Action>,
+    S: WidgetView<State, Action>,
+    B: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::FrozenPanes>;
+    type ViewState = (C::ViewState, H::ViewState, S::ViewState, B::ViewState);
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (corner, corner_state) = ctx.with_id(CORNER_VIEW_ID, |ctx| self.corner.build(ctx));
+        let (header, header_state) = ctx.with_id(HEADER_VIEW_ID, |ctx| self.header.build(ctx));
+        let (side, side_state) = ctx.with_id(SIDE_VIEW_ID, |ctx| self.side.build(ctx));
+        let (body, body_state) = ctx.with_id(BODY_VIEW_ID, |ctx| self.body.build(ctx));
+        let pod = ctx.new_pod(
+            widget::FrozenPanes::new_pod(
+                corner.inner.boxed(),
+                header.inner.boxed(),
+                side.inner.boxed(),
+                body.inner.boxed(),
+            )
+            .with_spacing(self.spacing),
+        );
+        (pod, (corner_state, header_state, side_state, body_state))
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        (corner_state, header_state, side_state, body_state): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.spacing != self.spacing {
+            widget::FrozenPanes::set_spacing(&mut element, self.spacing);
+        }
+        ctx.with_id(CORNER_VIEW_ID, |ctx| {
+            let mut corner = widget::FrozenPanes::corner_mut(&mut element);
+            self.corner
+                .rebuild(&prev.corner, corner_state, ctx, corner.downcast());
+        });
+        ctx.with_id(HEADER_VIEW_ID, |ctx| {
+            let mut pane = widget::FrozenPanes::header_mut(&mut element);
+            let mut header = Pane::child_mut(&mut pane);
+            self.header
+                .rebuild(&prev.header, header_state, ctx, header.downcast());
+        });
+        ctx.with_id(SIDE_VIEW_ID, |ctx| {
+            let mut pane = widget::FrozenPanes::side_mut(&mut element);
+            let mut side = Pane::child_mut(&mut pane);
+            self.side
+                .rebuild(&prev.side, side_state, ctx, side.downcast());
+        });
+        ctx.with_id(BODY_VIEW_ID, |ctx| {
+            let mut pane = widget::FrozenPanes::body_mut(&mut element);
+            let mut body = Pane::child_mut(&mut pane);
+            self.body
+                .rebuild(&prev.body, body_state, ctx, body.downcast());
+        });
+    }
+
+    fn teardown(
+        &self,
+        (corner_state, header_state, side_state, body_state): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CORNER_VIEW_ID, |ctx| {
+            let mut corner = widget::FrozenPanes::corner_mut(&mut element);
+            self.corner.teardown(corner_state, ctx, corner.downcast());
+        });
+        ctx.with_id(HEADER_VIEW_ID, |ctx| {
+            let mut pane = widget::FrozenPanes::header_mut(&mut element);
+            let mut header = Pane::child_mut(&mut pane);
+            self.header.teardown(header_state, ctx, header.downcast());
+        });
+        ctx.with_id(SIDE_VIEW_ID, |ctx| {
+            let mut pane = widget::FrozenPanes::side_mut(&mut element);
+            let mut side = Pane::child_mut(&mut pane);
+            self.side.teardown(side_state, ctx, side.downcast());
+        });
+        ctx.with_id(BODY_VIEW_ID, |ctx| {
+            let mut pane = widget::FrozenPanes::body_mut(&mut element);
+            let mut body = Pane::child_mut(&mut pane);
+            self.body.teardown(body_state, ctx, body.downcast());
+        });
+    }
+
+    fn message(
+        &self,
+        (corner_state, header_state, side_state, body_state): &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        match id_path.split_first() {
+            Some((&CORNER_VIEW_ID, rest)) => {
+                self.corner.message(corner_state, rest, message, app_state)
+            }
+            Some((&HEADER_VIEW_ID, rest)) => {
+                self.header.message(header_state, rest, message, app_state)
+            }
+            Some((&SIDE_VIEW_ID, rest)) => self.side.message(side_state, rest, message, app_state),
+            Some((&BODY_VIEW_ID, rest)) => self.body.message(body_state, rest, message, app_state),
+            _ => {
+                tracing::error!("Wrong id path in FrozenPanes::message: {id_path:?}");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::gesture::Gesture;
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker, ViewPathTracker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+/// The id of the child view, which distinguishes its messages from the actions of the
+/// [`GestureDetector`](widget::GestureDetector) widget.
+const CHILD_VIEW_ID: ViewId = ViewId::new(0);
+
+/// A view which calls `on_gesture` with the taps, long presses, drags, pinches, rotations and pans performed
+/// on `child`.
+///
+/// Drags report their velocity, which makes it possible to implement interactions like
+/// swipe-to-dismiss or pull-down sheets. Positions are relative to the child.
+///
+/// Pointer events which the child handles itself, e.g. a click on a
+/// [`button`](crate::view::button), aren't part of any gesture.
+pub fn gesture_detector<State, Action, V, F>(child: V, on_gesture: F) -> GestureDetector<V, F>
+where
+    V: WidgetView<State, Action>,
+    F: Fn(&mut State, Gesture) -> Action + Send + Sync + 'static,
+{
+    GestureDetector { child, on_gesture }
+}
+
+/// The [`View`] created by [`gesture_detector`].
+///
+/// See `gesture_detector`'s docs for more details.
+pub struct GestureDetector<V, F> {
+    child: V,
+    on_gesture: F,
+}
+
+impl<V, F> ViewMarker for GestureDetector<V, F> {}
+impl<V, F, State, Action> View<State, Action, ViewCtx> for GestureDetector<V, F>
+where
+    V: WidgetView<State, Action>,
+    F: Fn(&mut State, Gesture) -> Action + Send + Sync + 'static,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::GestureDetector<Box<dyn masonry::Widget>>>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (child, child_state) = ctx.with_id(CHILD_VIEW_ID, |ctx| self.child.build(ctx));
+        let pod = ctx.with_action_widget(|ctx| {
+            ctx.new_pod(widget::GestureDetector::new_pod(child.inner.boxed()))
+        });
+        (pod, child_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            let mut child = widget::GestureDetector::child_mut(&mut element);
+            self.child
+                .rebuild(&prev.child, view_state, ctx, child.downcast());
+        });
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            let mut child = widget::GestureDetector::child_mut(&mut element);
+            self.child.teardown(view_state, ctx, child.downcast());
+        });
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        if let Some((first, rest)) = id_path.split_first() {
+            if *first != CHILD_VIEW_ID {
+                tracing::error!("Wrong id path in GestureDetector::message: {id_path:?}");
+                return MessageResult::Stale(message);
+            }
+            return self.child.message(view_state, rest, message, app_state);
+        }
+        match message.downcast::<masonry::Action>() {
+            Ok(action) => {
+                if let masonry::Action::Gesture(gesture, _) = *action {
+                    MessageResult::Action((self.on_gesture)(app_state, gesture))
+                } else {
+                    tracing::error!("Wrong action type in GestureDetector::message: {action:?}");
+                    MessageResult::Stale(action)
+                }
+            }
+            Err(message) => {
+                tracing::error!("Wrong message type in GestureDetector::message");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An image fetched over HTTP.
+
+use std::sync::Arc;
+
+use masonry::assets::{Asset, AssetKind};
+use masonry::widget::{self, ObjectFit};
+use tokio::task::JoinHandle;
+use vello::peniko::{Blob, Format, Image as ImageBuf};
+
+use super::asset::placeholder_image;
+use crate::core::{
+    DynMessage, MessageProxy, MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker,
+};
+use crate::fetch::FetchError;
+use crate::{Pod, ViewCtx};
+
+/// The message sent when the image of an [`ImageUrl`] view has been fetched.
+#[derive(Debug)]
+struct UrlFetched {
+    /// Distinguishes the results of requests made before the url changed.
+    generation: u64,
+    result: Result<Asset, FetchError>,
+}
+
+/// Displays the image at `url`, fetched with the [fetcher](ViewCtx::fetcher) of the app.
+///
+/// Images are decoded in the background, and cached, so that showing the same url again
+/// doesn't make a new request. Failed requests are retried as configured on the
+/// [`Fetcher`](crate::fetch::Fetcher).
+///
+/// An empty image is shown while the image is loading, and a dark red image if it can't
+/// be loaded. These can be changed with [`placeholder`](ImageUrl::placeholder) and
+/// [`error_image`](ImageUrl::error_image).
+///
+/// By default, the Image will scale to fit its box constraints ([`ObjectFit::Fill`]).
+/// To configure this, call [`fit`](ImageUrl::fit) on the returned value.
+///
+/// Corresponds to the [`Image`](widget::Image) widget.
+pub fn image_url(url: impl Into<String>) -> ImageUrl {
+    ImageUrl {
+        url: url.into(),
+        object_fit: ObjectFit::default(),
+        placeholder: None,
+        error_image: None,
+    }
+}
+
+/// The [`View`] created by [`image_url`].
+///
+/// See `image_url`'s docs for more details.
+pub struct ImageUrl {
+    url: String,
+    object_fit: ObjectFit,
+    placeholder: Option<ImageBuf>,
+    error_image: Option<ImageBuf>,
+}
+
+impl ImageUrl {
+    /// Specify the object fit.
+    pub fn fit(mut self, fill: ObjectFit) -> Self {
+        self.object_fit = fill;
+        self
+    }
+
+    /// Specify the image shown while the image is loading.
+    pub fn placeholder(mut self, image: &ImageBuf) -> Self {
+        self.placeholder = Some(image.clone());
+        self
+    }
+
+    /// Specify the image shown if the image can't be loaded.
+    pub fn error_image(mut self, image: &ImageBuf) -> Self {
+        self.error_image = Some(image.clone());
+        self
+    }
+
+    fn error_image_or_default(&self) -> ImageBuf {
+        self.error_image.clone().unwrap_or_else(|| {
+            ImageBuf::new(
+                Blob::new(Arc::new([0x60_u8, 0x10, 0x10, 0xff])),
+                Format::Rgba8,
+                1,
+                1,
+            )
+        })
+    }
+
+    /// The image to show before the request is answered.
+    fn initial_image(&self, ctx: &ViewCtx) -> ImageBuf {
+        match ctx.fetcher().cached(&self.url, AssetKind::Image) {
+            Some(Asset::Image(image)) => image,
+            _ => self.placeholder.clone().unwrap_or_else(placeholder_image),
+        }
+    }
+}
+
+/// The state of an [`ImageUrl`] view.
+pub struct ImageUrlState {
+    task: JoinHandle<()>,
+    generation: u64,
+    /// An image which was loaded since the last rebuild.
+    loaded: Option<ImageBuf>,
+}
+
+impl ImageUrlState {
+    fn fetch(url: &str, generation: u64, ctx: &mut ViewCtx) -> Self {
+        let path: Arc<[ViewId]> = ctx.view_path().into();
+        let proxy = MessageProxy::new(ctx.proxy.clone(), path);
+        let fetcher = ctx.fetcher.clone();
+        let loader = ctx.asset_loader.clone();
+        let url = url.to_string();
+        let task = ctx.runtime().spawn(async move {
+            let result = fetcher.fetch_asset(&url, AssetKind::Image, &loader).await;
+            drop(proxy.message(UrlFetched { generation, result }));
+        });
+        Self {
+            task,
+            generation,
+            loaded: None,
+        }
+    }
+}
+
+impl ViewMarker for ImageUrl {}
+impl<State, Action> View<State, Action, ViewCtx> for ImageUrl {
+    type Element = Pod<widget::Image>;
+    type ViewState = ImageUrlState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let widget = widget::Image::new(self.initial_image(ctx)).fit_mode(self.object_fit);
+        let view_state = ImageUrlState::fetch(&self.url, 0, ctx);
+        (ctx.new_pod(widget), view_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.object_fit != self.object_fit {
+            widget::Image::set_fit_mode(&mut element, self.object_fit);
+        }
+        if prev.url != self.url {
+            view_state.task.abort();
+            *view_state = ImageUrlState::fetch(&self.url, view_state.generation + 1, ctx);
+            widget::Image::set_image_data(&mut element, self.initial_image(ctx));
+        }
+        if let Some(image) = view_state.loaded.take() {
+            widget::Image::set_image_data(&mut element, image);
+        }
+    }
+
+    fn teardown(&self, view_state: &mut Self::ViewState, _: &mut ViewCtx, _: Mut<Self::Element>) {
+        view_state.task.abort();
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        _: &mut State,
+    ) -> MessageResult<Action> {
+        debug_assert!(
+            id_path.is_empty(),
+            "id path should be empty in ImageUrl::message"
+        );
+        let fetched = message.downcast::<UrlFetched>().unwrap();
+        if fetched.generation != view_state.generation {
+            return MessageResult::Nop;
+        }
+        view_state.loaded = Some(match fetched.result {
+            Ok(Asset::Image(image)) => image,
+            Ok(asset) => {
+                tracing::error!("ImageUrl received a non-image asset: {asset:?}");
+                self.error_image_or_default()
+            }
+            Err(err) => {
+                tracing::warn!("Loading image from {} failed: {err}", self.url);
+                self.error_image_or_default()
+            }
+        });
+        MessageResult::RequestRebuild
+    }
+}
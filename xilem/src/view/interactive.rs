@@ -0,0 +1,227 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::clipboard::ClipboardContent;
+use masonry::widget;
+use masonry::PointerButton;
+use winit::keyboard::{Key, ModifiersState};
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker, ViewPathTracker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+type ClickCallback<State, Action> =
+    Box<dyn Fn(&mut State, PointerButton) -> Action + Send + Sync + 'static>;
+type HoverCallback<State, Action> = Box<dyn Fn(&mut State, bool) -> Action + Send + Sync + 'static>;
+type KeyCallback<State, Action> =
+    Box<dyn Fn(&mut State, &Key, ModifiersState) -> Action + Send + Sync + 'static>;
+type PasteCallback<State, Action> =
+    Box<dyn Fn(&mut State, String) -> Action + Send + Sync + 'static>;
+
+/// The id of the child view, which distinguishes its messages from the actions of the
+/// [`Interactive`](widget::Interactive) widget.
+const CHILD_VIEW_ID: ViewId = ViewId::new(0);
+
+/// A view which adds click, hover, key and paste handlers to `child`.
+///
+/// This is usually created with [`WidgetView::on_click`], [`WidgetView::on_hover`],
+/// [`WidgetView::on_key`] or [`WidgetView::on_paste`]. Calling more of these methods on
+/// the result adds handlers to the same view.
+///
+/// Events which the child handles itself, e.g. a click on a [`button`](crate::view::button),
+/// don't call the handlers.
+pub fn interactive<State, Action, V>(child: V) -> Interactive<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+{
+    Interactive {
+        child,
+        on_click: None,
+        on_hover: None,
+        on_key: None,
+        on_paste: None,
+        copy_content: None,
+    }
+}
+
+/// The [`View`] created by [`interactive`].
+///
+/// See `interactive`'s docs for more details.
+pub struct Interactive<V, State, Action> {
+    child: V,
+    on_click: Option<ClickCallback<State, Action>>,
+    on_hover: Option<HoverCallback<State, Action>>,
+    on_key: Option<KeyCallback<State, Action>>,
+    on_paste: Option<PasteCallback<State, Action>>,
+    copy_content: Option<ClipboardContent>,
+}
+
+impl<V, State, Action> Interactive<V, State, Action> {
+    /// Call `on_click` when the child is clicked.
+    pub fn on_click<F>(mut self, on_click: F) -> Self
+    where
+        F: Fn(&mut State, PointerButton) -> Action + Send + Sync + 'static,
+    {
+        self.on_click = Some(Box::new(on_click));
+        self
+    }
+
+    /// Call `on_hover` when the pointer enters (`true`) or leaves (`false`) the child.
+    pub fn on_hover<F>(mut self, on_hover: F) -> Self
+    where
+        F: Fn(&mut State, bool) -> Action + Send + Sync + 'static,
+    {
+        self.on_hover = Some(Box::new(on_hover));
+        self
+    }
+
+    /// Call `on_key` when a key is pressed while the view has focus.
+    ///
+    /// This also makes the view focusable, so that children which don't accept
+    /// focus can still be used with the keyboard. Tab still moves the focus.
+    pub fn on_key<F>(mut self, on_key: F) -> Self
+    where
+        F: Fn(&mut State, &Key, ModifiersState) -> Action + Send + Sync + 'static,
+    {
+        self.on_key = Some(Box::new(on_key));
+        self
+    }
+
+    /// Call `on_paste` with the text of the clipboard when Ctrl+V (Cmd+V on macOS) is
+    /// pressed while the view has focus.
+    ///
+    /// Like `on_key`, this makes the view focusable.
+    pub fn on_paste<F>(mut self, on_paste: F) -> Self
+    where
+        F: Fn(&mut State, String) -> Action + Send + Sync + 'static,
+    {
+        self.on_paste = Some(Box::new(on_paste));
+        self
+    }
+
+    /// Copy `content` to the clipboard when Ctrl+C (Cmd+C on macOS) is pressed while the
+    /// view has focus. With `None`, nothing is copied.
+    ///
+    /// Like `on_key`, this makes the view focusable, unless `content` is `None` when the
+    /// view is first built.
+    pub fn copy_content(mut self, content: Option<ClipboardContent>) -> Self {
+        self.copy_content = content;
+        self
+    }
+}
+
+impl<V, State, Action> ViewMarker for Interactive<V, State, Action> {}
+impl<V, State, Action> View<State, Action, ViewCtx> for Interactive<V, State, Action>
+where
+    V: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::Interactive<Box<dyn masonry::Widget>>>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (child, child_state) = ctx.with_id(CHILD_VIEW_ID, |ctx| self.child.build(ctx));
+        let pod = ctx.with_action_widget(|ctx| {
+            let mut widget = widget::Interactive::new_pod(child.inner.boxed());
+            if self.on_click.is_some() {
+                widget = widget.on_click();
+            }
+            if self.on_hover.is_some() {
+                widget = widget.on_hover();
+            }
+            if self.on_key.is_some() {
+                widget = widget.on_key();
+            }
+            if self.on_paste.is_some() {
+                widget = widget.on_paste();
+            }
+            if let Some(content) = &self.copy_content {
+                widget = widget.with_copy_content(content.clone());
+            }
+            ctx.new_pod(widget)
+        });
+        (pod, child_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if self.on_click.is_some() != prev.on_click.is_some() {
+            widget::Interactive::set_on_click(&mut element, self.on_click.is_some());
+        }
+        if self.on_hover.is_some() != prev.on_hover.is_some() {
+            widget::Interactive::set_on_hover(&mut element, self.on_hover.is_some());
+        }
+        if self.on_key.is_some() != prev.on_key.is_some() {
+            widget::Interactive::set_on_key(&mut element, self.on_key.is_some());
+        }
+        if self.on_paste.is_some() != prev.on_paste.is_some() {
+            widget::Interactive::set_on_paste(&mut element, self.on_paste.is_some());
+        }
+        if self.copy_content != prev.copy_content {
+            widget::Interactive::set_copy_content(&mut element, self.copy_content.clone());
+        }
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            let mut child = widget::Interactive::child_mut(&mut element);
+            self.child
+                .rebuild(&prev.child, view_state, ctx, child.downcast());
+        });
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            let mut child = widget::Interactive::child_mut(&mut element);
+            self.child.teardown(view_state, ctx, child.downcast());
+        });
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        if let Some((first, rest)) = id_path.split_first() {
+            if *first != CHILD_VIEW_ID {
+                tracing::error!("Wrong id path in Interactive::message: {id_path:?}");
+                return MessageResult::Stale(message);
+            }
+            return self.child.message(view_state, rest, message, app_state);
+        }
+        match message.downcast::<masonry::Action>() {
+            Ok(action) => match *action {
+                masonry::Action::Clicked(button, _) if self.on_click.is_some() => {
+                    MessageResult::Action((self.on_click.as_ref().unwrap())(app_state, button))
+                }
+                masonry::Action::HoverChanged(hovered, _) if self.on_hover.is_some() => {
+                    MessageResult::Action((self.on_hover.as_ref().unwrap())(app_state, hovered))
+                }
+                masonry::Action::KeyPressed(ref key, mods, _) if self.on_key.is_some() => {
+                    MessageResult::Action((self.on_key.as_ref().unwrap())(app_state, key, mods))
+                }
+                masonry::Action::Pasted(text, _) if self.on_paste.is_some() => {
+                    MessageResult::Action((self.on_paste.as_ref().unwrap())(app_state, text))
+                }
+                _ => {
+                    tracing::error!("Wrong action type in Interactive::message: {action:?}");
+                    MessageResult::Stale(action)
+                }
+            },
+            Err(message) => {
+                tracing::error!("Wrong message type in Interactive::message");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::text::ArcStr;
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, ViewId};
+
+type PressCallback<State, Action> = Box<dyn Fn(&mut State) -> Action + Send + Sync + 'static>;
+type SelectCallback<State, Action> =
+    Box<dyn Fn(&mut State, usize) -> Action + Send + Sync + 'static>;
+
+/// A button which opens a menu of `items`, and calls `on_select` with the index of the
+/// chosen item.
+///
+/// See [`MenuButton`](widget::MenuButton) for more details.
+pub fn menu_button<State, Action>(
+    label: impl Into<ArcStr>,
+    items: impl IntoIterator<Item = impl Into<ArcStr>>,
+    on_select: impl Fn(&mut State, usize) -> Action + Send + Sync + 'static,
+) -> MenuButton<State, Action> {
+    MenuButton {
+        label: label.into(),
+        items: items.into_iter().map(Into::into).collect(),
+        on_press: None,
+        on_select: Box::new(on_select),
+    }
+}
+
+/// A split button, which calls `on_press` when its main part is pressed, and whose
+/// chevron opens a menu of `items`. `on_select` is called with the index of the chosen item.
+///
+/// See [`MenuButton`](widget::MenuButton) for more details.
+pub fn split_button<State, Action>(
+    label: impl Into<ArcStr>,
+    items: impl IntoIterator<Item = impl Into<ArcStr>>,
+    on_press: impl Fn(&mut State) -> Action + Send + Sync + 'static,
+    on_select: impl Fn(&mut State, usize) -> Action + Send + Sync + 'static,
+) -> MenuButton<State, Action> {
+    MenuButton {
+        on_press: Some(Box::new(on_press)),
+        ..menu_button(label, items, on_select)
+    }
+}
+
+/// The [`View`] created by [`menu_button`] or [`split_button`].
+pub struct MenuButton<State, Action> {
+    label: ArcStr,
+    items: Vec<ArcStr>,
+    on_press: Option<PressCallback<State, Action>>,
+    on_select: SelectCallback<State, Action>,
+}
+
+impl<State, Action> ViewMarker for MenuButton<State, Action> {}
+impl<State: 'static, Action: 'static> View<State, Action, ViewCtx> for MenuButton<State, Action> {
+    type Element = Pod<widget::MenuButton>;
+    type ViewState = ();
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_leaf_action_widget(|ctx| {
+            let items = self.items.iter().cloned();
+            ctx.new_pod(if self.on_press.is_some() {
+                widget::MenuButton::split(self.label.clone(), items)
+            } else {
+                widget::MenuButton::new(self.label.clone(), items)
+            })
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        _: &mut Self::ViewState,
+        _ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.label != self.label {
+            widget::MenuButton::set_text(&mut element, self.label.clone());
+        }
+        if prev.items != self.items {
+            widget::MenuButton::set_items(&mut element, self.items.iter().cloned());
+        }
+        if prev.on_press.is_some() != self.on_press.is_some() {
+            widget::MenuButton::set_split(&mut element, self.on_press.is_some());
+        }
+    }
+
+    fn teardown(&self, _: &mut Self::ViewState, ctx: &mut ViewCtx, element: Mut<Self::Element>) {
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        _: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        debug_assert!(
+            id_path.is_empty(),
+            "id path should be empty in MenuButton::message"
+        );
+        match message.downcast::<masonry::Action>() {
+            Ok(action) => match *action {
+                masonry::Action::MenuItemSelected(index) => {
+                    MessageResult::Action((self.on_select)(app_state, index))
+                }
+                masonry::Action::ButtonPressed(masonry::PointerButton::Primary) => {
+                    match &self.on_press {
+                        Some(on_press) => MessageResult::Action(on_press(app_state)),
+                        None => MessageResult::Nop,
+                    }
+                }
+                masonry::Action::ButtonPressed(_) => MessageResult::Nop,
+                _ => {
+                    tracing::error!("Wrong action type in MenuButton::message: {action:?}");
+                    MessageResult::Stale(action)
+                }
+            },
+            Err(message) => {
+                tracing::error!("Wrong message type in MenuButton::message: {message:?}");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
@@ -9,18 +9,48 @@ pub use task::*;
 mod worker;
 pub use worker::*;
 
+mod accessibility_hidden;
+pub use accessibility_hidden::*;
+
+mod animated;
+pub use animated::*;
+
+mod busy;
+pub use busy::*;
+
 mod button;
 pub use button::*;
 
 mod checkbox;
 pub use checkbox::*;
 
+mod column_chart;
+pub use column_chart::*;
+
+mod menu_button;
+pub use menu_button::*;
+
+mod command_palette;
+pub use command_palette::*;
+
+mod drawer;
+pub use drawer::*;
+
+mod error_boundary;
+pub use error_boundary::*;
+
 mod flex;
 pub use flex::*;
 
 mod grid;
 pub use grid::*;
 
+mod frozen_panes;
+pub use frozen_panes::*;
+
+mod table;
+pub use table::*;
+
 mod sized_box;
 pub use sized_box::*;
 
@@ -30,18 +60,53 @@ pub use spinner::*;
 mod image;
 pub use image::*;
 
+mod interactive;
+pub use interactive::*;
+
+mod gesture_detector;
+pub use gesture_detector::*;
+
+mod asset;
+pub use asset::*;
+
+#[cfg(feature = "http")]
+mod image_url;
+#[cfg(feature = "http")]
+pub use image_url::*;
+
 mod label;
 pub use label::*;
 
 mod variable_label;
 pub use variable_label::*;
 
+mod prebuild;
+pub use prebuild::*;
+
 mod progress_bar;
 pub use progress_bar::*;
 
+mod rate_limit;
+pub use rate_limit::*;
+
+mod rating;
+pub use rating::*;
+
+mod reads;
+pub use reads::*;
+
+mod rubber_band;
+pub use rubber_band::*;
+
+mod segmented_control;
+pub use segmented_control::*;
+
 mod prose;
 pub use prose::*;
 
+mod tag_input;
+pub use tag_input::*;
+
 mod textbox;
 pub use textbox::*;
 
@@ -3,7 +3,8 @@
 
 use std::marker::PhantomData;
 
-use masonry::widget;
+use masonry::animation::Transition;
+use masonry::widget::{self, ScrollBarMode, ScrollBarStyle, ScrollSnap};
 
 use crate::core::{DynMessage, Mut, ViewMarker};
 use crate::{MessageResult, Pod, View, ViewCtx, ViewId, WidgetView};
@@ -17,15 +18,51 @@ where
 {
     Portal {
         child,
+        scroll_transition: Some(widget::DEFAULT_SCROLL_TRANSITION),
+        scrollbar_style: None,
+        scrollbar_mode: ScrollBarMode::default(),
+        scroll_snap: None,
         phantom: PhantomData,
     }
 }
 
 pub struct Portal<V, State, Action> {
     child: V,
+    scroll_transition: Option<Transition>,
+    scrollbar_style: Option<ScrollBarStyle>,
+    scrollbar_mode: ScrollBarMode,
+    scroll_snap: Option<ScrollSnap>,
     phantom: PhantomData<(State, Action)>,
 }
 
+impl<V, State, Action> Portal<V, State, Action> {
+    /// Set how the content moves after a wheel event, or `None` to make it jump.
+    ///
+    /// The default is [`DEFAULT_SCROLL_TRANSITION`](widget::DEFAULT_SCROLL_TRANSITION).
+    pub fn scroll_transition(mut self, transition: Option<Transition>) -> Self {
+        self.scroll_transition = transition;
+        self
+    }
+
+    /// Set how the scrollbars look, instead of using the theme values of the env.
+    pub fn scrollbar_style(mut self, style: ScrollBarStyle) -> Self {
+        self.scrollbar_style = Some(style);
+        self
+    }
+
+    /// Set when the scrollbars are shown, see [`ScrollBarMode`].
+    pub fn scrollbar_mode(mut self, mode: ScrollBarMode) -> Self {
+        self.scrollbar_mode = mode;
+        self
+    }
+
+    /// Make the viewport settle on snap points after it's scrolled, see [`ScrollSnap`].
+    pub fn scroll_snap(mut self, snap: ScrollSnap) -> Self {
+        self.scroll_snap = Some(snap);
+        self
+    }
+}
+
 impl<V, State, Action> ViewMarker for Portal<V, State, Action> {}
 impl<Child, State, Action> View<State, Action, ViewCtx> for Portal<Child, State, Action>
 where
@@ -40,7 +77,16 @@ where
         // The Portal `View` doesn't get any messages directly (yet - scroll events?), so doesn't need to
         // use ctx.with_id.
         let (child, child_state) = self.child.build(ctx);
-        let widget_pod = ctx.new_pod(widget::Portal::new_pod(child.inner));
+        let mut widget = widget::Portal::new_pod(child.inner)
+            .with_scroll_transition(self.scroll_transition)
+            .with_scrollbar_mode(self.scrollbar_mode);
+        if let Some(style) = self.scrollbar_style {
+            widget = widget.with_scrollbar_style(style);
+        }
+        if let Some(snap) = &self.scroll_snap {
+            widget = widget.with_scroll_snap(snap.clone());
+        }
+        let widget_pod = ctx.new_pod(widget);
         (widget_pod, child_state)
     }
 
@@ -51,6 +97,21 @@ where
         ctx: &mut ViewCtx,
         mut element: Mut<Self::Element>,
     ) {
+        if self.scroll_transition != prev.scroll_transition {
+            widget::Portal::set_scroll_transition(&mut element, self.scroll_transition);
+        }
+        if self.scrollbar_style != prev.scrollbar_style {
+            match self.scrollbar_style {
+                Some(style) => widget::Portal::set_scrollbar_style(&mut element, style),
+                None => widget::Portal::reset_scrollbar_style(&mut element),
+            }
+        }
+        if self.scrollbar_mode != prev.scrollbar_mode {
+            widget::Portal::set_scrollbar_mode(&mut element, self.scrollbar_mode);
+        }
+        if self.scroll_snap != prev.scroll_snap {
+            widget::Portal::set_scroll_snap(&mut element, self.scroll_snap.clone());
+        }
         let child_element = widget::Portal::child_mut(&mut element);
         self.child
             .rebuild(&prev.child, view_state, ctx, child_element);
@@ -0,0 +1,187 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+/// The id of the placeholder view.
+const PLACEHOLDER_VIEW_ID: ViewId = ViewId::new(0);
+/// The id of the child view.
+const CHILD_VIEW_ID: ViewId = ViewId::new(1);
+/// Show `placeholder` first, and build `child` in a later rebuild, once a frame showing
+/// the placeholder has been presented.
+///
+/// This is meant for heavy screens, whose widgets take long enough to build that
+/// navigating to them would miss frames. The navigation shows the placeholder
+/// immediately instead, and the screen replaces it once built. See
+/// [`Prebuilt`](widget::Prebuilt) for more details.
+///
+/// Widgets have to be built on the UI thread, but the data they show can be prepared
+/// on a worker, with a [`task`](crate::view::task) or [`worker`](crate::view::worker)
+/// storing it in the app state. Until it's ready, the app logic can return the
+/// placeholder by itself.
+///
+/// # Examples
+/// ```
+/// use xilem::view::{label, prebuild, spinner, flex};
+/// use xilem::WidgetView;
+///
+/// # fn view(rows: &[String]) -> impl WidgetView<()> {
+/// prebuild(
+///     spinner(),
+///     flex(rows.iter().map(|row| label(row.clone())).collect::<Vec<_>>()),
+/// )
+/// # }
+/// ```
+pub fn prebuild<State, Action, P, V>(placeholder: P, child: V) -> Prebuild<P, V, State, Action>
+where
+    P: WidgetView<State, Action>,
+    V: WidgetView<State, Action>,
+{
+    Prebuild {
+        placeholder,
+        child,
+        phantom: PhantomData,
+    }
+}
+
+/// The [`View`] created by [`prebuild`].
+///
+/// See `prebuild`'s docs for more details.
+pub struct Prebuild<P, V, State, Action = ()> {
+    placeholder: P,
+    child: V,
+    phantom: PhantomData<fn() -> (State, Action)>,
+}
+
+#[doc(hidden)] // Implementation detail, public because of trait visibility rules
+pub struct PrebuildState<P, S> {
+    placeholder: P,
+    /// The state of the child, once it's built.
+    child: Option<S>,
+    /// Whether the child should be built in the next rebuild.
+    ready: bool,
+}
+
+impl<P, V, State, Action> ViewMarker for Prebuild<P, V, State, Action> {}
+impl<P, V, State, Action> View<State, Action, ViewCtx> for Prebuild<P, V, State, Action>
+where
+    P: WidgetView<State, Action>,
+    V: WidgetView<State, Action>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::Prebuilt<Box<dyn masonry::Widget>>>;
+    type ViewState = PrebuildState<P::ViewState, V::ViewState>;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (placeholder, placeholder_state) =
+            ctx.with_id(PLACEHOLDER_VIEW_ID, |ctx| self.placeholder.build(ctx));
+        let pod = ctx.with_action_widget(|ctx| {
+            ctx.new_pod(widget::Prebuilt::new_pod(placeholder.inner.boxed()))
+        });
+        let state = PrebuildState {
+            placeholder: placeholder_state,
+            child: None,
+            ready: false,
+        };
+        (pod, state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(PLACEHOLDER_VIEW_ID, |ctx| {
+            let mut placeholder = widget::Prebuilt::placeholder_mut(&mut element);
+            self.placeholder.rebuild(
+                &prev.placeholder,
+                &mut view_state.placeholder,
+                ctx,
+                placeholder.downcast(),
+            );
+        });
+        ctx.with_id(CHILD_VIEW_ID, |ctx| match &mut view_state.child {
+            Some(child_state) => {
+                let mut child = widget::Prebuilt::content_mut(&mut element)
+                    .expect("Prebuild child was built, so its widget was attached");
+                self.child
+                    .rebuild(&prev.child, child_state, ctx, child.downcast());
+            }
+            None if view_state.ready => {
+                let (child, child_state) = self.child.build(ctx);
+                widget::Prebuilt::set_content_pod(&mut element, child.inner.boxed());
+                view_state.child = Some(child_state);
+            }
+            None => {}
+        });
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(PLACEHOLDER_VIEW_ID, |ctx| {
+            let mut placeholder = widget::Prebuilt::placeholder_mut(&mut element);
+            self.placeholder
+                .teardown(&mut view_state.placeholder, ctx, placeholder.downcast());
+        });
+        if let Some(child_state) = &mut view_state.child {
+            ctx.with_id(CHILD_VIEW_ID, |ctx| {
+                let mut child = widget::Prebuilt::content_mut(&mut element)
+                    .expect("Prebuild child was built, so its widget was attached");
+                self.child.teardown(child_state, ctx, child.downcast());
+            });
+        }
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        match id_path.split_first() {
+            Some((&PLACEHOLDER_VIEW_ID, rest)) => {
+                self.placeholder
+                    .message(&mut view_state.placeholder, rest, message, app_state)
+            }
+            Some((&CHILD_VIEW_ID, rest)) => match &mut view_state.child {
+                Some(child_state) => self.child.message(child_state, rest, message, app_state),
+                None => MessageResult::Stale(message),
+            },
+            Some(_) => {
+                tracing::error!("Wrong id path in Prebuild::message: {id_path:?}");
+                MessageResult::Stale(message)
+            }
+            None => match message.downcast::<masonry::Action>() {
+                Ok(action) => match *action {
+                    masonry::Action::PlaceholderPresented if view_state.child.is_none() => {
+                        view_state.ready = true;
+                        MessageResult::RequestRebuild
+                    }
+                    masonry::Action::PlaceholderPresented => MessageResult::Nop,
+                    _ => {
+                        tracing::error!("Wrong action type in Prebuild::message: {action:?}");
+                        MessageResult::Stale(action)
+                    }
+                },
+                Err(message) => {
+                    tracing::error!("Wrong message type in Prebuild::message");
+                    MessageResult::Stale(message)
+                }
+            },
+        }
+    }
+}
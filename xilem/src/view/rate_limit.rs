@@ -0,0 +1,208 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+use crate::core::{
+    DynMessage, MessageProxy, MessageResult, Mut, View, ViewId, ViewMarker, ViewPathTracker,
+};
+use crate::ViewCtx;
+
+/// The id of the child view.
+const CHILD_VIEW_ID: ViewId = ViewId::new(0);
+/// The id the timer messages are sent to, which distinguishes them from the child's messages.
+const TIMER_VIEW_ID: ViewId = ViewId::new(1);
+
+/// Only handle the last message of `child` in each burst, once it has sent no message
+/// for `duration`.
+///
+/// This is usually created with [`WidgetView::debounce`](crate::WidgetView::debounce).
+/// It's meant for views whose events carry their full value, e.g. the changes of a
+/// [`textbox`](crate::view::textbox), so that expensive work downstream only runs once
+/// the user pauses. The handler of the child is called when the message is delivered,
+/// so the app state isn't updated in the meantime.
+///
+/// All the messages of the child are delayed, and those followed by another message
+/// within `duration` are dropped.
+pub fn debounce<V>(child: V, duration: Duration) -> RateLimit<V> {
+    RateLimit {
+        child,
+        duration,
+        mode: Mode::Debounce,
+    }
+}
+
+/// Handle at most one message of `child` every `duration`.
+///
+/// This is usually created with [`WidgetView::throttle`](crate::WidgetView::throttle).
+/// The first message is handled right away, and the messages sent within `duration`
+/// after it are dropped, except for the last one, which is handled at the end of that
+/// window. This keeps the state following e.g. a slider drag, at a bounded rate.
+pub fn throttle<V>(child: V, duration: Duration) -> RateLimit<V> {
+    RateLimit {
+        child,
+        duration,
+        mode: Mode::Throttle,
+    }
+}
+
+/// The [`View`] created by [`debounce`] or [`throttle`].
+///
+/// See their docs for more details.
+pub struct RateLimit<V> {
+    child: V,
+    duration: Duration,
+    mode: Mode,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    Debounce,
+    Throttle,
+}
+
+/// The message sent when a timer started by a [`RateLimit`] elapses, with its generation.
+#[derive(Debug)]
+struct Elapsed(u64);
+
+#[doc(hidden)] // Implementation detail, public because of trait visibility rules
+pub struct RateLimitState<S> {
+    child: S,
+    runtime: Handle,
+    proxy: MessageProxy<Elapsed>,
+    /// The latest message which wasn't handled yet, with its id path below the child.
+    pending: Option<(Vec<ViewId>, DynMessage)>,
+    timer: Option<JoinHandle<()>>,
+    /// Incremented whenever a timer starts, so that timers which were aborted too late
+    /// are ignored.
+    generation: u64,
+}
+
+impl<S> RateLimitState<S> {
+    fn start_timer(&mut self, duration: Duration) {
+        self.cancel_timer();
+        self.generation += 1;
+        let generation = self.generation;
+        let proxy = self.proxy.clone();
+        self.timer = Some(self.runtime.spawn(async move {
+            tokio::time::sleep(duration).await;
+            drop(proxy.message(Elapsed(generation)));
+        }));
+    }
+
+    fn cancel_timer(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            timer.abort();
+        }
+    }
+}
+
+impl<V> ViewMarker for RateLimit<V> {}
+impl<State, Action, V> View<State, Action, ViewCtx> for RateLimit<V>
+where
+    V: View<State, Action, ViewCtx>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = V::Element;
+    type ViewState = RateLimitState<V::ViewState>;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (element, child) = ctx.with_id(CHILD_VIEW_ID, |ctx| self.child.build(ctx));
+        let path: Arc<[ViewId]> = ctx.with_id(TIMER_VIEW_ID, |ctx| ctx.view_path().into());
+        let state = RateLimitState {
+            child,
+            runtime: ctx.runtime().handle().clone(),
+            proxy: MessageProxy::new(ctx.proxy.clone(), path),
+            pending: None,
+            timer: None,
+            generation: 0,
+        };
+        (element, state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            self.child
+                .rebuild(&prev.child, &mut view_state.child, ctx, element);
+        });
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        element: Mut<Self::Element>,
+    ) {
+        view_state.cancel_timer();
+        view_state.pending = None;
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            self.child.teardown(&mut view_state.child, ctx, element);
+        });
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        let Some((first, rest)) = id_path.split_first() else {
+            tracing::error!("Empty id path in RateLimit::message");
+            return MessageResult::Stale(message);
+        };
+        match *first {
+            CHILD_VIEW_ID => match self.mode {
+                Mode::Debounce => {
+                    view_state.pending = Some((rest.to_vec(), message));
+                    view_state.start_timer(self.duration);
+                    MessageResult::Nop
+                }
+                Mode::Throttle if view_state.timer.is_some() => {
+                    view_state.pending = Some((rest.to_vec(), message));
+                    MessageResult::Nop
+                }
+                Mode::Throttle => {
+                    view_state.start_timer(self.duration);
+                    (self.child).message(&mut view_state.child, rest, message, app_state)
+                }
+            },
+            TIMER_VIEW_ID => {
+                let generation = match message.downcast::<Elapsed>() {
+                    Ok(elapsed) => elapsed.0,
+                    Err(message) => {
+                        tracing::error!("Wrong message type in RateLimit::message");
+                        return MessageResult::Stale(message);
+                    }
+                };
+                if generation != view_state.generation {
+                    return MessageResult::Nop;
+                }
+                view_state.timer = None;
+                let Some((path, message)) = view_state.pending.take() else {
+                    return MessageResult::Nop;
+                };
+                if self.mode == Mode::Throttle {
+                    // The delivered message opens a new window.
+                    view_state.start_timer(self.duration);
+                }
+                (self.child).message(&mut view_state.child, &path, message, app_state)
+            }
+            _ => {
+                tracing::error!("Wrong id path in RateLimit::message: {id_path:?}");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
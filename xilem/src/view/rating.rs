@@ -0,0 +1,113 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, ViewId};
+
+/// A row of stars showing `value`, which calls `callback` with the new rating when the
+/// user changes it.
+///
+/// See [`Rating`](widget::Rating) for more details.
+pub fn rating<F, State, Action>(value: f64, callback: F) -> Rating<F>
+where
+    F: Fn(&mut State, f64) -> Action + Send + Sync + 'static,
+{
+    Rating {
+        value,
+        max: 5,
+        half_stars: false,
+        callback,
+    }
+}
+
+/// The [`View`] created by [`rating`].
+pub struct Rating<F> {
+    value: f64,
+    max: u32,
+    half_stars: bool,
+    callback: F,
+}
+
+impl<F> Rating<F> {
+    /// Set the number of stars.
+    pub fn max(mut self, max: u32) -> Self {
+        self.max = max;
+        self
+    }
+
+    /// Allow ratings in steps of half a star.
+    pub fn half_stars(mut self, half_stars: bool) -> Self {
+        self.half_stars = half_stars;
+        self
+    }
+}
+
+impl<F> ViewMarker for Rating<F> {}
+impl<F, State, Action> View<State, Action, ViewCtx> for Rating<F>
+where
+    F: Fn(&mut State, f64) -> Action + Send + Sync + 'static,
+{
+    type Element = Pod<widget::Rating>;
+    type ViewState = ();
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_leaf_action_widget(|ctx| {
+            ctx.new_pod(
+                widget::Rating::new(self.value)
+                    .with_max(self.max)
+                    .with_half_stars(self.half_stars),
+            )
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        (): &mut Self::ViewState,
+        _ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.max != self.max {
+            widget::Rating::set_max(&mut element, self.max);
+        }
+        if prev.half_stars != self.half_stars {
+            widget::Rating::set_half_stars(&mut element, self.half_stars);
+        }
+        if prev.value != self.value {
+            widget::Rating::set_value(&mut element, self.value);
+        }
+    }
+
+    fn teardown(&self, (): &mut Self::ViewState, ctx: &mut ViewCtx, element: Mut<Self::Element>) {
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        (): &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        debug_assert!(
+            id_path.is_empty(),
+            "id path should be empty in Rating::message"
+        );
+        match message.downcast::<masonry::Action>() {
+            Ok(action) => {
+                if let masonry::Action::RatingChanged(value) = *action {
+                    MessageResult::Action((self.callback)(app_state, value))
+                } else {
+                    tracing::error!("Wrong action type in Rating::message: {action:?}");
+                    MessageResult::Stale(action)
+                }
+            }
+            Err(message) => {
+                tracing::error!("Wrong message type in Rating::message");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::panic::Location;
+use std::sync::Arc;
+
+use crate::core::{DynMessage, MessageResult, Mut, View, ViewId, ViewMarker};
+use crate::{Tracked, ViewCtx};
+
+/// A section of the view tree which only depends on the state at `paths`.
+///
+/// `view_fn` is only called, and its view rebuilt, if one of `paths` was
+/// [marked](crate::Changes::mark) as changed since the last rebuild. Otherwise, the
+/// previous view and its widgets are kept as they are, which makes rebuilding very large
+/// UIs cheap when each message only changes a small part of the state.
+/// Sections which appear for the first time, e.g. when switching to a branch of
+/// [`OneOf`](crate::core::one_of::OneOf), are always built.
+///
+/// The previous view of a section is found from the place `reads` is called from and
+/// `paths`. When `reads` is called several times from the same place with the same
+/// `paths`, e.g. in a loop, the sections are told apart by the order of the calls.
+///
+/// This requires [`Xilem::with_change_tracking`](crate::Xilem::with_change_tracking),
+/// which clears the changes after each rebuild. If `view_fn` reads parts of the state
+/// outside of `paths`, changes to them won't be shown until one of `paths` changes.
+///
+/// # Examples
+/// ```
+/// use xilem::view::{flex, label, reads};
+/// use xilem::{Changes, Tracked, WidgetView};
+///
+/// struct AppState {
+///     title: String,
+///     items: Vec<String>,
+///     changes: Changes,
+/// }
+///
+/// impl Tracked for AppState {
+///     fn changes(&self) -> &Changes {
+///         &self.changes
+///     }
+///     fn changes_mut(&mut self) -> &mut Changes {
+///         &mut self.changes
+///     }
+/// }
+///
+/// fn app_logic(state: &mut AppState) -> impl WidgetView<AppState> {
+///     flex((
+///         reads(state, &["title"], |state| label(state.title.clone())),
+///         reads(state, &["items"], |state| {
+///             flex(state.items.iter().map(|item| label(item.clone())).collect::<Vec<_>>())
+///         }),
+///     ))
+/// }
+/// ```
+#[track_caller]
+pub fn reads<State, V>(
+    state: &mut State,
+    paths: &[&str],
+    view_fn: impl FnOnce(&mut State) -> V,
+) -> Reads<V>
+where
+    State: Tracked,
+    V: 'static,
+{
+    let changes = state.changes_mut();
+    let changed = paths.iter().any(|path| changes.touches(path));
+    let key = changes.views.next_key(Location::caller(), paths);
+    if !changed {
+        if let Some(view) = changes.views.get::<V>(&key) {
+            return Reads { view };
+        }
+    }
+    let view = Arc::new(view_fn(state));
+    state.changes_mut().views.insert(key, view.clone());
+    Reads { view }
+}
+
+/// The [`View`] created by [`reads`].
+///
+/// See `reads`'s docs for more details.
+pub struct Reads<V> {
+    /// The view, which is the one kept from the last rebuild if its paths didn't change.
+    view: Arc<V>,
+}
+
+#[doc(hidden)] // Implementation detail, public because of trait visibility rules
+pub struct ReadsState<V, S> {
+    /// The view which was last built or rebuilt.
+    view: Arc<V>,
+    view_state: S,
+}
+
+impl<V> ViewMarker for Reads<V> {}
+impl<State, Action, V> View<State, Action, ViewCtx> for Reads<V>
+where
+    V: View<State, Action, ViewCtx>,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = V::Element;
+    type ViewState = ReadsState<V, V::ViewState>;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (element, view_state) = V::build(&self.view, ctx);
+        let state = ReadsState {
+            view: self.view.clone(),
+            view_state,
+        };
+        (element, state)
+    }
+
+    fn rebuild(
+        &self,
+        _prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        element: Mut<Self::Element>,
+    ) {
+        if Arc::ptr_eq(&self.view, &view_state.view) {
+            return;
+        }
+        // `prev` may not have been rebuilt either, so we rebuild against the view we kept.
+        V::rebuild(
+            &self.view,
+            &view_state.view,
+            &mut view_state.view_state,
+            ctx,
+            element,
+        );
+        view_state.view = self.view.clone();
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        element: Mut<Self::Element>,
+    ) {
+        let view = view_state.view.clone();
+        V::teardown(&view, &mut view_state.view_state, ctx, element);
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        let view = view_state.view.clone();
+        V::message(
+            &view,
+            &mut view_state.view_state,
+            id_path,
+            message,
+            app_state,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::view::{label, Label};
+    use crate::Changes;
+
+    struct AppState {
+        title: String,
+        changes: Changes,
+    }
+
+    impl Tracked for AppState {
+        fn changes(&self) -> &Changes {
+            &self.changes
+        }
+        fn changes_mut(&mut self) -> &mut Changes {
+            &mut self.changes
+        }
+    }
+
+    #[test]
+    fn view_fn_only_runs_when_paths_change() {
+        let mut state = AppState {
+            title: "Title".into(),
+            changes: Changes::new(),
+        };
+        let calls = Cell::new(0);
+        let mut app_logic = |state: &mut AppState| -> Reads<Label> {
+            reads(state, &["title"], |state| {
+                calls.set(calls.get() + 1);
+                label(state.title.clone())
+            })
+        };
+
+        let first = app_logic(&mut state);
+        state.changes.clear();
+        assert_eq!(calls.get(), 1);
+
+        state.changes.mark("items.3");
+        let second = app_logic(&mut state);
+        state.changes.clear();
+        assert_eq!(calls.get(), 1);
+        assert!(Arc::ptr_eq(&first.view, &second.view));
+
+        state.title = "New title".into();
+        state.changes.mark("title");
+        let third = app_logic(&mut state);
+        state.changes.clear();
+        assert_eq!(calls.get(), 2);
+        assert!(!Arc::ptr_eq(&second.view, &third.view));
+    }
+}
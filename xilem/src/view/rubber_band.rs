@@ -0,0 +1,126 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker, ViewPathTracker};
+use crate::{MessageResult, Pod, ViewCtx, WidgetView};
+
+/// The id of the child view, which distinguishes its messages from the actions of the
+/// [`RubberBand`](widget::RubberBand) widget.
+const CHILD_VIEW_ID: ViewId = ViewId::new(0);
+
+/// A view which lets the user select the items of `child` by dragging a rectangle over them.
+///
+/// The items are the children of `child`, e.g. the views of a [`grid`](crate::view::grid),
+/// and are identified by their index. `selection` holds the indices of the selected items,
+/// and `on_select` is called with all selected indices when the user changes the selection.
+/// Shift adds the items under the rectangle to the selection, and Ctrl (Cmd on macOS)
+/// toggles them.
+pub fn rubber_band<State, Action, V, F>(
+    child: V,
+    selection: Vec<usize>,
+    on_select: F,
+) -> RubberBand<V, F>
+where
+    V: WidgetView<State, Action>,
+    F: Fn(&mut State, Vec<usize>) -> Action + Send + Sync + 'static,
+{
+    RubberBand {
+        child,
+        selection,
+        on_select,
+    }
+}
+
+/// The [`View`] created by [`rubber_band`].
+///
+/// See `rubber_band`'s docs for more details.
+pub struct RubberBand<V, F> {
+    child: V,
+    selection: Vec<usize>,
+    on_select: F,
+}
+
+impl<V, F> ViewMarker for RubberBand<V, F> {}
+impl<V, F, State, Action> View<State, Action, ViewCtx> for RubberBand<V, F>
+where
+    V: WidgetView<State, Action>,
+    F: Fn(&mut State, Vec<usize>) -> Action + Send + Sync + 'static,
+    State: 'static,
+    Action: 'static,
+{
+    type Element = Pod<widget::RubberBand<Box<dyn masonry::Widget>>>;
+    type ViewState = V::ViewState;
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let (child, child_state) = ctx.with_id(CHILD_VIEW_ID, |ctx| self.child.build(ctx));
+        let pod = ctx.with_action_widget(|ctx| {
+            ctx.new_pod(
+                widget::RubberBand::new_pod(child.inner.boxed())
+                    .with_selection(self.selection.clone()),
+            )
+        });
+        (pod, child_state)
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.selection != self.selection {
+            widget::RubberBand::set_selection(&mut element, self.selection.clone());
+        }
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            let mut child = widget::RubberBand::child_mut(&mut element);
+            self.child
+                .rebuild(&prev.child, view_state, ctx, child.downcast());
+        });
+    }
+
+    fn teardown(
+        &self,
+        view_state: &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        ctx.with_id(CHILD_VIEW_ID, |ctx| {
+            let mut child = widget::RubberBand::child_mut(&mut element);
+            self.child.teardown(view_state, ctx, child.downcast());
+        });
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        view_state: &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        if let Some((first, rest)) = id_path.split_first() {
+            if *first != CHILD_VIEW_ID {
+                tracing::error!("Wrong id path in RubberBand::message: {id_path:?}");
+                return MessageResult::Stale(message);
+            }
+            return self.child.message(view_state, rest, message, app_state);
+        }
+        match message.downcast::<masonry::Action>() {
+            Ok(action) => {
+                if let masonry::Action::ItemsSelected(selection) = *action {
+                    MessageResult::Action((self.on_select)(app_state, selection))
+                } else {
+                    tracing::error!("Wrong action type in RubberBand::message: {action:?}");
+                    MessageResult::Stale(action)
+                }
+            }
+            Err(message) => {
+                tracing::error!("Wrong message type in RubberBand::message");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::text::ArcStr;
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, ViewId};
+
+/// A row of joined buttons labelled with `segments`, of which the one at `selected` is
+/// highlighted. `callback` is called with the index of the segment the user selects.
+///
+/// See [`SegmentedControl`](widget::SegmentedControl) for more details.
+pub fn segmented_control<F, State, Action>(
+    segments: impl IntoIterator<Item = impl Into<ArcStr>>,
+    selected: Option<usize>,
+    callback: F,
+) -> SegmentedControl<F>
+where
+    F: Fn(&mut State, usize) -> Action + Send + Sync + 'static,
+{
+    SegmentedControl {
+        segments: segments.into_iter().map(Into::into).collect(),
+        selected,
+        callback,
+        label: None,
+    }
+}
+
+/// The [`View`] created by [`segmented_control`].
+pub struct SegmentedControl<F> {
+    segments: Vec<ArcStr>,
+    selected: Option<usize>,
+    callback: F,
+    label: Option<ArcStr>,
+}
+
+impl<F> SegmentedControl<F> {
+    /// Set the name of the control read by screen readers.
+    pub fn label(mut self, label: impl Into<ArcStr>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+impl<F> ViewMarker for SegmentedControl<F> {}
+impl<F, State, Action> View<State, Action, ViewCtx> for SegmentedControl<F>
+where
+    F: Fn(&mut State, usize) -> Action + Send + Sync + 'static,
+{
+    type Element = Pod<widget::SegmentedControl>;
+    type ViewState = ();
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_leaf_action_widget(|ctx| {
+            let mut widget = widget::SegmentedControl::new(self.segments.iter().cloned())
+                .with_selected(self.selected);
+            if let Some(label) = &self.label {
+                widget = widget.with_label(label.clone());
+            }
+            ctx.new_pod(widget)
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        (): &mut Self::ViewState,
+        _ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        if prev.segments != self.segments {
+            widget::SegmentedControl::set_segments(&mut element, self.segments.iter().cloned());
+        }
+        if prev.selected != self.selected || prev.segments != self.segments {
+            widget::SegmentedControl::set_selected(&mut element, self.selected);
+        }
+        if prev.label != self.label {
+            widget::SegmentedControl::set_label(&mut element, self.label.clone());
+        }
+    }
+
+    fn teardown(&self, (): &mut Self::ViewState, ctx: &mut ViewCtx, element: Mut<Self::Element>) {
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        (): &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        debug_assert!(
+            id_path.is_empty(),
+            "id path should be empty in SegmentedControl::message"
+        );
+        match message.downcast::<masonry::Action>() {
+            Ok(action) => {
+                if let masonry::Action::SegmentSelected(index) = *action {
+                    MessageResult::Action((self.callback)(app_state, index))
+                } else {
+                    tracing::error!("Wrong action type in SegmentedControl::message: {action:?}");
+                    MessageResult::Stale(action)
+                }
+            }
+            Err(message) => {
+                tracing::error!("Wrong message type in SegmentedControl::message");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
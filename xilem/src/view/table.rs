@@ -0,0 +1,777 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use masonry::clipboard::{parse_delimited, ClipboardContent, Delimiter};
+use masonry::locale::LocaleFormatter;
+use masonry::text::ArcStr;
+use winit::keyboard::{Key, NamedKey};
+
+use crate::any_view::DynWidget;
+use crate::core::{DynMessage, Mut, View, ViewId, ViewMarker};
+use crate::view::{
+    button, checkbox, frozen_panes, grid, interactive, label, sized_box, textbox, GridExt,
+};
+use crate::{AnyWidgetView, Color, MessageResult, Pod, TextWeight, ViewCtx, WidgetView};
+
+type Formatter<Row> = Arc<dyn Fn(&Row, &dyn LocaleFormatter) -> String + Send + Sync>;
+type Comparator<Row> = Arc<dyn Fn(&Row, &Row) -> Ordering + Send + Sync>;
+type EditorFactory<State, Row> =
+    Arc<dyn Fn(&Row, RowLens<State, Row>) -> Box<AnyWidgetView<State>> + Send + Sync>;
+type PasteMapper<Row> = Arc<dyn Fn(&mut Row, &str, &dyn LocaleFormatter) + Send + Sync>;
+type SortCallback<State> = Arc<dyn Fn(&mut State, usize, SortOrder) + Send + Sync>;
+type SelectCallback<State> = Arc<dyn Fn(&mut State, CellRange) + Send + Sync>;
+type RowsAccessor<State, Row> = fn(&mut State) -> &mut Arc<Vec<Row>>;
+
+/// A value which can be shown, sorted and edited in a [`table`] column created with
+/// [`Column::field`] or [`Column::editable_field`].
+///
+/// Values are formatted and parsed with the [formatter](crate::Xilem::with_formatter) of the app.
+pub trait CellValue: Sized + 'static {
+    /// The text shown in a read-only cell.
+    fn format(&self, formatter: &dyn LocaleFormatter) -> String;
+
+    /// The order of the rows when the table is sorted by this column.
+    fn compare(&self, other: &Self) -> Ordering;
+
+    /// A view which edits the value, and calls `on_changed` with the new value.
+    fn editor<State: 'static>(
+        &self,
+        on_changed: impl Fn(&mut State, Self) + Send + Sync + 'static,
+    ) -> Box<AnyWidgetView<State>>;
+
+    /// The value of text pasted into a cell, or `None` if the text isn't a valid value.
+    fn parse(text: &str, formatter: &dyn LocaleFormatter) -> Option<Self>;
+}
+
+impl CellValue for String {
+    fn format(&self, _formatter: &dyn LocaleFormatter) -> String {
+        self.clone()
+    }
+
+    fn parse(text: &str, _formatter: &dyn LocaleFormatter) -> Option<Self> {
+        Some(text.into())
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn editor<State: 'static>(
+        &self,
+        on_changed: impl Fn(&mut State, Self) + Send + Sync + 'static,
+    ) -> Box<AnyWidgetView<State>> {
+        Box::new(textbox(self.clone(), on_changed))
+    }
+}
+
+impl CellValue for bool {
+    /// Booleans are shown as check marks, which don't depend on the language.
+    fn format(&self, _formatter: &dyn LocaleFormatter) -> String {
+        if *self { "✓" } else { "✗" }.into()
+    }
+
+    fn parse(text: &str, _formatter: &dyn LocaleFormatter) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "✓" | "yes" | "true" | "1" => Some(true),
+            "✗" | "no" | "false" | "0" | "" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+
+    fn editor<State: 'static>(
+        &self,
+        on_changed: impl Fn(&mut State, Self) + Send + Sync + 'static,
+    ) -> Box<AnyWidgetView<State>> {
+        Box::new(checkbox("", *self, on_changed))
+    }
+}
+
+/// Gives the cell editors of a [`table`] access to their row in the app state.
+pub struct RowLens<State, Row> {
+    rows: RowsAccessor<State, Row>,
+    index: usize,
+}
+
+impl<State, Row> Clone for RowLens<State, Row> {
+    fn clone(&self) -> Self {
+        Self {
+            rows: self.rows,
+            index: self.index,
+        }
+    }
+}
+
+impl<State, Row> RowLens<State, Row> {
+    /// The index of the row, in the rows given to [`table`].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The row in the app state, if it still exists.
+    ///
+    /// The rows are copied first if they're shared, e.g. with the table view.
+    pub fn row_mut<'a>(&self, state: &'a mut State) -> Option<&'a mut Row>
+    where
+        Row: Clone,
+    {
+        Arc::make_mut((self.rows)(state)).get_mut(self.index)
+    }
+}
+
+/// A rectangular range of cells of a [`table`], spanning from the `anchor` cell to the
+/// `active` cell.
+///
+/// Cells are given as `(row, column)`, where rows are counted in the order they are
+/// shown, without the header row.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CellRange {
+    /// The cell where the selection started.
+    pub anchor: (usize, usize),
+    /// The cell which is moved when the selection is extended.
+    pub active: (usize, usize),
+}
+
+impl CellRange {
+    /// The range made of the cell at `row` and `column`.
+    pub fn cell(row: usize, column: usize) -> Self {
+        Self {
+            anchor: (row, column),
+            active: (row, column),
+        }
+    }
+
+    /// The rows of the range.
+    pub fn rows(&self) -> RangeInclusive<usize> {
+        self.anchor.0.min(self.active.0)..=self.anchor.0.max(self.active.0)
+    }
+
+    /// The columns of the range.
+    pub fn columns(&self) -> RangeInclusive<usize> {
+        self.anchor.1.min(self.active.1)..=self.anchor.1.max(self.active.1)
+    }
+
+    /// Whether the cell at `row` and `column` is in the range.
+    pub fn contains(&self, row: usize, column: usize) -> bool {
+        self.rows().contains(&row) && self.columns().contains(&column)
+    }
+}
+
+/// The order of the rows of a sorted [`table`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// The specification of a [`table`] column: what each cell shows, how the rows are sorted
+/// by this column, and optionally how a cell is edited.
+pub struct Column<State, Row> {
+    header: ArcStr,
+    format: Formatter<Row>,
+    compare: Option<Comparator<Row>>,
+    editor: Option<EditorFactory<State, Row>>,
+    paste: Option<PasteMapper<Row>>,
+}
+
+impl<State, Row> Clone for Column<State, Row> {
+    fn clone(&self) -> Self {
+        Self {
+            header: self.header.clone(),
+            format: self.format.clone(),
+            compare: self.compare.clone(),
+            editor: self.editor.clone(),
+            paste: self.paste.clone(),
+        }
+    }
+}
+
+impl<State: 'static, Row: 'static> Column<State, Row> {
+    /// A read-only column, whose cells show the text returned by `format`.
+    ///
+    /// The table can't be sorted by this column unless a comparator is given with
+    /// [`sort_by`](Self::sort_by).
+    pub fn new(
+        header: impl Into<ArcStr>,
+        format: impl Fn(&Row) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self::localized(header, move |row, _| format(row))
+    }
+
+    /// A read-only column, whose cells show the text returned by `format`, which is given
+    /// the [formatter](crate::Xilem::with_formatter) of the app.
+    pub fn localized(
+        header: impl Into<ArcStr>,
+        format: impl Fn(&Row, &dyn LocaleFormatter) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            header: header.into(),
+            format: Arc::new(format),
+            compare: None,
+            editor: None,
+            paste: None,
+        }
+    }
+
+    /// A read-only column showing the field returned by `get`, which the table can be sorted by.
+    pub fn field<T: CellValue>(header: impl Into<ArcStr>, get: fn(&Row) -> &T) -> Self {
+        Self::localized(header, move |row, formatter| get(row).format(formatter))
+            .sort_by(move |a, b| get(a).compare(get(b)))
+    }
+
+    /// A read-only column showing the number returned by `get` with `fraction_digits`,
+    /// formatted for the locale of the app. The table can be sorted by this column.
+    pub fn number(
+        header: impl Into<ArcStr>,
+        get: impl Fn(&Row) -> f64 + Send + Sync + 'static,
+        fraction_digits: usize,
+    ) -> Self {
+        let get = Arc::new(get);
+        let compare = get.clone();
+        Self::localized(header, move |row, formatter| {
+            formatter.format_number(get(row), fraction_digits)
+        })
+        .sort_by(move |a, b| compare(a).total_cmp(&compare(b)))
+    }
+
+    /// A read-only column showing the date returned by `get`, formatted for the locale of
+    /// the app. The table can be sorted by this column.
+    pub fn date(
+        header: impl Into<ArcStr>,
+        get: impl Fn(&Row) -> time::Date + Send + Sync + 'static,
+    ) -> Self {
+        let get = Arc::new(get);
+        let compare = get.clone();
+        Self::localized(header, move |row, formatter| {
+            formatter.format_date(get(row))
+        })
+        .sort_by(move |a, b| compare(a).cmp(&compare(b)))
+    }
+
+    /// A column showing the field returned by `get`, whose cells are edited with the
+    /// [editor](CellValue::editor) of the field's type.
+    ///
+    /// The table can be sorted by this column, and text pasted into it is
+    /// [parsed](CellValue::parse) as values of the field's type.
+    pub fn editable_field<T: CellValue>(
+        header: impl Into<ArcStr>,
+        get: fn(&Row) -> &T,
+        get_mut: fn(&mut Row) -> &mut T,
+    ) -> Self
+    where
+        Row: Clone,
+    {
+        Self::field(header, get)
+            .editor(move |row, lens| {
+                get(row).editor(move |state: &mut State, value| {
+                    if let Some(row) = lens.row_mut(state) {
+                        *get_mut(row) = value;
+                    }
+                })
+            })
+            .on_localized_paste(move |row, text, formatter| {
+                if let Some(value) = T::parse(text, formatter) {
+                    *get_mut(row) = value;
+                }
+            })
+    }
+
+    /// Builder-style method to set the comparator used when the table is sorted by this column.
+    pub fn sort_by(
+        mut self,
+        compare: impl Fn(&Row, &Row) -> Ordering + Send + Sync + 'static,
+    ) -> Self {
+        self.compare = Some(Arc::new(compare));
+        self
+    }
+
+    /// Builder-style method to make the cells editable.
+    ///
+    /// `editor` is called for each row, with a [`RowLens`] which gives its callbacks access
+    /// to the row in the app state. It replaces the formatted text of the cells.
+    pub fn editor(
+        mut self,
+        editor: impl Fn(&Row, RowLens<State, Row>) -> Box<AnyWidgetView<State>> + Send + Sync + 'static,
+    ) -> Self {
+        self.editor = Some(Arc::new(editor));
+        self
+    }
+
+    /// Builder-style method to accept pasted data in this column.
+    ///
+    /// When delimited data is pasted into a [selection](Table::selection), `on_paste` is
+    /// called with each row of the selection and the text pasted into its cell. It maps
+    /// the text to the row, e.g. by parsing it into one of its fields. Cells of columns
+    /// without `on_paste` are left unchanged.
+    pub fn on_paste(self, on_paste: impl Fn(&mut Row, &str) + Send + Sync + 'static) -> Self {
+        self.on_localized_paste(move |row, text, _| on_paste(row, text))
+    }
+
+    /// Builder-style method to accept pasted data in this column, like
+    /// [`on_paste`](Self::on_paste), parsing it with the [formatter](crate::Xilem::with_formatter)
+    /// of the app.
+    pub fn on_localized_paste(
+        mut self,
+        on_paste: impl Fn(&mut Row, &str, &dyn LocaleFormatter) + Send + Sync + 'static,
+    ) -> Self {
+        self.paste = Some(Arc::new(on_paste));
+        self
+    }
+}
+
+/// A view which shows `rows` in a grid, with one column per [`Column`] specification.
+///
+/// The rows are shared with the app state rather than copied, so `rows` is usually a clone
+/// of an `Arc` kept in the state, and `rows_mut` returns that `Arc`, for the cell editors.
+/// Editing a row copies the rows if the table still shares them.
+/// The table is built on a [`grid`], so it doesn't scroll by itself, unless its header row
+/// or leading columns are [frozen](Table::frozen_header).
+///
+/// ```ignore
+/// table(
+///     state.people.clone(),
+///     |state: &mut AppState| &mut state.people,
+///     vec![
+///         Column::editable_field("Name", |p: &Person| &p.name, |p| &mut p.name),
+///         Column::number("Age", |p: &Person| p.age.into(), 0),
+///     ],
+/// )
+/// .sorted(state.sort_column, state.sort_order)
+/// .on_sort(|state, column, order| {
+///     state.sort_column = column;
+///     state.sort_order = order;
+/// })
+/// ```
+pub fn table<State, Row>(
+    rows: Arc<Vec<Row>>,
+    rows_mut: RowsAccessor<State, Row>,
+    columns: Vec<Column<State, Row>>,
+) -> Table<State, Row> {
+    Table {
+        rows,
+        rows_mut,
+        columns,
+        sort: None,
+        on_sort: None,
+        selection: None,
+        on_select: None,
+        spacing: 4.,
+        frozen_header: false,
+        frozen_columns: 0,
+        cell_size: (120., 32.),
+    }
+}
+
+/// The [`View`] created by [`table`].
+///
+/// See `table`'s docs for more details.
+pub struct Table<State, Row> {
+    rows: Arc<Vec<Row>>,
+    rows_mut: RowsAccessor<State, Row>,
+    columns: Vec<Column<State, Row>>,
+    sort: Option<(usize, SortOrder)>,
+    on_sort: Option<SortCallback<State>>,
+    selection: Option<CellRange>,
+    on_select: Option<SelectCallback<State>>,
+    spacing: f64,
+    frozen_header: bool,
+    frozen_columns: usize,
+    cell_size: (f64, f64),
+}
+
+impl<State, Row> Table<State, Row> {
+    /// Show the rows sorted by the column at `column`, if it has a comparator.
+    ///
+    /// This only changes the order in which the rows are shown: [`RowLens::index`]
+    /// still refers to the rows as given.
+    pub fn sorted(mut self, column: usize, order: SortOrder) -> Self {
+        self.sort = Some((column, order));
+        self
+    }
+
+    /// Make the headers of the sortable columns clickable, and call `on_sort` when one is
+    /// clicked, with the column and the order the table should now be sorted in.
+    ///
+    /// The table isn't sorted by itself: `on_sort` should update the app state, which is
+    /// then passed to [`sorted`](Self::sorted).
+    pub fn on_sort<F>(mut self, on_sort: F) -> Self
+    where
+        F: Fn(&mut State, usize, SortOrder) + Send + Sync + 'static,
+    {
+        self.on_sort = Some(Arc::new(on_sort));
+        self
+    }
+
+    /// Highlight the cells in `selection`.
+    ///
+    /// When the table has focus, Ctrl+C (Cmd+C on macOS) copies the selected cells as
+    /// tab-separated values, with an HTML table flavor for rich text apps. Ctrl+V pastes
+    /// tab- or comma-separated values into the columns which [accept pasted
+    /// data](Column::on_paste), starting at the top left cell of the selection.
+    pub fn selection(mut self, selection: Option<CellRange>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Make the cells selectable, and call `on_select` with the new selection when the user
+    /// clicks a read-only cell or moves the selection with the arrow keys.
+    ///
+    /// The selection is extended while Shift is held, and covers the pasted cells after a
+    /// paste. Like sorting, selecting doesn't change the table by itself: `on_select` should
+    /// update the app state, which is then passed to [`selection`](Self::selection).
+    pub fn on_select<F>(mut self, on_select: F) -> Self
+    where
+        F: Fn(&mut State, CellRange) + Send + Sync + 'static,
+    {
+        self.on_select = Some(Arc::new(on_select));
+        self
+    }
+
+    /// Set the spacing between the cells.
+    ///
+    /// The default spacing is `4.0`.
+    pub fn spacing(mut self, spacing: f64) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Keep the header row in view while the rows are scrolled.
+    ///
+    /// This makes the table scrollable, with cells of a fixed [size](Self::cell_size).
+    pub fn frozen_header(mut self) -> Self {
+        self.frozen_header = true;
+        self
+    }
+
+    /// Keep the first `columns` columns in view while the other columns are scrolled.
+    ///
+    /// This makes the table scrollable, with cells of a fixed [size](Self::cell_size).
+    pub fn frozen_columns(mut self, columns: usize) -> Self {
+        self.frozen_columns = columns;
+        self
+    }
+
+    /// Set the size of the cells of a table with frozen rows or columns.
+    ///
+    /// The default size is 120 by 32.
+    pub fn cell_size(mut self, width: f64, height: f64) -> Self {
+        self.cell_size = (width, height);
+        self
+    }
+}
+
+impl<State: 'static, Row: Clone + 'static> Table<State, Row> {
+    /// The indices of the rows, in the order they are shown.
+    fn row_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.rows.len()).collect();
+        let Some((column, sort_order)) = self.sort else {
+            return order;
+        };
+        let Some(compare) = self.columns.get(column).and_then(|c| c.compare.as_ref()) else {
+            return order;
+        };
+        order.sort_by(|&a, &b| {
+            let ordering = compare(&self.rows[a], &self.rows[b]);
+            match sort_order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        });
+        order
+    }
+
+    fn header(&self, index: usize, column: &Column<State, Row>) -> Box<AnyWidgetView<State>> {
+        let sort_order = self
+            .sort
+            .and_then(|(sorted, order)| (sorted == index).then_some(order));
+        let text = match sort_order {
+            Some(SortOrder::Ascending) => format!("{} ▲", column.header),
+            Some(SortOrder::Descending) => format!("{} ▼", column.header),
+            None => column.header.to_string(),
+        };
+        match (&self.on_sort, &column.compare) {
+            (Some(on_sort), Some(_)) => {
+                let on_sort = on_sort.clone();
+                let next_order = match sort_order {
+                    Some(SortOrder::Ascending) => SortOrder::Descending,
+                    _ => SortOrder::Ascending,
+                };
+                Box::new(button(text, move |state: &mut State| {
+                    on_sort(state, index, next_order);
+                }))
+            }
+            _ => Box::new(label(text).weight(TextWeight::BOLD)),
+        }
+    }
+
+    /// The views of the cells, starting with the header row.
+    fn cells(
+        &self,
+        order: &[usize],
+        formatter: &dyn LocaleFormatter,
+    ) -> Vec<Vec<Box<AnyWidgetView<State>>>> {
+        let selectable = self.on_select.is_some() || self.selection.is_some();
+        let mut cells = Vec::with_capacity(order.len() + 1);
+        cells.push(
+            self.columns
+                .iter()
+                .enumerate()
+                .map(|(x, column)| self.header(x, column))
+                .collect(),
+        );
+        for (y, &index) in order.iter().enumerate() {
+            let row = &self.rows[index];
+            cells.push(
+                (self.columns.iter().enumerate())
+                    .map(|(x, column)| {
+                        let cell = match (&column.editor, &self.on_select) {
+                            (Some(editor), _) => editor(
+                                row,
+                                RowLens {
+                                    rows: self.rows_mut,
+                                    index,
+                                },
+                            ),
+                            (None, Some(on_select)) => {
+                                let on_select = on_select.clone();
+                                Box::new(label((column.format)(row, formatter)).on_click(
+                                    move |state: &mut State, _| {
+                                        on_select(state, CellRange::cell(y, x));
+                                    },
+                                ))
+                            }
+                            (None, None) => Box::new(label((column.format)(row, formatter))),
+                        };
+                        if !selectable {
+                            return cell;
+                        }
+                        // The cell is wrapped even when it isn't selected, so that changing
+                        // the selection doesn't rebuild its widget.
+                        let selected = self.selection.is_some_and(|s| s.contains(y, x));
+                        let background = if selected {
+                            *masonry::theme::SELECTED_TEXT_BACKGROUND_COLOR.default_value()
+                        } else {
+                            Color::TRANSPARENT
+                        };
+                        Box::new(sized_box(cell).background(background))
+                    })
+                    .collect(),
+            );
+        }
+        cells
+    }
+
+    /// The formatted text of the selected cells.
+    fn selected_text(
+        &self,
+        order: &[usize],
+        formatter: &dyn LocaleFormatter,
+    ) -> Option<Vec<Vec<String>>> {
+        let selection = self.selection?;
+        let rows = selection.rows().filter_map(|y| order.get(y));
+        let text: Vec<Vec<String>> = rows
+            .map(|&index| {
+                (selection.columns())
+                    .filter_map(|x| self.columns.get(x))
+                    .map(|column| (column.format)(&self.rows[index], formatter))
+                    .collect()
+            })
+            .collect();
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn view(&self, formatter: &Arc<dyn LocaleFormatter>) -> Box<AnyWidgetView<State>> {
+        let order = self.row_order();
+        let cells = self.grid_view(self.cells(&order, &**formatter));
+        if self.on_select.is_none() && self.selection.is_none() {
+            return cells;
+        }
+
+        let content = self
+            .selected_text(&order, &**formatter)
+            .map(|text| ClipboardContent::table(&text));
+        let mut view = interactive(cells).copy_content(content);
+        if let Some(on_select) = self.on_select.clone() {
+            let (rows, columns) = (self.rows.len(), self.columns.len());
+            let selection = self.selection;
+            view = view.on_key(move |state: &mut State, key, mods| {
+                let (row, column) = selection.map_or((0, 0), |s| s.active);
+                let active = match key {
+                    Key::Named(NamedKey::ArrowUp) => (row.saturating_sub(1), column),
+                    Key::Named(NamedKey::ArrowDown) => (row + 1, column),
+                    Key::Named(NamedKey::ArrowLeft) => (row, column.saturating_sub(1)),
+                    Key::Named(NamedKey::ArrowRight) => (row, column + 1),
+                    _ => return,
+                };
+                if rows == 0 || columns == 0 {
+                    return;
+                }
+                let active = (active.0.min(rows - 1), active.1.min(columns - 1));
+                let anchor = match selection {
+                    Some(selection) if mods.shift_key() => selection.anchor,
+                    _ => active,
+                };
+                on_select(state, CellRange { anchor, active });
+            });
+        }
+        if let Some(selection) = self.selection {
+            view = view.on_paste(self.paste_handler(order, selection, formatter.clone()));
+        }
+        Box::new(view)
+    }
+
+    /// The callback writing pasted data into the rows, starting at the top left cell
+    /// of `selection`.
+    fn paste_handler(
+        &self,
+        order: Vec<usize>,
+        selection: CellRange,
+        formatter: Arc<dyn LocaleFormatter>,
+    ) -> impl Fn(&mut State, String) + Send + Sync + 'static {
+        let rows_mut = self.rows_mut;
+        let mappers: Vec<_> = self.columns.iter().map(|c| c.paste.clone()).collect();
+        let on_select = self.on_select.clone();
+        let origin = (*selection.rows().start(), *selection.columns().start());
+        move |state: &mut State, text: String| {
+            let values = parse_delimited(&text, Delimiter::detect(&text));
+            let rows = Arc::make_mut(rows_mut(state));
+            let mut end = origin;
+            for (y, (values, &index)) in values.iter().zip(order.iter().skip(origin.0)).enumerate()
+            {
+                let Some(row) = rows.get_mut(index) else {
+                    continue;
+                };
+                for (x, (value, mapper)) in
+                    values.iter().zip(mappers.iter().skip(origin.1)).enumerate()
+                {
+                    if let Some(mapper) = mapper {
+                        mapper(row, value, &*formatter);
+                    }
+                    end = (end.0.max(origin.0 + y), end.1.max(origin.1 + x));
+                }
+            }
+            if let Some(on_select) = &on_select {
+                on_select(
+                    state,
+                    CellRange {
+                        anchor: origin,
+                        active: end,
+                    },
+                );
+            }
+        }
+    }
+
+    fn grid_view(&self, cells: Vec<Vec<Box<AnyWidgetView<State>>>>) -> Box<AnyWidgetView<State>> {
+        let width = self.columns.len();
+        let height = self.rows.len() + 1;
+        if !self.frozen_header && self.frozen_columns == 0 {
+            let cells: Vec<_> = (cells.into_iter().enumerate())
+                .flat_map(|(y, row)| {
+                    (row.into_iter().enumerate())
+                        .map(move |(x, cell)| cell.grid_pos(x as i32, y as i32))
+                })
+                .collect();
+            return Box::new(grid(cells, width as i32, height as i32).spacing(self.spacing));
+        }
+
+        // Each pane is a grid of cells of the same size, so that the columns and rows of
+        // adjacent panes line up.
+        let frozen_rows = usize::from(self.frozen_header);
+        let frozen_columns = self.frozen_columns.min(width);
+        let mut panes: [Vec<_>; 4] = Default::default();
+        for (y, row) in cells.into_iter().enumerate() {
+            for (x, cell) in row.into_iter().enumerate() {
+                let (pane, x, y) = match (y < frozen_rows, x < frozen_columns) {
+                    (true, true) => (0, x, y),
+                    (true, false) => (1, x - frozen_columns, y),
+                    (false, true) => (2, x, y - frozen_rows),
+                    (false, false) => (3, x - frozen_columns, y - frozen_rows),
+                };
+                panes[pane].push(cell.grid_pos(x as i32, y as i32));
+            }
+        }
+        let [corner, header, side, body] = panes;
+        let (cell_width, cell_height) = self.cell_size;
+        let pane = |cells, columns: usize, rows: usize| {
+            let length = |count: usize, cell: f64| {
+                (count as f64 * (cell + self.spacing) - self.spacing).max(0.)
+            };
+            sized_box(grid(cells, columns as i32, rows as i32).spacing(self.spacing))
+                .width(length(columns, cell_width))
+                .height(length(rows, cell_height))
+        };
+        let scrolled_columns = width - frozen_columns;
+        let scrolled_rows = height - frozen_rows;
+        Box::new(
+            frozen_panes(
+                pane(corner, frozen_columns, frozen_rows),
+                pane(header, scrolled_columns, frozen_rows),
+                pane(side, frozen_columns, scrolled_rows),
+                pane(body, scrolled_columns, scrolled_rows),
+            )
+            .spacing(self.spacing),
+        )
+    }
+}
+
+impl<State, Row> ViewMarker for Table<State, Row> {}
+impl<State, Row> View<State, (), ViewCtx> for Table<State, Row>
+where
+    State: 'static,
+    Row: Clone + Send + Sync + 'static,
+{
+    type Element = Pod<DynWidget>;
+    /// The view built from this table, and its state.
+    type ViewState = (
+        Box<AnyWidgetView<State>>,
+        <Box<AnyWidgetView<State>> as View<State, (), ViewCtx>>::ViewState,
+    );
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        let view = self.view(&ctx.formatter);
+        let (element, view_state) = view.build(ctx);
+        (element, (view, view_state))
+    }
+
+    fn rebuild(
+        &self,
+        _prev: &Self,
+        (prev_view, view_state): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        element: Mut<Self::Element>,
+    ) {
+        let view = self.view(&ctx.formatter);
+        view.rebuild(prev_view, view_state, ctx, element);
+        *prev_view = view;
+    }
+
+    fn teardown(
+        &self,
+        (view, view_state): &mut Self::ViewState,
+        ctx: &mut ViewCtx,
+        element: Mut<Self::Element>,
+    ) {
+        view.teardown(view_state, ctx, element);
+    }
+
+    fn message(
+        &self,
+        (view, view_state): &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<()> {
+        // The view stored in the view state is the one matching the widgets, whose
+        // callbacks the message is meant for.
+        view.message(view_state, id_path, message, app_state)
+    }
+}
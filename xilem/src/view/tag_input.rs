@@ -0,0 +1,106 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+use masonry::text::ArcStr;
+use masonry::widget;
+
+use crate::core::{DynMessage, Mut, View, ViewMarker};
+use crate::{MessageResult, Pod, ViewCtx, ViewId};
+
+/// A text field showing `tags` as removable chips, which calls `on_change` with all the
+/// tags when the user adds or removes one.
+///
+/// See [`TagInput`](widget::TagInput) for more details.
+pub fn tag_input<F, State, Action>(tags: Vec<String>, on_change: F) -> TagInput<F>
+where
+    F: Fn(&mut State, Vec<String>) -> Action + Send + Sync + 'static,
+{
+    TagInput {
+        tags,
+        suggestions: Vec::new(),
+        on_change,
+    }
+}
+
+/// The [`View`] created by [`tag_input`].
+pub struct TagInput<F> {
+    tags: Vec<String>,
+    suggestions: Vec<ArcStr>,
+    on_change: F,
+}
+
+impl<F> TagInput<F> {
+    /// Set the suggestions listed while typing.
+    pub fn suggestions(mut self, suggestions: impl IntoIterator<Item = impl Into<ArcStr>>) -> Self {
+        self.suggestions = suggestions.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl<F> ViewMarker for TagInput<F> {}
+impl<F, State, Action> View<State, Action, ViewCtx> for TagInput<F>
+where
+    F: Fn(&mut State, Vec<String>) -> Action + Send + Sync + 'static,
+{
+    type Element = Pod<widget::TagInput>;
+    type ViewState = ();
+
+    fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
+        ctx.with_leaf_action_widget(|ctx| {
+            ctx.new_pod(
+                widget::TagInput::new(self.tags.iter().cloned())
+                    .with_suggestions(self.suggestions.iter().cloned()),
+            )
+        })
+    }
+
+    fn rebuild(
+        &self,
+        prev: &Self,
+        (): &mut Self::ViewState,
+        _ctx: &mut ViewCtx,
+        mut element: Mut<Self::Element>,
+    ) {
+        // The widget already holds the tags the user changed, so this usually does nothing.
+        if prev.tags != self.tags {
+            widget::TagInput::set_tags(&mut element, self.tags.iter().cloned());
+        }
+        if prev.suggestions != self.suggestions {
+            widget::TagInput::set_suggestions(&mut element, self.suggestions.iter().cloned());
+        }
+    }
+
+    fn teardown(&self, (): &mut Self::ViewState, ctx: &mut ViewCtx, element: Mut<Self::Element>) {
+        ctx.teardown_leaf(element);
+    }
+
+    fn message(
+        &self,
+        (): &mut Self::ViewState,
+        id_path: &[ViewId],
+        message: DynMessage,
+        app_state: &mut State,
+    ) -> MessageResult<Action> {
+        debug_assert!(
+            id_path.is_empty(),
+            "id path should be empty in TagInput::message"
+        );
+        match message.downcast::<masonry::Action>() {
+            Ok(action) => match *action {
+                masonry::Action::TagsChanged(tags) => {
+                    MessageResult::Action((self.on_change)(app_state, tags))
+                }
+                // The typed text isn't part of the binding.
+                masonry::Action::TextChanged(_) => MessageResult::Nop,
+                _ => {
+                    tracing::error!("Wrong action type in TagInput::message: {action:?}");
+                    MessageResult::Stale(action)
+                }
+            },
+            Err(message) => {
+                tracing::error!("Wrong message type in TagInput::message");
+                MessageResult::Stale(message)
+            }
+        }
+    }
+}
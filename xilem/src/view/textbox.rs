@@ -1,8 +1,8 @@
 // Copyright 2024 the Xilem Authors
 // SPDX-License-Identifier: Apache-2.0
 
-use masonry::text::TextBrush;
-use masonry::widget;
+use masonry::text::{CaretStyle, TextBrush};
+use masonry::widget::{self, LineBreaking};
 
 use crate::core::{DynMessage, Mut, View, ViewMarker};
 use crate::{Color, MessageResult, Pod, TextAlignment, ViewCtx, ViewId};
@@ -24,6 +24,9 @@ where
         on_enter: None,
         text_brush: Color::WHITE.into(),
         alignment: TextAlignment::default(),
+        line_break_mode: LineBreaking::WordWrap,
+        caret_style: None,
+        selection_color: None,
         disabled: false,
     }
 }
@@ -34,6 +37,9 @@ pub struct Textbox<State, Action> {
     on_enter: Option<Callback<State, Action>>,
     text_brush: TextBrush,
     alignment: TextAlignment,
+    line_break_mode: LineBreaking,
+    caret_style: Option<CaretStyle>,
+    selection_color: Option<Color>,
     disabled: bool,
     // TODO: add more attributes of `masonry::widget::Label`
 }
@@ -50,6 +56,30 @@ impl<State, Action> Textbox<State, Action> {
         self
     }
 
+    /// Set how lines which are too wide for the textbox are handled.
+    ///
+    /// With [`LineBreaking::Overflow`], put the textbox in a [`portal`](crate::view::portal)
+    /// to scroll long lines horizontally.
+    pub fn line_break_mode(mut self, line_break_mode: LineBreaking) -> Self {
+        self.line_break_mode = line_break_mode;
+        self
+    }
+
+    /// Set how the caret is drawn.
+    ///
+    /// A [block](masonry::text::CaretShape::Block) caret also makes typing overwrite the
+    /// character it covers.
+    pub fn caret_style(mut self, style: CaretStyle) -> Self {
+        self.caret_style = Some(style);
+        self
+    }
+
+    /// Set the color behind selected text.
+    pub fn selection_color(mut self, color: Color) -> Self {
+        self.selection_color = Some(color);
+        self
+    }
+
     pub fn disabled(mut self) -> Self {
         self.disabled = true;
         self
@@ -71,11 +101,17 @@ impl<State: 'static, Action: 'static> View<State, Action, ViewCtx> for Textbox<S
 
     fn build(&self, ctx: &mut ViewCtx) -> (Self::Element, Self::ViewState) {
         ctx.with_leaf_action_widget(|ctx| {
-            ctx.new_pod(
-                masonry::widget::Textbox::new(self.contents.clone())
-                    .with_text_brush(self.text_brush.clone())
-                    .with_text_alignment(self.alignment),
-            )
+            let mut textbox = masonry::widget::Textbox::new(self.contents.clone())
+                .with_text_brush(self.text_brush.clone())
+                .with_text_alignment(self.alignment)
+                .with_line_break_mode(self.line_break_mode);
+            if let Some(style) = self.caret_style {
+                textbox = textbox.with_caret_style(style);
+            }
+            if let Some(color) = self.selection_color {
+                textbox = textbox.with_selection_color(color);
+            }
+            ctx.new_pod(textbox)
         })
     }
 
@@ -103,6 +139,21 @@ impl<State: 'static, Action: 'static> View<State, Action, ViewCtx> for Textbox<S
         if prev.alignment != self.alignment {
             widget::Textbox::set_alignment(&mut element, self.alignment);
         }
+        if prev.line_break_mode != self.line_break_mode {
+            widget::Textbox::set_line_break_mode(&mut element, self.line_break_mode);
+        }
+        if prev.caret_style != self.caret_style {
+            match self.caret_style {
+                Some(style) => widget::Textbox::set_caret_style(&mut element, style),
+                None => widget::Textbox::reset_caret_style(&mut element),
+            }
+        }
+        if prev.selection_color != self.selection_color {
+            match self.selection_color {
+                Some(color) => widget::Textbox::set_selection_color(&mut element, color),
+                None => widget::Textbox::reset_selection_color(&mut element),
+            }
+        }
     }
 
     fn teardown(&self, _: &mut Self::ViewState, ctx: &mut ViewCtx, element: Mut<Self::Element>) {
@@ -0,0 +1,182 @@
+// Copyright 2024 the Xilem Authors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Secondary windows, described by the app logic like the views they show.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use masonry::widget::RootWidget;
+use masonry::{AppWindowId, WidgetId};
+use winit::window::{Window, WindowAttributes};
+
+use crate::any_view::DynWidget;
+use crate::core::{DynMessage, Mut, ProxyError, RawProxy, View, ViewId};
+use crate::{AnyWidgetView, Pod, ViewCtx, WidgetMap, WidgetView};
+
+pub(crate) type WindowsLogic<State> = Box<dyn FnMut(&mut State) -> Vec<WindowView<State>>>;
+type CloseWindowCallback<State> = Arc<dyn Fn(&mut State) + Send + Sync>;
+
+/// A secondary window of the app, returned by the logic given to
+/// [`Xilem::with_windows`](crate::Xilem::with_windows).
+///
+/// See [`window`] for more details.
+pub struct WindowView<State> {
+    pub(crate) key: u64,
+    pub(crate) attributes: WindowAttributes,
+    pub(crate) view: Box<AnyWidgetView<State>>,
+    pub(crate) on_close: Option<CloseWindowCallback<State>>,
+}
+
+/// A secondary window showing `view`, e.g. a tool palette or a second document.
+///
+/// Windows are told apart by their `key`: a window is opened when the app logic first
+/// returns its key, its view is rebuilt with the app state like the main view, and it's
+/// closed when the app logic stops returning its key. The attributes of the window, such
+/// as its `title`, are only used when the window is opened.
+///
+/// When the user closes the window, [`on_close`](WindowView::on_close) is called, and should
+/// update the state so that the app logic stops returning the window. Otherwise, the window
+/// is opened again.
+pub fn window<State: 'static, V: WidgetView<State>>(
+    key: impl Hash,
+    title: impl Into<String>,
+    view: V,
+) -> WindowView<State> {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    WindowView {
+        key: hasher.finish(),
+        attributes: Window::default_attributes().with_title(title),
+        view: Box::new(view),
+        on_close: None,
+    }
+}
+
+impl<State> WindowView<State> {
+    /// Set the attributes the window is opened with, which replace its title.
+    pub fn with_attributes(mut self, attributes: WindowAttributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set the callback called when the user closes the window.
+    pub fn on_close(mut self, on_close: impl Fn(&mut State) + Send + Sync + 'static) -> Self {
+        self.on_close = Some(Arc::new(on_close));
+        self
+    }
+}
+
+/// A secondary window opened by the driver, with the view tree it shows.
+pub(crate) struct OpenWindow<State: 'static> {
+    pub(crate) id: AppWindowId,
+    pub(crate) view: WindowView<State>,
+    pub(crate) view_state: <Box<AnyWidgetView<State>> as View<State, (), ViewCtx>>::ViewState,
+    /// The widgets of this window which may send actions.
+    widget_map: WidgetMap,
+    proxy: Arc<dyn RawProxy>,
+}
+
+impl<State: 'static> OpenWindow<State> {
+    /// Build the view of a new window, and open the window with `open_window`.
+    pub(crate) fn open(
+        view: WindowView<State>,
+        ctx: &mut ViewCtx,
+        open_window: impl FnOnce(WindowAttributes, RootWidget<DynWidget>) -> AppWindowId,
+    ) -> Self {
+        let proxy: Arc<dyn RawProxy> = Arc::new(WindowProxy {
+            inner: ctx.proxy.clone(),
+            window: view.key,
+        });
+        let mut widget_map = WidgetMap::default();
+        let (pod, view_state) =
+            with_window_ctx(ctx, &mut widget_map, &proxy, |ctx| view.view.build(ctx));
+        let id = open_window(view.attributes.clone(), RootWidget::from_pod(pod.inner));
+        Self {
+            id,
+            view,
+            view_state,
+            widget_map,
+            proxy,
+        }
+    }
+
+    /// Rebuild the view of the window, whose root widget is `root`.
+    pub(crate) fn rebuild(
+        &mut self,
+        next: WindowView<State>,
+        ctx: &mut ViewCtx,
+        root: Mut<Pod<DynWidget>>,
+    ) {
+        with_window_ctx(ctx, &mut self.widget_map, &self.proxy, |ctx| {
+            next.view
+                .rebuild(&self.view.view, &mut self.view_state, ctx, root);
+        });
+        self.view = next;
+    }
+
+    /// Tear down the view of the window, whose root widget is `root`.
+    pub(crate) fn teardown(&mut self, ctx: &mut ViewCtx, root: Mut<Pod<DynWidget>>) {
+        with_window_ctx(ctx, &mut self.widget_map, &self.proxy, |ctx| {
+            self.view.view.teardown(&mut self.view_state, ctx, root);
+        });
+    }
+
+    /// The path of the view which created the widget `id`, if it's in this window.
+    pub(crate) fn view_path(&self, id: WidgetId) -> Option<&[ViewId]> {
+        self.widget_map.get(&id).map(Vec::as_slice)
+    }
+}
+
+/// Run `f` with `ctx` set up for the view tree of a secondary window.
+///
+/// Each window has its own widget map, so that actions are sent to the right view tree,
+/// and its own proxy, so that async messages are too.
+fn with_window_ctx<R>(
+    ctx: &mut ViewCtx,
+    widget_map: &mut WidgetMap,
+    proxy: &Arc<dyn RawProxy>,
+    f: impl FnOnce(&mut ViewCtx) -> R,
+) -> R {
+    std::mem::swap(&mut ctx.widget_map, widget_map);
+    let main_proxy = std::mem::replace(&mut ctx.proxy, proxy.clone());
+    let result = f(ctx);
+    ctx.proxy = main_proxy;
+    std::mem::swap(&mut ctx.widget_map, widget_map);
+    result
+}
+
+/// An async message sent to a view in a secondary window.
+#[derive(Debug)]
+pub(crate) struct WindowMessage {
+    /// The key of the window.
+    pub(crate) window: u64,
+    pub(crate) message: DynMessage,
+}
+
+/// Sends the async messages of the views of a secondary window.
+struct WindowProxy {
+    inner: Arc<dyn RawProxy>,
+    window: u64,
+}
+
+impl RawProxy for WindowProxy {
+    fn send_message(&self, path: Arc<[ViewId]>, message: DynMessage) -> Result<(), ProxyError> {
+        let message = Box::new(WindowMessage {
+            window: self.window,
+            message,
+        });
+        self.inner
+            .send_message(path, message)
+            .map_err(|err| match err {
+                ProxyError::DriverFinished(message) => {
+                    let message = message
+                        .into_any()
+                        .downcast::<WindowMessage>()
+                        .expect("We know this is the value we just created, which has this type");
+                    ProxyError::DriverFinished(message.message)
+                }
+                err => err,
+            })
+    }
+}